@@ -20,11 +20,83 @@ fn main() {
     // Skip frontend build if SKIP_FRONTEND_BUILD is set (used in cross-compilation)
     if std::env::var("SKIP_FRONTEND_BUILD").is_ok() {
         println!("cargo:warning=SKIP_FRONTEND_BUILD set, skipping frontend build");
-        return;
+    } else {
+        // Always build the frontend to ensure it's up-to-date
+        build_frontend();
+    }
+
+    // Metadata for `lodestarr dist verify` and /api/info
+    emit_build_info();
+}
+
+fn emit_build_info() {
+    println!(
+        "cargo:rustc-env=LODESTARR_TARGET={}",
+        std::env::var("TARGET").unwrap_or_default()
+    );
+
+    // Docker builds have no .git, so the hash can be passed in
+    println!("cargo:rerun-if-env-changed=LODESTARR_GIT_HASH");
+    let git_hash = std::env::var("LODESTARR_GIT_HASH")
+        .ok()
+        .filter(|h| !h.is_empty())
+        .or_else(|| {
+            let output = Command::new("git")
+                .args(["rev-parse", "--short=12", "HEAD"])
+                .output()
+                .ok()?;
+            output
+                .status
+                .success()
+                .then(|| String::from_utf8_lossy(&output.stdout).trim().to_string())
+        })
+        .unwrap_or_else(|| "unknown".to_string());
+    println!("cargo:rustc-env=LODESTARR_GIT_HASH={}", git_hash);
+    if let Ok(head) = std::fs::read_to_string(".git/HEAD") {
+        println!("cargo:rerun-if-changed=.git/HEAD");
+        if let Some(reference) = head.trim().strip_prefix("ref: ")
+            && Path::new(".git").join(reference).exists()
+        {
+            println!("cargo:rerun-if-changed=.git/{}", reference);
+        }
     }
 
-    // Always build the frontend to ensure it's up-to-date
-    build_frontend();
+    let ui_hash = hash_dir(Path::new("web/dist")).unwrap_or_else(|| "none".to_string());
+    println!("cargo:rustc-env=LODESTARR_UI_HASH={}", ui_hash);
+}
+
+/// FNV-1a over the relative paths and contents of every file under `dir`
+fn hash_dir(dir: &Path) -> Option<String> {
+    fn walk(dir: &Path, files: &mut Vec<std::path::PathBuf>) {
+        if let Ok(entries) = std::fs::read_dir(dir) {
+            for entry in entries.flatten() {
+                let path = entry.path();
+                if path.is_dir() {
+                    walk(&path, files);
+                } else {
+                    files.push(path);
+                }
+            }
+        }
+    }
+
+    let mut files = Vec::new();
+    walk(dir, &mut files);
+    if files.is_empty() {
+        return None;
+    }
+    files.sort();
+
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for file in files {
+        let name = file.strip_prefix(dir).unwrap_or(&file).to_string_lossy();
+        let contents = std::fs::read(&file).unwrap_or_default();
+        for byte in name.replace('\\', "/").bytes().chain(contents) {
+            hash ^= u64::from(byte);
+            hash = hash.wrapping_mul(0x100000001b3);
+        }
+    }
+    Some(format!("{:016x}", hash))
 }
 
 fn check_formatting() {
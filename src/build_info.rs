@@ -0,0 +1,113 @@
+//! Build metadata baked into the binary (`lodestarr dist verify`, /api/info)
+//!
+//! build.rs records the target triple, git commit and a hash of the web UI
+//! bundle. They are embedded as a single marker string, so any release
+//! artifact can be identified by reading its bytes, whatever platform it was
+//! built for.
+
+use serde::Serialize;
+
+const MARKER: &[u8] = b"LODESTARR_BUILD_INFO{";
+/// Longest field list accepted after the marker
+const MAX_LEN: usize = 512;
+
+/// This binary's metadata, in the format [`read_from`] looks for
+static EMBEDDED: &str = concat!(
+    "LODESTARR_BUILD_INFO{version=",
+    env!("CARGO_PKG_VERSION"),
+    ";target=",
+    env!("LODESTARR_TARGET"),
+    ";git=",
+    env!("LODESTARR_GIT_HASH"),
+    ";ui=",
+    env!("LODESTARR_UI_HASH"),
+    "}"
+);
+
+/// Which build a binary came from
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct BuildInfo {
+    pub version: String,
+    /// Rust target triple
+    pub target: String,
+    /// Short commit hash, or "unknown" when built outside a checkout
+    pub git_hash: String,
+    /// Hash of the bundled web UI, or "none" when built without it
+    pub ui_hash: String,
+}
+
+/// Metadata of the running binary
+pub fn current() -> BuildInfo {
+    read_from(EMBEDDED.as_bytes()).expect("embedded build info is well-formed")
+}
+
+/// Find the build metadata in a binary's bytes
+pub fn read_from(bytes: &[u8]) -> Option<BuildInfo> {
+    let mut start = 0;
+    // The marker prefix also appears on its own (in the code that looks for
+    // it), so try every occurrence until one parses
+    while let Some(found) = bytes[start..]
+        .windows(MARKER.len())
+        .position(|w| w == MARKER)
+    {
+        let fields = start + found + MARKER.len();
+        if let Some(info) = parse_fields(&bytes[fields..]) {
+            return Some(info);
+        }
+        start = fields;
+    }
+    None
+}
+
+/// Parse `version=..;target=..;git=..;ui=..}`
+fn parse_fields(bytes: &[u8]) -> Option<BuildInfo> {
+    let end = bytes.iter().take(MAX_LEN).position(|&b| b == b'}')?;
+    let text = std::str::from_utf8(&bytes[..end]).ok()?;
+    if !text.bytes().all(|b| b.is_ascii_graphic()) {
+        return None;
+    }
+    let mut info = BuildInfo {
+        version: String::new(),
+        target: String::new(),
+        git_hash: String::new(),
+        ui_hash: String::new(),
+    };
+    for field in text.split(';') {
+        let (key, value) = field.split_once('=')?;
+        let slot = match key {
+            "version" => &mut info.version,
+            "target" => &mut info.target,
+            "git" => &mut info.git_hash,
+            "ui" => &mut info.ui_hash,
+            _ => continue,
+        };
+        *slot = value.to_string();
+    }
+    (!info.version.is_empty() && !info.target.is_empty()).then_some(info)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_from() {
+        let info = current();
+        assert_eq!(info.version, env!("CARGO_PKG_VERSION"));
+        assert!(!info.target.is_empty());
+
+        // Surrounded by other data, after a bare marker
+        let mut binary = b"\x7fELF\0\0LODESTARR_BUILD_INFO{\0\x01garbage".to_vec();
+        binary.extend_from_slice(EMBEDDED.as_bytes());
+        binary.extend_from_slice(b"\0more");
+        assert_eq!(read_from(&binary), Some(info));
+
+        let other = b"LODESTARR_BUILD_INFO{version=1.0.0;target=aarch64-apple-darwin;git=abc;ui=none;extra=1}";
+        let other = read_from(other).unwrap();
+        assert_eq!(other.target, "aarch64-apple-darwin");
+        assert_eq!(other.git_hash, "abc");
+
+        assert!(read_from(b"no metadata here").is_none());
+        assert!(read_from(b"LODESTARR_BUILD_INFO{version=1.0.0}").is_none());
+    }
+}
@@ -0,0 +1,209 @@
+//! Release push to Sonarr, Radarr, Lidarr and Readarr (`/api/push/{app}`)
+//!
+//! The app's `release/push` endpoint takes a release as if one of its own
+//! indexers had found it: the app runs its decision engine, sends the grab to
+//! its download client and records it in its history, so the import and
+//! renaming happen as for any other grab. Releases are attributed to the
+//! app's indexer for the Lodestarr indexer they came from when one exists.
+
+use crate::config::{ArrApp, ArrKind};
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+
+/// A release in the shape of the app's `ReleaseResource`
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Release {
+    pub title: String,
+    pub download_url: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub magnet_url: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub info_hash: Option<String>,
+    pub guid: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub info_url: Option<String>,
+    pub protocol: &'static str,
+    pub publish_date: DateTime<Utc>,
+    pub indexer: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub indexer_id: Option<i32>,
+    pub size: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub seeders: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub leechers: Option<u32>,
+}
+
+/// What the app decided about a pushed release
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+pub struct PushOutcome {
+    pub approved: bool,
+    pub rejections: Vec<String>,
+}
+
+impl Default for PushOutcome {
+    fn default() -> Self {
+        Self {
+            approved: true,
+            rejections: Vec::new(),
+        }
+    }
+}
+
+pub struct ArrClient {
+    app: ArrApp,
+    client: Client,
+}
+
+impl ArrClient {
+    pub fn new(app: &ArrApp) -> Self {
+        Self {
+            app: app.clone(),
+            client: Client::builder()
+                .timeout(std::time::Duration::from_secs(30))
+                .build()
+                .expect("Failed to create HTTP client"),
+        }
+    }
+
+    fn api_url(&self, path: &str) -> String {
+        let version = match self.app.kind {
+            ArrKind::Sonarr | ArrKind::Radarr => "v3",
+            ArrKind::Lidarr | ArrKind::Readarr => "v1",
+        };
+        format!(
+            "{}/api/{}/{}",
+            self.app.url.trim_end_matches('/'),
+            version,
+            path
+        )
+    }
+
+    /// The app's indexer id for a Lodestarr indexer: `indexer_ids` first,
+    /// then a Torznab indexer whose URL points at it (`lodestarr_url` is
+    /// Lodestarr's URL as the app knows it)
+    pub async fn indexer_id(&self, indexer: &str, lodestarr_url: &str) -> Option<i32> {
+        if let Some(id) = self.app.indexer_ids.get(indexer) {
+            return Some(*id);
+        }
+        let indexers: Vec<serde_json::Value> = self
+            .client
+            .get(self.api_url("indexer"))
+            .header("X-Api-Key", &self.app.api_key)
+            .send()
+            .await
+            .ok()?
+            .json()
+            .await
+            .ok()?;
+        match_indexer_id(&indexers, indexer, lodestarr_url)
+    }
+
+    /// Push a release and return the app's decision
+    pub async fn push(&self, release: &Release) -> Result<PushOutcome> {
+        let resp = self
+            .client
+            .post(self.api_url("release/push"))
+            .header("X-Api-Key", &self.app.api_key)
+            .json(release)
+            .send()
+            .await
+            .with_context(|| format!("Failed to connect to {}", self.app.name))?;
+
+        let status = resp.status();
+        let body = resp.text().await.unwrap_or_default();
+        if !status.is_success() {
+            anyhow::bail!("{} responded with {}: {}", self.app.name, status, body);
+        }
+        Ok(parse_outcome(&body))
+    }
+}
+
+/// Find the indexer whose `baseUrl` is the Torznab URL for `indexer`.
+/// Jackett uses the same paths, so one on `lodestarr_url` wins.
+fn match_indexer_id(
+    indexers: &[serde_json::Value],
+    indexer: &str,
+    lodestarr_url: &str,
+) -> Option<i32> {
+    let path = format!("/indexers/{}", indexer.to_lowercase());
+    let lodestarr_url = lodestarr_url.trim_end_matches('/').to_lowercase();
+    let candidates: Vec<(String, i32)> = indexers
+        .iter()
+        .filter_map(|idx| {
+            let base_url = idx["fields"]
+                .as_array()?
+                .iter()
+                .find(|f| f["name"] == "baseUrl")?["value"]
+                .as_str()?
+                .trim_end_matches('/')
+                .to_lowercase();
+            let id = i32::try_from(idx["id"].as_i64()?).ok()?;
+            (base_url.ends_with(&path) || base_url.contains(&format!("{}/", path)))
+                .then_some((base_url, id))
+        })
+        .collect();
+    candidates
+        .iter()
+        .find(|(url, _)| url.starts_with(&lodestarr_url))
+        .or(candidates.first())
+        .map(|(_, id)| *id)
+}
+
+/// Depending on the app and version, the answer is the release or a list
+fn parse_outcome(body: &str) -> PushOutcome {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Response {
+        List(Vec<PushOutcome>),
+        One(PushOutcome),
+    }
+    match serde_json::from_str(body) {
+        Ok(Response::List(mut list)) if !list.is_empty() => list.swap_remove(0),
+        Ok(Response::One(outcome)) => outcome,
+        // Accepted without a decision in the body
+        _ => PushOutcome::default(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_match_indexer_id() {
+        let indexers: Vec<serde_json::Value> = serde_json::from_str(
+            r#"[
+                {"id": 3, "fields": [{"name": "baseUrl", "value": "http://jackett:9117/api/v2.0/indexers/yts/results/torznab/"}]},
+                {"id": 4, "fields": [{"name": "baseUrl", "value": "http://lodestarr:3420/api/v2.0/indexers/yts/results/torznab/"}]},
+                {"id": 7, "fields": [{"name": "apiPath", "value": "/api"}, {"name": "baseUrl", "value": "http://lodestarr:3420/api/v2.0/indexers/EZTV/results/torznab"}]},
+                {"id": 9, "fields": [{"name": "baseUrl", "value": "http://lodestarr:3420/api/v2.0/indexers/eztv2"}]}
+            ]"#,
+        )
+        .unwrap();
+        let lodestarr = "http://lodestarr:3420/";
+        assert_eq!(match_indexer_id(&indexers, "eztv", lodestarr), Some(7));
+        assert_eq!(match_indexer_id(&indexers, "eztv2", lodestarr), Some(9));
+        assert_eq!(match_indexer_id(&indexers, "yts", lodestarr), Some(4));
+        // The UI's URL may not be the one the app uses
+        assert_eq!(
+            match_indexer_id(&indexers, "yts", "http://localhost:3420"),
+            Some(3)
+        );
+        assert_eq!(match_indexer_id(&indexers, "nyaa", lodestarr), None);
+    }
+
+    #[test]
+    fn test_parse_outcome() {
+        let outcome = parse_outcome(r#"[{"approved": false, "rejections": ["Unknown Series"]}]"#);
+        assert!(!outcome.approved);
+        assert_eq!(outcome.rejections, vec!["Unknown Series"]);
+
+        assert!(parse_outcome(r#"{"approved": true, "title": "x"}"#).approved);
+        assert!(parse_outcome("").approved);
+    }
+}
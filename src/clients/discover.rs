@@ -0,0 +1,311 @@
+//! Finding download clients on this machine and known hosts
+//!
+//! Each host is probed on the default ports: qBittorrent 8080, Transmission
+//! 9091 and TorrServer 8090. A client that wants a login still counts as
+//! found, since its web UI or RPC answers in a way only it does. Hosts are
+//! localhost, the hosts of configured clients and *arr apps, and any given
+//! on the command line or in the request.
+
+use crate::config::{ClientType, Config};
+use futures::future::join_all;
+use reqwest::{Client, StatusCode, Url};
+use serde::Serialize;
+use serde_json::{Value, json};
+use std::time::Duration;
+
+/// Client types and the ports they listen on by default
+const PROBES: [(ClientType, u16); 3] = [
+    (ClientType::QBittorrent, 8080),
+    (ClientType::Transmission, 9091),
+    (ClientType::TorrServer, 8090),
+];
+
+const TIMEOUT: Duration = Duration::from_secs(2);
+
+/// A client that answered a probe
+#[derive(Debug, Clone, Serialize)]
+pub struct Candidate {
+    pub client_type: ClientType,
+    /// Suggested name, e.g. "qBittorrent" or "Transmission (nas)"
+    pub name: String,
+    pub url: String,
+    pub version: Option<String>,
+    /// Answered, but needs a username and password to be added
+    pub needs_login: bool,
+    /// A configured client already points at it
+    pub configured: bool,
+}
+
+/// Hosts to probe: localhost, configured clients' and apps' hosts, `extra`.
+/// Loopback addresses are all probed as localhost.
+pub fn hosts(config: &Config, extra: &[String]) -> Vec<String> {
+    let configured = config
+        .download_clients
+        .iter()
+        .map(|c| c.url.as_str())
+        .chain(config.arr_apps.iter().map(|a| a.url.as_str()))
+        .filter_map(|url| Url::parse(url).ok()?.host_str().map(str::to_string));
+    let mut hosts: Vec<String> = Vec::new();
+    for host in std::iter::once("localhost".to_string())
+        .chain(configured)
+        .chain(extra.iter().map(|h| h.trim().to_string()))
+    {
+        let host = if is_loopback(&host) {
+            "localhost".to_string()
+        } else {
+            host
+        };
+        if !host.is_empty() && !hosts.contains(&host) {
+            hosts.push(host);
+        }
+    }
+    hosts
+}
+
+fn is_loopback(host: &str) -> bool {
+    host.eq_ignore_ascii_case("localhost")
+        || host
+            .trim_matches(['[', ']'])
+            .parse::<std::net::IpAddr>()
+            .is_ok_and(|ip| ip.is_loopback())
+}
+
+/// Probe every host on every client's default port
+pub async fn discover(config: &Config, hosts: &[String]) -> Vec<Candidate> {
+    let client = Client::builder()
+        .timeout(TIMEOUT)
+        .build()
+        .expect("Failed to create HTTP client");
+    let client = &client;
+    let probes = hosts.iter().flat_map(|host| {
+        PROBES.iter().map(move |(client_type, port)| {
+            let base = format!("http://{}:{}", host, port);
+            async move {
+                let found = probe(client, client_type, &base).await?;
+                Some(candidate(config, client_type.clone(), host, base, found))
+            }
+        })
+    });
+    join_all(probes).await.into_iter().flatten().collect()
+}
+
+/// What a probe learned: the version, if the client told it, and whether
+/// it wants a login
+type Found = (Option<String>, bool);
+
+async fn probe(client: &Client, client_type: &ClientType, base: &str) -> Option<Found> {
+    match client_type {
+        ClientType::QBittorrent => probe_qbittorrent(client, base).await,
+        ClientType::Transmission => probe_transmission(client, base).await,
+        ClientType::TorrServer => probe_torrserver(client, base).await,
+    }
+}
+
+/// `/api/v2/app/version` answers without a login when localhost auth is off;
+/// otherwise the login page gives qBittorrent away
+async fn probe_qbittorrent(client: &Client, base: &str) -> Option<Found> {
+    let resp = client
+        .get(format!("{}/api/v2/app/version", base))
+        .send()
+        .await
+        .ok()?;
+    match resp.status() {
+        StatusCode::OK => {
+            let version = resp.text().await.ok()?.trim().to_string();
+            version.starts_with('v').then_some((Some(version), false))
+        }
+        StatusCode::FORBIDDEN => {
+            let page = client.get(base).send().await.ok()?.text().await.ok()?;
+            page.contains("qBittorrent").then_some((None, true))
+        }
+        _ => None,
+    }
+}
+
+/// The RPC endpoint answers 409 with a session id, or 401 when it wants a login
+async fn probe_transmission(client: &Client, base: &str) -> Option<Found> {
+    let rpc = format!("{}/transmission/rpc", base);
+    let body = json!({ "method": "session-get", "arguments": { "fields": ["version"] } });
+    let resp = client.post(&rpc).json(&body).send().await.ok()?;
+    match resp.status() {
+        StatusCode::CONFLICT => {
+            let session = resp
+                .headers()
+                .get(super::transmission::SESSION_HEADER)?
+                .clone();
+            let reply: Option<Value> = match client
+                .post(&rpc)
+                .header(super::transmission::SESSION_HEADER, session)
+                .json(&body)
+                .send()
+                .await
+            {
+                Ok(resp) => resp.json().await.ok(),
+                Err(_) => None,
+            };
+            let version =
+                reply.and_then(|r| r["arguments"]["version"].as_str().map(str::to_string));
+            Some((version, false))
+        }
+        StatusCode::UNAUTHORIZED => {
+            let realm = resp.headers().get(reqwest::header::WWW_AUTHENTICATE)?;
+            realm
+                .to_str()
+                .ok()?
+                .contains("Transmission")
+                .then_some((None, true))
+        }
+        _ => None,
+    }
+}
+
+/// `/echo` answers with TorrServer's version
+async fn probe_torrserver(client: &Client, base: &str) -> Option<Found> {
+    let resp = client.get(format!("{}/echo", base)).send().await.ok()?;
+    if !resp.status().is_success() {
+        return None;
+    }
+    let version = resp.text().await.ok()?.trim().to_string();
+    let plausible = !version.is_empty() && version.len() < 64 && !version.contains('<');
+    plausible.then_some((Some(version), false))
+}
+
+fn candidate(
+    config: &Config,
+    client_type: ClientType,
+    host: &str,
+    url: String,
+    (version, needs_login): Found,
+) -> Candidate {
+    let label = match client_type {
+        ClientType::QBittorrent => "qBittorrent",
+        ClientType::Transmission => "Transmission",
+        ClientType::TorrServer => "TorrServer",
+    };
+    let name = if host == "localhost" {
+        label.to_string()
+    } else {
+        format!("{} ({})", label, host)
+    };
+    let configured = config
+        .download_clients
+        .iter()
+        .any(|c| c.client_type == client_type && same_server(&c.url, &url));
+    Candidate {
+        client_type,
+        name,
+        url,
+        version,
+        needs_login,
+        configured,
+    }
+}
+
+/// Whether two URLs point at the same host and port
+fn same_server(a: &str, b: &str) -> bool {
+    match (Url::parse(a), Url::parse(b)) {
+        (Ok(a), Ok(b)) => {
+            a.host_str() == b.host_str() && a.port_or_known_default() == b.port_or_known_default()
+        }
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::DownloadClient;
+    use axum::{
+        Router,
+        http::HeaderMap,
+        response::IntoResponse,
+        routing::{get, post},
+    };
+
+    async fn serve(app: Router) -> String {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await });
+        format!("http://{}", addr)
+    }
+
+    #[tokio::test]
+    async fn test_probe() {
+        let client = Client::new();
+        let qbittorrent =
+            serve(Router::new().route("/api/v2/app/version", get(|| async { "v4.6.2" }))).await;
+        let locked_qbittorrent = serve(
+            Router::new()
+                .route(
+                    "/api/v2/app/version",
+                    get(|| async { StatusCode::FORBIDDEN }),
+                )
+                .route("/", get(|| async { "<title>qBittorrent Web UI</title>" })),
+        )
+        .await;
+        let transmission = serve(Router::new().route(
+            "/transmission/rpc",
+            post(|headers: HeaderMap| async move {
+                if headers.get("X-Transmission-Session-Id").is_none() {
+                    return (StatusCode::CONFLICT, [("X-Transmission-Session-Id", "s1")])
+                        .into_response();
+                }
+                axum::Json(json!({ "result": "success", "arguments": { "version": "4.0.5" } }))
+                    .into_response()
+            }),
+        ))
+        .await;
+        let torrserver = serve(Router::new().route("/echo", get(|| async { "MatriX.131" }))).await;
+
+        assert_eq!(
+            probe(&client, &ClientType::QBittorrent, &qbittorrent).await,
+            Some((Some("v4.6.2".to_string()), false))
+        );
+        assert_eq!(
+            probe(&client, &ClientType::QBittorrent, &locked_qbittorrent).await,
+            Some((None, true))
+        );
+        assert_eq!(
+            probe(&client, &ClientType::Transmission, &transmission).await,
+            Some((Some("4.0.5".to_string()), false))
+        );
+        assert_eq!(
+            probe(&client, &ClientType::TorrServer, &torrserver).await,
+            Some((Some("MatriX.131".to_string()), false))
+        );
+        // Something else on the port
+        assert_eq!(
+            probe(&client, &ClientType::Transmission, &torrserver).await,
+            None
+        );
+        assert_eq!(
+            probe(&client, &ClientType::TorrServer, "http://127.0.0.1:9").await,
+            None
+        );
+
+        let mut config = Config::default();
+        config.download_clients.push(DownloadClient {
+            id: "1".to_string(),
+            name: "qb".to_string(),
+            client_type: ClientType::QBittorrent,
+            url: "http://nas:8080/".to_string(),
+            username: None,
+            password: None,
+        });
+        let extra = [
+            "box".to_string(),
+            "127.0.0.1".to_string(),
+            "[::1]".to_string(),
+        ];
+        assert_eq!(hosts(&config, &extra), ["localhost", "nas", "box"]);
+        let found = candidate(
+            &config,
+            ClientType::QBittorrent,
+            "nas",
+            "http://nas:8080".to_string(),
+            (None, true),
+        );
+        assert_eq!(found.name, "qBittorrent (nas)");
+        assert!(found.configured);
+    }
+}
@@ -1,12 +1,16 @@
 //! Download clients interface
 
+pub mod arr;
+pub mod discover;
 pub mod qbittorrent;
+pub mod transmission;
 
 use crate::config::{ClientType, DownloadClient};
 use anyhow::{Context, Result};
 use qbittorrent::QBittorrentClient;
 use reqwest::Client;
 use serde::Serialize;
+use transmission::TransmissionClient;
 
 /// Trait for download clients
 #[async_trait::async_trait]
@@ -27,6 +31,11 @@ pub fn create_client(config: &DownloadClient) -> Box<dyn Downloader> {
             config.username.clone(),
             config.password.clone(),
         )),
+        ClientType::Transmission => Box::new(TransmissionClient::new(
+            &config.url,
+            config.username.clone(),
+            config.password.clone(),
+        )),
     }
 }
 
@@ -0,0 +1,207 @@
+//! Transmission client over its JSON RPC
+//!
+//! Every RPC request must carry the `X-Transmission-Session-Id` header. The
+//! first request is answered with 409 and the id to use, which is then kept
+//! for later requests until Transmission hands out a new one.
+
+use crate::clients::Downloader;
+use anyhow::{Context, Result, bail};
+use base64::{Engine as _, engine::general_purpose::STANDARD};
+use reqwest::{Client, StatusCode};
+use serde_json::{Value, json};
+use std::sync::Mutex;
+
+pub(super) const SESSION_HEADER: &str = "X-Transmission-Session-Id";
+
+pub struct TransmissionClient {
+    /// RPC endpoint, `.../transmission/rpc`
+    rpc_url: String,
+    username: Option<String>,
+    password: Option<String>,
+    session_id: Mutex<Option<String>>,
+    client: Client,
+}
+
+impl TransmissionClient {
+    /// `url` is the web UI address (`http://host:9091`) or the RPC endpoint
+    pub fn new(url: &str, username: Option<String>, password: Option<String>) -> Self {
+        Self {
+            rpc_url: rpc_url(url),
+            username: username.filter(|u| !u.is_empty()),
+            password,
+            session_id: Mutex::new(None),
+            client: Client::builder()
+                .timeout(std::time::Duration::from_secs(15))
+                .build()
+                .expect("Failed to create HTTP client"),
+        }
+    }
+
+    /// Call an RPC method, returning its `arguments`
+    async fn call(&self, method: &str, arguments: Value) -> Result<Value> {
+        let body = json!({ "method": method, "arguments": arguments });
+        // A second try with the session id from the 409
+        for _ in 0..2 {
+            let mut request = self.client.post(&self.rpc_url).json(&body);
+            if let Some(id) = self.session_id.lock().ok().and_then(|id| id.clone()) {
+                request = request.header(SESSION_HEADER, id);
+            }
+            if let Some(user) = &self.username {
+                request = request.basic_auth(user, self.password.as_deref());
+            }
+            let resp = request
+                .send()
+                .await
+                .context("Failed to connect to Transmission")?;
+
+            match resp.status() {
+                StatusCode::CONFLICT => {
+                    let id = resp
+                        .headers()
+                        .get(SESSION_HEADER)
+                        .and_then(|v| v.to_str().ok())
+                        .context("Transmission sent no session id")?;
+                    if let Ok(mut session) = self.session_id.lock() {
+                        *session = Some(id.to_string());
+                    }
+                    continue;
+                }
+                StatusCode::UNAUTHORIZED => bail!("Transmission rejected the username or password"),
+                status if !status.is_success() => {
+                    bail!("Transmission responded with status: {}", status)
+                }
+                _ => {}
+            }
+
+            let reply: Value = resp
+                .json()
+                .await
+                .context("Invalid response from Transmission")?;
+            let result = reply["result"].as_str().unwrap_or_default();
+            if result != "success" {
+                bail!("Transmission error: {}", result);
+            }
+            return Ok(reply["arguments"].clone());
+        }
+        bail!("Transmission kept refusing the session id")
+    }
+
+    /// `torrent-add` arguments for a link: magnets are passed on, .torrent
+    /// files are fetched here (Transmission may not be able to reach the
+    /// tracker or this server) and sent as metainfo
+    async fn add_arguments(&self, link: &str) -> Value {
+        if link.starts_with("magnet:") {
+            return json!({ "filename": link });
+        }
+        match self.fetch(link).await {
+            Ok(bytes) => json!({ "metainfo": STANDARD.encode(bytes) }),
+            Err(e) => {
+                tracing::debug!("Letting Transmission fetch {} itself: {}", link, e);
+                json!({ "filename": link })
+            }
+        }
+    }
+
+    async fn fetch(&self, link: &str) -> Result<Vec<u8>> {
+        let resp = self.client.get(link).send().await?.error_for_status()?;
+        Ok(resp.bytes().await?.to_vec())
+    }
+}
+
+/// RPC endpoint for a configured URL: the URL itself when it has a path,
+/// else `/transmission/rpc` on it
+fn rpc_url(url: &str) -> String {
+    let url = url.trim_end_matches('/');
+    match reqwest::Url::parse(url) {
+        Ok(parsed) if parsed.path().trim_matches('/').is_empty() => {
+            format!("{}/transmission/rpc", url)
+        }
+        _ => url.to_string(),
+    }
+}
+
+#[async_trait::async_trait]
+impl Downloader for TransmissionClient {
+    async fn add_torrent(&self, link: &str) -> Result<()> {
+        let arguments = self.add_arguments(link).await;
+        let added = self.call("torrent-add", arguments).await?;
+        if added.get("torrent-duplicate").is_some() {
+            tracing::debug!("Transmission already has {}", link);
+        }
+        Ok(())
+    }
+
+    async fn test_connection(&self) -> Result<()> {
+        self.call("session-get", json!({ "fields": ["version"] }))
+            .await
+            .map(|_| ())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{
+        Json, Router,
+        http::HeaderMap,
+        response::IntoResponse,
+        routing::{get, post},
+    };
+    use std::sync::Arc;
+
+    /// Local Transmission that wants session id `s1` and records what was
+    /// added, with a tracker serving `/file.torrent`
+    async fn transmission(added: Arc<Mutex<Vec<Value>>>) -> String {
+        let app = Router::new()
+            .route("/file.torrent", get(|| async { "d4:infoe" }))
+            .route(
+                "/transmission/rpc",
+                post(move |headers: HeaderMap, Json(body): Json<Value>| {
+                    let added = added.clone();
+                    async move {
+                        if headers.get(SESSION_HEADER).is_none_or(|id| id != "s1") {
+                            return (StatusCode::CONFLICT, [(SESSION_HEADER, "s1")])
+                                .into_response();
+                        }
+                        if body["method"] == "torrent-add" {
+                            added.lock().unwrap().push(body["arguments"].clone());
+                        }
+                        Json(json!({ "result": "success", "arguments": {} })).into_response()
+                    }
+                }),
+            );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await });
+        format!("http://{}", addr)
+    }
+
+    #[tokio::test]
+    async fn test_add_torrent() {
+        assert_eq!(
+            rpc_url("http://nas:9091/"),
+            "http://nas:9091/transmission/rpc"
+        );
+        assert_eq!(rpc_url("http://nas/tr/rpc"), "http://nas/tr/rpc");
+
+        let added = Arc::new(Mutex::new(Vec::new()));
+        let url = transmission(added.clone()).await;
+        let client = TransmissionClient::new(&url, None, None);
+        client.test_connection().await.unwrap();
+        client.add_torrent("magnet:?xt=urn:btih:abc").await.unwrap();
+        client
+            .add_torrent(&format!("{}/file.torrent", url))
+            .await
+            .unwrap();
+        // Unreachable .torrent links are left to Transmission
+        client
+            .add_torrent("http://127.0.0.1:9/file.torrent")
+            .await
+            .unwrap();
+
+        let added = added.lock().unwrap();
+        assert_eq!(added[0]["filename"], "magnet:?xt=urn:btih:abc");
+        assert_eq!(added[1]["metainfo"], STANDARD.encode("d4:infoe"));
+        assert_eq!(added[2]["filename"], "http://127.0.0.1:9/file.torrent");
+    }
+}
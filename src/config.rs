@@ -1,19 +1,46 @@
+use crate::models::SearchType;
 use anyhow::{Context, Result};
 use directories::ProjectDirs;
 use serde::{Deserialize, Serialize};
 use std::fs;
 use std::path::PathBuf;
 
+mod layers;
+pub mod validate;
+
+pub use layers::{ConfigBuilder, Overrides};
+
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct Config {
+    #[serde(default)]
     pub indexers: Vec<IndexerConfig>,
     pub download_path: Option<String>,
+    /// Where under `download_path` the server saves torrents, e.g.
+    /// `{indexer}/{category}/{title}.torrent` (see [`crate::download::render_template`])
+    pub download_template: Option<String>,
+    /// Trackers added to magnets built from a public indexer's infohash (a
+    /// built-in list when unset, none when empty)
+    pub magnet_trackers: Option<Vec<String>>,
     pub proxy_url: Option<String>,
+    /// Refuse native searches and downloads while `proxy_url` is unset or
+    /// unreachable, instead of connecting to trackers directly
+    #[serde(default)]
+    pub require_proxy: bool,
     pub db_path: Option<String>,
     pub indexers_path: Option<String>,
     #[serde(default)]
     pub disabled_indexers: Vec<String>,
 
+    /// Indexers searched when a search doesn't name any (all when empty).
+    /// Entries can be `group:<name>`.
+    #[serde(default)]
+    pub default_indexers: Vec<String>,
+
+    /// Named sets of indexers, searched with `group:<name>` and served as
+    /// the Torznab indexer `tag-<name>`
+    #[serde(default)]
+    pub indexer_groups: std::collections::HashMap<String, Vec<String>>,
+
     /// Override settings for native indexers (IndexID -> Key -> Value)
     #[serde(default)]
     pub native_settings:
@@ -22,6 +49,315 @@ pub struct Config {
     /// Configured download clients
     #[serde(default)]
     pub download_clients: Vec<DownloadClient>,
+
+    /// Sonarr/Radarr/Lidarr/Readarr instances results can be pushed to
+    #[serde(default)]
+    pub arr_apps: Vec<ArrApp>,
+
+    /// Scoring profile used to auto-select a single best result
+    #[serde(default)]
+    pub scoring: ScoringProfile,
+
+    /// Terminal UI preferences
+    #[serde(default)]
+    pub tui: TuiConfig,
+
+    /// How sizes and numbers are written in CLI, TUI and web output
+    #[serde(default)]
+    pub display: DisplayConfig,
+
+    /// Order search results are listed in when a search doesn't pick one
+    #[serde(default)]
+    pub sort: crate::sort::Sort,
+
+    /// Show desktop notifications when CLI downloads finish
+    #[serde(default)]
+    pub notifications: bool,
+
+    /// Check GitHub once a day for a newer release (opt-in)
+    #[serde(default)]
+    pub check_updates: bool,
+
+    /// Refresh recurring *arr RSS polls shortly before they're due
+    #[serde(default)]
+    pub rss_prefetch: bool,
+
+    /// Download the .torrent files of top web UI results in the background
+    #[serde(default)]
+    pub torrent_prefetch: TorrentPrefetchConfig,
+
+    /// Which download Torznab items offer when a result has both a .torrent
+    /// link and a magnet
+    #[serde(default)]
+    pub magnet_preference: MagnetPreference,
+
+    /// Match music searches against MusicBrainz (opt-in, queries musicbrainz.org)
+    #[serde(default)]
+    pub musicbrainz: bool,
+
+    /// Keep the pages of native searches that returned no results, for
+    /// diagnosing broken definitions (`/api/history/{key}/raw`)
+    #[serde(default)]
+    pub raw_responses: bool,
+
+    /// Most results kept from any one indexer in a search (1000 when unset).
+    /// A native indexer's `_resultLimit` can only lower it.
+    pub max_results: Option<usize>,
+
+    /// Connection tuning for indexer HTTP clients
+    #[serde(default)]
+    pub http: HttpConfig,
+
+    /// How aggregate searches spread over indexers
+    #[serde(default)]
+    pub search: SearchConfig,
+
+    /// Where the server keeps its search cache and history
+    #[serde(default)]
+    pub store: StoreConfig,
+
+    /// Web server bind address (`serve` flags override it)
+    #[serde(default)]
+    pub server: ServerConfig,
+
+    /// What came from the environment and flags, kept out of [`Config::save`]
+    #[serde(skip)]
+    pub overrides: Overrides,
+}
+
+/// Indexers picked by [`Config::select_indexers`]
+#[derive(Debug, Clone, PartialEq)]
+pub enum IndexerSelection {
+    All,
+    Names(Vec<String>),
+}
+
+impl IndexerSelection {
+    pub fn includes(&self, name: &str) -> bool {
+        match self {
+            Self::All => true,
+            Self::Names(names) => names.iter().any(|n| n == name),
+        }
+    }
+
+    /// Whether the search asked for one particular indexer
+    pub fn is_single(&self) -> bool {
+        matches!(self, Self::Names(names) if names.len() == 1)
+    }
+}
+
+/// Per-indexer result ceiling when `max_results` isn't set
+pub const DEFAULT_MAX_RESULTS: usize = 1000;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ServerConfig {
+    pub host: String,
+    pub port: u16,
+    /// Lodestarr's URL as download clients reach it, for the links RSS
+    /// rules send (default: this host and port)
+    pub public_url: Option<String>,
+    /// Who may use the web UI and APIs (everyone when nothing is set)
+    pub auth: AuthConfig,
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        Self {
+            host: "0.0.0.0".to_string(),
+            port: 3420,
+            public_url: None,
+            auth: AuthConfig::default(),
+        }
+    }
+}
+
+impl ServerConfig {
+    /// `public_url`, or the bind address (loopback when bound to all
+    /// interfaces)
+    pub fn base_url(&self) -> String {
+        if let Some(url) = self.public_url.as_deref().filter(|u| !u.is_empty()) {
+            return url.trim_end_matches('/').to_string();
+        }
+        let host = match self.host.as_str() {
+            "" | "0.0.0.0" => "127.0.0.1".to_string(),
+            "::" => "[::1]".to_string(),
+            host if host.contains(':') => format!("[{}]", host.trim_matches(['[', ']'])),
+            host => host.to_string(),
+        };
+        format!("http://{}:{}", host, self.port)
+    }
+}
+
+/// `[server.auth]`: ways a request can prove who it comes from
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct AuthConfig {
+    /// Key accepted as an `apikey` query parameter (what *arr apps send)
+    /// or an `X-Api-Key` header
+    pub api_key: Option<String>,
+    /// Header a reverse proxy (Authelia, authentik) sets to the signed-in
+    /// user, e.g. `X-Forwarded-User` or `Remote-User`
+    pub forward_user_header: Option<String>,
+    /// Header holding the user's comma-separated groups, e.g. `Remote-Groups`
+    pub forward_groups_header: Option<String>,
+    /// Group a forwarded user must be in
+    pub required_group: Option<String>,
+    /// Addresses or CIDR ranges allowed to send the forward-auth headers.
+    /// Loopback only when empty.
+    pub trusted_proxies: Vec<String>,
+    /// Web UI login name; set together with the password by
+    /// `lodestarr auth set-password`
+    pub username: Option<String>,
+    /// PBKDF2 hash of the web UI password
+    pub password_hash: Option<String>,
+}
+
+impl AuthConfig {
+    /// Whether the web UI login is set up
+    pub fn login_enabled(&self) -> bool {
+        self.username.is_some() && self.password_hash.is_some()
+    }
+
+    /// Parse `trusted_proxies`, accepting bare addresses as single-host ranges
+    pub fn trusted_networks(&self) -> Result<Vec<ipnet::IpNet>, String> {
+        self.trusted_proxies
+            .iter()
+            .map(|entry| {
+                let entry = entry.trim();
+                entry
+                    .parse::<ipnet::IpNet>()
+                    .or_else(|_| entry.parse::<std::net::IpAddr>().map(ipnet::IpNet::from))
+                    .map_err(|_| format!("'{}' is not an IP address or CIDR range", entry))
+            })
+            .collect()
+    }
+}
+
+/// reqwest client tuning shared by native and proxied indexer clients.
+/// Unset values keep reqwest's defaults. Applied at startup.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HttpConfig {
+    /// Negotiate HTTP/2 with servers that offer it (HTTP/1.1 only when false)
+    pub http2: bool,
+    /// Seconds an idle pooled connection is kept open
+    pub pool_idle_timeout_secs: Option<u64>,
+    /// Maximum idle connections kept per host
+    pub pool_max_idle_per_host: Option<usize>,
+    /// TCP keepalive interval in seconds
+    pub tcp_keepalive_secs: Option<u64>,
+    /// DNS-over-HTTPS JSON endpoint used to resolve indexer hosts,
+    /// e.g. `https://cloudflare-dns.com/dns-query`
+    pub doh_url: Option<String>,
+    /// When `proxy_url` is a SOCKS proxy (Tor), use a distinct SOCKS username
+    /// per native indexer so each tracker gets its own circuit
+    pub tor_isolation: bool,
+    /// Requests per second allowed to any single tracker domain, across
+    /// searches, downloads and health checks (unlimited when unset)
+    pub domain_rate: Option<f64>,
+    /// Requests allowed in a burst before `domain_rate` kicks in
+    pub domain_burst: u32,
+}
+
+impl Default for HttpConfig {
+    fn default() -> Self {
+        Self {
+            http2: false,
+            pool_idle_timeout_secs: None,
+            pool_max_idle_per_host: None,
+            tcp_keepalive_secs: None,
+            doh_url: None,
+            tor_isolation: false,
+            domain_rate: None,
+            domain_burst: 5,
+        }
+    }
+}
+
+/// `[search]`: how aggregate searches in the web UI and the CLI spread
+/// over indexers
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SearchConfig {
+    /// Indexers searched at the same time
+    pub concurrency: usize,
+    /// Failed searches in a row after which an indexer is skipped for a
+    /// while (never when 0)
+    pub failure_threshold: u32,
+    /// How long a failing indexer is first skipped, doubling with every
+    /// further failure up to a day
+    pub failure_backoff_mins: u64,
+    /// Give each indexer a deadline derived from its recent latency
+    /// (p95 × `adaptive_timeout_factor`, clamped to the bounds)
+    pub adaptive_timeout: bool,
+    pub adaptive_timeout_factor: f64,
+    pub adaptive_timeout_min_secs: u64,
+    pub adaptive_timeout_max_secs: u64,
+    /// For definitions with several links and no `_mirror` chosen, race the
+    /// first two and search whichever answers first
+    pub race_mirrors: bool,
+}
+
+impl Default for SearchConfig {
+    fn default() -> Self {
+        Self {
+            concurrency: 4,
+            failure_threshold: 5,
+            failure_backoff_mins: 5,
+            adaptive_timeout: false,
+            adaptive_timeout_factor: 3.0,
+            adaptive_timeout_min_secs: 5,
+            adaptive_timeout_max_secs: 60,
+            race_mirrors: false,
+        }
+    }
+}
+
+impl SearchConfig {
+    /// `concurrency`, at least 1
+    pub fn concurrency(&self) -> usize {
+        self.concurrency.max(1)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct TuiConfig {
+    /// Keybinding overrides (action name -> key), see `tui::keys`
+    #[serde(default)]
+    pub keys: std::collections::HashMap<String, String>,
+}
+
+/// Size and number formatting, registered at startup
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct DisplayConfig {
+    pub size_units: SizeUnits,
+    /// Language tag picking the decimal and thousands separators, e.g.
+    /// `de-DE` for `1.234,5`. Falls back to `LC_ALL`, `LC_NUMERIC` or `LANG`.
+    pub locale: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum SizeUnits {
+    /// Powers of 1024: KiB, MiB, GiB, TiB
+    #[default]
+    Binary,
+    /// Powers of 1000: kB, MB, GB, TB
+    Decimal,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum MagnetPreference {
+    /// Magnet as the enclosure, the .torrent link only when there is none
+    #[default]
+    MagnetFirst,
+    /// .torrent link as the enclosure, the magnet only when there is none
+    LinkFirst,
+    /// .torrent link as the enclosure, the magnet as `magneturl` as well
+    Both,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -35,6 +371,7 @@ pub struct IndexerConfig {
 pub enum ClientType {
     TorrServer,
     QBittorrent,
+    Transmission,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -48,25 +385,102 @@ pub struct DownloadClient {
     pub password: Option<String>,
 }
 
-fn default_client_id() -> String {
-    uuid::Uuid::new_v4().to_string()
+/// Search cache and history backend (`[store]`)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StoreConfig {
+    pub backend: StoreBackend,
+    /// Server for the redis backend, e.g. `redis://cache:6379/0`
+    pub url: Option<String>,
+    /// Prepended to every key ("lodestarr:" when unset), so separate
+    /// clusters can share one server
+    pub prefix: Option<String>,
 }
 
-impl Config {
-    pub fn load() -> Result<Self> {
-        let path = Self::config_path()?;
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum StoreBackend {
+    /// The local database file
+    #[default]
+    Sqlite,
+    /// A Redis server shared by every instance (needs the `redis-store` feature)
+    Redis,
+}
 
-        if !path.exists() {
-            return Ok(Config::default());
-        }
+/// Background prefetch of .torrent files for web UI searches
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TorrentPrefetchConfig {
+    /// How many of the top results to prefetch (0 turns it off)
+    pub top: usize,
+    /// Also prefetch from private and semi-private trackers, where a
+    /// download may count against the account
+    pub private: bool,
+}
 
-        let content = fs::read_to_string(&path).context("Failed to read config file")?;
+/// Kind of *arr app, which decides its API version
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ArrKind {
+    Sonarr,
+    Radarr,
+    Lidarr,
+    Readarr,
+}
 
-        let config: Config = toml::from_str(&content).context("Failed to parse config file")?;
+/// A *arr app that receives releases pushed from the web UI (`/api/push/{name}`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArrApp {
+    pub name: String,
+    pub kind: ArrKind,
+    pub url: String,
+    pub api_key: String,
+    /// Lodestarr's URL as the app reaches it, for download links
+    /// (default: the URL the web UI uses)
+    pub lodestarr_url: Option<String>,
+    /// Lodestarr indexer -> the app's indexer id. Indexers not listed are
+    /// matched against the app's Torznab indexers pointing at Lodestarr.
+    #[serde(default)]
+    pub indexer_ids: std::collections::HashMap<String, i32>,
+}
+
+/// Preferences for picking the "best" result (`search --best`, `/api/best`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ScoringProfile {
+    /// Resolutions in order of preference (matched against the title)
+    pub resolutions: Vec<String>,
+    /// Results with fewer seeders are never picked
+    pub min_seeders: u32,
+    /// Results larger than this (in GiB) are never picked
+    pub max_size_gb: Option<f64>,
+}
 
-        Ok(config)
+impl Default for ScoringProfile {
+    fn default() -> Self {
+        Self {
+            resolutions: vec!["1080p".into(), "2160p".into(), "720p".into()],
+            min_seeders: 1,
+            max_size_gb: None,
+        }
+    }
+}
+
+fn default_client_id() -> String {
+    uuid::Uuid::new_v4().to_string()
+}
+
+impl Config {
+    /// Builder with the config file and `LODESTARR__*` environment layers
+    /// applied; add CLI flag overrides on top and `build()`
+    pub fn builder() -> Result<ConfigBuilder> {
+        Ok(ConfigBuilder::new()
+            .add_file(&Self::config_path()?)?
+            .add_env(std::env::vars()))
     }
 
+    /// Write the config file. Values from the environment or flags that
+    /// haven't been changed since are left as the file had them.
     pub fn save(&self) -> Result<()> {
         let path = Self::config_path()?;
 
@@ -74,13 +488,16 @@ impl Config {
             fs::create_dir_all(parent)?;
         }
 
-        let content = toml::to_string_pretty(self)?;
+        let file = self
+            .overrides
+            .file_config(self, &layers::read_file(&path)?)?;
+        let content = toml::to_string_pretty(&file)?;
         fs::write(&path, content)?;
 
         Ok(())
     }
 
-    fn config_path() -> Result<PathBuf> {
+    pub fn config_path() -> Result<PathBuf> {
         let proj_dirs = ProjectDirs::from("com", "lodestarr", "lodestarr")
             .ok_or_else(|| anyhow::anyhow!("Could not determine config directory"))?;
 
@@ -98,6 +515,27 @@ impl Config {
         Ok(proj_dirs.config_dir().join("lodestarr.db"))
     }
 
+    /// Directory for cached poster images, next to the database
+    pub fn get_image_cache_path(&self) -> Result<PathBuf> {
+        let db_path = self.get_db_path()?;
+        let dir = db_path.parent().map(PathBuf::from).unwrap_or_default();
+        Ok(dir.join("cache").join("images"))
+    }
+
+    /// Directory for prefetched .torrent files, next to the database
+    pub fn get_torrent_cache_path(&self) -> Result<PathBuf> {
+        let db_path = self.get_db_path()?;
+        let dir = db_path.parent().map(PathBuf::from).unwrap_or_default();
+        Ok(dir.join("cache").join("torrents"))
+    }
+
+    /// Directory for cookies imported from a browser, next to the database
+    pub fn get_cookies_path(&self) -> Result<PathBuf> {
+        let db_path = self.get_db_path()?;
+        let dir = db_path.parent().map(PathBuf::from).unwrap_or_default();
+        Ok(dir.join("cookies"))
+    }
+
     pub fn get_indexers_path(&self) -> Result<PathBuf> {
         if let Some(path) = &self.indexers_path {
             return Ok(PathBuf::from(path));
@@ -140,6 +578,76 @@ impl Config {
         !self.disabled_indexers.contains(&name.to_string())
     }
 
+    /// Indexers a search runs on. `target` is `all` or a comma-separated
+    /// list of indexer names and `group:<name>` groups. Without one,
+    /// `default_indexers` are searched when set, else all indexers.
+    pub fn select_indexers(&self, target: Option<&str>) -> Result<IndexerSelection> {
+        let target = match target.map(str::trim).filter(|t| !t.is_empty()) {
+            Some(target) => target.to_string(),
+            None if self.default_indexers.is_empty() => return Ok(IndexerSelection::All),
+            None => self.default_indexers.join(","),
+        };
+        let mut names: Vec<String> = Vec::new();
+        for part in target.split(',').map(str::trim).filter(|p| !p.is_empty()) {
+            if part == "all" {
+                return Ok(IndexerSelection::All);
+            }
+            let members = match part.strip_prefix("group:") {
+                Some(group) => self
+                    .indexer_groups
+                    .get(group)
+                    .with_context(|| format!("Unknown indexer group '{}'", group))?
+                    .clone(),
+                None => vec![part.to_string()],
+            };
+            for name in members {
+                if !names.contains(&name) {
+                    names.push(name);
+                }
+            }
+        }
+        Ok(IndexerSelection::Names(names))
+    }
+
+    /// Indexers behind an aggregate Torznab indexer: `all`, or `tag-<name>`
+    /// for the members of a group. `None` for anything else.
+    pub fn aggregate_indexers(&self, id: &str) -> Option<IndexerSelection> {
+        if id == "all" {
+            return Some(IndexerSelection::All);
+        }
+        let group = self.indexer_groups.get(id.strip_prefix("tag-")?)?;
+        Some(IndexerSelection::Names(group.clone()))
+    }
+
+    /// Search types turned off for a native indexer (`_disabledModes`, a
+    /// comma-separated list of Torznab actions). Unknown names are ignored.
+    pub fn disabled_search_types(&self, id: &str) -> Vec<SearchType> {
+        self.native_settings
+            .get(id)
+            .and_then(|s| s.get("_disabledModes"))
+            .map(|modes| {
+                modes
+                    .split(',')
+                    .filter_map(|m| SearchType::from_param(m.trim()))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    pub fn search_type_enabled(&self, id: &str, search_type: SearchType) -> bool {
+        !self.disabled_search_types(id).contains(&search_type)
+    }
+
+    /// Most results kept from indexer `id` in one search
+    pub fn max_results_for(&self, id: &str) -> usize {
+        let ceiling = self.max_results.unwrap_or(DEFAULT_MAX_RESULTS);
+        self.native_settings
+            .get(id)
+            .and_then(|s| s.get("_resultLimit"))
+            .and_then(|v| v.trim().parse().ok())
+            .map_or(ceiling, |limit: usize| limit.min(ceiling))
+    }
+
     pub fn set_enabled(&mut self, name: &str, enabled: bool) {
         if enabled {
             self.disabled_indexers.retain(|x| x != name);
@@ -153,6 +661,49 @@ impl Config {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_select_indexers() {
+        let mut config: Config = toml::from_str(
+            r#"
+            [indexer_groups]
+            public = ["tpb", "1337x"]
+            "#,
+        )
+        .unwrap();
+        let names =
+            |list: &[&str]| IndexerSelection::Names(list.iter().map(|n| n.to_string()).collect());
+
+        assert_eq!(config.select_indexers(None).unwrap(), IndexerSelection::All);
+        assert_eq!(
+            config
+                .select_indexers(Some("group:public,yts,tpb"))
+                .unwrap(),
+            names(&["tpb", "1337x", "yts"])
+        );
+        assert!(config.select_indexers(Some("group:private")).is_err());
+
+        config.default_indexers = vec!["rarbg-clone".into(), "group:public".into()];
+        let defaults = config.select_indexers(None).unwrap();
+        assert_eq!(defaults, names(&["rarbg-clone", "tpb", "1337x"]));
+        assert!(defaults.includes("1337x") && !defaults.includes("yts"));
+        assert_eq!(
+            config.select_indexers(Some("all")).unwrap(),
+            IndexerSelection::All
+        );
+        assert!(config.select_indexers(Some("yts")).unwrap().is_single());
+
+        assert_eq!(
+            config.aggregate_indexers("tag-public"),
+            Some(names(&["tpb", "1337x"]))
+        );
+        assert_eq!(
+            config.aggregate_indexers("all"),
+            Some(IndexerSelection::All)
+        );
+        assert_eq!(config.aggregate_indexers("tag-private"), None);
+        assert_eq!(config.aggregate_indexers("public"), None);
+    }
+
     #[test]
     fn test_add_indexer() {
         let mut config = Config::default();
@@ -190,4 +741,57 @@ mod tests {
 
         assert!(!config.remove_indexer("NonExistent"));
     }
+
+    #[test]
+    fn test_disabled_search_types() {
+        let mut config = Config::default();
+        assert!(config.search_type_enabled("yts", SearchType::TvSearch));
+
+        config.native_settings.insert(
+            "yts".to_string(),
+            std::collections::HashMap::from([(
+                "_disabledModes".to_string(),
+                "tvsearch, bogus".to_string(),
+            )]),
+        );
+        assert_eq!(
+            config.disabled_search_types("yts"),
+            vec![SearchType::TvSearch]
+        );
+        assert!(!config.search_type_enabled("yts", SearchType::TvSearch));
+        assert!(config.search_type_enabled("yts", SearchType::Movie));
+        assert!(config.search_type_enabled("eztv", SearchType::TvSearch));
+    }
+
+    #[test]
+    fn test_max_results_for() {
+        let mut config = Config::default();
+        assert_eq!(config.max_results_for("yts"), DEFAULT_MAX_RESULTS);
+
+        config.max_results = Some(200);
+        config.native_settings.insert(
+            "yts".to_string(),
+            std::collections::HashMap::from([("_resultLimit".to_string(), "50".to_string())]),
+        );
+        config.native_settings.insert(
+            "eztv".to_string(),
+            std::collections::HashMap::from([("_resultLimit".to_string(), "500".to_string())]),
+        );
+        assert_eq!(config.max_results_for("yts"), 50);
+        // The global ceiling wins over a higher per-indexer limit
+        assert_eq!(config.max_results_for("eztv"), 200);
+        assert_eq!(config.max_results_for("1337x"), 200);
+    }
+
+    #[test]
+    fn test_server_base_url() {
+        let mut server = ServerConfig::default();
+        assert_eq!(server.base_url(), "http://127.0.0.1:3420");
+        server.host = "fe80::1".to_string();
+        assert_eq!(server.base_url(), "http://[fe80::1]:3420");
+        server.host = "192.168.1.5".to_string();
+        assert_eq!(server.base_url(), "http://192.168.1.5:3420");
+        server.public_url = Some("https://lodestarr.lan/".to_string());
+        assert_eq!(server.base_url(), "https://lodestarr.lan");
+    }
 }
@@ -0,0 +1,425 @@
+//! Layered configuration: config file < environment < CLI flags
+//!
+//! Environment variables use `LODESTARR__` plus the key path in upper case,
+//! with `__` between levels: `LODESTARR__SERVER__PORT=8080`,
+//! `LODESTARR__HTTP__DOMAIN_RATE=1.5`. Array items are addressed by index
+//! (`LODESTARR__INDEXERS__0__URL`) and whole values can be given as TOML
+//! (`LODESTARR__DISABLED_INDEXERS='["a", "b"]'`).
+
+use super::{ClientType, Config, DownloadClient, HttpConfig, IndexerConfig, ServerConfig};
+use anyhow::{Context, Result};
+use std::collections::HashMap;
+use std::path::Path;
+use toml::{Table, Value};
+
+const ENV_PREFIX: &str = "LODESTARR__";
+
+/// Builds a [`Config`] from layers, later layers overriding earlier ones
+pub struct ConfigBuilder {
+    table: Table,
+    /// Serialized [`shape`], used to tell which keys hold strings
+    shape: Table,
+    /// Values set by the environment and flags
+    overrides: Vec<(Vec<String>, Value)>,
+}
+
+/// Keys a [`Config`] got from the environment or flags rather than the
+/// file, so [`Config::save`] can leave them out of it
+#[derive(Debug, Clone, Default)]
+pub struct Overrides(Vec<(Vec<String>, Value)>);
+
+impl Overrides {
+    /// `config` as the file should hold it: keys still holding an override
+    /// get the file's value back (or are dropped); keys changed since are
+    /// kept
+    pub fn file_config(&self, config: &Config, file: &Table) -> Result<Config> {
+        let mut table = Table::try_from(config)?;
+        for (path, value) in &self.0 {
+            if lookup(&table, path) != Some(value) {
+                continue;
+            }
+            match lookup(file, path) {
+                Some(original) => {
+                    set_path(&mut table, path, original.clone());
+                }
+                None => {
+                    // Drop from the first level the file lacks, so array
+                    // items made by the environment go as a whole
+                    let missing = (1..=path.len())
+                        .find(|&len| lookup(file, &path[..len]).is_none())
+                        .unwrap_or(path.len());
+                    remove_path(&mut table, &path[..missing]);
+                }
+            }
+        }
+        Value::Table(table)
+            .try_into()
+            .context("Failed to rebuild config for saving")
+    }
+}
+
+impl Default for ConfigBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ConfigBuilder {
+    pub fn new() -> Self {
+        Self {
+            table: Table::new(),
+            shape: Table::try_from(shape()).unwrap_or_default(),
+            overrides: Vec::new(),
+        }
+    }
+
+    /// Merge a TOML file (skipped if it doesn't exist)
+    pub fn add_file(mut self, path: &Path) -> Result<Self> {
+        merge(&mut self.table, read_file(path)?);
+        Ok(self)
+    }
+
+    /// Apply `LODESTARR__*` variables
+    pub fn add_env(mut self, vars: impl IntoIterator<Item = (String, String)>) -> Self {
+        let mut vars: Vec<_> = vars
+            .into_iter()
+            .filter_map(|(k, v)| Some((k.strip_prefix(ENV_PREFIX)?.to_string(), v)))
+            .collect();
+        // Deterministic order so array items are created by index
+        vars.sort();
+        for (key, raw) in vars {
+            let path: Vec<String> = key.split("__").map(|s| s.to_lowercase()).collect();
+            if path.iter().any(String::is_empty) {
+                tracing::warn!("Ignoring malformed config variable {}{}", ENV_PREFIX, key);
+                continue;
+            }
+            let value = self.typed_value(&path, &raw);
+            if set_path(&mut self.table, &path, value.clone()) {
+                self.overrides.push((path, value));
+            } else {
+                tracing::warn!(
+                    "Ignoring {}{}: no such array item or not a table",
+                    ENV_PREFIX,
+                    key
+                );
+            }
+        }
+        self
+    }
+
+    /// Set one key (dotted path), e.g. from a CLI flag
+    pub fn set_override(mut self, key: &str, value: impl Into<Value>) -> Self {
+        let path: Vec<String> = key.split('.').map(String::from).collect();
+        let value = value.into();
+        if set_path(&mut self.table, &path, value.clone()) {
+            self.overrides.push((path, value));
+        }
+        self
+    }
+
+    pub fn build(self) -> Result<Config> {
+        let mut config: Config = Value::Table(self.table)
+            .try_into()
+            .context("Failed to parse config file")?;
+        config.overrides = Overrides(self.overrides);
+        Ok(config)
+    }
+
+    /// Keys that hold strings keep the raw text, anything else is parsed as a
+    /// TOML value (falling back to a string)
+    fn typed_value(&self, path: &[String], raw: &str) -> Value {
+        let shape_path: Vec<String> = path
+            .iter()
+            .map(|s| match s.parse::<usize>() {
+                Ok(_) => "0".to_string(),
+                Err(_) => s.clone(),
+            })
+            .collect();
+        let current = lookup(&self.table, path).or_else(|| lookup(&self.shape, &shape_path));
+        if matches!(current, Some(Value::String(_))) {
+            return Value::String(raw.to_string());
+        }
+        format!("v = {}", raw)
+            .parse::<Table>()
+            .ok()
+            .and_then(|mut t| t.remove("v"))
+            .unwrap_or_else(|| Value::String(raw.to_string()))
+    }
+}
+
+/// A config with every optional string set and one item in each list or map
+/// (`*` standing for any map key), so the type of any key can be looked up
+fn shape() -> Config {
+    let any = || Some(String::new());
+    Config {
+        indexers: vec![IndexerConfig {
+            name: String::new(),
+            url: String::new(),
+            apikey: any(),
+        }],
+        download_path: any(),
+        download_template: any(),
+        magnet_trackers: Some(vec![String::new()]),
+        proxy_url: any(),
+        db_path: any(),
+        indexers_path: any(),
+        native_settings: HashMap::from([(
+            "*".to_string(),
+            HashMap::from([("*".to_string(), String::new())]),
+        )]),
+        download_clients: vec![DownloadClient {
+            id: String::new(),
+            name: String::new(),
+            client_type: ClientType::QBittorrent,
+            url: String::new(),
+            username: any(),
+            password: any(),
+        }],
+        http: HttpConfig {
+            doh_url: any(),
+            ..Default::default()
+        },
+        server: ServerConfig {
+            public_url: any(),
+            ..Default::default()
+        },
+        ..Default::default()
+    }
+}
+
+/// A TOML file as a table (empty if it doesn't exist)
+pub fn read_file(path: &Path) -> Result<Table> {
+    if !path.exists() {
+        return Ok(Table::new());
+    }
+    let content = std::fs::read_to_string(path).context("Failed to read config file")?;
+    toml::from_str(&content).context("Failed to parse config file")
+}
+
+/// Deep-merge `other` into `base`
+fn merge(base: &mut Table, other: Table) {
+    for (key, value) in other {
+        match (base.get_mut(&key), value) {
+            (Some(Value::Table(b)), Value::Table(o)) => merge(b, o),
+            (_, value) => {
+                base.insert(key, value);
+            }
+        }
+    }
+}
+
+/// Key in `table` matching `key` case-insensitively (so lower-cased
+/// environment paths still reach keys like `_remoteApiKey`)
+fn find_key(table: &Table, key: &str) -> String {
+    table
+        .keys()
+        .find(|k| k.eq_ignore_ascii_case(key))
+        .cloned()
+        .unwrap_or_else(|| key.to_string())
+}
+
+fn lookup<'a>(table: &'a Table, path: &[String]) -> Option<&'a Value> {
+    let (first, rest) = path.split_first()?;
+    let mut node = table
+        .get(&find_key(table, first))
+        .or_else(|| table.get("*"))?;
+    for segment in rest {
+        node = match node {
+            Value::Table(t) => t.get(&find_key(t, segment)).or_else(|| t.get("*"))?,
+            Value::Array(a) => a.get(segment.parse::<usize>().ok()?)?,
+            _ => return None,
+        };
+    }
+    Some(node)
+}
+
+/// Set a value, creating tables (and appending array items) along the way.
+/// Returns false if the path runs through something else.
+fn set_path(table: &mut Table, path: &[String], value: Value) -> bool {
+    let Some((first, rest)) = path.split_first() else {
+        return false;
+    };
+    let key = find_key(table, first);
+    if rest.is_empty() {
+        table.insert(key, value);
+        return true;
+    }
+    let next_is_index = rest[0].parse::<usize>().is_ok();
+    let child = table.entry(key).or_insert_with(|| {
+        if next_is_index {
+            Value::Array(Vec::new())
+        } else {
+            Value::Table(Table::new())
+        }
+    });
+    set_value(child, rest, value)
+}
+
+fn set_value(node: &mut Value, path: &[String], value: Value) -> bool {
+    match node {
+        Value::Table(t) => set_path(t, path, value),
+        Value::Array(items) => {
+            let Some((first, rest)) = path.split_first() else {
+                return false;
+            };
+            let Ok(index) = first.parse::<usize>() else {
+                return false;
+            };
+            if index == items.len() {
+                items.push(Value::Table(Table::new()));
+            }
+            match items.get_mut(index) {
+                Some(item) if rest.is_empty() => {
+                    *item = value;
+                    true
+                }
+                Some(item) => set_value(item, rest, value),
+                None => false,
+            }
+        }
+        _ => false,
+    }
+}
+
+/// Remove a key or array item
+fn remove_path(table: &mut Table, path: &[String]) {
+    match path {
+        [] => {}
+        [last] => {
+            table.remove(&find_key(table, last));
+        }
+        [first, rest @ ..] => {
+            if let Some(child) = table.get_mut(&find_key(table, first)) {
+                remove_value(child, rest);
+            }
+        }
+    }
+}
+
+fn remove_value(node: &mut Value, path: &[String]) {
+    match node {
+        Value::Table(t) => remove_path(t, path),
+        Value::Array(items) => {
+            let Some((first, rest)) = path.split_first() else {
+                return;
+            };
+            let Ok(index) = first.parse::<usize>() else {
+                return;
+            };
+            if rest.is_empty() {
+                if index < items.len() {
+                    items.remove(index);
+                }
+            } else if let Some(item) = items.get_mut(index) {
+                remove_value(item, rest);
+            }
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn env(vars: &[(&str, &str)]) -> Vec<(String, String)> {
+        vars.iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_layers() {
+        let mut builder = ConfigBuilder::new();
+        merge(
+            &mut builder.table,
+            toml::from_str(
+                r#"
+                indexers = []
+                proxy_url = "http://file-proxy:8080"
+                [server]
+                port = 1000
+                [native_settings.rutracker]
+                _remoteApiKey = "old"
+                "#,
+            )
+            .unwrap(),
+        );
+
+        let config = builder
+            .add_env(env(&[
+                ("LODESTARR__SERVER__PORT", "2000"),
+                ("LODESTARR__HTTP__TOR_ISOLATION", "true"),
+                ("LODESTARR__DB_PATH", "12345"),
+                ("LODESTARR__INDEXERS__0__NAME", "jackett"),
+                ("LODESTARR__INDEXERS__0__URL", "http://jackett:9117/api"),
+                (
+                    "LODESTARR__NATIVE_SETTINGS__RUTRACKER___REMOTEAPIKEY",
+                    "new",
+                ),
+                ("LODESTARR__NATIVE_SETTINGS__RUTRACKER___PRIORITY", "10"),
+                ("OTHER__SERVER__PORT", "1"),
+            ]))
+            .set_override("server.port", 3000)
+            .build()
+            .unwrap();
+
+        assert_eq!(config.server.port, 3000);
+        assert!(config.http.tor_isolation);
+        assert_eq!(config.proxy_url.as_deref(), Some("http://file-proxy:8080"));
+        assert_eq!(config.db_path.as_deref(), Some("12345"));
+        assert_eq!(config.indexers.len(), 1);
+        assert_eq!(config.indexers[0].name, "jackett");
+        assert_eq!(config.native_settings["rutracker"]["_remoteApiKey"], "new");
+        assert_eq!(config.native_settings["rutracker"]["_priority"], "10");
+    }
+
+    #[test]
+    fn test_save_keeps_overrides_out() {
+        let file: Table = toml::from_str(
+            r#"
+            download_path = "/downloads"
+            [server]
+            port = 1000
+            "#,
+        )
+        .unwrap();
+        let mut builder = ConfigBuilder::new();
+        merge(&mut builder.table, file.clone());
+        let mut config = builder
+            .add_env(env(&[
+                ("LODESTARR__SERVER__AUTH__API_KEY", "secret"),
+                ("LODESTARR__PROXY_URL", "http://env-proxy:8080"),
+                ("LODESTARR__INDEXERS__0__NAME", "jackett"),
+                ("LODESTARR__INDEXERS__0__URL", "http://jackett:9117/api"),
+            ]))
+            .set_override("server.port", 3000)
+            .set_override("server.host", "0.0.0.0")
+            .build()
+            .unwrap();
+
+        // Edits made at runtime are saved, even to overridden keys
+        config.download_path = Some("/media".to_string());
+        config.proxy_url = Some("http://edited:8080".to_string());
+        config.disabled_indexers.push("x".to_string());
+
+        let saved = config.overrides.file_config(&config, &file).unwrap();
+        assert_eq!(saved.server.port, 1000);
+        assert_eq!(saved.server.host, ServerConfig::default().host);
+        assert_eq!(saved.server.auth.api_key, None);
+        assert!(saved.indexers.is_empty());
+        assert_eq!(saved.download_path.as_deref(), Some("/media"));
+        assert_eq!(saved.proxy_url.as_deref(), Some("http://edited:8080"));
+        assert_eq!(saved.disabled_indexers, vec!["x"]);
+    }
+
+    #[test]
+    fn test_env_only() {
+        let config = ConfigBuilder::new()
+            .add_env(env(&[("LODESTARR__DISABLED_INDEXERS", r#"["a", "b"]"#)]))
+            .build()
+            .unwrap();
+        assert_eq!(config.disabled_indexers, vec!["a", "b"]);
+        assert_eq!(config.server.port, 3420);
+    }
+}
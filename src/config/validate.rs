@@ -0,0 +1,500 @@
+//! Config validation (`lodestarr config validate`, `POST /api/settings/validate`)
+//!
+//! Checks an edited config before it is saved: TOML syntax, keys the schema
+//! doesn't know (serde would silently drop them), URLs and duplicate names.
+//! Every issue points at the key path and, where it can be found, the line.
+
+use super::Config;
+use serde::Serialize;
+use std::collections::HashSet;
+
+/// A problem found in a config file
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Issue {
+    /// Key path, e.g. `indexers[1].url`
+    pub path: String,
+    /// 1-based line and column, when the key could be located
+    pub line: Option<usize>,
+    pub column: Option<usize>,
+    pub message: String,
+}
+
+impl std::fmt::Display for Issue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match (self.line, self.column) {
+            (Some(line), Some(col)) => write!(f, "{}:{}: ", line, col)?,
+            (Some(line), None) => write!(f, "{}: ", line)?,
+            _ => {}
+        }
+        if self.path.is_empty() {
+            write!(f, "{}", self.message)
+        } else {
+            write!(f, "{}: {}", self.path, self.message)
+        }
+    }
+}
+
+/// Validate config file contents. An empty list means the config is valid.
+pub fn validate(content: &str) -> Vec<Issue> {
+    let table: toml::Table = match content.parse() {
+        Ok(t) => t,
+        Err(e) => return vec![parse_issue(content, &e)],
+    };
+    let config: Config = match toml::from_str(content) {
+        Ok(c) => c,
+        Err(e) => return vec![parse_issue(content, &e)],
+    };
+
+    let mut issues = Vec::new();
+    let mut issue = |path: Vec<Segment>, message: String| {
+        let (line, column) = match locate(content, &path) {
+            Some((l, c)) => (Some(l), Some(c)),
+            None => (None, None),
+        };
+        issues.push(Issue {
+            path: format_path(&path),
+            line,
+            column,
+            message,
+        });
+    };
+
+    // Unknown keys: whatever doesn't survive a round trip through the schema
+    if let Ok(known) = toml::Value::try_from(&config) {
+        let mut unknown = Vec::new();
+        unknown_keys(
+            &toml::Value::Table(table),
+            &known,
+            &mut Vec::new(),
+            &mut unknown,
+        );
+        for path in unknown {
+            issue(path, "unknown key".to_string());
+        }
+    }
+
+    let mut names = HashSet::new();
+    for (i, idx) in config.indexers.iter().enumerate() {
+        if !names.insert(idx.name.as_str()) {
+            issue(
+                vec![
+                    Segment::key("indexers"),
+                    Segment::Index(i),
+                    Segment::key("name"),
+                ],
+                format!("duplicate indexer name '{}'", idx.name),
+            );
+        }
+        if let Err(e) = check_url(&idx.url, &["http", "https"]) {
+            issue(
+                vec![
+                    Segment::key("indexers"),
+                    Segment::Index(i),
+                    Segment::key("url"),
+                ],
+                e,
+            );
+        }
+    }
+
+    for entry in &config.default_indexers {
+        if let Some(group) = entry.trim().strip_prefix("group:")
+            && !config.indexer_groups.contains_key(group)
+        {
+            issue(
+                vec![Segment::key("default_indexers")],
+                format!("unknown indexer group '{}'", group),
+            );
+        }
+    }
+
+    let mut client_names = HashSet::new();
+    for (i, client) in config.download_clients.iter().enumerate() {
+        if !client_names.insert(client.name.as_str()) {
+            issue(
+                vec![
+                    Segment::key("download_clients"),
+                    Segment::Index(i),
+                    Segment::key("name"),
+                ],
+                format!("duplicate download client name '{}'", client.name),
+            );
+        }
+        if let Err(e) = check_url(&client.url, &["http", "https"]) {
+            issue(
+                vec![
+                    Segment::key("download_clients"),
+                    Segment::Index(i),
+                    Segment::key("url"),
+                ],
+                e,
+            );
+        }
+    }
+
+    let mut app_names = HashSet::new();
+    for (i, app) in config.arr_apps.iter().enumerate() {
+        let path = |key: &str| {
+            vec![
+                Segment::key("arr_apps"),
+                Segment::Index(i),
+                Segment::key(key),
+            ]
+        };
+        if !app_names.insert(app.name.as_str()) {
+            issue(path("name"), format!("duplicate app name '{}'", app.name));
+        }
+        if let Err(e) = check_url(&app.url, &["http", "https"]) {
+            issue(path("url"), e);
+        }
+        if let Some(url) = &app.lodestarr_url
+            && let Err(e) = check_url(url, &["http", "https"])
+        {
+            issue(path("lodestarr_url"), e);
+        }
+    }
+
+    if let Some(proxy) = &config.proxy_url
+        && let Err(e) = check_url(proxy, &["http", "https", "socks5", "socks5h"])
+    {
+        issue(vec![Segment::key("proxy_url")], e);
+    }
+    if config.require_proxy && config.proxy_url.as_deref().is_none_or(str::is_empty) {
+        issue(
+            vec![Segment::key("require_proxy")],
+            "require_proxy is set without a proxy_url, so native searches will fail".to_string(),
+        );
+    }
+    if let Some(doh) = &config.http.doh_url
+        && let Err(e) = check_url(doh, &["https", "http"])
+    {
+        issue(vec![Segment::key("http"), Segment::key("doh_url")], e);
+    }
+    if config.store.backend == crate::config::StoreBackend::Redis {
+        match config.store.url.as_deref().map(str::trim) {
+            None | Some("") => issue(
+                vec![Segment::key("store"), Segment::key("backend")],
+                "the redis backend needs a url".to_string(),
+            ),
+            Some(url) => {
+                if let Err(e) = check_url(url, &["redis", "rediss"]) {
+                    issue(vec![Segment::key("store"), Segment::key("url")], e);
+                }
+            }
+        }
+    }
+
+    let mut settings: Vec<_> = config.native_settings.iter().collect();
+    settings.sort_by_key(|(id, _)| id.as_str());
+    for (id, values) in settings {
+        if let Some(remote) = values.get("_remote").filter(|v| !v.trim().is_empty())
+            && let Err(e) = check_url(remote.trim(), &["http", "https"])
+        {
+            issue(
+                vec![
+                    Segment::key("native_settings"),
+                    Segment::key(id),
+                    Segment::key("_remote"),
+                ],
+                e,
+            );
+        }
+        if let Some(modes) = values.get("_disabledModes") {
+            for mode in modes.split(',').map(str::trim).filter(|m| !m.is_empty()) {
+                if crate::models::SearchType::from_param(mode).is_none() {
+                    issue(
+                        vec![
+                            Segment::key("native_settings"),
+                            Segment::key(id),
+                            Segment::key("_disabledModes"),
+                        ],
+                        format!(
+                            "unknown search type '{}' (expected search, tvsearch, movie, music, book)",
+                            mode
+                        ),
+                    );
+                }
+            }
+        }
+    }
+
+    let auth = &config.server.auth;
+    if let Err(e) = auth.trusted_networks() {
+        issue(
+            vec![
+                Segment::key("server"),
+                Segment::key("auth"),
+                Segment::key("trusted_proxies"),
+            ],
+            e,
+        );
+    }
+    if auth.required_group.is_some() && auth.forward_groups_header.is_none() {
+        issue(
+            vec![
+                Segment::key("server"),
+                Segment::key("auth"),
+                Segment::key("required_group"),
+            ],
+            "needs forward_groups_header".to_string(),
+        );
+    }
+
+    if auth.username.is_some() != auth.password_hash.is_some() {
+        issue(
+            vec![
+                Segment::key("server"),
+                Segment::key("auth"),
+                Segment::key("username"),
+            ],
+            "username and password_hash go together; set them with `lodestarr auth set-password`"
+                .to_string(),
+        );
+    }
+
+    if config.http.domain_rate.is_some_and(|r| r <= 0.0) {
+        issue(
+            vec![Segment::key("http"), Segment::key("domain_rate")],
+            "must be greater than 0".to_string(),
+        );
+    }
+    if config.search.adaptive_timeout_min_secs > config.search.adaptive_timeout_max_secs {
+        issue(
+            vec![
+                Segment::key("search"),
+                Segment::key("adaptive_timeout_min_secs"),
+            ],
+            "is larger than adaptive_timeout_max_secs".to_string(),
+        );
+    }
+
+    issues.sort_by_key(|i| i.line.unwrap_or(usize::MAX));
+    issues
+}
+
+/// One step in a key path
+#[derive(Debug, Clone, PartialEq)]
+enum Segment {
+    Key(String),
+    Index(usize),
+}
+
+impl Segment {
+    fn key(k: &str) -> Self {
+        Self::Key(k.to_string())
+    }
+}
+
+fn format_path(path: &[Segment]) -> String {
+    let mut out = String::new();
+    for segment in path {
+        match segment {
+            Segment::Key(k) => {
+                if !out.is_empty() {
+                    out.push('.');
+                }
+                out.push_str(k);
+            }
+            Segment::Index(i) => out.push_str(&format!("[{}]", i)),
+        }
+    }
+    out
+}
+
+fn parse_issue(content: &str, e: &toml::de::Error) -> Issue {
+    let (line, column) = match e.span() {
+        Some(span) => {
+            let (l, c) = line_col(content, span.start);
+            (Some(l), Some(c))
+        }
+        None => (None, None),
+    };
+    Issue {
+        path: String::new(),
+        line,
+        column,
+        message: e.message().to_string(),
+    }
+}
+
+fn line_col(content: &str, offset: usize) -> (usize, usize) {
+    let before = &content[..offset.min(content.len())];
+    let line = before.matches('\n').count() + 1;
+    let column = before.len() - before.rfind('\n').map_or(0, |i| i + 1) + 1;
+    (line, column)
+}
+
+fn check_url(value: &str, schemes: &[&str]) -> Result<(), String> {
+    let url = url::Url::parse(value).map_err(|e| format!("invalid URL '{}': {}", value, e))?;
+    if !schemes.contains(&url.scheme()) {
+        return Err(format!(
+            "unsupported scheme '{}' (expected {})",
+            url.scheme(),
+            schemes.join(", ")
+        ));
+    }
+    if url.host_str().is_none_or(str::is_empty) {
+        return Err(format!("URL '{}' has no host", value));
+    }
+    Ok(())
+}
+
+/// Collect paths present in `original` but missing from `known`
+fn unknown_keys(
+    original: &toml::Value,
+    known: &toml::Value,
+    path: &mut Vec<Segment>,
+    out: &mut Vec<Vec<Segment>>,
+) {
+    match (original, known) {
+        (toml::Value::Table(orig), toml::Value::Table(known)) => {
+            for (key, value) in orig {
+                path.push(Segment::Key(key.clone()));
+                match known.get(key) {
+                    Some(k) => unknown_keys(value, k, path, out),
+                    None => out.push(path.clone()),
+                }
+                path.pop();
+            }
+        }
+        (toml::Value::Array(orig), toml::Value::Array(known)) => {
+            for (i, (o, k)) in orig.iter().zip(known).enumerate() {
+                path.push(Segment::Index(i));
+                unknown_keys(o, k, path, out);
+                path.pop();
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Find the line and column of a key by scanning table headers and
+/// `key = value` lines. Dotted keys and inline tables aren't resolved.
+fn locate(content: &str, target: &[Segment]) -> Option<(usize, usize)> {
+    let mut table: Vec<Segment> = Vec::new();
+    let mut array_counts: std::collections::HashMap<String, usize> = Default::default();
+
+    for (n, raw) in content.lines().enumerate() {
+        let line = raw.trim_start();
+        let indent = raw.len() - line.len();
+        if line.starts_with('#') || line.is_empty() {
+            continue;
+        }
+        if let Some(header) = line.strip_prefix("[[") {
+            let name = header.split("]]").next()?.trim();
+            let count = array_counts.entry(name.to_string()).or_insert(0);
+            table = header_path(name);
+            table.push(Segment::Index(*count));
+            *count += 1;
+            if target.starts_with(&table) && target.len() == table.len() {
+                return Some((n + 1, indent + 1));
+            }
+            continue;
+        }
+        if let Some(header) = line.strip_prefix('[') {
+            table = header_path(header.split(']').next()?.trim());
+            if target == table.as_slice() {
+                return Some((n + 1, indent + 1));
+            }
+            continue;
+        }
+        if let Some((key, _)) = line.split_once('=') {
+            let key = key.trim().trim_matches('"');
+            if target.len() == table.len() + 1
+                && target.starts_with(&table)
+                && target.last() == Some(&Segment::key(key))
+            {
+                return Some((n + 1, indent + 1));
+            }
+        }
+    }
+    None
+}
+
+fn header_path(name: &str) -> Vec<Segment> {
+    name.split('.')
+        .map(|part| Segment::key(part.trim().trim_matches('"')))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_ok() {
+        let content = r#"
+proxy_url = "socks5h://127.0.0.1:9050"
+
+[[indexers]]
+name = "a"
+url = "http://localhost:9117/api"
+
+[native_settings.rutracker]
+_remote = "http://10.8.0.2:3420"
+_disabledModes = "tvsearch,music"
+
+[[arr_apps]]
+name = "sonarr"
+kind = "sonarr"
+url = "http://sonarr:8989"
+api_key = "abc"
+lodestarr_url = "http://lodestarr:3420"
+
+[arr_apps.indexer_ids]
+yts = 12
+
+[server.auth]
+forward_user_header = "Remote-User"
+forward_groups_header = "Remote-Groups"
+required_group = "media"
+trusted_proxies = ["172.16.0.0/12", "::1"]
+"#;
+        assert_eq!(validate(content), vec![]);
+    }
+
+    #[test]
+    fn test_validate_issues() {
+        let content = r#"
+proxy_url = "ftp://proxy"
+check_updatez = true
+default_indexers = ["tpb", "group:public"]
+
+[http]
+domain_burst = 5
+pool_size = 3
+
+[[indexers]]
+name = "a"
+url = "http://a.example/api"
+
+[[indexers]]
+name = "a"
+url = "not a url"
+
+[store]
+backend = "redis"
+url = "http://cache:6379"
+"#;
+        let issues: Vec<String> = validate(content).iter().map(|i| i.to_string()).collect();
+        assert_eq!(
+            issues,
+            vec![
+                "2:1: proxy_url: unsupported scheme 'ftp' (expected http, https, socks5, socks5h)",
+                "3:1: check_updatez: unknown key",
+                "4:1: default_indexers: unknown indexer group 'public'",
+                "8:1: http.pool_size: unknown key",
+                "15:1: indexers[1].name: duplicate indexer name 'a'",
+                "16:1: indexers[1].url: invalid URL 'not a url': relative URL without a base",
+                "20:1: store.url: unsupported scheme 'http' (expected redis, rediss)",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_validate_syntax_error() {
+        let issues = validate("indexers = []\nproxy_url = \n");
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].line, Some(2));
+    }
+}
@@ -1,3 +1,4 @@
+use crate::models::{DateSource, TorrentResult};
 use chrono::{DateTime, Utc};
 use r2d2::Pool;
 use r2d2_sqlite::SqliteConnectionManager;
@@ -49,6 +50,192 @@ pub fn init_db<P: AsRef<Path>>(path: P) -> DbPool {
     )
     .expect("Failed to create download_logs table");
 
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS tui_history (
+            query TEXT PRIMARY KEY,
+            last_used DATETIME NOT NULL
+        )",
+        [],
+    )
+    .expect("Failed to create tui_history table");
+
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS indexer_latency (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            indexer TEXT NOT NULL,
+            timestamp DATETIME NOT NULL,
+            duration_ms INTEGER NOT NULL,
+            success INTEGER NOT NULL,
+            error TEXT
+        )",
+        [],
+    )
+    .expect("Failed to create indexer_latency table");
+    // Databases created before failures were classified
+    conn.execute("ALTER TABLE indexer_latency ADD COLUMN error TEXT", [])
+        .ok();
+    // ...and before result counts were recorded
+    conn.execute(
+        "ALTER TABLE indexer_latency ADD COLUMN result_count INTEGER",
+        [],
+    )
+    .ok();
+
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS first_seen (
+            guid TEXT PRIMARY KEY,
+            first_seen DATETIME NOT NULL,
+            last_seen DATETIME NOT NULL
+        )",
+        [],
+    )
+    .expect("Failed to create first_seen table");
+
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS saved_searches (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            name TEXT NOT NULL,
+            query TEXT NOT NULL,
+            indexer TEXT,
+            category TEXT,
+            created_at DATETIME NOT NULL
+        )",
+        [],
+    )
+    .expect("Failed to create saved_searches table");
+
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS raw_responses (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            search_key TEXT NOT NULL,
+            indexer TEXT NOT NULL,
+            url TEXT NOT NULL,
+            status INTEGER NOT NULL,
+            body TEXT NOT NULL,
+            timestamp DATETIME NOT NULL
+        )",
+        [],
+    )
+    .expect("Failed to create raw_responses table");
+
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS library (
+            path TEXT PRIMARY KEY,
+            name TEXT NOT NULL,
+            info_hash TEXT NOT NULL,
+            size INTEGER NOT NULL,
+            modified INTEGER NOT NULL,
+            indexed_at DATETIME NOT NULL
+        )",
+        [],
+    )
+    .expect("Failed to create library table");
+
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS sessions (
+            token_hash TEXT PRIMARY KEY,
+            username TEXT NOT NULL,
+            created_at DATETIME NOT NULL,
+            expires_at DATETIME NOT NULL
+        )",
+        [],
+    )
+    .expect("Failed to create sessions table");
+
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS indexer_traffic (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            indexer TEXT NOT NULL,
+            kind TEXT NOT NULL,
+            bytes INTEGER NOT NULL,
+            timestamp DATETIME NOT NULL
+        )",
+        [],
+    )
+    .expect("Failed to create indexer_traffic table");
+
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS guids (
+            indexer TEXT NOT NULL,
+            guid TEXT NOT NULL,
+            link TEXT,
+            magnet TEXT,
+            info_hash TEXT,
+            last_seen DATETIME NOT NULL,
+            pinned INTEGER NOT NULL DEFAULT 0,
+            PRIMARY KEY (indexer, guid)
+        )",
+        [],
+    )
+    .expect("Failed to create guids table");
+
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS rss_rules (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            name TEXT NOT NULL,
+            query TEXT NOT NULL,
+            indexer TEXT,
+            category TEXT,
+            title_pattern TEXT,
+            min_seeders INTEGER,
+            min_size INTEGER,
+            max_size INTEGER,
+            client_id TEXT,
+            interval_minutes INTEGER NOT NULL,
+            enabled INTEGER NOT NULL DEFAULT 1,
+            last_run DATETIME,
+            created_at DATETIME NOT NULL
+        )",
+        [],
+    )
+    .expect("Failed to create rss_rules table");
+
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS rss_matches (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            rule_id INTEGER NOT NULL,
+            guid TEXT NOT NULL,
+            title TEXT NOT NULL,
+            indexer TEXT,
+            link TEXT,
+            magnet TEXT,
+            size INTEGER,
+            seeders INTEGER,
+            status TEXT NOT NULL,
+            error TEXT,
+            found_at DATETIME NOT NULL,
+            last_seen DATETIME,
+            attempts INTEGER NOT NULL DEFAULT 0,
+            UNIQUE (rule_id, guid)
+        )",
+        [],
+    )
+    .expect("Failed to create rss_matches table");
+    // Databases created before matches were retried and pruned
+    conn.execute("ALTER TABLE rss_matches ADD COLUMN last_seen DATETIME", [])
+        .ok();
+    conn.execute(
+        "ALTER TABLE rss_matches ADD COLUMN attempts INTEGER NOT NULL DEFAULT 0",
+        [],
+    )
+    .ok();
+
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS indexer_health (
+            indexer TEXT PRIMARY KEY,
+            successes INTEGER NOT NULL DEFAULT 0,
+            failures INTEGER NOT NULL DEFAULT 0,
+            consecutive_failures INTEGER NOT NULL DEFAULT 0,
+            total_ms INTEGER NOT NULL DEFAULT 0,
+            last_success DATETIME,
+            last_failure DATETIME,
+            last_error TEXT,
+            failing_until DATETIME
+        )",
+        [],
+    )
+    .expect("Failed to create indexer_health table");
+
     // Indexes
     conn.execute(
         "CREATE INDEX IF NOT EXISTS idx_search_logs_timestamp ON search_logs(timestamp)",
@@ -65,6 +252,21 @@ pub fn init_db<P: AsRef<Path>>(path: P) -> DbPool {
         [],
     )
     .ok();
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_indexer_latency_indexer ON indexer_latency(indexer, timestamp)",
+        [],
+    )
+    .ok();
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_library_info_hash ON library(info_hash)",
+        [],
+    )
+    .ok();
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_indexer_traffic_timestamp ON indexer_traffic(timestamp)",
+        [],
+    )
+    .ok();
 
     pool
 }
@@ -91,26 +293,327 @@ pub fn log_search(
     Ok(())
 }
 
+/// Record how long one indexer took to answer a search, and how many
+/// results it gave if it succeeded
+pub fn log_indexer_latency(
+    pool: &DbPool,
+    indexer: &str,
+    duration_ms: u128,
+    error: Option<crate::indexer::ErrorKind>,
+    result_count: Option<usize>,
+) -> anyhow::Result<()> {
+    let conn = pool.get()?;
+    conn.execute(
+        "INSERT INTO indexer_latency (indexer, timestamp, duration_ms, success, error, result_count)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        params![
+            indexer,
+            Utc::now(),
+            duration_ms as i64,
+            error.is_none(),
+            error.map(|k| k.as_str()),
+            result_count.map(|c| c as i64)
+        ],
+    )?;
+    Ok(())
+}
+
+/// Result counts of each indexer's successful searches since `since`,
+/// newest first
+pub fn get_indexer_result_counts(
+    pool: &DbPool,
+    since: DateTime<Utc>,
+) -> anyhow::Result<std::collections::HashMap<String, Vec<usize>>> {
+    let conn = pool.get()?;
+    let mut stmt = conn.prepare(
+        "SELECT indexer, result_count FROM indexer_latency
+         WHERE success = 1 AND result_count IS NOT NULL AND timestamp > ?1
+         ORDER BY timestamp DESC, id DESC",
+    )?;
+    let mut counts: std::collections::HashMap<String, Vec<usize>> = Default::default();
+    let rows = stmt.query_map(params![since], |row| {
+        Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)?))
+    })?;
+    for row in rows {
+        let (indexer, count) = row?;
+        counts
+            .entry(indexer)
+            .or_default()
+            .push(count.max(0) as usize);
+    }
+    Ok(counts)
+}
+
+/// One indexer's answer to a search
+pub struct IndexerSample {
+    pub indexer: String,
+    pub timestamp: DateTime<Utc>,
+    pub success: bool,
+    pub result_count: usize,
+}
+
+/// Every indexer search since `since`, oldest first
+pub fn get_indexer_samples(
+    pool: &DbPool,
+    since: DateTime<Utc>,
+) -> anyhow::Result<Vec<IndexerSample>> {
+    let conn = pool.get()?;
+    let mut stmt = conn.prepare(
+        "SELECT indexer, timestamp, success, result_count FROM indexer_latency
+         WHERE timestamp > ?1 ORDER BY timestamp, id",
+    )?;
+    let samples = stmt
+        .query_map(params![since], |row| {
+            Ok(IndexerSample {
+                indexer: row.get(0)?,
+                timestamp: row.get(1)?,
+                success: row.get(2)?,
+                result_count: row.get::<_, Option<i64>>(3)?.unwrap_or(0).max(0) as usize,
+            })
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(samples)
+}
+
+/// Failed indexer searches grouped by indexer and kind
+#[derive(Serialize, Clone)]
+pub struct IndexerErrorStat {
+    pub indexer: String,
+    pub kind: crate::indexer::ErrorKind,
+    pub count: usize,
+    pub last_seen: DateTime<Utc>,
+}
+
+/// Failures per indexer and kind since `since`, most recent first
+pub fn get_indexer_errors(
+    pool: &DbPool,
+    since: DateTime<Utc>,
+) -> anyhow::Result<Vec<IndexerErrorStat>> {
+    let conn = pool.get()?;
+    let mut stmt = conn.prepare(
+        "SELECT indexer, COALESCE(error, 'other'), COUNT(*), MAX(timestamp) FROM indexer_latency
+         WHERE success = 0 AND timestamp > ?1
+         GROUP BY indexer, COALESCE(error, 'other') ORDER BY MAX(timestamp) DESC",
+    )?;
+    let stats = stmt
+        .query_map(params![since], |row| {
+            Ok(IndexerErrorStat {
+                indexer: row.get(0)?,
+                kind: crate::indexer::ErrorKind::parse(&row.get::<_, String>(1)?),
+                count: row.get::<_, i64>(2)? as usize,
+                last_seen: row.get(3)?,
+            })
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(stats)
+}
+
+/// Share of each indexer's searches since `since` that succeeded (0-1)
+pub fn get_indexer_success_rates(
+    pool: &DbPool,
+    since: DateTime<Utc>,
+) -> anyhow::Result<std::collections::HashMap<String, f64>> {
+    let conn = pool.get()?;
+    let mut stmt = conn.prepare(
+        "SELECT indexer, AVG(success) FROM indexer_latency WHERE timestamp > ?1 GROUP BY indexer",
+    )?;
+    let rates = stmt
+        .query_map(params![since], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, f64>(1)?))
+        })?
+        .collect::<Result<_, _>>()?;
+    Ok(rates)
+}
+
+/// An indexer's search record, see [`crate::indexer::failing`]
+#[derive(Serialize, Clone, Debug)]
+pub struct IndexerHealth {
+    pub indexer: String,
+    pub successes: u64,
+    pub failures: u64,
+    /// Failures since the last success, not counting rate limits
+    pub consecutive_failures: u32,
+    /// Mean response time of all searches
+    pub avg_ms: u64,
+    pub last_success: Option<DateTime<Utc>>,
+    pub last_failure: Option<DateTime<Utc>>,
+    pub last_error: Option<crate::indexer::ErrorKind>,
+    /// Skipped by aggregate searches right now
+    pub failing: bool,
+    /// When it was or will be tried again, once marked failing
+    pub failing_until: Option<DateTime<Utc>>,
+}
+
+/// Count one search in `indexer_health` and return the indexer's failures
+/// in a row. A success clears them along with any failing mark; a rate
+/// limit counts as a failure but not towards the run.
+pub fn record_indexer_health(
+    pool: &DbPool,
+    indexer: &str,
+    duration_ms: u128,
+    error: Option<crate::indexer::ErrorKind>,
+) -> anyhow::Result<u32> {
+    let conn = pool.get()?;
+    let now = Utc::now();
+    conn.execute(
+        "INSERT OR IGNORE INTO indexer_health (indexer) VALUES (?1)",
+        params![indexer],
+    )?;
+    match error {
+        None => conn.execute(
+            "UPDATE indexer_health SET successes = successes + 1, total_ms = total_ms + ?2,
+                 consecutive_failures = 0, failing_until = NULL, last_success = ?3
+             WHERE indexer = ?1",
+            params![indexer, duration_ms as i64, now],
+        )?,
+        Some(kind) => conn.execute(
+            "UPDATE indexer_health SET failures = failures + 1, total_ms = total_ms + ?2,
+                 consecutive_failures = consecutive_failures + ?3, last_failure = ?4,
+                 last_error = ?5
+             WHERE indexer = ?1",
+            params![
+                indexer,
+                duration_ms as i64,
+                kind != crate::indexer::ErrorKind::RateLimited,
+                now,
+                kind.as_str()
+            ],
+        )?,
+    };
+    let consecutive: i64 = conn.query_row(
+        "SELECT consecutive_failures FROM indexer_health WHERE indexer = ?1",
+        params![indexer],
+        |r| r.get(0),
+    )?;
+    Ok(consecutive as u32)
+}
+
+/// Mark an indexer failing until `until`, or clear the mark and its run of
+/// failures when `None`
+pub fn set_indexer_failing(
+    pool: &DbPool,
+    indexer: &str,
+    until: Option<DateTime<Utc>>,
+) -> anyhow::Result<()> {
+    let conn = pool.get()?;
+    match until {
+        Some(until) => conn.execute(
+            "UPDATE indexer_health SET failing_until = ?2 WHERE indexer = ?1",
+            params![indexer, until],
+        )?,
+        None => conn.execute(
+            "UPDATE indexer_health SET failing_until = NULL, consecutive_failures = 0
+             WHERE indexer = ?1",
+            params![indexer],
+        )?,
+    };
+    Ok(())
+}
+
+/// Every indexer's search record, failing ones first
+pub fn get_indexer_health(pool: &DbPool) -> anyhow::Result<Vec<IndexerHealth>> {
+    let conn = pool.get()?;
+    let now = Utc::now();
+    let mut stmt = conn.prepare(
+        "SELECT indexer, successes, failures, consecutive_failures, total_ms, last_success,
+                last_failure, last_error, failing_until
+         FROM indexer_health ORDER BY failing_until IS NULL, consecutive_failures DESC, indexer",
+    )?;
+    let health = stmt
+        .query_map([], |row| {
+            let successes: i64 = row.get(1)?;
+            let failures: i64 = row.get(2)?;
+            let total_ms: i64 = row.get(4)?;
+            let failing_until: Option<DateTime<Utc>> = row.get(8)?;
+            Ok(IndexerHealth {
+                indexer: row.get(0)?,
+                successes: successes as u64,
+                failures: failures as u64,
+                consecutive_failures: row.get::<_, i64>(3)? as u32,
+                avg_ms: (total_ms / (successes + failures).max(1)) as u64,
+                last_success: row.get(5)?,
+                last_failure: row.get(6)?,
+                last_error: row
+                    .get::<_, Option<String>>(7)?
+                    .map(|e| crate::indexer::ErrorKind::parse(&e)),
+                failing: failing_until.is_some_and(|until| until > now),
+                failing_until,
+            })
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(health)
+}
+
+/// Indexers whose failing mark hasn't run out yet, with its end
+pub fn get_failing_indexers(
+    pool: &DbPool,
+) -> anyhow::Result<std::collections::HashMap<String, DateTime<Utc>>> {
+    let conn = pool.get()?;
+    let mut stmt =
+        conn.prepare("SELECT indexer, failing_until FROM indexer_health WHERE failing_until > ?1")?;
+    let failing = stmt
+        .query_map(params![Utc::now()], |row| Ok((row.get(0)?, row.get(1)?)))?
+        .collect::<Result<_, _>>()?;
+    Ok(failing)
+}
+
+/// Durations (ms) of an indexer's most recent successful searches
+pub fn get_indexer_latencies(
+    pool: &DbPool,
+    indexer: &str,
+    limit: usize,
+) -> anyhow::Result<Vec<u64>> {
+    let conn = pool.get()?;
+    let mut stmt = conn.prepare(
+        "SELECT duration_ms FROM indexer_latency WHERE indexer = ?1 AND success = 1
+         ORDER BY timestamp DESC LIMIT ?2",
+    )?;
+    let durations = stmt
+        .query_map(params![indexer, limit as i64], |row| row.get::<_, i64>(0))?
+        .map(|d| d.map(|d| d.max(0) as u64))
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(durations)
+}
+
 pub fn get_recent_logs(pool: &DbPool, limit: usize) -> anyhow::Result<Vec<SearchLog>> {
     let conn = pool.get()?;
     let mut stmt = conn.prepare(
-        "SELECT query, indexer, timestamp, result_count FROM search_logs 
+        "SELECT query, indexer, timestamp, result_count, duration_ms FROM search_logs 
          ORDER BY timestamp DESC LIMIT ?",
     )?;
     let logs = stmt
-        .query_map([limit], |row| {
-            Ok(SearchLog {
-                query: row.get(0)?,
-                indexer: row.get(1)?,
-                timestamp: row.get(2)?,
-                result_count: row.get::<_, i64>(3)? as usize,
-            })
-        })?
+        .query_map([limit], search_log_row)?
         .collect::<Result<Vec<_>, _>>()?;
 
     Ok(logs)
 }
 
+/// Searches logged since `since`, oldest first
+pub fn get_search_logs_since(
+    pool: &DbPool,
+    since: DateTime<Utc>,
+) -> anyhow::Result<Vec<SearchLog>> {
+    let conn = pool.get()?;
+    let mut stmt = conn.prepare(
+        "SELECT query, indexer, timestamp, result_count, duration_ms FROM search_logs
+         WHERE timestamp > ?1 ORDER BY timestamp, id",
+    )?;
+    let logs = stmt
+        .query_map(params![since], search_log_row)?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(logs)
+}
+
+fn search_log_row(row: &rusqlite::Row) -> rusqlite::Result<SearchLog> {
+    Ok(SearchLog {
+        query: row.get(0)?,
+        indexer: row.get(1)?,
+        timestamp: row.get(2)?,
+        result_count: row.get::<_, i64>(3)?.max(0) as usize,
+        duration_ms: row.get::<_, i64>(4)?.max(0) as u64,
+    })
+}
+
 pub fn get_total_searches(pool: &DbPool) -> anyhow::Result<usize> {
     let conn = pool.get()?;
     let count: i64 = conn.query_row("SELECT COUNT(*) FROM search_logs", [], |r| r.get(0))?;
@@ -142,9 +645,19 @@ pub fn set_cached_results(
     key: &str,
     results: &str,
     ttl_hours: i64,
+) -> anyhow::Result<()> {
+    set_cached_results_for(pool, key, results, chrono::Duration::hours(ttl_hours))
+}
+
+/// Cache results with an arbitrary time-to-live
+pub fn set_cached_results_for(
+    pool: &DbPool,
+    key: &str,
+    results: &str,
+    ttl: chrono::Duration,
 ) -> anyhow::Result<()> {
     let conn = pool.get()?;
-    let expires_at = Utc::now() + chrono::Duration::hours(ttl_hours);
+    let expires_at = Utc::now() + ttl;
     conn.execute(
         "INSERT OR REPLACE INTO search_cache (key, results, expires_at) VALUES (?1, ?2, ?3)",
         params![key, results, expires_at],
@@ -152,6 +665,125 @@ pub fn set_cached_results(
     Ok(())
 }
 
+/// Get a cache entry and remove it, so it's served at most once
+pub fn take_cached_results(pool: &DbPool, key: &str) -> anyhow::Result<Option<String>> {
+    let res = get_cached_results(pool, key)?;
+    if res.is_some() {
+        let conn = pool.get()?;
+        conn.execute("DELETE FROM search_cache WHERE key = ?1", params![key])?;
+    }
+    Ok(res)
+}
+
+/// Date undated results by when they were first seen, so clients don't
+/// treat them as ancient
+pub fn fill_first_seen(pool: &DbPool, results: &mut [TorrentResult]) -> anyhow::Result<()> {
+    if results.iter().all(|r| r.publish_date.is_some()) {
+        return Ok(());
+    }
+    let mut conn = pool.get()?;
+    let tx = conn.transaction()?;
+    let now = Utc::now();
+    {
+        let mut upsert = tx.prepare_cached(
+            "INSERT INTO first_seen (guid, first_seen, last_seen) VALUES (?1, ?2, ?2)
+             ON CONFLICT(guid) DO UPDATE SET last_seen = excluded.last_seen
+             RETURNING first_seen",
+        )?;
+        for result in results.iter_mut().filter(|r| r.publish_date.is_none()) {
+            let seen: DateTime<Utc> = upsert.query_row(params![result.guid, now], |r| r.get(0))?;
+            result.publish_date = Some(seen);
+            result.date_source = Some(DateSource::FirstSeen);
+        }
+    }
+    tx.commit()?;
+    Ok(())
+}
+
+/// Remember where results can be downloaded from, so a GUID keeps
+/// resolving (`dl?guid=`) after the search cache has expired. Results
+/// without an indexer use `indexer`.
+pub fn register_guids(
+    pool: &DbPool,
+    indexer: Option<&str>,
+    results: &[TorrentResult],
+) -> anyhow::Result<()> {
+    let mut conn = pool.get()?;
+    let tx = conn.transaction()?;
+    let now = Utc::now();
+    {
+        let mut upsert = tx.prepare_cached(
+            "INSERT INTO guids (indexer, guid, link, magnet, info_hash, last_seen)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+             ON CONFLICT(indexer, guid) DO UPDATE SET link = excluded.link,
+                 magnet = excluded.magnet, info_hash = excluded.info_hash,
+                 last_seen = excluded.last_seen",
+        )?;
+        for result in results {
+            let Some(indexer) = result.indexer.as_deref().or(indexer) else {
+                continue;
+            };
+            if result.guid.is_empty() || (result.link.is_none() && result.magnet.is_none()) {
+                continue;
+            }
+            upsert.execute(params![
+                indexer,
+                result.guid,
+                result.link,
+                result.magnet,
+                result.info_hash,
+                now
+            ])?;
+        }
+    }
+    tx.commit()?;
+    Ok(())
+}
+
+/// A result's download location, as registered by [`register_guids`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct RegisteredGuid {
+    pub indexer: String,
+    pub link: Option<String>,
+    pub magnet: Option<String>,
+    pub info_hash: Option<String>,
+}
+
+/// Look up a GUID, from any indexer when `indexer` is `None` (the most
+/// recently seen wins)
+pub fn lookup_guid(
+    pool: &DbPool,
+    indexer: Option<&str>,
+    guid: &str,
+) -> anyhow::Result<Option<RegisteredGuid>> {
+    let conn = pool.get()?;
+    Ok(conn
+        .query_row(
+            "SELECT indexer, link, magnet, info_hash FROM guids
+             WHERE guid = ?1 AND (?2 IS NULL OR indexer = ?2)
+             ORDER BY last_seen DESC LIMIT 1",
+            params![guid, indexer],
+            |row| {
+                Ok(RegisteredGuid {
+                    indexer: row.get(0)?,
+                    link: row.get(1)?,
+                    magnet: row.get(2)?,
+                    info_hash: row.get(3)?,
+                })
+            },
+        )
+        .optional()?)
+}
+
+/// Keep a grabbed result resolvable however old it gets
+pub fn pin_guid(pool: &DbPool, indexer: &str, guid: &str) -> anyhow::Result<bool> {
+    let conn = pool.get()?;
+    Ok(conn.execute(
+        "UPDATE guids SET pinned = 1 WHERE indexer = ?1 AND guid = ?2",
+        params![indexer, guid],
+    )? > 0)
+}
+
 pub fn cleanup_cache(pool: &DbPool) -> anyhow::Result<()> {
     let conn = pool.get()?;
     conn.execute(
@@ -175,19 +807,15 @@ pub fn clear_search_logs(pool: &DbPool) -> anyhow::Result<usize> {
     Ok(deleted)
 }
 
-/// Clear all cached searches
-pub fn clear_cache(pool: &DbPool) -> anyhow::Result<usize> {
-    let conn = pool.get()?;
-    let deleted = conn.execute("DELETE FROM search_cache", [])?;
-    Ok(deleted)
-}
-
 #[derive(Serialize, Deserialize, Clone)]
 pub struct SearchLog {
     pub query: String,
     pub indexer: String,
     pub timestamp: DateTime<Utc>,
     pub result_count: usize,
+    /// 0 in Redis history written before durations were kept
+    #[serde(default)]
+    pub duration_ms: u64,
 }
 
 /// Represents a cached search entry
@@ -204,7 +832,8 @@ pub struct CachedSearch {
 pub fn get_cached_search_list(pool: &DbPool) -> anyhow::Result<Vec<CachedSearch>> {
     let conn = pool.get()?;
     let mut stmt = conn.prepare(
-        "SELECT key, results, expires_at FROM search_cache WHERE expires_at > ?1 ORDER BY expires_at DESC",
+        "SELECT key, results, expires_at FROM search_cache
+         WHERE expires_at > ?1 AND key NOT LIKE 'partial:%' AND key NOT LIKE 'rss:%' ORDER BY expires_at DESC",
     )?;
 
     let rows = stmt.query_map(params![Utc::now()], |row| {
@@ -217,45 +846,41 @@ pub fn get_cached_search_list(pool: &DbPool) -> anyhow::Result<Vec<CachedSearch>
     let mut searches = Vec::new();
     for row in rows {
         let (key, results_json, expires_at) = row?;
+        searches.push(CachedSearch::new(key, &results_json, expires_at));
+    }
 
-        // Parse key to extract query and indexer
-        // Format: "proxied:indexer:query:category" or "native:indexer:query:..."
-        let parts: Vec<&str> = key.split(':').collect();
-        let (indexer, query) = if parts.len() >= 3 {
-            (parts[1].to_string(), parts[2].to_string())
-        } else {
-            ("unknown".to_string(), key.clone())
-        };
+    Ok(searches)
+}
+
+impl CachedSearch {
+    /// Describe one cache entry
+    pub fn new(key: String, results_json: &str, expires_at: DateTime<Utc>) -> Self {
+        let (indexer, query) = describe_cache_key(&key);
 
         // Count results from JSON
-        let result_count = serde_json::from_str::<Vec<serde_json::Value>>(&results_json)
+        let result_count = serde_json::from_str::<Vec<serde_json::Value>>(results_json)
             .map(|v| v.len())
             .unwrap_or(0);
 
-        searches.push(CachedSearch {
+        CachedSearch {
             cache_key: key,
             query,
             indexer,
             expires_at,
             result_count,
-        });
+        }
     }
-
-    Ok(searches)
 }
 
-/// Get cached results by key (returns raw JSON string)
-pub fn get_cached_results_by_key(pool: &DbPool, key: &str) -> anyhow::Result<Option<String>> {
-    let conn = pool.get()?;
-    let res: Option<String> = conn
-        .query_row(
-            "SELECT results FROM search_cache WHERE key = ?1 AND expires_at > ?2",
-            params![key, Utc::now()],
-            |r| r.get(0),
-        )
-        .optional()?;
-
-    Ok(res)
+/// Indexer and query of a web search cache key
+/// ("proxied:indexer:query:category" or "native:indexer:query:...")
+pub fn describe_cache_key(key: &str) -> (String, String) {
+    let parts: Vec<&str> = key.split(':').collect();
+    if parts.len() >= 3 {
+        (parts[1].to_string(), parts[2].to_string())
+    } else {
+        ("unknown".to_string(), key.to_string())
+    }
 }
 
 /// Record of a download sent to a client or saved to server
@@ -338,3 +963,1287 @@ pub fn clear_download_logs(pool: &DbPool) -> anyhow::Result<usize> {
     let deleted = conn.execute("DELETE FROM download_logs", [])?;
     Ok(deleted)
 }
+
+/// Record a TUI search query (moves it to the top if already present)
+pub fn add_tui_history(pool: &DbPool, query: &str) -> anyhow::Result<()> {
+    let conn = pool.get()?;
+    conn.execute(
+        "INSERT OR REPLACE INTO tui_history (query, last_used) VALUES (?1, ?2)",
+        params![query, Utc::now()],
+    )?;
+    Ok(())
+}
+
+/// Get TUI search queries, most recent first
+pub fn get_tui_history(pool: &DbPool, limit: usize) -> anyhow::Result<Vec<String>> {
+    let conn = pool.get()?;
+    let mut stmt = conn.prepare("SELECT query FROM tui_history ORDER BY last_used DESC LIMIT ?")?;
+    let queries = stmt
+        .query_map([limit], |row| row.get(0))?
+        .collect::<Result<Vec<String>, _>>()?;
+    Ok(queries)
+}
+
+/// A search kept for its RSS feed (`/feeds/saved/{id}.rss`)
+#[derive(Serialize, Deserialize, Clone)]
+pub struct SavedSearch {
+    pub id: i64,
+    pub name: String,
+    pub query: String,
+    /// Native or proxied indexer to search; all enabled indexers if `None`
+    pub indexer: Option<String>,
+    /// Comma-separated Torznab categories
+    pub category: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Save a search, returning its id
+pub fn add_saved_search(
+    pool: &DbPool,
+    name: &str,
+    query: &str,
+    indexer: Option<&str>,
+    category: Option<&str>,
+) -> anyhow::Result<i64> {
+    let conn = pool.get()?;
+    conn.execute(
+        "INSERT INTO saved_searches (name, query, indexer, category, created_at)
+         VALUES (?1, ?2, ?3, ?4, ?5)",
+        params![name, query, indexer, category, Utc::now()],
+    )?;
+    Ok(conn.last_insert_rowid())
+}
+
+fn saved_search_from_row(row: &rusqlite::Row) -> rusqlite::Result<SavedSearch> {
+    Ok(SavedSearch {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        query: row.get(2)?,
+        indexer: row.get(3)?,
+        category: row.get(4)?,
+        created_at: row.get(5)?,
+    })
+}
+
+pub fn get_saved_searches(pool: &DbPool) -> anyhow::Result<Vec<SavedSearch>> {
+    let conn = pool.get()?;
+    let mut stmt = conn.prepare(
+        "SELECT id, name, query, indexer, category, created_at FROM saved_searches ORDER BY id",
+    )?;
+    let searches = stmt
+        .query_map([], saved_search_from_row)?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(searches)
+}
+
+pub fn get_saved_search(pool: &DbPool, id: i64) -> anyhow::Result<Option<SavedSearch>> {
+    let conn = pool.get()?;
+    let search = conn
+        .query_row(
+            "SELECT id, name, query, indexer, category, created_at FROM saved_searches WHERE id = ?1",
+            [id],
+            saved_search_from_row,
+        )
+        .optional()?;
+    Ok(search)
+}
+
+/// Returns whether the search existed
+pub fn delete_saved_search(pool: &DbPool, id: i64) -> anyhow::Result<bool> {
+    let conn = pool.get()?;
+    Ok(conn.execute("DELETE FROM saved_searches WHERE id = ?1", [id])? > 0)
+}
+
+/// A search run on a schedule by the server, whose new matches are kept
+/// and optionally sent to a download client (`/api/rss/rules`)
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct RssRule {
+    pub id: i64,
+    pub name: String,
+    pub query: String,
+    /// Indexer to search; all enabled indexers if `None`
+    pub indexer: Option<String>,
+    /// Comma-separated Torznab categories, searched and required of matches
+    pub category: Option<String>,
+    /// Regex the title must match (case-insensitive)
+    pub title_pattern: Option<String>,
+    pub min_seeders: Option<u32>,
+    /// Size bounds in bytes
+    pub min_size: Option<u64>,
+    pub max_size: Option<u64>,
+    /// Download client new matches are sent to; kept as pending if `None`
+    pub client_id: Option<String>,
+    pub interval_minutes: u32,
+    pub enabled: bool,
+    pub last_run: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+const RSS_RULE_COLUMNS: &str = "id, name, query, indexer, category, title_pattern, min_seeders,
+    min_size, max_size, client_id, interval_minutes, enabled, last_run, created_at";
+
+fn rss_rule_from_row(row: &rusqlite::Row) -> rusqlite::Result<RssRule> {
+    Ok(RssRule {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        query: row.get(2)?,
+        indexer: row.get(3)?,
+        category: row.get(4)?,
+        title_pattern: row.get(5)?,
+        min_seeders: row.get(6)?,
+        min_size: row.get::<_, Option<i64>>(7)?.map(|s| s as u64),
+        max_size: row.get::<_, Option<i64>>(8)?.map(|s| s as u64),
+        client_id: row.get(9)?,
+        interval_minutes: row.get(10)?,
+        enabled: row.get(11)?,
+        last_run: row.get(12)?,
+        created_at: row.get(13)?,
+    })
+}
+
+/// Add a rule, returning its id (`id`, `last_run` and `created_at` are
+/// ignored)
+pub fn add_rss_rule(pool: &DbPool, rule: &RssRule) -> anyhow::Result<i64> {
+    let conn = pool.get()?;
+    conn.execute(
+        "INSERT INTO rss_rules (name, query, indexer, category, title_pattern, min_seeders,
+             min_size, max_size, client_id, interval_minutes, enabled, created_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)",
+        params![
+            rule.name,
+            rule.query,
+            rule.indexer,
+            rule.category,
+            rule.title_pattern,
+            rule.min_seeders,
+            rule.min_size.map(|s| s as i64),
+            rule.max_size.map(|s| s as i64),
+            rule.client_id,
+            rule.interval_minutes,
+            rule.enabled,
+            Utc::now()
+        ],
+    )?;
+    Ok(conn.last_insert_rowid())
+}
+
+/// Replace a rule's settings, returning whether it exists
+pub fn update_rss_rule(pool: &DbPool, rule: &RssRule) -> anyhow::Result<bool> {
+    let conn = pool.get()?;
+    let updated = conn.execute(
+        "UPDATE rss_rules SET name = ?2, query = ?3, indexer = ?4, category = ?5,
+             title_pattern = ?6, min_seeders = ?7, min_size = ?8, max_size = ?9,
+             client_id = ?10, interval_minutes = ?11, enabled = ?12
+         WHERE id = ?1",
+        params![
+            rule.id,
+            rule.name,
+            rule.query,
+            rule.indexer,
+            rule.category,
+            rule.title_pattern,
+            rule.min_seeders,
+            rule.min_size.map(|s| s as i64),
+            rule.max_size.map(|s| s as i64),
+            rule.client_id,
+            rule.interval_minutes,
+            rule.enabled
+        ],
+    )?;
+    Ok(updated > 0)
+}
+
+pub fn get_rss_rules(pool: &DbPool) -> anyhow::Result<Vec<RssRule>> {
+    let conn = pool.get()?;
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM rss_rules ORDER BY id",
+        RSS_RULE_COLUMNS
+    ))?;
+    let rules = stmt
+        .query_map([], rss_rule_from_row)?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(rules)
+}
+
+pub fn get_rss_rule(pool: &DbPool, id: i64) -> anyhow::Result<Option<RssRule>> {
+    let conn = pool.get()?;
+    let rule = conn
+        .query_row(
+            &format!("SELECT {} FROM rss_rules WHERE id = ?1", RSS_RULE_COLUMNS),
+            [id],
+            rss_rule_from_row,
+        )
+        .optional()?;
+    Ok(rule)
+}
+
+/// Delete a rule and its matches, returning whether it existed
+pub fn delete_rss_rule(pool: &DbPool, id: i64) -> anyhow::Result<bool> {
+    let conn = pool.get()?;
+    conn.execute("DELETE FROM rss_matches WHERE rule_id = ?1", [id])?;
+    Ok(conn.execute("DELETE FROM rss_rules WHERE id = ?1", [id])? > 0)
+}
+
+pub fn set_rss_rule_run(pool: &DbPool, id: i64, at: DateTime<Utc>) -> anyhow::Result<()> {
+    let conn = pool.get()?;
+    conn.execute(
+        "UPDATE rss_rules SET last_run = ?2 WHERE id = ?1",
+        params![id, at],
+    )?;
+    Ok(())
+}
+
+/// What happened to a rule's match
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum MatchStatus {
+    /// Already listed when the rule first ran, so never grabbed
+    Seen,
+    /// Waiting to be grabbed by hand
+    Pending,
+    /// Sent to the rule's download client
+    Sent,
+    /// Sending to the download client failed
+    Failed,
+}
+
+impl MatchStatus {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Seen => "seen",
+            Self::Pending => "pending",
+            Self::Sent => "sent",
+            Self::Failed => "failed",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "seen" => Some(Self::Seen),
+            "pending" => Some(Self::Pending),
+            "sent" => Some(Self::Sent),
+            "failed" => Some(Self::Failed),
+            _ => None,
+        }
+    }
+}
+
+/// A result found by an RSS rule
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct RssMatch {
+    pub id: i64,
+    pub rule_id: i64,
+    pub guid: String,
+    pub title: String,
+    pub indexer: Option<String>,
+    pub link: Option<String>,
+    pub magnet: Option<String>,
+    pub size: Option<u64>,
+    pub seeders: Option<u32>,
+    pub status: MatchStatus,
+    /// Why sending failed
+    pub error: Option<String>,
+    /// Times it was sent to the rule's client
+    pub attempts: u32,
+    pub found_at: DateTime<Utc>,
+}
+
+impl RssMatch {
+    /// The result as far as it was kept
+    pub fn to_result(&self) -> TorrentResult {
+        TorrentResult {
+            guid: self.guid.clone(),
+            title: self.title.clone(),
+            indexer: self.indexer.clone(),
+            link: self.link.clone(),
+            magnet: self.magnet.clone(),
+            size: self.size,
+            seeders: self.seeders,
+            ..Default::default()
+        }
+    }
+}
+
+/// Keep a rule's match, returning whether it is new to the rule. A match
+/// seen before only has its `last_seen` moved on.
+pub fn add_rss_match(
+    pool: &DbPool,
+    rule_id: i64,
+    result: &TorrentResult,
+    status: MatchStatus,
+) -> anyhow::Result<bool> {
+    let conn = pool.get()?;
+    let now = Utc::now();
+    let updated = conn.execute(
+        "UPDATE rss_matches SET last_seen = ?3 WHERE rule_id = ?1 AND guid = ?2",
+        params![rule_id, result.guid, now],
+    )?;
+    if updated > 0 {
+        return Ok(false);
+    }
+    conn.execute(
+        "INSERT INTO rss_matches
+             (rule_id, guid, title, indexer, link, magnet, size, seeders, status, found_at,
+              last_seen)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?10)",
+        params![
+            rule_id,
+            result.guid,
+            result.title,
+            result.indexer,
+            result.link,
+            result.magnet,
+            result.size.map(|s| s as i64),
+            result.seeders,
+            status.as_str(),
+            now
+        ],
+    )?;
+    Ok(true)
+}
+
+/// Record the outcome of sending a match to the rule's client
+pub fn set_rss_match_status(
+    pool: &DbPool,
+    rule_id: i64,
+    guid: &str,
+    status: MatchStatus,
+    error: Option<&str>,
+) -> anyhow::Result<()> {
+    let conn = pool.get()?;
+    conn.execute(
+        "UPDATE rss_matches SET status = ?3, error = ?4, attempts = attempts + 1
+         WHERE rule_id = ?1 AND guid = ?2",
+        params![rule_id, guid, status.as_str(), error],
+    )?;
+    Ok(())
+}
+
+/// A rule's failed matches sent fewer than `max_attempts` times, oldest first
+pub fn get_rss_retries(
+    pool: &DbPool,
+    rule_id: i64,
+    max_attempts: u32,
+) -> anyhow::Result<Vec<RssMatch>> {
+    let conn = pool.get()?;
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM rss_matches WHERE rule_id = ?1 AND status = ?2 AND attempts < ?3
+         ORDER BY found_at, id",
+        RSS_MATCH_COLUMNS
+    ))?;
+    let matches = stmt
+        .query_map(
+            params![rule_id, MatchStatus::Failed.as_str(), max_attempts],
+            rss_match_from_row,
+        )?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(matches)
+}
+
+/// Delete matches no rule has listed since `before`, returning how many
+pub fn prune_rss_matches(pool: &DbPool, before: DateTime<Utc>) -> anyhow::Result<usize> {
+    let conn = pool.get()?;
+    Ok(conn.execute(
+        "DELETE FROM rss_matches WHERE COALESCE(last_seen, found_at) < ?1",
+        params![before],
+    )?)
+}
+
+/// Matches of every rule, newest first, optionally only those with `status`
+pub fn get_rss_matches(
+    pool: &DbPool,
+    status: Option<MatchStatus>,
+    limit: usize,
+) -> anyhow::Result<Vec<RssMatch>> {
+    let conn = pool.get()?;
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM rss_matches WHERE ?1 IS NULL OR status = ?1
+         ORDER BY found_at DESC, id DESC LIMIT ?2",
+        RSS_MATCH_COLUMNS
+    ))?;
+    let matches = stmt
+        .query_map(
+            params![status.map(MatchStatus::as_str), limit],
+            rss_match_from_row,
+        )?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(matches)
+}
+
+const RSS_MATCH_COLUMNS: &str = "id, rule_id, guid, title, indexer, link, magnet, size, seeders,
+    status, error, attempts, found_at";
+
+fn rss_match_from_row(row: &rusqlite::Row) -> rusqlite::Result<RssMatch> {
+    let status: String = row.get(9)?;
+    Ok(RssMatch {
+        id: row.get(0)?,
+        rule_id: row.get(1)?,
+        guid: row.get(2)?,
+        title: row.get(3)?,
+        indexer: row.get(4)?,
+        link: row.get(5)?,
+        magnet: row.get(6)?,
+        size: row.get::<_, Option<i64>>(7)?.map(|s| s as u64),
+        seeders: row.get(8)?,
+        status: MatchStatus::parse(&status).unwrap_or(MatchStatus::Pending),
+        error: row.get(10)?,
+        attempts: row.get(11)?,
+        found_at: row.get(12)?,
+    })
+}
+
+/// Raw responses kept before the oldest are dropped
+const RAW_RESPONSES_KEPT: i64 = 50;
+
+/// Search page that gave no results, stored with `raw_responses = true`
+#[derive(Serialize, Clone)]
+pub struct RawResponse {
+    pub indexer: String,
+    pub url: String,
+    pub status: u16,
+    pub body: String,
+    pub timestamp: DateTime<Utc>,
+}
+
+/// Store a search page under the history key of the search that fetched it,
+/// dropping the oldest beyond the last [`RAW_RESPONSES_KEPT`]
+pub fn add_raw_response(
+    pool: &DbPool,
+    search_key: &str,
+    indexer: &str,
+    url: &str,
+    status: u16,
+    body: &str,
+) -> anyhow::Result<()> {
+    let conn = pool.get()?;
+    conn.execute(
+        "INSERT INTO raw_responses (search_key, indexer, url, status, body, timestamp)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        params![search_key, indexer, url, status, body, Utc::now()],
+    )?;
+    conn.execute(
+        "DELETE FROM raw_responses WHERE id NOT IN
+         (SELECT id FROM raw_responses ORDER BY id DESC LIMIT ?1)",
+        [RAW_RESPONSES_KEPT],
+    )?;
+    Ok(())
+}
+
+/// Stored pages for a search, oldest first
+pub fn get_raw_responses(pool: &DbPool, search_key: &str) -> anyhow::Result<Vec<RawResponse>> {
+    let conn = pool.get()?;
+    let mut stmt = conn.prepare(
+        "SELECT indexer, url, status, body, timestamp FROM raw_responses
+         WHERE search_key = ?1 ORDER BY id",
+    )?;
+    let responses = stmt
+        .query_map([search_key], |row| {
+            Ok(RawResponse {
+                indexer: row.get(0)?,
+                url: row.get(1)?,
+                status: row.get(2)?,
+                body: row.get(3)?,
+                timestamp: row.get(4)?,
+            })
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(responses)
+}
+
+/// Searches with stored pages and when the latest was stored, newest first
+pub fn get_raw_response_keys(pool: &DbPool) -> anyhow::Result<Vec<(String, DateTime<Utc>)>> {
+    let conn = pool.get()?;
+    let mut stmt = conn.prepare(
+        "SELECT search_key, MAX(timestamp) FROM raw_responses
+         GROUP BY search_key ORDER BY MAX(timestamp) DESC",
+    )?;
+    let keys = stmt
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(keys)
+}
+
+/// A .torrent file found in the download path
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct LibraryEntry {
+    pub path: String,
+    /// Torrent name from its info dictionary
+    pub name: String,
+    pub info_hash: String,
+    pub size: u64,
+    pub indexed_at: DateTime<Utc>,
+}
+
+/// Modification time (Unix seconds) of every indexed file, by path
+pub fn get_library_mtimes(pool: &DbPool) -> anyhow::Result<std::collections::HashMap<String, i64>> {
+    let conn = pool.get()?;
+    let mut stmt = conn.prepare("SELECT path, modified FROM library")?;
+    let mtimes = stmt
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+        .collect::<Result<std::collections::HashMap<_, _>, _>>()?;
+    Ok(mtimes)
+}
+
+/// Add or refresh a library file
+pub fn upsert_library_entry(
+    pool: &DbPool,
+    entry: &LibraryEntry,
+    modified: i64,
+) -> anyhow::Result<()> {
+    let conn = pool.get()?;
+    conn.execute(
+        "INSERT OR REPLACE INTO library (path, name, info_hash, size, modified, indexed_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        params![
+            entry.path,
+            entry.name,
+            entry.info_hash,
+            entry.size as i64,
+            modified,
+            entry.indexed_at
+        ],
+    )?;
+    Ok(())
+}
+
+pub fn remove_library_entry(pool: &DbPool, path: &str) -> anyhow::Result<()> {
+    let conn = pool.get()?;
+    conn.execute("DELETE FROM library WHERE path = ?1", [path])?;
+    Ok(())
+}
+
+/// Library files whose name contains every word of `query`, newest first
+pub fn search_library(
+    pool: &DbPool,
+    query: &str,
+    limit: usize,
+) -> anyhow::Result<Vec<LibraryEntry>> {
+    let words: Vec<String> = query.split_whitespace().map(str::to_lowercase).collect();
+    let conn = pool.get()?;
+    let mut stmt = conn.prepare(
+        "SELECT path, name, info_hash, size, indexed_at FROM library ORDER BY indexed_at DESC",
+    )?;
+    let entries = stmt
+        .query_map([], |row| {
+            Ok(LibraryEntry {
+                path: row.get(0)?,
+                name: row.get(1)?,
+                info_hash: row.get(2)?,
+                size: row.get::<_, i64>(3)? as u64,
+                indexed_at: row.get(4)?,
+            })
+        })?
+        .filter_map(Result::ok)
+        .filter(|e| {
+            let name = e.name.to_lowercase();
+            words.iter().all(|w| name.contains(w.as_str()))
+        })
+        .take(limit)
+        .collect();
+    Ok(entries)
+}
+
+/// Info hashes and lower-cased names of every library file
+pub fn get_library_keys(
+    pool: &DbPool,
+) -> anyhow::Result<(
+    std::collections::HashSet<String>,
+    std::collections::HashSet<String>,
+)> {
+    let conn = pool.get()?;
+    let mut stmt = conn.prepare("SELECT info_hash, name FROM library")?;
+    let mut hashes = std::collections::HashSet::new();
+    let mut names = std::collections::HashSet::new();
+    for row in stmt.query_map([], |row| {
+        Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+    })? {
+        let (hash, name) = row?;
+        hashes.insert(hash);
+        names.insert(name.to_lowercase());
+    }
+    Ok((hashes, names))
+}
+
+/// Record bytes received from an indexer. `kind` is `search` or `download`.
+pub fn log_indexer_traffic(
+    pool: &DbPool,
+    indexer: &str,
+    kind: &str,
+    bytes: u64,
+) -> anyhow::Result<()> {
+    if bytes == 0 {
+        return Ok(());
+    }
+    let conn = pool.get()?;
+    conn.execute(
+        "INSERT INTO indexer_traffic (indexer, kind, bytes, timestamp) VALUES (?1, ?2, ?3, ?4)",
+        params![indexer, kind, bytes as i64, Utc::now()],
+    )?;
+    Ok(())
+}
+
+/// Bytes an indexer sent since some time, split by search and download
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct IndexerTraffic {
+    pub indexer: String,
+    pub search_bytes: u64,
+    pub download_bytes: u64,
+    pub total_bytes: u64,
+}
+
+/// The indexers that sent the most bytes since `since`, biggest first
+pub fn get_top_traffic(
+    pool: &DbPool,
+    since: DateTime<Utc>,
+    limit: usize,
+) -> anyhow::Result<Vec<IndexerTraffic>> {
+    let conn = pool.get()?;
+    let mut stmt = conn.prepare(
+        "SELECT indexer,
+                SUM(CASE WHEN kind = 'search' THEN bytes ELSE 0 END),
+                SUM(CASE WHEN kind = 'download' THEN bytes ELSE 0 END),
+                SUM(bytes) AS total
+         FROM indexer_traffic
+         WHERE timestamp >= ?1
+         GROUP BY indexer
+         ORDER BY total DESC
+         LIMIT ?2",
+    )?;
+    let traffic = stmt
+        .query_map(params![since, limit as i64], |row| {
+            Ok(IndexerTraffic {
+                indexer: row.get(0)?,
+                search_bytes: row.get::<_, i64>(1)? as u64,
+                download_bytes: row.get::<_, i64>(2)? as u64,
+                total_bytes: row.get::<_, i64>(3)? as u64,
+            })
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(traffic)
+}
+
+/// Record a web UI session, keyed by a hash of its token. Expired sessions
+/// are dropped at the same time.
+pub fn create_session(
+    pool: &DbPool,
+    token_hash: &str,
+    username: &str,
+    expires_at: DateTime<Utc>,
+) -> anyhow::Result<()> {
+    let conn = pool.get()?;
+    let now = Utc::now();
+    conn.execute("DELETE FROM sessions WHERE expires_at < ?1", params![now])?;
+    conn.execute(
+        "INSERT INTO sessions (token_hash, username, created_at, expires_at)
+         VALUES (?1, ?2, ?3, ?4)",
+        params![token_hash, username, now, expires_at],
+    )?;
+    Ok(())
+}
+
+/// The user a session belongs to, if it exists and hasn't expired
+pub fn get_session_user(pool: &DbPool, token_hash: &str) -> anyhow::Result<Option<String>> {
+    let conn = pool.get()?;
+    let user = conn
+        .query_row(
+            "SELECT username FROM sessions WHERE token_hash = ?1 AND expires_at > ?2",
+            params![token_hash, Utc::now()],
+            |row| row.get(0),
+        )
+        .optional()?;
+    Ok(user)
+}
+
+pub fn delete_session(pool: &DbPool, token_hash: &str) -> anyhow::Result<()> {
+    let conn = pool.get()?;
+    conn.execute("DELETE FROM sessions WHERE token_hash = ?1", [token_hash])?;
+    Ok(())
+}
+
+/// End every web UI session, e.g. after a password change
+pub fn clear_sessions(pool: &DbPool) -> anyhow::Result<usize> {
+    let conn = pool.get()?;
+    Ok(conn.execute("DELETE FROM sessions", [])?)
+}
+
+/// Tables managed by Lodestarr
+pub const TABLES: [&str; 15] = [
+    "search_logs",
+    "search_cache",
+    "download_logs",
+    "tui_history",
+    "indexer_latency",
+    "first_seen",
+    "saved_searches",
+    "raw_responses",
+    "library",
+    "sessions",
+    "indexer_traffic",
+    "guids",
+    "rss_rules",
+    "rss_matches",
+    "indexer_health",
+];
+
+/// Row count and on-disk size of a table
+#[derive(Serialize, Clone)]
+pub struct TableStats {
+    pub name: String,
+    pub rows: usize,
+    /// Bytes used by the table, if SQLite was built with `dbstat`
+    pub bytes: Option<u64>,
+}
+
+/// Summary for `lodestarr db stats`
+#[derive(Serialize, Clone)]
+pub struct DbStats {
+    pub tables: Vec<TableStats>,
+    /// Database size in bytes (page count * page size)
+    pub size: u64,
+    /// Bytes on free pages that `VACUUM` would reclaim
+    pub free: u64,
+    pub cache_valid: usize,
+    pub cache_expired: usize,
+}
+
+pub fn get_db_stats(pool: &DbPool) -> anyhow::Result<DbStats> {
+    let conn = pool.get()?;
+
+    let mut tables = Vec::new();
+    for name in TABLES {
+        let rows: i64 =
+            conn.query_row(&format!("SELECT COUNT(*) FROM {}", name), [], |r| r.get(0))?;
+        let bytes: Option<i64> = conn
+            .query_row(
+                "SELECT SUM(pgsize) FROM dbstat WHERE name = ?1",
+                [name],
+                |r| r.get(0),
+            )
+            .ok()
+            .flatten();
+        tables.push(TableStats {
+            name: name.to_string(),
+            rows: rows as usize,
+            bytes: bytes.map(|b| b as u64),
+        });
+    }
+
+    let page_size: i64 = conn.query_row("PRAGMA page_size", [], |r| r.get(0))?;
+    let page_count: i64 = conn.query_row("PRAGMA page_count", [], |r| r.get(0))?;
+    let free_pages: i64 = conn.query_row("PRAGMA freelist_count", [], |r| r.get(0))?;
+    let (cache_valid, cache_expired): (i64, i64) = conn.query_row(
+        "SELECT COUNT(*) FILTER (WHERE expires_at > ?1), COUNT(*) FILTER (WHERE expires_at <= ?1)
+         FROM search_cache",
+        params![Utc::now()],
+        |r| Ok((r.get(0)?, r.get(1)?)),
+    )?;
+
+    Ok(DbStats {
+        tables,
+        size: (page_size * page_count) as u64,
+        free: (page_size * free_pages) as u64,
+        cache_valid: cache_valid as usize,
+        cache_expired: cache_expired as usize,
+    })
+}
+
+/// Rebuild the database file, reclaiming free pages
+pub fn vacuum(pool: &DbPool) -> anyhow::Result<()> {
+    let conn = pool.get()?;
+    conn.execute_batch("VACUUM")?;
+    Ok(())
+}
+
+/// Rows removed by [`prune`]
+#[derive(Serialize, Clone, Default)]
+pub struct PruneCounts {
+    pub expired_cache: usize,
+    pub search_logs: usize,
+    pub download_logs: usize,
+    pub indexer_latency: usize,
+    pub indexer_traffic: usize,
+    pub first_seen: usize,
+    pub guids: usize,
+    pub rss_matches: usize,
+}
+
+/// Delete expired cache entries and history older than `days`
+pub fn prune(pool: &DbPool, days: i64) -> anyhow::Result<PruneCounts> {
+    let cutoff = Utc::now() - chrono::Duration::days(days);
+    let rss_matches = prune_rss_matches(pool, cutoff)?;
+    let conn = pool.get()?;
+    Ok(PruneCounts {
+        expired_cache: conn.execute(
+            "DELETE FROM search_cache WHERE expires_at < ?1",
+            params![Utc::now()],
+        )?,
+        search_logs: conn.execute(
+            "DELETE FROM search_logs WHERE timestamp < ?1",
+            params![cutoff],
+        )?,
+        download_logs: conn.execute(
+            "DELETE FROM download_logs WHERE timestamp < ?1",
+            params![cutoff],
+        )?,
+        indexer_latency: conn.execute(
+            "DELETE FROM indexer_latency WHERE timestamp < ?1",
+            params![cutoff],
+        )?,
+        indexer_traffic: conn.execute(
+            "DELETE FROM indexer_traffic WHERE timestamp < ?1",
+            params![cutoff],
+        )?,
+        // Only results no longer listed, so live ones keep their date
+        first_seen: conn.execute(
+            "DELETE FROM first_seen WHERE last_seen < ?1",
+            params![cutoff],
+        )?,
+        // Grabbed results stay resolvable
+        guids: conn.execute(
+            "DELETE FROM guids WHERE last_seen < ?1 AND pinned = 0",
+            params![cutoff],
+        )?,
+        rss_matches,
+    })
+}
+
+/// Search log row as exported by `lodestarr db export`
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ExportedSearch {
+    pub query: String,
+    pub indexer: String,
+    pub timestamp: DateTime<Utc>,
+    pub result_count: usize,
+    #[serde(default)]
+    pub duration_ms: i64,
+}
+
+/// TUI history row as exported by `lodestarr db export`
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ExportedQuery {
+    pub query: String,
+    pub last_used: DateTime<Utc>,
+}
+
+/// Search and download history (the search cache is not exported)
+#[derive(Serialize, Deserialize, Clone, Default)]
+pub struct HistoryExport {
+    #[serde(default)]
+    pub searches: Vec<ExportedSearch>,
+    #[serde(default)]
+    pub downloads: Vec<DownloadLog>,
+    #[serde(default)]
+    pub tui_history: Vec<ExportedQuery>,
+}
+
+pub fn export_history(pool: &DbPool) -> anyhow::Result<HistoryExport> {
+    let conn = pool.get()?;
+
+    let searches = conn
+        .prepare(
+            "SELECT query, indexer, timestamp, result_count, duration_ms FROM search_logs
+             ORDER BY timestamp",
+        )?
+        .query_map([], |row| {
+            Ok(ExportedSearch {
+                query: row.get(0)?,
+                indexer: row.get(1)?,
+                timestamp: row.get(2)?,
+                result_count: row.get::<_, i64>(3)? as usize,
+                duration_ms: row.get(4)?,
+            })
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let downloads = conn
+        .prepare(
+            "SELECT id, title, magnet, download_link, client_name, download_type, timestamp
+             FROM download_logs ORDER BY timestamp",
+        )?
+        .query_map([], |row| {
+            Ok(DownloadLog {
+                id: row.get(0)?,
+                title: row.get(1)?,
+                magnet: row.get(2)?,
+                download_link: row.get(3)?,
+                client_name: row.get(4)?,
+                download_type: row.get(5)?,
+                timestamp: row.get(6)?,
+            })
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let tui_history = conn
+        .prepare("SELECT query, last_used FROM tui_history ORDER BY last_used")?
+        .query_map([], |row| {
+            Ok(ExportedQuery {
+                query: row.get(0)?,
+                last_used: row.get(1)?,
+            })
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(HistoryExport {
+        searches,
+        downloads,
+        tui_history,
+    })
+}
+
+/// Import an exported history. Rows already present (same timestamp and
+/// content) are skipped, so importing the same file twice is harmless.
+///
+/// Returns the number of rows inserted.
+pub fn import_history(pool: &DbPool, history: &HistoryExport) -> anyhow::Result<usize> {
+    let mut conn = pool.get()?;
+    let tx = conn.transaction()?;
+    let mut inserted = 0;
+
+    for s in &history.searches {
+        inserted += tx.execute(
+            "INSERT INTO search_logs (query, indexer, timestamp, result_count, duration_ms)
+             SELECT ?1, ?2, ?3, ?4, ?5 WHERE NOT EXISTS (
+                 SELECT 1 FROM search_logs WHERE query = ?1 AND indexer = ?2 AND timestamp = ?3)",
+            params![
+                s.query,
+                s.indexer,
+                s.timestamp,
+                s.result_count as i64,
+                s.duration_ms
+            ],
+        )?;
+    }
+
+    for d in &history.downloads {
+        inserted += tx.execute(
+            "INSERT INTO download_logs (title, magnet, download_link, client_name, download_type, timestamp)
+             SELECT ?1, ?2, ?3, ?4, ?5, ?6 WHERE NOT EXISTS (
+                 SELECT 1 FROM download_logs WHERE title IS ?1 AND download_type = ?5 AND timestamp = ?6)",
+            params![
+                d.title,
+                d.magnet,
+                d.download_link,
+                d.client_name,
+                d.download_type,
+                d.timestamp
+            ],
+        )?;
+    }
+
+    for q in &history.tui_history {
+        // Keep whichever use is more recent
+        inserted += tx.execute(
+            "INSERT INTO tui_history (query, last_used) VALUES (?1, ?2)
+             ON CONFLICT(query) DO UPDATE SET last_used = excluded.last_used
+             WHERE excluded.last_used > tui_history.last_used",
+            params![q.query, q.last_used],
+        )?;
+    }
+
+    tx.commit()?;
+    Ok(inserted)
+}
+
+/// Render history as CSV, one row per search or download
+pub fn history_to_csv(history: &HistoryExport) -> String {
+    fn field(value: &str) -> String {
+        if value.contains([',', '"', '\n', '\r']) {
+            format!("\"{}\"", value.replace('"', "\"\""))
+        } else {
+            value.to_string()
+        }
+    }
+
+    let mut out = String::from("type,timestamp,query_or_title,indexer_or_client,results,link\n");
+    for s in &history.searches {
+        out.push_str(&format!(
+            "search,{},{},{},{},\n",
+            s.timestamp.to_rfc3339(),
+            field(&s.query),
+            field(&s.indexer),
+            s.result_count
+        ));
+    }
+    for d in &history.downloads {
+        let link = d.magnet.as_deref().or(d.download_link.as_deref());
+        out.push_str(&format!(
+            "download,{},{},{},,{}\n",
+            d.timestamp.to_rfc3339(),
+            field(d.title.as_deref().unwrap_or_default()),
+            field(d.client_name.as_deref().unwrap_or(&d.download_type)),
+            field(link.unwrap_or_default())
+        ));
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_pool(name: &str) -> (DbPool, std::path::PathBuf) {
+        let path =
+            std::env::temp_dir().join(format!("lodestarr-test-{}-{}.db", name, std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        (init_db(&path), path)
+    }
+
+    #[test]
+    fn test_export_import_roundtrip() {
+        let (src, src_path) = temp_pool("export");
+        log_search(&src, "ubuntu", "yts", 12, 340).unwrap();
+        log_download(
+            &src,
+            Some("Ubuntu, 24.04"),
+            Some("magnet:?xt=1"),
+            None,
+            None,
+            "magnet",
+        )
+        .unwrap();
+        add_tui_history(&src, "debian").unwrap();
+
+        let history = export_history(&src).unwrap();
+        assert_eq!(history.searches[0].duration_ms, 340);
+        let csv = history_to_csv(&history);
+        assert!(csv.contains("\"Ubuntu, 24.04\""));
+
+        let (dst, dst_path) = temp_pool("import");
+        assert_eq!(import_history(&dst, &history).unwrap(), 3);
+        // Re-importing skips rows that already exist
+        assert_eq!(import_history(&dst, &history).unwrap(), 0);
+        assert_eq!(get_total_searches(&dst).unwrap(), 1);
+        assert_eq!(get_tui_history(&dst, 10).unwrap(), vec!["debian"]);
+
+        let pruned = prune(&dst, 0).unwrap();
+        assert_eq!(pruned.search_logs, 1);
+        assert_eq!(pruned.download_logs, 1);
+
+        let stats = get_db_stats(&dst).unwrap();
+        assert_eq!(stats.tables.len(), TABLES.len());
+        assert!(stats.size > 0);
+
+        let _ = std::fs::remove_file(src_path);
+        let _ = std::fs::remove_file(dst_path);
+    }
+
+    #[test]
+    fn test_fill_first_seen() {
+        let (pool, path) = temp_pool("first-seen");
+        let dated = Utc::now() - chrono::Duration::days(30);
+        let mut results = vec![
+            TorrentResult::new("a".into(), "guid-a".into()),
+            TorrentResult::new("b".into(), "guid-b".into()),
+        ];
+        results[1].publish_date = Some(dated);
+        results[1].date_source = Some(DateSource::Indexer);
+
+        fill_first_seen(&pool, &mut results).unwrap();
+        let first = results[0].publish_date.unwrap();
+        assert_eq!(results[0].date_source, Some(DateSource::FirstSeen));
+        assert_eq!(results[1].publish_date, Some(dated));
+
+        // Seen again later: keeps the original date
+        let mut again = vec![TorrentResult::new("a".into(), "guid-a".into())];
+        fill_first_seen(&pool, &mut again).unwrap();
+        assert_eq!(again[0].publish_date, Some(first));
+
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[test]
+    fn test_guid_registry() {
+        let (pool, path) = temp_pool("guids");
+        let mut result = TorrentResult::new("a".into(), "guid-a".into());
+        result.link = Some("https://tracker.example/dl/1".into());
+        result.info_hash = Some("abc".into());
+        let mut other = TorrentResult::new("b".into(), "guid-b".into());
+        other.indexer = Some("eztv".into());
+        other.magnet = Some("magnet:?xt=urn:btih:def".into());
+        // Nothing to download: not registered
+        let bare = TorrentResult::new("c".into(), "guid-c".into());
+        register_guids(&pool, Some("yts"), &[result, other, bare]).unwrap();
+
+        let found = lookup_guid(&pool, Some("yts"), "guid-a").unwrap().unwrap();
+        assert_eq!(found.link.as_deref(), Some("https://tracker.example/dl/1"));
+        assert_eq!(found.info_hash.as_deref(), Some("abc"));
+        assert_eq!(
+            lookup_guid(&pool, None, "guid-b").unwrap().unwrap().indexer,
+            "eztv"
+        );
+        assert_eq!(lookup_guid(&pool, Some("yts"), "guid-b").unwrap(), None);
+        assert_eq!(lookup_guid(&pool, None, "guid-c").unwrap(), None);
+
+        // Pinned results outlive pruning
+        assert!(pin_guid(&pool, "yts", "guid-a").unwrap());
+        assert_eq!(prune(&pool, 0).unwrap().guids, 1);
+        assert!(lookup_guid(&pool, None, "guid-a").unwrap().is_some());
+        assert_eq!(lookup_guid(&pool, None, "guid-b").unwrap(), None);
+
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[test]
+    fn test_saved_searches() {
+        let (pool, path) = temp_pool("saved");
+        let id = add_saved_search(&pool, "Ubuntu", "ubuntu", Some("yts"), None).unwrap();
+        add_saved_search(&pool, "Debian", "debian", None, Some("4000")).unwrap();
+
+        let saved = get_saved_search(&pool, id).unwrap().unwrap();
+        assert_eq!(saved.query, "ubuntu");
+        assert_eq!(saved.indexer.as_deref(), Some("yts"));
+        assert_eq!(get_saved_searches(&pool).unwrap().len(), 2);
+
+        assert!(delete_saved_search(&pool, id).unwrap());
+        assert!(!delete_saved_search(&pool, id).unwrap());
+        assert!(get_saved_search(&pool, id).unwrap().is_none());
+
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[test]
+    fn test_rss_rules() {
+        let (pool, path) = temp_pool("rss");
+        let mut rule = RssRule {
+            name: "Ubuntu".to_string(),
+            query: "ubuntu".to_string(),
+            max_size: Some(5_000_000_000),
+            interval_minutes: 60,
+            enabled: true,
+            ..Default::default()
+        };
+        rule.id = add_rss_rule(&pool, &rule).unwrap();
+        rule.client_id = Some("qbit".to_string());
+        assert!(update_rss_rule(&pool, &rule).unwrap());
+        set_rss_rule_run(&pool, rule.id, Utc::now()).unwrap();
+        let saved = get_rss_rule(&pool, rule.id).unwrap().unwrap();
+        assert_eq!(saved.max_size, Some(5_000_000_000));
+        assert_eq!(saved.client_id.as_deref(), Some("qbit"));
+        assert!(saved.last_run.is_some());
+
+        let result = TorrentResult {
+            guid: "g1".to_string(),
+            title: "Ubuntu 24.04".to_string(),
+            ..Default::default()
+        };
+        assert!(add_rss_match(&pool, rule.id, &result, MatchStatus::Pending).unwrap());
+        // Seen before
+        assert!(!add_rss_match(&pool, rule.id, &result, MatchStatus::Pending).unwrap());
+        set_rss_match_status(&pool, rule.id, "g1", MatchStatus::Failed, Some("refused")).unwrap();
+        let matches = get_rss_matches(&pool, Some(MatchStatus::Failed), 10).unwrap();
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].error.as_deref(), Some("refused"));
+        assert_eq!(matches[0].attempts, 1);
+        assert!(
+            get_rss_matches(&pool, Some(MatchStatus::Sent), 10)
+                .unwrap()
+                .is_empty()
+        );
+
+        // Failed sends are retried until they run out of attempts
+        let retries = get_rss_retries(&pool, rule.id, 2).unwrap();
+        assert_eq!(retries.len(), 1);
+        assert_eq!(retries[0].to_result().title, "Ubuntu 24.04");
+        set_rss_match_status(&pool, rule.id, "g1", MatchStatus::Failed, Some("refused")).unwrap();
+        assert!(get_rss_retries(&pool, rule.id, 2).unwrap().is_empty());
+
+        let seen = TorrentResult {
+            guid: "g2".to_string(),
+            ..result.clone()
+        };
+        assert!(add_rss_match(&pool, rule.id, &seen, MatchStatus::Seen).unwrap());
+        assert_eq!(
+            get_rss_matches(&pool, Some(MatchStatus::Seen), 10).unwrap()[0].guid,
+            "g2"
+        );
+        assert_eq!(
+            prune_rss_matches(&pool, Utc::now() - chrono::Duration::days(1)).unwrap(),
+            0
+        );
+        assert_eq!(
+            prune_rss_matches(&pool, Utc::now() + chrono::Duration::days(1)).unwrap(),
+            2
+        );
+        assert!(add_rss_match(&pool, rule.id, &result, MatchStatus::Pending).unwrap());
+
+        assert!(delete_rss_rule(&pool, rule.id).unwrap());
+        assert!(get_rss_matches(&pool, None, 10).unwrap().is_empty());
+
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[test]
+    fn test_raw_responses_rotate() {
+        let (pool, path) = temp_pool("raw");
+        add_raw_response(
+            &pool,
+            "native:yts:a:",
+            "yts",
+            "https://yts/1",
+            200,
+            "<html/>",
+        )
+        .unwrap();
+        for i in 0..RAW_RESPONSES_KEPT {
+            add_raw_response(&pool, "native:yts:b:", "yts", &i.to_string(), 200, "x").unwrap();
+        }
+
+        assert!(
+            get_raw_responses(&pool, "native:yts:a:")
+                .unwrap()
+                .is_empty()
+        );
+        let kept = get_raw_responses(&pool, "native:yts:b:").unwrap();
+        assert_eq!(kept.len(), RAW_RESPONSES_KEPT as usize);
+        assert_eq!(kept[0].url, "0");
+        assert_eq!(get_raw_response_keys(&pool).unwrap().len(), 1);
+
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[test]
+    fn test_indexer_success_rates() {
+        let (pool, path) = temp_pool("success-rates");
+        log_indexer_latency(&pool, "yts", 100, None, Some(0)).unwrap();
+        log_indexer_latency(&pool, "yts", 100, None, Some(0)).unwrap();
+        log_indexer_latency(&pool, "yts", 100, None, Some(0)).unwrap();
+        log_indexer_latency(
+            &pool,
+            "yts",
+            100,
+            Some(crate::indexer::ErrorKind::Network),
+            None,
+        )
+        .unwrap();
+
+        let rates =
+            get_indexer_success_rates(&pool, Utc::now() - chrono::Duration::days(1)).unwrap();
+        assert_eq!(rates.get("yts"), Some(&0.75));
+        assert!(
+            get_indexer_success_rates(&pool, Utc::now())
+                .unwrap()
+                .is_empty()
+        );
+
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[test]
+    fn test_top_traffic() {
+        let (pool, path) = temp_pool("traffic");
+        log_indexer_traffic(&pool, "yts", "search", 300).unwrap();
+        log_indexer_traffic(&pool, "yts", "download", 50).unwrap();
+        log_indexer_traffic(&pool, "1337x", "search", 1000).unwrap();
+        log_indexer_traffic(&pool, "eztv", "search", 0).unwrap();
+
+        let top = get_top_traffic(&pool, Utc::now() - chrono::Duration::days(1), 10).unwrap();
+        let totals: Vec<_> = top
+            .iter()
+            .map(|t| (t.indexer.as_str(), t.total_bytes))
+            .collect();
+        assert_eq!(totals, vec![("1337x", 1000), ("yts", 350)]);
+        assert_eq!(top[1].download_bytes, 50);
+
+        let _ = std::fs::remove_file(path);
+    }
+}
@@ -0,0 +1,245 @@
+//! Merging of results that several indexers return for the same torrent
+//!
+//! Results are matched by infohash (from the result or its magnet link), or
+//! failing that by title and size: titles equal once case and punctuation
+//! are ignored, sizes within 1% of each other. Results whose infohashes
+//! differ are never merged. The best-seeded copy is kept, the other
+//! indexers are listed in its `other_indexers`, and fields it lacks are
+//! filled in from the others. For example, one indexer may give the date
+//! while another gives the file count and poster. Tracker-specific fields
+//! (freeleech factors, ratio rules, flags) are never borrowed, and the
+//! download (link, magnet, infohash) only when the infohashes matched.
+
+use crate::models::{DateSource, TorrentResult};
+use std::collections::HashMap;
+
+/// Lower-cased infohash of a result, from its own field or its magnet link
+pub fn info_hash_of(result: &TorrentResult) -> Option<String> {
+    let hash = result.info_hash.as_deref().or_else(|| {
+        let magnet = result.magnet.as_deref()?;
+        let start = magnet.to_ascii_lowercase().find("urn:btih:")? + "urn:btih:".len();
+        magnet[start..].split('&').next()
+    })?;
+    let hash = hash.trim();
+    (!hash.is_empty()).then(|| hash.to_ascii_lowercase())
+}
+
+/// Title with case, spacing and punctuation removed
+fn title_key(title: &str) -> String {
+    title
+        .chars()
+        .filter(|c| c.is_alphanumeric())
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+fn similar_size(a: u64, b: u64) -> bool {
+    a.abs_diff(b) as f64 <= a.max(b) as f64 * 0.01
+}
+
+/// Merge results for the same torrent into one, keeping the order of the
+/// remaining results
+pub fn merge_duplicates(results: Vec<TorrentResult>) -> Vec<TorrentResult> {
+    let mut merged: Vec<TorrentResult> = Vec::with_capacity(results.len());
+    let mut by_hash: HashMap<String, usize> = HashMap::new();
+    let mut by_title: HashMap<String, Vec<usize>> = HashMap::new();
+    // Infohash of each merged result's group, which a kept result without
+    // one may not carry
+    let mut group_hash: Vec<Option<String>> = Vec::with_capacity(results.len());
+    for result in results {
+        let hash = info_hash_of(&result);
+        let title = title_key(&result.title);
+        let same = match &hash {
+            Some(hash) => by_hash.get(hash).copied(),
+            None => None,
+        }
+        .or_else(|| {
+            let size = result.size?;
+            by_title.get(&title)?.iter().copied().find(|&i| {
+                merged[i].size.is_some_and(|s| similar_size(s, size))
+                    && match (&hash, &group_hash[i]) {
+                        (Some(a), Some(b)) => a == b,
+                        _ => true,
+                    }
+            })
+        });
+
+        let i = match same {
+            Some(i) => {
+                let kept = &mut merged[i];
+                let same_hash = hash.is_some() && info_hash_of(kept) == hash;
+                if group_hash[i].is_none() {
+                    group_hash[i] = hash;
+                }
+                if result.seeders.unwrap_or(0) > kept.seeders.unwrap_or(0) {
+                    let other = std::mem::replace(kept, result);
+                    enrich(kept, other, same_hash);
+                } else {
+                    enrich(kept, result, same_hash);
+                }
+                i
+            }
+            None => {
+                merged.push(result);
+                group_hash.push(hash);
+                by_title.entry(title).or_default().push(merged.len() - 1);
+                merged.len() - 1
+            }
+        };
+        if let Some(hash) = &group_hash[i] {
+            by_hash.entry(hash.clone()).or_insert(i);
+        }
+    }
+    merged
+}
+
+/// Fill the fields `result` lacks from `other`. Unless both have the same
+/// infohash, a title and size match may be a different torrent, so its
+/// download isn't taken.
+fn enrich(result: &mut TorrentResult, other: TorrentResult, same_hash: bool) {
+    fn fill<T>(field: &mut Option<T>, other: Option<T>) {
+        if field.is_none() {
+            *field = other;
+        }
+    }
+
+    for indexer in other.indexer.into_iter().chain(other.other_indexers) {
+        if result.indexer.as_ref() != Some(&indexer) && !result.other_indexers.contains(&indexer) {
+            result.other_indexers.push(indexer);
+        }
+    }
+
+    // A date an indexer listed beats one Lodestarr made up
+    let guessed = matches!(result.date_source, None | Some(DateSource::FirstSeen));
+    if other.publish_date.is_some()
+        && (result.publish_date.is_none()
+            || guessed && !matches!(other.date_source, None | Some(DateSource::FirstSeen)))
+    {
+        result.publish_date = other.publish_date;
+        result.date_source = other.date_source;
+    }
+    if result.categories.is_empty() {
+        result.categories = other.categories;
+    }
+    if same_hash {
+        fill(&mut result.link, other.link);
+        fill(&mut result.magnet, other.magnet);
+        fill(&mut result.info_hash, other.info_hash);
+    }
+    fill(&mut result.details, other.details);
+    fill(&mut result.size, other.size);
+    fill(&mut result.files, other.files);
+    fill(&mut result.grabs, other.grabs);
+    fill(&mut result.imdb_id, other.imdb_id);
+    fill(&mut result.tmdb_id, other.tmdb_id);
+    fill(&mut result.tvdb_id, other.tvdb_id);
+    fill(&mut result.uploader, other.uploader);
+    fill(&mut result.release_group, other.release_group);
+    fill(&mut result.description, other.description);
+    fill(&mut result.genre, other.genre);
+    fill(&mut result.poster, other.poster);
+    fill(&mut result.music, other.music);
+    fill(&mut result.sports, other.sports);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    #[test]
+    fn test_merge_duplicates() {
+        let hash = "0123456789ABCDEF0123456789ABCDEF01234567";
+        let dated = TorrentResult {
+            title: "Show S01".into(),
+            guid: "a".into(),
+            indexer: Some("a".into()),
+            info_hash: Some(hash.into()),
+            seeders: Some(5),
+            publish_date: Some(Utc::now()),
+            date_source: Some(DateSource::Indexer),
+            ..Default::default()
+        };
+        let detailed = TorrentResult {
+            title: "Show.S01.1080p".into(),
+            guid: "b".into(),
+            indexer: Some("b".into()),
+            magnet: Some(format!("magnet:?xt=urn:btih:{}&dn=x", hash.to_lowercase())),
+            seeders: Some(50),
+            files: Some(10),
+            poster: Some("https://b.org/p.jpg".into()),
+            publish_date: Some(Utc::now()),
+            date_source: Some(DateSource::FirstSeen),
+            download_volume_factor: Some(0.0),
+            ..Default::default()
+        };
+        let other = TorrentResult::new("Other".into(), "c".into());
+
+        let merged = merge_duplicates(vec![dated.clone(), other, detailed]);
+        assert_eq!(merged.len(), 2);
+        let show = &merged[0];
+        // The better-seeded copy is kept, with what only the other had
+        assert_eq!(show.guid, "b");
+        assert_eq!(show.files, Some(10));
+        assert_eq!(show.info_hash.as_deref(), Some(hash));
+        assert_eq!(show.date_source, Some(DateSource::Indexer));
+        assert_eq!(show.publish_date, dated.publish_date);
+        assert_eq!(show.download_volume_factor, Some(0.0));
+        assert_eq!(show.other_indexers, ["a"]);
+        assert_eq!(merged[1].guid, "c");
+
+        // Without hashes, the same title and size
+        let sized = |title: &str, indexer: &str, size: u64, seeders: u32| TorrentResult {
+            title: title.into(),
+            guid: format!("{indexer}-{title}"),
+            indexer: Some(indexer.into()),
+            size: Some(size),
+            seeders: Some(seeders),
+            ..Default::default()
+        };
+        let merged = merge_duplicates(vec![
+            TorrentResult {
+                info_hash: Some("aa".into()),
+                ..sized("Movie.2024.1080p.WEB-DL", "a", 4_000_000_000, 3)
+            },
+            sized("Movie 2024 1080p WEB DL", "b", 4_010_000_000, 30),
+            sized("movie.2024.1080p.web-dl", "c", 4_020_000_000, 1),
+            // Too different in size, or a different torrent
+            sized("Movie.2024.1080p.WEB-DL", "d", 4_500_000_000, 9),
+            TorrentResult {
+                info_hash: Some("ff".into()),
+                ..sized("Movie.2024.1080p.WEB-DL", "e", 4_000_000_000, 1)
+            },
+        ]);
+        let summary: Vec<_> = merged
+            .iter()
+            .map(|r| (r.indexer.as_deref().unwrap(), r.other_indexers.join(",")))
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                ("b", "a,c".to_string()),
+                ("d", String::new()),
+                ("e", String::new())
+            ]
+        );
+        // Matched on title and size only, so the kept copy keeps its own download
+        assert_eq!(merged[0].info_hash, None);
+
+        let linked = |indexer: &str, seeders: u32| TorrentResult {
+            link: Some(format!("https://{indexer}.org/dl/1")),
+            ..sized("Movie.2024.1080p.WEB-DL", indexer, 4_000_000_000, seeders)
+        };
+        let merged = merge_duplicates(vec![
+            linked("a", 3),
+            TorrentResult {
+                link: None,
+                magnet: Some("magnet:?xt=urn:btih:bb".into()),
+                ..sized("Movie.2024.1080p.WEB-DL", "b", 4_000_000_000, 30)
+            },
+        ]);
+        assert_eq!(merged.len(), 1);
+        assert_eq!(merged[0].link, None);
+        assert_eq!(merged[0].info_hash, None);
+    }
+}
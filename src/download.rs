@@ -1,79 +1,342 @@
+use crate::config::Config;
+use crate::indexer::{IndexerManager, SearchExecutor};
+use crate::models::get_category;
 use crate::torznab;
 use crate::utils::sanitize_filename;
 use anyhow::Result;
 use colored::Colorize;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
 
-pub async fn perform_download(
-    client: &torznab::TorznabClient,
-    url: &str,
-    output: Option<String>,
-    magnet: bool,
-    title: Option<&str>,
-) -> Result<()> {
-    // Check if it's a magnet link
-    if url.starts_with("magnet:") || magnet {
-        let name = if let Some(t) = title {
+/// What a download would do, as reported by `--dry-run` / `dryRun`
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DownloadPlan {
+    /// URL as given
+    pub url: String,
+    /// Indexer the link belongs to (Lodestarr proxy links only)
+    pub indexer: Option<String>,
+    /// Details page fetched to find the real link (multi-step definitions)
+    pub details_page: Option<String>,
+    /// Link that would be fetched (or saved, for magnets)
+    pub resolved_url: String,
+    /// "magnet" or "torrent"
+    pub kind: &'static str,
+    /// File the result would be written to
+    pub target: String,
+}
+
+/// Decode a `link` parameter from a `/dl` proxy URL (URL-safe BASE64, or plain)
+pub fn decode_link(link: &str) -> String {
+    // Encoded links never contain ':'
+    if link.contains(':') {
+        return link.to_string();
+    }
+    use base64::{Engine as _, engine::general_purpose::URL_SAFE_NO_PAD};
+    URL_SAFE_NO_PAD
+        .decode(link)
+        .ok()
+        .and_then(|bytes| String::from_utf8(bytes).ok())
+        .unwrap_or_else(|| link.to_string())
+}
+
+/// Lodestarr's download proxy URL for `link` on `indexer`. The GUID pins
+/// the grab to its search result; the API key is for clients outside the web UI.
+pub fn proxy_download_url(
+    base: &str,
+    indexer: &str,
+    link: &str,
+    guid: &str,
+    api_key: Option<&str>,
+) -> String {
+    use base64::{Engine as _, engine::general_purpose::URL_SAFE_NO_PAD};
+    let mut url = format!(
+        "{}/api/v2.0/indexers/{}/dl?link={}&guid={}",
+        base.trim_end_matches('/'),
+        urlencoding::encode(indexer),
+        URL_SAFE_NO_PAD.encode(link),
+        urlencoding::encode(guid)
+    );
+    if let Some(key) = api_key {
+        url.push_str(&format!("&apikey={}", urlencoding::encode(key)));
+    }
+    url
+}
+
+/// Split a Lodestarr `/api/v2.0/indexers/{id}/dl?link=...` URL into the
+/// indexer id and the decoded upstream link
+pub fn parse_proxy_link(url: &str) -> Option<(String, String)> {
+    let parsed = reqwest::Url::parse(url).ok()?;
+    let mut segments = parsed.path_segments()?;
+    let indexer = loop {
+        if segments.next()? == "indexers" {
+            break segments.next()?.to_string();
+        }
+    };
+    if segments.next()? != "dl" {
+        return None;
+    }
+    let link = parsed
+        .query_pairs()
+        .find(|(k, _)| k == "link")
+        .map(|(_, v)| decode_link(&v))?;
+    Some((indexer, link))
+}
+
+/// File a download would be written to
+pub fn target_path(url: &str, output: Option<&str>, magnet: bool, title: Option<&str>) -> String {
+    let name = if url.starts_with("magnet:") || magnet {
+        if let Some(t) = title {
             format!("{}.magnet", sanitize_filename(t))
         } else if let Some(start) = url.find("dn=") {
             let name_part = &url[start + 3..];
             let end = name_part.find('&').unwrap_or(name_part.len());
             let name = urlencoding::decode(&name_part[..end]).unwrap_or_default();
-            format!("{}.magnet", name)
+            format!("{}.magnet", safe_name(&name, "download"))
         } else {
             "download.magnet".to_string()
-        };
-
-        let filename = if let Some(out) = &output {
-            let path = std::path::Path::new(out);
-            if path.is_dir() {
-                path.join(&name).to_string_lossy().to_string()
-            } else {
-                out.clone()
-            }
-        } else {
-            name
-        };
-
-        std::fs::write(&filename, url)?;
-        println!(
-            "{} Saved magnet link to {}",
-            "✓".green().bold(),
-            filename.green()
-        );
-        return Ok(());
-    }
-
-    let name = if let Some(t) = title {
+        }
+    } else if let Some(t) = title {
         format!("{}.torrent", sanitize_filename(t))
     } else {
         url.split('/')
             .next_back()
             .and_then(|s| s.split('?').next())
-            .filter(|s| !s.is_empty())
-            .unwrap_or("download.torrent")
-            .to_string()
+            .filter(|s| !s.chars().all(|c| c == '.'))
+            .map_or_else(
+                || "download.torrent".to_string(),
+                |s| safe_name(s, "download"),
+            )
     };
 
-    let filename = if let Some(out) = &output {
+    if let Some(out) = output {
         let path = std::path::Path::new(out);
         if path.is_dir() {
             path.join(&name).to_string_lossy().to_string()
         } else {
-            out.clone()
+            out.to_string()
         }
     } else {
         name
+    }
+}
+
+/// `name` made safe to use as a file name, or `fallback` if nothing is left
+fn safe_name(name: &str, fallback: &str) -> String {
+    let name = sanitize_filename(name.trim());
+    if name.chars().all(|c| c == '.') {
+        fallback.to_string()
+    } else {
+        name
+    }
+}
+
+/// What a download path template is filled in from
+#[derive(Debug, Default, Clone, Copy)]
+pub struct TemplateValues<'a> {
+    pub title: Option<&'a str>,
+    pub indexer: Option<&'a str>,
+    pub category: Option<i32>,
+}
+
+/// Fill in a path template such as `{indexer}/{category}/{title}.torrent`,
+/// relative to the download directory.
+///
+/// Placeholders are `{title}`, `{indexer}`, `{category}` (the category name),
+/// `{category_id}` and `{date}` (today, as `YYYY-MM-DD`). Missing values
+/// become `unknown`. Each path segment is sanitized, so a value can't add
+/// folders or climb out of the download directory. The file always ends in
+/// `.torrent`, or `.magnet` for magnets.
+pub fn render_template(template: &str, values: &TemplateValues, magnet: bool) -> PathBuf {
+    let value = |name: &str| match name {
+        "title" => values.title.map(str::to_string),
+        "indexer" => values.indexer.map(str::to_string),
+        "category" => values
+            .category
+            .map(|id| get_category(id).map_or_else(|| id.to_string(), |c| c.name.to_string())),
+        "category_id" => values.category.map(|id| id.to_string()),
+        "date" => Some(chrono::Local::now().format("%Y-%m-%d").to_string()),
+        // Unknown placeholders are kept as written
+        other => Some(other.to_string()),
     };
 
+    let mut path = PathBuf::new();
+    for segment in template.split(['/', '\\']) {
+        let mut filled = String::new();
+        let mut rest = segment;
+        while let Some(start) = rest.find('{')
+            && let Some(len) = rest[start..].find('}')
+        {
+            filled.push_str(&rest[..start]);
+            let filler = value(&rest[start + 1..start + len]).filter(|v| !v.trim().is_empty());
+            filled.push_str(filler.as_deref().unwrap_or("unknown"));
+            rest = &rest[start + len + 1..];
+        }
+        filled.push_str(rest);
+        let segment = sanitize_filename(filled.trim());
+        if !segment.chars().all(|c| c == '.') {
+            path.push(segment);
+        }
+    }
+
+    let extension = if magnet { "magnet" } else { "torrent" };
+    let stem = path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .map(|name| {
+            let lower = name.to_ascii_lowercase();
+            match [".torrent", ".magnet"]
+                .iter()
+                .find(|ext| lower.ends_with(*ext))
+            {
+                Some(ext) => name[..name.len() - ext.len()].to_string(),
+                None => name,
+            }
+        })
+        .filter(|stem| !stem.is_empty())
+        .unwrap_or_else(|| "download".to_string());
+    path.set_file_name(format!("{}.{}", stem, extension));
+    path
+}
+
+/// `path`, or if that file exists, the first free `name-1.ext`, `name-2.ext`, ...
+pub async fn unique_path(path: PathBuf) -> PathBuf {
+    async fn taken(path: &Path) -> bool {
+        tokio::fs::try_exists(path).await.unwrap_or(false)
+    }
+    if !taken(&path).await {
+        return path;
+    }
+    let stem = path
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let extension = path.extension().map(|e| e.to_string_lossy().to_string());
+    let mut n = 1;
+    loop {
+        let name = match &extension {
+            Some(ext) => format!("{}-{}.{}", stem, n, ext),
+            None => format!("{}-{}", stem, n),
+        };
+        let candidate = path.with_file_name(name);
+        if !taken(&candidate).await {
+            return candidate;
+        }
+        n += 1;
+    }
+}
+
+/// Work out what downloading `url` would fetch and where it would be saved,
+/// without requesting the torrent itself (so the tracker's grab counter is
+/// not touched).
+///
+/// Lodestarr proxy links are decoded locally; native indexers with
+/// multi-step download selectors have their details page fetched.
+pub async fn plan_download(
+    url: &str,
+    output: Option<&str>,
+    magnet: bool,
+    title: Option<&str>,
+    native: &IndexerManager,
+    config: &Config,
+) -> Result<DownloadPlan> {
+    let (indexer, mut resolved_url) = match parse_proxy_link(url) {
+        Some((indexer, link)) => (Some(indexer), link),
+        None => (None, url.to_string()),
+    };
+
+    let mut details_page = None;
+    if let Some(id) = &indexer
+        && !resolved_url.starts_with("magnet:")
+        && let Some(def) = native.get_definition(id).await
+        && SearchExecutor::has_download_selectors(&def)
+    {
+        let settings = config.native_settings.get(id);
+        let executor = SearchExecutor::for_indexer(id, config.proxy_url.as_deref(), settings)?;
+        let _ = executor.visit_base_url(&def).await;
+        details_page = Some(resolved_url.clone());
+        resolved_url = executor
+            .resolve_download_url(&def, &resolved_url, settings)
+            .await?;
+    }
+
+    let is_magnet = resolved_url.starts_with("magnet:") || magnet;
+    Ok(DownloadPlan {
+        url: url.to_string(),
+        indexer,
+        details_page,
+        target: target_path(&resolved_url, output, magnet, title),
+        kind: if is_magnet { "magnet" } else { "torrent" },
+        resolved_url,
+    })
+}
+
+/// Print a dry-run plan for the CLI
+pub fn print_plan(plan: &DownloadPlan) {
+    println!("{} Dry run - nothing will be downloaded", "→".cyan());
+    if let Some(indexer) = &plan.indexer {
+        println!("  Indexer:      {}", indexer);
+    }
+    if let Some(page) = &plan.details_page {
+        println!("  Details page: {}", page);
+    }
+    println!("  Would fetch:  {}", plan.resolved_url.cyan());
+    println!("  Type:         {}", plan.kind);
+    println!("  Save to:      {}", plan.target.green());
+}
+
+pub async fn perform_download(
+    client: &torznab::TorznabClient,
+    url: &str,
+    output: Option<String>,
+    magnet: bool,
+    title: Option<&str>,
+    template: Option<(&str, TemplateValues<'_>)>,
+) -> Result<()> {
+    // A template sorts the file into folders below the output directory,
+    // unless an output file was named outright
+    let filename = match template {
+        Some((template, values)) if output.as_deref().is_none_or(|o| Path::new(o).is_dir()) => {
+            let is_magnet = url.starts_with("magnet:") || magnet;
+            let base = output.map(PathBuf::from).unwrap_or_default();
+            let path = unique_path(base.join(render_template(template, &values, is_magnet))).await;
+            if let Some(parent) = path.parent() {
+                tokio::fs::create_dir_all(parent).await?;
+            }
+            path.to_string_lossy().to_string()
+        }
+        _ => target_path(url, output.as_deref(), magnet, title),
+    };
+
+    // Check if it's a magnet link
+    if url.starts_with("magnet:") || magnet {
+        tokio::fs::write(&filename, url).await?;
+        println!(
+            "{} Saved magnet link to {}",
+            "✓".green().bold(),
+            filename.green()
+        );
+        return Ok(());
+    }
+
     println!("Downloading to {}...", filename.cyan());
 
-    match client.download(url).await {
-        Ok(bytes) => {
-            std::fs::write(&filename, &bytes)?;
+    // Trackers answer expired logins and missing torrents with web pages,
+    // which shouldn't be saved as .torrent files
+    let downloaded = client.download(url).await.and_then(|bytes| {
+        let torrent = crate::torrent::parse(&bytes)
+            .map_err(|e| anyhow::anyhow!("Not a torrent file: {}", e))?;
+        Ok((bytes, torrent))
+    });
+    match downloaded {
+        Ok((bytes, torrent)) => {
+            tokio::fs::write(&filename, &bytes).await?;
             println!(
-                "{} Downloaded {} bytes to {}",
+                "{} Downloaded {} ({}, {} files) to {}",
                 "✓".green().bold(),
-                bytes.len().to_string().cyan(),
+                torrent.name.cyan(),
+                crate::utils::format_size(torrent.total_size),
+                torrent.files.len(),
                 filename.green()
             );
             Ok(())
@@ -105,4 +368,88 @@ mod tests {
         let fname = format!("{}.magnet", safe);
         assert_eq!(fname, "My_Movie__2025_.magnet");
     }
+
+    #[test]
+    fn test_parse_proxy_link() {
+        use base64::{Engine as _, engine::general_purpose::URL_SAFE_NO_PAD};
+        let encoded = URL_SAFE_NO_PAD.encode("https://tracker.example/details/42");
+        let url = format!(
+            "http://localhost:3420/api/v2.0/indexers/yts/dl?link={}",
+            encoded
+        );
+        assert_eq!(
+            parse_proxy_link(&url),
+            Some((
+                "yts".to_string(),
+                "https://tracker.example/details/42".to_string()
+            ))
+        );
+        assert_eq!(parse_proxy_link("https://tracker.example/dl/42"), None);
+    }
+
+    #[test]
+    fn test_target_path() {
+        assert_eq!(
+            target_path("magnet:?xt=urn:btih:abc&dn=Some%20Show", None, false, None),
+            "Some_Show.magnet"
+        );
+        // A magnet's name can't climb out of the output directory
+        assert_eq!(
+            target_path("magnet:?xt=urn:btih:abc&dn=..%2F..%2Fx", None, false, None),
+            ".._.._x.magnet"
+        );
+        assert_eq!(
+            target_path("magnet:?xt=urn:btih:abc&dn=..", None, false, None),
+            "download.magnet"
+        );
+        assert_eq!(
+            target_path("https://t.example/get/..", None, false, None),
+            "download.torrent"
+        );
+        assert_eq!(
+            target_path("https://t.example/get/file.torrent?x=1", None, false, None),
+            "file.torrent"
+        );
+        assert_eq!(
+            target_path("https://t.example/get/1", Some("out.torrent"), false, None),
+            "out.torrent"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_render_template() {
+        let values = TemplateValues {
+            title: Some("Dune: Part Two (2024)"),
+            indexer: Some("yts"),
+            category: Some(2040),
+        };
+        assert_eq!(
+            render_template("{indexer}/{category}/{title}.torrent", &values, false),
+            PathBuf::from("yts/Movies_HD/Dune__Part_Two__2024_.torrent")
+        );
+        // Values can't climb out of the download directory, and magnets
+        // keep their own extension
+        let sneaky = TemplateValues {
+            title: Some("../../etc/passwd"),
+            ..Default::default()
+        };
+        assert_eq!(
+            render_template("../{indexer}/{title}.torrent", &sneaky, true),
+            PathBuf::from("unknown/.._.._etc_passwd.magnet")
+        );
+        assert_eq!(
+            render_template("{category_id}/", &values, false),
+            PathBuf::from("2040.torrent")
+        );
+
+        let dir =
+            std::env::temp_dir().join(format!("lodestarr-test-unique-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("a.torrent");
+        assert_eq!(unique_path(path.clone()).await, path);
+        std::fs::write(&path, b"").unwrap();
+        std::fs::write(dir.join("a-1.torrent"), b"").unwrap();
+        assert_eq!(unique_path(path).await, dir.join("a-2.torrent"));
+        let _ = std::fs::remove_dir_all(dir);
+    }
 }
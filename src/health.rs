@@ -0,0 +1,113 @@
+//! Torrent health: one 0-100 number summarising seeders, peers, age,
+//! indexer reliability and freeleech
+//!
+//! Points are split as follows:
+//! - 40 for the seeder count (logarithmic, full at 500)
+//! - 10 for the share of seeders among all peers
+//! - 20 for age (halving every 90 days)
+//! - 20 for the indexer's recent search success rate
+//! - 10 for freeleech (5 for half-leech)
+//!
+//! Results without a date or indexer history get half the points for that
+//! part, so they aren't ranked as if they were the worst.
+
+use crate::models::TorrentResult;
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+
+/// Seeder count that earns all the seeder points
+const FULL_SEEDERS: f64 = 500.0;
+/// Days after which the age points halve
+const AGE_HALF_LIFE_DAYS: f64 = 90.0;
+/// How far back indexer success rates are counted
+pub const RELIABILITY_WINDOW_DAYS: i64 = 7;
+
+/// Health of a result. `reliability` is its indexer's success rate (0-1).
+pub fn score(result: &TorrentResult, reliability: Option<f64>, now: DateTime<Utc>) -> u8 {
+    let seeders = result.seeders.unwrap_or(0) as f64;
+    let leechers = result.leechers.unwrap_or(0) as f64;
+
+    let swarm = 40.0 * ((1.0 + seeders).ln() / (1.0 + FULL_SEEDERS).ln()).min(1.0);
+    let ratio = if seeders + leechers > 0.0 {
+        10.0 * seeders / (seeders + leechers)
+    } else {
+        0.0
+    };
+    let age = match result.publish_date {
+        Some(date) => {
+            let days = (now - date).num_hours().max(0) as f64 / 24.0;
+            20.0 * 0.5f64.powf(days / AGE_HALF_LIFE_DAYS)
+        }
+        None => 10.0,
+    };
+    let indexer = 20.0 * reliability.unwrap_or(0.5).clamp(0.0, 1.0);
+    let flagged = result
+        .flags
+        .iter()
+        .any(|f| f.eq_ignore_ascii_case("freeleech"));
+    let freeleech = match result.download_volume_factor {
+        Some(f) if f <= 0.0 => 10.0,
+        Some(f) if f < 1.0 => 5.0,
+        _ if flagged => 10.0,
+        _ => 0.0,
+    };
+
+    (swarm + ratio + age + indexer + freeleech)
+        .round()
+        .clamp(0.0, 100.0) as u8
+}
+
+/// Set `health` on every result. `rates` maps indexer (id or name, as
+/// recorded in the latency log) to its success rate.
+pub fn apply(results: &mut [TorrentResult], rates: &HashMap<String, f64>) {
+    let now = Utc::now();
+    for result in results {
+        let reliability = result.indexer.as_ref().and_then(|i| rates.get(i)).copied();
+        result.health = Some(score(result, reliability, now));
+    }
+}
+
+/// Recent success rates of all indexers (empty if the database fails)
+pub fn reliability(pool: &crate::db::DbPool) -> HashMap<String, f64> {
+    let since = Utc::now() - chrono::Duration::days(RELIABILITY_WINDOW_DAYS);
+    crate::db::get_indexer_success_rates(pool, since).unwrap_or_else(|e| {
+        tracing::debug!("Failed to read indexer success rates: {}", e);
+        HashMap::new()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    fn result(seeders: u32, leechers: u32, age_days: Option<i64>) -> TorrentResult {
+        TorrentResult {
+            seeders: Some(seeders),
+            leechers: Some(leechers),
+            publish_date: age_days.map(|d| Utc::now() - Duration::days(d)),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_score() {
+        let now = Utc::now();
+        let fresh = result(1000, 0, Some(0));
+        assert_eq!(score(&fresh, Some(1.0), now), 90);
+
+        let mut freeleech = fresh.clone();
+        freeleech.download_volume_factor = Some(0.0);
+        assert_eq!(score(&freeleech, Some(1.0), now), 100);
+
+        let dead = result(0, 0, Some(3650));
+        assert_eq!(score(&dead, Some(0.0), now), 0);
+
+        // More seeders, a better ratio and a younger upload all help
+        let base = score(&result(20, 20, Some(30)), None, now);
+        assert!(score(&result(200, 20, Some(30)), None, now) > base);
+        assert!(score(&result(20, 0, Some(30)), None, now) > base);
+        assert!(score(&result(20, 20, Some(1)), None, now) > base);
+        assert!(score(&result(20, 20, Some(30)), Some(1.0), now) > base);
+    }
+}
@@ -0,0 +1,153 @@
+//! DNS-over-HTTPS resolver for indexer clients
+//!
+//! Uses the JSON DoH API (`application/dns-json`) offered by Cloudflare,
+//! Google and most public resolvers, so tracker domains that are blocked by
+//! ISP DNS still resolve. Answers are cached for their TTL.
+
+use reqwest::Client;
+use reqwest::dns::{Addrs, Name, Resolve, Resolving};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+type BoxError = Box<dyn std::error::Error + Send + Sync>;
+/// Host -> (addresses, expiry)
+type Cache = HashMap<String, (Vec<IpAddr>, Instant)>;
+
+/// Lower bound for cached answers, so very short TTLs don't cause a lookup per request
+const MIN_TTL: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Deserialize)]
+struct DohResponse {
+    #[serde(rename = "Status")]
+    status: u32,
+    #[serde(rename = "Answer", default)]
+    answer: Vec<DohAnswer>,
+}
+
+#[derive(Debug, Deserialize)]
+struct DohAnswer {
+    #[serde(rename = "TTL", default)]
+    ttl: u64,
+    data: String,
+}
+
+#[derive(Clone)]
+pub struct DohResolver {
+    endpoint: String,
+    /// Plain client (system DNS) used to reach the DoH endpoint itself
+    client: Client,
+    cache: Arc<Mutex<Cache>>,
+}
+
+impl DohResolver {
+    pub fn new(endpoint: &str) -> Self {
+        Self {
+            endpoint: endpoint.to_string(),
+            client: Client::builder()
+                .timeout(Duration::from_secs(10))
+                .build()
+                .unwrap_or_default(),
+            cache: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// Resolve A records, falling back to AAAA
+    async fn lookup(&self, host: &str) -> Result<Vec<IpAddr>, BoxError> {
+        if let Some((addrs, expires)) = self.cache.lock().ok().and_then(|c| c.get(host).cloned())
+            && expires > Instant::now()
+        {
+            return Ok(addrs);
+        }
+
+        let mut result = self.query(host, "A").await?;
+        if result.0.is_empty() {
+            result = self.query(host, "AAAA").await?;
+        }
+        let (addrs, ttl) = result;
+        if addrs.is_empty() {
+            return Err(format!("DoH: no addresses for {}", host).into());
+        }
+
+        tracing::debug!("DoH resolved {} -> {:?}", host, addrs);
+        if let Ok(mut cache) = self.cache.lock() {
+            cache.insert(
+                host.to_string(),
+                (addrs.clone(), Instant::now() + ttl.max(MIN_TTL)),
+            );
+        }
+        Ok(addrs)
+    }
+
+    async fn query(&self, host: &str, record: &str) -> Result<(Vec<IpAddr>, Duration), BoxError> {
+        let response: DohResponse = self
+            .client
+            .get(&self.endpoint)
+            .query(&[("name", host), ("type", record)])
+            .header("Accept", "application/dns-json")
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        if response.status != 0 {
+            return Err(
+                format!("DoH lookup for {} failed (rcode {})", host, response.status).into(),
+            );
+        }
+        Ok(parse_answers(&response.answer))
+    }
+}
+
+/// Extract IP addresses (skipping CNAMEs etc.) and the shortest TTL
+fn parse_answers(answers: &[DohAnswer]) -> (Vec<IpAddr>, Duration) {
+    let ips: Vec<(IpAddr, u64)> = answers
+        .iter()
+        .filter_map(|a| a.data.parse().ok().map(|ip| (ip, a.ttl)))
+        .collect();
+    let ttl = ips.iter().map(|(_, t)| *t).min().unwrap_or(0);
+    (
+        ips.into_iter().map(|(ip, _)| ip).collect(),
+        Duration::from_secs(ttl),
+    )
+}
+
+impl Resolve for DohResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        let resolver = self.clone();
+        Box::pin(async move {
+            let addrs = resolver.lookup(name.as_str()).await?;
+            let addrs: Addrs = Box::new(addrs.into_iter().map(|ip| SocketAddr::new(ip, 0)));
+            Ok(addrs)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_answers() {
+        let response: DohResponse = serde_json::from_str(
+            r#"{"Status":0,"Answer":[
+                {"name":"tracker.example","type":5,"TTL":300,"data":"cdn.example."},
+                {"name":"cdn.example","type":1,"TTL":120,"data":"104.16.0.1"},
+                {"name":"cdn.example","type":1,"TTL":60,"data":"104.16.0.2"}
+            ]}"#,
+        )
+        .unwrap();
+
+        let (ips, ttl) = parse_answers(&response.answer);
+        assert_eq!(
+            ips,
+            vec![
+                "104.16.0.1".parse::<IpAddr>().unwrap(),
+                "104.16.0.2".parse().unwrap()
+            ]
+        );
+        assert_eq!(ttl, Duration::from_secs(60));
+    }
+}
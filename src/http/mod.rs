@@ -0,0 +1,257 @@
+//! Shared HTTP client tuning
+//!
+//! `SearchExecutor` and `TorznabClient` build their reqwest clients through
+//! [`client_builder`], which applies the `[http]` config section registered
+//! at startup and the proxy (required with `require_proxy`).
+
+mod doh;
+mod guard;
+mod throttle;
+pub mod traffic;
+
+pub use guard::ensure_proxy;
+pub use throttle::DomainMetrics;
+
+use crate::config::{Config, HttpConfig};
+use doh::DohResolver;
+use once_cell::sync::Lazy;
+use reqwest::ClientBuilder;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::Duration;
+
+struct Settings {
+    config: HttpConfig,
+    require_proxy: bool,
+    /// Shared so every client benefits from the same answer cache
+    resolver: Option<Arc<DohResolver>>,
+}
+
+static SETTINGS: Lazy<RwLock<Settings>> = Lazy::new(|| {
+    RwLock::new(Settings {
+        config: HttpConfig::default(),
+        require_proxy: false,
+        resolver: None,
+    })
+});
+
+/// Per-indexer circuit generation, bumped to force a fresh Tor circuit
+static CIRCUITS: Lazy<Mutex<HashMap<String, u32>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Register the settings used by clients created from now on
+pub fn configure(config: &Config) {
+    let resolver = config
+        .http
+        .doh_url
+        .as_deref()
+        .filter(|url| !url.is_empty())
+        .map(|url| {
+            tracing::info!("Resolving indexer hosts via DNS-over-HTTPS: {}", url);
+            Arc::new(DohResolver::new(url))
+        });
+    if let Ok(mut settings) = SETTINGS.write() {
+        *settings = Settings {
+            config: config.http.clone(),
+            require_proxy: config.require_proxy,
+            resolver,
+        };
+    }
+}
+
+/// Apply the configured tuning to a client builder
+pub fn tune(builder: ClientBuilder) -> ClientBuilder {
+    let Ok(settings) = SETTINGS.read() else {
+        return builder;
+    };
+    let mut builder = apply(builder, &settings.config);
+    if let Some(resolver) = &settings.resolver {
+        builder = builder.dns_resolver(resolver.clone());
+    }
+    builder
+}
+
+/// A tuned client builder going through `proxy_url`. Fails when
+/// `require_proxy` is on and there is no proxy, so no client goes direct.
+pub fn client_builder(
+    proxy_url: Option<&str>,
+    indexer_id: Option<&str>,
+) -> anyhow::Result<ClientBuilder> {
+    with_proxy(
+        tune(ClientBuilder::new()),
+        proxy_url,
+        indexer_id,
+        require_proxy(),
+    )
+}
+
+fn with_proxy(
+    builder: ClientBuilder,
+    proxy_url: Option<&str>,
+    indexer_id: Option<&str>,
+    required: bool,
+) -> anyhow::Result<ClientBuilder> {
+    match proxy_url.map(str::trim).filter(|url| !url.is_empty()) {
+        Some(url) => {
+            let proxy =
+                proxy(url, indexer_id).map_err(|e| anyhow::anyhow!("Invalid proxy URL: {}", e))?;
+            Ok(builder.proxy(proxy))
+        }
+        None if required => Err(guard::no_proxy()),
+        None => Ok(builder),
+    }
+}
+
+fn apply(mut builder: ClientBuilder, settings: &HttpConfig) -> ClientBuilder {
+    if !settings.http2 {
+        builder = builder.http1_only();
+    }
+    if let Some(secs) = settings.pool_idle_timeout_secs {
+        builder = builder.pool_idle_timeout(Duration::from_secs(secs));
+    }
+    if let Some(max) = settings.pool_max_idle_per_host {
+        builder = builder.pool_max_idle_per_host(max);
+    }
+    if let Some(secs) = settings.tcp_keepalive_secs {
+        builder = builder.tcp_keepalive(Duration::from_secs(secs));
+    }
+    builder
+}
+
+/// Whether `require_proxy` is on
+pub fn require_proxy() -> bool {
+    SETTINGS.read().is_ok_and(|s| s.require_proxy)
+}
+
+/// True if requests for `indexer_id` through `proxy_url` get their own Tor circuit
+pub fn isolates(proxy_url: Option<&str>, indexer_id: Option<&str>) -> bool {
+    let enabled = SETTINGS.read().is_ok_and(|s| s.config.tor_isolation);
+    enabled && indexer_id.is_some() && proxy_url.is_some_and(|url| url.starts_with("socks5"))
+}
+
+/// Build the proxy for a client. With Tor isolation, the SOCKS credentials
+/// identify the indexer (and circuit generation); Tor's IsolateSOCKSAuth
+/// puts each distinct username on its own circuit.
+pub fn proxy(proxy_url: &str, indexer_id: Option<&str>) -> anyhow::Result<reqwest::Proxy> {
+    let mut url = url::Url::parse(proxy_url)?;
+    if let Some(id) = indexer_id
+        && isolates(Some(proxy_url), indexer_id)
+    {
+        let generation = CIRCUITS
+            .lock()
+            .map(|c| c.get(id).copied().unwrap_or(0))
+            .unwrap_or(0);
+        let _ = url.set_username(&isolation_username(id, generation));
+        let _ = url.set_password(Some("lodestarr"));
+    }
+    Ok(reqwest::Proxy::all(url)?)
+}
+
+/// Switch an indexer to a new Tor circuit (NEWNYM for a single SOCKS identity)
+pub fn new_circuit(indexer_id: &str) {
+    if let Ok(mut circuits) = CIRCUITS.lock() {
+        *circuits.entry(indexer_id.to_string()).or_insert(0) += 1;
+    }
+    tracing::info!("Requesting a new Tor circuit for {}", indexer_id);
+}
+
+fn isolation_username(indexer_id: &str, generation: u32) -> String {
+    format!("lodestarr-{}-{}", indexer_id, generation)
+}
+
+/// True if the error chain contains a connection failure or timeout
+pub fn is_connection_error(e: &anyhow::Error) -> bool {
+    e.chain().any(|cause| {
+        cause
+            .downcast_ref::<reqwest::Error>()
+            .is_some_and(|e| e.is_connect() || e.is_timeout())
+    })
+}
+
+/// Count a request to `url` and wait for the per-domain request budget
+/// (`[http] domain_rate`) before it is sent. Only counts when throttling is
+/// disabled.
+pub async fn throttle(url: &str) {
+    let (rate, burst) = match SETTINGS.read() {
+        Ok(s) => (s.config.domain_rate, s.config.domain_burst),
+        Err(_) => return,
+    };
+    if let Some(host) = url::Url::parse(url)
+        .ok()
+        .and_then(|u| u.host_str().map(String::from))
+    {
+        throttle::acquire(&host, rate, burst).await;
+    }
+}
+
+/// Per-domain request counters
+pub fn domain_metrics() -> Vec<DomainMetrics> {
+    throttle::metrics()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::indexer::error::{ErrorKind, kind_of};
+
+    #[test]
+    fn test_with_proxy() {
+        let build =
+            |proxy_url, required| with_proxy(ClientBuilder::new(), proxy_url, None, required);
+        let e = build(None, true).unwrap_err();
+        assert_eq!(kind_of(&e), ErrorKind::ProxyDown);
+        assert_eq!(
+            kind_of(&build(Some(" "), true).unwrap_err()),
+            ErrorKind::ProxyDown
+        );
+        assert!(build(Some("socks5h://127.0.0.1:9050"), true).is_ok());
+        assert!(build(None, false).is_ok());
+        assert!(build(Some("not a url"), false).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_apply_pool_settings() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        // Keep-alive server counting the connections it accepts
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/", listener.local_addr().unwrap());
+        let accepted = Arc::new(AtomicUsize::new(0));
+        let counter = accepted.clone();
+        tokio::spawn(async move {
+            while let Ok((mut socket, _)) = listener.accept().await {
+                counter.fetch_add(1, Ordering::SeqCst);
+                tokio::spawn(async move {
+                    let mut buf = [0; 1024];
+                    while socket.read(&mut buf).await.is_ok_and(|n| n > 0) {
+                        let response = "HTTP/1.1 200 OK\r\nContent-Length: 2\r\n\r\nok";
+                        if socket.write_all(response.as_bytes()).await.is_err() {
+                            break;
+                        }
+                    }
+                });
+            }
+        });
+
+        let connections = |config: HttpConfig| {
+            let url = url.clone();
+            let accepted = accepted.clone();
+            async move {
+                let before = accepted.load(Ordering::SeqCst);
+                let client = apply(ClientBuilder::new(), &config).build().unwrap();
+                for _ in 0..2 {
+                    let body = client.get(&url).send().await.unwrap().text().await.unwrap();
+                    assert_eq!(body, "ok");
+                }
+                accepted.load(Ordering::SeqCst) - before
+            }
+        };
+        // Idle connections are reused by default, and not when none may be kept
+        assert_eq!(connections(HttpConfig::default()).await, 1);
+        let no_pool = HttpConfig {
+            pool_max_idle_per_host: Some(0),
+            ..Default::default()
+        };
+        assert_eq!(connections(no_pool).await, 2);
+    }
+}
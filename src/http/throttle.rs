@@ -0,0 +1,150 @@
+//! Per-domain request budget
+//!
+//! Every tracker-facing request (searches, downloads, caps, cookie pre-fetches)
+//! takes a token from its domain's bucket before it is sent, so concurrent
+//! subsystems share one budget instead of each hammering the tracker.
+
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+static BUCKETS: Lazy<Mutex<HashMap<String, Bucket>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
+    requests: u64,
+    throttled: u64,
+    waited: Duration,
+}
+
+/// Counters for one domain, exported via `/api/stats`
+#[derive(Debug, Clone, Serialize)]
+pub struct DomainMetrics {
+    pub domain: String,
+    pub requests: u64,
+    pub throttled: u64,
+    pub wait_ms: u64,
+}
+
+/// Take a token for `domain`, returning how long the caller must wait.
+///
+/// Tokens are reserved up front (the bucket may go negative), so concurrent
+/// callers queue up behind each other instead of all waking at once.
+fn reserve(domain: &str, rate: f64, burst: f64, now: Instant) -> Duration {
+    let Ok(mut buckets) = BUCKETS.lock() else {
+        return Duration::ZERO;
+    };
+    let bucket = buckets.entry(domain.to_string()).or_insert(Bucket {
+        tokens: burst,
+        updated: now,
+        requests: 0,
+        throttled: 0,
+        waited: Duration::ZERO,
+    });
+
+    let elapsed = now.saturating_duration_since(bucket.updated).as_secs_f64();
+    bucket.tokens = (bucket.tokens + elapsed * rate).min(burst);
+    bucket.updated = now;
+    bucket.tokens -= 1.0;
+    bucket.requests += 1;
+
+    if bucket.tokens >= 0.0 {
+        return Duration::ZERO;
+    }
+    let wait = Duration::from_secs_f64(-bucket.tokens / rate);
+    bucket.throttled += 1;
+    bucket.waited += wait;
+    wait
+}
+
+/// Count a request to `domain` without limiting it
+fn count(domain: &str, now: Instant) {
+    let Ok(mut buckets) = BUCKETS.lock() else {
+        return;
+    };
+    buckets
+        .entry(domain.to_string())
+        .or_insert(Bucket {
+            // Full, whatever burst a later budget has
+            tokens: f64::INFINITY,
+            updated: now,
+            requests: 0,
+            throttled: 0,
+            waited: Duration::ZERO,
+        })
+        .requests += 1;
+}
+
+/// Wait for the domain's budget. `rate` is requests per second; without one
+/// the request is only counted.
+pub async fn acquire(domain: &str, rate: Option<f64>, burst: u32) {
+    let Some(rate) = rate.filter(|rate| *rate > 0.0) else {
+        count(domain, Instant::now());
+        return;
+    };
+    let wait = reserve(domain, rate, f64::from(burst.max(1)), Instant::now());
+    if !wait.is_zero() {
+        tracing::debug!("Throttling request to {} for {:?}", domain, wait);
+        tokio::time::sleep(wait).await;
+    }
+}
+
+/// Snapshot of the per-domain counters, busiest first
+pub fn metrics() -> Vec<DomainMetrics> {
+    let Ok(buckets) = BUCKETS.lock() else {
+        return Vec::new();
+    };
+    let mut metrics: Vec<DomainMetrics> = buckets
+        .iter()
+        .map(|(domain, b)| DomainMetrics {
+            domain: domain.clone(),
+            requests: b.requests,
+            throttled: b.throttled,
+            wait_ms: b.waited.as_millis() as u64,
+        })
+        .collect();
+    metrics.sort_by_key(|m| std::cmp::Reverse(m.requests));
+    metrics
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bucket_reserves_in_order() {
+        let now = Instant::now();
+        let domain = "throttle-test.example";
+
+        // Burst of 2 goes through immediately
+        assert_eq!(reserve(domain, 1.0, 2.0, now), Duration::ZERO);
+        assert_eq!(reserve(domain, 1.0, 2.0, now), Duration::ZERO);
+        // Further requests queue one second apart
+        assert_eq!(reserve(domain, 1.0, 2.0, now), Duration::from_secs(1));
+        assert_eq!(reserve(domain, 1.0, 2.0, now), Duration::from_secs(2));
+        // Tokens refill over time
+        let later = now + Duration::from_secs(5);
+        assert_eq!(reserve(domain, 1.0, 2.0, later), Duration::ZERO);
+
+        let m = metrics().into_iter().find(|m| m.domain == domain).unwrap();
+        assert_eq!((m.requests, m.throttled, m.wait_ms), (5, 2, 3000));
+    }
+
+    #[tokio::test]
+    async fn test_unthrottled_requests_are_counted() {
+        let domain = "unthrottled-test.example";
+        acquire(domain, None, 1).await;
+        acquire(domain, None, 1).await;
+        let m = metrics().into_iter().find(|m| m.domain == domain).unwrap();
+        assert_eq!((m.requests, m.throttled, m.wait_ms), (2, 0, 0));
+
+        // A budget set later starts from a full bucket
+        let now = Instant::now();
+        assert_eq!(reserve(domain, 1.0, 2.0, now), Duration::ZERO);
+        assert_eq!(reserve(domain, 1.0, 2.0, now), Duration::ZERO);
+        assert_eq!(reserve(domain, 1.0, 2.0, now), Duration::from_secs(1));
+    }
+}
@@ -0,0 +1,44 @@
+//! Bytes received from indexers, metered per search or download
+//!
+//! Code reading an indexer response calls [`add`]. The bytes count toward
+//! the enclosing [`metered`] future, so concurrent searches of different
+//! indexers don't mix their totals.
+
+use std::future::Future;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+tokio::task_local! {
+    static METER: Arc<AtomicU64>;
+}
+
+/// Count a response body toward the current [`metered`] call, if any
+pub fn add(bytes: usize) {
+    let _ = METER.try_with(|meter| meter.fetch_add(bytes as u64, Ordering::Relaxed));
+}
+
+/// Run `future` and return its output with the bytes it received
+pub async fn metered<F: Future>(future: F) -> (F::Output, u64) {
+    let meter = Arc::new(AtomicU64::new(0));
+    let output = METER.scope(meter.clone(), future).await;
+    (output, meter.load(Ordering::Relaxed))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_metered() {
+        let (_, a) = metered(async {
+            add(100);
+            let (_, inner) = metered(async { add(5) }).await;
+            assert_eq!(inner, 5);
+            add(20);
+        })
+        .await;
+        assert_eq!(a, 120);
+        // Outside a metered call bytes are simply dropped
+        add(1);
+    }
+}
@@ -0,0 +1,339 @@
+//! Anime-aware searching for queries in the TV/Anime category (5070)
+//!
+//! Fansub releases are usually named by romaji title and absolute episode
+//! number ("[SubsPlease] Sousou no Frieren - 18 (1080p)"), which a plain
+//! "Frieren: Beyond Journey's End S02E05" search never finds. For such
+//! queries the series is looked up on AniList, and the search is repeated
+//! with its romaji/English titles and the absolute episode number. Results
+//! get the fansub group and a `batch` flag parsed from their titles.
+
+use crate::models::{SearchQuery, SearchType, TorrentResult};
+use anyhow::Result;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use reqwest::Client;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+/// Torznab TV/Anime
+pub const ANIME_CATEGORY: i32 = 5070;
+
+const ANILIST_URL: &str = "https://graphql.anilist.co";
+/// Searches run per query at most, the original included
+const MAX_VARIANTS: usize = 5;
+/// Seasons walked through AniList's sequel relations at most
+const MAX_SEASONS: u32 = 12;
+/// Remembered lookups (including misses) before the cache resets
+const CACHE_SIZE: usize = 500;
+
+static CACHE: Lazy<Mutex<HashMap<String, Option<AnimeInfo>>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// What AniList knows about a series
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct AnimeInfo {
+    /// Romaji, English and synonyms, most useful first
+    pub titles: Vec<String>,
+    /// Episode counts of the first season and its TV sequels, in order
+    pub season_episodes: Vec<u32>,
+}
+
+impl AnimeInfo {
+    /// Absolute number of `episode` in `season` (1-based)
+    pub fn absolute_episode(&self, season: Option<u32>, episode: u32) -> Option<u32> {
+        let season = season.unwrap_or(1).max(1) as usize;
+        if season == 1 {
+            return Some(episode);
+        }
+        let earlier = self.season_episodes.get(..season - 1)?;
+        Some(earlier.iter().sum::<u32>() + episode)
+    }
+}
+
+/// Whether the query asks for anime
+pub fn is_anime(query: &SearchQuery) -> bool {
+    query.categories.contains(&ANIME_CATEGORY)
+        && matches!(query.search_type, SearchType::Search | SearchType::TvSearch)
+        && query.query.as_deref().is_some_and(|q| !q.trim().is_empty())
+}
+
+/// Look up a series on AniList (cached)
+pub async fn lookup(client: &Client, title: &str) -> Option<AnimeInfo> {
+    let key = title.to_lowercase();
+    if let Some(cached) = CACHE.lock().ok().and_then(|c| c.get(&key).cloned()) {
+        return cached;
+    }
+    let info = match fetch_info(client, title).await {
+        Ok(info) => info,
+        Err(e) => {
+            // Not cached, so the next search tries again
+            tracing::debug!("AniList lookup for '{}' failed: {}", title, e);
+            return None;
+        }
+    };
+    if let Ok(mut cache) = CACHE.lock() {
+        if cache.len() >= CACHE_SIZE {
+            cache.clear();
+        }
+        cache.insert(key, info.clone());
+    }
+    info
+}
+
+/// Queries to run for an anime search: the original, the same search under
+/// the other titles, and free-text "<title> <absolute episode>" searches
+pub fn variants(query: &SearchQuery, info: Option<&AnimeInfo>) -> Vec<SearchQuery> {
+    let original = query.query.clone().unwrap_or_default();
+    let mut titles = vec![original.clone()];
+    for title in info.map(|i| i.titles.as_slice()).unwrap_or_default() {
+        if !titles.iter().any(|t| t.eq_ignore_ascii_case(title)) {
+            titles.push(title.clone());
+        }
+    }
+
+    let mut out = vec![query.clone()];
+    let absolute = query.episode.and_then(|ep| match info {
+        Some(info) => info.absolute_episode(query.season, ep),
+        None if query.season.unwrap_or(1) <= 1 => Some(ep),
+        None => None,
+    });
+    if let Some(absolute) = absolute {
+        for title in &titles {
+            out.push(SearchQuery {
+                search_type: SearchType::Search,
+                query: Some(format!("{} {:02}", title, absolute)),
+                season: None,
+                episode: None,
+                ..query.clone()
+            });
+        }
+    }
+    for title in titles.iter().skip(1) {
+        out.push(SearchQuery {
+            query: Some(title.clone()),
+            ..query.clone()
+        });
+    }
+    out.truncate(MAX_VARIANTS);
+    out
+}
+
+/// Record the fansub group and batch releases from result titles
+pub fn tag_results(results: &mut [TorrentResult]) {
+    for result in results {
+        if result.release_group.is_none() {
+            result.release_group = fansub_group(&result.title);
+        }
+        if is_batch(&result.title) && !result.flags.iter().any(|f| f == "batch") {
+            result.flags.push("batch".to_string());
+        }
+    }
+}
+
+/// Leading "[Group]" tag of a fansub release
+pub fn fansub_group(title: &str) -> Option<String> {
+    static GROUP: Lazy<Regex> =
+        Lazy::new(|| Regex::new(r"^\s*\[([^\]]+)\]").expect("invalid group regex"));
+    let group = GROUP.captures(title)?.get(1)?.as_str().trim();
+    // "[1080p]" or "[HEVC]" at the start are not groups
+    let lower = group.to_lowercase();
+    let technical = lower.ends_with('p') && lower[..lower.len() - 1].parse::<u32>().is_ok()
+        || ["hevc", "x264", "x265", "multi", "raw"].contains(&lower.as_str());
+    (!group.is_empty() && !technical).then(|| group.to_string())
+}
+
+/// Whether the title names a batch (several episodes or a whole season)
+pub fn is_batch(title: &str) -> bool {
+    static WORDS: Lazy<Regex> =
+        Lazy::new(|| Regex::new(r"(?i)\b(?:batch|complete)\b").expect("invalid batch regex"));
+    static RANGE: Lazy<Regex> = Lazy::new(|| {
+        Regex::new(r"\b(\d{1,4})\s*[-~]\s*(\d{1,4})\b").expect("invalid episode range regex")
+    });
+    // An episode range counts up ("01 ~ 28"); "2024 - 05" is a year and episode
+    WORDS.is_match(title)
+        || RANGE.captures_iter(title).any(|c| {
+            let first = c[1].parse::<u32>().unwrap_or(0);
+            let last = c[2].parse::<u32>().unwrap_or(0);
+            first < last
+        })
+}
+
+const MEDIA_QUERY: &str = r#"
+query ($search: String, $id: Int) {
+  Media(search: $search, id: $id, type: ANIME) {
+    id
+    format
+    episodes
+    title { romaji english }
+    synonyms
+    relations { edges { relationType node { id type format episodes } } }
+  }
+}"#;
+
+#[derive(Deserialize)]
+struct Response {
+    data: Option<Data>,
+}
+
+#[derive(Deserialize)]
+struct Data {
+    #[serde(rename = "Media")]
+    media: Option<Media>,
+}
+
+#[derive(Deserialize)]
+struct Media {
+    #[serde(default)]
+    episodes: Option<u32>,
+    #[serde(default)]
+    title: Titles,
+    #[serde(default)]
+    synonyms: Vec<String>,
+    #[serde(default)]
+    relations: Relations,
+}
+
+#[derive(Deserialize, Default)]
+struct Titles {
+    romaji: Option<String>,
+    english: Option<String>,
+}
+
+#[derive(Deserialize, Default)]
+struct Relations {
+    #[serde(default)]
+    edges: Vec<Edge>,
+}
+
+#[derive(Deserialize)]
+struct Edge {
+    #[serde(rename = "relationType")]
+    relation_type: String,
+    node: Node,
+}
+
+#[derive(Deserialize)]
+struct Node {
+    id: i64,
+    #[serde(rename = "type")]
+    kind: Option<String>,
+    format: Option<String>,
+}
+
+async fn fetch_media(client: &Client, variables: serde_json::Value) -> Result<Option<Media>> {
+    crate::http::throttle(ANILIST_URL).await;
+    let response: Response = client
+        .post(ANILIST_URL)
+        .json(&serde_json::json!({ "query": MEDIA_QUERY, "variables": variables }))
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+    Ok(response.data.and_then(|d| d.media))
+}
+
+async fn fetch_info(client: &Client, title: &str) -> Result<Option<AnimeInfo>> {
+    let Some(first) = fetch_media(client, serde_json::json!({ "search": title })).await? else {
+        return Ok(None);
+    };
+
+    let mut titles: Vec<String> = [first.title.romaji.clone(), first.title.english.clone()]
+        .into_iter()
+        .flatten()
+        .chain(first.synonyms.iter().cloned())
+        .filter(|t| t.is_ascii() && !t.trim().is_empty())
+        .collect();
+    titles.dedup();
+
+    // Follow TV sequels for the later seasons' episode offsets
+    let mut season_episodes = Vec::new();
+    let mut current = first;
+    while let Some(episodes) = current.episodes {
+        season_episodes.push(episodes);
+        if season_episodes.len() as u32 >= MAX_SEASONS {
+            break;
+        }
+        let sequel = current.relations.edges.iter().find(|e| {
+            e.relation_type == "SEQUEL"
+                && e.node.kind.as_deref() == Some("ANIME")
+                && e.node.format.as_deref() == Some("TV")
+        });
+        let Some(sequel) = sequel else {
+            break;
+        };
+        match fetch_media(client, serde_json::json!({ "id": sequel.node.id })).await? {
+            Some(next) => current = next,
+            None => break,
+        }
+    }
+
+    Ok(Some(AnimeInfo {
+        titles,
+        season_episodes,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_variants() {
+        let query = SearchQuery {
+            search_type: SearchType::TvSearch,
+            query: Some("Frieren".to_string()),
+            categories: vec![5000, ANIME_CATEGORY],
+            season: Some(2),
+            episode: Some(5),
+            ..Default::default()
+        };
+        assert!(is_anime(&query));
+        let info = AnimeInfo {
+            titles: vec![
+                "Sousou no Frieren".to_string(),
+                "Frieren: Beyond Journey's End".to_string(),
+            ],
+            season_episodes: vec![28, 10],
+        };
+
+        let variants = variants(&query, Some(&info));
+        let queries: Vec<_> = variants
+            .iter()
+            .map(|q| q.query.as_deref().unwrap())
+            .collect();
+        assert_eq!(
+            queries,
+            vec![
+                "Frieren",
+                "Frieren 33",
+                "Sousou no Frieren 33",
+                "Frieren: Beyond Journey's End 33",
+                "Sousou no Frieren",
+            ]
+        );
+        assert_eq!(variants[1].search_type, SearchType::Search);
+        assert_eq!(variants[1].episode, None);
+        assert_eq!(variants[4].season, Some(2));
+
+        // Season 2 without AniList data has no known absolute number
+        assert_eq!(super::variants(&query, None).len(), 1);
+    }
+
+    #[test]
+    fn test_fansub_tags() {
+        assert_eq!(
+            fansub_group("[SubsPlease] Sousou no Frieren - 18 (1080p) [ABCD1234].mkv").as_deref(),
+            Some("SubsPlease")
+        );
+        assert_eq!(fansub_group("[1080p] Something"), None);
+        assert_eq!(fansub_group("Sousou no Frieren S01E18"), None);
+
+        assert!(is_batch("[Judas] Sousou no Frieren (Season 1) [Batch]"));
+        assert!(is_batch("[Erai-raws] Frieren - 01 ~ 28 [1080p]"));
+        assert!(is_batch("Frieren Complete Series"));
+        assert!(!is_batch("[SubsPlease] Sousou no Frieren - 18 (1080p)"));
+        assert!(!is_batch("[Group] Title 2024 - 05 [1080p]"));
+    }
+}
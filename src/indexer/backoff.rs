@@ -0,0 +1,122 @@
+//! Per-indexer cooldowns after a tracker rate-limits us
+//!
+//! A 429 (or a 503 with `Retry-After`) puts the indexer on cooldown for the
+//! requested time. Aggregate searches skip it until then instead of hitting
+//! it again and risking a ban.
+
+use super::error;
+use chrono::{DateTime, Utc};
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// Cooldown when the tracker doesn't say how long to wait
+const DEFAULT_COOLDOWN: Duration = Duration::from_secs(60);
+/// Longest cooldown honoured, whatever the tracker asks for
+const MAX_COOLDOWN: Duration = Duration::from_secs(3600);
+
+static COOLDOWNS: Lazy<Mutex<HashMap<String, DateTime<Utc>>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// An indexer waiting out a rate limit
+#[derive(Debug, Clone, Serialize)]
+pub struct Cooldown {
+    pub indexer: String,
+    pub until: DateTime<Utc>,
+    pub remaining_secs: u64,
+}
+
+/// Put an indexer on cooldown
+pub fn record(indexer: &str, retry_after: Option<Duration>) {
+    let wait = retry_after.unwrap_or(DEFAULT_COOLDOWN).min(MAX_COOLDOWN);
+    let until = Utc::now() + chrono::Duration::from_std(wait).unwrap_or_default();
+    tracing::warn!(
+        "{} is rate limiting us, pausing it for {}s",
+        indexer,
+        wait.as_secs()
+    );
+    if let Ok(mut cooldowns) = COOLDOWNS.lock() {
+        cooldowns.insert(indexer.to_string(), until);
+    }
+}
+
+/// Record a cooldown if the error is a rate limit
+pub fn note(indexer: &str, e: &anyhow::Error) {
+    if let Some(retry_after) = error::retry_after_of(e) {
+        record(indexer, retry_after);
+    }
+}
+
+/// Time left on an indexer's cooldown
+pub fn remaining(indexer: &str) -> Option<Duration> {
+    let mut cooldowns = COOLDOWNS.lock().ok()?;
+    let until = *cooldowns.get(indexer)?;
+    match (until - Utc::now()).to_std() {
+        Ok(left) if !left.is_zero() => Some(left),
+        _ => {
+            cooldowns.remove(indexer);
+            None
+        }
+    }
+}
+
+/// Rate-limit error for an indexer that is still cooling down
+pub fn check(indexer: &str) -> anyhow::Result<()> {
+    match remaining(indexer) {
+        Some(left) => Err(error::IndexerError::RateLimited {
+            message: format!(
+                "{} is rate limited, retrying in {}s",
+                indexer,
+                left.as_secs().max(1)
+            ),
+            // Same deadline, so noting this error again doesn't extend it
+            retry_after: Some(left),
+        }
+        .into()),
+        None => Ok(()),
+    }
+}
+
+/// Indexers currently on cooldown
+pub fn active() -> Vec<Cooldown> {
+    let now = Utc::now();
+    let Ok(mut cooldowns) = COOLDOWNS.lock() else {
+        return Vec::new();
+    };
+    cooldowns.retain(|_, until| *until > now);
+    let mut active: Vec<Cooldown> = cooldowns
+        .iter()
+        .map(|(indexer, until)| Cooldown {
+            indexer: indexer.clone(),
+            until: *until,
+            remaining_secs: (*until - now).num_seconds().max(0) as u64,
+        })
+        .collect();
+    active.sort_by_key(|c| c.until);
+    active
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cooldown() {
+        assert!(remaining("backoff-test").is_none());
+        assert!(check("backoff-test").is_ok());
+
+        record("backoff-test", Some(Duration::from_secs(7200)));
+        let left = remaining("backoff-test").unwrap();
+        assert!(left <= MAX_COOLDOWN && left > MAX_COOLDOWN - Duration::from_secs(5));
+        assert_eq!(
+            error::kind_of(&check("backoff-test").unwrap_err()),
+            error::ErrorKind::RateLimited
+        );
+        assert!(active().iter().any(|c| c.indexer == "backoff-test"));
+
+        record("backoff-test", Some(Duration::ZERO));
+        assert!(remaining("backoff-test").is_none());
+    }
+}
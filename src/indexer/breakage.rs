@@ -0,0 +1,82 @@
+//! "Probably broken" detection for native definitions
+//!
+//! When a tracker changes its layout, the definition usually keeps
+//! answering searches, just with no rows. Each indexer's latest searches are
+//! compared with its own history: if the last few all came back empty while
+//! it normally finds something for most queries, it is flagged so the user
+//! updates the definition.
+
+use serde::Serialize;
+use std::collections::HashMap;
+
+/// Consecutive empty searches before an indexer is suspected
+const EMPTY_STREAK: usize = 8;
+/// Earlier searches needed to know what's normal for the indexer
+const MIN_BASELINE: usize = 10;
+/// Indexers that usually find nothing this often aren't flagged
+const MAX_BASELINE_EMPTY_RATE: f64 = 0.5;
+/// How far back searches are considered
+pub const WINDOW_DAYS: i64 = 30;
+
+/// Why an indexer is considered probably broken
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Breakage {
+    /// Latest searches in a row that returned nothing
+    pub empty_streak: usize,
+    /// Searches before the streak
+    pub baseline_searches: usize,
+    /// Share of those that returned nothing (0-1)
+    pub baseline_empty_rate: f64,
+}
+
+/// Judge an indexer by the result counts of its successful searches,
+/// newest first
+pub fn assess(counts: &[usize]) -> Option<Breakage> {
+    let empty_streak = counts.iter().take_while(|&&c| c == 0).count();
+    let baseline = &counts[empty_streak..];
+    if empty_streak < EMPTY_STREAK || baseline.len() < MIN_BASELINE {
+        return None;
+    }
+    let empty = baseline.iter().filter(|&&c| c == 0).count();
+    let baseline_empty_rate = empty as f64 / baseline.len() as f64;
+    (baseline_empty_rate <= MAX_BASELINE_EMPTY_RATE).then_some(Breakage {
+        empty_streak,
+        baseline_searches: baseline.len(),
+        baseline_empty_rate,
+    })
+}
+
+/// Indexers that are probably broken, by id (empty if the database fails)
+pub fn probably_broken(pool: &crate::db::DbPool) -> HashMap<String, Breakage> {
+    let since = chrono::Utc::now() - chrono::Duration::days(WINDOW_DAYS);
+    let counts = match crate::db::get_indexer_result_counts(pool, since) {
+        Ok(counts) => counts,
+        Err(e) => {
+            tracing::debug!("Failed to read indexer result counts: {}", e);
+            return HashMap::new();
+        }
+    };
+    counts
+        .into_iter()
+        .filter_map(|(indexer, counts)| assess(&counts).map(|b| (indexer, b)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_assess() {
+        let healthy = [vec![0; 8], vec![12; 20]].concat();
+        let broken = assess(&healthy).unwrap();
+        assert_eq!(broken.empty_streak, 8);
+        assert_eq!(broken.baseline_searches, 20);
+
+        // Too short a streak, too little history, or often empty anyway
+        assert!(assess(&[vec![0; 7], vec![12; 20]].concat()).is_none());
+        assert!(assess(&[vec![0; 8], vec![12; 9]].concat()).is_none());
+        assert!(assess(&[vec![0; 8], [3, 0, 0].repeat(5)].concat()).is_none());
+        assert!(assess(&[vec![5], vec![0; 8], vec![12; 20]].concat()).is_none());
+    }
+}
@@ -0,0 +1,269 @@
+//! Category mapping fallback (`_categoryFallback`)
+//!
+//! A search in a Torznab category the tracker has no mapping for, such as
+//! 5030 (TV/SD), is sent as a nearby category instead. How far that goes is
+//! set per indexer:
+//!
+//! - `parent` (the default): the parent category's mapping (5030 → 5000)
+//! - `all`: every mapping under the parent (5000-5999)
+//! - `strict`: nothing; a search in only unmapped categories finds nothing
+//!
+//! Results of a search that fell back and aren't in any of the requested
+//! categories are marked with `category_fallback`, so clients can tell them
+//! apart from exact matches.
+//!
+//! With `_trackerCategories = true`, an indexer's results also carry the
+//! tracker's own category as `100000 + id`, the custom category Jackett
+//! emits, and its caps list those categories. Searches in a custom category
+//! always go to that tracker category. The same custom id means a different
+//! category on each indexer, so aggregate feeds neither list nor take them.
+
+use super::definition::IndexerDefinition;
+use crate::models::TorrentResult;
+use std::collections::HashMap;
+
+/// Setting choosing an indexer's fallback
+pub const SETTING: &str = "_categoryFallback";
+
+/// Setting adding tracker-specific categories to results and caps
+pub const TRACKER_SETTING: &str = "_trackerCategories";
+
+/// First custom category; tracker category `n` is `CUSTOM_BASE + n`
+pub const CUSTOM_BASE: i32 = 100_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CategoryFallback {
+    Strict,
+    #[default]
+    Parent,
+    All,
+}
+
+impl CategoryFallback {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "strict" => Some(Self::Strict),
+            "parent" => Some(Self::Parent),
+            "all" => Some(Self::All),
+            _ => None,
+        }
+    }
+
+    /// The indexer's fallback, `parent` when unset or invalid
+    pub fn of(user_settings: Option<&HashMap<String, String>>) -> Self {
+        user_settings
+            .and_then(|s| s.get(SETTING))
+            .and_then(|v| Self::parse(v))
+            .unwrap_or_default()
+    }
+}
+
+/// Tracker categories a Torznab category is searched as
+#[derive(Debug, Default, PartialEq)]
+pub struct Mapped {
+    pub ids: Vec<String>,
+    /// True when the category has no mapping of its own
+    pub fallback: bool,
+}
+
+pub fn tracker_categories(
+    definition: &IndexerDefinition,
+    torznab_cat: i32,
+    fallback: CategoryFallback,
+) -> Mapped {
+    let mapped = |matches: &dyn Fn(i32) -> bool| -> Vec<String> {
+        definition
+            .caps
+            .categorymappings
+            .iter()
+            .filter(|m| {
+                IndexerDefinition::resolve_torznab_category_name(&m.cat).is_some_and(matches)
+            })
+            .map(|m| m.id.to_string())
+            .collect()
+    };
+
+    if torznab_cat >= CUSTOM_BASE {
+        let tracker_cat = (torznab_cat - CUSTOM_BASE).to_string();
+        let known = definition
+            .caps
+            .categorymappings
+            .iter()
+            .any(|m| m.id.to_string() == tracker_cat);
+        return Mapped {
+            ids: if known { vec![tracker_cat] } else { Vec::new() },
+            fallback: false,
+        };
+    }
+    if let Some(exact) = mapped(&|id| id == torznab_cat).into_iter().next() {
+        return Mapped {
+            ids: vec![exact],
+            fallback: false,
+        };
+    }
+    let parent = (torznab_cat / 1000) * 1000;
+    let ids = match fallback {
+        CategoryFallback::Strict => Vec::new(),
+        _ if parent == torznab_cat => Vec::new(),
+        CategoryFallback::Parent => mapped(&|id| id == parent).into_iter().take(1).collect(),
+        CategoryFallback::All => mapped(&|id| id / 1000 * 1000 == parent),
+    };
+    Mapped {
+        fallback: !ids.is_empty(),
+        ids,
+    }
+}
+
+/// Whether an indexer's results and caps include its tracker categories
+pub fn tracker_categories_enabled(user_settings: Option<&HashMap<String, String>>) -> bool {
+    user_settings
+        .and_then(|s| s.get(TRACKER_SETTING))
+        .is_some_and(|v| v == "true")
+}
+
+/// Custom category of a tracker category, if the tracker's id is a number
+pub fn custom_category(tracker_cat: &str) -> Option<i32> {
+    let id: i32 = tracker_cat.trim().parse().ok().filter(|id| *id >= 0)?;
+    CUSTOM_BASE.checked_add(id)
+}
+
+/// Custom categories of a definition with their names, for caps
+pub fn custom_categories(definition: &IndexerDefinition) -> Vec<(i32, String)> {
+    let mut custom: Vec<(i32, String)> = Vec::new();
+    for mapping in &definition.caps.categorymappings {
+        let Some(id) = custom_category(&mapping.id.to_string()) else {
+            continue;
+        };
+        if custom.iter().any(|(known, _)| *known == id) {
+            continue;
+        }
+        let name = mapping.desc.clone().unwrap_or_else(|| mapping.cat.clone());
+        custom.push((id, name));
+    }
+    custom.sort();
+    custom
+}
+
+/// `cat` (comma-separated) without custom categories, and whether it had any
+pub fn without_custom(cat: Option<&str>) -> (Option<String>, bool) {
+    let Some(cat) = cat else {
+        return (None, false);
+    };
+    let (custom, standard): (Vec<&str>, Vec<&str>) = cat
+        .split(',')
+        .map(str::trim)
+        .filter(|c| !c.is_empty())
+        .partition(|c| c.parse::<i32>().is_ok_and(|id| id >= CUSTOM_BASE));
+    let standard = (!standard.is_empty()).then(|| standard.join(","));
+    (standard, !custom.is_empty())
+}
+
+/// Drop the custom categories from results
+pub fn strip_custom(results: &mut [TorrentResult]) {
+    for result in results {
+        result.categories.retain(|&c| c < CUSTOM_BASE);
+    }
+}
+
+/// Mark the results that aren't in any of the `requested` categories
+pub fn mark_fallback(results: &mut [TorrentResult], requested: &[i32]) {
+    for result in results {
+        if !result.categories.iter().any(|c| requested.contains(c)) {
+            result.category_fallback = true;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tracker_categories() {
+        let definition = IndexerDefinition::from_yaml(
+            r#"
+id: cats
+name: Cats
+links: ["https://tracker.example/"]
+caps:
+  categorymappings:
+    - {id: 1, cat: TV}
+    - {id: 2, cat: TV/HD}
+    - {id: 3, cat: TV/Anime}
+    - {id: 4, cat: Movies}
+search:
+  paths: [{path: search}]
+  rows: {selector: tr}
+  fields: {title: {selector: a}}
+"#,
+        )
+        .unwrap();
+        let ids = |cat, fallback| {
+            let mapped = tracker_categories(&definition, cat, fallback);
+            (mapped.ids, mapped.fallback)
+        };
+
+        assert_eq!(
+            ids(5040, CategoryFallback::Strict),
+            (vec!["2".into()], false)
+        );
+        // TV/SD isn't mapped
+        assert_eq!(ids(5030, CategoryFallback::Strict), (vec![], false));
+        assert_eq!(
+            ids(5030, CategoryFallback::Parent),
+            (vec!["1".into()], true)
+        );
+        assert_eq!(
+            ids(5030, CategoryFallback::All),
+            (vec!["1".into(), "2".into(), "3".into()], true)
+        );
+        assert_eq!(ids(3000, CategoryFallback::All), (vec![], false));
+        // Custom categories go straight to the tracker category
+        assert_eq!(
+            ids(100_003, CategoryFallback::Strict),
+            (vec!["3".into()], false)
+        );
+        assert_eq!(ids(100_009, CategoryFallback::All), (vec![], false));
+        assert_eq!(custom_category("42"), Some(100_042));
+        assert_eq!(custom_category("tv"), None);
+        assert_eq!(
+            custom_categories(&definition)[2],
+            (100_003, "TV/Anime".to_string())
+        );
+        assert_eq!(
+            without_custom(Some("5000, 100003,5040")),
+            (Some("5000,5040".to_string()), true)
+        );
+        assert_eq!(without_custom(Some("100003")), (None, true));
+        assert_eq!(
+            without_custom(Some("2000")),
+            (Some("2000".to_string()), false)
+        );
+        let mut results = [TorrentResult {
+            categories: vec![5040, 100_007],
+            ..Default::default()
+        }];
+        strip_custom(&mut results);
+        assert_eq!(results[0].categories, [5040]);
+
+        assert_eq!(
+            CategoryFallback::parse(" All "),
+            Some(CategoryFallback::All)
+        );
+        assert_eq!(CategoryFallback::of(None), CategoryFallback::Parent);
+
+        let mut results = vec![
+            TorrentResult {
+                categories: vec![5030],
+                ..Default::default()
+            },
+            TorrentResult {
+                categories: vec![5070],
+                ..Default::default()
+            },
+        ];
+        mark_fallback(&mut results, &[5030]);
+        assert!(!results[0].category_fallback);
+        assert!(results[1].category_fallback);
+    }
+}
@@ -0,0 +1,114 @@
+//! Detection of anti-bot challenge pages (Cloudflare, DDoS-Guard)
+//!
+//! Protected trackers answer with a JS/captcha challenge instead of results.
+//! Recognising these lets us report "protected" rather than "0 results".
+
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Protection {
+    Cloudflare,
+    DdosGuard,
+}
+
+impl fmt::Display for Protection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Protection::Cloudflare => "Cloudflare",
+            Protection::DdosGuard => "DDoS-Guard",
+        })
+    }
+}
+
+/// A request was answered with a challenge page
+#[derive(Debug, thiserror::Error)]
+#[error(
+    "{url} is protected by a {protection} challenge - needs FlareSolverr or a browser cookie (cookie setting)"
+)]
+pub struct ChallengeError {
+    pub protection: Protection,
+    pub url: String,
+}
+
+/// Markers that only appear on challenge pages (not on normal pages served
+/// through the same CDN). `/cdn-cgi/challenge-platform/` isn't one: Cloudflare
+/// injects that script into ordinary pages too.
+const CLOUDFLARE_MARKERS: &[&str] = &[
+    "cf-browser-verification",
+    "cf_chl_opt",
+    "<title>Just a moment...</title>",
+    "<title>Attention Required! | Cloudflare</title>",
+];
+const DDOS_GUARD_MARKERS: &[&str] = &[
+    "check.ddos-guard.net",
+    "ddos-guard/js-challenge",
+    "<title>DDoS-Guard</title>",
+];
+
+/// Classify a response body (and its `Server` header) as a challenge page
+pub fn detect(server: Option<&str>, body: &str) -> Option<Protection> {
+    let server = server.unwrap_or_default().to_ascii_lowercase();
+    // Challenge pages are small; skip scanning large result pages
+    let head = &body[..floor_char_boundary(body, 64 * 1024)];
+
+    if CLOUDFLARE_MARKERS.iter().any(|m| head.contains(m)) {
+        return Some(Protection::Cloudflare);
+    }
+    if DDOS_GUARD_MARKERS.iter().any(|m| head.contains(m))
+        || (server == "ddos-guard" && head.contains("ddos-guard"))
+    {
+        return Some(Protection::DdosGuard);
+    }
+    None
+}
+
+/// Check a response and return a [`ChallengeError`] if it is a challenge page
+pub fn check(url: &str, server: Option<&str>, body: &str) -> Result<(), ChallengeError> {
+    match detect(server, body) {
+        Some(protection) => Err(ChallengeError {
+            protection,
+            url: url.to_string(),
+        }),
+        None => Ok(()),
+    }
+}
+
+/// Returns the challenge if the error (or its cause) is a [`ChallengeError`]
+pub fn protection_of(e: &anyhow::Error) -> Option<Protection> {
+    e.chain()
+        .find_map(|c| c.downcast_ref::<ChallengeError>())
+        .map(|c| c.protection)
+}
+
+/// Largest char boundary of `s` at or below `index`
+pub fn floor_char_boundary(s: &str, mut index: usize) -> usize {
+    if index >= s.len() {
+        return s.len();
+    }
+    while !s.is_char_boundary(index) {
+        index -= 1;
+    }
+    index
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_challenges() {
+        let cf = r#"<html><head><title>Just a moment...</title></head>
+            <body><script>window._cf_chl_opt={cvId: '3'};</script></body></html>"#;
+        assert_eq!(detect(Some("cloudflare"), cf), Some(Protection::Cloudflare));
+
+        let ddg = r#"<html><head><title>DDoS-Guard</title></head></html>"#;
+        assert_eq!(detect(Some("ddos-guard"), ddg), Some(Protection::DdosGuard));
+
+        // Normal pages served through Cloudflare are not challenges
+        let normal = "<html><head><title>Torrents</title></head><table></table></html>";
+        assert_eq!(detect(Some("cloudflare"), normal), None);
+        let injected = r#"<html><head><title>Torrents</title></head><table></table>
+            <script src="/cdn-cgi/challenge-platform/scripts/jsd/main.js"></script></html>"#;
+        assert_eq!(detect(Some("cloudflare"), injected), None);
+    }
+}
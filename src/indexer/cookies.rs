@@ -0,0 +1,405 @@
+//! Cookies imported from a browser (`lodestarr indexer cookies`,
+//! `POST /api/native/{id}/cookies`)
+//!
+//! Accepts a Netscape `cookies.txt` (curl, wget and the "export cookies"
+//! browser extensions) or a JSON export (Cookie-Editor, EditThisCookie,
+//! Playwright). The cookies for the tracker's domains are saved per indexer
+//! next to the database and go into the indexer's session cookie jar, so a
+//! Cloudflare clearance or a browser login is sent with every search and
+//! download, and survives restarts.
+
+use super::definition::IndexerDefinition;
+use crate::config::Config;
+use anyhow::{Context, Result, bail};
+use chrono::{DateTime, TimeZone, Utc};
+use once_cell::sync::Lazy;
+use reqwest::cookie::Jar;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::RwLock;
+
+/// Directory of the saved cookies, one JSON file per indexer
+static DIR: Lazy<RwLock<Option<PathBuf>>> = Lazy::new(|| RwLock::new(None));
+
+/// Register where cookies are saved (next to the database)
+pub fn configure(config: &Config) {
+    if let Ok(mut dir) = DIR.write() {
+        *dir = config.get_cookies_path().ok();
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Cookie {
+    /// Domain without a leading dot; the cookie also goes to its subdomains
+    pub domain: String,
+    pub path: String,
+    pub name: String,
+    pub value: String,
+    #[serde(default)]
+    pub secure: bool,
+    /// None for session cookies
+    pub expires: Option<DateTime<Utc>>,
+}
+
+impl Cookie {
+    fn expired(&self, now: DateTime<Utc>) -> bool {
+        self.expires.is_some_and(|expires| expires <= now)
+    }
+
+    /// Whether the cookie belongs to `host`, a subdomain of it or a parent
+    /// domain of it
+    fn matches(&self, host: &str) -> bool {
+        let (domain, host) = (self.domain.as_str(), host.trim_start_matches("www."));
+        domain.eq_ignore_ascii_case(host)
+            || domain.ends_with(&format!(".{host}"))
+            || host.ends_with(&format!(".{domain}"))
+    }
+
+    /// Add the cookie to `jar` as if the tracker had set it
+    fn add_to(&self, jar: &Jar) {
+        let expires = self.expires.map(|expires| {
+            expires
+                .format("; Expires=%a, %d %b %Y %H:%M:%S GMT")
+                .to_string()
+        });
+        self.set_in(jar, expires.as_deref().unwrap_or_default());
+    }
+
+    /// Take the cookie out of `jar`, as a tracker expiring it would
+    fn remove_from(&self, jar: &Jar) {
+        self.set_in(jar, "; Max-Age=0");
+    }
+
+    fn set_in(&self, jar: &Jar, attributes: &str) {
+        let scheme = if self.secure { "https" } else { "http" };
+        let Ok(url) = url::Url::parse(&format!("{scheme}://{}{}", self.domain, self.path)) else {
+            return;
+        };
+        let mut cookie = format!(
+            "{}={}; Domain={}; Path={}",
+            self.name, self.value, self.domain, self.path
+        );
+        if self.secure {
+            cookie.push_str("; Secure");
+        }
+        cookie.push_str(attributes);
+        jar.add_cookie_str(&cookie, &url);
+    }
+}
+
+/// Cookies of a Netscape `cookies.txt` or a browser JSON export
+pub fn parse(text: &str) -> Result<Vec<Cookie>> {
+    let cookies = match text.trim_start().chars().next() {
+        Some('[' | '{') => parse_json(text)?,
+        _ => parse_netscape(text)?,
+    };
+    if cookies.is_empty() {
+        bail!("No cookies found (expected a Netscape cookies.txt or a JSON export)");
+    }
+    Ok(cookies)
+}
+
+/// `domain  include-subdomains  path  secure  expires  name  value`, tab
+/// separated. curl marks HttpOnly cookies with a `#HttpOnly_` prefix.
+fn parse_netscape(text: &str) -> Result<Vec<Cookie>> {
+    let mut cookies = Vec::new();
+    for (number, line) in text.lines().enumerate() {
+        let line = line.trim_end_matches('\r');
+        let line = line.strip_prefix("#HttpOnly_").unwrap_or(line);
+        if line.trim().is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let fields: Vec<&str> = if line.contains('\t') {
+            line.split('\t').collect()
+        } else {
+            line.split_whitespace().collect()
+        };
+        let [domain, _, path, secure, expires, name, rest @ ..] = fields.as_slice() else {
+            bail!(
+                "Line {}: expected 7 tab-separated fields (domain, subdomains, path, secure, expires, name, value)",
+                number + 1
+            );
+        };
+        let expires: i64 = expires
+            .trim()
+            .parse()
+            .with_context(|| format!("Line {}: invalid expiry '{}'", number + 1, expires))?;
+        cookies.push(Cookie {
+            domain: domain.trim().trim_start_matches('.').to_lowercase(),
+            path: non_empty_path(path),
+            name: name.to_string(),
+            value: rest.first().copied().unwrap_or_default().to_string(),
+            secure: secure.eq_ignore_ascii_case("true"),
+            expires: (expires > 0)
+                .then(|| Utc.timestamp_opt(expires, 0).single())
+                .flatten(),
+        });
+    }
+    Ok(cookies)
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct JsonCookie {
+    name: String,
+    #[serde(default)]
+    value: String,
+    domain: String,
+    path: Option<String>,
+    #[serde(default)]
+    secure: bool,
+    /// Cookie-Editor, EditThisCookie (seconds)
+    expiration_date: Option<f64>,
+    /// Playwright, Puppeteer (seconds, -1 for session cookies)
+    expires: Option<f64>,
+    #[serde(default)]
+    session: bool,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum JsonExport {
+    List(Vec<JsonCookie>),
+    /// Playwright `storageState`
+    State {
+        cookies: Vec<JsonCookie>,
+    },
+}
+
+fn parse_json(text: &str) -> Result<Vec<Cookie>> {
+    let export: JsonExport = serde_json::from_str(text)
+        .context("Invalid cookie JSON (expected a list of cookies with name, value and domain)")?;
+    let (JsonExport::List(cookies) | JsonExport::State { cookies }) = export;
+    Ok(cookies
+        .into_iter()
+        .map(|c| Cookie {
+            domain: c.domain.trim().trim_start_matches('.').to_lowercase(),
+            path: non_empty_path(c.path.as_deref().unwrap_or_default()),
+            expires: c
+                .expiration_date
+                .or(c.expires)
+                .filter(|&t| t > 0.0 && !c.session)
+                .and_then(|t| Utc.timestamp_opt(t as i64, 0).single()),
+            name: c.name,
+            value: c.value,
+            secure: c.secure,
+        })
+        .collect())
+}
+
+fn non_empty_path(path: &str) -> String {
+    match path.trim() {
+        "" => "/".to_string(),
+        path => path.to_string(),
+    }
+}
+
+fn file(indexer_id: &str) -> Option<PathBuf> {
+    // Ids come from definition files, but never leave the directory
+    if indexer_id.is_empty() || indexer_id.starts_with('.') || indexer_id.contains(['/', '\\']) {
+        return None;
+    }
+    let dir = DIR.read().ok()?.clone()?;
+    Some(dir.join(format!("{indexer_id}.json")))
+}
+
+/// Saved cookies of an indexer that haven't expired
+pub fn load(indexer_id: &str) -> Vec<Cookie> {
+    let Some(path) = file(indexer_id) else {
+        return Vec::new();
+    };
+    let Ok(json) = std::fs::read_to_string(&path) else {
+        return Vec::new();
+    };
+    let mut cookies: Vec<Cookie> = serde_json::from_str(&json).unwrap_or_else(|e| {
+        tracing::warn!("Ignoring unreadable cookies in {}: {}", path.display(), e);
+        Vec::new()
+    });
+    let now = Utc::now();
+    cookies.retain(|c| !c.expired(now));
+    cookies
+}
+
+fn save(indexer_id: &str, cookies: &[Cookie]) -> Result<()> {
+    let path = file(indexer_id).context("No directory to save cookies in")?;
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    std::fs::write(&path, serde_json::to_string_pretty(cookies)?)
+        .with_context(|| format!("Failed to save cookies to {}", path.display()))
+}
+
+/// Put an indexer's saved cookies in its session's jar
+pub fn add_saved(indexer_id: &str, jar: &Jar) {
+    let cookies = load(indexer_id);
+    if !cookies.is_empty() {
+        tracing::debug!(
+            "Using {} imported cookies for {}",
+            cookies.len(),
+            indexer_id
+        );
+    }
+    for cookie in &cookies {
+        cookie.add_to(jar);
+    }
+}
+
+/// Outcome of an import
+#[derive(Debug, Serialize)]
+pub struct Imported {
+    /// Cookies for the tracker's domains, now saved
+    pub imported: usize,
+    /// Expired cookies and cookies of other sites
+    pub skipped: usize,
+}
+
+/// Save the cookies of `text` that belong to the tracker and add them to its
+/// session. They replace saved cookies with the same domain, path and name.
+pub fn import(definition: &IndexerDefinition, text: &str) -> Result<Imported> {
+    let parsed = parse(text)?;
+    let hosts: Vec<String> = definition
+        .links
+        .iter()
+        .chain(&definition.legacylinks)
+        .filter_map(|link| {
+            url::Url::parse(link)
+                .ok()?
+                .host_str()
+                .map(str::to_lowercase)
+        })
+        .collect();
+    let now = Utc::now();
+    let total = parsed.len();
+    let new: Vec<Cookie> = parsed
+        .into_iter()
+        .filter(|c| !c.expired(now) && hosts.iter().any(|host| c.matches(host)))
+        .collect();
+    if new.is_empty() {
+        bail!(
+            "None of the {} cookies are for {} ({}) or still valid",
+            total,
+            definition.name,
+            hosts.join(", ")
+        );
+    }
+
+    let mut cookies = load(&definition.id);
+    cookies.retain(|old| {
+        !new.iter()
+            .any(|c| c.domain == old.domain && c.path == old.path && c.name == old.name)
+    });
+    cookies.extend(new.iter().cloned());
+    save(&definition.id, &cookies)?;
+
+    let session = super::session::get(&definition.id);
+    for cookie in &new {
+        cookie.add_to(&session.jar);
+    }
+    Ok(Imported {
+        imported: new.len(),
+        skipped: total - new.len(),
+    })
+}
+
+/// Forget an indexer's saved cookies, returning how many there were. The
+/// indexer's session is dropped, and executors still holding it stop
+/// sending them too.
+pub fn clear(indexer_id: &str) -> Result<usize> {
+    let cookies = load(indexer_id);
+    if let Some(path) = file(indexer_id)
+        && path.exists()
+    {
+        std::fs::remove_file(&path)?;
+    }
+    if let Some(session) = super::session::remove(indexer_id) {
+        for cookie in &cookies {
+            cookie.remove_from(&session.jar);
+        }
+    }
+    Ok(cookies.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::cookie::CookieStore;
+
+    #[test]
+    fn test_parse() {
+        let txt = "# Netscape HTTP Cookie File\n\
+                   .tracker.example\tTRUE\t/\tTRUE\t4102444800\tcf_clearance\tabc\n\
+                   #HttpOnly_tracker.example\tFALSE\t/\tFALSE\t0\tuid\t1\n\
+                   \n\
+                   other.example\tFALSE\t/\tFALSE\t0\tsid\t\n";
+        let cookies = parse(txt).unwrap();
+        assert_eq!(cookies.len(), 3);
+        assert_eq!(cookies[0].domain, "tracker.example");
+        assert!(cookies[0].secure);
+        assert_eq!(cookies[0].expires.unwrap().timestamp(), 4102444800);
+        assert_eq!(
+            (cookies[1].name.as_str(), cookies[1].expires),
+            ("uid", None)
+        );
+        assert_eq!(cookies[2].value, "");
+        assert!(parse("tracker.example\tTRUE\t/\n").is_err());
+
+        let json = r#"[
+            {"domain": ".tracker.example", "name": "pass", "value": "x", "path": "/",
+             "secure": false, "session": false, "expirationDate": 4102444800.5},
+            {"domain": "tracker.example", "name": "lang", "value": "en", "session": true}
+        ]"#;
+        let cookies = parse(json).unwrap();
+        assert_eq!(cookies[0].expires.unwrap().timestamp(), 4102444800);
+        assert_eq!((cookies[1].path.as_str(), cookies[1].expires), ("/", None));
+        let state = r#"{"cookies": [{"name": "a", "value": "1", "domain": "tracker.example",
+                        "path": "/", "expires": -1, "secure": true}], "origins": []}"#;
+        assert_eq!(parse(state).unwrap()[0].expires, None);
+        assert!(parse("[]").is_err());
+        assert!(parse(r#"[{"name": "a"}]"#).is_err());
+
+        // Only the tracker's cookies are sent, and only to it
+        let jar = Jar::default();
+        for cookie in parse(txt).unwrap() {
+            if cookie.matches("www.tracker.example") {
+                cookie.add_to(&jar);
+            }
+        }
+        let url = url::Url::parse("https://www.tracker.example/browse.php").unwrap();
+        let header = jar.cookies(&url).unwrap();
+        let header = header.to_str().unwrap();
+        assert!(header.contains("cf_clearance=abc") && header.contains("uid=1"));
+        assert!(!header.contains("sid="));
+    }
+
+    #[test]
+    fn test_clear_resets_session() {
+        let dir =
+            std::env::temp_dir().join(format!("lodestarr-test-cookies-{}", std::process::id()));
+        *DIR.write().unwrap() = Some(dir.clone());
+        let definition = IndexerDefinition::from_yaml(
+            r#"
+id: clear-test
+name: Clear Test
+links: ["https://clear.example/"]
+search:
+  paths: [{path: search}]
+  rows: {selector: tr}
+  fields: {title: {selector: a}}
+"#,
+        )
+        .unwrap();
+        import(&definition, "clear.example\tFALSE\t/\tFALSE\t0\tuid\t42\n").unwrap();
+        let url = url::Url::parse("https://clear.example/browse.php").unwrap();
+        let session = crate::indexer::session::get("clear-test");
+        assert!(session.jar.cookies(&url).is_some());
+
+        assert_eq!(clear("clear-test").unwrap(), 1);
+        // Neither the running session nor a new one sends the cookie
+        assert!(session.jar.cookies(&url).is_none());
+        let fresh = crate::indexer::session::get("clear-test");
+        assert!(fresh.jar.cookies(&url).is_none());
+
+        *DIR.write().unwrap() = None;
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
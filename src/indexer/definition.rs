@@ -39,6 +39,10 @@ pub struct IndexerDefinition {
     #[serde(rename = "requestDelay")]
     pub request_delay: Option<f64>,
 
+    /// Timezone of absolute dates on the site (IANA name or UTC offset)
+    #[serde(default)]
+    pub timezone: Option<String>,
+
     /// List of known domains (first is default)
     #[serde(default)]
     pub links: Vec<String>,
@@ -63,6 +67,23 @@ pub struct IndexerDefinition {
 
     /// Download configuration
     pub download: Option<Download>,
+
+    /// Definitions this one supersedes (informational)
+    #[serde(default)]
+    pub replaces: Vec<String>,
+
+    /// Jackett's self-test torrent link (informational)
+    #[serde(default)]
+    pub testlinktorrent: Option<bool>,
+
+    /// Pinned TLS certificate hashes (informational)
+    #[serde(default)]
+    pub certificates: Vec<String>,
+
+    /// Keys this version doesn't understand, as dotted paths. Filled in by
+    /// [`Self::from_yaml`]; the definition still loads without them.
+    #[serde(skip)]
+    pub unknown_fields: Vec<String>,
 }
 
 /// Setting definition for indexer configuration
@@ -72,7 +93,7 @@ pub struct Setting {
     pub name: String,
 
     /// Setting type (text, select, checkbox, info, etc.)
-    #[serde(rename = "type")]
+    #[serde(rename = "type", default = "default_setting_type")]
     pub setting_type: String,
 
     /// Display label
@@ -84,6 +105,10 @@ pub struct Setting {
     /// Options for select type
     #[serde(default)]
     pub options: HashMap<String, String>,
+
+    /// Default values of a multi-select
+    #[serde(default)]
+    pub defaults: Vec<String>,
 }
 
 /// Capabilities block
@@ -93,9 +118,44 @@ pub struct Caps {
     #[serde(default)]
     pub categorymappings: Vec<CategoryMapping>,
 
+    /// Older short form of `categorymappings`: tracker category ID to
+    /// Torznab category name. Moved into `categorymappings` on load.
+    #[serde(default)]
+    pub categories: serde_yml::Mapping,
+
     /// Search modes
     #[serde(default)]
     pub modes: HashMap<String, Vec<String>>,
+
+    /// Whether the tracker takes raw search strings (informational)
+    #[serde(default)]
+    pub allowrawsearch: bool,
+}
+
+impl Caps {
+    /// Move `categories` entries into `categorymappings`, after any mappings
+    /// already there
+    fn fold_categories(&mut self) {
+        for (id, cat) in std::mem::take(&mut self.categories) {
+            let id = match id {
+                serde_yml::Value::Number(n) => match n.as_i64() {
+                    Some(n) => StringOrInt::Int(n),
+                    None => StringOrInt::String(n.to_string()),
+                },
+                serde_yml::Value::String(s) => StringOrInt::String(s),
+                _ => continue,
+            };
+            let Some(cat) = cat.as_str() else {
+                continue;
+            };
+            self.categorymappings.push(CategoryMapping {
+                id,
+                cat: cat.to_string(),
+                desc: None,
+                default: false,
+            });
+        }
+    }
 }
 
 /// Category mapping from tracker ID to Torznab category
@@ -202,6 +262,19 @@ pub struct Search {
 
     /// Field definitions
     pub fields: Fields,
+
+    /// Fields read from each result's details page (second request)
+    #[serde(default)]
+    pub details: Option<DetailsPass>,
+}
+
+/// Extra fields extracted from a result's details page
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct DetailsPass {
+    /// Field name (`infohash`, `files`, `poster`, ...) to selector, relative
+    /// to the whole page
+    #[serde(default)]
+    pub fields: HashMap<String, SelectorDef>,
 }
 
 /// Search path configuration
@@ -237,7 +310,7 @@ pub struct SearchPath {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ResponseConfig {
     /// Response type: "html" or "json"
-    #[serde(rename = "type")]
+    #[serde(rename = "type", default = "default_response_type")]
     pub response_type: String,
 }
 
@@ -254,6 +327,11 @@ pub struct RowSelector {
     /// CSS selector for result rows (or JSON path for JSON responses)
     pub selector: String,
 
+    /// Selectors tried in order when `selector` finds no rows, for sites with
+    /// several layouts (logged in or out, desktop or mobile markup)
+    #[serde(default)]
+    pub fallbacks: Vec<String>,
+
     /// Attribute to expand for nested arrays (e.g., "torrents" in YTS)
     pub attribute: Option<String>,
 
@@ -282,6 +360,13 @@ pub struct RowSelector {
     pub dateheaders: Option<SelectorDef>,
 }
 
+impl RowSelector {
+    /// `selector`, then the fallbacks
+    pub fn selectors(&self) -> impl Iterator<Item = &str> {
+        std::iter::once(self.selector.as_str()).chain(self.fallbacks.iter().map(String::as_str))
+    }
+}
+
 /// Count selector for checking result count
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CountSelector {
@@ -695,6 +780,14 @@ fn default_method() -> String {
     "get".to_string()
 }
 
+fn default_setting_type() -> String {
+    "text".to_string()
+}
+
+fn default_response_type() -> String {
+    "html".to_string()
+}
+
 fn default_true() -> bool {
     true
 }
@@ -703,16 +796,25 @@ impl IndexerDefinition {
     /// Load from a YAML file
     pub fn from_file(path: &std::path::Path) -> anyhow::Result<Self> {
         let content = std::fs::read_to_string(path)?;
+        Self::from_yaml(&content)
+    }
 
+    /// Parse a definition from YAML text
+    pub fn from_yaml(content: &str) -> anyhow::Result<Self> {
         // Strip BOM if present
-        let content = if let Some(stripped) = content.strip_prefix("\u{feff}") {
-            stripped
-        } else {
-            &content
-        };
-
-        // Parse YAML
-        let definition: IndexerDefinition = serde_yml::from_str(content)?;
+        let content = content.strip_prefix("\u{feff}").unwrap_or(content);
+
+        // Parse YAML, noting keys that are skipped rather than failing on them
+        let mut unknown = Vec::new();
+        let mut definition: IndexerDefinition =
+            serde_ignored::deserialize(serde_yml::Deserializer::from_str(content), |path| {
+                // Optional values show up as "?" segments, as in "response.?.key"
+                let path = path.to_string();
+                let segments: Vec<&str> = path.split('.').filter(|s| *s != "?").collect();
+                unknown.push(segments.join("."))
+            })?;
+        definition.unknown_fields = unknown;
+        definition.caps.fold_categories();
 
         Ok(definition)
     }
@@ -722,33 +824,6 @@ impl IndexerDefinition {
         self.links.first().map(|s| s.as_str())
     }
 
-    /// Get category ID for a Torznab category
-    /// Falls back to parent category if subcategory not found (e.g., 5030 → 5000)
-    pub fn get_tracker_category(&self, torznab_cat: i32) -> Option<String> {
-        // First try exact match
-        for mapping in &self.caps.categorymappings {
-            if let Some(id) = Self::resolve_torznab_category_name(&mapping.cat)
-                && id == torznab_cat
-            {
-                return Some(mapping.id.to_string());
-            }
-        }
-
-        // If no exact match, try parent category (floor to nearest 1000)
-        let parent_cat = (torznab_cat / 1000) * 1000;
-        if parent_cat != torznab_cat {
-            for mapping in &self.caps.categorymappings {
-                if let Some(id) = Self::resolve_torznab_category_name(&mapping.cat)
-                    && id == parent_cat
-                {
-                    return Some(mapping.id.to_string());
-                }
-            }
-        }
-
-        None
-    }
-
     /// Get default config values from settings
     pub fn get_default_config(&self) -> HashMap<String, String> {
         let mut config = HashMap::new();
@@ -768,6 +843,9 @@ impl IndexerDefinition {
             "sitelink".to_string(),
             self.base_url().unwrap_or("").to_string(),
         );
+        if let Some(ref tz) = self.timezone {
+            config.insert("_timezone".to_string(), tz.clone());
+        }
         config
     }
 
@@ -985,3 +1063,54 @@ where
 
     deserializer.deserialize_any(CaseMapVisitor)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unknown_fields() {
+        let definition = IndexerDefinition::from_yaml(
+            r#"
+id: newer
+name: Newer
+links: ["https://tracker.example/"]
+replaces: [older]
+testlinktorrent: false
+certificates: [abc123]
+flaresolverr: true
+caps:
+  allowrawsearch: true
+  modes: {search: [q]}
+settings:
+  - name: sort
+    label: Sort
+  - name: cats
+    type: multi-select
+    defaults: ["1", "2"]
+search:
+  paths:
+    - path: browse
+      response: {noResultsMessage: Nothing}
+  rows: {selector: tr}
+  fields:
+    title: {selector: a}
+    somethingnew: {selector: b}
+"#,
+        )
+        .unwrap();
+
+        assert_eq!(definition.replaces, ["older"]);
+        assert!(definition.caps.allowrawsearch);
+        assert_eq!(definition.settings[0].setting_type, "text");
+        assert_eq!(definition.settings[1].defaults, ["1", "2"]);
+        let response = definition.search.paths[0].response.as_ref().unwrap();
+        assert_eq!(response.response_type, "html");
+        // Custom fields are kept, unknown keys elsewhere are reported
+        assert!(definition.search.fields.extra.contains_key("somethingnew"));
+        assert_eq!(
+            definition.unknown_fields,
+            ["flaresolverr", "search.paths.0.response.noResultsMessage"]
+        );
+    }
+}
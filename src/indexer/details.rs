@@ -0,0 +1,251 @@
+//! Second-request extraction from details pages (`search.details`)
+//!
+//! Some trackers only show the infohash, file list, poster or genre on a
+//! result's own page. Results missing a field the definition's details
+//! selectors provide get that page fetched, a few at a time and at most
+//! [`MAX_FETCHES`] per search. Only the missing values are filled in, and
+//! extracted values are cached per page.
+
+use super::definition::{IndexerDefinition, SelectorDef};
+use super::executor::SearchExecutor;
+use super::field_extractor::extract_html_field;
+use super::result_builder::make_torrent_result;
+use super::template::TemplateContext;
+use crate::models::{DateSource, TorrentResult};
+use futures::StreamExt;
+use once_cell::sync::Lazy;
+use scraper::Html;
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+/// Details pages fetched per search at most
+const MAX_FETCHES: usize = 25;
+const CONCURRENCY: usize = 4;
+/// Pages whose extracted fields are remembered before the cache resets
+const CACHE_SIZE: usize = 2000;
+
+type Values = HashMap<String, String>;
+
+static CACHE: Lazy<Mutex<HashMap<String, Values>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Fill in missing fields from each result's details page
+pub async fn fill(
+    executor: &SearchExecutor,
+    definition: &IndexerDefinition,
+    results: &mut [TorrentResult],
+    base_ctx: &TemplateContext,
+    base_url: &str,
+    user_settings: Option<&HashMap<String, String>>,
+) {
+    let Some(pass) = &definition.search.details else {
+        return;
+    };
+    if pass.fields.is_empty() {
+        return;
+    }
+
+    let pending: Vec<(usize, String)> = results
+        .iter()
+        .enumerate()
+        .filter(|(_, r)| pass.fields.keys().any(|name| is_missing(r, name)))
+        .filter_map(|(i, r)| Some((i, r.details.clone()?)))
+        .take(MAX_FETCHES)
+        .collect();
+    if pending.is_empty() {
+        return;
+    }
+    tracing::debug!(
+        "Fetching {} details pages for {}",
+        pending.len(),
+        definition.id
+    );
+
+    let fetched: Vec<(usize, Values)> = futures::stream::iter(pending)
+        .map(|(i, url)| async move {
+            let cached = CACHE.lock().ok().and_then(|c| c.get(&url).cloned());
+            if let Some(values) = cached {
+                return Some((i, values));
+            }
+            let body = match executor.fetch_page(&url, user_settings).await {
+                Ok(body) => body,
+                Err(e) => {
+                    tracing::debug!("Details page {} failed: {}", url, e);
+                    return None;
+                }
+            };
+            let values = extract(&body, &pass.fields, base_ctx);
+            if let Ok(mut cache) = CACHE.lock() {
+                if cache.len() >= CACHE_SIZE {
+                    cache.clear();
+                }
+                cache.insert(url, values.clone());
+            }
+            Some((i, values))
+        })
+        .buffer_unordered(CONCURRENCY)
+        .filter_map(std::future::ready)
+        .collect()
+        .await;
+
+    for (i, values) in fetched {
+        let result = &mut results[i];
+        let mut ctx = base_ctx.clone();
+        ctx.set_result("title", result.title.clone());
+        for (name, value) in values {
+            ctx.set_result(&name, value);
+        }
+        if let Some(found) = make_torrent_result(definition, &ctx, base_url) {
+            merge_missing(result, found);
+        }
+    }
+}
+
+/// Run the details selectors against a page. Selectors go first, then text
+/// templates, which may refer to them as `.Result.<name>`.
+fn extract(
+    html: &str,
+    fields: &HashMap<String, SelectorDef>,
+    base_ctx: &TemplateContext,
+) -> Values {
+    let document = Html::parse_document(html);
+    let root = document.root_element();
+    let mut ctx = base_ctx.clone();
+
+    let (templates, selectors): (Vec<_>, Vec<_>) = fields
+        .iter()
+        .partition(|(_, def)| def.selector().is_none() && def.text().is_some());
+    for (name, def) in selectors.into_iter().chain(templates) {
+        if let Some(value) = extract_html_field(&root, def, &ctx) {
+            ctx.set_result(name, value);
+        }
+    }
+    ctx.result
+}
+
+fn is_missing(result: &TorrentResult, field: &str) -> bool {
+    match field {
+        "download" => result.link.is_none() || result.link == result.magnet,
+        "magnet" => result.magnet.is_none(),
+        "infohash" => result.info_hash.is_none(),
+        "size" => result.size.is_none(),
+        "date" => result.publish_date.is_none(),
+        "seeders" => result.seeders.is_none(),
+        "leechers" => result.leechers.is_none(),
+        "grabs" => result.grabs.is_none(),
+        "files" => result.files.is_none(),
+        "imdbid" | "imdb" => result.imdb_id.is_none(),
+        "tmdbid" => result.tmdb_id.is_none(),
+        "tvdbid" => result.tvdb_id.is_none(),
+        "genre" => result.genre.is_none(),
+        "poster" => result.poster.is_none(),
+        "description" => result.description.is_none(),
+        "category" => result.categories.is_empty(),
+        // Helper values only feed other fields' templates
+        _ => false,
+    }
+}
+
+/// Copy the fields the result doesn't have yet
+fn merge_missing(result: &mut TorrentResult, found: TorrentResult) {
+    fn fill<T>(slot: &mut Option<T>, value: Option<T>) {
+        if slot.is_none() {
+            *slot = value;
+        }
+    }
+    // A magnet standing in for the download link gives way to a real one
+    if found.link.is_some() && found.link != found.magnet && result.link == result.magnet {
+        result.link = found.link;
+    }
+    fill(&mut result.magnet, found.magnet);
+    fill(&mut result.info_hash, found.info_hash);
+    fill(&mut result.size, found.size);
+    if result.publish_date.is_none() && found.publish_date.is_some() {
+        result.publish_date = found.publish_date;
+        result.date_source = Some(DateSource::Details);
+    }
+    fill(&mut result.seeders, found.seeders);
+    fill(&mut result.leechers, found.leechers);
+    fill(&mut result.grabs, found.grabs);
+    fill(&mut result.files, found.files);
+    fill(&mut result.imdb_id, found.imdb_id);
+    fill(&mut result.tmdb_id, found.tmdb_id);
+    fill(&mut result.tvdb_id, found.tvdb_id);
+    fill(&mut result.genre, found.genre);
+    fill(&mut result.poster, found.poster);
+    fill(&mut result.description, found.description);
+    if result.categories.is_empty() {
+        result.categories = found.categories;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_details_pass() {
+        let definition: IndexerDefinition = serde_yml::from_str(
+            r#"
+id: example
+name: Example
+links: [https://example.org/]
+caps:
+  categorymappings: []
+search:
+  paths: [{path: search}]
+  rows: {selector: tr}
+  fields:
+    title: {selector: a}
+  details:
+    fields:
+      infohash:
+        selector: a[href^="magnet:"]
+        attribute: href
+        filters:
+          - name: regexp
+            args: "btih:([0-9a-fA-F]{40})"
+      poster:
+        selector: img.cover
+        attribute: src
+      files:
+        selector: "td.files"
+"#,
+        )
+        .unwrap();
+        let html = r#"<html><body>
+            <a href="magnet:?xt=urn:btih:0123456789abcdef0123456789abcdef01234567&dn=x">Magnet</a>
+            <img class="cover" src="/img/42.jpg">
+            <table><tr><td class="files">12</td></tr></table>
+        </body></html>"#;
+
+        let ctx = TemplateContext::default();
+        let values = extract(
+            html,
+            &definition.search.details.as_ref().unwrap().fields,
+            &ctx,
+        );
+        let mut ctx = ctx.clone();
+        ctx.set_result("title", "Example".to_string());
+        for (name, value) in values {
+            ctx.set_result(&name, value);
+        }
+        let found = make_torrent_result(&definition, &ctx, "https://example.org/").unwrap();
+
+        let mut result = TorrentResult::new("Example".into(), "guid".into());
+        result.files = Some(3);
+        assert!(is_missing(&result, "infohash"));
+        merge_missing(&mut result, found);
+
+        assert_eq!(
+            result.info_hash.as_deref(),
+            Some("0123456789abcdef0123456789abcdef01234567")
+        );
+        assert!(result.magnet.is_some());
+        assert_eq!(
+            result.poster.as_deref(),
+            Some("https://example.org/img/42.jpg")
+        );
+        // Already known from the search page
+        assert_eq!(result.files, Some(3));
+    }
+}
@@ -1,6 +1,7 @@
 //! GitHub indexer definition downloader
 //! Downloads indexer YAML definitions from Jackett's GitHub repository
 
+use super::error::IndexerError;
 use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
 use std::path::Path;
@@ -81,11 +82,12 @@ impl IndexerDownloader {
             .context(format!("Failed to download {}", indexer.name))?;
 
         if !response.status().is_success() {
-            anyhow::bail!(
-                "Failed to download {}: HTTP {}",
-                indexer.name,
-                response.status()
-            );
+            let status = response.status();
+            return Err(IndexerError::from_status(
+                status,
+                format!("Failed to download {}: HTTP {}", indexer.name, status),
+            )
+            .into());
         }
 
         let yaml_content = response
@@ -147,24 +149,26 @@ impl IndexerDownloader {
             None => return Ok(Vec::new()),
         };
 
-        let path = Path::new(available_dir);
-        if !path.exists() {
-            return Ok(Vec::new());
-        }
+        definition_names(Path::new(available_dir)).await
+    }
 
-        let mut indexers = Vec::new();
-        let mut entries = fs::read_dir(path).await?;
+    /// Install an indexer by copying its definition from the available cache
+    pub async fn install_from_available(&self, name: &str) -> Result<String> {
+        let available_dir = self
+            .available_dir
+            .as_ref()
+            .ok_or_else(|| anyhow::anyhow!("Available directory not configured"))?;
 
-        while let Some(entry) = entries.next_entry().await? {
-            if let Some(filename) = entry.file_name().to_str()
-                && filename.ends_with(".yml")
-            {
-                let name = filename.trim_end_matches(".yml").to_string();
-                indexers.push(name);
-            }
-        }
+        let filename = format!("{}.yml", name);
+        let source = Path::new(available_dir).join(&filename);
+        let output_path = Path::new(&self.indexers_dir).join(&filename);
+        fs::create_dir_all(&self.indexers_dir).await?;
+        fs::copy(&source, &output_path)
+            .await
+            .context(format!("Failed to copy {}", source.display()))?;
 
-        Ok(indexers)
+        tracing::info!("Installed {} from {}", name, source.display());
+        Ok(output_path.display().to_string())
     }
 
     /// List all available indexer definitions from GitHub
@@ -184,11 +188,16 @@ impl IndexerDownloader {
             .context("Failed to fetch indexer list from GitHub")?;
 
         if !response.status().is_success() {
-            anyhow::bail!(
-                "GitHub API returned error: {} - {}",
-                response.status(),
-                response.text().await.unwrap_or_default()
-            );
+            let status = response.status();
+            return Err(IndexerError::from_status(
+                status,
+                format!(
+                    "GitHub API returned error: {} - {}",
+                    status,
+                    response.text().await.unwrap_or_default()
+                ),
+            )
+            .into());
         }
 
         let contents: Vec<GitHubContent> = response
@@ -229,11 +238,12 @@ impl IndexerDownloader {
             .context(format!("Failed to download {}", indexer.name))?;
 
         if !response.status().is_success() {
-            anyhow::bail!(
-                "Failed to download {}: HTTP {}",
-                indexer.name,
-                response.status()
-            );
+            let status = response.status();
+            return Err(IndexerError::from_status(
+                status,
+                format!("Failed to download {}: HTTP {}", indexer.name, status),
+            )
+            .into());
         }
 
         let yaml_content = response
@@ -274,7 +284,10 @@ impl IndexerDownloader {
             } else {
                 results.push((
                     name.clone(),
-                    Err(anyhow::anyhow!("Indexer '{}' not found in GitHub", name)),
+                    Err(
+                        IndexerError::NotFound(format!("Indexer '{}' not found in GitHub", name))
+                            .into(),
+                    ),
                 ));
             }
         }
@@ -319,28 +332,32 @@ impl IndexerDownloader {
 
     /// List locally installed indexers
     pub async fn list_local_indexers(&self) -> Result<Vec<String>> {
-        let path = Path::new(&self.indexers_dir);
-
-        if !path.exists() {
-            return Ok(Vec::new());
-        }
-
-        let mut indexers = Vec::new();
-        let mut entries = fs::read_dir(path).await?;
-
-        while let Some(entry) = entries.next_entry().await? {
-            if let Some(filename) = entry.file_name().to_str()
-                && filename.ends_with(".yml")
-            {
-                let name = filename.trim_end_matches(".yml").to_string();
-                indexers.push(name);
-            }
-        }
-
-        Ok(indexers)
+        definition_names(Path::new(&self.indexers_dir)).await
     }
 }
 
+/// Names of the `.yml` definitions in `dir` (none if it doesn't exist), read
+/// in one pass on a blocking thread
+async fn definition_names(dir: &Path) -> Result<Vec<String>> {
+    let dir = dir.to_path_buf();
+    tokio::task::spawn_blocking(move || {
+        let entries = match std::fs::read_dir(&dir) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e.into()),
+        };
+        Ok(entries
+            .flatten()
+            .filter_map(|entry| {
+                let filename = entry.file_name();
+                let name = filename.to_str()?.strip_suffix(".yml")?;
+                Some(name.to_string())
+            })
+            .collect())
+    })
+    .await?
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
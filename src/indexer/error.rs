@@ -0,0 +1,268 @@
+//! Typed indexer failures
+//!
+//! Indexer code still returns `anyhow::Result`, but failures a caller may want
+//! to act on are raised as [`IndexerError`] so they survive the error chain.
+//! [`kind_of`] classifies any error (including [`ChallengeError`] and
+//! [`LoginRequired`]) for API responses and stats.
+
+use super::challenge::ChallengeError;
+use super::session::LoginRequired;
+use reqwest::StatusCode;
+use reqwest::header::{HeaderMap, RETRY_AFTER};
+use serde::Serialize;
+use std::fmt;
+use std::time::Duration;
+
+#[derive(Debug, thiserror::Error)]
+pub enum IndexerError {
+    /// Tracker unreachable or answering with a server error
+    #[error("{0}")]
+    Network(String),
+    /// Response could not be understood
+    #[error("{0}")]
+    Parse(String),
+    /// Missing or rejected credentials
+    #[error("{0}")]
+    Login(String),
+    /// Tracker asked us to slow down (429, or 503 with Retry-After)
+    #[error("{message}")]
+    RateLimited {
+        message: String,
+        retry_after: Option<Duration>,
+    },
+    /// Refused by the tracker or its CDN
+    #[error("{0}")]
+    Blocked(String),
+    /// Page, torrent or definition does not exist
+    #[error("{0}")]
+    NotFound(String),
+    /// `require_proxy` is set and the proxy can't be used
+    #[error("{0}")]
+    ProxyDown(String),
+}
+
+impl IndexerError {
+    /// Error for an unsuccessful HTTP status
+    pub fn from_status(status: StatusCode, message: impl Into<String>) -> Self {
+        let message = message.into();
+        match status {
+            StatusCode::UNAUTHORIZED => Self::Login(message),
+            StatusCode::FORBIDDEN => Self::Blocked(message),
+            StatusCode::NOT_FOUND | StatusCode::GONE => Self::NotFound(message),
+            StatusCode::TOO_MANY_REQUESTS => Self::RateLimited {
+                message,
+                retry_after: None,
+            },
+            _ => Self::Network(message),
+        }
+    }
+
+    /// Error for an unsuccessful response, honouring `Retry-After`
+    pub fn from_response(
+        status: StatusCode,
+        headers: &HeaderMap,
+        message: impl Into<String>,
+    ) -> Self {
+        match Self::rate_limit(status, headers, message) {
+            Ok(e) => e,
+            Err(message) => Self::from_status(status, message),
+        }
+    }
+
+    /// The rate-limit error for a 429, or a 503 that says when to come back.
+    /// Hands the message back for any other response.
+    pub fn rate_limit(
+        status: StatusCode,
+        headers: &HeaderMap,
+        message: impl Into<String>,
+    ) -> std::result::Result<Self, String> {
+        let message = message.into();
+        let retry_after = retry_after(headers);
+        if status == StatusCode::TOO_MANY_REQUESTS
+            || (status == StatusCode::SERVICE_UNAVAILABLE && retry_after.is_some())
+        {
+            Ok(Self::RateLimited {
+                message,
+                retry_after,
+            })
+        } else {
+            Err(message)
+        }
+    }
+
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            Self::Network(_) => ErrorKind::Network,
+            Self::Parse(_) => ErrorKind::Parse,
+            Self::Login(_) => ErrorKind::Login,
+            Self::RateLimited { .. } => ErrorKind::RateLimited,
+            Self::Blocked(_) => ErrorKind::Blocked,
+            Self::NotFound(_) => ErrorKind::NotFound,
+            Self::ProxyDown(_) => ErrorKind::ProxyDown,
+        }
+    }
+}
+
+/// What went wrong, as reported to API clients and stored in stats
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorKind {
+    Network,
+    Parse,
+    Login,
+    RateLimited,
+    Blocked,
+    NotFound,
+    ProxyDown,
+    Other,
+}
+
+impl ErrorKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Network => "network",
+            Self::Parse => "parse",
+            Self::Login => "login",
+            Self::RateLimited => "rate_limited",
+            Self::Blocked => "blocked",
+            Self::NotFound => "not_found",
+            Self::ProxyDown => "proxy_down",
+            Self::Other => "other",
+        }
+    }
+
+    pub fn parse(s: &str) -> Self {
+        match s {
+            "network" => Self::Network,
+            "parse" => Self::Parse,
+            "login" => Self::Login,
+            "rate_limited" => Self::RateLimited,
+            "blocked" => Self::Blocked,
+            "not_found" => Self::NotFound,
+            "proxy_down" => Self::ProxyDown,
+            _ => Self::Other,
+        }
+    }
+
+    /// Status for our own API when an indexer request fails this way
+    pub fn http_status(self) -> StatusCode {
+        match self {
+            Self::Login | Self::Blocked => StatusCode::FORBIDDEN,
+            Self::RateLimited => StatusCode::TOO_MANY_REQUESTS,
+            Self::NotFound => StatusCode::NOT_FOUND,
+            Self::ProxyDown => StatusCode::SERVICE_UNAVAILABLE,
+            Self::Network | Self::Parse | Self::Other => StatusCode::BAD_GATEWAY,
+        }
+    }
+}
+
+impl fmt::Display for ErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Parse `Retry-After`: delay in seconds or an HTTP date
+pub fn retry_after(headers: &HeaderMap) -> Option<Duration> {
+    let value = headers.get(RETRY_AFTER)?.to_str().ok()?.trim();
+    if let Ok(secs) = value.parse::<u64>() {
+        return Some(Duration::from_secs(secs));
+    }
+    let at = chrono::DateTime::parse_from_rfc2822(value).ok()?;
+    (at.with_timezone(&chrono::Utc) - chrono::Utc::now())
+        .to_std()
+        .ok()
+}
+
+/// The delay a rate-limited tracker asked for, if the error is a rate limit
+pub fn retry_after_of(e: &anyhow::Error) -> Option<Option<Duration>> {
+    e.chain()
+        .find_map(|c| match c.downcast_ref::<IndexerError>() {
+            Some(IndexerError::RateLimited { retry_after, .. }) => Some(*retry_after),
+            _ => None,
+        })
+}
+
+/// Classify an error by the first recognised cause in its chain
+pub fn kind_of(e: &anyhow::Error) -> ErrorKind {
+    for cause in e.chain() {
+        if let Some(e) = cause.downcast_ref::<IndexerError>() {
+            return e.kind();
+        }
+        if cause.is::<ChallengeError>() {
+            return ErrorKind::Blocked;
+        }
+        if cause.is::<LoginRequired>() {
+            return ErrorKind::Login;
+        }
+        if let Some(e) = cause.downcast_ref::<reqwest::Error>() {
+            return match e.status() {
+                Some(status) => IndexerError::from_status(status, "").kind(),
+                None if e.is_decode() => ErrorKind::Parse,
+                None => ErrorKind::Network,
+            };
+        }
+        if cause.is::<serde_json::Error>()
+            || cause.is::<serde_yml::Error>()
+            || cause.is::<quick_xml::Error>()
+        {
+            return ErrorKind::Parse;
+        }
+    }
+    ErrorKind::Other
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Context;
+
+    #[test]
+    fn test_kind_of() {
+        let e: anyhow::Error = IndexerError::Login("bad password".into()).into();
+        assert_eq!(kind_of(&e), ErrorKind::Login);
+        assert_eq!(e.to_string(), "bad password");
+
+        let e = Err::<(), _>(IndexerError::from_status(
+            StatusCode::TOO_MANY_REQUESTS,
+            "HTTP 429",
+        ))
+        .context("Searching example")
+        .unwrap_err();
+        assert_eq!(kind_of(&e), ErrorKind::RateLimited);
+        assert_eq!(retry_after_of(&e), Some(None));
+
+        let e: anyhow::Error = LoginRequired("Example".into()).into();
+        assert_eq!(kind_of(&e), ErrorKind::Login);
+
+        let e = serde_json::from_str::<serde_json::Value>("{").unwrap_err();
+        assert_eq!(kind_of(&e.into()), ErrorKind::Parse);
+
+        assert_eq!(kind_of(&anyhow::anyhow!("something")), ErrorKind::Other);
+        assert_eq!(
+            ErrorKind::parse(ErrorKind::NotFound.as_str()),
+            ErrorKind::NotFound
+        );
+    }
+
+    #[test]
+    fn test_rate_limit() {
+        let mut headers = HeaderMap::new();
+        let e = IndexerError::from_response(StatusCode::SERVICE_UNAVAILABLE, &headers, "down");
+        assert_eq!(e.kind(), ErrorKind::Network);
+
+        headers.insert(RETRY_AFTER, "120".parse().unwrap());
+        let e = IndexerError::from_response(StatusCode::SERVICE_UNAVAILABLE, &headers, "busy");
+        assert!(matches!(
+            e,
+            IndexerError::RateLimited { retry_after: Some(d), .. } if d == Duration::from_secs(120)
+        ));
+
+        let later = (chrono::Utc::now() + chrono::Duration::minutes(5))
+            .format("%a, %d %b %Y %H:%M:%S GMT")
+            .to_string();
+        headers.insert(RETRY_AFTER, later.parse().unwrap());
+        let delay = retry_after(&headers).unwrap();
+        assert!(delay > Duration::from_secs(280) && delay <= Duration::from_secs(300));
+    }
+}
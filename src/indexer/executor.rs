@@ -3,22 +3,253 @@
 //! Handles HTTP requests, cookies, redirects, and delegates field extraction
 //! and result building to dedicated modules.
 
-use reqwest::{Client, Proxy};
+use reqwest::cookie::Jar;
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+use reqwest::{Client, RequestBuilder};
 use scraper::{Html, Selector};
+use serde::Serialize;
 
+use super::anime;
+use super::categories;
+use super::challenge;
 use super::definition::IndexerDefinition;
+use super::error::IndexerError;
 use super::field_extractor::{extract_html_fields, extract_json_fields};
 use super::filters::apply_filters_with_context;
+use super::login::{self, LoginMethod};
+use super::mirrors;
+use super::pacing;
+use super::remote::Remote;
 use super::result_builder::{make_absolute_url, make_torrent_result};
+use super::rewrite;
 use super::selector::{apply_selector_chain, parse_selector_chain};
+use super::session::{self, LoginRequired, Session};
+use super::settings;
 use super::template::{TemplateContext, render_template};
+use super::timings;
 use crate::Result;
-use crate::models::{SearchQuery, TorrentResult};
+use crate::http::traffic;
+use crate::models::{SearchQuery, SearchType, TorrentResult};
+use std::sync::Arc;
+use std::time::Instant;
+use tracing::Instrument;
+
+const DEFAULT_USER_AGENT: &str = "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/121.0.0.0 Safari/537.36";
+
+/// Parse the `_headers` setting: one `Name: value` per line. Invalid lines are skipped.
+pub fn parse_header_lines(raw: &str) -> HeaderMap {
+    let mut headers = HeaderMap::new();
+    for line in raw.lines() {
+        let Some((name, value)) = line.split_once(':') else {
+            continue;
+        };
+        match (
+            HeaderName::from_bytes(name.trim().as_bytes()),
+            HeaderValue::from_str(value.trim()),
+        ) {
+            (Ok(name), Ok(value)) => {
+                headers.insert(name, value);
+            }
+            _ => tracing::warn!("Ignoring invalid header override: {}", line),
+        }
+    }
+    headers
+}
+
+/// Base URL to use - the `_mirror` setting picks from links + legacylinks
+fn selected_base_url(
+    definition: &IndexerDefinition,
+    user_settings: Option<&std::collections::HashMap<String, String>>,
+) -> Result<String> {
+    let mirror_index: usize = user_settings
+        .and_then(|s| s.get("_mirror"))
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(0);
+
+    // Combine links and legacylinks for mirror selection
+    let all_links: Vec<&str> = definition
+        .links
+        .iter()
+        .chain(definition.legacylinks.iter())
+        .map(|s| s.as_str())
+        .collect();
+
+    all_links
+        .get(mirror_index)
+        .or_else(|| all_links.first())
+        .map(|s| s.to_string())
+        .ok_or_else(|| anyhow::anyhow!("No base URL configured"))
+}
+
+/// Template config: definition defaults overridden by user settings
+fn template_config(
+    definition: &IndexerDefinition,
+    user_settings: Option<&std::collections::HashMap<String, String>>,
+) -> std::collections::HashMap<String, String> {
+    let mut config = definition.get_default_config();
+    if let Some(settings) = user_settings {
+        for (k, v) in settings {
+            config.insert(k.clone(), v.clone());
+        }
+    }
+    config
+}
+
+/// Current TOTP code if a `_totpSecret` is configured
+fn totp_code(
+    user_settings: Option<&std::collections::HashMap<String, String>>,
+) -> Result<Option<String>> {
+    match user_settings
+        .and_then(|s| s.get("_totpSecret"))
+        .filter(|s| !s.is_empty())
+    {
+        Some(secret) => Ok(Some(super::totp::current_code(secret)?)),
+        None => Ok(None),
+    }
+}
+
+/// Whether empty TV searches are retried as keyword searches (`_keywordRetry`)
+fn keyword_retry(user_settings: Option<&std::collections::HashMap<String, String>>) -> bool {
+    user_settings
+        .and_then(|s| s.get("_keywordRetry"))
+        .is_some_and(|v| v == "true")
+}
+
+/// A TV search with a season as a plain keyword search, e.g. "Show S01E02"
+fn keyword_query(query: &SearchQuery) -> Option<SearchQuery> {
+    let title = query
+        .query
+        .as_deref()
+        .map(str::trim)
+        .filter(|q| !q.is_empty())?;
+    if query.search_type != SearchType::TvSearch {
+        return None;
+    }
+    let marker = match (query.season?, query.episode) {
+        (season, Some(episode)) => format!("S{:02}E{:02}", season, episode),
+        (season, None) => format!("S{:02}", season),
+    };
+    Some(SearchQuery {
+        search_type: SearchType::Search,
+        query: Some(format!("{} {}", title, marker)),
+        season: None,
+        episode: None,
+        imdb_id: None,
+        tvdb_id: None,
+        tmdb_id: None,
+        tvmaze_id: None,
+        trakt_id: None,
+        douban_id: None,
+        ..query.clone()
+    })
+}
+
+/// `login.test.selector`, if the definition logs in
+fn login_test_selector(definition: &IndexerDefinition) -> Option<&str> {
+    LoginMethod::of(definition)?;
+    definition
+        .login
+        .as_ref()?
+        .test
+        .as_ref()?
+        .selector
+        .as_deref()
+}
+
+/// True if the selector matches anything on the page. Selectors we can't
+/// parse are treated as matching, so they never force a re-login.
+fn page_matches(body: &str, selector: &str) -> bool {
+    match Selector::parse(selector) {
+        Ok(sel) => Html::parse_document(body).select(&sel).next().is_some(),
+        Err(_) => true,
+    }
+}
+
+fn server_header(response: &reqwest::Response) -> Option<String> {
+    response
+        .headers()
+        .get(reqwest::header::SERVER)
+        .and_then(|v| v.to_str().ok())
+        .map(String::from)
+}
+
+/// Apply per-indexer request overrides from user settings (`_userAgent`,
+/// `_cookie`, `_headers`). These replace any header already on the request.
+fn apply_user_headers(
+    request: RequestBuilder,
+    user_settings: Option<&std::collections::HashMap<String, String>>,
+) -> RequestBuilder {
+    let Some(settings) = user_settings else {
+        return request;
+    };
+    let setting = |key: &str| settings.get(key).filter(|s| !s.is_empty());
+
+    let mut headers = HeaderMap::new();
+    if let Some(ua) = setting("_userAgent")
+        && let Ok(value) = HeaderValue::from_str(ua)
+    {
+        headers.insert(reqwest::header::USER_AGENT, value);
+    }
+    if let Some(cookie) = setting("_cookie")
+        && let Ok(value) = HeaderValue::from_str(cookie)
+    {
+        tracing::debug!("Injecting user cookie header");
+        headers.insert(reqwest::header::COOKIE, value);
+    }
+    if let Some(raw) = setting("_headers") {
+        headers.extend(parse_header_lines(raw));
+    }
+
+    if headers.is_empty() {
+        request
+    } else {
+        request.headers(headers)
+    }
+}
+
+/// Bytes of a search page kept by [`SearchExecutor::take_empty_responses`]
+const RAW_RESPONSE_MAX_BYTES: usize = 512 * 1024;
+
+/// A search page that yielded no rows, kept for diagnosing broken definitions
+#[derive(Debug, Clone)]
+pub struct RawResponse {
+    pub url: String,
+    pub status: u16,
+    /// Body, cut off at [`RAW_RESPONSE_MAX_BYTES`]
+    pub body: String,
+}
+
+/// A search request and what came of it, for test reports
+#[derive(Debug, Clone, Serialize)]
+pub struct SearchRequest {
+    pub method: &'static str,
+    pub url: String,
+    /// `None` if no response came back
+    pub status: Option<u16>,
+    /// Results parsed from the page; `None` if it was rejected or didn't parse
+    pub rows: Option<usize>,
+}
 
 /// Executes searches against indexers
 #[derive(Clone)]
 pub struct SearchExecutor {
     client: Client,
+    proxy_url: Option<String>,
+    timeout_secs: u64,
+    /// Indexer this executor is dedicated to (used for Tor circuit isolation)
+    indexer_id: Option<String>,
+    /// Login state and cookies shared by all executors for the indexer
+    session: Option<Arc<Session>>,
+    /// Cookies of this executor's requests (the session's, if it has one)
+    jar: Arc<Jar>,
+    /// Lodestarr instance that handles this indexer instead (`_remote`)
+    remote: Option<Remote>,
+    /// Non-empty search pages that produced no results
+    empty_responses: Arc<std::sync::Mutex<Vec<RawResponse>>>,
+    /// Search requests sent, oldest first
+    requests: Arc<std::sync::Mutex<Vec<SearchRequest>>>,
+    /// Phase durations of the current search
+    timings: timings::Recorder,
 }
 
 impl SearchExecutor {
@@ -31,6 +262,38 @@ impl SearchExecutor {
     pub fn new_with_settings(
         proxy_url: Option<&str>,
         user_settings: Option<&std::collections::HashMap<String, String>>,
+    ) -> Result<Self> {
+        Self::build(proxy_url, user_settings, None)
+    }
+
+    /// Create an executor dedicated to one indexer. With `[http] tor_isolation`
+    /// it gets its own Tor circuit, and connection failures are retried once
+    /// on a fresh circuit.
+    pub fn for_indexer(
+        indexer_id: &str,
+        proxy_url: Option<&str>,
+        user_settings: Option<&std::collections::HashMap<String, String>>,
+    ) -> Result<Self> {
+        Self::build(proxy_url, user_settings, Some(indexer_id))
+    }
+
+    /// Create an executor for a trial run of a definition that isn't
+    /// installed: no shared login session, and its timings are only
+    /// available from [`Self::take_timings`]
+    pub fn trial(
+        proxy_url: Option<&str>,
+        user_settings: Option<&std::collections::HashMap<String, String>>,
+    ) -> Result<Self> {
+        let mut executor = Self::build(proxy_url, user_settings, None)?;
+        executor.remote = None;
+        executor.timings = timings::Recorder::detached();
+        Ok(executor)
+    }
+
+    fn build(
+        proxy_url: Option<&str>,
+        user_settings: Option<&std::collections::HashMap<String, String>>,
+        indexer_id: Option<&str>,
     ) -> Result<Self> {
         // Extract timeout from settings (default 30s)
         let timeout_secs: u64 = user_settings
@@ -38,28 +301,139 @@ impl SearchExecutor {
             .and_then(|v| v.parse().ok())
             .unwrap_or(30);
 
-        let client_builder = Client::builder()
-            .user_agent("Lodestarr/0.4.2")
-            .cookie_store(true)
-            .timeout(std::time::Duration::from_secs(timeout_secs));
+        let session = indexer_id.map(session::get);
+        let jar = session.as_ref().map(|s| s.jar.clone()).unwrap_or_default();
+        Ok(Self {
+            client: Self::build_client(proxy_url, timeout_secs, indexer_id, &jar)?,
+            proxy_url: proxy_url.map(String::from),
+            timeout_secs,
+            indexer_id: indexer_id.map(String::from),
+            session,
+            jar,
+            remote: Remote::from_settings(user_settings),
+            empty_responses: Default::default(),
+            requests: Default::default(),
+            timings: Default::default(),
+        })
+    }
 
-        let client = if let Some(url) = proxy_url {
-            let proxy = Proxy::all(url).map_err(|e| anyhow::anyhow!("Invalid proxy URL: {}", e))?;
+    fn build_client(
+        proxy_url: Option<&str>,
+        timeout_secs: u64,
+        indexer_id: Option<&str>,
+        jar: &Arc<Jar>,
+    ) -> Result<Client> {
+        if let Some(url) = proxy_url {
             tracing::info!("Using proxy: {} (timeout: {}s)", url, timeout_secs);
-            client_builder.proxy(proxy)
-        } else {
-            client_builder
         }
-        .build()
-        .map_err(|e| anyhow::anyhow!("Failed to create HTTP client: {}", e))?;
+        let client = crate::http::client_builder(proxy_url, indexer_id)?
+            .user_agent("Lodestarr/0.4.2")
+            .timeout(std::time::Duration::from_secs(timeout_secs))
+            .cookie_provider(jar.clone())
+            .build()
+            .map_err(|e| anyhow::anyhow!("Failed to create HTTP client: {}", e))?;
+
+        Ok(client)
+    }
+
+    /// If `err` is a connection failure on an isolated Tor circuit, switch the
+    /// indexer to a new circuit and return an executor using it
+    fn retry_on_new_circuit(&self, err: &anyhow::Error) -> Option<Self> {
+        let id = self.indexer_id.as_deref()?;
+        if !crate::http::isolates(self.proxy_url.as_deref(), Some(id))
+            || !crate::http::is_connection_error(err)
+        {
+            return None;
+        }
+        crate::http::new_circuit(id);
+        let client = Self::build_client(
+            self.proxy_url.as_deref(),
+            self.timeout_secs,
+            Some(id),
+            &self.jar,
+        )
+        .ok()?;
+        Some(Self {
+            client,
+            ..self.clone()
+        })
+    }
+
+    /// Search pages this executor got that had a body but no results (or
+    /// failed to parse), oldest first
+    pub fn take_empty_responses(&self) -> Vec<RawResponse> {
+        self.empty_responses
+            .lock()
+            .map(|mut kept| std::mem::take(&mut *kept))
+            .unwrap_or_default()
+    }
 
-        Ok(Self { client })
+    /// Search requests this executor sent, oldest first
+    pub fn take_requests(&self) -> Vec<SearchRequest> {
+        self.requests
+            .lock()
+            .map(|mut sent| std::mem::take(&mut *sent))
+            .unwrap_or_default()
+    }
+
+    /// Remember a search request about to be sent, returning its place for
+    /// [`Self::update_request`]
+    fn log_request(&self, method: &'static str, url: &str) -> usize {
+        let Ok(mut sent) = self.requests.lock() else {
+            return usize::MAX;
+        };
+        sent.push(SearchRequest {
+            method,
+            url: url.to_string(),
+            status: None,
+            rows: None,
+        });
+        sent.len() - 1
+    }
+
+    fn update_request(&self, request: usize, update: impl FnOnce(&mut SearchRequest)) {
+        if let Ok(mut sent) = self.requests.lock()
+            && let Some(request) = sent.get_mut(request)
+        {
+            update(request);
+        }
+    }
+
+    /// Phase breakdown of the last search of a [`Self::trial`] executor
+    pub fn take_timings(&self) -> Option<timings::SearchTimings> {
+        self.timings.take_finished()
+    }
+
+    fn keep_empty_response(&self, url: &str, status: reqwest::StatusCode, body: &str) {
+        let body = &body[..challenge::floor_char_boundary(body, RAW_RESPONSE_MAX_BYTES)];
+        if let Ok(mut kept) = self.empty_responses.lock() {
+            kept.push(RawResponse {
+                url: url.to_string(),
+                status: status.as_u16(),
+                body: body.to_string(),
+            });
+        }
+    }
+
+    /// Check the required proxy, then wait for the tracker's domain budget
+    /// and the indexer's `requestDelay`
+    async fn throttle(&self, url: &str) -> Result<()> {
+        crate::http::ensure_proxy(self.proxy_url.as_deref()).await?;
+        crate::http::throttle(url).await;
+        if let Some(id) = &self.indexer_id {
+            pacing::wait(id).await;
+        }
+        Ok(())
     }
 
     /// Visit the base URL to acquire cookies
     pub async fn visit_base_url(&self, definition: &IndexerDefinition) -> Result<()> {
+        if self.remote.is_some() {
+            return Ok(());
+        }
         if let Some(base_url) = definition.base_url() {
             tracing::debug!("Pre-fetching {} to acquire session cookies", base_url);
+            self.throttle(base_url).await?;
             let _ = self
                 .client
                 .get(base_url)
@@ -71,18 +445,180 @@ impl SearchExecutor {
     }
 
     /// Download a torrent/magnet, handling multi-step selectors if defined
-    pub async fn download(&self, definition: &IndexerDefinition, url: &str) -> Result<Vec<u8>> {
+    pub async fn download(
+        &self,
+        definition: &IndexerDefinition,
+        url: &str,
+        user_settings: Option<&std::collections::HashMap<String, String>>,
+    ) -> Result<Vec<u8>> {
+        match self.download_once(definition, url, user_settings).await {
+            Err(e) => match self.retry_on_new_circuit(&e) {
+                Some(retry) => retry.download_once(definition, url, user_settings).await,
+                None => Err(e),
+            },
+            ok => ok,
+        }
+    }
+
+    async fn download_once(
+        &self,
+        definition: &IndexerDefinition,
+        url: &str,
+        user_settings: Option<&std::collections::HashMap<String, String>>,
+    ) -> Result<Vec<u8>> {
+        let download_url = self
+            .resolve_download_url(definition, url, user_settings)
+            .await?;
+
+        // Final download
+        tracing::debug!("Downloading from: {}", download_url);
+        self.throttle(&download_url).await?;
+        let response = apply_user_headers(self.client.get(&download_url), user_settings)
+            .send()
+            .await?;
+        if !response.status().is_success() {
+            let status = response.status();
+            let headers = response.headers().clone();
+            let server = server_header(&response);
+            let body = response.text().await.unwrap_or_default();
+            traffic::add(body.len());
+            challenge::check(&download_url, server.as_deref(), &body)?;
+            return Err(IndexerError::from_response(
+                status,
+                &headers,
+                format!("Download failed: HTTP {}", status),
+            )
+            .into());
+        }
+
+        let bytes = response.bytes().await?;
+        traffic::add(bytes.len());
+        Ok(bytes.to_vec())
+    }
+
+    /// Returns true if the definition needs a details page fetch to find the real link
+    pub fn has_download_selectors(definition: &IndexerDefinition) -> bool {
+        definition
+            .download
+            .as_ref()
+            .and_then(|d| d.selectors.as_ref())
+            .is_some_and(|s| !s.is_empty())
+    }
+
+    /// Fetch a page from the tracker (e.g. a result's details page) with the
+    /// indexer's session, headers and proxy
+    pub async fn fetch_page(
+        &self,
+        url: &str,
+        user_settings: Option<&std::collections::HashMap<String, String>>,
+    ) -> Result<String> {
+        self.throttle(url).await?;
+        let response = apply_user_headers(self.client.get(url), user_settings)
+            .send()
+            .await?;
+        let status = response.status();
+        let headers = response.headers().clone();
+        let server = server_header(&response);
+        let body = response.text().await?;
+        traffic::add(body.len());
+        challenge::check(url, server.as_deref(), &body)?;
+        if !status.is_success() {
+            return Err(IndexerError::from_response(
+                status,
+                &headers,
+                format!("Failed to fetch {}: {}", url, status),
+            )
+            .into());
+        }
+        Ok(body)
+    }
+
+    /// Fetch an image (e.g. a poster) with the indexer's session and proxy.
+    /// Returns the content type and bytes; fails for non-images and bodies
+    /// over `max_bytes`.
+    pub async fn fetch_image(
+        &self,
+        url: &str,
+        max_bytes: usize,
+        user_settings: Option<&std::collections::HashMap<String, String>>,
+    ) -> Result<(String, Vec<u8>)> {
+        self.throttle(url).await?;
+        let mut response = apply_user_headers(self.client.get(url), user_settings)
+            .send()
+            .await?;
+        let status = response.status();
+        if !status.is_success() {
+            return Err(IndexerError::from_response(
+                status,
+                &response.headers().clone(),
+                format!("Image fetch failed: HTTP {}", status),
+            )
+            .into());
+        }
+        let content_type = response
+            .headers()
+            .get(reqwest::header::CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .unwrap_or_default()
+            .to_string();
+        // SVG can carry scripts, which would run on whatever origin serves it
+        if !content_type.starts_with("image/") || content_type.starts_with("image/svg") {
+            return Err(IndexerError::Parse(format!("Not an image: {}", content_type)).into());
+        }
+        if response
+            .content_length()
+            .is_some_and(|len| len > max_bytes as u64)
+        {
+            anyhow::bail!("Image larger than {} bytes", max_bytes);
+        }
+        let mut body = Vec::new();
+        while let Some(chunk) = response.chunk().await? {
+            body.extend_from_slice(&chunk);
+            if body.len() > max_bytes {
+                anyhow::bail!("Image larger than {} bytes", max_bytes);
+            }
+        }
+        Ok((content_type, body))
+    }
+
+    /// Resolve the link that would actually be downloaded.
+    ///
+    /// For multi-step definitions this fetches the details page and applies the
+    /// download selectors; the final URL itself is never requested.
+    pub async fn resolve_download_url(
+        &self,
+        definition: &IndexerDefinition,
+        url: &str,
+        user_settings: Option<&std::collections::HashMap<String, String>>,
+    ) -> Result<String> {
+        // Links from a remote instance point at its download proxy, which resolves them
+        if self.remote.is_some() {
+            return Ok(url.to_string());
+        }
+        self.add_user_cookie(definition, user_settings);
+
         // Multi-step download logic
         let download_url = if let Some(ref download_config) = definition.download {
             if let Some(ref selectors) = download_config.selectors {
                 if !selectors.is_empty() {
                     tracing::info!("Performing multi-step download for {}", url);
                     // 1. Fetch the details page
-                    let response = self.client.get(url).send().await?;
-                    if !response.status().is_success() {
-                        anyhow::bail!("Failed to fetch details page: {}", response.status());
-                    }
+                    self.throttle(url).await?;
+                    let response = apply_user_headers(self.client.get(url), user_settings)
+                        .send()
+                        .await?;
+                    let status = response.status();
+                    let server = server_header(&response);
                     let body = response.text().await?;
+                    traffic::add(body.len());
+                    challenge::check(url, server.as_deref(), &body)?;
+                    if !status.is_success() {
+                        return Err(IndexerError::from_status(
+                            status,
+                            format!("Failed to fetch details page: {}", status),
+                        )
+                        .into());
+                    }
                     let document = Html::parse_document(&body);
                     let ctx = TemplateContext::default();
 
@@ -121,10 +657,19 @@ impl SearchExecutor {
 
                     match final_link {
                         Some(l) => {
+                            // Result links are rewritten when built, this one only now
+                            let config = template_config(definition, user_settings);
+                            let l =
+                                rewrite::apply(&l, &TemplateContext::default().with_config(config));
                             tracing::info!("Found real download link: {}", l);
                             l
                         }
-                        None => anyhow::bail!("Could not extract download link from details page"),
+                        None => {
+                            return Err(IndexerError::Parse(
+                                "Could not extract download link from details page".to_string(),
+                            )
+                            .into());
+                        }
                     }
                 } else {
                     url.to_string()
@@ -136,14 +681,355 @@ impl SearchExecutor {
             url.to_string()
         };
 
-        // Final download
-        tracing::debug!("Downloading from: {}", download_url);
-        let response = self.client.get(&download_url).send().await?;
+        Ok(download_url)
+    }
+
+    /// Log in unless the indexer's session is already logged in.
+    ///
+    /// Supports the `post` (default), `get`, `form` and `cookie` login methods
+    /// (see [`login`]). Afterwards `login.test` is checked.
+    async fn ensure_login(
+        &self,
+        definition: &IndexerDefinition,
+        ctx: &TemplateContext,
+        base_url: &str,
+        user_settings: Option<&std::collections::HashMap<String, String>>,
+    ) -> Result<()> {
+        if LoginMethod::of(definition).is_none() {
+            return Ok(());
+        }
+        let Some(session) = &self.session else {
+            // Not bound to an indexer: nothing to cache, log in every time
+            return self
+                .login(definition, ctx, base_url, user_settings, &[])
+                .await;
+        };
+        if session.is_logged_in() {
+            return Ok(());
+        }
+
+        let _guard = session.login_lock.lock().await;
+        // Another search may have logged in while we waited
+        if session.is_logged_in() {
+            return Ok(());
+        }
+        if definition
+            .login
+            .as_ref()
+            .is_some_and(|l| l.captcha.is_some())
+        {
+            return Err(IndexerError::Login(format!(
+                "{} requires a captcha to log in - solve it from the indexer settings",
+                definition.name
+            ))
+            .into());
+        }
+        self.login(definition, ctx, base_url, user_settings, &[])
+            .await?;
+        session.set_logged_in(true);
+        Ok(())
+    }
+
+    /// Post the login form. `extra` inputs (e.g. a captcha answer) are sent
+    /// alongside the rendered `login.inputs`.
+    async fn login(
+        &self,
+        definition: &IndexerDefinition,
+        ctx: &TemplateContext,
+        base_url: &str,
+        user_settings: Option<&std::collections::HashMap<String, String>>,
+        extra: &[(String, String)],
+    ) -> Result<()> {
+        let Some(login) = &definition.login else {
+            return Ok(());
+        };
+        let login_url = make_absolute_url(login.path.as_deref().unwrap_or(""), base_url);
+
+        // Two-factor: fill the definition's 2FA setting with the current code
+        let totp = totp_code(user_settings)?;
+        let mut ctx = ctx.clone();
+        if let Some(code) = &totp {
+            for setting in &definition.settings {
+                let name = setting.name.to_lowercase();
+                if name.contains("2fa") || name.contains("totp") {
+                    ctx.config.insert(setting.name.clone(), code.clone());
+                }
+            }
+        }
+
+        let mut form: Vec<(String, String)> = login
+            .inputs
+            .iter()
+            .map(|(k, v)| (k.clone(), render_template(v, &ctx)))
+            .collect();
+        form.extend_from_slice(extra);
+        if let (Some(code), Some(input)) = (
+            &totp,
+            user_settings
+                .and_then(|s| s.get("_totpInput"))
+                .filter(|s| !s.is_empty()),
+        ) {
+            form.push((input.clone(), code.clone()));
+        }
+
+        tracing::info!("Logging in to {} at {}", definition.name, login_url);
+        let (url, request) = match LoginMethod::of(definition).unwrap_or(LoginMethod::Post) {
+            LoginMethod::Cookie => {
+                self.add_login_cookie(definition, &ctx, &form, base_url)?;
+                (login_url, None)
+            }
+            LoginMethod::Get => {
+                let request = self.client.get(&login_url).query(&form);
+                (login_url, Some(request))
+            }
+            LoginMethod::Post => {
+                let request = self.client.post(&login_url).form(&form);
+                (login_url, Some(request))
+            }
+            LoginMethod::Form => {
+                self.throttle(&login_url).await?;
+                let request = self
+                    .client
+                    .get(&login_url)
+                    .header("User-Agent", DEFAULT_USER_AGENT);
+                let response = apply_user_headers(request, user_settings).send().await?;
+                let server = server_header(&response);
+                let body = response.text().await?;
+                traffic::add(body.len());
+                challenge::check(&login_url, server.as_deref(), &body)?;
+
+                let mut login_form = login::find_form(&body, &login_url, login.form.as_deref())?;
+                login::fill(&mut login_form.fields, &form);
+                let request = if login_form.get {
+                    self.client
+                        .get(&login_form.action)
+                        .query(&login_form.fields)
+                } else {
+                    self.client
+                        .post(&login_form.action)
+                        .form(&login_form.fields)
+                };
+                (login_form.action, Some(request))
+            }
+        };
+
+        if let Some(request) = request {
+            self.throttle(&url).await?;
+            let request = request.header("User-Agent", DEFAULT_USER_AGENT);
+            let response = apply_user_headers(request, user_settings).send().await?;
+            let status = response.status();
+            let server = server_header(&response);
+            let body = response.text().await?;
+            traffic::add(body.len());
+            challenge::check(&url, server.as_deref(), &body)?;
+            if !status.is_success() {
+                return Err(IndexerError::from_status(
+                    status,
+                    format!("Login to {} failed: HTTP {}", definition.name, status),
+                )
+                .into());
+            }
+        }
+
+        // Verify with login.test (if defined)
+        if let Some(test) = &login.test {
+            let test_url = make_absolute_url(&test.path, base_url);
+            self.throttle(&test_url).await?;
+            let body = apply_user_headers(self.client.get(&test_url), user_settings)
+                .send()
+                .await?
+                .text()
+                .await?;
+            traffic::add(body.len());
+            if let Some(selector) = &test.selector
+                && !page_matches(&body, selector)
+            {
+                if totp.is_some() {
+                    return Err(IndexerError::Login(format!(
+                        "Login to {} failed - the two-factor code was rejected (check the TOTP secret and the system clock)",
+                        definition.name
+                    ))
+                    .into());
+                }
+                if LoginMethod::of(definition) == Some(LoginMethod::Cookie) {
+                    return Err(IndexerError::Login(format!(
+                        "Login to {} failed - the cookie was rejected or has expired",
+                        definition.name
+                    ))
+                    .into());
+                }
+                return Err(IndexerError::Login(format!(
+                    "Login to {} failed - check the username/password settings",
+                    definition.name
+                ))
+                .into());
+            }
+        }
+
+        tracing::info!("Logged in to {}", definition.name);
+        Ok(())
+    }
+
+    /// `login.method: cookie`: put the user's cookie (the rendered `cookie`
+    /// input, or the `cookie` setting) in the cookie jar
+    fn add_login_cookie(
+        &self,
+        definition: &IndexerDefinition,
+        ctx: &TemplateContext,
+        inputs: &[(String, String)],
+        base_url: &str,
+    ) -> Result<()> {
+        let cookie = inputs
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case("cookie"))
+            .map(|(_, value)| value.clone())
+            .or_else(|| ctx.config.get("cookie").cloned())
+            .unwrap_or_default();
+        let pairs = login::cookie_pairs(&cookie);
+        if pairs.is_empty() {
+            return Err(IndexerError::Login(format!(
+                "{} logs in with a browser cookie - set it with `lodestarr indexer settings {} --set cookie`",
+                definition.name, definition.id
+            ))
+            .into());
+        }
+        let url = url::Url::parse(base_url)
+            .map_err(|e| anyhow::anyhow!("Invalid base URL {}: {}", base_url, e))?;
+        for pair in pairs {
+            self.jar.add_cookie_str(pair, &url);
+        }
+        Ok(())
+    }
+
+    /// Put the user's browser cookie (the `cookie` setting) in the cookie jar
+    /// for each of the tracker's domains, next to the session's own cookies.
+    /// Unlike `_cookie`, which replaces the whole header, a login still works.
+    fn add_user_cookie(
+        &self,
+        definition: &IndexerDefinition,
+        user_settings: Option<&std::collections::HashMap<String, String>>,
+    ) {
+        let Some(cookie) = user_settings.and_then(|s| s.get(settings::COOKIE)) else {
+            return;
+        };
+        let pairs = login::cookie_pairs(cookie);
+        for link in &definition.links {
+            if let Ok(url) = url::Url::parse(link) {
+                for pair in &pairs {
+                    self.jar.add_cookie_str(pair, &url);
+                }
+            }
+        }
+    }
+
+    /// Fetch the login page's captcha image (`login.captcha.selector`).
+    ///
+    /// Uses the indexer's session cookies, so the answer passed to
+    /// [`Self::login_with_captcha`] matches the image the user saw.
+    /// Returns the image bytes and content type.
+    pub async fn fetch_captcha(
+        &self,
+        definition: &IndexerDefinition,
+        user_settings: Option<&std::collections::HashMap<String, String>>,
+    ) -> Result<(Vec<u8>, String)> {
+        let login = definition
+            .login
+            .as_ref()
+            .ok_or_else(|| anyhow::anyhow!("{} has no login", definition.name))?;
+        let captcha = login
+            .captcha
+            .as_ref()
+            .ok_or_else(|| anyhow::anyhow!("{} does not use a captcha", definition.name))?;
+        if let Some(kind) = &captcha.captcha_type
+            && !kind.eq_ignore_ascii_case("image")
+        {
+            anyhow::bail!("Unsupported captcha type '{}'", kind);
+        }
+        let selector = captcha
+            .selector
+            .as_deref()
+            .ok_or_else(|| anyhow::anyhow!("Captcha has no image selector"))?;
+
+        let base_url = selected_base_url(definition, user_settings)?;
+        let login_url = make_absolute_url(login.path.as_deref().unwrap_or(""), &base_url);
+        self.throttle(&login_url).await?;
+        let request = self
+            .client
+            .get(&login_url)
+            .header("User-Agent", DEFAULT_USER_AGENT);
+        let body = apply_user_headers(request, user_settings)
+            .send()
+            .await?
+            .text()
+            .await?;
+        traffic::add(body.len());
+
+        let image_url = {
+            let document = Html::parse_document(&body);
+            let sel = Selector::parse(selector)
+                .map_err(|e| anyhow::anyhow!("Invalid captcha selector: {:?}", e))?;
+            let src = document
+                .select(&sel)
+                .next()
+                .and_then(|el| el.value().attr("src"))
+                .ok_or_else(|| {
+                    IndexerError::Parse("Captcha image not found on login page".to_string())
+                })?;
+            make_absolute_url(src, &base_url)
+        };
+
+        self.throttle(&image_url).await?;
+        let response = apply_user_headers(self.client.get(&image_url), user_settings)
+            .send()
+            .await?;
         if !response.status().is_success() {
-            anyhow::bail!("Download failed: HTTP {}", response.status());
+            let status = response.status();
+            return Err(IndexerError::from_status(
+                status,
+                format!("Failed to fetch captcha: HTTP {}", status),
+            )
+            .into());
         }
+        let content_type = response
+            .headers()
+            .get(reqwest::header::CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .unwrap_or("image/png")
+            .to_string();
+        let bytes = response.bytes().await?;
+        traffic::add(bytes.len());
+        Ok((bytes.to_vec(), content_type))
+    }
 
-        Ok(response.bytes().await?.to_vec())
+    /// Complete the login with the user's captcha answer
+    pub async fn login_with_captcha(
+        &self,
+        definition: &IndexerDefinition,
+        user_settings: Option<&std::collections::HashMap<String, String>>,
+        answer: &str,
+    ) -> Result<()> {
+        let input = definition
+            .login
+            .as_ref()
+            .and_then(|l| l.captcha.as_ref())
+            .and_then(|c| c.input.clone())
+            .unwrap_or_else(|| "captcha".to_string());
+        let base_url = selected_base_url(definition, user_settings)?;
+        let ctx =
+            TemplateContext::default().with_config(template_config(definition, user_settings));
+
+        self.login(
+            definition,
+            &ctx,
+            &base_url,
+            user_settings,
+            &[(input, answer.to_string())],
+        )
+        .await?;
+        if let Some(session) = &self.session {
+            session.set_logged_in(true);
+        }
+        Ok(())
     }
 
     /// Execute a search against an indexer
@@ -153,36 +1039,119 @@ impl SearchExecutor {
         query: &SearchQuery,
         user_settings: Option<&std::collections::HashMap<String, String>>,
     ) -> Result<Vec<TorrentResult>> {
-        // Get base URL - use mirror selection if set
-        let mirror_index: usize = user_settings
-            .and_then(|s| s.get("_mirror"))
-            .and_then(|v| v.parse().ok())
-            .unwrap_or(0);
-
-        // Combine links and legacylinks for mirror selection
-        let all_links: Vec<&str> = definition
-            .links
-            .iter()
-            .chain(definition.legacylinks.iter())
-            .map(|s| s.as_str())
-            .collect();
-
-        let base_url = all_links
-            .get(mirror_index)
-            .or_else(|| all_links.first())
-            .ok_or_else(|| anyhow::anyhow!("No base URL configured"))?;
+        crate::http::ensure_proxy(self.proxy_url.as_deref()).await?;
+        if let Some(remote) = &self.remote {
+            return remote.search(&definition.id, query).await;
+        }
+        let started_at = chrono::Utc::now();
+        let start = Instant::now();
+        let span = tracing::info_span!("search", indexer = %definition.id);
+        let result = async {
+            if anime::is_anime(query) {
+                self.search_anime(definition, query, user_settings).await
+            } else {
+                match self.search_query(definition, query, user_settings).await {
+                    // Many HTML trackers ignore the season and episode parameters
+                    Ok(results) if results.is_empty() && keyword_retry(user_settings) => {
+                        match keyword_query(query) {
+                            Some(retry) => {
+                                tracing::debug!(
+                                    "Retrying {} as {:?}",
+                                    definition.id,
+                                    retry.query.as_deref().unwrap_or_default()
+                                );
+                                self.search_query(definition, &retry, user_settings).await
+                            }
+                            None => Ok(results),
+                        }
+                    }
+                    result => result,
+                }
+            }
+        }
+        .instrument(span)
+        .await;
+        self.timings
+            .finish(&definition.id, started_at, start, &result);
+        result
+    }
 
-        // Create template context with config defaults
-        let mut config = definition.get_default_config();
+    /// Run the anime query variants (see [`anime`]) and merge their results
+    async fn search_anime(
+        &self,
+        definition: &IndexerDefinition,
+        query: &SearchQuery,
+        user_settings: Option<&std::collections::HashMap<String, String>>,
+    ) -> Result<Vec<TorrentResult>> {
+        let title = query.query.as_deref().unwrap_or_default();
+        let info = anime::lookup(&self.client, title).await;
+        let mut results: Vec<TorrentResult> = Vec::new();
+        let mut first_error = None;
+        for variant in anime::variants(query, info.as_ref()) {
+            tracing::debug!(
+                "Anime search on {}: {:?}",
+                definition.id,
+                variant.query.as_deref().unwrap_or_default()
+            );
+            match self.search_query(definition, &variant, user_settings).await {
+                Ok(found) => {
+                    for r in found {
+                        if !results.iter().any(|existing| existing.guid == r.guid) {
+                            results.push(r);
+                        }
+                    }
+                }
+                Err(e) => {
+                    first_error.get_or_insert(e);
+                }
+            }
+        }
+        if results.is_empty()
+            && let Some(e) = first_error
+        {
+            return Err(e);
+        }
+        anime::tag_results(&mut results);
+        Ok(results)
+    }
 
-        // Apply user overrides
-        if let Some(settings) = user_settings {
-            for (k, v) in settings {
-                config.insert(k.clone(), v.clone());
+    /// A single search, retried after re-login or on a new Tor circuit
+    async fn search_query(
+        &self,
+        definition: &IndexerDefinition,
+        query: &SearchQuery,
+        user_settings: Option<&std::collections::HashMap<String, String>>,
+    ) -> Result<Vec<TorrentResult>> {
+        let result = match self.search_once(definition, query, user_settings).await {
+            // Got the login page back: log in again and retry once
+            Err(e) if session::is_login_required(&e) => {
+                tracing::info!("{}", e);
+                if let Some(session) = &self.session {
+                    session.set_logged_in(false);
+                }
+                self.search_once(definition, query, user_settings).await
             }
+            result => result,
+        };
+        match result {
+            Err(e) => match self.retry_on_new_circuit(&e) {
+                Some(retry) => retry.search_once(definition, query, user_settings).await,
+                None => Err(e),
+            },
+            ok => ok,
         }
+    }
 
-        let mut ctx = TemplateContext::from_search(query).with_config(config);
+    async fn search_once(
+        &self,
+        definition: &IndexerDefinition,
+        query: &SearchQuery,
+        user_settings: Option<&std::collections::HashMap<String, String>>,
+    ) -> Result<Vec<TorrentResult>> {
+        let base_url = self.search_base_url(definition, user_settings).await?;
+        let base_url = base_url.as_str();
+        let mut ctx = TemplateContext::from_search(query)
+            .with_config(template_config(definition, user_settings));
 
         // Apply preprocessing filters to keywords
         if !definition.search.preprocessingfilters.is_empty() {
@@ -218,16 +1187,32 @@ impl SearchExecutor {
         }
 
         // Map Torznab categories to Tracker categories
+        let fallback = categories::CategoryFallback::of(user_settings);
+        let mut fell_back = false;
         if !query.categories.is_empty() {
-            let resolved_categories: Vec<String> = query
-                .categories
-                .iter()
-                .filter_map(|&cat_id| definition.get_tracker_category(cat_id))
-                .collect();
+            let mut resolved_categories: Vec<String> = Vec::new();
+            for &cat_id in &query.categories {
+                let mapped = categories::tracker_categories(definition, cat_id, fallback);
+                fell_back |= mapped.fallback;
+                for id in mapped.ids {
+                    if !resolved_categories.contains(&id) {
+                        resolved_categories.push(id);
+                    }
+                }
+            }
 
             // If we found mapped categories, use them. Otherwise leave as is (raw Torznab IDs might be valid for some)
             if !resolved_categories.is_empty() {
                 ctx.query.categories = resolved_categories;
+            } else if fallback == categories::CategoryFallback::Strict
+                && !definition.caps.categorymappings.is_empty()
+            {
+                tracing::debug!(
+                    "{} maps none of the categories {:?}",
+                    definition.id,
+                    query.categories
+                );
+                return Ok(Vec::new());
             }
         }
 
@@ -238,7 +1223,18 @@ impl SearchExecutor {
             anyhow::bail!("No search path configured");
         }
 
+        self.add_user_cookie(definition, user_settings);
+        // Private trackers: log in first (no-op while the session is valid)
+        let start = Instant::now();
+        self.ensure_login(definition, &ctx, base_url, user_settings)
+            .instrument(tracing::debug_span!("login"))
+            .await?;
+        self.timings.record("login", start, None);
+
         let mut all_results = Vec::new();
+        let mut connection_failures = Vec::new();
+        let mut challenge_error = None;
+        let mut login_expired = None;
 
         // Try each matching path
         for (path_idx, search_path) in paths_to_try.iter().enumerate() {
@@ -251,6 +1247,7 @@ impl SearchExecutor {
 
             match self
                 .execute_search_path(definition, search_path, &ctx, base_url, user_settings)
+                .instrument(tracing::debug_span!("search_path", path = %search_path.path))
                 .await
             {
                 Ok(results) => {
@@ -259,11 +1256,32 @@ impl SearchExecutor {
                 }
                 Err(e) => {
                     tracing::warn!("Path {} failed: {}", path_idx + 1, e);
+                    if crate::http::is_connection_error(&e) {
+                        connection_failures.push(e);
+                    } else if challenge::protection_of(&e).is_some() {
+                        challenge_error = Some(e);
+                    } else if session::is_login_required(&e) {
+                        login_expired = Some(e);
+                    }
                     // Continue to next path
                 }
             }
         }
 
+        // A challenge or login page explains an empty result better than "0 results"
+        if all_results.is_empty()
+            && let Some(e) = challenge_error.or(login_expired)
+        {
+            return Err(e);
+        }
+
+        // Surface the error if the tracker was unreachable on every path
+        if connection_failures.len() == paths_to_try.len()
+            && let Some(e) = connection_failures.pop()
+        {
+            return Err(e);
+        }
+
         tracing::info!(
             "Extracted {} total results from {} paths",
             all_results.len(),
@@ -281,9 +1299,52 @@ impl SearchExecutor {
             tracing::debug!("Truncated results to {} limit", result_limit);
         }
 
+        let start = Instant::now();
+        async {
+            super::details::fill(
+                self,
+                definition,
+                &mut all_results,
+                &ctx,
+                base_url,
+                user_settings,
+            )
+            .await;
+            if super::pubdate::enabled(user_settings) {
+                super::pubdate::fill_from_details(self, &mut all_results, user_settings).await;
+            }
+        }
+        .instrument(tracing::debug_span!("details"))
+        .await;
+        self.timings.record("details", start, None);
+
+        if fell_back {
+            categories::mark_fallback(&mut all_results, &query.categories);
+        }
         Ok(all_results)
     }
 
+    /// Base URL for a search: the chosen `_mirror`, or with
+    /// `[search] race_mirrors` the first of the definition's links to answer
+    async fn search_base_url(
+        &self,
+        definition: &IndexerDefinition,
+        user_settings: Option<&std::collections::HashMap<String, String>>,
+    ) -> Result<String> {
+        let mirror_chosen = user_settings.is_some_and(|s| s.contains_key("_mirror"));
+        if !mirror_chosen && definition.links.len() > 1 && mirrors::enabled() {
+            let start = Instant::now();
+            let winner = mirrors::fastest(&self.client, &definition.id, &definition.links)
+                .instrument(tracing::debug_span!("mirror"))
+                .await;
+            self.timings.record("mirror", start, winner.as_deref());
+            if let Some(winner) = winner {
+                return Ok(winner);
+            }
+        }
+        selected_base_url(definition, user_settings)
+    }
+
     /// Get search paths that match the query categories
     fn get_matching_paths<'a>(
         &self,
@@ -378,15 +1439,8 @@ impl SearchExecutor {
         };
 
         // Add default headers
-        // Check for user-provided user-agent override
-        let user_agent = user_settings
-            .and_then(|s| s.get("_userAgent"))
-            .filter(|s| !s.is_empty())
-            .map(|s| s.as_str())
-            .unwrap_or("Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/121.0.0.0 Safari/537.36");
-
         request = request
-            .header("User-Agent", user_agent)
+            .header("User-Agent", DEFAULT_USER_AGENT)
             .header(
                 "Accept",
                 if is_json {
@@ -397,15 +1451,6 @@ impl SearchExecutor {
             )
             .header("Accept-Language", "en-US,en;q=0.5");
 
-        // Add user-provided cookie if present
-        if let Some(cookie) = user_settings
-            .and_then(|s| s.get("_cookie"))
-            .filter(|s| !s.is_empty())
-        {
-            tracing::debug!("Injecting user cookie header");
-            request = request.header("Cookie", cookie.as_str());
-        }
-
         // Add custom headers from definition
         for (key, values) in &definition.search.headers {
             for value in values {
@@ -417,12 +1462,24 @@ impl SearchExecutor {
             }
         }
 
+        // User-Agent, cookie and header overrides win over everything above
+        request = apply_user_headers(request, user_settings);
+
         // Add form data for POST requests
         if is_post && !form_data.is_empty() {
             request = request.form(&form_data);
         }
 
+        let start = Instant::now();
+        self.throttle(&search_url).await?;
+        if start.elapsed().as_millis() > 0 {
+            self.timings.record("throttle", start, Some(&search_url));
+        }
+        // Until the response headers arrive: DNS, connect, TLS and the tracker
+        let start = Instant::now();
+        let logged = self.log_request(if is_post { "POST" } else { "GET" }, &search_url);
         let response = request.send().await?;
+        self.timings.record("request", start, Some(&search_url));
 
         // Handle redirects if needed
         let final_url = response.url().to_string();
@@ -430,11 +1487,33 @@ impl SearchExecutor {
             tracing::debug!("Redirected to: {}", final_url);
         }
 
-        if !response.status().is_success() {
-            anyhow::bail!("HTTP {} from {}", response.status(), search_url);
+        let status = response.status();
+        self.update_request(logged, |r| r.status = Some(status.as_u16()));
+        let headers = response.headers().clone();
+        let server = server_header(&response);
+        let start = Instant::now();
+        let body = response.text().await?;
+        traffic::add(body.len());
+        self.timings.record("body", start, Some(&search_url));
+
+        // Challenge pages are usually served as 403/503, sometimes as 200
+        challenge::check(&search_url, server.as_deref(), &body)?;
+        if !status.is_success() {
+            return Err(IndexerError::from_response(
+                status,
+                &headers,
+                format!("HTTP {} from {}", status, search_url),
+            )
+            .into());
         }
 
-        let body = response.text().await?;
+        // Without the login.test element we were served the login page
+        if !is_json
+            && let Some(selector) = login_test_selector(definition)
+            && !page_matches(&body, selector)
+        {
+            return Err(LoginRequired(definition.name.clone()).into());
+        }
 
         // DEBUG: Log response details
         tracing::debug!(
@@ -462,6 +1541,7 @@ impl SearchExecutor {
 
                     if !message.is_empty() {
                         tracing::warn!("Indexer returned error: {}", message);
+                        // Tracker-defined message: could be anything, leave unclassified
                         anyhow::bail!("Indexer error: {}", message);
                     }
                 }
@@ -471,13 +1551,21 @@ impl SearchExecutor {
         // Parse results based on response type
         // Use search_url as base for relative URL resolution (not just base_url)
         // This ensures download.php resolves to /forum/download.php not /download.php
+        let start = Instant::now();
         let results = if is_json {
             self.parse_json_results(definition, &body, base_url, ctx)
         } else {
             self.parse_html_results(definition, &body, &search_url, ctx)
-        }?;
+        };
+        self.timings.record("parse", start, Some(&search_url));
+        if let Ok(results) = &results {
+            self.update_request(logged, |r| r.rows = Some(results.len()));
+        }
+        if !body.trim().is_empty() && !results.as_ref().is_ok_and(|r| !r.is_empty()) {
+            self.keep_empty_response(&search_url, status, &body);
+        }
 
-        Ok(results)
+        results
     }
 
     /// Build search request URL and form data for a specific path
@@ -550,6 +1638,22 @@ impl SearchExecutor {
     }
 }
 
+/// Rows matched by a row selector. Comma-separated alternatives are combined.
+fn select_rows<'a>(document: &'a Html, selector: &str) -> Vec<scraper::ElementRef<'a>> {
+    let mut rows = Vec::new();
+    for selector_part in selector.split(',') {
+        let selector_chain = parse_selector_chain(selector_part);
+        if selector_chain.is_empty() {
+            continue;
+        }
+        rows.extend(apply_selector_chain(
+            vec![document.root_element()],
+            &selector_chain,
+        ));
+    }
+    rows
+}
+
 impl SearchExecutor {
     /// Parse HTML search results into TorrentResult objects
     fn parse_html_results(
@@ -562,27 +1666,20 @@ impl SearchExecutor {
         let document = Html::parse_document(html);
         let mut results = Vec::new();
 
-        let row_selector_str = render_template(&definition.search.rows.selector, base_ctx);
-        tracing::debug!("HTML row selector: '{}'", row_selector_str);
-
-        let mut all_rows = Vec::new();
-
-        // Handle comma-separated alternatives
-        for selector_part in row_selector_str.split(',') {
-            let selector_chain = parse_selector_chain(selector_part);
-            if selector_chain.is_empty() {
-                continue;
+        // The page isn't an error or login page here, so a layout the main
+        // selector doesn't know gets the fallbacks before counting as empty
+        let mut rows = Vec::new();
+        for (i, selector) in definition.search.rows.selectors().enumerate() {
+            let row_selector_str = render_template(selector, base_ctx);
+            tracing::debug!("HTML row selector: '{}'", row_selector_str);
+            rows = select_rows(&document, &row_selector_str);
+            if !rows.is_empty() {
+                if i > 0 {
+                    tracing::debug!("Rows matched fallback selector '{}'", row_selector_str);
+                }
+                break;
             }
-
-            // Start with document root
-            let current_elements: Vec<scraper::ElementRef> = vec![document.root_element()];
-
-            // Apply selector chain
-            let rows = apply_selector_chain(current_elements, &selector_chain);
-            all_rows.extend(rows);
         }
-
-        let rows = all_rows;
         tracing::info!("Found {} rows (proper chain parsing)", rows.len());
 
         // Iterate over rows
@@ -629,11 +1726,22 @@ impl SearchExecutor {
 
         // Parse JSON
         let json: serde_json::Value = serde_json::from_str(json_str)
-            .map_err(|e| anyhow::anyhow!("Failed to parse JSON: {}", e))?;
+            .map_err(|e| IndexerError::Parse(format!("Failed to parse JSON: {}", e)))?;
 
         // Get rows using selector path (e.g., "data.movies" or "$")
         let row_selector = &definition.search.rows.selector;
-        let items = self.get_json_path(&json, row_selector);
+        let fallback = definition
+            .search
+            .rows
+            .fallbacks
+            .iter()
+            .filter_map(|selector| self.get_json_path(&json, selector))
+            .find(|v| v.as_array().is_some_and(|a| !a.is_empty()));
+        let items = match self.get_json_path(&json, row_selector) {
+            Some(v) if v.as_array().is_some_and(|a| a.is_empty()) => fallback.or(Some(v)),
+            None => fallback,
+            items => items,
+        };
 
         let items = match items {
             Some(serde_json::Value::Array(arr)) => arr,
@@ -747,6 +1855,7 @@ mod tests {
             encoding: "utf-8".to_string(),
             followredirect: false,
             request_delay: None,
+            timezone: None,
             links: vec!["http://example.com/".to_string()],
             legacylinks: Vec::new(),
             caps: Default::default(),
@@ -773,6 +1882,7 @@ mod tests {
                 keywordsfilters: Vec::new(),
                 error: Vec::new(),
                 preprocessingfilters: Vec::new(),
+                details: None,
                 rows: crate::indexer::definition::RowSelector {
                     selector: "".to_string(),
                     ..Default::default()
@@ -780,9 +1890,64 @@ mod tests {
                 fields: crate::indexer::definition::Fields::default(),
             },
             download: None,
+            replaces: Vec::new(),
+            testlinktorrent: None,
+            certificates: Vec::new(),
+            unknown_fields: Vec::new(),
         }
     }
 
+    #[test]
+    fn test_login_page_detection() {
+        let mut def = make_stub_definition("get");
+        assert_eq!(login_test_selector(&def), None);
+
+        def.login = Some(crate::indexer::definition::Login {
+            path: Some("login.php".to_string()),
+            method: None,
+            inputs: HashMap::new(),
+            form: None,
+            submitbutton: None,
+            captcha: None,
+            test: Some(crate::indexer::definition::LoginTest {
+                path: "index.php".to_string(),
+                selector: Some("a[href*=\"logout.php\"]".to_string()),
+            }),
+            cookies: Vec::new(),
+        });
+        let selector = login_test_selector(&def).unwrap();
+
+        let logged_in = r#"<html><a href="/logout.php">Logout</a><table></table></html>"#;
+        let login_page = r#"<html><form action="login.php"><input name="username"></form></html>"#;
+        assert!(page_matches(logged_in, selector));
+        assert!(!page_matches(login_page, selector));
+    }
+
+    #[test]
+    fn test_user_header_overrides() {
+        let settings = HashMap::from([
+            ("_userAgent".to_string(), "CustomAgent/1.0".to_string()),
+            (
+                "_headers".to_string(),
+                "Referer: https://tracker.example/\nbogus line\nX-Api: 1".to_string(),
+            ),
+        ]);
+        let request = apply_user_headers(
+            Client::new()
+                .get("http://example.com")
+                .header("User-Agent", DEFAULT_USER_AGENT),
+            Some(&settings),
+        )
+        .build()
+        .unwrap();
+
+        let headers = request.headers();
+        assert_eq!(headers.get_all("user-agent").iter().count(), 1);
+        assert_eq!(headers["user-agent"], "CustomAgent/1.0");
+        assert_eq!(headers["referer"], "https://tracker.example/");
+        assert_eq!(headers["x-api"], "1");
+    }
+
     #[tokio::test]
     async fn test_build_request_get() {
         let def = make_stub_definition("get");
@@ -835,4 +2000,333 @@ mod tests {
         assert_eq!(chain2.len(), 2);
         assert_eq!(chain2[0].contains, Some("Value with spaces".to_string()));
     }
+
+    #[tokio::test]
+    async fn test_fallback_row_selectors() {
+        let mut def = make_stub_definition("get");
+        def.search.rows.selector = "table.desktop tr".to_string();
+        def.search.fields = serde_yml::from_str("title: {selector: a}").unwrap();
+        let executor = SearchExecutor::new(None).unwrap();
+        let ctx = TemplateContext::default();
+        let mobile = r#"<ul class="mobile"><li><a href="/t/1">Debian</a></li><li><a href="/t/2">Arch</a></li></ul>"#;
+
+        let parse = |def: &IndexerDefinition, html: &str| {
+            executor
+                .parse_html_results(def, html, "http://example.com/", &ctx)
+                .unwrap()
+        };
+        assert!(parse(&def, mobile).is_empty());
+
+        def.search.rows.fallbacks = vec!["div.none li".to_string(), "ul.mobile li".to_string()];
+        let results = parse(&def, mobile);
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[1].title, "Arch");
+
+        // The main selector wins when it finds rows
+        let desktop =
+            r#"<table class="desktop"><tr><td><a href="/t/3">Fedora</a></td></tr></table>"#;
+        assert_eq!(parse(&def, &format!("{desktop}{mobile}")).len(), 1);
+    }
+
+    #[test]
+    fn test_keyword_query() {
+        let query = SearchQuery {
+            search_type: SearchType::TvSearch,
+            query: Some("The Expanse ".to_string()),
+            season: Some(3),
+            episode: Some(7),
+            tvdb_id: Some(280619),
+            ..Default::default()
+        };
+        let retry = keyword_query(&query).unwrap();
+        assert_eq!(retry.search_type, SearchType::Search);
+        assert_eq!(retry.query.as_deref(), Some("The Expanse S03E07"));
+        assert_eq!(
+            (retry.season, retry.episode, retry.tvdb_id),
+            (None, None, None)
+        );
+
+        let season = SearchQuery {
+            episode: None,
+            ..query.clone()
+        };
+        assert_eq!(
+            keyword_query(&season).unwrap().query.as_deref(),
+            Some("The Expanse S03")
+        );
+        // Nothing to add, or nothing to search for
+        assert!(
+            keyword_query(&SearchQuery {
+                season: None,
+                ..query.clone()
+            })
+            .is_none()
+        );
+        assert!(
+            keyword_query(&SearchQuery {
+                query: None,
+                ..query.clone()
+            })
+            .is_none()
+        );
+        assert!(
+            keyword_query(&SearchQuery {
+                search_type: SearchType::Search,
+                ..query
+            })
+            .is_none()
+        );
+    }
+
+    /// Tracker with a CSRF-protected login form, answering `/index.php`
+    /// with a logout link once the `uid` cookie is set
+    async fn tracker() -> String {
+        use axum::http::{HeaderMap, StatusCode, header};
+        use axum::routing::{get, post};
+
+        let app = axum::Router::new()
+            .route(
+                "/login.php",
+                get(|| async {
+                    axum::response::Html(
+                        r#"<form action="take.php" method="post">
+                        <input type="hidden" name="csrf" value="abc">
+                        <input name="username"><input type="password" name="password">
+                        <img id="captcha" src="/captcha.png">
+                        </form>"#,
+                    )
+                }),
+            )
+            .route(
+                "/captcha.png",
+                get(|| async { ([(header::CONTENT_TYPE, "image/png")], "PNG") }),
+            )
+            .route(
+                "/take.php",
+                post(
+                    |axum::Form(form): axum::Form<HashMap<String, String>>| async move {
+                        // A captcha answer, when sent, has to be right
+                        if form.get("csrf").map(String::as_str) == Some("abc")
+                            && form.get("password").map(String::as_str) == Some("secret")
+                            && form.get("captcha").is_none_or(|c| c == "42")
+                        {
+                            ([(header::SET_COOKIE, "uid=1; Path=/")], "welcome")
+                        } else {
+                            ([(header::SET_COOKIE, "failed=1; Path=/")], "try again")
+                        }
+                    },
+                ),
+            )
+            .route(
+                "/index.php",
+                get(|headers: HeaderMap| async move {
+                    let cookie = headers
+                        .get(header::COOKIE)
+                        .and_then(|c| c.to_str().ok())
+                        .unwrap_or_default();
+                    if cookie.contains("uid=1") {
+                        (
+                            StatusCode::OK,
+                            r#"<a class="logout" href="/logout">Log out</a>"#,
+                        )
+                    } else {
+                        (StatusCode::OK, r#"<a href="/login.php">Log in</a>"#)
+                    }
+                }),
+            );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await });
+        format!("http://{}/", addr)
+    }
+
+    #[tokio::test]
+    async fn test_login_methods() {
+        let base_url = tracker().await;
+        let with_login = |method: &str| {
+            let mut def = make_stub_definition("get");
+            def.login = Some(
+                serde_yml::from_str(&format!(
+                    r#"
+path: login.php
+method: {method}
+inputs:
+  username: "{{{{ .Config.username }}}}"
+  password: "{{{{ .Config.password }}}}"
+  cookie: "{{{{ .Config.cookie }}}}"
+test: {{path: index.php, selector: a.logout}}
+"#
+                ))
+                .unwrap(),
+            );
+            def
+        };
+        let login = |def: IndexerDefinition, settings: &[(&str, &str)]| {
+            let base_url = base_url.clone();
+            let settings: HashMap<String, String> = settings
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect();
+            async move {
+                let executor = SearchExecutor::new(None).unwrap();
+                let ctx = TemplateContext::default().with_config(settings);
+                executor.login(&def, &ctx, &base_url, None, &[]).await
+            }
+        };
+
+        // The form's hidden CSRF field is sent along with the credentials
+        let form = with_login("form");
+        login(form.clone(), &[("username", "me"), ("password", "secret")])
+            .await
+            .unwrap();
+        let e = login(form, &[("username", "me"), ("password", "wrong")])
+            .await
+            .unwrap_err();
+        assert!(e.to_string().contains("username/password"), "{e}");
+        // A plain post has no CSRF token
+        assert!(
+            login(
+                with_login("post"),
+                &[("username", "me"), ("password", "secret")]
+            )
+            .await
+            .is_err()
+        );
+
+        let cookie = with_login("cookie");
+        login(cookie.clone(), &[("cookie", "uid=1; lang=en")])
+            .await
+            .unwrap();
+        let e = login(cookie.clone(), &[("cookie", "uid=2")])
+            .await
+            .unwrap_err();
+        assert!(e.to_string().contains("cookie was rejected"), "{e}");
+        let e = login(cookie, &[]).await.unwrap_err();
+        assert!(e.to_string().contains("--set cookie"), "{e}");
+
+        // Without a login block, the cookie setting still reaches the tracker
+        let mut open = make_stub_definition("get");
+        open.links = vec![base_url.clone()];
+        let executor = SearchExecutor::new(None).unwrap();
+        let settings = HashMap::from([("cookie".to_string(), "uid=1".to_string())]);
+        executor.add_user_cookie(&open, Some(&settings));
+        let page = executor
+            .fetch_page(&format!("{base_url}index.php"), Some(&settings))
+            .await
+            .unwrap();
+        assert!(page.contains("logout"), "{page}");
+    }
+
+    #[tokio::test]
+    async fn test_captcha_login() {
+        let base_url = tracker().await;
+        let mut def = make_stub_definition("get");
+        def.links = vec![base_url];
+        def.login = Some(
+            serde_yml::from_str(
+                r#"
+path: login.php
+method: form
+inputs:
+  username: "{{ .Config.username }}"
+  password: "{{ .Config.password }}"
+captcha: {type: image, selector: "img#captcha", input: captcha}
+test: {path: index.php, selector: a.logout}
+"#,
+            )
+            .unwrap(),
+        );
+        let settings = HashMap::from([
+            ("username".to_string(), "me".to_string()),
+            ("password".to_string(), "secret".to_string()),
+        ]);
+        let executor = SearchExecutor::new(None).unwrap();
+
+        let (image, content_type) = executor.fetch_captcha(&def, Some(&settings)).await.unwrap();
+        assert_eq!(image, b"PNG");
+        assert_eq!(content_type, "image/png");
+
+        // The answer is posted with the login form
+        assert!(
+            executor
+                .login_with_captcha(&def, Some(&settings), "41")
+                .await
+                .is_err()
+        );
+        executor
+            .login_with_captcha(&def, Some(&settings), "42")
+            .await
+            .unwrap();
+
+        def.login
+            .as_mut()
+            .unwrap()
+            .captcha
+            .as_mut()
+            .unwrap()
+            .captcha_type = Some("recaptcha".to_string());
+        assert!(executor.fetch_captcha(&def, Some(&settings)).await.is_err());
+    }
+
+    /// SOCKS5 proxy recording the username of each connection, then
+    /// dropping it before connecting anywhere
+    async fn socks_proxy() -> (String, std::sync::Arc<std::sync::Mutex<Vec<String>>>) {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("socks5h://{}", listener.local_addr().unwrap());
+        let usernames = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let seen = usernames.clone();
+        tokio::spawn(async move {
+            while let Ok((mut socket, _)) = listener.accept().await {
+                let seen = seen.clone();
+                tokio::spawn(async move {
+                    let mut greeting = [0; 2];
+                    socket.read_exact(&mut greeting).await?;
+                    let mut methods = vec![0; greeting[1] as usize];
+                    socket.read_exact(&mut methods).await?;
+                    // Username/password authentication
+                    socket.write_all(&[5, 2]).await?;
+                    let mut header = [0; 2];
+                    socket.read_exact(&mut header).await?;
+                    let mut username = vec![0; header[1] as usize];
+                    socket.read_exact(&mut username).await?;
+                    let len = socket.read_u8().await?;
+                    let mut password = vec![0; len as usize];
+                    socket.read_exact(&mut password).await?;
+                    seen.lock()
+                        .unwrap()
+                        .push(String::from_utf8_lossy(&username).into_owned());
+                    socket.write_all(&[1, 0]).await?;
+                    Ok::<_, std::io::Error>(())
+                });
+            }
+        });
+        (url, usernames)
+    }
+
+    #[tokio::test]
+    async fn test_tor_isolation_retries_on_new_circuit() {
+        let (proxy_url, usernames) = socks_proxy().await;
+        crate::http::configure(&crate::config::Config {
+            http: crate::config::HttpConfig {
+                tor_isolation: true,
+                ..Default::default()
+            },
+            ..Default::default()
+        });
+        let executor = SearchExecutor::for_indexer("circuit-test", Some(&proxy_url), None).unwrap();
+        let def = make_stub_definition("get");
+        let result = executor
+            .download(&def, "http://tracker.example/1.torrent", None)
+            .await;
+        crate::http::configure(&crate::config::Config::default());
+
+        // The failed connection is retried once, on the indexer's next circuit
+        assert!(result.is_err());
+        assert_eq!(
+            *usernames.lock().unwrap(),
+            vec!["lodestarr-circuit-test-0", "lodestarr-circuit-test-1"]
+        );
+    }
 }
@@ -0,0 +1,130 @@
+//! Skipping indexers that keep failing
+//!
+//! Every indexer search in an aggregate search updates the indexer's row in
+//! `indexer_health`. After `[search] failure_threshold` failures in a row the
+//! indexer is marked failing, and aggregate searches skip it for
+//! `failure_backoff_mins`. Each failure after that doubles the wait, up to a
+//! day. When the wait is over the next aggregate search tries the indexer
+//! again, and a success clears the mark. Turning the indexer on again
+//! through its status endpoint clears it straight away.
+//!
+//! Rate limits don't count towards the threshold, since [`super::backoff`]
+//! already pauses the indexer for as long as the tracker asks.
+
+use super::ErrorKind;
+use crate::config::SearchConfig;
+use crate::db::{self, DbPool};
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+use std::time::Duration;
+
+/// Longest an indexer is skipped
+const MAX_BACKOFF: Duration = Duration::from_secs(24 * 3600);
+
+/// How long to skip an indexer with `consecutive` failures in a row, if at all
+pub fn backoff(config: &SearchConfig, consecutive: u32) -> Option<Duration> {
+    if config.failure_threshold == 0 || consecutive < config.failure_threshold {
+        return None;
+    }
+    let doublings = (consecutive - config.failure_threshold).min(16);
+    let base = Duration::from_secs(config.failure_backoff_mins.max(1) * 60);
+    Some((base * 2u32.pow(doublings)).min(MAX_BACKOFF))
+}
+
+/// Record the outcome of one search, marking the indexer failing when it
+/// reaches the threshold
+pub fn record(
+    pool: &DbPool,
+    config: &SearchConfig,
+    indexer: &str,
+    duration_ms: u128,
+    error: Option<ErrorKind>,
+) -> anyhow::Result<()> {
+    let consecutive = db::record_indexer_health(pool, indexer, duration_ms, error)?;
+    if error.is_none_or(|kind| kind == ErrorKind::RateLimited) {
+        return Ok(());
+    }
+    if let Some(wait) = backoff(config, consecutive) {
+        tracing::warn!(
+            "{} failed {} searches in a row, skipping it for {} minutes",
+            indexer,
+            consecutive,
+            wait.as_secs() / 60
+        );
+        let until = Utc::now() + chrono::Duration::from_std(wait).unwrap_or_default();
+        db::set_indexer_failing(pool, indexer, Some(until))?;
+    }
+    Ok(())
+}
+
+/// Indexers aggregate searches skip for now, with when they'll be tried again
+/// (empty if the database fails)
+pub fn failing(pool: &DbPool) -> HashMap<String, DateTime<Utc>> {
+    db::get_failing_indexers(pool).unwrap_or_else(|e| {
+        tracing::debug!("Failed to read failing indexers: {}", e);
+        HashMap::new()
+    })
+}
+
+/// Clear an indexer's failing mark
+pub fn reenable(pool: &DbPool, indexer: &str) -> anyhow::Result<()> {
+    db::set_indexer_failing(pool, indexer, None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_failing() {
+        let config = SearchConfig {
+            failure_threshold: 3,
+            failure_backoff_mins: 5,
+            ..Default::default()
+        };
+        assert_eq!(backoff(&config, 2), None);
+        assert_eq!(backoff(&config, 3), Some(Duration::from_secs(300)));
+        assert_eq!(backoff(&config, 5), Some(Duration::from_secs(1200)));
+        assert_eq!(backoff(&config, 40), Some(MAX_BACKOFF));
+        let never = SearchConfig {
+            failure_threshold: 0,
+            ..Default::default()
+        };
+        assert_eq!(backoff(&never, 100), None);
+
+        let path =
+            std::env::temp_dir().join(format!("lodestarr-test-failing-{}.db", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let pool = db::init_db(&path);
+        let fail = |kind| record(&pool, &config, "yts", 100, Some(kind)).unwrap();
+
+        fail(ErrorKind::Network);
+        fail(ErrorKind::Parse);
+        // Rate limits don't add to the run
+        fail(ErrorKind::RateLimited);
+        assert!(failing(&pool).is_empty());
+        fail(ErrorKind::Network);
+        assert!(failing(&pool).contains_key("yts"));
+
+        let health = db::get_indexer_health(&pool).unwrap();
+        assert_eq!(health[0].failures, 4);
+        assert_eq!(health[0].consecutive_failures, 3);
+        assert_eq!(health[0].last_error, Some(ErrorKind::Network));
+
+        // Turning it back on, or a success, clears the mark
+        reenable(&pool, "yts").unwrap();
+        assert!(failing(&pool).is_empty());
+        for _ in 0..3 {
+            fail(ErrorKind::Network);
+        }
+        record(&pool, &config, "yts", 100, None).unwrap();
+        assert!(failing(&pool).is_empty());
+        let health = db::get_indexer_health(&pool).unwrap();
+        assert_eq!(
+            (health[0].successes, health[0].consecutive_failures),
+            (1, 0)
+        );
+
+        let _ = std::fs::remove_file(path);
+    }
+}
@@ -2,7 +2,8 @@
 //!
 //! Implements filters like querystring, regexp, replace, dateparse, etc.
 
-use chrono::{NaiveDateTime, TimeZone, Utc};
+use chrono::{DateTime, FixedOffset, NaiveDateTime, TimeZone, Utc};
+use chrono_tz::Tz;
 use once_cell::sync::Lazy;
 use regex::Regex;
 use std::collections::HashMap;
@@ -30,8 +31,12 @@ static RE_TODAY_YESTERDAY: Lazy<Regex> = Lazy::new(|| {
     Regex::new(r"(?i)^(today|yesterday)[,\s]+(\d{1,2}:\d{2}(?::\d{2})?)\s*(am|pm)?")
         .expect("invalid today/yesterday regex")
 });
+static RE_UTC_OFFSET: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?i)^(?:utc|gmt)?\s*([+-])(\d{1,2})(?::?(\d{2}))?$")
+        .expect("invalid utc offset regex")
+});
 static RE_PARSE_SIZE: Lazy<Regex> = Lazy::new(|| {
-    Regex::new(r"([\d.]+)\s*(b|kb|mb|gb|tb|kib|mib|gib|tib)?").expect("invalid parse_size regex")
+    Regex::new(r"(\d[\d.,' \x{a0}\x{202f}]*)\s*([a-zа-яё]+)?").expect("invalid parse_size regex")
 });
 
 /// Get or compile a cached regex pattern
@@ -53,6 +58,14 @@ pub fn apply_filters_with_context(
     filters: &[Filter],
     ctx: &TemplateContext,
 ) -> String {
+    let zone = ctx.config.get("_timezone").and_then(|tz| {
+        let zone = parse_zone(tz);
+        if zone.is_none() && !tz.trim().is_empty() {
+            tracing::debug!("Ignoring invalid timezone '{}'", tz);
+        }
+        zone
+    });
+
     let mut result = value.to_string();
     for filter in filters {
         // Render filter args if they contain templates
@@ -61,7 +74,7 @@ pub fn apply_filters_with_context(
             name: filter.name.clone(),
             args: rendered_args,
         };
-        result = apply_filter(&result, &rendered_filter);
+        result = apply_filter(&result, &rendered_filter, zone);
     }
     result
 }
@@ -90,8 +103,8 @@ fn render_filter_args(args: &FilterArgs, ctx: &TemplateContext) -> FilterArgs {
     }
 }
 
-/// Apply a single filter to a value
-pub fn apply_filter(value: &str, filter: &Filter) -> String {
+/// Apply a single filter to a value, reading absolute dates in `zone` (UTC if `None`)
+pub fn apply_filter(value: &str, filter: &Filter, zone: Option<Zone>) -> String {
     match filter.name.as_str() {
         "querystring" => filter_querystring(value, &filter.args),
         "regexp" => filter_regexp(value, &filter.args),
@@ -107,11 +120,11 @@ pub fn apply_filter(value: &str, filter: &Filter) -> String {
         "urlencode" => urlencoding::encode(value).to_string(),
         "htmldecode" => html_escape::decode_html_entities(value).to_string(),
         "htmlencode" => html_escape::encode_text(value).to_string(),
-        "dateparse" => filter_dateparse(value, &filter.args),
-        "timeparse" => filter_dateparse(value, &filter.args), // alias for dateparse
+        "dateparse" => filter_dateparse(value, &filter.args, zone),
+        "timeparse" => filter_dateparse(value, &filter.args, zone), // alias for dateparse
         "timeago" => filter_timeago(value),
         "reltime" => filter_timeago(value), // alias for timeago
-        "fuzzytime" => filter_fuzzytime(value),
+        "fuzzytime" => filter_fuzzytime(value, zone),
         "validfilename" => filter_validfilename(value),
         "diacritics" => filter_diacritics(value),
         // Text case filters
@@ -157,7 +170,7 @@ pub fn apply_filter(value: &str, filter: &Filter) -> String {
                     name: base_name.to_string(),
                     args: filter.args.clone(),
                 };
-                return apply_filter(value, &base_filter);
+                return apply_filter(value, &base_filter, zone);
             }
             tracing::warn!("Unknown filter: {}", filter.name);
             value.to_string()
@@ -301,29 +314,77 @@ pub fn filter_striptags(input: &str) -> String {
     RE_STRIPTAGS.replace_all(input, "").to_string()
 }
 
-pub fn filter_dateparse(input: &str, args: &FilterArgs) -> String {
+/// Timezone that absolute tracker dates are interpreted in
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Zone {
+    Named(Tz),
+    Fixed(FixedOffset),
+}
+
+/// Parse a timezone setting: an IANA name ("Europe/Moscow") or a UTC offset
+/// ("+03:00", "-0500", "UTC+3")
+pub fn parse_zone(s: &str) -> Option<Zone> {
+    let s = s.trim();
+    if s.is_empty() {
+        return None;
+    }
+    if let Ok(tz) = s.parse::<Tz>() {
+        return Some(Zone::Named(tz));
+    }
+
+    let caps = RE_UTC_OFFSET.captures(s)?;
+    let hours: i32 = caps[2].parse().ok()?;
+    let minutes: i32 = caps.get(3).map_or(Ok(0), |m| m.as_str().parse()).ok()?;
+    let secs = (hours * 3600 + minutes * 60) * if &caps[1] == "-" { -1 } else { 1 };
+    FixedOffset::east_opt(secs).map(Zone::Fixed)
+}
+
+/// Convert a site-local time to UTC.
+///
+/// Dates slightly in the future (tracker clock skew or a wrong zone) are
+/// clamped to now so clients don't see releases from tomorrow.
+fn local_to_utc(dt: &NaiveDateTime, zone: Option<Zone>) -> Option<DateTime<Utc>> {
+    let utc = match zone {
+        None => Utc.from_utc_datetime(dt),
+        Some(Zone::Named(tz)) => tz.from_local_datetime(dt).earliest()?.with_timezone(&Utc),
+        Some(Zone::Fixed(offset)) => offset
+            .from_local_datetime(dt)
+            .earliest()?
+            .with_timezone(&Utc),
+    };
+    let now = Utc::now();
+    if utc > now && utc - now <= chrono::Duration::days(1) {
+        return Some(now);
+    }
+    Some(utc)
+}
+
+/// Parse `input` with a chrono format. Explicit offsets in the input win;
+/// otherwise the time is read in `zone`.
+fn parse_in_zone(input: &str, format: &str, zone: Option<Zone>) -> Option<String> {
+    if let Ok(dt) = DateTime::parse_from_str(input, format) {
+        return Some(dt.with_timezone(&Utc).to_rfc3339());
+    }
+    let dt = NaiveDateTime::parse_from_str(input, format).ok()?;
+    local_to_utc(&dt, zone).map(|dt| dt.to_rfc3339())
+}
+
+pub fn filter_dateparse(input: &str, args: &FilterArgs, zone: Option<Zone>) -> String {
     let format = args.as_str().unwrap_or_default();
 
     if format.is_empty() {
         // No format specified, try fuzzy parsing
-        return filter_fuzzytime(input);
+        return filter_fuzzytime(input, zone);
     }
 
     // Convert .NET format to chrono format
     let chrono_format = convert_dotnet_format(&format);
 
-    // Try parsing with chrono
-    if let Ok(dt) = NaiveDateTime::parse_from_str(input.trim(), &chrono_format) {
-        return Utc.from_utc_datetime(&dt).to_rfc3339();
-    }
-
-    // Try without trim
-    if let Ok(dt) = NaiveDateTime::parse_from_str(input, &chrono_format) {
-        return Utc.from_utc_datetime(&dt).to_rfc3339();
-    }
-
-    // Return original if parsing fails
-    input.to_string()
+    // Try parsing with chrono, then without trim
+    parse_in_zone(input.trim(), &chrono_format, zone)
+        .or_else(|| parse_in_zone(input, &chrono_format, zone))
+        // Return original if parsing fails
+        .unwrap_or_else(|| input.to_string())
 }
 
 /// Convert .NET date format to chrono format
@@ -399,7 +460,7 @@ fn filter_timeago(value: &str) -> String {
 }
 
 /// Parse fuzzy time expressions, handling various common formats
-fn filter_fuzzytime(value: &str) -> String {
+fn filter_fuzzytime(value: &str, zone: Option<Zone>) -> String {
     let cleaned = value.trim();
     if cleaned.is_empty() {
         return value.to_string();
@@ -428,8 +489,8 @@ fn filter_fuzzytime(value: &str) -> String {
     ];
 
     for fmt in &formats {
-        if let Ok(dt) = NaiveDateTime::parse_from_str(cleaned, fmt) {
-            return Utc.from_utc_datetime(&dt).to_rfc3339();
+        if let Some(parsed) = parse_in_zone(cleaned, fmt, zone) {
+            return parsed;
         }
     }
 
@@ -440,7 +501,12 @@ fn filter_fuzzytime(value: &str) -> String {
         let time_str = &caps[2];
         let meridiem = caps.get(3).map(|m| m.as_str().to_lowercase());
 
-        let now = Utc::now();
+        // "Today" is the site's today, not ours
+        let now = match zone {
+            None => Utc::now().naive_utc(),
+            Some(Zone::Named(tz)) => Utc::now().with_timezone(&tz).naive_local(),
+            Some(Zone::Fixed(offset)) => Utc::now().with_timezone(&offset).naive_local(),
+        };
         let target_date = if day_str == "yesterday" {
             now - chrono::Duration::days(1)
         } else {
@@ -463,8 +529,9 @@ fn filter_fuzzytime(value: &str) -> String {
         if let Ok(parsed_time) = NaiveDateTime::parse_from_str(
             &format!("{} {}", target_date.format("%Y-%m-%d"), full_time_str),
             &format!("%Y-%m-%d {}", time_fmt),
-        ) {
-            return Utc.from_utc_datetime(&parsed_time).to_rfc3339();
+        ) && let Some(dt) = local_to_utc(&parsed_time, zone)
+        {
+            return dt.to_rfc3339();
         }
     }
 
@@ -483,19 +550,23 @@ fn filter_validfilename(value: &str) -> String {
 }
 
 /// Parse file size to bytes
-pub fn parse_size(value: &str) -> u64 {
-    let lower = value.to_lowercase().replace([',', ' '], "");
+/// Parse a human readable size ("1.5 GB", "1,5 ГБ", "1 024 Mo") into bytes.
+///
+/// `decimal_comma` is the locale hint for ambiguous numbers like "1,024" or
+/// "1.024" (see [`decimal_comma`]); unambiguous separators are detected.
+pub fn parse_size(value: &str, decimal_comma: bool) -> u64 {
+    let lower = value.to_lowercase();
 
     if let Some(caps) = RE_PARSE_SIZE.captures(&lower) {
-        let num: f64 = caps[1].parse().unwrap_or(0.0);
+        let num = parse_localized_number(&caps[1], decimal_comma).unwrap_or(0.0);
         let unit = caps.get(2).map(|m| m.as_str()).unwrap_or("b");
 
         let multiplier = match unit {
-            "b" => 1.0,
-            "kb" => 1_000.0,
-            "mb" => 1_000_000.0,
-            "gb" => 1_000_000_000.0,
-            "tb" => 1_000_000_000_000.0,
+            "b" | "o" | "б" | "байт" => 1.0,
+            "kb" | "ko" | "кб" => 1_000.0,
+            "mb" | "mo" | "мб" => 1_000_000.0,
+            "gb" | "go" | "гб" => 1_000_000_000.0,
+            "tb" | "to" | "тб" => 1_000_000_000_000.0,
             "kib" => 1_024.0,
             "mib" => 1_048_576.0,
             "gib" => 1_073_741_824.0,
@@ -509,6 +580,82 @@ pub fn parse_size(value: &str) -> u64 {
     0
 }
 
+/// Returns true if numbers on sites in this language use a decimal comma
+/// ("1,5") and dot or space grouping ("1.024")
+pub fn decimal_comma(language: &str) -> bool {
+    let lang = language
+        .split(['-', '_'])
+        .next()
+        .unwrap_or_default()
+        .to_lowercase();
+    matches!(
+        lang.as_str(),
+        "ru" | "uk"
+            | "be"
+            | "bg"
+            | "cs"
+            | "da"
+            | "de"
+            | "el"
+            | "es"
+            | "fi"
+            | "fr"
+            | "hu"
+            | "it"
+            | "nb"
+            | "nl"
+            | "no"
+            | "pl"
+            | "pt"
+            | "ro"
+            | "sk"
+            | "sl"
+            | "sr"
+            | "sv"
+            | "tr"
+    )
+}
+
+/// Parse a number with locale grouping/decimal separators
+fn parse_localized_number(raw: &str, decimal_comma: bool) -> Option<f64> {
+    let digits: String = raw
+        .chars()
+        .filter(|c| !matches!(c, ' ' | '\'' | '\u{a0}' | '\u{202f}'))
+        .collect();
+    let digits = digits.trim_end_matches(['.', ',']);
+
+    let last_comma = digits.rfind(',');
+    let last_dot = digits.rfind('.');
+    let decimal = match (last_comma, last_dot) {
+        // Both present: whichever comes last is the decimal separator
+        (Some(c), Some(d)) => Some(if c > d { ',' } else { '.' }),
+        (Some(_), None) => single_separator(digits, ',', decimal_comma),
+        (None, Some(_)) => single_separator(digits, '.', !decimal_comma),
+        (None, None) => None,
+    };
+
+    let normalized: String = digits
+        .chars()
+        .filter_map(|c| match c {
+            ',' | '.' if Some(c) == decimal => Some('.'),
+            ',' | '.' => None,
+            c => Some(c),
+        })
+        .collect();
+    normalized.parse().ok()
+}
+
+/// Decide whether the only separator kind in `digits` is a decimal point.
+/// Repeated separators are grouping; a single one followed by exactly three
+/// digits is ambiguous and resolved by `prefer_decimal`.
+fn single_separator(digits: &str, sep: char, prefer_decimal: bool) -> Option<char> {
+    if digits.matches(sep).count() > 1 {
+        return None;
+    }
+    let after = digits.rsplit(sep).next().unwrap_or_default().len();
+    (after != 3 || prefer_decimal).then_some(sep)
+}
+
 // Math filter helper
 fn filter_math<F>(input: &str, args: &FilterArgs, op: F) -> String
 where
@@ -770,9 +917,34 @@ mod tests {
 
     #[test]
     fn test_parse_size() {
-        assert_eq!(parse_size("1.5 GB"), 1_500_000_000);
-        assert_eq!(parse_size("500 MB"), 500_000_000);
-        assert_eq!(parse_size("1 GiB"), 1_073_741_824);
+        assert_eq!(parse_size("1.5 GB", false), 1_500_000_000);
+        assert_eq!(parse_size("500 MB", false), 500_000_000);
+        assert_eq!(parse_size("1 GiB", false), 1_073_741_824);
+    }
+
+    #[test]
+    fn test_parse_size_locales() {
+        // Unambiguous decimal comma and grouped thousands
+        assert_eq!(parse_size("1,5 GB", false), 1_500_000_000);
+        assert_eq!(parse_size("1 024 MB", false), 1_024_000_000);
+        assert_eq!(parse_size("1\u{a0}024,50 MB", true), 1_024_500_000);
+        assert_eq!(parse_size("1.024,5 MB", true), 1_024_500_000);
+        assert_eq!(parse_size("1,024.5 MB", false), 1_024_500_000);
+        assert_eq!(parse_size("1.234.567 B", false), 1_234_567);
+
+        // Ambiguous "x,yyy" / "x.yyy" follow the language hint
+        assert_eq!(parse_size("1,234 MB", false), 1_234_000_000);
+        assert_eq!(parse_size("1,234 MB", true), 1_234_000);
+        assert_eq!(parse_size("1.024 MB", true), 1_024_000_000);
+        assert_eq!(parse_size("1.024 MB", false), 1_024_000);
+
+        // Localized units
+        assert_eq!(parse_size("700 МБ", true), 700_000_000);
+        assert_eq!(parse_size("1,37 Go", true), 1_370_000_000);
+
+        assert!(decimal_comma("ru-RU"));
+        assert!(decimal_comma("de"));
+        assert!(!decimal_comma("en-US"));
     }
 
     #[test]
@@ -781,16 +953,56 @@ mod tests {
         let now = Utc::now();
 
         // "2 hours ago"
-        let ago = filter_fuzzytime("2 hours ago");
-        assert!(ago.len() > 0);
+        let ago = filter_fuzzytime("2 hours ago", None);
+        assert!(!ago.is_empty());
 
         // "Today, 10:30"
         let today_str = format!("Today, {}", now.format("%H:%M"));
-        let parsed = filter_fuzzytime(&today_str);
+        let parsed = filter_fuzzytime(&today_str, None);
         // Should parse correctly
         assert!(parsed.contains(&now.format("%Y-%m-%d").to_string()));
     }
 
+    #[test]
+    fn test_dateparse_timezone() {
+        let args = FilterArgs::String("yyyy-MM-dd HH:mm".to_string());
+        let moscow = parse_zone("Europe/Moscow");
+        assert_eq!(
+            filter_dateparse("2020-06-01 12:00", &args, moscow),
+            "2020-06-01T09:00:00+00:00"
+        );
+        assert_eq!(
+            filter_dateparse("2020-06-01 12:00", &args, parse_zone("UTC-5")),
+            "2020-06-01T17:00:00+00:00"
+        );
+        // Default stays UTC
+        assert_eq!(
+            filter_dateparse("2020-06-01 12:00", &args, None),
+            "2020-06-01T12:00:00+00:00"
+        );
+        assert_eq!(
+            filter_fuzzytime("2020-06-01 12:00", parse_zone("+02:00")),
+            "2020-06-01T10:00:00+00:00"
+        );
+
+        assert_eq!(
+            parse_zone("+0530"),
+            FixedOffset::east_opt(5 * 3600 + 30 * 60).map(Zone::Fixed)
+        );
+        assert_eq!(parse_zone("Mars/Olympus"), None);
+        assert_eq!(parse_zone(""), None);
+    }
+
+    #[test]
+    fn test_future_dates_clamped() {
+        let soon = (Utc::now() + chrono::Duration::hours(3)).naive_utc();
+        let clamped = local_to_utc(&soon, None).unwrap();
+        assert!(clamped <= Utc::now());
+
+        let later = (Utc::now() + chrono::Duration::days(30)).naive_utc();
+        assert!(local_to_utc(&later, None).unwrap() > Utc::now());
+    }
+
     #[test]
     fn test_math_filters() {
         // Addition
@@ -861,10 +1073,10 @@ mod tests {
         };
         // html_escape::encode_text escapes <, >, &, " but not single quotes
         assert_eq!(
-            apply_filter("<script>alert('xss')</script>", &filter),
+            apply_filter("<script>alert('xss')</script>", &filter, None),
             "&lt;script&gt;alert('xss')&lt;/script&gt;"
         );
-        assert_eq!(apply_filter("a & b", &filter), "a &amp; b");
+        assert_eq!(apply_filter("a & b", &filter, None), "a &amp; b");
     }
 
     #[test]
@@ -956,13 +1168,13 @@ mod tests {
             name: "re_replace#S01toсезон1".to_string(),
             args: FilterArgs::Array(vec!["S(\\d+)".to_string(), "сезон $1".to_string()]),
         };
-        assert_eq!(apply_filter("S01", &filter), "сезон 01");
+        assert_eq!(apply_filter("S01", &filter, None), "сезон 01");
 
         // Another example with replace
         let filter2 = Filter {
             name: "replace#removethepostedontag".to_string(),
             args: FilterArgs::Array(vec!["Posted on".to_string(), "".to_string()]),
         };
-        assert_eq!(apply_filter("Posted on 2025", &filter2), " 2025");
+        assert_eq!(apply_filter("Posted on 2025", &filter2, None), " 2025");
     }
 }
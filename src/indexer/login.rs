@@ -0,0 +1,221 @@
+//! Login flows of the Cardigann `login` block
+//!
+//! - `post` (the default): post the rendered `login.inputs` to `login.path`
+//! - `get`: send them as the query string instead
+//! - `form`: load `login.path`, fill in the login form found there (keeping
+//!   its hidden fields, such as CSRF tokens) and submit it to its action
+//! - `cookie`: no request at all, the user's cookie is put in the session
+//!
+//! [`super::SearchExecutor`] performs the requests; this module holds the
+//! parts that don't touch the network.
+
+use super::definition::IndexerDefinition;
+use super::error::IndexerError;
+use super::result_builder::make_absolute_url;
+use scraper::{ElementRef, Html, Selector};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LoginMethod {
+    Post,
+    Get,
+    Form,
+    Cookie,
+}
+
+impl LoginMethod {
+    /// Login method if the definition requires a login we can perform
+    pub fn of(definition: &IndexerDefinition) -> Option<Self> {
+        let login = definition.login.as_ref()?;
+        match login
+            .method
+            .as_deref()
+            .map(str::to_ascii_lowercase)
+            .as_deref()
+        {
+            None | Some("post") => Some(Self::Post),
+            Some("get") => Some(Self::Get),
+            Some("form") => Some(Self::Form),
+            Some("cookie") => Some(Self::Cookie),
+            Some(m) => {
+                tracing::debug!("Login method '{}' not supported for {}", m, definition.id);
+                None
+            }
+        }
+    }
+}
+
+/// Login form found on a page
+#[derive(Debug, PartialEq)]
+pub struct LoginForm {
+    /// Absolute URL the form submits to
+    pub action: String,
+    /// True for `method="get"` forms
+    pub get: bool,
+    /// Fields the form would submit as it is
+    pub fields: Vec<(String, String)>,
+}
+
+/// Find the login form (`login.form`, or the first form with a password
+/// field, or the first form) on the page at `page_url`
+pub fn find_form(
+    body: &str,
+    page_url: &str,
+    selector: Option<&str>,
+) -> Result<LoginForm, IndexerError> {
+    let document = Html::parse_document(body);
+    let form = match selector {
+        Some(selector) => {
+            let sel = Selector::parse(selector).map_err(|e| {
+                IndexerError::Parse(format!("Invalid login form selector: {:?}", e))
+            })?;
+            document.select(&sel).next()
+        }
+        None => {
+            let forms = Selector::parse("form").expect("valid selector");
+            let password = Selector::parse("input[type=password]").expect("valid selector");
+            document
+                .select(&forms)
+                .find(|f| f.select(&password).next().is_some())
+                .or_else(|| document.select(&forms).next())
+        }
+    }
+    .ok_or_else(|| IndexerError::Login("Login form not found on the login page".to_string()))?;
+
+    let action = form
+        .value()
+        .attr("action")
+        .map(str::trim)
+        .filter(|a| !a.is_empty())
+        .map(|a| make_absolute_url(a, page_url))
+        .unwrap_or_else(|| page_url.to_string());
+    let get = form
+        .value()
+        .attr("method")
+        .is_some_and(|m| m.eq_ignore_ascii_case("get"));
+    Ok(LoginForm {
+        action,
+        get,
+        fields: form_fields(form),
+    })
+}
+
+/// Values a browser would submit for the form, without any submit button
+fn form_fields(form: ElementRef) -> Vec<(String, String)> {
+    let controls = Selector::parse("input[name], select[name], textarea[name]").expect("valid");
+    let options = Selector::parse("option").expect("valid selector");
+    let mut fields = Vec::new();
+    for control in form.select(&controls) {
+        let element = control.value();
+        let name = element.attr("name").unwrap_or_default().to_string();
+        if element.attr("disabled").is_some() {
+            continue;
+        }
+        let value = match element.name() {
+            "select" => {
+                let selected = control
+                    .select(&options)
+                    .find(|o| o.value().attr("selected").is_some())
+                    .or_else(|| control.select(&options).next());
+                let Some(option) = selected else {
+                    continue;
+                };
+                option
+                    .value()
+                    .attr("value")
+                    .map(String::from)
+                    .unwrap_or_else(|| option.text().collect::<String>().trim().to_string())
+            }
+            "textarea" => control.text().collect(),
+            _ => {
+                let kind = element.attr("type").unwrap_or("text").to_ascii_lowercase();
+                match kind.as_str() {
+                    "submit" | "button" | "image" | "reset" | "file" => continue,
+                    "checkbox" | "radio" if element.attr("checked").is_none() => continue,
+                    "checkbox" | "radio" => element.attr("value").unwrap_or("on").to_string(),
+                    _ => element.attr("value").unwrap_or_default().to_string(),
+                }
+            }
+        };
+        fields.push((name, value));
+    }
+    fields
+}
+
+/// Replace or add `inputs` in the form's own fields
+pub fn fill(fields: &mut Vec<(String, String)>, inputs: &[(String, String)]) {
+    for (name, value) in inputs {
+        match fields.iter_mut().find(|(n, _)| n == name) {
+            Some(field) => field.1 = value.clone(),
+            None => fields.push((name.clone(), value.clone())),
+        }
+    }
+}
+
+/// `name=value` pairs of a cookie string as copied from a browser
+/// (`uid=1; pass=abc`)
+pub fn cookie_pairs(cookie: &str) -> Vec<&str> {
+    cookie
+        .split(';')
+        .map(str::trim)
+        .filter(|pair| {
+            pair.split_once('=')
+                .is_some_and(|(name, _)| !name.is_empty())
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_form() {
+        let page = r#"
+<form action="/search.php"><input name="q"></form>
+<form action="takelogin.php" method="POST">
+  <input type="hidden" name="csrf" value="t0k3n">
+  <input type="text" name="username">
+  <input type="password" name="password">
+  <input type="checkbox" name="remember" value="yes" checked>
+  <input type="checkbox" name="ssl">
+  <select name="lang"><option value="en">English</option><option value="de" selected>Deutsch</option></select>
+  <input type="submit" name="login" value="Log in">
+</form>"#;
+        let mut form = find_form(page, "https://tracker.example/account/login.php", None).unwrap();
+        assert_eq!(form.action, "https://tracker.example/account/takelogin.php");
+        assert!(!form.get);
+        fill(
+            &mut form.fields,
+            &[
+                ("username".to_string(), "me".to_string()),
+                ("password".to_string(), "secret".to_string()),
+                ("returnto".to_string(), "/".to_string()),
+            ],
+        );
+        let fields: Vec<_> = form
+            .fields
+            .iter()
+            .map(|(n, v)| format!("{n}={v}"))
+            .collect();
+        assert_eq!(
+            fields,
+            [
+                "csrf=t0k3n",
+                "username=me",
+                "password=secret",
+                "remember=yes",
+                "lang=de",
+                "returnto=/"
+            ]
+        );
+
+        let form = find_form(page, "https://tracker.example/", Some("form")).unwrap();
+        assert_eq!(form.action, "https://tracker.example/search.php");
+        assert!(find_form("<p>down</p>", "https://tracker.example/", None).is_err());
+
+        assert_eq!(
+            cookie_pairs(" uid=1; pass=abc ;; junk; =x"),
+            ["uid=1", "pass=abc"]
+        );
+    }
+}
@@ -1,7 +1,7 @@
 //! Indexer manager - loads and manages indexer instances
 
 use std::collections::HashMap;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use tokio::sync::RwLock;
 
@@ -44,65 +44,81 @@ impl IndexerManager {
         definitions.get(id).cloned()
     }
 
-    /// Load indexer definitions from a directory
+    /// Load indexer definitions from a directory, replacing those loaded.
+    /// Files are read and parsed off the async runtime, and the maps are
+    /// only locked to swap in the result, so searches keep running meanwhile.
     pub async fn load_definitions(&self, path: &Path) -> Result<usize> {
-        let mut count = 0;
-
-        if !path.exists() {
+        let Some(parsed) = read_definitions(path).await? else {
             tracing::warn!("Indexers directory does not exist: {:?}", path);
             return Ok(0);
-        }
-
-        let entries = std::fs::read_dir(path)?;
-
-        // precise locking: clear first, then populate
-        let mut definitions = self.definitions.write().await;
-        definitions.clear();
-
-        let mut indexers_map = self.indexers.write().await;
-        indexers_map.clear();
-
-        for entry in entries.flatten() {
-            let path = entry.path();
-            if path.extension().is_some_and(|e| e == "yaml" || e == "yml") {
-                match IndexerDefinition::from_file(&path) {
-                    Ok(def) => {
-                        tracing::info!("Loaded indexer definition: {}", def.name);
-
-                        // Create executor
-                        let executor = SearchExecutor::new(self.proxy_url.as_deref())
-                            .unwrap_or_else(|e| {
-                                tracing::error!(
-                                    "Failed to create executor for {}: {}",
-                                    def.name,
-                                    e
-                                );
-                                SearchExecutor::new(None).expect("Failed to create basic executor")
-                            });
-
-                        // Create indexer
-                        let indexer = NativeIndexer::new(def.clone(), executor);
-                        let id = def.id.clone();
-
-                        // Insert definition
-                        definitions.insert(id.clone(), def);
-
-                        // Insert indexer instance
-                        indexers_map.insert(id, Arc::new(indexer));
-
-                        count += 1;
-                    }
-                    Err(e) => {
-                        tracing::error!("Failed to load indexer from {:?}: {}", path, e);
-                    }
+        };
+
+        let mut definitions = HashMap::new();
+        let mut indexers: HashMap<String, Arc<dyn Indexer>> = HashMap::new();
+        for (path, def) in parsed {
+            let def = match def {
+                Ok(def) => def,
+                Err(e) => {
+                    tracing::error!("Failed to load indexer from {:?}: {}", path, e);
+                    continue;
                 }
+            };
+            tracing::info!("Loaded indexer definition: {}", def.name);
+            if !def.unknown_fields.is_empty() {
+                tracing::debug!(
+                    "{} has keys this version ignores: {}",
+                    def.id,
+                    def.unknown_fields.join(", ")
+                );
             }
+            super::pacing::register(&def);
+
+            // Create executor
+            let executor = SearchExecutor::for_indexer(&def.id, self.proxy_url.as_deref(), None)
+                .unwrap_or_else(|e| {
+                    tracing::error!("Failed to create executor for {}: {}", def.name, e);
+                    SearchExecutor::new(None).expect("Failed to create basic executor")
+                });
+
+            let indexer = NativeIndexer::new(def.clone(), executor);
+            indexers.insert(def.id.clone(), Arc::new(indexer));
+            definitions.insert(def.id.clone(), def);
         }
 
+        let count = definitions.len();
+        *self.definitions.write().await = definitions;
+        *self.indexers.write().await = indexers;
         Ok(count)
     }
 }
 
+/// A definition file and what parsing it gave
+type Parsed = (PathBuf, anyhow::Result<IndexerDefinition>);
+
+/// Parse every `.yml`/`.yaml` file in `path` on a blocking thread, in one
+/// pass over the directory. `None` if the directory doesn't exist.
+async fn read_definitions(path: &Path) -> Result<Option<Vec<Parsed>>> {
+    let path = path.to_path_buf();
+    tokio::task::spawn_blocking(move || {
+        let entries = match std::fs::read_dir(&path) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+        let parsed = entries
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().is_some_and(|e| e == "yaml" || e == "yml"))
+            .map(|path| {
+                let def = IndexerDefinition::from_file(&path);
+                (path, def)
+            })
+            .collect();
+        Ok(Some(parsed))
+    })
+    .await?
+}
+
 impl Default for IndexerManager {
     fn default() -> Self {
         Self::new(None)
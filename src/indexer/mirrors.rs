@@ -0,0 +1,128 @@
+//! Mirror racing (`[search] race_mirrors`)
+//!
+//! For definitions listing several domains, the first two are requested at
+//! once and the search continues on whichever answers first; the slower
+//! request is dropped. The winner is remembered for a while so every search
+//! doesn't pay for two requests.
+
+use crate::config::SearchConfig;
+use once_cell::sync::Lazy;
+use reqwest::Client;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+/// Mirrors raced per search
+const RACED: usize = 2;
+/// How long a winner is used before racing again
+const WINNER_TTL: Duration = Duration::from_secs(10 * 60);
+
+static WINNERS: Lazy<Mutex<HashMap<String, (String, Instant)>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+/// Register whether mirrors are raced
+pub fn configure(config: &SearchConfig) {
+    ENABLED.store(config.race_mirrors, Ordering::Relaxed);
+}
+
+/// Whether `[search] race_mirrors` is on
+pub fn enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+/// Fastest of the first links for `indexer`, or `None` if none answered
+pub async fn fastest(client: &Client, indexer: &str, links: &[String]) -> Option<String> {
+    if let Some(winner) = WINNERS.lock().ok().and_then(|w| {
+        w.get(indexer)
+            .filter(|(link, at)| at.elapsed() < WINNER_TTL && links.contains(link))
+            .map(|(link, _)| link.clone())
+    }) {
+        return Some(winner);
+    }
+
+    let races = links.iter().take(RACED).map(|link| {
+        Box::pin(async move {
+            crate::http::throttle(link).await;
+            let response = client
+                .get(link)
+                .header("Accept", "text/html")
+                .send()
+                .await?;
+            // A mirror answering 5xx is up but not serving the site
+            if response.status().is_server_error() {
+                anyhow::bail!("HTTP {} from {}", response.status(), link);
+            }
+            Ok::<_, anyhow::Error>(link.clone())
+        })
+    });
+    match futures::future::select_ok(races).await {
+        Ok((winner, _rest)) => {
+            tracing::debug!("{} mirror race won by {}", indexer, winner);
+            if let Ok(mut winners) = WINNERS.lock() {
+                winners.insert(indexer.to_string(), (winner.clone(), Instant::now()));
+            }
+            Some(winner)
+        }
+        Err(e) => {
+            tracing::debug!("{} mirror race failed: {}", indexer, e);
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    /// Local server answering every request after `delay`
+    async fn mirror(delay: Duration) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            while let Ok((mut socket, _)) = listener.accept().await {
+                tokio::spawn(async move {
+                    let mut buf = [0u8; 1024];
+                    let _ = socket.read(&mut buf).await;
+                    tokio::time::sleep(delay).await;
+                    let _ = socket
+                        .write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 0\r\n\r\n")
+                        .await;
+                });
+            }
+        });
+        format!("http://{}/", addr)
+    }
+
+    #[tokio::test]
+    async fn test_fastest_mirror_wins() {
+        let slow = mirror(Duration::from_secs(1)).await;
+        let fast = mirror(Duration::ZERO).await;
+        let client = Client::new();
+        let links = vec![slow.clone(), fast.clone()];
+
+        let started = Instant::now();
+        assert_eq!(
+            fastest(&client, "race-test", &links).await.as_deref(),
+            Some(fast.as_str())
+        );
+        assert!(started.elapsed() < Duration::from_secs(1));
+
+        // Remembered for the next search, as long as it is still listed
+        assert_eq!(
+            fastest(&client, "race-test", &links).await.as_deref(),
+            Some(fast.as_str())
+        );
+        let dead = "http://127.0.0.1:9/".to_string();
+        assert_eq!(
+            fastest(&client, "race-test", &[dead, slow.clone()])
+                .await
+                .as_deref(),
+            Some(slow.as_str())
+        );
+    }
+}
@@ -1,18 +1,40 @@
 //! Indexer module - manages torrent indexer definitions and execution
 
+pub mod anime;
+pub mod backoff;
+pub mod breakage;
+pub mod categories;
+pub mod challenge;
+pub mod cookies;
 pub mod definition;
+mod details;
 pub mod downloader;
+pub mod error;
 pub mod executor;
+pub mod failing;
 mod field_extractor;
 pub mod filters;
+mod login;
 mod manager;
+pub mod mirrors;
 pub mod native;
-mod result_builder;
+pub mod pacing;
+pub mod pubdate;
+pub mod remote;
+pub mod report;
+pub mod result_builder;
+pub mod rewrite;
 pub mod selector;
+pub mod selftest;
+pub mod session;
+pub mod settings;
 pub mod template;
+pub mod timings;
+mod totp;
 pub mod traits;
 
 pub use downloader::{AvailableIndexer, IndexerDownloader};
+pub use error::{ErrorKind, IndexerError};
 pub use executor::SearchExecutor;
 pub use manager::IndexerManager;
 pub use traits::SearchCapabilities;
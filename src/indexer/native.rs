@@ -19,7 +19,7 @@ impl NativeIndexer {
     /// Create a new native indexer
     pub fn new(definition: IndexerDefinition, executor: SearchExecutor) -> Self {
         let categories = definition.extract_categories();
-        let capabilities = Self::extract_capabilities(&definition);
+        let capabilities = SearchCapabilities::from_modes(&definition.caps.modes);
 
         Self {
             definition,
@@ -28,60 +28,6 @@ impl NativeIndexer {
             capabilities,
         }
     }
-
-    /// Extract search capabilities from definition
-    fn extract_capabilities(definition: &IndexerDefinition) -> SearchCapabilities {
-        let mut caps = SearchCapabilities::default();
-
-        // Check supported search modes
-        // Available modes: search, tv-search, movie-search, music-search, book-search
-
-        if definition.caps.modes.contains_key("search") {
-            caps.search = true;
-        }
-
-        if definition.caps.modes.contains_key("tv-search") {
-            caps.tv_search = true;
-
-            // Check for ID search parameters in the tv-search mode
-            if let Some(params) = definition.caps.modes.get("tv-search") {
-                for param in params {
-                    match param.as_str() {
-                        "q" => {} // Standard text search
-                        "season" | "ep" => caps.season_episode = true,
-                        "rid" | "tvdbid" => caps.tvdb_id = true,
-                        "tmdbid" => caps.tmdb_id = true,
-                        "imdbid" => caps.imdb_id = true,
-                        _ => {}
-                    }
-                }
-            }
-        }
-
-        if definition.caps.modes.contains_key("movie-search") {
-            caps.movie_search = true;
-
-            if let Some(params) = definition.caps.modes.get("movie-search") {
-                for param in params {
-                    match param.as_str() {
-                        "imdbid" => caps.imdb_id = true,
-                        "tmdbid" => caps.tmdb_id = true,
-                        _ => {}
-                    }
-                }
-            }
-        }
-
-        if definition.caps.modes.contains_key("music-search") {
-            caps.music_search = true;
-        }
-
-        if definition.caps.modes.contains_key("book-search") {
-            caps.book_search = true;
-        }
-
-        caps
-    }
 }
 
 #[async_trait]
@@ -0,0 +1,90 @@
+//! Per-indexer request spacing (`requestDelay`)
+//!
+//! A definition's `requestDelay` is the least time between two requests to
+//! the tracker, in seconds. Every request an indexer's executors send takes
+//! the next free slot, so concurrent searches and the pages of a multi-path
+//! search queue up behind each other instead of tripping the tracker's flood
+//! protection. This comes on top of the per-domain `[http] domain_rate`.
+
+use super::definition::IndexerDefinition;
+use once_cell::sync::Lazy;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Indexer id -> delay from its definition
+static DELAYS: Lazy<Mutex<HashMap<String, Duration>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+/// Indexer id -> earliest time of its next request
+static NEXT: Lazy<Mutex<HashMap<String, Instant>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Remember the definition's `requestDelay` for its indexer
+pub fn register(definition: &IndexerDefinition) {
+    let Ok(mut delays) = DELAYS.lock() else {
+        return;
+    };
+    match definition
+        .request_delay
+        .filter(|d| d.is_finite() && *d > 0.0)
+    {
+        Some(secs) => {
+            delays.insert(definition.id.clone(), Duration::from_secs_f64(secs));
+        }
+        None => {
+            delays.remove(&definition.id);
+        }
+    }
+}
+
+/// Take the indexer's next slot, returning how long to wait for it
+fn reserve(indexer_id: &str, delay: Duration, now: Instant) -> Duration {
+    let Ok(mut next) = NEXT.lock() else {
+        return Duration::ZERO;
+    };
+    let slot = next
+        .get(indexer_id)
+        .copied()
+        .filter(|&at| at > now)
+        .unwrap_or(now);
+    next.insert(indexer_id.to_string(), slot + delay);
+    slot - now
+}
+
+/// Wait until the indexer may send its next request
+pub async fn wait(indexer_id: &str) {
+    let delay = match DELAYS.lock() {
+        Ok(delays) => delays.get(indexer_id).copied(),
+        Err(_) => None,
+    };
+    let Some(delay) = delay else {
+        return;
+    };
+    let wait = reserve(indexer_id, delay, Instant::now());
+    if !wait.is_zero() {
+        tracing::debug!("Spacing request to {} by {:?}", indexer_id, wait);
+        tokio::time::sleep(wait).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reserve() {
+        let now = Instant::now();
+        let delay = Duration::from_secs(2);
+        let id = "pacing-test";
+
+        // Concurrent requests queue up one delay apart
+        assert_eq!(reserve(id, delay, now), Duration::ZERO);
+        assert_eq!(reserve(id, delay, now), Duration::from_secs(2));
+        assert_eq!(reserve(id, delay, now), Duration::from_secs(4));
+        // After a quiet spell the next request goes straight out
+        let later = now + Duration::from_secs(10);
+        assert_eq!(reserve(id, delay, later), Duration::ZERO);
+        assert_eq!(
+            reserve(id, delay, later + Duration::from_millis(500)),
+            Duration::from_millis(1500)
+        );
+    }
+}
@@ -0,0 +1,178 @@
+//! Publication dates for results the indexer listed without one
+//!
+//! With `_dateFromDetails = "true"` in an indexer's settings, undated results
+//! get a second request for their details page, which is searched for a
+//! publish date (`<time datetime>`, `datePublished` markup or an
+//! "Added: 2024-05-01" style label). Anything still undated is given its
+//! first-seen time by the server (see `db::fill_first_seen`).
+
+use super::executor::SearchExecutor;
+use crate::models::{DateSource, TorrentResult};
+use chrono::{DateTime, NaiveDate, NaiveDateTime, Utc};
+use futures::StreamExt;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use scraper::{Html, Selector};
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+/// Details pages fetched per search at most
+const MAX_FETCHES: usize = 10;
+const CONCURRENCY: usize = 3;
+/// Remembered details-page dates (including misses) before the cache resets
+const CACHE_SIZE: usize = 2000;
+
+static CACHE: Lazy<Mutex<HashMap<String, Option<DateTime<Utc>>>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Whether the indexer is set up to read dates from details pages
+pub fn enabled(user_settings: Option<&HashMap<String, String>>) -> bool {
+    user_settings
+        .and_then(|s| s.get("_dateFromDetails"))
+        .is_some_and(|v| v == "true")
+}
+
+/// Fill in missing dates from the results' details pages
+pub async fn fill_from_details(
+    executor: &SearchExecutor,
+    results: &mut [TorrentResult],
+    user_settings: Option<&HashMap<String, String>>,
+) {
+    let pending: Vec<(usize, String)> = results
+        .iter()
+        .enumerate()
+        .filter(|(_, r)| r.publish_date.is_none())
+        .filter_map(|(i, r)| Some((i, r.details.clone()?)))
+        .take(MAX_FETCHES)
+        .collect();
+    if pending.is_empty() {
+        return;
+    }
+
+    let found: Vec<(usize, Option<DateTime<Utc>>)> = futures::stream::iter(pending)
+        .map(|(i, url)| async move {
+            let cached = CACHE.lock().ok().and_then(|c| c.get(&url).copied());
+            if let Some(date) = cached {
+                return (i, date);
+            }
+            let date = match executor.fetch_page(&url, user_settings).await {
+                Ok(body) => from_details_page(&body),
+                Err(e) => {
+                    // Not cached, so the next search tries again
+                    tracing::debug!("Details page {} failed: {}", url, e);
+                    return (i, None);
+                }
+            };
+            if let Ok(mut cache) = CACHE.lock() {
+                if cache.len() >= CACHE_SIZE {
+                    cache.clear();
+                }
+                cache.insert(url, date);
+            }
+            (i, date)
+        })
+        .buffer_unordered(CONCURRENCY)
+        .collect()
+        .await;
+
+    for (i, date) in found {
+        if let Some(date) = date {
+            results[i].publish_date = Some(date);
+            results[i].date_source = Some(DateSource::Details);
+        }
+    }
+}
+
+/// Find a publish date on a details page
+pub fn from_details_page(html: &str) -> Option<DateTime<Utc>> {
+    static MARKUP: Lazy<Vec<(Selector, &str)>> = Lazy::new(|| {
+        [
+            (r#"meta[property="article:published_time"]"#, "content"),
+            (r#"meta[itemprop="datePublished"]"#, "content"),
+            (r#"meta[name="date"]"#, "content"),
+            (r#"[itemprop="datePublished"]"#, "datetime"),
+            ("time[datetime]", "datetime"),
+        ]
+        .into_iter()
+        .filter_map(|(s, attr)| Some((Selector::parse(s).ok()?, attr)))
+        .collect()
+    });
+    static LABELLED: Lazy<Regex> = Lazy::new(|| {
+        Regex::new(
+            r"(?i)\b(?:added|uploaded|published|created|date)\b[^0-9\n]{0,20}?(\d{4}-\d{2}-\d{2}(?:[ T]\d{2}:\d{2}(?::\d{2})?)?|\d{2}\.\d{2}\.\d{4}(?: \d{2}:\d{2})?)",
+        )
+        .expect("invalid date label regex")
+    });
+
+    let document = Html::parse_document(html);
+    for (selector, attr) in MARKUP.iter() {
+        for el in document.select(selector) {
+            if let Some(date) = el.value().attr(attr).and_then(parse_date) {
+                return Some(date);
+            }
+        }
+    }
+
+    let text = document.root_element().text().collect::<Vec<_>>().join(" ");
+    LABELLED
+        .captures_iter(&text)
+        .find_map(|c| parse_date(c.get(1)?.as_str()))
+}
+
+/// Absolute dates only: relative ones ("2 days ago") on a cached page go stale
+fn parse_date(value: &str) -> Option<DateTime<Utc>> {
+    let value = value.trim();
+    if let Ok(date) = DateTime::parse_from_rfc3339(value) {
+        return Some(date.with_timezone(&Utc));
+    }
+    if let Ok(date) = DateTime::parse_from_rfc2822(value) {
+        return Some(date.with_timezone(&Utc));
+    }
+    for format in [
+        "%Y-%m-%d %H:%M:%S",
+        "%Y-%m-%dT%H:%M:%S",
+        "%Y-%m-%d %H:%M",
+        "%d.%m.%Y %H:%M",
+    ] {
+        if let Ok(dt) = NaiveDateTime::parse_from_str(value, format) {
+            return Some(dt.and_utc());
+        }
+    }
+    for format in ["%Y-%m-%d", "%d.%m.%Y"] {
+        if let Ok(d) = NaiveDate::parse_from_str(value, format) {
+            return d.and_hms_opt(0, 0, 0).map(|dt| dt.and_utc());
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_details_page() {
+        let html = r#"<html><head>
+            <meta property="article:published_time" content="2024-05-01T10:30:00+02:00">
+            </head><body></body></html>"#;
+        assert_eq!(
+            from_details_page(html).unwrap().to_rfc3339(),
+            "2024-05-01T08:30:00+00:00"
+        );
+
+        let html = r#"<table><tr><td>Size</td><td>1.4 GB</td></tr>
+            <tr><td>Added:</td><td>2023-11-20 18:04</td></tr></table>"#;
+        assert_eq!(
+            from_details_page(html).unwrap().to_rfc3339(),
+            "2023-11-20T18:04:00+00:00"
+        );
+
+        let html = "<p>Зарегистрирован | Date: 07.03.2022</p>";
+        assert_eq!(
+            from_details_page(html).unwrap().to_rfc3339(),
+            "2022-03-07T00:00:00+00:00"
+        );
+
+        assert!(from_details_page("<p>Uploaded 2 days ago by someone</p>").is_none());
+    }
+}
@@ -0,0 +1,114 @@
+//! Forwarding an indexer to another Lodestarr instance
+//!
+//! With the `_remote` setting, searches for the indexer are sent to the
+//! Torznab endpoint of that instance (e.g. one behind a VPN in another
+//! country) instead of the tracker. The remote must have the indexer
+//! installed and configured. Its results link to its own download proxy, so
+//! downloads go through the remote too.
+
+use crate::models::{SearchQuery, TorrentResult};
+use crate::torznab::{SearchParams, TorznabClient};
+use anyhow::Result;
+use std::collections::HashMap;
+
+/// A remote Lodestarr instance searches are forwarded to
+#[derive(Debug, Clone)]
+pub struct Remote {
+    /// Base URL, e.g. `http://10.8.0.2:3420`
+    pub url: String,
+    pub apikey: Option<String>,
+}
+
+impl Remote {
+    /// Remote configured in the indexer's settings (`_remote`, `_remoteApiKey`)
+    pub fn from_settings(user_settings: Option<&HashMap<String, String>>) -> Option<Self> {
+        let settings = user_settings?;
+        let url = settings.get("_remote")?.trim().trim_end_matches('/');
+        if url.is_empty() {
+            return None;
+        }
+        Some(Self {
+            url: url.to_string(),
+            apikey: settings
+                .get("_remoteApiKey")
+                .filter(|k| !k.is_empty())
+                .cloned(),
+        })
+    }
+
+    /// The remote's Torznab endpoint for an indexer
+    pub fn torznab_url(&self, indexer_id: &str) -> String {
+        format!(
+            "{}/api/v2.0/indexers/{}/results/torznab/api",
+            self.url, indexer_id
+        )
+    }
+
+    /// Run the search on the remote instance
+    pub async fn search(
+        &self,
+        indexer_id: &str,
+        query: &SearchQuery,
+    ) -> Result<Vec<TorrentResult>> {
+        tracing::debug!("Forwarding {} search to {}", indexer_id, self.url);
+        let client = TorznabClient::direct(&self.torznab_url(indexer_id), self.apikey.as_deref())?;
+        client.search(&search_params(query)).await
+    }
+}
+
+fn search_params(query: &SearchQuery) -> SearchParams {
+    SearchParams {
+        query: query.query.clone().unwrap_or_default(),
+        search_type: query.search_type.as_param().to_string(),
+        cat: (!query.categories.is_empty()).then(|| {
+            query
+                .categories
+                .iter()
+                .map(|c| c.to_string())
+                .collect::<Vec<_>>()
+                .join(",")
+        }),
+        season: query.season,
+        ep: query.episode,
+        imdbid: query.imdb_id.clone(),
+        tmdbid: query.tmdb_id,
+        tvdbid: query.tvdb_id,
+        year: query.year,
+        limit: query.limit,
+        tvmazeid: query.tvmaze_id,
+        traktid: query.trakt_id,
+        doubanid: query.douban_id,
+        genre: query.genre.clone(),
+        album: query.album.clone(),
+        artist: query.artist.clone(),
+        label: query.label.clone(),
+        track: query.track.clone(),
+        title: query.title.clone(),
+        author: query.author.clone(),
+        publisher: query.publisher.clone(),
+        ..Default::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_remote_from_settings() {
+        assert!(Remote::from_settings(None).is_none());
+
+        let mut settings = HashMap::new();
+        settings.insert("_remote".to_string(), " ".to_string());
+        assert!(Remote::from_settings(Some(&settings)).is_none());
+
+        settings.insert("_remote".to_string(), "http://10.8.0.2:3420/".to_string());
+        settings.insert("_remoteApiKey".to_string(), "secret".to_string());
+        let remote = Remote::from_settings(Some(&settings)).unwrap();
+        assert_eq!(remote.apikey.as_deref(), Some("secret"));
+        assert_eq!(
+            remote.torznab_url("1337x"),
+            "http://10.8.0.2:3420/api/v2.0/indexers/1337x/results/torznab/api"
+        );
+    }
+}
@@ -0,0 +1,190 @@
+//! Indexer test reports
+//!
+//! `lodestarr indexer test --output json` and `POST /api/native/{id}/test`
+//! describe a test search the same way: the requests sent with their status
+//! codes and row counts, a sample of the parsed results, and warnings about
+//! fields the definition never filled in, so CI jobs for custom definitions
+//! can assert on them.
+
+use super::challenge;
+use super::error::{ErrorKind, kind_of};
+use super::executor::SearchRequest;
+use crate::models::TorrentResult;
+use serde::Serialize;
+use std::time::Duration;
+
+/// Results included in a report
+const SAMPLE_SIZE: usize = 5;
+
+#[derive(Serialize)]
+pub struct TestReport {
+    pub indexer: String,
+    pub query: String,
+    pub success: bool,
+    /// "ok", "empty", "protected" (anti-bot challenge) or "error"
+    pub status: &'static str,
+    pub count: usize,
+    pub time_ms: u128,
+    pub message: String,
+    /// Why the test failed (e.g. "login" vs "network")
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<ErrorKind>,
+    pub requests: Vec<SearchRequest>,
+    /// The first results, with every field that was parsed
+    pub sample: Vec<TorrentResult>,
+    pub warnings: Vec<String>,
+}
+
+impl TestReport {
+    pub fn new(
+        indexer: &str,
+        query: &str,
+        outcome: &anyhow::Result<Vec<TorrentResult>>,
+        elapsed: Duration,
+        requests: Vec<SearchRequest>,
+    ) -> Self {
+        let time_ms = elapsed.as_millis();
+        let mut report = Self {
+            indexer: indexer.to_string(),
+            query: query.to_string(),
+            success: false,
+            status: "error",
+            count: 0,
+            time_ms,
+            message: String::new(),
+            error: None,
+            warnings: Vec::new(),
+            sample: Vec::new(),
+            requests,
+        };
+        match outcome {
+            Ok(results) if results.is_empty() => {
+                report.status = "empty";
+                report.message =
+                    "No results found - indexer may be down or misconfigured".to_string();
+            }
+            Ok(results) => {
+                report.success = true;
+                report.status = "ok";
+                report.count = results.len();
+                report.message = format!("Found {} releases in {}ms", results.len(), time_ms);
+                report.sample = results.iter().take(SAMPLE_SIZE).cloned().collect();
+            }
+            Err(e) => {
+                if challenge::protection_of(e).is_some() {
+                    report.status = "protected";
+                }
+                report.message = format!("Test failed: {}", e);
+                report.error = Some(kind_of(e));
+            }
+        }
+        let results = outcome.as_deref().unwrap_or_default();
+        report.warnings = warnings(results, &report.requests);
+        report
+    }
+}
+
+/// A result field and whether a result has it
+type Field = (&'static str, fn(&TorrentResult) -> bool);
+
+/// Things that look wrong with the definition even when the search worked
+fn warnings(results: &[TorrentResult], requests: &[SearchRequest]) -> Vec<String> {
+    let mut warnings = Vec::new();
+    for request in requests {
+        if request.rows == Some(0) {
+            warnings.push(format!(
+                "No rows found on {} (check search.rows.selector)",
+                request.url
+            ));
+        }
+    }
+    if results.is_empty() {
+        return warnings;
+    }
+
+    let fields: [Field; 5] = [
+        ("size", |r| r.size.is_some()),
+        ("seeders", |r| r.seeders.is_some()),
+        ("publish date", |r| r.publish_date.is_some()),
+        ("download link or magnet", |r| {
+            r.link.is_some() || r.magnet.is_some()
+        }),
+        ("category", |r| !r.categories.is_empty()),
+    ];
+    for (field, has) in fields {
+        let without = results.iter().filter(|r| !has(r)).count();
+        if without == results.len() {
+            warnings.push(format!("{} missing from every result", field));
+        } else if without > 0 {
+            warnings.push(format!(
+                "{} missing from {} of {} results",
+                field,
+                without,
+                results.len()
+            ));
+        }
+    }
+    warnings
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_report() {
+        let requests = vec![
+            SearchRequest {
+                method: "GET",
+                url: "https://tracker.example/search?q=ubuntu".to_string(),
+                status: Some(200),
+                rows: Some(2),
+            },
+            SearchRequest {
+                method: "GET",
+                url: "https://tracker.example/search?q=ubuntu&page=2".to_string(),
+                status: Some(200),
+                rows: Some(0),
+            },
+        ];
+        let result = |size| TorrentResult {
+            title: "Ubuntu".to_string(),
+            size,
+            seeders: Some(3),
+            magnet: Some("magnet:?xt=urn:btih:abc".to_string()),
+            categories: vec![4000],
+            ..Default::default()
+        };
+        let outcome = Ok(vec![result(Some(100)), result(None)]);
+        let report = TestReport::new(
+            "example",
+            "ubuntu",
+            &outcome,
+            Duration::from_millis(250),
+            requests,
+        );
+
+        assert!(report.success);
+        assert_eq!(report.status, "ok");
+        assert_eq!(report.count, 2);
+        assert_eq!(report.sample.len(), 2);
+        assert_eq!(
+            report.warnings,
+            [
+                "No rows found on https://tracker.example/search?q=ubuntu&page=2 (check search.rows.selector)",
+                "size missing from 1 of 2 results",
+                "publish date missing from every result",
+            ]
+        );
+
+        let failed = TestReport::new(
+            "example",
+            "ubuntu",
+            &Err(anyhow::anyhow!("boom")),
+            Duration::ZERO,
+            Vec::new(),
+        );
+        assert_eq!(failed.status, "error");
+        assert_eq!(failed.error, Some(ErrorKind::Other));
+    }
+}
@@ -1,14 +1,67 @@
 //! Result builder - constructs TorrentResult from extracted fields
 //!
 //! This module handles the conversion of extracted field data (stored in TemplateContext)
-//! into properly formatted TorrentResult objects.
+//! into properly formatted TorrentResult objects. Text fields go through
+//! [`sanitize_text`] so every indexer's titles are cleaned the same way.
+//! Results with only an infohash get a magnet link built by [`make_magnet`].
 
 use chrono::{DateTime, Utc};
+use once_cell::sync::Lazy;
+use std::sync::RwLock;
 
 use super::definition::IndexerDefinition;
-use super::filters::parse_size;
+use super::filters::{decimal_comma, parse_size};
 use super::template::TemplateContext;
-use crate::models::TorrentResult;
+use crate::config::Config;
+use crate::models::{DateSource, TorrentResult};
+
+/// Trackers added to built magnets when `magnet_trackers` is unset
+const DEFAULT_TRACKERS: &[&str] = &[
+    "udp://tracker.opentrackr.org:1337/announce",
+    "udp://open.stealth.si:80/announce",
+    "udp://tracker.torrent.eu.org:451/announce",
+    "udp://exodus.desync.com:6969/announce",
+    "udp://open.demonii.com:1337/announce",
+];
+
+static TRACKERS: Lazy<RwLock<Vec<String>>> =
+    Lazy::new(|| RwLock::new(DEFAULT_TRACKERS.iter().map(|t| t.to_string()).collect()));
+
+/// Set the trackers added to magnets built for public indexers
+pub fn configure_trackers(config: &Config) {
+    if let Ok(mut trackers) = TRACKERS.write() {
+        *trackers = config
+            .magnet_trackers
+            .clone()
+            .unwrap_or_else(|| DEFAULT_TRACKERS.iter().map(|t| t.to_string()).collect());
+    }
+}
+
+/// Magnet link for an infohash (40 hex or 32 base32 characters), or None if
+/// `info_hash` is neither
+pub fn make_magnet(info_hash: &str, name: &str, trackers: &[String]) -> Option<String> {
+    let hash = info_hash.trim();
+    let hash = match hash.len() {
+        40 if hash.chars().all(|c| c.is_ascii_hexdigit()) => hash.to_ascii_lowercase(),
+        32 if hash
+            .chars()
+            .all(|c| matches!(c.to_ascii_uppercase(), 'A'..='Z' | '2'..='7')) =>
+        {
+            hash.to_ascii_uppercase()
+        }
+        _ => return None,
+    };
+    let mut magnet = format!("magnet:?xt=urn:btih:{}", hash);
+    if !name.is_empty() {
+        magnet.push_str("&dn=");
+        magnet.push_str(&urlencoding::encode(name));
+    }
+    for tracker in trackers {
+        magnet.push_str("&tr=");
+        magnet.push_str(&urlencoding::encode(tracker));
+    }
+    Some(magnet)
+}
 
 /// Construct a TorrentResult from a populated TemplateContext
 pub fn make_torrent_result(
@@ -16,8 +69,11 @@ pub fn make_torrent_result(
     ctx: &TemplateContext,
     base_url: &str,
 ) -> Option<TorrentResult> {
+    let strip_bbcode = ctx.config.get("_stripBbcode").is_some_and(|v| v == "true");
+    let clean = |value: &str| sanitize_text(value, strip_bbcode);
+
     // 1. Extract title (required)
-    let title = ctx.result.get("title")?.clone();
+    let title = clean(ctx.result.get("title")?);
     if title.is_empty() {
         return None;
     }
@@ -33,22 +89,44 @@ pub fn make_torrent_result(
     result.details = details;
     result.indexer = Some(definition.id.clone());
 
-    // 3. Categories
-    if let Some(cat_id) = ctx.result.get("category")
-        && let Some(torznab_id) = definition.resolve_category(cat_id)
-    {
-        result.categories.push(torznab_id);
+    // 3. Categories, plus the tracker's own when `_trackerCategories` is on
+    if let Some(cat_id) = ctx.result.get("category") {
+        if let Some(torznab_id) = definition.resolve_category(cat_id) {
+            result.categories.push(torznab_id);
+        }
+        if super::categories::tracker_categories_enabled(Some(&ctx.config))
+            && let Some(custom) = super::categories::custom_category(cat_id)
+        {
+            result.categories.push(custom);
+        }
     }
 
     // 4. Download Link
     if let Some(link) = ctx.result.get("download") {
-        result.link = Some(make_absolute_url(link, base_url));
+        result.link = Some(super::rewrite::apply(
+            &make_absolute_url(link, base_url),
+            ctx,
+        ));
     }
 
-    // 5. Magnet
+    // 5. Magnet and InfoHash
     if let Some(magnet) = ctx.result.get("magnet") {
         result.magnet = Some(magnet.clone());
     }
+    if let Some(hash) = ctx.result.get("infohash").map(|h| h.trim()) {
+        if !hash.is_empty() {
+            result.info_hash = Some(hash.to_string());
+        }
+        if result.magnet.is_none() {
+            // Public trackers are only added for public indexers, so a
+            // private torrent's hash isn't announced anywhere else
+            let trackers = match definition.indexer_type.as_str() {
+                "public" => TRACKERS.read().map(|t| t.clone()).unwrap_or_default(),
+                _ => Vec::new(),
+            };
+            result.magnet = make_magnet(hash, &result.title, &trackers);
+        }
+    }
 
     // Fallback: Use magnet as link if link missing
     if result.link.is_none() && result.magnet.is_some() {
@@ -57,7 +135,7 @@ pub fn make_torrent_result(
 
     // 6. Size
     if let Some(size_str) = ctx.result.get("size") {
-        result.size = Some(parse_size(size_str));
+        result.size = Some(parse_size(size_str, decimal_comma(&definition.language)));
     }
 
     // 7. Peers
@@ -71,33 +149,76 @@ pub fn make_torrent_result(
         result.grabs = parse_numeric_field(g);
     }
 
-    // 8. InfoHash
-    if let Some(h) = ctx.result.get("infohash") {
-        result.info_hash = Some(h.clone());
-        // If magnet missing, create one
-        if result.magnet.is_none() {
-            let magnet = format!(
-                "magnet:?xt=urn:btih:{}&dn={}",
-                h.to_lowercase(),
-                urlencoding::encode(&result.title)
-            );
-            result.magnet = Some(magnet);
-        }
-    }
-
-    // 9. IMDB
+    // 8. IMDB
     if let Some(imdb) = ctx.result.get("imdbid").or_else(|| ctx.result.get("imdb")) {
         result.imdb_id = Some(imdb.clone());
     }
 
-    // 10. Date
+    // 9. Date
     if let Some(date_str) = ctx.result.get("date") {
         result.publish_date = parse_date_field(date_str);
+        result.date_source = result.publish_date.map(|_| DateSource::Indexer);
     }
 
+    // 10. Metadata (mostly from details pages)
+    if let Some(files) = ctx.result.get("files") {
+        result.files = parse_numeric_field(files);
+    }
+    if let Some(id) = ctx.result.get("tmdbid") {
+        result.tmdb_id = id.trim().parse().ok();
+    }
+    if let Some(id) = ctx.result.get("tvdbid") {
+        result.tvdb_id = id.trim().parse().ok();
+    }
+    let text = |name: &str| {
+        ctx.result
+            .get(name)
+            .map(|v| clean(v))
+            .filter(|v| !v.is_empty())
+    };
+    result.genre = text("genre");
+    result.description = text("description");
+    result.poster = text("poster").map(|p| make_absolute_url(&p, base_url));
+
     Some(result)
 }
 
+/// Clean a text field scraped from a tracker: decode HTML entities, drop
+/// control and zero-width characters, collapse whitespace and, with
+/// `strip_bbcode`, remove BBCode tags (keeping the text between them)
+pub fn sanitize_text(value: &str, strip_bbcode: bool) -> String {
+    use once_cell::sync::Lazy;
+    use regex::Regex;
+
+    static RE_BBCODE: Lazy<Regex> = Lazy::new(|| {
+        Regex::new(
+            r"(?i)\[/?(?:b|i|u|s|url|img|color|colour|size|font|quote|code|center|left|right|spoiler|hide|list|\*)(?:=[^\]]*)?\]",
+        )
+        .expect("invalid bbcode regex")
+    });
+
+    let decoded = html_escape::decode_html_entities(value);
+    let text = if strip_bbcode {
+        RE_BBCODE.replace_all(&decoded, "")
+    } else {
+        decoded
+    };
+    let cleaned: String = text
+        .chars()
+        .filter(|c| !is_invisible(*c))
+        .map(|c| if c.is_control() { ' ' } else { c })
+        .collect();
+    cleaned.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Zero-width and direction marks that render as nothing
+fn is_invisible(c: char) -> bool {
+    matches!(
+        c,
+        '\u{00ad}' | '\u{200b}'..='\u{200f}' | '\u{202a}'..='\u{202e}' | '\u{2060}' | '\u{feff}'
+    )
+}
+
 /// Parse a numeric field with comma handling
 fn parse_numeric_field(value: &str) -> Option<u32> {
     value.replace(',', "").parse().ok()
@@ -211,3 +332,71 @@ pub fn make_absolute_url(url: &str, base_url: &str) -> String {
         format!("{}/{}", base_url.trim_end_matches('/'), url)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sanitize_text() {
+        assert_eq!(
+            sanitize_text("Tom &amp; Jerry &#8211; S01\u{200b}E02&nbsp;1080p", false),
+            "Tom & Jerry \u{2013} S01E02 1080p"
+        );
+        assert_eq!(
+            sanitize_text("  Some\tMovie\r\n(2024)\u{feff}  ", false),
+            "Some Movie (2024)"
+        );
+
+        let bbcode = "[b]Show[/b] [color=red]S01[/color] [url=https://x.org]Extras[/url]";
+        assert_eq!(sanitize_text(bbcode, true), "Show S01 Extras");
+        assert_eq!(sanitize_text(bbcode, false), bbcode);
+        // Square brackets that aren't BBCode stay
+        assert_eq!(
+            sanitize_text("[SubsPlease] Show - 01 [1080p]", true),
+            "[SubsPlease] Show - 01 [1080p]"
+        );
+    }
+
+    #[test]
+    fn test_infohash_magnet() {
+        let definition = |kind: &str| {
+            IndexerDefinition::from_yaml(&format!(
+                "id: hashes\nname: Hashes\ntype: {}\nlinks: [\"https://tracker.example/\"]\n\
+                 search:\n  paths: [{{path: search}}]\n  rows: {{selector: tr}}\n  \
+                 fields: {{title: {{selector: a}}}}\n",
+                kind
+            ))
+            .unwrap()
+        };
+        let mut ctx = TemplateContext::default();
+        ctx.result.insert("title".into(), "Dune 2021".into());
+        let hash = "0123456789ABCDEF0123456789ABCDEF01234567";
+        ctx.result.insert("infohash".into(), hash.into());
+
+        let result = make_torrent_result(&definition("public"), &ctx, "https://x/").unwrap();
+        let magnet = result.magnet.unwrap();
+        assert!(magnet.starts_with(
+            "magnet:?xt=urn:btih:0123456789abcdef0123456789abcdef01234567&dn=Dune%202021&tr=udp%3A%2F%2F"
+        ));
+        // With no download link the magnet is the link
+        assert_eq!(result.link.as_ref(), Some(&magnet));
+        assert_eq!(result.info_hash.as_deref(), Some(hash));
+
+        // No public trackers for private indexers
+        let result = make_torrent_result(&definition("private"), &ctx, "https://x/").unwrap();
+        assert!(!result.magnet.unwrap().contains("&tr="));
+
+        assert_eq!(make_magnet("not-a-hash", "x", &[]), None);
+        assert_eq!(
+            make_magnet(
+                "mfrgg2lonzxw4zdfmfrgg2lonzxw4zdf",
+                "",
+                &["http://t/a".into()]
+            ),
+            Some(
+                "magnet:?xt=urn:btih:MFRGG2LONZXW4ZDFMFRGG2LONZXW4ZDF&tr=http%3A%2F%2Ft%2Fa".into()
+            )
+        );
+    }
+}
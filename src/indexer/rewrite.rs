@@ -0,0 +1,99 @@
+//! Download link rewrite rules (`_downloadRewrite`)
+//!
+//! An indexer's `_downloadRewrite` setting holds one `pattern => replacement`
+//! rule per line, for trackers whose links need a passkey or another host.
+//! The replacement can use regex groups (`$1`) and the indexer's settings
+//! (`{{ .Config.passkey }}`). Rules run in order on result links and on
+//! links resolved from details pages. Magnets are left alone.
+
+use super::template::{TemplateContext, render_template};
+use anyhow::{Context, Result, bail};
+use regex::Regex;
+
+/// Setting holding an indexer's rules
+pub const SETTING: &str = "_downloadRewrite";
+
+pub struct Rule {
+    pattern: Regex,
+    replacement: String,
+}
+
+/// Parse `pattern => replacement` lines. Blank lines and `#` comments are
+/// skipped.
+pub fn parse(raw: &str) -> Result<Vec<Rule>> {
+    let mut rules = Vec::new();
+    for (number, line) in raw.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let Some((pattern, replacement)) = line.split_once("=>") else {
+            bail!("Rule {}: expected 'pattern => replacement'", number + 1);
+        };
+        let pattern = Regex::new(pattern.trim())
+            .with_context(|| format!("Rule {}: invalid pattern", number + 1))?;
+        rules.push(Rule {
+            pattern,
+            replacement: replacement.trim().to_string(),
+        });
+    }
+    Ok(rules)
+}
+
+/// Apply the indexer's rules (from `ctx.config`) to a download link
+pub fn apply(link: &str, ctx: &TemplateContext) -> String {
+    let Some(raw) = ctx.config.get(SETTING).filter(|r| !r.trim().is_empty()) else {
+        return link.to_string();
+    };
+    if link.starts_with("magnet:") {
+        return link.to_string();
+    }
+    let rules = match parse(raw) {
+        Ok(rules) => rules,
+        Err(e) => {
+            tracing::warn!("Ignoring {}: {:#}", SETTING, e);
+            return link.to_string();
+        }
+    };
+    rules.iter().fold(link.to_string(), |link, rule| {
+        let replacement = render_template(&rule.replacement, ctx);
+        rule.pattern
+            .replace_all(&link, replacement.as_str())
+            .into_owned()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_apply() {
+        let rules = "# Mirror that serves the files\n\
+                     ^https://tracker\\.example/dl/(\\d+)$ => https://files.example/get/$1\n\
+                     \n\
+                     $ => ?passkey={{ .Config.passkey }}";
+        let ctx = TemplateContext::default().with_config(HashMap::from([
+            (SETTING.to_string(), rules.to_string()),
+            ("passkey".to_string(), "abc123".to_string()),
+        ]));
+        assert_eq!(
+            apply("https://tracker.example/dl/42", &ctx),
+            "https://files.example/get/42?passkey=abc123"
+        );
+        assert_eq!(
+            apply("magnet:?xt=urn:btih:abc", &ctx),
+            "magnet:?xt=urn:btih:abc"
+        );
+
+        // No rules, or broken ones, leave the link as it is
+        let none = TemplateContext::default();
+        assert_eq!(
+            apply("https://tracker.example/dl/1", &none),
+            "https://tracker.example/dl/1"
+        );
+        assert!(parse("no arrow").is_err());
+        assert!(parse("([ => x").is_err());
+    }
+}
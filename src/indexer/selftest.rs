@@ -0,0 +1,177 @@
+//! Data-driven regression cases for filters and templates (`lodestarr selftest`)
+//!
+//! Each YAML file in `fixtures/selftest/` holds a list of cases. A case
+//! renders `template` (or starts from `input`), runs the value through
+//! `filters` and compares the result with `expected`. The bundled files are
+//! compiled in, so the command works from a release binary too.
+
+use anyhow::{Context, Result};
+use rust_embed::RustEmbed;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::path::Path;
+
+use super::definition::Filter;
+use super::filters::apply_filters_with_context;
+use super::template::{TemplateContext, render_template};
+use crate::models::SearchQuery;
+
+#[derive(RustEmbed)]
+#[folder = "fixtures/selftest"]
+struct Fixtures;
+
+/// One input and the output it must produce
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Case {
+    pub name: String,
+    /// Value fed to the filters when there is no template
+    #[serde(default)]
+    pub input: String,
+    /// Rendered first, replacing `input`
+    pub template: Option<String>,
+    #[serde(default)]
+    pub filters: Vec<Filter>,
+    #[serde(default)]
+    pub query: SearchQuery,
+    #[serde(default)]
+    pub config: HashMap<String, String>,
+    #[serde(default)]
+    pub result: HashMap<String, String>,
+    pub expected: String,
+}
+
+impl Case {
+    /// What the filters and templates produce for this case
+    pub fn run(&self) -> String {
+        let mut ctx = TemplateContext::from_search(&self.query).with_config(self.config.clone());
+        ctx.result = self.result.clone();
+        let value = match &self.template {
+            Some(template) => render_template(template, &ctx),
+            None => self.input.clone(),
+        };
+        apply_filters_with_context(&value, &self.filters, &ctx)
+    }
+}
+
+/// The cases of one fixture file
+#[derive(Debug)]
+pub struct Suite {
+    pub file: String,
+    pub cases: Vec<Case>,
+}
+
+/// A case whose output differs from `expected`
+#[derive(Debug)]
+pub struct Failure {
+    pub file: String,
+    pub name: String,
+    pub expected: String,
+    pub actual: String,
+}
+
+/// Outcome of running suites
+#[derive(Debug, Default)]
+pub struct Report {
+    pub passed: usize,
+    pub failures: Vec<Failure>,
+}
+
+fn parse(file: &str, content: &str) -> Result<Suite> {
+    let cases =
+        serde_yml::from_str(content).with_context(|| format!("Invalid fixture {}", file))?;
+    Ok(Suite {
+        file: file.to_string(),
+        cases,
+    })
+}
+
+/// The fixtures compiled into the binary
+pub fn bundled() -> Result<Vec<Suite>> {
+    let mut files: Vec<_> = Fixtures::iter()
+        .filter(|file| file.ends_with(".yml") || file.ends_with(".yaml"))
+        .collect();
+    files.sort();
+    files
+        .iter()
+        .filter_map(|file| Fixtures::get(file).map(|f| (file, f)))
+        .map(|(file, f)| parse(file, &String::from_utf8_lossy(&f.data)))
+        .collect()
+}
+
+/// Every `.yml`/`.yaml` file in `dir`
+pub fn load_dir(dir: &Path) -> Result<Vec<Suite>> {
+    let mut paths: Vec<_> = std::fs::read_dir(dir)
+        .with_context(|| format!("Failed to read {}", dir.display()))?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|p| {
+            p.extension()
+                .is_some_and(|ext| ext == "yml" || ext == "yaml")
+        })
+        .collect();
+    paths.sort();
+    paths
+        .iter()
+        .map(|path| {
+            let content = std::fs::read_to_string(path)
+                .with_context(|| format!("Failed to read {}", path.display()))?;
+            let file = path.file_name().unwrap_or_default().to_string_lossy();
+            parse(&file, &content)
+        })
+        .collect()
+}
+
+/// Run every case, keeping the ones that fail
+pub fn run(suites: &[Suite]) -> Report {
+    let mut report = Report::default();
+    for suite in suites {
+        for case in &suite.cases {
+            let actual = case.run();
+            if actual == case.expected {
+                report.passed += 1;
+            } else {
+                report.failures.push(Failure {
+                    file: suite.file.clone(),
+                    name: case.name.clone(),
+                    expected: case.expected.clone(),
+                    actual,
+                });
+            }
+        }
+    }
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bundled_fixtures() {
+        let suites = bundled().unwrap();
+        assert!(!suites.is_empty());
+        let report = run(&suites);
+        for failure in &report.failures {
+            eprintln!(
+                "{}: {}: expected {:?}, got {:?}",
+                failure.file, failure.name, failure.expected, failure.actual
+            );
+        }
+        assert!(report.failures.is_empty());
+        assert!(report.passed > 0);
+    }
+
+    #[test]
+    fn test_failing_case() {
+        let suite = parse(
+            "inline.yml",
+            "- name: wrong\n  input: a\n  filters: [{name: append, args: b}]\n  expected: a\n",
+        )
+        .unwrap();
+        let report = run(&[suite]);
+        assert_eq!(report.passed, 0);
+        assert_eq!(report.failures[0].actual, "ab");
+
+        assert!(parse("bad.yml", "- name: typo\n  expect: a\n").is_err());
+    }
+}
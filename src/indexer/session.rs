@@ -0,0 +1,64 @@
+//! Per-indexer login sessions
+//!
+//! Executors created for the same indexer share a cookie jar and a cached
+//! "logged in" flag, so private trackers are logged into once and only
+//! again when a search response turns out to be the login page. A new
+//! session starts with the indexer's imported cookies (see [`super::cookies`]).
+
+use once_cell::sync::Lazy;
+use reqwest::cookie::Jar;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+
+static SESSIONS: Lazy<Mutex<HashMap<String, Arc<Session>>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+#[derive(Default)]
+pub struct Session {
+    /// Cookies for every request made on behalf of the indexer
+    pub jar: Arc<Jar>,
+    logged_in: AtomicBool,
+    /// Held while logging in so concurrent searches don't each log in
+    pub login_lock: tokio::sync::Mutex<()>,
+}
+
+impl Session {
+    pub fn is_logged_in(&self) -> bool {
+        self.logged_in.load(Ordering::Acquire)
+    }
+
+    pub fn set_logged_in(&self, logged_in: bool) {
+        self.logged_in.store(logged_in, Ordering::Release);
+    }
+}
+
+/// The shared session for an indexer
+pub fn get(indexer_id: &str) -> Arc<Session> {
+    let mut sessions = SESSIONS.lock().unwrap_or_else(|e| e.into_inner());
+    sessions
+        .entry(indexer_id.to_string())
+        .or_insert_with(|| {
+            let session = Session::default();
+            super::cookies::add_saved(indexer_id, &session.jar);
+            Arc::new(session)
+        })
+        .clone()
+}
+
+/// Drop an indexer's session, so the next executor starts a new one. Returns
+/// the old session, which executors created before keep using.
+pub fn remove(indexer_id: &str) -> Option<Arc<Session>> {
+    let mut sessions = SESSIONS.lock().unwrap_or_else(|e| e.into_inner());
+    sessions.remove(indexer_id)
+}
+
+/// A search response was the tracker's login page (the session expired)
+#[derive(Debug, thiserror::Error)]
+#[error("{0}: not logged in (session expired)")]
+pub struct LoginRequired(pub String);
+
+/// True if the error (or its cause) is [`LoginRequired`]
+pub fn is_login_required(e: &anyhow::Error) -> bool {
+    e.chain().any(|c| c.is::<LoginRequired>())
+}
@@ -0,0 +1,364 @@
+//! Checking native indexer settings before they are saved (`lodestarr indexer settings`)
+//!
+//! Keys are either declared in the definition's `settings` block or are one
+//! of Lodestarr's own `_`-prefixed settings, which work for every indexer.
+
+use anyhow::{Result, bail};
+use std::collections::HashMap;
+
+use super::definition::{IndexerDefinition, Setting};
+
+/// Settings Lodestarr understands for every native indexer
+pub const BUILTIN: &[&str] = &[
+    "_categoryFallback",
+    "_cookie",
+    "_dateFromDetails",
+    "_disabledModes",
+    "_downloadRewrite",
+    "_enabled",
+    "_headers",
+    "_keywordRetry",
+    "_mirror",
+    "_priority",
+    "_remote",
+    "_remoteApiKey",
+    "_resultLimit",
+    "_stripBbcode",
+    "_timeout",
+    "_timezone",
+    "_totpInput",
+    "_totpSecret",
+    "_trackerCategories",
+    "_userAgent",
+];
+
+/// Browser cookie setting every native indexer has, declared or not
+pub const COOKIE: &str = "cookie";
+
+const BUILTIN_FLAGS: &[&str] = &[
+    "_dateFromDetails",
+    "_enabled",
+    "_keywordRetry",
+    "_stripBbcode",
+    "_trackerCategories",
+];
+const BUILTIN_NUMBERS: &[&str] = &["_mirror", "_priority", "_resultLimit", "_timeout"];
+
+/// The definition's settings, plus a [`COOKIE`] setting if it doesn't
+/// declare one, so semi-private trackers can be given a browser cookie
+pub fn schema(definition: &IndexerDefinition) -> Vec<Setting> {
+    let mut settings = definition.settings.clone();
+    if !settings.iter().any(|s| s.name == COOKIE) {
+        settings.push(Setting {
+            name: COOKIE.to_string(),
+            setting_type: "text".to_string(),
+            label: Some("Cookie (uid=1; pass=abc, sent with every request)".to_string()),
+            default: None,
+            options: Default::default(),
+            defaults: Vec::new(),
+        });
+    }
+    settings
+}
+
+/// Whether a value should be masked when shown or typed: passwords, keys and cookies
+pub fn is_secret(setting: &Setting) -> bool {
+    setting.setting_type == "password" || is_secret_name(&setting.name)
+}
+
+/// [`is_secret`] by name alone, for Lodestarr's own settings
+pub fn is_secret_name(name: &str) -> bool {
+    let name = name.to_lowercase();
+    ["password", "key", "cookie", "secret"]
+        .iter()
+        .any(|s| name.contains(s))
+}
+
+/// Settings a definition logs in with: username, password, cookie or keys
+pub fn credentials(definition: &IndexerDefinition) -> Vec<&Setting> {
+    if definition.login.is_none() {
+        return Vec::new();
+    }
+    definition
+        .settings
+        .iter()
+        .filter(|s| !s.setting_type.starts_with("info"))
+        .filter(|s| s.name.eq_ignore_ascii_case("username") || is_secret(s))
+        .collect()
+}
+
+/// Check one value against the definition's settings schema
+pub fn validate(definition: &IndexerDefinition, key: &str, value: &str) -> Result<()> {
+    if key.starts_with('_') {
+        if !BUILTIN.contains(&key) {
+            bail!("Unknown setting '{}'", key);
+        }
+        if BUILTIN_FLAGS.contains(&key) && !matches!(value, "true" | "false") {
+            bail!("'{}' must be true or false", key);
+        }
+        if BUILTIN_NUMBERS.contains(&key) && value.trim().parse::<u64>().is_err() {
+            bail!("'{}' must be a whole number", key);
+        }
+        if key == super::categories::SETTING
+            && super::categories::CategoryFallback::parse(value).is_none()
+        {
+            bail!("'{}' must be strict, parent or all", key);
+        }
+        if key == super::rewrite::SETTING {
+            super::rewrite::parse(value)?;
+        }
+        if key == "_disabledModes" {
+            for mode in value.split(',').map(str::trim).filter(|m| !m.is_empty()) {
+                if crate::models::SearchType::from_param(mode).is_none() {
+                    bail!(
+                        "Unknown search type '{}' (expected search, tvsearch, movie, music, book)",
+                        mode
+                    );
+                }
+            }
+        }
+        return Ok(());
+    }
+
+    let settings = schema(definition);
+    let Some(setting) = settings.iter().find(|s| s.name == key) else {
+        let names: Vec<_> = settings
+            .iter()
+            .filter(|s| !s.setting_type.starts_with("info"))
+            .map(|s| s.name.as_str())
+            .collect();
+        bail!(
+            "'{}' has no setting '{}' (settings: {})",
+            definition.id,
+            key,
+            if names.is_empty() {
+                "none".to_string()
+            } else {
+                names.join(", ")
+            }
+        );
+    };
+    match setting.setting_type.as_str() {
+        t if t.starts_with("info") => bail!("'{}' is informational and can't be set", key),
+        "checkbox" if !matches!(value, "true" | "false") => {
+            bail!("'{}' must be true or false", key)
+        }
+        "select" if !setting.options.contains_key(value) => {
+            let mut options: Vec<_> = setting.options.keys().map(String::as_str).collect();
+            options.sort();
+            bail!(
+                "'{}' is not an option of '{}' (options: {})",
+                value,
+                key,
+                options.join(", ")
+            )
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Apply `key=value` assignments to an indexer's saved values. An empty value
+/// removes the key. Nothing is changed unless every assignment is valid.
+pub fn apply(
+    definition: &IndexerDefinition,
+    values: &mut HashMap<String, String>,
+    assignments: &[String],
+) -> Result<()> {
+    let mut parsed = Vec::with_capacity(assignments.len());
+    for assignment in assignments {
+        let Some((key, value)) = assignment.split_once('=') else {
+            bail!("Expected key=value, got '{}'", assignment);
+        };
+        let key = key.trim();
+        if !value.is_empty() {
+            validate(definition, key, value)?;
+        }
+        parsed.push((key, value));
+    }
+    for (key, value) in parsed {
+        if value.is_empty() {
+            values.remove(key);
+        } else {
+            values.insert(key.to_string(), value.to_string());
+        }
+    }
+    Ok(())
+}
+
+/// Complete assignments given as a bare `key` with a value asked for by
+/// `prompt(key, label, secret)`. Other assignments are passed through.
+pub fn prompt_missing(
+    schema: &[Setting],
+    assignments: &[String],
+    mut prompt: impl FnMut(&str, Option<&str>, bool) -> Result<String>,
+) -> Result<Vec<String>> {
+    assignments
+        .iter()
+        .map(|assignment| {
+            if assignment.contains('=') {
+                return Ok(assignment.clone());
+            }
+            let key = assignment.trim();
+            let value = match schema.iter().find(|s| s.name == key) {
+                Some(s) => prompt(key, s.label.as_deref(), is_secret(s))?,
+                None => prompt(key, None, is_secret_name(key))?,
+            };
+            Ok(format!("{}={}", key, value))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_apply() {
+        let yaml = r#"
+id: example
+name: Example
+links: [https://example.com/]
+caps:
+  categorymappings: []
+settings:
+  - name: username
+    type: text
+    label: Username
+  - name: freeleech
+    type: checkbox
+    label: Freeleech only
+    default: false
+  - name: sort
+    type: select
+    label: Sort
+    default: added
+    options:
+      added: Date added
+      seeders: Seeders
+  - name: info_cookie
+    type: info
+    label: How to get the cookie
+search:
+  paths: [{path: browse}]
+  rows: {selector: tr}
+  fields: {title: {selector: a}}
+"#;
+        let definition = IndexerDefinition::from_yaml(yaml).unwrap();
+
+        let mut values = HashMap::from([("_priority".to_string(), "10".to_string())]);
+        let set = |s: &[&str]| s.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        apply(
+            &definition,
+            &mut values,
+            &set(&[
+                "username=me",
+                "sort=seeders",
+                "freeleech=true",
+                "cookie=uid=1; pass=abc",
+                "_priority=",
+            ]),
+        )
+        .unwrap();
+        assert_eq!(values["username"], "me");
+        assert_eq!(values["cookie"], "uid=1; pass=abc");
+        assert_eq!(values["sort"], "seeders");
+        assert!(!values.contains_key("_priority"));
+
+        for bad in [
+            "sort=size",
+            "freeleech=yes",
+            "info_cookie=x",
+            "password=x",
+            "_priority=high",
+            "_disabledModes=tv",
+            "_downloadRewrite=no arrow",
+            "_unknown=1",
+            "username",
+        ] {
+            assert!(
+                apply(&definition, &mut values, &set(&[bad])).is_err(),
+                "{bad}"
+            );
+        }
+        // A bad assignment leaves the good ones unapplied
+        assert!(
+            apply(
+                &definition,
+                &mut values,
+                &set(&["username=other", "sort=size"])
+            )
+            .is_err()
+        );
+        assert_eq!(values["username"], "me");
+
+        // No login block, nothing to log in with
+        assert!(credentials(&definition).is_empty());
+        let with_login =
+            IndexerDefinition::from_yaml(&format!("{yaml}login:\n  path: login.php\n")).unwrap();
+        let names: Vec<_> = credentials(&with_login)
+            .iter()
+            .map(|s| s.name.as_str())
+            .collect();
+        assert_eq!(names, vec!["username"]);
+    }
+
+    #[test]
+    fn test_prompt_missing() {
+        let definition = IndexerDefinition::from_yaml(
+            r#"
+id: example
+name: Example
+links: [https://example.com/]
+settings:
+  - name: username
+    type: text
+    label: Username
+  - name: pass
+    type: password
+    label: Password
+search:
+  paths: [{path: browse}]
+  rows: {selector: tr}
+  fields: {title: {selector: a}}
+"#,
+        )
+        .unwrap();
+        let assignments: Vec<String> = ["username", "pass", "cookie", "_remoteApiKey", "sort=x"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+
+        let mut asked = Vec::new();
+        let completed = prompt_missing(&schema(&definition), &assignments, |key, label, secret| {
+            asked.push((key.to_string(), label.map(str::to_string), secret));
+            Ok(format!("{key}-value"))
+        })
+        .unwrap();
+        assert_eq!(
+            completed,
+            vec![
+                "username=username-value",
+                "pass=pass-value",
+                "cookie=cookie-value",
+                "_remoteApiKey=_remoteApiKey-value",
+                "sort=x",
+            ]
+        );
+        // Passwords, cookies and keys are typed without echo
+        let secrets: Vec<_> = asked
+            .iter()
+            .map(|(key, _, secret)| (key.as_str(), *secret))
+            .collect();
+        assert_eq!(
+            secrets,
+            vec![
+                ("username", false),
+                ("pass", true),
+                ("cookie", true),
+                ("_remoteApiKey", true),
+            ]
+        );
+        assert_eq!(asked[0].1.as_deref(), Some("Username"));
+        assert_eq!(asked[3].1, None);
+    }
+}
@@ -590,10 +590,11 @@ impl TemplateContext {
     /// Create from a search query
     pub fn from_search(query: &crate::models::SearchQuery) -> Self {
         let keywords = query.query.clone().unwrap_or_default();
-        let imdbid = query.imdb_id.clone();
-        let imdbidshort = imdbid
-            .as_ref()
-            .map(|id| id.trim_start_matches("tt").to_string());
+        let imdbid = query
+            .imdb_id
+            .as_deref()
+            .and_then(crate::models::normalize_imdb_id);
+        let imdbidshort = imdbid.as_deref().and_then(crate::models::imdb_id_short);
 
         Self {
             query: QueryVariables {
@@ -651,6 +652,18 @@ mod tests {
         assert_eq!(result, "search=test query");
     }
 
+    #[test]
+    fn test_imdb_id_normalized() {
+        let query = crate::models::SearchQuery {
+            imdb_id: Some("133093".to_string()),
+            ..Default::default()
+        };
+        let ctx = TemplateContext::from_search(&query);
+
+        let result = render_template("{{ .Query.IMDBID }}/{{ .Query.IMDBIDShort }}", &ctx);
+        assert_eq!(result, "tt0133093/0133093");
+    }
+
     #[test]
     fn test_config_variable() {
         let mut ctx = TemplateContext::default();
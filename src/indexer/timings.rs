@@ -0,0 +1,162 @@
+//! Where each indexer's latest search spent its time (`/api/debug/timings`)
+//!
+//! The executor times the phases of a search: mirror race, login, and for
+//! each search path the wait for response headers (DNS, connect, TLS and the
+//! tracker's own processing), the body download and parsing, then details
+//! page lookups. reqwest doesn't report DNS and connect separately, so they
+//! are part of `request`. Every phase is also a tracing span.
+
+use chrono::{DateTime, Utc};
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+static LATEST: Lazy<Mutex<HashMap<String, SearchTimings>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// One timed part of a search
+#[derive(Debug, Clone, Serialize)]
+pub struct Phase {
+    pub name: &'static str,
+    pub ms: u64,
+    /// URL or other context, e.g. which search path
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+}
+
+/// Breakdown of an indexer's latest search
+#[derive(Debug, Clone, Serialize)]
+pub struct SearchTimings {
+    pub indexer: String,
+    pub started_at: DateTime<Utc>,
+    pub total_ms: u64,
+    pub phases: Vec<Phase>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Phases recorded by one executor, shared by its clones
+#[derive(Debug, Clone, Default)]
+pub struct Recorder {
+    phases: Arc<Mutex<Vec<Phase>>>,
+    /// Where finished searches go instead of [`latest`], for trial runs
+    detached: Option<Arc<Mutex<Option<SearchTimings>>>>,
+}
+
+impl Recorder {
+    /// A recorder that keeps finished searches to itself (see [`Self::take_finished`])
+    pub fn detached() -> Self {
+        Self {
+            phases: Arc::default(),
+            detached: Some(Arc::default()),
+        }
+    }
+
+    /// Record a phase that began at `start` and just ended
+    pub fn record(&self, name: &'static str, start: Instant, detail: Option<&str>) {
+        let ms = start.elapsed().as_millis() as u64;
+        tracing::debug!(phase = name, ms, "{}", detail.unwrap_or_default());
+        if let Ok(mut phases) = self.phases.lock() {
+            phases.push(Phase {
+                name,
+                ms,
+                detail: detail.map(String::from),
+            });
+        }
+    }
+
+    fn take(&self) -> Vec<Phase> {
+        self.phases
+            .lock()
+            .map(|mut phases| std::mem::take(&mut *phases))
+            .unwrap_or_default()
+    }
+
+    /// Store the phases recorded so far as `indexer`'s latest search
+    pub fn finish<T>(
+        &self,
+        indexer: &str,
+        started_at: DateTime<Utc>,
+        start: Instant,
+        result: &anyhow::Result<T>,
+    ) {
+        let timings = SearchTimings {
+            indexer: indexer.to_string(),
+            started_at,
+            total_ms: start.elapsed().as_millis() as u64,
+            phases: self.take(),
+            error: result.as_ref().err().map(|e| e.to_string()),
+        };
+        if let Some(detached) = &self.detached {
+            if let Ok(mut finished) = detached.lock() {
+                *finished = Some(timings);
+            }
+        } else if let Ok(mut latest) = LATEST.lock() {
+            latest.insert(indexer.to_string(), timings);
+        }
+    }
+
+    /// The last search finished by a detached recorder
+    pub fn take_finished(&self) -> Option<SearchTimings> {
+        let detached = self.detached.as_ref()?;
+        detached.lock().ok()?.take()
+    }
+}
+
+/// Latest search of every indexer searched since startup, by indexer id
+pub fn latest() -> Vec<SearchTimings> {
+    let Ok(latest) = LATEST.lock() else {
+        return Vec::new();
+    };
+    let mut all: Vec<SearchTimings> = latest.values().cloned().collect();
+    all.sort_by(|a, b| a.indexer.cmp(&b.indexer));
+    all
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_recorder() {
+        let recorder = Recorder::default();
+        let start = Instant::now();
+        recorder.clone().record("login", start, None);
+        recorder.record("parse", start, Some("https://example.org/search"));
+
+        recorder.finish("timings-test", Utc::now(), start, &anyhow::Ok(()));
+        let timings = latest()
+            .into_iter()
+            .find(|t| t.indexer == "timings-test")
+            .unwrap();
+        let names: Vec<_> = timings.phases.iter().map(|p| p.name).collect();
+        assert_eq!(names, vec!["login", "parse"]);
+        assert!(timings.error.is_none());
+
+        // The next search starts from a clean slate
+        recorder.finish(
+            "timings-test",
+            Utc::now(),
+            start,
+            &Err::<(), _>(anyhow::anyhow!("boom")),
+        );
+        let timings = latest()
+            .into_iter()
+            .find(|t| t.indexer == "timings-test")
+            .unwrap();
+        assert!(timings.phases.is_empty());
+        assert_eq!(timings.error.as_deref(), Some("boom"));
+
+        // Detached recorders don't touch the shared list
+        let detached = Recorder::detached();
+        detached.record("parse", start, None);
+        detached.finish("timings-trial", Utc::now(), start, &anyhow::Ok(()));
+        assert!(latest().iter().all(|t| t.indexer != "timings-trial"));
+        let timings = detached.take_finished().unwrap();
+        assert_eq!(timings.phases.len(), 1);
+        assert!(detached.take_finished().is_none());
+        assert!(recorder.take_finished().is_none());
+    }
+}
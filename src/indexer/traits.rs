@@ -1,8 +1,9 @@
 //! Indexer trait definition
 
 use crate::Result;
-use crate::models::{SearchQuery, TorrentResult};
+use crate::models::{SearchQuery, SearchType, TorrentResult};
 use async_trait::async_trait;
+use std::collections::HashMap;
 
 /// Trait that all indexers must implement
 #[async_trait]
@@ -107,9 +108,66 @@ pub struct SearchCapabilities {
     pub music_track: bool,
     /// Supports book publisher search
     pub book_publisher: bool,
+
+    /// Parameters of each mode ("tv-search" etc.) as a definition lists
+    /// them. Modes without an entry advertise what the flags above imply.
+    pub mode_params: HashMap<String, Vec<String>>,
 }
 
 impl SearchCapabilities {
+    /// Turn off a search mode (per-indexer `_disabledModes` override)
+    pub fn disable(&mut self, search_type: SearchType) {
+        match search_type {
+            SearchType::Search => self.search = false,
+            SearchType::TvSearch => self.tv_search = false,
+            SearchType::Movie => self.movie_search = false,
+            SearchType::Music => self.music_search = false,
+            SearchType::Book => self.book_search = false,
+        }
+    }
+
+    /// Capabilities of a definition's `caps.modes`: search, tv-search,
+    /// movie-search, music-search and book-search, with their parameters
+    pub fn from_modes(modes: &HashMap<String, Vec<String>>) -> Self {
+        let mut caps = Self::default();
+        for (mode, params) in modes {
+            match mode.as_str() {
+                "search" => caps.search = true,
+                "tv-search" => caps.tv_search = true,
+                "movie-search" => caps.movie_search = true,
+                "music-search" => caps.music_search = true,
+                "book-search" => caps.book_search = true,
+                _ => continue,
+            }
+            for param in params {
+                match param.as_str() {
+                    "season" | "ep" => caps.season_episode = true,
+                    "imdbid" => caps.imdb_id = true,
+                    "tvdbid" => caps.tvdb_id = true,
+                    "tmdbid" => caps.tmdb_id = true,
+                    "rid" => caps.rid = true,
+                    "tvmazeid" => caps.tvmaze_id = true,
+                    "traktid" => caps.trakt_id = true,
+                    "doubanid" => caps.douban_id = true,
+                    "genre" => caps.genre = true,
+                    "year" => caps.year = true,
+                    "label" => caps.music_label = true,
+                    "track" => caps.music_track = true,
+                    "publisher" => caps.book_publisher = true,
+                    _ => {}
+                }
+            }
+            let mut listed: Vec<String> = Vec::new();
+            for param in params {
+                if !listed.contains(param) {
+                    listed.push(param.clone());
+                }
+            }
+            caps.mode_params.insert(mode.clone(), listed);
+        }
+        caps
+    }
+
     /// Default capabilities for a basic public indexer
     pub fn basic() -> Self {
         Self {
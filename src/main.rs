@@ -1,11 +1,26 @@
+mod build_info;
 mod clients;
 mod config;
 mod db;
+mod dedup;
 mod download;
+mod health;
+mod http;
+mod musicbrainz;
+mod notify;
+mod password;
+mod scoring;
 mod search;
+#[cfg(feature = "self-update")]
+mod self_update;
 mod server;
+mod sort;
+mod sports;
+mod store;
+mod torrent;
 mod torznab;
 mod tui;
+mod update;
 mod utils;
 
 // Native indexer modules
@@ -13,15 +28,18 @@ mod error;
 mod indexer;
 mod models;
 
-use anyhow::Result;
-use clap::{Parser, Subcommand, ValueEnum};
+use anyhow::{Context, Result};
+use clap::{Args, Parser, Subcommand, ValueEnum};
 use colored::Colorize;
 use config::Config;
 use download::perform_download;
 use search::perform_search;
-use tabled::{Table, Tabled, settings::Style};
+use tabled::{
+    Table, Tabled,
+    settings::{Style, Width, peaker::Priority},
+};
 use tracing_subscriber::{EnvFilter, fmt, prelude::*};
-use utils::format_size;
+use utils::{format_count, format_size};
 
 #[derive(Debug, Clone, Copy, ValueEnum)]
 enum LogLevel {
@@ -69,6 +87,18 @@ struct Cli {
     #[arg(short = 'l', long, value_enum, default_value = "info", global = true)]
     log_level: LogLevel,
 
+    /// Disable colored output (also off when NO_COLOR is set or TERM=dumb)
+    #[arg(long, global = true)]
+    no_color: bool,
+
+    /// Draw tables without box-drawing characters
+    #[arg(long, global = true)]
+    plain: bool,
+
+    /// Truncate tables to this many columns
+    #[arg(long, global = true, value_name = "COLUMNS")]
+    width: Option<usize>,
+
     #[command(subcommand)]
     command: Option<Commands>,
 }
@@ -97,10 +127,6 @@ enum Commands {
         #[arg(short = 't', long, default_value = "search")]
         search_type: String,
 
-        /// Select specific indexer(s) to search (comma-separated, or 'all')
-        #[arg(short, long, default_value = "all")]
-        indexer: String,
-
         /// Category filter (comma-separated IDs)
         #[arg(short, long)]
         cat: Option<String>,
@@ -129,17 +155,82 @@ enum Commands {
         #[arg(long)]
         year: Option<u32>,
 
-        /// Limit per indexer
-        #[arg(short, long, default_value = "20")]
-        limit: u32,
+        #[command(flatten)]
+        opts: SearchOpts,
+    },
 
-        /// Output format: table, json, links
-        #[arg(short, long, default_value = "table")]
-        output: String,
+    /// Search for a TV show (tvsearch, TV categories)
+    Tv {
+        /// Show name
+        show: String,
+
+        /// Season number
+        #[arg(short, long)]
+        season: Option<u32>,
+
+        /// Episode number
+        #[arg(short, long)]
+        ep: Option<u32>,
+
+        /// IMDB ID
+        #[arg(long)]
+        imdbid: Option<String>,
+
+        /// TVDB ID
+        #[arg(long)]
+        tvdbid: Option<i32>,
+
+        /// Category filter (comma-separated IDs)
+        #[arg(short, long, default_value = "5000")]
+        cat: String,
+
+        #[command(flatten)]
+        opts: SearchOpts,
+    },
+
+    /// Search for a movie (movie search, Movies categories)
+    Movie {
+        /// Movie title
+        title: String,
+
+        /// Release year
+        #[arg(short, long)]
+        year: Option<u32>,
+
+        /// IMDB ID
+        #[arg(long)]
+        imdbid: Option<String>,
 
-        /// Interactive mode: select result to download
-        #[arg(short = 'i', long)]
-        interactive: bool,
+        /// TMDB ID
+        #[arg(long)]
+        tmdbid: Option<i32>,
+
+        /// Category filter (comma-separated IDs)
+        #[arg(short, long, default_value = "2000")]
+        cat: String,
+
+        #[command(flatten)]
+        opts: SearchOpts,
+    },
+
+    /// Search for music (music search, Audio categories)
+    Music {
+        /// Artist name
+        artist: String,
+
+        /// Album name
+        album: Option<String>,
+
+        /// Release year
+        #[arg(short, long)]
+        year: Option<u32>,
+
+        /// Category filter (comma-separated IDs)
+        #[arg(short, long, default_value = "3000")]
+        cat: String,
+
+        #[command(flatten)]
+        opts: SearchOpts,
     },
 
     /// Download a torrent file
@@ -154,20 +245,135 @@ enum Commands {
         /// Force save as .magnet file (for magnet links)
         #[arg(long)]
         magnet: bool,
+
+        /// Resolve the final link and show what would be saved where, without downloading
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// Check the config file
+    Config {
+        #[command(subcommand)]
+        command: ConfigCommands,
+    },
+
+    /// Maintain the SQLite database (stats, vacuum, prune, export/import history)
+    Db {
+        #[command(subcommand)]
+        command: DbCommands,
+    },
+
+    /// Show the server's scheduled RSS rules and the matches they found
+    Rss {
+        #[command(subcommand)]
+        command: RssCommands,
+    },
+
+    /// Find download clients on this machine and known hosts
+    Clients {
+        #[command(subcommand)]
+        command: ClientsCommands,
+    },
+
+    /// Manage the web UI login
+    Auth {
+        #[command(subcommand)]
+        command: AuthCommands,
+    },
+
+    /// Check that a running server is healthy (exit code 0/1, for container healthchecks)
+    Ping {
+        /// Server base URL
+        #[arg(long, default_value = "http://localhost:3420")]
+        url: String,
+
+        /// API key for protected instances (sent as X-Api-Key and ?apikey=)
+        #[arg(short = 'k', long, env = "LODESTARR_APIKEY")]
+        apikey: Option<String>,
+
+        /// Request timeout in seconds
+        #[arg(long, default_value_t = 5)]
+        timeout: u64,
+    },
+
+    /// Inspect release artifacts
+    Dist {
+        #[command(subcommand)]
+        command: DistCommands,
+    },
+
+    /// Run the filter and template regression cases (exit code 1 on failure)
+    Selftest {
+        /// Run the fixture files in this directory instead of the bundled ones
+        #[arg(long)]
+        dir: Option<std::path::PathBuf>,
+    },
+
+    /// Replace this binary with the latest GitHub release
+    #[cfg(feature = "self-update")]
+    SelfUpdate {
+        /// Only check whether a newer release exists
+        #[arg(long)]
+        check: bool,
     },
 
     /// Start the web server
     Serve {
-        /// Host to bind to
-        #[arg(short = 'H', long, default_value = "0.0.0.0")]
-        host: String,
+        /// Host to bind to [default: server.host, or 0.0.0.0]
+        #[arg(short = 'H', long)]
+        host: Option<String>,
 
-        /// Port to listen on
-        #[arg(short, long, default_value_t = 3420)]
-        port: u16,
+        /// Port to listen on [default: server.port, or 3420]
+        #[arg(short, long)]
+        port: Option<u16>,
     },
 }
 
+/// Indexer selection and output options shared by the search commands
+#[derive(Args)]
+struct SearchOpts {
+    /// Indexer(s) to search: comma-separated names, `group:<name>`, or 'all'
+    /// (default: `default_indexers` from the config, else all)
+    #[arg(short, long)]
+    indexer: Option<String>,
+
+    /// Limit per indexer
+    #[arg(short = 'n', long, default_value = "20")]
+    limit: u32,
+
+    /// Only show releases published in the last N days
+    #[arg(long, value_name = "DAYS")]
+    maxage: Option<u32>,
+
+    /// Sort results by (default from `[sort]` in the config, else seeders)
+    #[arg(long, value_enum)]
+    sort: Option<sort::SortField>,
+
+    /// Sort order (default from `[sort]` in the config, else desc)
+    #[arg(long, value_enum)]
+    order: Option<sort::SortOrder>,
+
+    /// Output format: table, json, links
+    #[arg(short, long, default_value = "table")]
+    output: String,
+
+    /// Interactive mode: select result to download
+    #[arg(short = 'I', long, conflicts_with = "best")]
+    interactive: bool,
+
+    /// Pick the single best match using the configured scoring profile and download it
+    #[arg(long)]
+    best: bool,
+
+    /// Stop with an error as soon as any indexer fails
+    #[arg(long)]
+    fail_fast: bool,
+
+    /// Exit with an error, printing no results, if any indexer failed
+    #[arg(long)]
+    require_all: bool,
+}
+
 #[derive(Subcommand)]
 enum IndexerCommands {
     /// List installed native indexers (default) or proxied Torznab servers
@@ -221,7 +427,203 @@ enum IndexerCommands {
         /// Search query to use for testing
         #[arg(short, long, default_value = "test")]
         query: String,
+        /// Output format: text, or json for a report scripts can check
+        /// (requests, status codes, row counts, sample results, warnings)
+        #[arg(short, long, default_value = "text")]
+        output: String,
+    },
+    /// Check that definitions parse and list keys this version ignores
+    Validate {
+        /// Installed indexer IDs or definition files (default: every installed one)
+        targets: Vec<String>,
+    },
+    /// Show or change a native indexer's settings (credentials, options)
+    Settings {
+        /// Native indexer ID
+        id: String,
+        /// Set a value, checked against the definition (repeatable; `key=` removes
+        /// it, and `key` alone asks for the value, hidden for passwords and cookies)
+        #[arg(long = "set", value_name = "KEY=VALUE")]
+        set: Vec<String>,
+        /// Print the settings after changing them (the default without --set)
+        #[arg(long)]
+        show: bool,
+    },
+    /// List, import or clear cookies exported from a browser
+    Cookies {
+        /// Native indexer ID
+        id: String,
+        /// Netscape cookies.txt or browser JSON export to import (`-` for stdin)
+        #[arg(long, value_name = "FILE")]
+        import: Option<std::path::PathBuf>,
+        /// Forget the imported cookies
+        #[arg(long, conflicts_with = "import")]
+        clear: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum ConfigCommands {
+    /// Report syntax errors, unknown keys, invalid URLs and duplicate names
+    Validate {
+        /// Config file to check (default: the active config.toml)
+        file: Option<std::path::PathBuf>,
+    },
+}
+
+#[derive(Subcommand)]
+enum AuthCommands {
+    /// Set or reset the web UI password (signs out every session)
+    SetPassword {
+        /// Login name [default: the current one, or admin]
+        #[arg(short, long)]
+        username: Option<String>,
+    },
+}
+
+#[derive(Subcommand)]
+enum DistCommands {
+    /// Show the build metadata embedded in a binary (exit code 1 on mismatch)
+    Verify {
+        /// Release binary to check
+        artifact: std::path::PathBuf,
+        /// Expected Rust target triple, e.g. aarch64-unknown-linux-gnu
+        #[arg(long)]
+        target: Option<String>,
+        /// Expected version
+        #[arg(long)]
+        version: Option<String>,
+        /// Expected git commit (short or full hash)
+        #[arg(long)]
+        git_hash: Option<String>,
+    },
+}
+
+#[derive(Subcommand)]
+enum DbCommands {
+    /// Show table sizes and cache counts
+    Stats,
+    /// Rebuild the database file to reclaim free space
+    Vacuum,
+    /// Delete expired cache entries and old search/download history
+    Prune {
+        /// Remove history older than this many days
+        #[arg(short, long, default_value_t = 90)]
+        days: i64,
     },
+    /// Export search/download history to a file
+    Export {
+        /// Output file
+        file: std::path::PathBuf,
+        /// Output format: json or csv (default: from the file extension)
+        #[arg(short, long)]
+        format: Option<String>,
+    },
+    /// Import history from a JSON export
+    Import {
+        /// File created by `db export`
+        file: std::path::PathBuf,
+    },
+}
+
+#[derive(Subcommand)]
+enum RssCommands {
+    /// List the RSS rules (managed through `/api/rss/rules`)
+    Rules,
+    /// List matches waiting to be grabbed, and those that failed to send
+    Pending {
+        /// List sent matches, and those seen on a rule's first run, too
+        #[arg(long)]
+        all: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum ClientsCommands {
+    /// Probe localhost and the hosts of configured clients and *arr apps for
+    /// qBittorrent (8080), Transmission (9091) and TorrServer (8090)
+    Discover {
+        /// Another host to probe (repeatable)
+        #[arg(long = "host", value_name = "HOST")]
+        hosts: Vec<String>,
+        /// Add the clients found that aren't configured and need no login
+        #[arg(long)]
+        add: bool,
+    },
+}
+
+/// Table rendering picked by `--plain` and `--width`
+#[derive(Clone, Copy, Default)]
+struct TableOptions {
+    plain: bool,
+    width: Option<usize>,
+}
+
+static TABLE_OPTIONS: once_cell::sync::OnceCell<TableOptions> = once_cell::sync::OnceCell::new();
+
+/// Read a line from stdin after printing `prompt`
+fn read_line(prompt: &str) -> Result<String> {
+    use std::io::Write;
+    print!("{}", prompt);
+    std::io::stdout().flush()?;
+    let mut line = String::new();
+    std::io::stdin().read_line(&mut line)?;
+    Ok(line.trim_end_matches(['\r', '\n']).to_string())
+}
+
+/// Like [`read_line`], without echoing what is typed on a terminal
+fn read_secret(prompt: &str) -> Result<String> {
+    use std::io::IsTerminal;
+    if std::io::stdin().is_terminal() {
+        return Ok(rpassword::prompt_password(prompt)?);
+    }
+    read_line(prompt)
+}
+
+/// Ask for a native indexer setting, hiding passwords, keys and cookies
+fn prompt_setting(name: &str, label: Option<&str>, secret: bool) -> Result<String> {
+    let prompt = format!("{}: ", label.unwrap_or(name));
+    if secret {
+        read_secret(&prompt)
+    } else {
+        read_line(&prompt)
+    }
+}
+
+/// Render rows as a table in the style chosen on the command line
+fn render_table<T: Tabled>(rows: impl IntoIterator<Item = T>) -> String {
+    TABLE_OPTIONS
+        .get()
+        .copied()
+        .unwrap_or_default()
+        .render(rows)
+}
+
+impl TableOptions {
+    fn render<T: Tabled>(self, rows: impl IntoIterator<Item = T>) -> String {
+        let mut table = Table::new(rows);
+        if self.plain {
+            table.with(Style::blank());
+        } else {
+            table.with(Style::rounded());
+        }
+        if let Some(width) = self.width {
+            // Shorten the widest columns first so narrow ones stay readable
+            table.with(
+                Width::truncate(width)
+                    .suffix("...")
+                    .priority(Priority::max(true)),
+            );
+        }
+        table.to_string()
+    }
+}
+
+/// Whether the command line or environment asks for uncolored output
+fn color_disabled(no_color: bool) -> bool {
+    no_color
+        || std::env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty())
+        || std::env::var("TERM").is_ok_and(|term| term == "dumb")
 }
 
 #[derive(Tabled)]
@@ -252,6 +654,30 @@ struct IndexerRow {
     apikey: String,
 }
 
+#[derive(Tabled)]
+struct CookieRow {
+    #[tabled(rename = "Domain")]
+    domain: String,
+    #[tabled(rename = "Path")]
+    path: String,
+    #[tabled(rename = "Name")]
+    name: String,
+    #[tabled(rename = "Expires")]
+    expires: String,
+}
+
+#[derive(Tabled)]
+struct SettingRow {
+    #[tabled(rename = "Setting")]
+    name: String,
+    #[tabled(rename = "Type")]
+    setting_type: String,
+    #[tabled(rename = "Value")]
+    value: String,
+    #[tabled(rename = "Options")]
+    options: String,
+}
+
 #[derive(Tabled)]
 struct NativeIndexerRow {
     #[tabled(rename = "Name")]
@@ -264,10 +690,65 @@ struct NativeIndexerRow {
     language: String,
 }
 
+#[derive(Tabled)]
+struct RssRuleRow {
+    #[tabled(rename = "ID")]
+    id: i64,
+    #[tabled(rename = "Name")]
+    name: String,
+    #[tabled(rename = "Query")]
+    query: String,
+    #[tabled(rename = "Indexer")]
+    indexer: String,
+    #[tabled(rename = "Every")]
+    interval: String,
+    #[tabled(rename = "Client")]
+    client: String,
+    #[tabled(rename = "Last Run")]
+    last_run: String,
+}
+
+#[derive(Tabled)]
+struct RssMatchRow {
+    #[tabled(rename = "Rule")]
+    rule: String,
+    #[tabled(rename = "Title")]
+    title: String,
+    #[tabled(rename = "Size")]
+    size: String,
+    #[tabled(rename = "S")]
+    seeders: String,
+    #[tabled(rename = "Status")]
+    status: String,
+    #[tabled(rename = "Found")]
+    found: String,
+}
+
+#[derive(Tabled)]
+struct DiscoveredClientRow {
+    #[tabled(rename = "Client")]
+    client: String,
+    #[tabled(rename = "URL")]
+    url: String,
+    #[tabled(rename = "Version")]
+    version: String,
+    #[tabled(rename = "Status")]
+    status: String,
+}
+
 #[tokio::main]
 async fn main() -> Result<()> {
     let cli = Cli::parse();
 
+    let no_color = color_disabled(cli.no_color);
+    if no_color {
+        colored::control::set_override(false);
+    }
+    let _ = TABLE_OPTIONS.set(TableOptions {
+        plain: cli.plain,
+        width: cli.width,
+    });
+
     // Initialize tracing/logging
     // Priority: RUST_LOG env var > CLI flag
     let filter = if std::env::var("RUST_LOG").is_ok() {
@@ -276,15 +757,48 @@ async fn main() -> Result<()> {
         EnvFilter::new(cli.log_level.as_filter())
     };
 
+    // Logs go to stderr so JSON output on stdout stays parseable
     tracing_subscriber::registry()
-        .with(fmt::layer().with_target(false).with_thread_ids(false))
+        .with(
+            fmt::layer()
+                .with_writer(std::io::stderr)
+                .with_target(false)
+                .with_thread_ids(false)
+                .with_ansi(!no_color),
+        )
         .with(filter)
         .init();
 
     tracing::debug!("Lodestarr starting...");
     tracing::debug!("Log level: {:?}", cli.log_level);
 
-    let mut config = Config::load()?;
+    // Validation must work on configs that don't load
+    if let Some(Commands::Config { command }) = cli.command {
+        return handle_config_command(command);
+    }
+    if let Some(Commands::Selftest { dir }) = cli.command {
+        return handle_selftest_command(dir.as_deref());
+    }
+    if let Some(Commands::Dist { command }) = cli.command {
+        return handle_dist_command(command);
+    }
+
+    // File < LODESTARR__* environment < command-line flags
+    let mut builder = Config::builder()?;
+    if let Some(Commands::Serve { host, port }) = &cli.command {
+        if let Some(host) = host {
+            builder = builder.set_override("server.host", host.as_str());
+        }
+        if let Some(port) = port {
+            builder = builder.set_override("server.port", i64::from(*port));
+        }
+    }
+    let mut config = builder.build()?;
+    http::configure(&config);
+    indexer::cookies::configure(&config);
+    indexer::mirrors::configure(&config.search);
+    indexer::result_builder::configure_trackers(&config);
+    utils::configure_display(&config.display);
 
     // Backward compatibility: if args provided, treat as a temporary "CLI" indexer
     let cli_indexer = if let Some(url) = cli.url {
@@ -298,61 +812,63 @@ async fn main() -> Result<()> {
     };
 
     // Helper to get active clients
-    let get_clients = |target_indexer: &str| -> Result<Vec<(String, torznab::TorznabClient)>> {
-        let mut clients = Vec::new();
-
-        // Priority 1: CLI Override
-        if let Some(ref idx) = cli_indexer
-            && (target_indexer == "all" || target_indexer == "CLI")
-        {
-            clients.push((
-                idx.name.clone(),
-                torznab::TorznabClient::new(
-                    &idx.url,
-                    idx.apikey.as_deref(),
-                    config.proxy_url.as_deref(),
-                )?,
-            ));
-        }
-
-        // Priority 2: Configured Indexers
-        if clients.is_empty() {
-            // Only load config if no CLI override or if explicitly requested?
-            // Better logic: if CLI args present, use ONLY CLI args unless "config" is requested?
-            // Actually, keep it simple: If CLI args, use them. If config, use them.
-            // If both, maybe merge?
-            if let Some(ref _idx) = cli_indexer {
-                // Already added above
-            } else if target_indexer == "all" {
-                for idx in &config.indexers {
-                    clients.push((
-                        idx.name.clone(),
-                        torznab::TorznabClient::new(
-                            &idx.url,
-                            idx.apikey.as_deref(),
-                            config.proxy_url.as_deref(),
-                        )?,
-                    ));
-                }
-            } else {
-                // specific list
-                for name in target_indexer.split(',') {
-                    if let Some(idx) = config.get_indexer(name) {
-                        clients.push((
-                            idx.name.clone(),
-                            torznab::TorznabClient::new(
-                                &idx.url,
-                                idx.apikey.as_deref(),
-                                config.proxy_url.as_deref(),
-                            )?,
-                        ));
+    let get_clients =
+        |target_indexer: Option<&str>| -> Result<Vec<(String, torznab::TorznabClient)>> {
+            let mut clients = Vec::new();
+
+            // Priority 1: CLI Override
+            if let Some(ref idx) = cli_indexer
+                && matches!(target_indexer, None | Some("all" | "CLI"))
+            {
+                clients.push((
+                    idx.name.clone(),
+                    torznab::TorznabClient::new(
+                        &idx.url,
+                        idx.apikey.as_deref(),
+                        config.proxy_url.as_deref(),
+                    )?,
+                ));
+            }
+
+            // Priority 2: Configured Indexers
+            if clients.is_empty() {
+                // Only load config if no CLI override or if explicitly requested?
+                // Better logic: if CLI args present, use ONLY CLI args unless "config" is requested?
+                // Actually, keep it simple: If CLI args, use them. If config, use them.
+                // If both, maybe merge?
+                if let Some(ref _idx) = cli_indexer {
+                    // Already added above
+                } else {
+                    let selection = config.select_indexers(target_indexer)?;
+                    for idx in &config.indexers {
+                        if selection.includes(&idx.name) {
+                            clients.push((
+                                idx.name.clone(),
+                                torznab::TorznabClient::new(
+                                    &idx.url,
+                                    idx.apikey.as_deref(),
+                                    config.proxy_url.as_deref(),
+                                )?,
+                            ));
+                        }
                     }
                 }
             }
-        }
 
-        Ok(clients)
-    };
+            Ok(clients)
+        };
+
+    // The server checks daily on its own and healthchecks skip it; other commands
+    // check (at most daily) alongside the command
+    let update_check = (config.check_updates
+        && !matches!(
+            cli.command,
+            Some(Commands::Serve { .. } | Commands::Ping { .. })
+        ))
+    .then(|| {
+        let proxy = config.proxy_url.clone();
+        tokio::spawn(async move { update::refresh(proxy.as_deref()).await })
+    });
 
     match cli.command {
         Some(Commands::Indexer { command }) => handle_indexer_command(command, &mut config).await?,
@@ -362,7 +878,6 @@ async fn main() -> Result<()> {
         Some(Commands::Search {
             query,
             search_type,
-            indexer,
             cat,
             season,
             ep,
@@ -370,14 +885,11 @@ async fn main() -> Result<()> {
             tmdbid,
             tvdbid,
             year,
-            limit,
-            output,
-            interactive,
+            opts,
         }) => {
-            handle_search_command(
+            let params = torznab::SearchParams {
                 query,
                 search_type,
-                indexer,
                 cat,
                 season,
                 ep,
@@ -385,25 +897,94 @@ async fn main() -> Result<()> {
                 tmdbid,
                 tvdbid,
                 year,
-                limit,
-                output,
-                interactive,
-                &get_clients,
-            )
-            .await?
+                ..Default::default()
+            };
+            handle_search_command(params, opts, &config, &get_clients).await?
+        }
+        Some(Commands::Tv {
+            show,
+            season,
+            ep,
+            imdbid,
+            tvdbid,
+            cat,
+            opts,
+        }) => {
+            let params = search::tv_params(show, season, ep, imdbid, tvdbid, cat);
+            handle_search_command(params, opts, &config, &get_clients).await?
+        }
+        Some(Commands::Movie {
+            title,
+            year,
+            imdbid,
+            tmdbid,
+            cat,
+            opts,
+        }) => {
+            let params = search::movie_params(title, year, imdbid, tmdbid, cat);
+            handle_search_command(params, opts, &config, &get_clients).await?
+        }
+        Some(Commands::Music {
+            artist,
+            album,
+            year,
+            cat,
+            opts,
+        }) => {
+            let params = search::music_params(artist, album, year, cat);
+            handle_search_command(params, opts, &config, &get_clients).await?
         }
         Some(Commands::Download {
             url,
             output,
             magnet,
-        }) => handle_download_command(url, output, magnet, &get_clients).await?,
-        Some(Commands::Serve { host, port }) => server::start_server(config, &host, port).await?,
+            dry_run,
+        }) => {
+            if dry_run {
+                handle_download_dry_run(&url, output.as_deref(), magnet, &config).await?
+            } else {
+                handle_download_command(url, output, magnet, &config, &get_clients).await?
+            }
+        }
+        Some(Commands::Config { .. } | Commands::Selftest { .. } | Commands::Dist { .. }) => {
+            unreachable!("handled before loading the config")
+        }
+        Some(Commands::Db { command }) => handle_db_command(command, &config)?,
+        Some(Commands::Rss { command }) => handle_rss_command(command, &config)?,
+        Some(Commands::Clients { command }) => handle_clients_command(command, &mut config).await?,
+        Some(Commands::Auth { command }) => handle_auth_command(command, &mut config)?,
+        Some(Commands::Ping {
+            url,
+            apikey,
+            timeout,
+        }) => handle_ping_command(&url, apikey.as_deref(), timeout).await?,
+        #[cfg(feature = "self-update")]
+        Some(Commands::SelfUpdate { check }) => {
+            self_update::run(config.proxy_url.as_deref(), check).await?
+        }
+        Some(Commands::Serve { .. }) => {
+            let (host, port) = (config.server.host.clone(), config.server.port);
+            server::start_server(config, &host, port).await?
+        }
         None => {
             let mut app = tui::App::new(config)?;
             return app.run().await;
         }
     }
 
+    if let Some(handle) = update_check
+        && let Ok(Ok(Some(info))) =
+            tokio::time::timeout(std::time::Duration::from_secs(2), handle).await
+    {
+        eprintln!(
+            "{} Lodestarr {} is available (running {}): {}",
+            "⬆".yellow(),
+            info.latest,
+            env!("CARGO_PKG_VERSION"),
+            info.url
+        );
+    }
+
     Ok(())
 }
 
@@ -437,7 +1018,7 @@ async fn handle_indexer_command(command: IndexerCommands, config: &mut Config) -
                                 .unwrap_or("-".to_string()),
                         })
                         .collect();
-                    println!("{}", Table::new(rows).with(Style::rounded()));
+                    println!("{}", render_table(rows));
                 }
             } else {
                 // List native indexers from ~/.config/lodestarr/indexers/active/native (default)
@@ -469,8 +1050,27 @@ async fn handle_indexer_command(command: IndexerCommands, config: &mut Config) -
                             language: d.language.clone(),
                         })
                         .collect();
-                    println!("\\n{} {} native indexers installed:\\n", "✓".green(), count);
-                    println!("{}", Table::new(rows).with(Style::rounded()));
+                    println!("\n{} {} native indexers installed:\n", "✓".green(), count);
+                    println!("{}", render_table(rows));
+
+                    // Searches the server ran tell which definitions went stale
+                    if let Ok(db_path) = config.get_db_path()
+                        && db_path.exists()
+                    {
+                        let pool = db::init_db(&db_path);
+                        let broken = indexer::breakage::probably_broken(&pool);
+                        let mut ids: Vec<_> = broken.keys().collect();
+                        ids.sort();
+                        for id in ids {
+                            println!(
+                                "{} '{}' is probably broken: its last {} searches found nothing. Update it with 'lodestarr indexer download {}'",
+                                "⚠".yellow(),
+                                id,
+                                broken[id].empty_streak,
+                                id
+                            );
+                        }
+                    }
                 }
             }
         }
@@ -655,16 +1255,28 @@ async fn handle_indexer_command(command: IndexerCommands, config: &mut Config) -
                 println!("\n{} Updated {} indexer(s)", "✓".green(), success_count);
             }
         }
-        IndexerCommands::Test { name, query } => {
+        IndexerCommands::Test {
+            name,
+            query,
+            output,
+        } => {
             use indexer::SearchExecutor;
             use models::SearchQuery;
+            use std::io::IsTerminal;
 
-            println!(
-                "{} Testing indexer '{}' with query '{}'",
-                "→".cyan(),
-                name,
-                query
-            );
+            let json = match output.as_str() {
+                "text" => false,
+                "json" => true,
+                other => anyhow::bail!("Unknown output format '{}' (use text or json)", other),
+            };
+            if !json {
+                println!(
+                    "{} Testing indexer '{}' with query '{}'",
+                    "→".cyan(),
+                    name,
+                    query
+                );
+            }
 
             // Load the indexer
             let proxy_url = config.proxy_url.as_deref();
@@ -684,18 +1296,67 @@ async fn handle_indexer_command(command: IndexerCommands, config: &mut Config) -
                 .await
                 .ok_or_else(|| anyhow::anyhow!("Indexer '{}' not found", name))?;
 
-            println!("{} Loaded indexer: {}", "✓".green(), indexer_def.name);
+            if !json {
+                println!("{} Loaded indexer: {}", "✓".green(), indexer_def.name);
+            }
+
+            // Credentials that aren't saved are asked for, for this run only
+            let mut settings = config
+                .native_settings
+                .get(&indexer_def.id)
+                .cloned()
+                .unwrap_or_default();
+            let mut prompted = Vec::new();
+            if !json && std::io::stdin().is_terminal() {
+                for setting in indexer::settings::credentials(&indexer_def) {
+                    if settings.get(&setting.name).is_some_and(|v| !v.is_empty()) {
+                        continue;
+                    }
+                    let value = prompt_setting(
+                        &setting.name,
+                        setting.label.as_deref(),
+                        indexer::settings::is_secret(setting),
+                    )?;
+                    if !value.is_empty() {
+                        settings.insert(setting.name.clone(), value);
+                        prompted.push(setting.name.clone());
+                    }
+                }
+            }
 
             // Execute a test search
-            let executor = SearchExecutor::new(None)?;
+            let executor =
+                SearchExecutor::for_indexer(&indexer_def.id, proxy_url, Some(&settings))?;
             let search_query = SearchQuery {
                 query: Some(query.clone()),
                 ..Default::default()
             };
 
+            if json {
+                let start = std::time::Instant::now();
+                let outcome = executor
+                    .search(&indexer_def, &search_query, Some(&settings))
+                    .await;
+                let report = indexer::report::TestReport::new(
+                    &indexer_def.id,
+                    &query,
+                    &outcome,
+                    start.elapsed(),
+                    executor.take_requests(),
+                );
+                println!("{}", serde_json::to_string_pretty(&report)?);
+                if !report.success {
+                    anyhow::bail!("{}", report.message);
+                }
+                return Ok(());
+            }
+
             println!("{} Executing search...", "→".cyan());
 
-            match executor.search(&indexer_def, &search_query, None).await {
+            match executor
+                .search(&indexer_def, &search_query, Some(&settings))
+                .await
+            {
                 Ok(results) => {
                     println!(
                         "\n{} Found {} result(s)\n",
@@ -717,23 +1378,229 @@ async fn handle_indexer_command(command: IndexerCommands, config: &mut Config) -
                     if results.len() > 5 {
                         println!("... and {} more results", results.len() - 5);
                     }
+                    for name in &prompted {
+                        println!(
+                            "{} Save {} with 'lodestarr indexer settings {} --set {}'",
+                            "ℹ".cyan(),
+                            name,
+                            indexer_def.id,
+                            name
+                        );
+                    }
                 }
                 Err(e) => {
+                    if let Some(protection) = indexer::challenge::protection_of(&e) {
+                        println!(
+                            "{} {} is behind a {} challenge. Use FlareSolverr or set a browser cookie with the cookie setting.",
+                            "⚠".yellow(),
+                            indexer_def.name,
+                            protection
+                        );
+                    } else if indexer::error::kind_of(&e) == indexer::ErrorKind::Login {
+                        println!(
+                            "{} {} needs valid credentials. Set them with 'lodestarr indexer settings {} --set password' or in the web UI.",
+                            "⚠".yellow(),
+                            indexer_def.name,
+                            indexer_def.id
+                        );
+                    }
                     println!("{} Search failed: {}", "✗".red(), e);
                     return Err(e);
                 }
             }
         }
+        IndexerCommands::Validate { targets } => {
+            let active_native_path = config.get_active_native_path()?;
+            let files: Vec<std::path::PathBuf> = if targets.is_empty() {
+                let mut files: Vec<_> = std::fs::read_dir(&active_native_path)?
+                    .flatten()
+                    .map(|entry| entry.path())
+                    .filter(|path| path.extension().is_some_and(|e| e == "yaml" || e == "yml"))
+                    .collect();
+                files.sort();
+                files
+            } else {
+                targets
+                    .iter()
+                    .map(|target| {
+                        let path = std::path::PathBuf::from(target);
+                        if path.is_file() {
+                            return Ok(path);
+                        }
+                        ["yml", "yaml"]
+                            .iter()
+                            .map(|ext| active_native_path.join(format!("{}.{}", target, ext)))
+                            .find(|path| path.is_file())
+                            .ok_or_else(|| {
+                                anyhow::anyhow!("No definition file or native indexer '{}'", target)
+                            })
+                    })
+                    .collect::<Result<_>>()?
+            };
+
+            let mut failed = 0;
+            for file in &files {
+                match indexer::definition::IndexerDefinition::from_file(file) {
+                    Ok(def) if def.unknown_fields.is_empty() => {
+                        println!("{} {}", "✓".green(), def.id);
+                    }
+                    Ok(def) => {
+                        println!(
+                            "{} {} (ignoring {} unknown keys)",
+                            "⚠".yellow(),
+                            def.id,
+                            def.unknown_fields.len()
+                        );
+                        for field in &def.unknown_fields {
+                            println!("    {}", field);
+                        }
+                    }
+                    Err(e) => {
+                        failed += 1;
+                        println!("{} {}: {}", "✗".red(), file.display(), e);
+                    }
+                }
+            }
+            if failed > 0 {
+                anyhow::bail!("{} of {} definitions failed to parse", failed, files.len());
+            }
+        }
+        IndexerCommands::Settings { id, set, show } => {
+            use indexer::settings;
+
+            let manager = indexer::IndexerManager::new(config.proxy_url.as_deref());
+            manager
+                .load_definitions(&config.get_active_native_path()?)
+                .await?;
+            let definition = manager
+                .get_definition(&id)
+                .await
+                .ok_or_else(|| anyhow::anyhow!("Native indexer '{}' not found", id))?;
+
+            let schema = settings::schema(&definition);
+            if !set.is_empty() {
+                // Secrets typed at the prompt stay out of the shell history
+                let assignments = settings::prompt_missing(&schema, &set, prompt_setting)?;
+                let mut values = config.native_settings.get(&id).cloned().unwrap_or_default();
+                settings::apply(&definition, &mut values, &assignments)?;
+                if values.is_empty() {
+                    config.native_settings.remove(&id);
+                } else {
+                    config.native_settings.insert(id.clone(), values);
+                }
+                config.save()?;
+                println!("{} Saved settings for '{}'", "✓".green(), id);
+            }
+
+            if show || set.is_empty() {
+                let values = config.native_settings.get(&id).cloned().unwrap_or_default();
+                let defaults = definition.get_default_config();
+                let mut rows: Vec<SettingRow> = schema
+                    .iter()
+                    .filter(|s| !s.setting_type.starts_with("info"))
+                    .map(|s| {
+                        let value = match values.get(&s.name) {
+                            Some(_) if settings::is_secret(s) => "********".to_string(),
+                            Some(v) => v.clone(),
+                            None => defaults
+                                .get(&s.name)
+                                .map(|d| format!("{} (default)", d))
+                                .unwrap_or_else(|| "-".to_string()),
+                        };
+                        let mut options: Vec<_> = s.options.keys().cloned().collect();
+                        options.sort();
+                        SettingRow {
+                            name: s.name.clone(),
+                            setting_type: s.setting_type.clone(),
+                            value,
+                            options: options.join(", "),
+                        }
+                    })
+                    .collect();
+                // Lodestarr's own settings, only when set
+                let mut builtin: Vec<_> =
+                    values.iter().filter(|(k, _)| k.starts_with('_')).collect();
+                builtin.sort();
+                rows.extend(builtin.into_iter().map(|(k, v)| SettingRow {
+                    name: k.clone(),
+                    setting_type: "lodestarr".to_string(),
+                    value: if settings::is_secret_name(k) {
+                        "********".to_string()
+                    } else {
+                        v.clone()
+                    },
+                    options: String::new(),
+                }));
+
+                if rows.is_empty() {
+                    println!("'{}' has no settings", definition.name);
+                } else {
+                    println!("{}", render_table(rows));
+                }
+            }
+        }
+        IndexerCommands::Cookies { id, import, clear } => {
+            use indexer::cookies;
+
+            if clear {
+                let count = cookies::clear(&id)?;
+                println!("{} Cleared {} cookies of '{}'", "✓".green(), count, id);
+                return Ok(());
+            }
+            if let Some(file) = import {
+                let manager = indexer::IndexerManager::new(config.proxy_url.as_deref());
+                manager
+                    .load_definitions(&config.get_active_native_path()?)
+                    .await?;
+                let definition = manager
+                    .get_definition(&id)
+                    .await
+                    .ok_or_else(|| anyhow::anyhow!("Native indexer '{}' not found", id))?;
+                let text = if file.as_os_str() == "-" {
+                    std::io::read_to_string(std::io::stdin())?
+                } else {
+                    std::fs::read_to_string(&file)
+                        .with_context(|| format!("Failed to read {}", file.display()))?
+                };
+                let imported = cookies::import(&definition, &text)?;
+                println!(
+                    "{} Imported {} cookies for '{}' ({} skipped: expired or for other sites)",
+                    "✓".green(),
+                    imported.imported,
+                    id,
+                    imported.skipped
+                );
+            }
+
+            let saved = cookies::load(&id);
+            if saved.is_empty() {
+                println!("'{}' has no imported cookies", id);
+            } else {
+                let rows: Vec<CookieRow> = saved
+                    .into_iter()
+                    .map(|c| CookieRow {
+                        domain: c.domain,
+                        path: c.path,
+                        name: c.name,
+                        expires: c
+                            .expires
+                            .map(|e| e.format("%Y-%m-%d %H:%M").to_string())
+                            .unwrap_or_else(|| "session".to_string()),
+                    })
+                    .collect();
+                println!("{}", render_table(rows));
+            }
+        }
     }
     Ok(())
 }
 
 async fn handle_caps_command(
     indexer: Option<String>,
-    get_clients: &impl Fn(&str) -> Result<Vec<(String, torznab::TorznabClient)>>,
+    get_clients: &impl Fn(Option<&str>) -> Result<Vec<(String, torznab::TorznabClient)>>,
     _cli_indexer: &Option<config::IndexerConfig>,
 ) -> Result<()> {
-    let clients = get_clients(indexer.as_deref().unwrap_or("all"))?;
+    let clients = get_clients(indexer.as_deref())?;
     if clients.is_empty() {
         anyhow::bail!("No indexers available. Use --url/--apikey or add an indexer.");
     }
@@ -759,54 +1626,100 @@ async fn handle_caps_command(
     Ok(())
 }
 
-#[allow(clippy::too_many_arguments)]
 async fn handle_search_command(
-    query: String,
-    search_type: String,
-    indexer: String,
-    cat: Option<String>,
-    season: Option<u32>,
-    ep: Option<u32>,
-    imdbid: Option<String>,
-    tmdbid: Option<i32>,
-    tvdbid: Option<i32>,
-    year: Option<u32>,
-    limit: u32,
-    output: String,
-    interactive: bool,
-    get_clients: &impl Fn(&str) -> Result<Vec<(String, torznab::TorznabClient)>>,
+    mut params: torznab::SearchParams,
+    opts: SearchOpts,
+    config: &Config,
+    get_clients: &impl Fn(Option<&str>) -> Result<Vec<(String, torznab::TorznabClient)>>,
 ) -> Result<()> {
-    let clients = get_clients(&indexer)?;
+    let SearchOpts {
+        indexer,
+        limit,
+        maxage,
+        sort,
+        order,
+        output,
+        interactive,
+        best,
+        fail_fast,
+        require_all,
+    } = opts;
+
+    let clients = get_clients(indexer.as_deref())?;
     if clients.is_empty() {
         anyhow::bail!(
             "No indexers available. Use --url/--apikey or add an indexer via 'indexer add'."
         );
     }
 
-    let params = torznab::SearchParams {
-        query,
-        search_type,
-        cat,
-        season,
-        ep,
-        imdbid,
-        tmdbid,
-        tvdbid,
-        year,
-        limit: Some(limit),
-        ..Default::default()
+    params.limit = Some(limit);
+    params.maxage = maxage;
+    // A season without an episode means we're after the whole season
+    let want_season_pack = params.season.is_some() && params.ep.is_none();
+
+    let sort = config.sort.with(sort, order);
+    let outcome = perform_search(
+        &clients,
+        params,
+        sort,
+        fail_fast,
+        config.search.concurrency(),
+    )
+    .await?;
+    let failures = outcome.failure_summary();
+    if require_all && let Some(failures) = failures {
+        anyhow::bail!(failures);
+    }
+    // Goes to stderr, after the results
+    let report_failures = || {
+        if let Some(failures) = &failures {
+            eprintln!("{} {}", "Warning:".yellow(), failures);
+        }
     };
 
-    let all_results = perform_search(&clients, params).await;
+    let mut all_results = outcome.results;
+    // Not every indexer honours maxage itself
+    search::apply_max_age(&mut all_results, maxage);
 
     if all_results.is_empty() {
         println!("{}", "No results found.".yellow());
+        report_failures();
+        return Ok(());
+    }
+
+    if best {
+        report_failures();
+        let Some((result, score)) =
+            scoring::pick_best(&all_results, &config.scoring, want_season_pack)
+        else {
+            println!("{}", "No result matched the scoring profile.".yellow());
+            return Ok(());
+        };
+
+        match output.as_str() {
+            "json" => println!("{}", serde_json::to_string_pretty(result)?),
+            "links" => {
+                if let Some(link) = result.link.as_ref().or(result.magnet.as_ref()) {
+                    println!("{}", link);
+                }
+            }
+            _ => {
+                println!(
+                    "{} Best match: {} (score {})",
+                    "✓".green(),
+                    result.title.cyan(),
+                    score
+                );
+                download_result(&clients, result, config).await?;
+            }
+        }
         return Ok(());
     }
 
     match output.as_str() {
         "json" => {
             println!("{}", serde_json::to_string_pretty(&all_results)?);
+            report_failures();
         }
         "links" => {
             for result in &all_results {
@@ -814,6 +1727,7 @@ async fn handle_search_command(
                     println!("{}", link);
                 }
             }
+            report_failures();
         }
         _ => {
             // Table output
@@ -831,8 +1745,8 @@ async fn handle_search_command(
                 .map(|(i, r)| ResultRow {
                     index: i + 1,
                     indexer: r.indexer.clone().unwrap_or_default(),
-                    title: if r.title.len() > 50 {
-                        format!("{}...", &r.title[..47])
+                    title: if r.title.chars().count() > 50 {
+                        format!("{}...", r.title.chars().take(47).collect::<String>())
                     } else {
                         r.title.clone()
                     },
@@ -847,8 +1761,8 @@ async fn handle_search_command(
                 })
                 .collect();
 
-            let table = Table::new(rows).with(Style::rounded()).to_string();
-            println!("{}", table);
+            println!("{}", render_table(rows));
+            report_failures();
 
             if interactive {
                 use std::io::Write;
@@ -864,42 +1778,8 @@ async fn handle_search_command(
                     if let Ok(idx) = input.parse::<usize>() {
                         if idx > 0 && idx <= all_results.len() {
                             let result = &all_results[idx - 1];
-                            // Prefer magnet if available? Or link? Usually link is better unless it's magnet-only
-                            let url = result.link.clone().or(result.magnet.clone());
-
-                            if let Some(dlink) = url {
-                                println!("Selected: {}", result.title.cyan());
-
-                                // Find the client used for this result
-                                let client_name = result.indexer.as_deref().unwrap_or("");
-                                let client = clients
-                                    .iter()
-                                    .find(|(n, _)| n == client_name)
-                                    .map(|(_, c)| c)
-                                    .or_else(|| clients.first().map(|(_, c)| c)); // Fallback
-
-                                if let Some(client) = client {
-                                    perform_download(
-                                        client,
-                                        &dlink,
-                                        None,
-                                        false,
-                                        Some(&result.title),
-                                    )
-                                    .await?;
-                                } else {
-                                    println!(
-                                        "{} Could not find client for indexer '{}'",
-                                        "✗".red(),
-                                        client_name
-                                    );
-                                }
-                            } else {
-                                println!(
-                                    "{} No download link available for this result.",
-                                    "✗".red()
-                                );
-                            }
+                            println!("Selected: {}", result.title.cyan());
+                            download_result(&clients, result, config).await?;
                         } else {
                             println!("{} Invalid index number.", "✗".red());
                         }
@@ -913,17 +1793,525 @@ async fn handle_search_command(
     Ok(())
 }
 
+/// Download a search result using the client of the indexer it came from
+async fn download_result(
+    clients: &[(String, torznab::TorznabClient)],
+    result: &torznab::TorrentResult,
+    config: &Config,
+) -> Result<()> {
+    // Prefer magnet if available? Or link? Usually link is better unless it's magnet-only
+    let Some(dlink) = result.link.clone().or(result.magnet.clone()) else {
+        println!("{} No download link available for this result.", "✗".red());
+        return Ok(());
+    };
+
+    // Find the client used for this result
+    let client_name = result.indexer.as_deref().unwrap_or("");
+    let client = clients
+        .iter()
+        .find(|(n, _)| n == client_name)
+        .map(|(_, c)| c)
+        .or_else(|| clients.first().map(|(_, c)| c)); // Fallback
+
+    if let Some(client) = client {
+        let values = download::TemplateValues {
+            title: Some(&result.title),
+            indexer: result.indexer.as_deref(),
+            category: result.categories.first().copied(),
+        };
+        let template = config.download_template.as_deref().map(|t| (t, values));
+        let res =
+            perform_download(client, &dlink, None, false, Some(&result.title), template).await;
+        notify::download_finished(config.notifications, &result.title, &res);
+        res?;
+    } else {
+        println!(
+            "{} Could not find client for indexer '{}'",
+            "✗".red(),
+            client_name
+        );
+    }
+    Ok(())
+}
+
 async fn handle_download_command(
     url: String,
     output: Option<String>,
     magnet: bool,
-    get_clients: &impl Fn(&str) -> Result<Vec<(String, torznab::TorznabClient)>>,
+    config: &Config,
+    get_clients: &impl Fn(Option<&str>) -> Result<Vec<(String, torznab::TorznabClient)>>,
 ) -> Result<()> {
-    let clients = get_clients("all")?;
+    let clients = get_clients(Some("all"))?;
     if clients.is_empty() {
         anyhow::bail!("No indexers available.");
     }
     let client = &clients[0].1;
-    perform_download(client, &url, output, magnet, None).await?;
+    let indexer = download::parse_proxy_link(&url).map(|(id, _)| id);
+    let values = download::TemplateValues {
+        indexer: indexer.as_deref(),
+        ..Default::default()
+    };
+    let template = config.download_template.as_deref().map(|t| (t, values));
+    let res = perform_download(client, &url, output, magnet, None, template).await;
+    notify::download_finished(config.notifications, &url, &res);
+    res
+}
+
+async fn handle_download_dry_run(
+    url: &str,
+    output: Option<&str>,
+    magnet: bool,
+    config: &Config,
+) -> Result<()> {
+    // Native definitions are needed to resolve multi-step download links
+    let manager = indexer::IndexerManager::new(config.proxy_url.as_deref());
+    manager
+        .load_definitions(&config.get_active_native_path()?)
+        .await?;
+
+    let plan = download::plan_download(url, output, magnet, None, &manager, config).await?;
+    download::print_plan(&plan);
     Ok(())
 }
+
+fn handle_config_command(command: ConfigCommands) -> Result<()> {
+    match command {
+        ConfigCommands::Validate { file } => {
+            let path = match file {
+                Some(path) => path,
+                None => Config::config_path()?,
+            };
+            let content = std::fs::read_to_string(&path)
+                .with_context(|| format!("Failed to read {}", path.display()))?;
+            let issues = config::validate::validate(&content);
+            if issues.is_empty() {
+                println!("{} {} is valid", "✓".green(), path.display());
+                return Ok(());
+            }
+            for issue in &issues {
+                let sep = if issue.line.is_some() { ":" } else { ": " };
+                println!("{} {}{}{}", "✗".red(), path.display(), sep, issue);
+            }
+            anyhow::bail!("{} problem(s) in {}", issues.len(), path.display());
+        }
+    }
+}
+
+fn handle_auth_command(command: AuthCommands, config: &mut Config) -> Result<()> {
+    match command {
+        AuthCommands::SetPassword { username } => {
+            use std::io::IsTerminal;
+
+            let username = username
+                .or_else(|| config.server.auth.username.clone())
+                .unwrap_or_else(|| "admin".to_string());
+            let password = read_secret("New password: ")?;
+            if password.is_empty() {
+                anyhow::bail!("The password can't be empty");
+            }
+            // Piped passwords (scripts) aren't asked twice
+            if std::io::stdin().is_terminal() && read_secret("Repeat password: ")? != password {
+                anyhow::bail!("The passwords don't match");
+            }
+
+            config.server.auth.username = Some(username.clone());
+            config.server.auth.password_hash = Some(password::hash_password(&password));
+            config.save()?;
+
+            // Sessions started with the old password end with it
+            let db_path = config.get_db_path()?;
+            let ended = if db_path.exists() {
+                db::clear_sessions(&db::init_db(&db_path))?
+            } else {
+                0
+            };
+            println!(
+                "{} Web UI login set for '{}' ({} session(s) signed out)",
+                "✓".green(),
+                username,
+                ended
+            );
+        }
+    }
+    Ok(())
+}
+
+fn handle_rss_command(command: RssCommands, config: &Config) -> Result<()> {
+    let db_path = config.get_db_path()?;
+    if !db_path.exists() {
+        anyhow::bail!("No database at {}", db_path.display());
+    }
+    let pool = db::init_db(&db_path);
+    let rules = db::get_rss_rules(&pool)?;
+
+    match command {
+        RssCommands::Rules => {
+            if rules.is_empty() {
+                println!("{}", "No RSS rules.".yellow());
+                return Ok(());
+            }
+            let client_name = |id: &str| {
+                config
+                    .download_clients
+                    .iter()
+                    .find(|c| c.id == id)
+                    .map_or_else(|| format!("{} (missing)", id), |c| c.name.clone())
+            };
+            let rows = rules.iter().map(|rule| RssRuleRow {
+                id: rule.id,
+                name: if rule.enabled {
+                    rule.name.clone()
+                } else {
+                    format!("{} (off)", rule.name)
+                },
+                query: rule.query.clone(),
+                indexer: rule.indexer.clone().unwrap_or_else(|| "all".to_string()),
+                interval: format!("{}m", rule.interval_minutes),
+                client: rule
+                    .client_id
+                    .as_deref()
+                    .map(client_name)
+                    .unwrap_or_default(),
+                last_run: rule
+                    .last_run
+                    .map(|t| t.format("%Y-%m-%d %H:%M").to_string())
+                    .unwrap_or_else(|| "-".to_string()),
+            });
+            println!("{}", render_table(rows));
+        }
+        RssCommands::Pending { all } => {
+            let matches: Vec<_> = db::get_rss_matches(&pool, None, 500)?
+                .into_iter()
+                .filter(|m| {
+                    all || matches!(m.status, db::MatchStatus::Pending | db::MatchStatus::Failed)
+                })
+                .collect();
+            if matches.is_empty() {
+                println!("{}", "No pending matches.".yellow());
+                return Ok(());
+            }
+            let rule_name = |id: i64| {
+                rules
+                    .iter()
+                    .find(|r| r.id == id)
+                    .map_or_else(|| id.to_string(), |r| r.name.clone())
+            };
+            let rows = matches.iter().map(|m| RssMatchRow {
+                rule: rule_name(m.rule_id),
+                title: m.title.clone(),
+                size: m.size.map(format_size).unwrap_or_default(),
+                seeders: m.seeders.map_or_else(|| "-".to_string(), |s| s.to_string()),
+                status: match &m.error {
+                    Some(error) => format!("{}: {}", m.status.as_str(), error),
+                    None => m.status.as_str().to_string(),
+                },
+                found: m.found_at.format("%Y-%m-%d %H:%M").to_string(),
+            });
+            println!("{}", render_table(rows));
+        }
+    }
+    Ok(())
+}
+
+async fn handle_clients_command(command: ClientsCommands, config: &mut Config) -> Result<()> {
+    use clients::discover;
+
+    let ClientsCommands::Discover { hosts, add } = command;
+    let hosts = discover::hosts(config, &hosts);
+    println!("{} Probing {}...", "→".cyan(), hosts.join(", "));
+    let found = discover::discover(config, &hosts).await;
+    if found.is_empty() {
+        println!("{}", "No download clients found.".yellow());
+        return Ok(());
+    }
+    let rows = found.iter().map(|c| DiscoveredClientRow {
+        client: c.name.clone(),
+        url: c.url.clone(),
+        version: c.version.clone().unwrap_or_else(|| "-".to_string()),
+        status: if c.configured {
+            "configured".to_string()
+        } else if c.needs_login {
+            "login required".to_string()
+        } else {
+            "ready".to_string()
+        },
+    });
+    println!("{}", render_table(rows));
+
+    let new: Vec<_> = found.iter().filter(|c| !c.configured).collect();
+    if !add {
+        if new.iter().any(|c| !c.needs_login) {
+            println!(
+                "{} Add the ready ones with 'lodestarr clients discover --add'",
+                "ℹ".cyan()
+            );
+        }
+    } else if new.iter().any(|c| !c.needs_login) {
+        for candidate in new.iter().filter(|c| !c.needs_login) {
+            config.download_clients.push(config::DownloadClient {
+                id: uuid::Uuid::new_v4().to_string(),
+                name: candidate.name.clone(),
+                client_type: candidate.client_type.clone(),
+                url: candidate.url.clone(),
+                username: None,
+                password: None,
+            });
+            println!("{} Added {}", "✓".green(), candidate.name);
+        }
+        config.save()?;
+    }
+    for candidate in new.iter().filter(|c| c.needs_login) {
+        println!(
+            "{} {} needs a login. Add it with its username and password under Settings → Download Clients in the web UI.",
+            "ℹ".cyan(),
+            candidate.name
+        );
+    }
+    Ok(())
+}
+
+fn handle_db_command(command: DbCommands, config: &Config) -> Result<()> {
+    let db_path = config.get_db_path()?;
+    if !db_path.exists() && !matches!(command, DbCommands::Import { .. }) {
+        anyhow::bail!("No database at {}", db_path.display());
+    }
+    if let Some(parent) = db_path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let pool = db::init_db(&db_path);
+
+    match command {
+        DbCommands::Stats => {
+            let stats = db::get_db_stats(&pool)?;
+            println!("{} {}", "Database:".bold(), db_path.display());
+            println!(
+                "Size: {} ({} reclaimable by vacuum)",
+                format_size(stats.size),
+                format_size(stats.free)
+            );
+            for table in &stats.tables {
+                let size = table.bytes.map(format_size).unwrap_or_else(|| "-".into());
+                let rows = format_count(table.rows as u64);
+                println!("  {:<16} {:>8} rows  {:>10}", table.name, rows, size);
+            }
+            println!(
+                "Cache: {} valid, {} expired",
+                stats.cache_valid, stats.cache_expired
+            );
+        }
+        DbCommands::Vacuum => {
+            let before = db::get_db_stats(&pool)?.size;
+            db::vacuum(&pool)?;
+            let after = db::get_db_stats(&pool)?.size;
+            println!(
+                "{} {} -> {}",
+                "✓ Vacuumed:".green(),
+                format_size(before),
+                format_size(after)
+            );
+        }
+        DbCommands::Prune { days } => {
+            let counts = db::prune(&pool, days)?;
+            println!(
+                "{} {} expired cache entries, {} searches, {} downloads, {} latency samples, {} traffic records, {} first-seen dates, {} result GUIDs and {} RSS rule matches older than {} days",
+                "✓ Removed".green(),
+                counts.expired_cache,
+                counts.search_logs,
+                counts.download_logs,
+                counts.indexer_latency,
+                counts.indexer_traffic,
+                counts.first_seen,
+                counts.guids,
+                counts.rss_matches,
+                days
+            );
+        }
+        DbCommands::Export { file, format } => {
+            let format = format
+                .or_else(|| file.extension().map(|e| e.to_string_lossy().to_lowercase()))
+                .unwrap_or_else(|| "json".to_string());
+            let history = db::export_history(&pool)?;
+            let contents = match format.as_str() {
+                "json" => serde_json::to_string_pretty(&history)?,
+                "csv" => db::history_to_csv(&history),
+                other => anyhow::bail!("Unknown export format '{}' (use json or csv)", other),
+            };
+            std::fs::write(&file, contents)?;
+            println!(
+                "{} {} searches, {} downloads to {}",
+                "✓ Exported".green(),
+                history.searches.len(),
+                history.downloads.len(),
+                file.display()
+            );
+        }
+        DbCommands::Import { file } => {
+            let contents = std::fs::read_to_string(&file)?;
+            let history: db::HistoryExport = serde_json::from_str(&contents).map_err(|e| {
+                anyhow::anyhow!(
+                    "{} is not a JSON export from `db export`: {}",
+                    file.display(),
+                    e
+                )
+            })?;
+            let inserted = db::import_history(&pool, &history)?;
+            println!(
+                "{} {} rows from {}",
+                "✓ Imported".green(),
+                inserted,
+                file.display()
+            );
+        }
+    }
+    Ok(())
+}
+
+fn handle_selftest_command(dir: Option<&std::path::Path>) -> Result<()> {
+    use indexer::selftest;
+
+    let suites = match dir {
+        Some(dir) => selftest::load_dir(dir)?,
+        None => selftest::bundled()?,
+    };
+    let report = selftest::run(&suites);
+    for failure in &report.failures {
+        println!("{} {}: {}", "✗".red(), failure.file, failure.name);
+        println!("    expected: {:?}", failure.expected);
+        println!("    actual:   {:?}", failure.actual);
+    }
+
+    let total = report.passed + report.failures.len();
+    if !report.failures.is_empty() {
+        anyhow::bail!("{} of {} cases failed", report.failures.len(), total);
+    }
+    println!("{} {} cases passed", "✓".green(), total);
+    Ok(())
+}
+
+fn handle_dist_command(command: DistCommands) -> Result<()> {
+    let DistCommands::Verify {
+        artifact,
+        target,
+        version,
+        git_hash,
+    } = command;
+    let bytes = std::fs::read(&artifact)
+        .with_context(|| format!("Failed to read {}", artifact.display()))?;
+    let Some(info) = build_info::read_from(&bytes) else {
+        anyhow::bail!(
+            "{} has no Lodestarr build metadata (not a Lodestarr binary, or built before it was embedded)",
+            artifact.display()
+        );
+    };
+
+    let this = build_info::current();
+    let fields = [
+        ("Version", &info.version, &this.version, version),
+        ("Target", &info.target, &this.target, target),
+        ("Git commit", &info.git_hash, &this.git_hash, git_hash),
+        ("Web UI", &info.ui_hash, &this.ui_hash, None),
+    ];
+    let mut mismatches = 0;
+    println!("{} {}", "Artifact:".bold(), artifact.display());
+    for (label, value, ours, expected) in fields {
+        let check = match expected {
+            // Commits match on a shared prefix, short or full
+            Some(expected)
+                if *value == expected
+                    || (label == "Git commit"
+                        && !expected.is_empty()
+                        && (value.starts_with(&expected)
+                            || expected.starts_with(value.as_str()))) =>
+            {
+                "✓".green().to_string()
+            }
+            Some(expected) => {
+                mismatches += 1;
+                format!("{} expected {}", "✗".red(), expected)
+            }
+            None if value == ours => "(same as this binary)".dimmed().to_string(),
+            None => String::new(),
+        };
+        println!("  {:<12} {:<28} {}", label, value, check);
+    }
+
+    if mismatches > 0 {
+        anyhow::bail!("{} field(s) don't match", mismatches);
+    }
+    Ok(())
+}
+
+async fn handle_ping_command(url: &str, apikey: Option<&str>, timeout: u64) -> Result<()> {
+    let client = reqwest::Client::builder()
+        .timeout(std::time::Duration::from_secs(timeout))
+        .build()?;
+    let mut request = client.get(format!("{}/healthz", url.trim_end_matches('/')));
+    if let Some(key) = apikey {
+        request = request.header("X-Api-Key", key).query(&[("apikey", key)]);
+    }
+
+    let response = request
+        .send()
+        .await
+        .map_err(|e| anyhow::anyhow!("{} is unreachable: {}", url, e))?;
+    let status = response.status();
+    let body: serde_json::Value = response.json().await.unwrap_or_default();
+    if !status.is_success() {
+        anyhow::bail!(
+            "{} is unhealthy ({}): {}",
+            url,
+            status,
+            body["status"].as_str().unwrap_or("no status")
+        );
+    }
+
+    println!(
+        "{} {} (Lodestarr {})",
+        "✓ OK".green(),
+        url,
+        body["version"].as_str().unwrap_or("unknown")
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_table_options() {
+        let rows = || {
+            vec![IndexerRow {
+                name: "yts".to_string(),
+                url: "https://yts.example/api/v2/torznab/a/very/long/path".to_string(),
+                apikey: "-".to_string(),
+            }]
+        };
+        let rounded = TableOptions::default().render(rows());
+        assert!(rounded.contains('╭'));
+        assert!(rounded.contains("a/very/long/path"));
+
+        let plain = TableOptions {
+            plain: true,
+            width: None,
+        }
+        .render(rows());
+        assert!(!plain.contains(['╭', '│', '─']), "{plain}");
+        assert!(plain.contains("yts"));
+
+        // The long URL column is cut, the short ones are kept
+        let narrow = TableOptions {
+            plain: false,
+            width: Some(40),
+        }
+        .render(rows());
+        assert!(
+            narrow.lines().all(|line| line.chars().count() <= 40),
+            "{narrow}"
+        );
+        assert!(narrow.contains("..."));
+        assert!(narrow.contains("yts"));
+
+        assert!(color_disabled(true));
+    }
+}
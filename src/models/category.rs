@@ -389,7 +389,6 @@ pub static CATEGORIES: &[Category] = &[
 ];
 
 /// Get category by ID
-#[allow(dead_code)]
 pub fn get_category(id: i32) -> Option<&'static Category> {
     CATEGORIES.iter().find(|c| c.id == id)
 }
@@ -4,6 +4,6 @@ mod category;
 mod search;
 mod torrent;
 
-pub use category::CATEGORIES;
-pub use search::{SearchQuery, SearchType};
-pub use torrent::TorrentResult;
+pub use category::{CATEGORIES, get_category};
+pub use search::{SearchQuery, SearchType, deserialize_imdb_id, imdb_id_short, normalize_imdb_id};
+pub use torrent::{DateSource, MusicMatch, SportsEvent, TorrentResult};
@@ -31,6 +31,17 @@ impl SearchType {
             _ => None,
         }
     }
+
+    /// Torznab 't' parameter
+    pub fn as_param(&self) -> &'static str {
+        match self {
+            Self::Search => "search",
+            Self::TvSearch => "tvsearch",
+            Self::Movie => "movie",
+            Self::Music => "music",
+            Self::Book => "book",
+        }
+    }
 }
 
 /// Search query parameters (from Torznab API)
@@ -105,3 +116,62 @@ impl SearchQuery {
         }
     }
 }
+
+/// Normalize an IMDB ID to the canonical `tt` + at least 7 digits form.
+///
+/// Clients send "tt0133093", "0133093" or "133093"; returns `None` for
+/// anything that isn't an IMDB ID.
+pub fn normalize_imdb_id(raw: &str) -> Option<String> {
+    let raw = raw.trim();
+    let digits = raw
+        .strip_prefix("tt")
+        .or_else(|| raw.strip_prefix("TT"))
+        .unwrap_or(raw);
+    if digits.is_empty() || !digits.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    match digits.parse::<u64>() {
+        Ok(0) | Err(_) => None,
+        Ok(n) => Some(format!("tt{:07}", n)),
+    }
+}
+
+/// IMDB ID without the `tt` prefix (zero padded like the full ID)
+pub fn imdb_id_short(raw: &str) -> Option<String> {
+    normalize_imdb_id(raw).map(|id| id[2..].to_string())
+}
+
+/// serde helper: normalize an optional IMDB ID while deserializing
+pub fn deserialize_imdb_id<'de, D>(deserializer: D) -> Result<Option<String>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let raw: Option<String> = Option::deserialize(deserializer)?;
+    Ok(raw.as_deref().and_then(normalize_imdb_id))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_imdb_id() {
+        assert_eq!(normalize_imdb_id("tt0133093").as_deref(), Some("tt0133093"));
+        assert_eq!(normalize_imdb_id("0133093").as_deref(), Some("tt0133093"));
+        assert_eq!(normalize_imdb_id("133093").as_deref(), Some("tt0133093"));
+        assert_eq!(
+            normalize_imdb_id(" TT133093 ").as_deref(),
+            Some("tt0133093")
+        );
+        // Newer IDs have 8 digits
+        assert_eq!(
+            normalize_imdb_id("tt10872600").as_deref(),
+            Some("tt10872600")
+        );
+        assert_eq!(imdb_id_short("tt0133093").as_deref(), Some("0133093"));
+        assert_eq!(normalize_imdb_id(""), None);
+        assert_eq!(normalize_imdb_id("tt"), None);
+        assert_eq!(normalize_imdb_id("matrix"), None);
+        assert_eq!(normalize_imdb_id("0"), None);
+    }
+}
@@ -1,8 +1,63 @@
 //! Torrent result model
 
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, NaiveDate, Utc};
 use serde::{Deserialize, Serialize};
 
+/// Origin of a result's publication date
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DateSource {
+    /// Listed by the indexer in its search results
+    Indexer,
+    /// Read from the result's details page
+    Details,
+    /// When Lodestarr first saw the result (the indexer gave no date)
+    FirstSeen,
+}
+
+impl DateSource {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Indexer => "indexer",
+            Self::Details => "details",
+            Self::FirstSeen => "first_seen",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "indexer" => Some(Self::Indexer),
+            "details" => Some(Self::Details),
+            "first_seen" => Some(Self::FirstSeen),
+            _ => None,
+        }
+    }
+}
+
+/// MusicBrainz release a music result was matched to
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MusicMatch {
+    /// Artist name as spelled on MusicBrainz
+    #[serde(rename = "Artist")]
+    pub artist: String,
+    #[serde(rename = "ArtistMbid")]
+    pub artist_mbid: String,
+    #[serde(rename = "Album", skip_serializing_if = "Option::is_none")]
+    pub album: Option<String>,
+    /// Release MBID (only when an album was searched for)
+    #[serde(rename = "ReleaseMbid", skip_serializing_if = "Option::is_none")]
+    pub release_mbid: Option<String>,
+}
+
+/// Sports event a release covers (see `crate::sports`)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SportsEvent {
+    #[serde(rename = "Date", skip_serializing_if = "Option::is_none")]
+    pub date: Option<NaiveDate>,
+    #[serde(rename = "League", skip_serializing_if = "Option::is_none")]
+    pub league: Option<String>,
+}
+
 /// A single torrent search result
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct TorrentResult {
@@ -30,6 +85,10 @@ pub struct TorrentResult {
     #[serde(rename = "PublishDate")]
     pub publish_date: Option<DateTime<Utc>>,
 
+    /// Where the publication date came from
+    #[serde(rename = "PublishDateSource", skip_serializing_if = "Option::is_none")]
+    pub date_source: Option<DateSource>,
+
     /// Category IDs (Torznab categories)
     #[serde(rename = "Category", default)]
     pub categories: Vec<i32>,
@@ -97,6 +156,18 @@ pub struct TorrentResult {
     #[serde(rename = "Indexer", skip_serializing_if = "Option::is_none")]
     pub indexer: Option<String>,
 
+    /// Other indexers that returned the same torrent (see `crate::dedup`)
+    #[serde(
+        rename = "OtherIndexers",
+        default,
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    pub other_indexers: Vec<String>,
+
+    /// Release/fansub group parsed from the title
+    #[serde(rename = "ReleaseGroup", skip_serializing_if = "Option::is_none")]
+    pub release_group: Option<String>,
+
     /// Indexer flags (freeleech, scene, internal, etc.)
     #[serde(rename = "Flags", default, skip_serializing_if = "Vec::is_empty")]
     pub flags: Vec<String>,
@@ -112,6 +183,43 @@ pub struct TorrentResult {
     /// Poster image URL
     #[serde(rename = "Poster", skip_serializing_if = "Option::is_none")]
     pub poster: Option<String>,
+
+    /// MusicBrainz match for music searches
+    #[serde(rename = "Music", skip_serializing_if = "Option::is_none")]
+    pub music: Option<MusicMatch>,
+
+    /// Event date and league for sports searches
+    #[serde(rename = "Sports", skip_serializing_if = "Option::is_none")]
+    pub sports: Option<SportsEvent>,
+
+    /// Health score 0-100 (see `crate::health`)
+    #[serde(rename = "Health", skip_serializing_if = "Option::is_none")]
+    pub health: Option<u8>,
+
+    /// A .torrent for this release is already in the download path
+    #[serde(
+        rename = "InLibrary",
+        default,
+        skip_serializing_if = "std::ops::Not::not"
+    )]
+    pub in_library: bool,
+
+    /// Found by searching a nearby category, not one that was asked for
+    /// (see `indexer::categories`)
+    #[serde(
+        rename = "CategoryFallback",
+        default,
+        skip_serializing_if = "std::ops::Not::not"
+    )]
+    pub category_fallback: bool,
+
+    /// When the search that found this result ran (web searches)
+    #[serde(rename = "FetchedAt", skip_serializing_if = "Option::is_none")]
+    pub fetched_at: Option<DateTime<Utc>>,
+
+    /// Served from the search cache instead of a new search
+    #[serde(rename = "Cached", default, skip_serializing_if = "std::ops::Not::not")]
+    pub cached: bool,
 }
 
 impl TorrentResult {
@@ -124,6 +232,7 @@ impl TorrentResult {
             details: None,
             magnet: None,
             publish_date: None,
+            date_source: None,
             categories: Vec::new(),
             size: None,
             files: None,
@@ -140,10 +249,19 @@ impl TorrentResult {
             download_volume_factor: None,
             upload_volume_factor: None,
             indexer: None,
+            other_indexers: Vec::new(),
+            release_group: None,
             flags: Vec::new(),
             description: None,
             genre: None,
             poster: None,
+            music: None,
+            sports: None,
+            health: None,
+            in_library: false,
+            category_fallback: false,
+            fetched_at: None,
+            cached: false,
         }
     }
 }
@@ -0,0 +1,304 @@
+//! MusicBrainz lookups for music searches (enabled with `musicbrainz = true`)
+//!
+//! `t=music` searches with an artist (and optionally an album) are looked up
+//! once, and results whose titles name that artist/album get the canonical
+//! names and MBIDs as Torznab attributes. Also backs artist autocomplete in
+//! the web UI. MusicBrainz allows one request per second per client, so
+//! requests are spaced out and answers cached in memory.
+
+use crate::models::MusicMatch;
+use anyhow::Result;
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+const API_URL: &str = "https://musicbrainz.org/ws/2";
+const USER_AGENT: &str = concat!(
+    "Lodestarr/",
+    env!("CARGO_PKG_VERSION"),
+    " ( https://github.com/ddonindia/Lodestarr )"
+);
+/// MusicBrainz search score (0-100) a match needs to be used
+const MIN_SCORE: u32 = 90;
+const REQUEST_INTERVAL: Duration = Duration::from_secs(1);
+/// Remembered lookups (including misses) before the cache resets
+const CACHE_SIZE: usize = 1000;
+
+static LAST_REQUEST: Lazy<tokio::sync::Mutex<Option<Instant>>> =
+    Lazy::new(|| tokio::sync::Mutex::new(None));
+static MATCHES: Lazy<Mutex<HashMap<String, Option<MusicMatch>>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+static ARTISTS: Lazy<Mutex<HashMap<String, Vec<ArtistSuggestion>>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Artist offered by autocomplete
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArtistSuggestion {
+    pub id: String,
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub disambiguation: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct ArtistSearch {
+    #[serde(default)]
+    artists: Vec<Artist>,
+}
+
+#[derive(Deserialize)]
+struct Artist {
+    id: String,
+    name: String,
+    #[serde(default)]
+    score: u32,
+    #[serde(default)]
+    disambiguation: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct ReleaseSearch {
+    #[serde(default)]
+    releases: Vec<Release>,
+}
+
+#[derive(Deserialize)]
+struct Release {
+    id: String,
+    title: String,
+    #[serde(default)]
+    score: u32,
+    #[serde(rename = "artist-credit", default)]
+    artist_credit: Vec<ArtistCredit>,
+}
+
+#[derive(Deserialize)]
+struct ArtistCredit {
+    artist: CreditedArtist,
+}
+
+#[derive(Deserialize)]
+struct CreditedArtist {
+    id: String,
+    name: String,
+}
+
+/// Best MusicBrainz match for an artist and optional album
+pub async fn lookup(
+    artist: &str,
+    album: Option<&str>,
+    proxy_url: Option<&str>,
+) -> Option<MusicMatch> {
+    let key = format!("{}\u{1f}{}", artist, album.unwrap_or("")).to_lowercase();
+    if let Some(cached) = MATCHES.lock().ok().and_then(|c| c.get(&key).cloned()) {
+        return cached;
+    }
+    let found = match album {
+        Some(album) => lookup_release(artist, album, proxy_url).await,
+        None => lookup_artist(artist, proxy_url).await,
+    };
+    let found = match found {
+        Ok(found) => found,
+        Err(e) => {
+            // Not cached, so the next search tries again
+            tracing::debug!("MusicBrainz lookup for {} failed: {}", artist, e);
+            return None;
+        }
+    };
+    remember(&MATCHES, key, found.clone());
+    found
+}
+
+/// Artists whose names match a (partial) query, for autocomplete
+pub async fn suggest_artists(
+    query: &str,
+    proxy_url: Option<&str>,
+) -> Result<Vec<ArtistSuggestion>> {
+    let key = query.to_lowercase();
+    if let Some(cached) = ARTISTS.lock().ok().and_then(|c| c.get(&key).cloned()) {
+        return Ok(cached);
+    }
+    let search: ArtistSearch = get(
+        "artist",
+        &format!("artist:{}*", lucene_escape(query)),
+        8,
+        proxy_url,
+    )
+    .await?;
+    let suggestions: Vec<ArtistSuggestion> = search
+        .artists
+        .into_iter()
+        .map(|a| ArtistSuggestion {
+            id: a.id,
+            name: a.name,
+            disambiguation: a.disambiguation.filter(|d| !d.is_empty()),
+        })
+        .collect();
+    remember(&ARTISTS, key, suggestions.clone());
+    Ok(suggestions)
+}
+
+/// Attach `found` to the results whose titles name its artist (and album)
+pub fn tag_results(results: &mut [crate::models::TorrentResult], found: &MusicMatch) {
+    for result in results {
+        if names(&result.title, &found.artist)
+            && found
+                .album
+                .as_deref()
+                .is_none_or(|album| names(&result.title, album))
+        {
+            result.music = Some(found.clone());
+        }
+    }
+}
+
+async fn lookup_artist(artist: &str, proxy_url: Option<&str>) -> Result<Option<MusicMatch>> {
+    let search: ArtistSearch = get(
+        "artist",
+        &format!("artist:\"{}\"", lucene_escape(artist)),
+        1,
+        proxy_url,
+    )
+    .await?;
+    Ok(search
+        .artists
+        .into_iter()
+        .find(|a| a.score >= MIN_SCORE)
+        .map(|a| MusicMatch {
+            artist: a.name,
+            artist_mbid: a.id,
+            album: None,
+            release_mbid: None,
+        }))
+}
+
+async fn lookup_release(
+    artist: &str,
+    album: &str,
+    proxy_url: Option<&str>,
+) -> Result<Option<MusicMatch>> {
+    let search: ReleaseSearch = get(
+        "release",
+        &format!(
+            "artist:\"{}\" AND release:\"{}\"",
+            lucene_escape(artist),
+            lucene_escape(album)
+        ),
+        1,
+        proxy_url,
+    )
+    .await?;
+    Ok(search
+        .releases
+        .into_iter()
+        .find(|r| r.score >= MIN_SCORE)
+        .and_then(|r| {
+            let credit = r.artist_credit.into_iter().next()?;
+            Some(MusicMatch {
+                artist: credit.artist.name,
+                artist_mbid: credit.artist.id,
+                album: Some(r.title),
+                release_mbid: Some(r.id),
+            })
+        }))
+}
+
+async fn get<T: serde::de::DeserializeOwned>(
+    entity: &str,
+    query: &str,
+    limit: u32,
+    proxy_url: Option<&str>,
+) -> Result<T> {
+    {
+        // Hold the slot until this request is on its way
+        let mut last = LAST_REQUEST.lock().await;
+        if let Some(wait) = last.and_then(|t| REQUEST_INTERVAL.checked_sub(t.elapsed())) {
+            tokio::time::sleep(wait).await;
+        }
+        *last = Some(Instant::now());
+    }
+    let mut builder = reqwest::Client::builder()
+        .user_agent(USER_AGENT)
+        .timeout(Duration::from_secs(10));
+    if let Some(url) = proxy_url {
+        builder = builder.proxy(reqwest::Proxy::all(url)?);
+    }
+    Ok(builder
+        .build()?
+        .get(format!("{}/{}", API_URL, entity))
+        .query(&[
+            ("query", query),
+            ("fmt", "json"),
+            ("limit", &limit.to_string()),
+        ])
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?)
+}
+
+fn remember<T>(cache: &Mutex<HashMap<String, T>>, key: String, value: T) {
+    if let Ok(mut cache) = cache.lock() {
+        if cache.len() >= CACHE_SIZE {
+            cache.clear();
+        }
+        cache.insert(key, value);
+    }
+}
+
+/// Escape Lucene query syntax in user input
+fn lucene_escape(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        if "+-&|!(){}[]^\"~*?:\\/".contains(c) {
+            out.push('\\');
+        }
+        out.push(c);
+    }
+    out
+}
+
+/// Lower-case alphanumeric words, so "AC/DC" and "ac dc" compare equal
+fn words(s: &str) -> Vec<String> {
+    s.split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .map(str::to_lowercase)
+        .collect()
+}
+
+/// Whether `title` contains `name` as a run of words
+fn names(title: &str, name: &str) -> bool {
+    let title = words(title);
+    let name = words(name);
+    !name.is_empty() && title.windows(name.len()).any(|w| w == name.as_slice())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::TorrentResult;
+
+    #[test]
+    fn test_tag_results() {
+        let found = MusicMatch {
+            artist: "AC/DC".to_string(),
+            artist_mbid: "66c662b6-6e2f-4930-8610-912e24c63ed1".to_string(),
+            album: Some("Back in Black".to_string()),
+            release_mbid: Some("38914b29-7788-4cff-80b7-1ced523f8675".to_string()),
+        };
+        let mut results = vec![
+            TorrentResult::new("AC DC - Back In Black (1980) [FLAC]".into(), "a".into()),
+            TorrentResult::new("ACDC - Highway to Hell".into(), "b".into()),
+            TorrentResult::new("Back in Black - Various Artists".into(), "c".into()),
+        ];
+        tag_results(&mut results, &found);
+        assert_eq!(results[0].music.as_ref(), Some(&found));
+        assert!(results[1].music.is_none());
+        assert!(results[2].music.is_none());
+
+        assert_eq!(lucene_escape("AC/DC: Live"), "AC\\/DC\\: Live");
+    }
+}
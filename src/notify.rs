@@ -0,0 +1,47 @@
+//! Desktop notifications for CLI downloads (enabled with `notifications = true`)
+
+use notify_rust::Notification;
+
+/// Show a desktop notification for a finished download.
+///
+/// Failures (no notification daemon, headless session) are logged and ignored.
+pub fn download_finished(enabled: bool, title: &str, result: &anyhow::Result<()>) {
+    if !enabled {
+        return;
+    }
+
+    let (summary, body) = message(title, result);
+    if let Err(e) = Notification::new()
+        .appname("Lodestarr")
+        .summary(summary)
+        .body(&body)
+        .show()
+    {
+        tracing::debug!("Desktop notification failed: {}", e);
+    }
+}
+
+/// Summary and body of the notification for a download of `title`
+fn message(title: &str, result: &anyhow::Result<()>) -> (&'static str, String) {
+    match result {
+        Ok(()) => ("Download complete", title.to_string()),
+        Err(e) => ("Download failed", format!("{}: {}", title, e)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_message() {
+        assert_eq!(
+            message("Dune", &Ok(())),
+            ("Download complete", "Dune".to_string())
+        );
+        assert_eq!(
+            message("Dune", &Err(anyhow::anyhow!("HTTP 404"))),
+            ("Download failed", "Dune: HTTP 404".to_string())
+        );
+    }
+}
@@ -0,0 +1,136 @@
+//! Result scoring for automatic "best match" selection
+//!
+//! Used by `lodestarr search --best` and `/api/best` to pick a single result
+//! from an aggregated result list according to the configured profile.
+
+use crate::config::ScoringProfile;
+use crate::models::TorrentResult;
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+static RE_EPISODE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?i)\bS\d{1,2}[ ._-]?E\d{1,3}\b|\b\d{1,2}x\d{2,3}\b")
+        .expect("invalid episode regex")
+});
+static RE_SEASON_PACK: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?i)\bS\d{1,2}\b|\bseason[ ._-]?\d{1,2}\b|\bcomplete\b")
+        .expect("invalid season pack regex")
+});
+
+/// Returns true if the title looks like a full season rather than a single episode
+pub fn is_season_pack(title: &str) -> bool {
+    !RE_EPISODE.is_match(title) && RE_SEASON_PACK.is_match(title)
+}
+
+/// Score a result against a profile. Returns `None` if the result is rejected
+/// by a hard limit (min seeders, max size).
+///
+/// When `want_season_pack` is set (season given without an episode), season
+/// packs are preferred over single episodes.
+pub fn score(
+    result: &TorrentResult,
+    profile: &ScoringProfile,
+    want_season_pack: bool,
+) -> Option<i64> {
+    let seeders = result.seeders.unwrap_or(0);
+    if seeders < profile.min_seeders {
+        return None;
+    }
+    if let (Some(max_gb), Some(size)) = (profile.max_size_gb, result.size)
+        && size as f64 > max_gb * 1024.0 * 1024.0 * 1024.0
+    {
+        return None;
+    }
+
+    let title = result.title.to_lowercase();
+    let mut score: i64 = 0;
+
+    // Earlier entries in the preference list score higher
+    if let Some(pos) = profile
+        .resolutions
+        .iter()
+        .position(|r| title.contains(&r.to_lowercase()))
+    {
+        score += (profile.resolutions.len() - pos) as i64 * 1000;
+    }
+
+    if want_season_pack {
+        if is_season_pack(&result.title) {
+            score += 5000;
+        } else if RE_EPISODE.is_match(&result.title) {
+            score -= 5000;
+        }
+    }
+
+    // Seeders break ties between otherwise equal results
+    score += seeders.min(999) as i64;
+
+    Some(score)
+}
+
+/// Pick the highest-scoring result, if any pass the profile's limits
+pub fn pick_best<'a>(
+    results: &'a [TorrentResult],
+    profile: &ScoringProfile,
+    want_season_pack: bool,
+) -> Option<(&'a TorrentResult, i64)> {
+    results
+        .iter()
+        .filter_map(|r| score(r, profile, want_season_pack).map(|s| (r, s)))
+        .max_by_key(|(_, s)| *s)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn result(title: &str, seeders: u32, size: u64) -> TorrentResult {
+        TorrentResult {
+            title: title.to_string(),
+            seeders: Some(seeders),
+            size: Some(size),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_season_pack_detection() {
+        assert!(is_season_pack("Show.S01.1080p.WEB-DL"));
+        assert!(is_season_pack("Show Season 2 Complete 720p"));
+        assert!(!is_season_pack("Show.S01E02.1080p.WEB-DL"));
+        assert!(!is_season_pack("Show 1x02 HDTV"));
+    }
+
+    #[test]
+    fn test_pick_best_prefers_resolution_and_limits() {
+        let profile = ScoringProfile {
+            resolutions: vec!["1080p".into(), "720p".into()],
+            min_seeders: 5,
+            max_size_gb: Some(10.0),
+        };
+        let results = vec![
+            result("Movie.2160p.UHD", 500, 50 * 1024 * 1024 * 1024),
+            result("Movie.720p.WEB", 300, 1024 * 1024 * 1024),
+            result("Movie.1080p.WEB", 20, 4 * 1024 * 1024 * 1024),
+            result("Movie.1080p.Dead", 1, 4 * 1024 * 1024 * 1024),
+        ];
+
+        let (best, _) = pick_best(&results, &profile, false).unwrap();
+        assert_eq!(best.title, "Movie.1080p.WEB");
+    }
+
+    #[test]
+    fn test_pick_best_prefers_season_pack() {
+        let profile = ScoringProfile::default();
+        let results = vec![
+            result("Show.S01E01.1080p", 900, 1024),
+            result("Show.S01.720p", 10, 1024),
+        ];
+
+        let (best, _) = pick_best(&results, &profile, true).unwrap();
+        assert_eq!(best.title, "Show.S01.720p");
+
+        let (best, _) = pick_best(&results, &profile, false).unwrap();
+        assert_eq!(best.title, "Show.S01E01.1080p");
+    }
+}
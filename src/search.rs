@@ -1,52 +1,338 @@
+use crate::indexer::error::{ErrorKind, kind_of};
+use crate::sort::Sort;
 use crate::torznab;
-use colored::Colorize;
-use futures::future::join_all;
+use anyhow::{Result, bail};
+use chrono::{DateTime, Utc};
+use futures::stream::{self, StreamExt};
+use indicatif::{MultiProgress, ProgressBar, ProgressStyle};
+use std::time::Duration;
 
+/// An indexer that failed during a CLI search
+pub struct Failure {
+    pub indexer: String,
+    pub error: anyhow::Error,
+}
+
+impl Failure {
+    /// Short reason, e.g. "timeout" or "login required"
+    pub fn reason(&self) -> &'static str {
+        let timed_out = self.error.chain().any(|cause| {
+            cause
+                .downcast_ref::<reqwest::Error>()
+                .is_some_and(|e| e.is_timeout())
+        });
+        if timed_out {
+            return "timeout";
+        }
+        match kind_of(&self.error) {
+            ErrorKind::Network => "unreachable",
+            ErrorKind::Parse => "bad response",
+            ErrorKind::Login => "login required",
+            ErrorKind::RateLimited => "rate limited",
+            ErrorKind::Blocked => "blocked",
+            ErrorKind::NotFound => "not found",
+            ErrorKind::ProxyDown => "proxy down",
+            ErrorKind::Other => "error",
+        }
+    }
+}
+
+pub struct SearchOutcome {
+    pub results: Vec<torznab::TorrentResult>,
+    /// Number of indexers searched
+    pub searched: usize,
+    pub failures: Vec<Failure>,
+}
+
+impl SearchOutcome {
+    /// "2 of 7 indexers failed: X (timeout), Y (login required)", if any did
+    pub fn failure_summary(&self) -> Option<String> {
+        if self.failures.is_empty() {
+            return None;
+        }
+        let failed: Vec<String> = self
+            .failures
+            .iter()
+            .map(|f| format!("{} ({})", f.indexer, f.reason()))
+            .collect();
+        Some(format!(
+            "{} of {} indexer{} failed: {}",
+            self.failures.len(),
+            self.searched,
+            if self.searched == 1 { "" } else { "s" },
+            failed.join(", ")
+        ))
+    }
+}
+
+/// A spinner per indexer on stderr while a search runs, hidden when stderr
+/// isn't a terminal and cleared once the search is done
+struct Progress {
+    multi: MultiProgress,
+    bars: Vec<ProgressBar>,
+}
+
+impl Progress {
+    fn new(clients: &[(String, torznab::TorznabClient)]) -> Self {
+        let multi = MultiProgress::new();
+        let style = ProgressStyle::with_template("{spinner:.cyan} {prefix:.bold} {msg}")
+            .unwrap_or_else(|_| ProgressStyle::default_spinner());
+        let bars = clients
+            .iter()
+            .map(|(name, _)| {
+                let bar = multi.add(ProgressBar::new_spinner().with_style(style.clone()));
+                bar.set_prefix(name.clone());
+                bar.set_message("waiting");
+                bar
+            })
+            .collect();
+        Self { multi, bars }
+    }
+}
+
+impl Drop for Progress {
+    fn drop(&mut self) {
+        let _ = self.multi.clear();
+    }
+}
+
+/// Search the clients, `concurrency` at a time. With `fail_fast` the search
+/// stops at the first indexer that fails, with its error.
 pub async fn perform_search(
     clients: &[(String, torznab::TorznabClient)],
     params: torznab::SearchParams,
-) -> Vec<torznab::TorrentResult> {
+    sort: Sort,
+    fail_fast: bool,
+    concurrency: usize,
+) -> Result<SearchOutcome> {
+    let progress = Progress::new(clients);
     // Scatter-gather
-    let futures = clients.iter().map(|(name, client)| {
-        let p = params.clone();
-        let n = name.clone();
-        async move {
-            match client.search(&p).await {
-                Ok(mut res) => {
-                    // Tag results with indexer name
-                    for r in &mut res {
-                        r.indexer = Some(n.clone());
-                    }
-                    Ok::<Vec<torznab::TorrentResult>, (String, anyhow::Error)>(res)
+    let mut searches = stream::iter(clients.iter().zip(&progress.bars))
+        .map(|((name, client), bar)| {
+            let p = params.clone();
+            async move {
+                bar.set_message("searching");
+                bar.enable_steady_tick(Duration::from_millis(100));
+                (name, bar, client.search(&p).await)
+            }
+        })
+        .buffer_unordered(concurrency.max(1));
+
+    let mut all_results = Vec::new();
+    let mut failures = Vec::new();
+    while let Some((name, bar, result)) = searches.next().await {
+        match result {
+            Ok(mut res) => {
+                bar.finish_with_message(format!("{} results", res.len()));
+                // Tag results with indexer name
+                for r in &mut res {
+                    r.indexer = Some(name.clone());
                 }
-                Err(e) => Err((n, e)),
+                all_results.extend(res);
+            }
+            Err(error) => {
+                let failure = Failure {
+                    indexer: name.clone(),
+                    error,
+                };
+                bar.finish_with_message(format!("failed ({})", failure.reason()));
+                if fail_fast {
+                    bail!(
+                        "Indexer '{}' failed ({}): {:#}",
+                        failure.indexer,
+                        failure.reason(),
+                        failure.error
+                    );
+                }
+                failures.push(failure);
             }
         }
-    });
+    }
+    // Listed in the order the indexers were given
+    failures.sort_by_key(|f| clients.iter().position(|(name, _)| *name == f.indexer));
 
-    let results_lists = join_all(futures).await;
+    let mut all_results = crate::dedup::merge_duplicates(all_results);
+    sort.apply(&mut all_results, &params.query);
 
-    let mut all_results = Vec::new();
-    for res in results_lists {
-        match res {
-            Ok(r) => all_results.extend(r),
-            Err((name, e)) => eprintln!("{} Indexer '{}' failed: {}", "Warning:".yellow(), name, e),
-        }
+    Ok(SearchOutcome {
+        results: all_results,
+        searched: clients.len(),
+        failures,
+    })
+}
+
+/// Build params for a TV episode/season search (`lodestarr tv`)
+pub fn tv_params(
+    show: String,
+    season: Option<u32>,
+    ep: Option<u32>,
+    imdbid: Option<String>,
+    tvdbid: Option<i32>,
+    cat: String,
+) -> torznab::SearchParams {
+    torznab::SearchParams {
+        query: show,
+        search_type: "tvsearch".to_string(),
+        cat: Some(cat),
+        season,
+        ep,
+        imdbid,
+        tvdbid,
+        ..Default::default()
+    }
+}
+
+/// Build params for a movie search (`lodestarr movie`)
+pub fn movie_params(
+    title: String,
+    year: Option<u32>,
+    imdbid: Option<String>,
+    tmdbid: Option<i32>,
+    cat: String,
+) -> torznab::SearchParams {
+    torznab::SearchParams {
+        query: title,
+        search_type: "movie".to_string(),
+        cat: Some(cat),
+        year,
+        imdbid,
+        tmdbid,
+        ..Default::default()
     }
+}
 
-    sort_results(&mut all_results);
+/// Build params for a music search (`lodestarr music`)
+///
+/// The free-text query is "artist album" so indexers that ignore the
+/// dedicated music parameters still get a useful search.
+pub fn music_params(
+    artist: String,
+    album: Option<String>,
+    year: Option<u32>,
+    cat: String,
+) -> torznab::SearchParams {
+    let query = match &album {
+        Some(album) => format!("{} {}", artist, album),
+        None => artist.clone(),
+    };
+    torznab::SearchParams {
+        query,
+        search_type: "music".to_string(),
+        cat: Some(cat),
+        year,
+        artist: Some(artist),
+        album,
+        ..Default::default()
+    }
+}
 
-    all_results
+/// Whether a release falls within `maxage` days. Undated releases are kept,
+/// since there's no telling how old they are.
+pub fn within_max_age(publish_date: Option<DateTime<Utc>>, maxage: Option<u32>) -> bool {
+    match (publish_date, maxage) {
+        (Some(date), Some(days)) => date >= Utc::now() - chrono::Duration::days(days.into()),
+        _ => true,
+    }
 }
 
-fn sort_results(results: &mut [torznab::TorrentResult]) {
-    results.sort_by(|a, b| b.seeders.unwrap_or(0).cmp(&a.seeders.unwrap_or(0)));
+/// Drop results older than `maxage` days (Torznab `maxage`)
+pub fn apply_max_age(results: &mut Vec<torznab::TorrentResult>, maxage: Option<u32>) {
+    if maxage.is_some() {
+        results.retain(|r| within_max_age(r.publish_date, maxage));
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[tokio::test]
+    async fn test_perform_search_concurrency() {
+        use std::sync::Arc;
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        // Torznab server answering `/{indexer}/api`, tracking how many
+        // searches run at once. The "broken" indexer answers with an error.
+        let running = Arc::new(AtomicUsize::new(0));
+        let peak = Arc::new(AtomicUsize::new(0));
+        let (now, max) = (running.clone(), peak.clone());
+        let app = axum::Router::new().fallback(move |uri: axum::http::Uri| {
+            let (now, max) = (now.clone(), max.clone());
+            async move {
+                max.fetch_max(now.fetch_add(1, Ordering::SeqCst) + 1, Ordering::SeqCst);
+                tokio::time::sleep(Duration::from_millis(50)).await;
+                now.fetch_sub(1, Ordering::SeqCst);
+                let indexer = uri.path().split('/').nth(1).unwrap_or_default().to_string();
+                if indexer == "broken" {
+                    return r#"<error code="100" description="Invalid API Key"/>"#.to_string();
+                }
+                format!(
+                    "<rss><channel><item><title>{0} result</title><guid>{0}</guid>\
+                     <link>http://t.example/{0}.torrent</link></item></channel></rss>",
+                    indexer
+                )
+            }
+        });
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await });
+
+        let clients: Vec<_> = ["a", "b", "c", "d", "broken"]
+            .iter()
+            .map(|name| {
+                let url = format!("http://{}/{}/api", addr, name);
+                (
+                    name.to_string(),
+                    torznab::TorznabClient::direct(&url, None).unwrap(),
+                )
+            })
+            .collect();
+        let params = torznab::SearchParams {
+            query: "ubuntu".to_string(),
+            search_type: "search".to_string(),
+            ..Default::default()
+        };
+
+        let outcome = perform_search(&clients, params.clone(), Sort::default(), false, 2)
+            .await
+            .unwrap();
+        assert_eq!(peak.load(Ordering::SeqCst), 2);
+        assert_eq!(outcome.searched, 5);
+        assert_eq!(outcome.results.len(), 4);
+        assert!(
+            outcome
+                .results
+                .iter()
+                .all(|r| r.title.starts_with(r.indexer.as_deref().unwrap()))
+        );
+        assert_eq!(outcome.failures.len(), 1);
+        assert_eq!(outcome.failures[0].indexer, "broken");
+
+        // With fail_fast the failing indexer's error is returned
+        let e = perform_search(&clients, params, Sort::default(), true, 8)
+            .await
+            .err()
+            .unwrap();
+        assert!(e.to_string().contains("broken"), "{e}");
+    }
+
+    #[test]
+    fn test_apply_max_age() {
+        let aged = |days: Option<i64>| torznab::TorrentResult {
+            title: format!("{:?}", days),
+            publish_date: days.map(|d| Utc::now() - chrono::Duration::days(d)),
+            ..Default::default()
+        };
+        let mut results = vec![aged(Some(1)), aged(Some(30)), aged(None)];
+
+        apply_max_age(&mut results, None);
+        assert_eq!(results.len(), 3);
+
+        apply_max_age(&mut results, Some(7));
+        let titles: Vec<_> = results.iter().map(|r| r.title.as_str()).collect();
+        assert_eq!(titles, vec!["Some(1)", "None"]);
+    }
+
     #[test]
     fn test_sort_results() {
         let mut results = vec![
@@ -67,10 +353,66 @@ mod tests {
             },
         ];
 
-        sort_results(&mut results);
+        Sort::default().apply(&mut results, "");
 
         assert_eq!(results[0].title, "B"); // 50
         assert_eq!(results[1].title, "A"); // 10
         assert_eq!(results[2].title, "C"); // 0 (None)
     }
+
+    #[test]
+    fn test_template_params() {
+        let tv = tv_params(
+            "Severance".into(),
+            Some(1),
+            Some(2),
+            None,
+            None,
+            "5000".into(),
+        );
+        assert_eq!(tv.search_type, "tvsearch");
+        assert_eq!(tv.cat.as_deref(), Some("5000"));
+        assert_eq!((tv.season, tv.ep), (Some(1), Some(2)));
+
+        let movie = movie_params("Heat".into(), Some(1995), None, None, "2000".into());
+        assert_eq!(movie.search_type, "movie");
+        assert_eq!(movie.year, Some(1995));
+
+        let music = music_params(
+            "Daft Punk".into(),
+            Some("Discovery".into()),
+            None,
+            "3000".into(),
+        );
+        assert_eq!(music.search_type, "music");
+        assert_eq!(music.query, "Daft Punk Discovery");
+        assert_eq!(music.artist.as_deref(), Some("Daft Punk"));
+        assert_eq!(music.album.as_deref(), Some("Discovery"));
+    }
+
+    #[test]
+    fn test_failure_summary() {
+        let outcome = |failures| SearchOutcome {
+            results: Vec::new(),
+            searched: 7,
+            failures,
+        };
+        assert_eq!(outcome(Vec::new()).failure_summary(), None);
+
+        let failures = vec![
+            Failure {
+                indexer: "X".to_string(),
+                error: anyhow::anyhow!("boom"),
+            },
+            Failure {
+                indexer: "Y".to_string(),
+                error: anyhow::Error::new(crate::indexer::session::LoginRequired("Y".to_string()))
+                    .context("Search failed"),
+            },
+        ];
+        assert_eq!(
+            outcome(failures).failure_summary().as_deref(),
+            Some("2 of 7 indexers failed: X (error), Y (login required)")
+        );
+    }
 }
@@ -0,0 +1,169 @@
+//! `lodestarr self-update` (built with `--features self-update`)
+//!
+//! Downloads the release binary for this platform from the latest GitHub
+//! release, checks it against the release's `SHA256SUMS` and swaps it in for
+//! the running executable.
+
+use crate::update::{self, GitHubRelease};
+use anyhow::{Context, Result};
+use colored::Colorize;
+use sha2::{Digest, Sha256};
+use std::path::Path;
+use std::time::Duration;
+
+const CHECKSUMS_ASSET: &str = "SHA256SUMS";
+
+/// Release asset name for the platform this binary was built for
+fn asset_name() -> Option<&'static str> {
+    match (std::env::consts::OS, std::env::consts::ARCH) {
+        ("linux", "x86_64") => Some("lodestarr-linux-x86_64"),
+        ("linux", "aarch64") => Some("lodestarr-linux-arm64"),
+        ("windows", "x86_64") => Some("lodestarr-windows-x86_64.exe"),
+        ("macos", "x86_64") => Some("lodestarr-macos-x86_64"),
+        ("macos", "aarch64") => Some("lodestarr-macos-arm64"),
+        _ => None,
+    }
+}
+
+pub async fn run(proxy_url: Option<&str>, check_only: bool) -> Result<()> {
+    let release = update::fetch_release(proxy_url).await?;
+    let latest = release.tag_name.trim_start_matches('v');
+    let current = env!("CARGO_PKG_VERSION");
+
+    if !update::is_newer(latest, current) {
+        println!("{} Lodestarr {} is up to date", "✓".green(), current);
+        return Ok(());
+    }
+    println!("Lodestarr {} is available (running {})", latest, current);
+    if check_only {
+        println!("{}", release.html_url);
+        return Ok(());
+    }
+
+    let name = asset_name().ok_or_else(|| {
+        anyhow::anyhow!(
+            "No release binary for {}-{}",
+            std::env::consts::OS,
+            std::env::consts::ARCH
+        )
+    })?;
+    let binary_url = asset_url(&release, name)?;
+    let sums_url = asset_url(&release, CHECKSUMS_ASSET)
+        .context("Release has no checksums; refusing to install an unverified binary")?;
+
+    let client = update::client(proxy_url, Duration::from_secs(300))?;
+    let sums = client
+        .get(sums_url)
+        .send()
+        .await?
+        .error_for_status()?
+        .text()
+        .await?;
+    let expected = parse_checksums(&sums)
+        .into_iter()
+        .find(|(_, file)| file == name)
+        .map(|(hash, _)| hash)
+        .ok_or_else(|| anyhow::anyhow!("{} is not listed in {}", name, CHECKSUMS_ASSET))?;
+
+    println!("Downloading {}...", name);
+    let binary = client
+        .get(binary_url)
+        .send()
+        .await?
+        .error_for_status()?
+        .bytes()
+        .await?;
+    let actual = hex::encode(Sha256::digest(&binary));
+    if !actual.eq_ignore_ascii_case(&expected) {
+        anyhow::bail!(
+            "Checksum mismatch for {} (expected {}, got {})",
+            name,
+            expected,
+            actual
+        );
+    }
+
+    let exe = std::env::current_exe()?;
+    replace_exe(&exe, &binary).with_context(|| format!("Failed to replace {}", exe.display()))?;
+    println!(
+        "{} Updated {} to {}",
+        "✓".green(),
+        exe.display(),
+        latest.bold()
+    );
+    Ok(())
+}
+
+fn asset_url<'a>(release: &'a GitHubRelease, name: &str) -> Result<&'a str> {
+    release
+        .assets
+        .iter()
+        .find(|a| a.name == name)
+        .map(|a| a.browser_download_url.as_str())
+        .ok_or_else(|| anyhow::anyhow!("Release {} has no asset '{}'", release.tag_name, name))
+}
+
+/// Parse `sha256sum` output: "<hash>  <file>" (or "<hash> *<file>" in binary mode)
+fn parse_checksums(sums: &str) -> Vec<(String, String)> {
+    sums.lines()
+        .filter_map(|line| {
+            let (hash, file) = line.trim().split_once(char::is_whitespace)?;
+            let file = file.trim_start().trim_start_matches('*');
+            Some((hash.to_string(), file.to_string()))
+        })
+        .collect()
+}
+
+/// Write the new binary next to the executable and swap it in.
+///
+/// Unix can rename over a running binary. Windows can't, but it can rename
+/// the running one out of the way first (and back if the swap fails).
+fn replace_exe(exe: &Path, binary: &[u8]) -> Result<()> {
+    let dir = exe
+        .parent()
+        .ok_or_else(|| anyhow::anyhow!("Executable has no parent directory"))?;
+    let staged = dir.join(".lodestarr-update.tmp");
+    std::fs::write(&staged, binary)?;
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(&staged, std::fs::Permissions::from_mode(0o755))?;
+    }
+
+    #[cfg(windows)]
+    let old = {
+        let old = exe.with_extension("old.exe");
+        let _ = std::fs::remove_file(&old);
+        std::fs::rename(exe, &old)?;
+        old
+    };
+
+    if let Err(e) = std::fs::rename(&staged, exe) {
+        let _ = std::fs::remove_file(&staged);
+        #[cfg(windows)]
+        let _ = std::fs::rename(&old, exe);
+        return Err(e.into());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_checksums() {
+        let sums = "abc123  lodestarr-linux-x86_64\ndef456 *lodestarr-windows-x86_64.exe\n\n";
+        assert_eq!(
+            parse_checksums(sums),
+            vec![
+                ("abc123".to_string(), "lodestarr-linux-x86_64".to_string()),
+                (
+                    "def456".to_string(),
+                    "lodestarr-windows-x86_64.exe".to_string()
+                ),
+            ]
+        );
+    }
+}
@@ -1,14 +1,17 @@
 //! API endpoints for managing download clients and sending torrents
 
+use crate::clients::arr::{ArrClient, Release};
 use crate::clients::create_client;
-use crate::config::{ClientType, DownloadClient};
+use crate::clients::discover::{self, Candidate};
+use crate::config::{ArrKind, ClientType, DownloadClient};
+use crate::models::TorrentResult;
 use crate::server::AppState;
 use axum::{
     Json,
-    extract::{Path, State},
-    http::StatusCode,
+    extract::{Path, Query, State},
+    http::{HeaderMap, StatusCode},
 };
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
 /// List all configured clients
 pub async fn list_clients(
@@ -89,6 +92,30 @@ pub async fn remove_client(
     Ok(StatusCode::OK)
 }
 
+#[derive(Deserialize)]
+pub struct DiscoverParams {
+    /// More hosts to probe, comma-separated
+    pub host: Option<String>,
+}
+
+/// Probe localhost and known hosts for download clients. Ones found are
+/// added through [`add_client`].
+pub async fn discover_clients(
+    State(state): State<AppState>,
+    Query(params): Query<DiscoverParams>,
+) -> Json<Vec<Candidate>> {
+    let extra: Vec<String> = params
+        .host
+        .as_deref()
+        .unwrap_or_default()
+        .split(',')
+        .map(str::to_string)
+        .collect();
+    let config = state.config.read().await.clone();
+    let hosts = discover::hosts(&config, &extra);
+    Json(discover::discover(&config, &hosts).await)
+}
+
 /// Request to send a torrent to a client
 #[derive(Deserialize)]
 pub struct SendToClientRequest {
@@ -122,14 +149,17 @@ pub async fn send_to_client(
     // Log the download to the database
     let client_name = client_config.name.clone();
     drop(config); // Release the read lock before DB operation
-    if let Err(e) = crate::db::log_download(
-        &state.db_pool,
-        req.title.as_deref(),
-        Some(&req.magnet),
-        None,
-        Some(&client_name),
-        "client",
-    ) {
+    if let Err(e) = state
+        .store
+        .log_download(
+            req.title.as_deref(),
+            Some(&req.magnet),
+            None,
+            Some(&client_name),
+            "client",
+        )
+        .await
+    {
         tracing::warn!("Failed to log download: {}", e);
     }
 
@@ -138,3 +168,173 @@ pub async fn send_to_client(
         "message": format!("Sent to {}", client_name)
     })))
 }
+
+#[derive(Serialize)]
+pub struct ArrAppInfo {
+    pub name: String,
+    pub kind: ArrKind,
+}
+
+/// List the *arr apps results can be pushed to
+pub async fn list_arr_apps(State(state): State<AppState>) -> Json<Vec<ArrAppInfo>> {
+    let config = state.config.read().await;
+    Json(
+        config
+            .arr_apps
+            .iter()
+            .map(|app| ArrAppInfo {
+                name: app.name.clone(),
+                kind: app.kind,
+            })
+            .collect(),
+    )
+}
+
+/// Push a search result to a *arr app's release push endpoint, so the grab
+/// goes through the app and shows up in its history
+pub async fn push_release(
+    State(state): State<AppState>,
+    Path(app): Path<String>,
+    headers: HeaderMap,
+    Json(result): Json<TorrentResult>,
+) -> Result<Json<serde_json::Value>, (StatusCode, String)> {
+    let config = state.config.read().await;
+    let app = config
+        .arr_apps
+        .iter()
+        .find(|a| a.name.eq_ignore_ascii_case(&app))
+        .cloned()
+        .ok_or((StatusCode::NOT_FOUND, "App not found".to_string()))?;
+    let api_key = config.server.auth.api_key.clone();
+    drop(config);
+
+    let lodestarr_url = app.lodestarr_url.clone().unwrap_or_else(|| {
+        let host = headers
+            .get("host")
+            .and_then(|h| h.to_str().ok())
+            .unwrap_or("localhost:3420");
+        format!("http://{}", host)
+    });
+    let client = ArrClient::new(&app);
+    let indexer = result.indexer.clone().unwrap_or_default();
+    let indexer_id = if indexer.is_empty() {
+        None
+    } else {
+        client.indexer_id(&indexer, &lodestarr_url).await
+    };
+
+    let release = release_for(&result, &lodestarr_url, api_key.as_deref(), indexer_id);
+    let outcome = client
+        .push(&release)
+        .await
+        .map_err(|e| (StatusCode::BAD_GATEWAY, format!("Push failed: {}", e)))?;
+    if !outcome.approved {
+        return Err((
+            StatusCode::UNPROCESSABLE_ENTITY,
+            format!(
+                "Rejected by {}: {}",
+                app.name,
+                outcome.rejections.join(", ")
+            ),
+        ));
+    }
+
+    if let Err(e) = state
+        .store
+        .log_download(
+            Some(&result.title),
+            result.magnet.as_deref(),
+            result.link.as_deref(),
+            Some(&app.name),
+            "push",
+        )
+        .await
+    {
+        tracing::warn!("Failed to log download: {}", e);
+    }
+
+    Ok(Json(serde_json::json!({
+        "success": true,
+        "message": format!("Pushed to {}", app.name),
+        "indexer_id": indexer_id,
+    })))
+}
+
+/// Build the release the app will grab. Links go through Lodestarr's
+/// download proxy, which has the indexer's session; magnets go as they are.
+pub(super) fn release_for(
+    result: &TorrentResult,
+    lodestarr_url: &str,
+    api_key: Option<&str>,
+    indexer_id: Option<i32>,
+) -> Release {
+    let indexer = result.indexer.clone().unwrap_or_default();
+    let link = result.link.as_ref().filter(|l| !l.starts_with("magnet:"));
+    let download_url = match (link, indexer.is_empty()) {
+        (Some(link), false) => crate::download::proxy_download_url(
+            lodestarr_url,
+            &indexer,
+            link,
+            &result.guid,
+            api_key,
+        ),
+        (Some(link), true) => link.clone(),
+        (None, _) => result
+            .magnet
+            .clone()
+            .or_else(|| result.link.clone())
+            .unwrap_or_default(),
+    };
+
+    Release {
+        title: result.title.clone(),
+        download_url,
+        magnet_url: result.magnet.clone(),
+        info_hash: result.info_hash.clone(),
+        guid: result.guid.clone(),
+        info_url: result.details.clone(),
+        protocol: "torrent",
+        publish_date: result.publish_date.unwrap_or_else(chrono::Utc::now),
+        indexer: if indexer.is_empty() {
+            "Lodestarr".to_string()
+        } else {
+            indexer
+        },
+        indexer_id,
+        size: result.size.unwrap_or(0),
+        seeders: result.seeders,
+        leechers: result.leechers,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_release_for() {
+        let result = TorrentResult {
+            title: "Show.S01E01.1080p".to_string(),
+            guid: "https://tracker.example/t/1".to_string(),
+            link: Some("https://tracker.example/dl/1".to_string()),
+            indexer: Some("rutracker".to_string()),
+            size: Some(1000),
+            ..Default::default()
+        };
+        let release = release_for(&result, "http://lodestarr:3420/", Some("k"), Some(5));
+        assert_eq!(
+            release.download_url,
+            "http://lodestarr:3420/api/v2.0/indexers/rutracker/dl?link=aHR0cHM6Ly90cmFja2VyLmV4YW1wbGUvZGwvMQ&guid=https%3A%2F%2Ftracker.example%2Ft%2F1&apikey=k"
+        );
+        assert_eq!(release.indexer, "rutracker");
+        assert_eq!(release.indexer_id, Some(5));
+
+        let magnet = TorrentResult {
+            magnet: Some("magnet:?xt=urn:btih:abc".to_string()),
+            ..Default::default()
+        };
+        let release = release_for(&magnet, "http://lodestarr:3420", None, None);
+        assert_eq!(release.download_url, "magnet:?xt=urn:btih:abc");
+        assert_eq!(release.indexer, "Lodestarr");
+    }
+}
@@ -1,8 +1,11 @@
 //! Proxied indexer API endpoints (Torznab compatible)
 
 use super::AppState;
-use crate::indexer::SearchExecutor;
+use crate::config::IndexerSelection;
+use crate::indexer::{ErrorKind, SearchExecutor, backoff, categories};
 use crate::models::{SearchQuery, SearchType};
+use crate::sort::{SortField, SortOrder};
+use crate::store::Store;
 use crate::torznab::{SearchParams, TorrentResult, TorznabClient};
 use axum::{
     Json,
@@ -68,6 +71,14 @@ pub(super) struct SearchApiParams {
     q: String,
     indexer: Option<String>,
     cat: Option<String>,
+    /// Overrides the configured `[sort]`
+    sort: Option<SortField>,
+    order: Option<SortOrder>,
+    /// Only releases published in the last this many days
+    maxage: Option<u32>,
+    /// Search again even if the results are cached, replacing them
+    #[serde(default)]
+    refresh: bool,
 }
 
 pub(super) async fn search_api(
@@ -82,45 +93,91 @@ pub(super) async fn search_api(
         params.q,
         params.cat.as_deref().unwrap_or("")
     );
+    let (sort, selection) = {
+        let config = state.config.read().await;
+        (
+            config.sort.with(params.sort, params.order),
+            config.select_indexers(params.indexer.as_deref()),
+        )
+    };
+    let selection = match selection {
+        Ok(selection) => selection,
+        Err(e) => return (StatusCode::BAD_REQUEST, e.to_string()).into_response(),
+    };
 
-    // Check cache
-    if let Ok(Some(cached)) = crate::db::get_cached_results(&state.db_pool, &cache_key)
-        && let Ok(results) = serde_json::from_str::<Vec<TorrentResult>>(&cached)
+    // Check cache, or drop it when asked for fresh results
+    if params.refresh {
+        let _ = state.store.take_cached(&cache_key).await;
+    } else if let Ok(Some(cached)) = state.store.get_cached(&cache_key).await
+        && let Ok(mut results) = serde_json::from_str::<Vec<TorrentResult>>(&cached)
     {
+        for r in &mut results {
+            r.cached = true;
+        }
         // Log cached search
-        let _ = crate::db::log_search(
-            &state.db_pool,
-            &params.q,
-            target,
-            results.len(),
-            start.elapsed().as_millis(),
-        );
+        let _ = state
+            .store
+            .log_search(
+                &params.q,
+                target,
+                results.len(),
+                start.elapsed().as_millis(),
+            )
+            .await;
+        crate::search::apply_max_age(&mut results, params.maxage);
+        super::library::flag(&state.db_pool, &mut results);
+        sort.apply(&mut results, &params.q);
         return Json(results).into_response();
     }
 
+    // Identical searches running at the same time share one run
+    let mut all_results = super::single_flight::run(
+        &state.store,
+        &cache_key,
+        search_proxied(&state, &params, &selection, &cache_key),
+    )
+    .await;
+
+    // Record stat
+    let duration = start.elapsed();
+    let _ = state
+        .store
+        .log_search(&params.q, target, all_results.len(), duration.as_millis())
+        .await;
+
+    crate::search::apply_max_age(&mut all_results, params.maxage);
+    super::library::flag(&state.db_pool, &mut all_results);
+    sort.apply(&mut all_results, &params.q);
+
+    Json(all_results).into_response()
+}
+
+/// Search the proxied indexers for the web UI and cache the merged results
+async fn search_proxied(
+    state: &AppState,
+    params: &SearchApiParams,
+    selection: &IndexerSelection,
+    cache_key: &str,
+) -> Vec<TorrentResult> {
     let config = state.config.read().await;
 
-    // Determine clients to query
+    // Determine clients to query. Failing indexers sit out searches of
+    // several indexers, as they do for native ones.
+    let single = selection.is_single();
+    let failing = crate::indexer::failing::failing(&state.db_pool);
     let mut clients = Vec::new();
-    let target = params.indexer.as_deref().unwrap_or("all");
-
-    if target == "all" {
-        for idx in &config.indexers {
-            if !config.is_enabled(&idx.name) {
-                continue;
-            }
-            if let Ok(client) =
-                TorznabClient::new(&idx.url, idx.apikey.as_deref(), config.proxy_url.as_deref())
-            {
-                clients.push((idx.name.clone(), client));
-            }
+    for idx in &config.indexers {
+        if !selection.includes(&idx.name)
+            || !config.is_enabled(&idx.name)
+            || (!single && failing.contains_key(&idx.name))
+        {
+            continue;
         }
-    } else if let Some(idx) = config.get_indexer(target)
-        && config.is_enabled(&idx.name)
-        && let Ok(client) =
+        if let Ok(client) =
             TorznabClient::new(&idx.url, idx.apikey.as_deref(), config.proxy_url.as_deref())
-    {
-        clients.push((idx.name.clone(), client));
+        {
+            clients.push((idx.name.clone(), client));
+        }
     }
 
     let search_params = SearchParams {
@@ -137,12 +194,17 @@ pub(super) async fn search_api(
         ..Default::default()
     };
 
+    let concurrency = config.search.concurrency();
     let futures = clients.into_iter().map(|(name, client)| {
         let p = search_params.clone();
         let n = name.clone();
+        let pool = state.db_pool.clone();
+        let search_config = config.search.clone();
+        let cap = config.max_results_for(&name);
         async move {
-            match client.search(&p).await {
+            match super::latency::timed(&pool, &search_config, &n, client.search(&p)).await {
                 Ok(mut res) => {
+                    res.truncate(cap);
                     for r in &mut res {
                         r.indexer = Some(n.clone());
                     }
@@ -154,41 +216,48 @@ pub(super) async fn search_api(
     });
 
     let results_lists: Vec<Result<Vec<TorrentResult>, _>> = futures::stream::iter(futures)
-        .buffer_unordered(4)
+        .buffer_unordered(concurrency)
         .collect()
         .await;
 
-    let mut all_results = Vec::new();
-    for list in results_lists.into_iter().flatten() {
-        all_results.extend(list);
-    }
+    let found: Vec<TorrentResult> = results_lists.into_iter().flatten().flatten().collect();
+    register_guids(&state.db_pool, None, &found);
+    let mut all_results = crate::dedup::merge_duplicates(found);
 
-    // Record stat
-    let duration = start.elapsed();
-    let _ = crate::db::log_search(
-        &state.db_pool,
-        &params.q,
-        target,
-        all_results.len(),
-        duration.as_millis(),
+    crate::health::apply(
+        &mut all_results,
+        &crate::health::reliability(&state.db_pool),
     );
+    let now = chrono::Utc::now();
+    for r in &mut all_results {
+        r.fetched_at = Some(now);
+    }
 
     // Cache results
     if !all_results.is_empty()
         && let Ok(serialized) = serde_json::to_string(&all_results)
     {
-        let _ = crate::db::set_cached_results(&state.db_pool, &cache_key, &serialized, 1);
+        let _ = state
+            .store
+            .set_cached(cache_key, &serialized, chrono::Duration::hours(1))
+            .await;
     }
-
-    // Sort by seeders
-    all_results.sort_by(|a, b| b.seeders.unwrap_or(0).cmp(&a.seeders.unwrap_or(0)));
-
-    Json(all_results).into_response()
+    all_results
 }
 
 #[derive(Deserialize)]
 pub(super) struct DownloadParams {
-    link: String,
+    /// Upstream link, BASE64 encoded or as it is
+    link: Option<String>,
+    /// GUID of a result found by an earlier search, looked up when there is
+    /// no `link` (see `db::register_guids`). Downloading pins it.
+    guid: Option<String>,
+}
+
+pub(super) fn log_download_traffic(pool: &crate::db::DbPool, indexer: &str, bytes: u64) {
+    if let Err(e) = crate::db::log_indexer_traffic(pool, indexer, "download", bytes) {
+        tracing::debug!("Failed to record traffic for {}: {}", indexer, e);
+    }
 }
 
 pub(super) async fn proxy_download(
@@ -196,16 +265,42 @@ pub(super) async fn proxy_download(
     Path(indexer): Path<String>,
     Query(params): Query<DownloadParams>,
 ) -> impl IntoResponse {
+    // "all" stands for whichever indexer the GUID was seen on
+    let registered = match params.guid.as_deref() {
+        Some(guid) => {
+            let on = Some(indexer.as_str()).filter(|i| *i != "all");
+            match crate::db::lookup_guid(&state.db_pool, on, guid) {
+                Ok(found) => found,
+                Err(e) => {
+                    tracing::warn!("Failed to look up GUID {}: {}", guid, e);
+                    None
+                }
+            }
+        }
+        None => None,
+    };
+    if let (Some(guid), Some(entry)) = (params.guid.as_deref(), &registered)
+        && let Err(e) = crate::db::pin_guid(&state.db_pool, &entry.indexer, guid)
+    {
+        tracing::debug!("Failed to pin GUID {}: {}", guid, e);
+    }
+    let indexer = registered
+        .as_ref()
+        .map_or(indexer, |entry| entry.indexer.clone());
+
     // Decode BASE64 URL if it looks encoded (no ":" in the link)
-    let download_url = if !params.link.contains(':') {
-        // Try to decode as BASE64
-        use base64::{Engine as _, engine::general_purpose::URL_SAFE_NO_PAD};
-        match URL_SAFE_NO_PAD.decode(&params.link) {
-            Ok(bytes) => String::from_utf8(bytes).unwrap_or(params.link.clone()),
-            Err(_) => params.link.clone(),
+    let download_url = match (&params.link, registered) {
+        (Some(link), _) => crate::download::decode_link(link),
+        (None, Some(entry)) => match entry.link.or(entry.magnet) {
+            Some(link) => link,
+            None => return (StatusCode::NOT_FOUND, "Nothing to download").into_response(),
+        },
+        (None, None) if params.guid.is_some() => {
+            return (StatusCode::NOT_FOUND, "Unknown GUID").into_response();
+        }
+        (None, None) => {
+            return (StatusCode::BAD_REQUEST, "A link or guid is required").into_response();
         }
-    } else {
-        params.link.clone()
     };
 
     tracing::debug!("Proxy download for indexer '{}': {}", indexer, download_url);
@@ -221,12 +316,31 @@ pub(super) async fn proxy_download(
 
     let config = state.config.read().await;
 
+    // Fetched ahead of time by `[torrent_prefetch]`
+    if let Some(bytes) = super::torrent_cache::get(&config, &download_url).await {
+        tracing::debug!("Serving prefetched torrent for {}", download_url);
+        return (
+            [
+                (axum::http::header::CONTENT_TYPE, "application/x-bittorrent"),
+                (
+                    axum::http::header::CONTENT_DISPOSITION,
+                    "attachment; filename=\"download.torrent\"",
+                ),
+            ],
+            bytes,
+        )
+            .into_response();
+    }
+
     // First try as a proxied indexer (external Torznab)
     if let Some(idx) = config.get_indexer(&indexer)
         && let Ok(client) =
             TorznabClient::new(&idx.url, idx.apikey.as_deref(), config.proxy_url.as_deref())
     {
-        match client.download(&download_url).await {
+        let (download, received) =
+            crate::http::traffic::metered(client.download(&download_url)).await;
+        log_download_traffic(&state.db_pool, &idx.name, received);
+        match download {
             Ok(bytes) => {
                 let mut headers = axum::http::HeaderMap::new();
                 headers.insert(
@@ -252,12 +366,19 @@ pub(super) async fn proxy_download(
     // Find the indexer definition to get its base URL
     if let Some(def) = definitions.iter().find(|d| d.id == indexer) {
         // Create SearchExecutor to handle download logic (cookies + multi-step)
-        if let Ok(executor) = SearchExecutor::new(config.proxy_url.as_deref()) {
-            // Pre-request to acquire cookies if needed
-            let _ = executor.visit_base_url(def).await;
-
-            // Execute download
-            match executor.download(def, &download_url).await {
+        let settings = config.native_settings.get(&def.id);
+        if let Ok(executor) =
+            SearchExecutor::for_indexer(&def.id, config.proxy_url.as_deref(), settings)
+        {
+            let (download, received) = crate::http::traffic::metered(async {
+                // Pre-request to acquire cookies if needed
+                let _ = executor.visit_base_url(def).await;
+                executor.download(def, &download_url, settings).await
+            })
+            .await;
+            log_download_traffic(&state.db_pool, &def.id, received);
+
+            match download {
                 Ok(bytes) => {
                     let mut headers = axum::http::HeaderMap::new();
                     headers.insert(
@@ -272,8 +393,8 @@ pub(super) async fn proxy_download(
                 }
                 Err(e) => {
                     tracing::error!("Native download failed for {}: {}", indexer, e);
-                    return (StatusCode::BAD_GATEWAY, format!("Download failed: {}", e))
-                        .into_response();
+                    let kind = crate::indexer::error::kind_of(&e);
+                    return (kind.http_status(), format!("Download failed: {}", e)).into_response();
                 }
             }
         }
@@ -287,7 +408,7 @@ pub(super) async fn proxy_download(
 }
 
 /// Torznab API query parameters
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Default, Deserialize)]
 pub struct TorznabParams {
     /// API key
     #[allow(dead_code)]
@@ -306,7 +427,8 @@ pub struct TorznabParams {
     pub season: Option<u32>,
     /// Episode (for tvsearch)
     pub ep: Option<u32>,
-    /// IMDB ID
+    /// IMDB ID (normalized to "tt" + 7/8 digits)
+    #[serde(default, deserialize_with = "crate::models::deserialize_imdb_id")]
     pub imdbid: Option<String>,
     /// TVDB ID
     pub tvdbid: Option<i32>,
@@ -324,6 +446,17 @@ pub struct TorznabParams {
     pub title: Option<String>,
     /// Author (for book)
     pub author: Option<String>,
+    /// First event date, YYYY-MM-DD (for `cat=5060` searches)
+    pub from: Option<String>,
+    /// Last event date, YYYY-MM-DD (for `cat=5060` searches)
+    pub to: Option<String>,
+    /// League (for `cat=5060` searches)
+    pub league: Option<String>,
+    /// Only releases published in the last this many days
+    pub maxage: Option<u32>,
+    /// Result order (extended attribute), overriding the configured `[sort]`
+    pub sort: Option<SortField>,
+    pub order: Option<SortOrder>,
 }
 
 /// Torznab API handler
@@ -333,6 +466,7 @@ pub(super) async fn torznab_api(
     Query(params): Query<TorznabParams>,
     headers: axum::http::HeaderMap,
 ) -> impl IntoResponse {
+    let params = params.with_capped_limit();
     // Extract request base URL for proxy download links
     let host = headers
         .get("host")
@@ -340,9 +474,10 @@ pub(super) async fn torznab_api(
         .unwrap_or("localhost:3420");
     let proxy_base_url = format!("http://{}", host);
 
-    // Handle "all" aggregate indexer
-    if indexer == "all" {
-        return torznab_all_indexers(state, params, &proxy_base_url).await;
+    // "all" and the `tag-<group>` aggregate indexers
+    let aggregate = state.config.read().await.aggregate_indexers(&indexer);
+    if let Some(selection) = aggregate {
+        return torznab_all_indexers(state, params, &proxy_base_url, &indexer, selection).await;
     }
 
     // Get native indexer manager
@@ -367,61 +502,77 @@ pub(super) async fn torznab_api(
 
     match action {
         "caps" => {
-            // Return capabilities
-            let caps = crate::indexer::SearchCapabilities::basic();
-            let categories = vec![
-                // Console
-                1000, 1010, 1020, 1030, 1040, 1050, 1080, 1090, // Movies
-                2000, 2010, 2020, 2030, 2040, 2045, 2050, 2060, 2070, 2080, 2090, // Audio
-                3000, 3010, 3020, 3030, 3040, 3050, // PC
-                4000, 4010, 4020, 4030, 4050, // TV
-                5000, 5010, 5020, 5030, 5040, 5045, 5050, 5060, 5070, 5080, 5090, // XXX
-                6000, 6010, 6020, 6030, 6040, 6045, 6050, 6080, 6090, // Books
-                7000, 7010, 7020, 7030, 7040, 7050, // Other
-                8000, 8010, 8020,
-            ];
+            // Return the definition's modes and categories, minus the search
+            // types turned off for it. Definitions without them get the basics.
+            let config = state.config.read().await;
+            let mut caps = if definition.caps.modes.is_empty() {
+                crate::indexer::SearchCapabilities::basic()
+            } else {
+                crate::indexer::SearchCapabilities::from_modes(&definition.caps.modes)
+            };
+            for search_type in config.disabled_search_types(&definition.id) {
+                caps.disable(search_type);
+            }
+            let custom = if categories::tracker_categories_enabled(
+                config.native_settings.get(&definition.id),
+            ) {
+                categories::custom_categories(&definition)
+            } else {
+                Vec::new()
+            };
+            let mut categories = definition.extract_categories();
+            if categories.is_empty() {
+                categories = STANDARD_CATEGORIES.to_vec();
+            }
 
             (
                 StatusCode::OK,
                 [("Content-Type", "application/xml")],
-                crate::torznab::generate_caps_xml(&definition.name, &categories, &caps),
+                crate::torznab::generate_caps_xml(&definition.name, &categories, &custom, &caps),
             )
                 .into_response()
         }
         "search" | "tvsearch" | "movie" | "music" | "book" => {
-            // Build search query
-            let query = SearchQuery {
-                search_type: SearchType::from_param(action).unwrap_or_default(),
-                query: params.q,
-                categories: params
-                    .cat
-                    .map(|c| c.split(',').filter_map(|s| s.parse().ok()).collect())
-                    .unwrap_or_default(),
-                limit: params.limit,
-                offset: params.offset,
-                season: params.season,
-                episode: params.ep,
-                imdb_id: params.imdbid,
-                tvdb_id: params.tvdbid,
-                tmdb_id: params.tmdbid,
-                year: params.year,
-                genre: params.genre,
-                album: params.album,
-                artist: params.artist,
-                title: params.title,
-                author: params.author,
-                ..Default::default()
+            let sports = match params.sports_filter(action) {
+                Ok(sports) => sports,
+                Err(e) => {
+                    return (
+                        StatusCode::BAD_REQUEST,
+                        [("Content-Type", "application/xml")],
+                        crate::torznab::generate_error_xml(201, &e),
+                    )
+                        .into_response();
+                }
+            };
+            // Recurring *arr RSS polls may have been prefetched just before they arrive
+            let prefetched = if super::rss_prefetch::is_rss(&params) {
+                super::rss_prefetch::record_poll(&definition.id, action, &params);
+                super::rss_prefetch::take(&*state.store, &definition.id, action, &params).await
+            } else {
+                None
             };
+            let mut result = match prefetched {
+                Some(results) => Ok(results),
+                None => search_native_indexer(&state, &definition, action, &params).await,
+            };
+            let (magnets, sort, api_key) = {
+                let config = state.config.read().await;
+                (
+                    config.magnet_preference,
+                    config.sort.with(params.sort, params.order),
+                    config.server.auth.api_key.clone(),
+                )
+            };
+            if let Ok(results) = &mut result {
+                super::music::match_music(&state, action, &params, results).await;
+                if let Some(sports) = &sports {
+                    crate::sports::apply(results, sports);
+                }
+                crate::search::apply_max_age(results, params.maxage);
+                sort.apply(results, params.q.as_deref().unwrap_or_default());
+            }
 
-            // Execute search with proxy support
-            let config = state.config.read().await;
-            let settings = config.native_settings.get(&definition.id).cloned();
-            let executor = SearchExecutor::new(config.proxy_url.as_deref())
-                .unwrap_or_else(|_| SearchExecutor::new(None).expect("Failed to create executor"));
-            match executor
-                .search(&definition, &query, settings.as_ref())
-                .await
-            {
+            match result {
                 Ok(results) => (
                     StatusCode::OK,
                     [("Content-Type", "application/xml")],
@@ -430,15 +581,52 @@ pub(super) async fn torznab_api(
                         &definition.name,
                         Some(&proxy_base_url),
                         Some(&definition.id),
+                        api_key.as_deref(),
+                        magnets,
                     ),
                 )
                     .into_response(),
+                Err(e) if crate::indexer::challenge::protection_of(&e).is_some() => {
+                    tracing::warn!("Torznab search blocked for {}: {}", definition.id, e);
+                    (
+                        StatusCode::OK,
+                        [("Content-Type", "application/xml")],
+                        crate::torznab::generate_error_xml(900, &e.to_string()),
+                    )
+                        .into_response()
+                }
+                // Torznab 100: lets the *arr flag the indexer as needing credentials
+                Err(e) if crate::indexer::error::kind_of(&e) == ErrorKind::ProxyDown => {
+                    tracing::warn!("Torznab search refused for {}: {}", definition.id, e);
+                    (
+                        StatusCode::OK,
+                        [("Content-Type", "application/xml")],
+                        crate::torznab::generate_error_xml(900, &e.to_string()),
+                    )
+                        .into_response()
+                }
+                Err(e) if crate::indexer::error::kind_of(&e) == ErrorKind::Login => {
+                    tracing::warn!("Torznab search needs login for {}: {}", definition.id, e);
+                    (
+                        StatusCode::OK,
+                        [("Content-Type", "application/xml")],
+                        crate::torznab::generate_error_xml(100, &e.to_string()),
+                    )
+                        .into_response()
+                }
                 Err(e) => {
                     tracing::error!("Torznab search failed for {}: {}", definition.id, e);
                     (
                         StatusCode::OK, // Return OK with empty results on error for Torznab stability
                         [("Content-Type", "application/xml")],
-                        crate::torznab::generate_results_xml(&[], &definition.name, None, None),
+                        crate::torznab::generate_results_xml(
+                            &[],
+                            &definition.name,
+                            None,
+                            None,
+                            None,
+                            magnets,
+                        ),
                     )
                         .into_response()
                 }
@@ -453,179 +641,517 @@ pub(super) async fn torznab_api(
     }
 }
 
-/// Handle Torznab API for "all" aggregate indexer
+/// Most results a Torznab response holds, whatever `limit` the client asks for
+const TORZNAB_MAX_RESULTS: u32 = 1000;
+
+/// Categories in the caps of aggregate indexers, and of definitions that
+/// don't map any
+const STANDARD_CATEGORIES: &[i32] = &[
+    // Console
+    1000, 1010, 1020, 1030, 1040, 1050, 1080, 1090, // Movies
+    2000, 2010, 2020, 2030, 2040, 2045, 2050, 2060, 2070, 2080, 2090, // Audio
+    3000, 3010, 3020, 3030, 3040, 3050, // PC
+    4000, 4010, 4020, 4030, 4050, // TV
+    5000, 5010, 5020, 5030, 5040, 5045, 5050, 5060, 5070, 5080, 5090, // XXX
+    6000, 6010, 6020, 6030, 6040, 6045, 6050, 6080, 6090, // Books
+    7000, 7010, 7020, 7030, 7040, 7050, // Other
+    8000, 8010, 8020,
+];
+
+impl TorznabParams {
+    /// The same search with `limit` no higher than [`TORZNAB_MAX_RESULTS`]
+    fn with_capped_limit(mut self) -> Self {
+        self.limit = self.limit.map(|l| l.min(TORZNAB_MAX_RESULTS));
+        self
+    }
+
+    /// Native search query for a Torznab action
+    fn to_search_query(&self, action: &str) -> SearchQuery {
+        SearchQuery {
+            search_type: SearchType::from_param(action).unwrap_or_default(),
+            query: self.q.clone(),
+            categories: self
+                .cat
+                .as_ref()
+                .map(|c| c.split(',').filter_map(|s| s.parse().ok()).collect())
+                .unwrap_or_default(),
+            limit: self.limit,
+            offset: self.offset,
+            season: self.season,
+            episode: self.ep,
+            imdb_id: self.imdbid.clone(),
+            tvdb_id: self.tvdbid,
+            tmdb_id: self.tmdbid,
+            year: self.year,
+            genre: self.genre.clone(),
+            album: self.album.clone(),
+            artist: self.artist.clone(),
+            title: self.title.clone(),
+            author: self.author.clone(),
+            ..Default::default()
+        }
+    }
+
+    /// Everything that identifies a search except the indexer
+    pub(super) fn fingerprint(&self, action: &str) -> String {
+        format!(
+            "{}:{}:{}",
+            self.q.as_deref().unwrap_or(""),
+            action,
+            [
+                self.cat.clone(),
+                self.limit.map(|v| v.to_string()),
+                self.offset.map(|v| v.to_string()),
+                self.season.map(|v| v.to_string()),
+                self.ep.map(|v| v.to_string()),
+                self.imdbid.clone(),
+                self.tvdbid.map(|v| v.to_string()),
+                self.tmdbid.map(|v| v.to_string()),
+                self.year.map(|v| v.to_string()),
+                self.genre.clone(),
+                self.album.clone(),
+                self.artist.clone(),
+                self.title.clone(),
+                self.author.clone(),
+            ]
+            .map(Option::unwrap_or_default)
+            .join("|")
+        )
+    }
+
+    /// Event filter for sports searches (see [`crate::sports`])
+    pub(super) fn sports_filter(
+        &self,
+        action: &str,
+    ) -> Result<Option<crate::sports::SportsFilter>, String> {
+        if action != "search" {
+            return Ok(None);
+        }
+        crate::sports::SportsFilter::for_search(
+            self.cat.as_deref(),
+            self.from.as_deref(),
+            self.to.as_deref(),
+            self.league.as_deref(),
+        )
+    }
+}
+
+/// Search a single native indexer with its configured settings.
+/// Fails without a request while the indexer is rate limited.
+pub(super) async fn search_native_indexer(
+    state: &AppState,
+    definition: &crate::indexer::definition::IndexerDefinition,
+    action: &str,
+    params: &TorznabParams,
+) -> anyhow::Result<Vec<TorrentResult>> {
+    backoff::check(&definition.id)?;
+    let query = params.to_search_query(action);
+    let config = state.config.read().await;
+    if !config.search_type_enabled(&definition.id, query.search_type) {
+        tracing::debug!("{} has {} search turned off", definition.id, action);
+        return Ok(Vec::new());
+    }
+    let settings = config.native_settings.get(&definition.id).cloned();
+    let executor = SearchExecutor::for_indexer(
+        &definition.id,
+        config.proxy_url.as_deref(),
+        settings.as_ref(),
+    )
+    .unwrap_or_else(|_| SearchExecutor::new(None).expect("Failed to create executor"));
+    let cap = config.max_results_for(&definition.id);
+    drop(config);
+    let mut result = executor.search(definition, &query, settings.as_ref()).await;
+    match &mut result {
+        Ok(results) => {
+            results.truncate(cap);
+            register_guids(&state.db_pool, Some(&definition.id), results);
+            fill_first_seen(&state.db_pool, results)
+        }
+        Err(e) => backoff::note(&definition.id, e),
+    }
+    result
+}
+
+/// Keep results downloadable by GUID after the cache has expired
+pub(super) fn register_guids(
+    pool: &crate::db::DbPool,
+    indexer: Option<&str>,
+    results: &[TorrentResult],
+) {
+    if let Err(e) = crate::db::register_guids(pool, indexer, results) {
+        tracing::warn!("Failed to register result GUIDs: {}", e);
+    }
+}
+
+/// Date undated results by when they were first seen
+pub(super) fn fill_first_seen(pool: &crate::db::DbPool, results: &mut [TorrentResult]) {
+    if let Err(e) = crate::db::fill_first_seen(pool, results) {
+        tracing::warn!("Failed to record first-seen dates: {}", e);
+    }
+}
+
+/// How long an indexer's share of an aggregate search is reused. Short, so
+/// it covers client retries without serving stale RSS polls.
+const PARTIAL_TTL_MINUTES: i64 = 5;
+
+/// Cache key for one indexer's share of an aggregate search.
+///
+/// Each indexer's results are cached as soon as they arrive, so if the client
+/// gives up on a slow "all" search, a retry only re-queries the stragglers.
+fn partial_cache_key(indexer: &str, action: &str, params: &TorznabParams) -> String {
+    format!("partial:{}:{}", indexer, params.fingerprint(action))
+}
+
+/// Results an indexer already returned for this search (see [`partial_cache_key`])
+async fn cached_partial(store: &dyn Store, key: &str) -> Option<Vec<TorrentResult>> {
+    let cached = store.get_cached(key).await.ok()??;
+    serde_json::from_str(&cached).ok()
+}
+
+async fn store_partial(store: &dyn Store, key: &str, results: &[TorrentResult]) {
+    if let Ok(serialized) = serde_json::to_string(results)
+        && let Err(e) = store
+            .set_cached(
+                key,
+                &serialized,
+                chrono::Duration::minutes(PARTIAL_TTL_MINUTES),
+            )
+            .await
+    {
+        tracing::debug!("Failed to cache partial results for {}: {}", key, e);
+    }
+}
+
+/// Search every enabled native and proxied indexer in `selection` in parallel.
+///
+/// Failed indexers are logged and skipped. Results are sorted as `params`
+/// or the configured `[sort]` ask.
+/// Indexers that answered this search recently are served from the cache.
+pub(super) async fn search_all_indexers(
+    state: &AppState,
+    action: &str,
+    params: &TorznabParams,
+    selection: &IndexerSelection,
+) -> Vec<TorrentResult> {
+    let config = state.config.read().await;
+    let manager = state.native_indexers.read().await;
+    let sort = config.sort.with(params.sort, params.order);
+    let failing = crate::indexer::failing::failing(&state.db_pool);
+
+    // Build search query for native indexers
+    let query = params.to_search_query(action);
+
+    // Build search params for proxied indexers
+    let search_params = SearchParams {
+        query: params.q.clone().unwrap_or_default(),
+        search_type: action.to_string(),
+        cat: params.cat.clone(),
+        season: params.season,
+        ep: params.ep,
+        imdbid: params.imdbid.clone(),
+        tmdbid: params.tmdbid,
+        tvdbid: params.tvdbid,
+        year: params.year,
+        limit: params.limit,
+        maxage: params.maxage,
+        ..Default::default()
+    };
+
+    // Collect all search futures
+    let mut futures: Vec<
+        std::pin::Pin<Box<dyn std::future::Future<Output = Vec<TorrentResult>> + Send>>,
+    > = Vec::new();
+
+    // Native indexers
+    let definitions = manager.list_all_definitions().await;
+    for def in definitions {
+        // Check if native indexer is enabled, and for this kind of search
+        if !selection.includes(&def.id)
+            || !config.is_enabled(&def.id)
+            || !config.search_type_enabled(&def.id, query.search_type)
+        {
+            continue;
+        }
+
+        let settings = config.native_settings.get(&def.id).cloned();
+        let executor = match SearchExecutor::for_indexer(
+            &def.id,
+            config.proxy_url.as_deref(),
+            settings.as_ref(),
+        ) {
+            Ok(e) => e,
+            Err(_) => continue,
+        };
+        let q = query.clone();
+        let indexer_id = def.id.clone();
+        let key = partial_cache_key(&indexer_id, action, params);
+        if let Some(results) = cached_partial(&*state.store, &key).await {
+            futures.push(Box::pin(std::future::ready(results)));
+            continue;
+        }
+        if let Some(left) = backoff::remaining(&indexer_id) {
+            tracing::debug!(
+                "Skipping {} (rate limited for {}s)",
+                indexer_id,
+                left.as_secs()
+            );
+            continue;
+        }
+        if let Some(until) = failing.get(&indexer_id) {
+            tracing::debug!("Skipping {} (failing until {})", indexer_id, until);
+            continue;
+        }
+        let pool = state.db_pool.clone();
+        let store = state.store.clone();
+        let search_config = config.search.clone();
+        let cap = config.max_results_for(&indexer_id);
+
+        futures.push(Box::pin(async move {
+            // Shared with identical aggregate searches running at the same time
+            super::single_flight::run(&store, &key, async {
+                let search = executor.search(&def, &q, settings.as_ref());
+                match super::latency::timed(&pool, &search_config, &indexer_id, search).await {
+                    Ok(mut results) => {
+                        results.truncate(cap);
+                        for r in &mut results {
+                            r.indexer = Some(indexer_id.clone());
+                        }
+                        store_partial(&*store, &key, &results).await;
+                        results
+                    }
+                    Err(e) => {
+                        tracing::warn!("Native indexer {} search failed: {}", indexer_id, e);
+                        vec![]
+                    }
+                }
+            })
+            .await
+        }));
+    }
+
+    // Proxied indexers
+    for idx in &config.indexers {
+        if !selection.includes(&idx.name) || !config.is_enabled(&idx.name) {
+            continue;
+        }
+
+        let client = match TorznabClient::new(
+            &idx.url,
+            idx.apikey.as_deref(),
+            config.proxy_url.as_deref(),
+        ) {
+            Ok(c) => c,
+            Err(_) => continue,
+        };
+
+        let p = search_params.clone();
+        let indexer_name = idx.name.clone();
+        let key = partial_cache_key(&indexer_name, action, params);
+        if let Some(results) = cached_partial(&*state.store, &key).await {
+            futures.push(Box::pin(std::future::ready(results)));
+            continue;
+        }
+        if let Some(left) = backoff::remaining(&indexer_name) {
+            tracing::debug!(
+                "Skipping {} (rate limited for {}s)",
+                indexer_name,
+                left.as_secs()
+            );
+            continue;
+        }
+        if let Some(until) = failing.get(&indexer_name) {
+            tracing::debug!("Skipping {} (failing until {})", indexer_name, until);
+            continue;
+        }
+        let pool = state.db_pool.clone();
+        let store = state.store.clone();
+        let search_config = config.search.clone();
+        let cap = config.max_results_for(&indexer_name);
+
+        futures.push(Box::pin(async move {
+            // Shared with identical aggregate searches running at the same time
+            super::single_flight::run(&store, &key, async {
+                match super::latency::timed(&pool, &search_config, &indexer_name, client.search(&p))
+                    .await
+                {
+                    Ok(mut results) => {
+                        results.truncate(cap);
+                        for r in &mut results {
+                            r.indexer = Some(indexer_name.clone());
+                        }
+                        store_partial(&*store, &key, &results).await;
+                        results
+                    }
+                    Err(e) => {
+                        tracing::warn!("Proxied indexer {} search failed: {}", indexer_name, e);
+                        vec![]
+                    }
+                }
+            })
+            .await
+        }));
+    }
+
+    // Drop locks before awaiting
+    drop(config);
+    drop(manager);
+
+    // Execute all searches in parallel
+    let results_lists: Vec<Vec<TorrentResult>> = futures::future::join_all(futures).await;
+
+    // Aggregate results
+    let found: Vec<TorrentResult> = results_lists.into_iter().flatten().collect();
+    register_guids(&state.db_pool, None, &found);
+    let mut all_results = crate::dedup::merge_duplicates(found);
+    fill_first_seen(&state.db_pool, &mut all_results);
+    super::library::flag(&state.db_pool, &mut all_results);
+    sort.apply(&mut all_results, params.q.as_deref().unwrap_or_default());
+    all_results
+}
+
+#[derive(Serialize)]
+pub(super) struct BestMatchResponse {
+    result: Option<TorrentResult>,
+    score: Option<i64>,
+    candidates: usize,
+}
+
+/// Search all indexers and return the single best match for the configured scoring profile.
+///
+/// Accepts the same query parameters as the Torznab API (`t`, `q`, `cat`, `season`, ...).
+pub(super) async fn best_match_api(
+    State(state): State<AppState>,
+    Query(params): Query<TorznabParams>,
+) -> impl IntoResponse {
+    let action = params.t.clone().unwrap_or_else(|| "search".to_string());
+    if SearchType::from_param(&action).is_none() {
+        return (
+            StatusCode::BAD_REQUEST,
+            format!("Unknown search type: {}", action),
+        )
+            .into_response();
+    }
+
+    let mut results = search_all_indexers(&state, &action, &params, &IndexerSelection::All).await;
+    crate::search::apply_max_age(&mut results, params.maxage);
+    let profile = state.config.read().await.scoring.clone();
+    let want_season_pack = params.season.is_some() && params.ep.is_none();
+    let best = crate::scoring::pick_best(&results, &profile, want_season_pack);
+
+    Json(BestMatchResponse {
+        result: best.map(|(r, _)| r.clone()),
+        score: best.map(|(_, s)| s),
+        candidates: results.len(),
+    })
+    .into_response()
+}
+
+/// Handle Torznab API for an aggregate indexer: "all", or a `tag-<group>`
+/// searching only the group's indexers
 async fn torznab_all_indexers(
     state: AppState,
-    params: TorznabParams,
+    mut params: TorznabParams,
     proxy_base_url: &str,
+    id: &str,
+    selection: IndexerSelection,
 ) -> axum::response::Response {
     let action = params.t.as_deref().unwrap_or("search");
+    let title = match id.strip_prefix("tag-") {
+        Some(group) => format!("Tag: {}", group),
+        None => "All Indexers".to_string(),
+    };
 
     match action {
         "caps" => {
-            // Return aggregate capabilities
+            // Return aggregate capabilities. Tracker categories are left out:
+            // each indexer gives the same custom ids its own meaning.
             let caps = crate::indexer::SearchCapabilities::basic();
-            let categories = vec![
-                // Console
-                1000, 1010, 1020, 1030, 1040, 1050, 1080, 1090, // Movies
-                2000, 2010, 2020, 2030, 2040, 2045, 2050, 2060, 2070, 2080, 2090, // Audio
-                3000, 3010, 3020, 3030, 3040, 3050, // PC
-                4000, 4010, 4020, 4030, 4050, // TV
-                5000, 5010, 5020, 5030, 5040, 5045, 5050, 5060, 5070, 5080, 5090, // XXX
-                6000, 6010, 6020, 6030, 6040, 6045, 6050, 6080, 6090, // Books
-                7000, 7010, 7020, 7030, 7040, 7050, // Other
-                8000, 8010, 8020,
-            ];
 
             (
                 StatusCode::OK,
                 [("Content-Type", "application/xml")],
-                crate::torznab::generate_caps_xml("All Indexers", &categories, &caps),
+                crate::torznab::generate_caps_xml(&title, STANDARD_CATEGORIES, &[], &caps),
             )
                 .into_response()
         }
         "search" | "tvsearch" | "movie" | "music" | "book" => {
-            let config = state.config.read().await;
-            let manager = state.native_indexers.read().await;
-
-            // Build search query for native indexers
-            let query = SearchQuery {
-                search_type: SearchType::from_param(action).unwrap_or_default(),
-                query: params.q.clone(),
-                categories: params
-                    .cat
-                    .as_ref()
-                    .map(|c| c.split(',').filter_map(|s| s.parse().ok()).collect())
-                    .unwrap_or_default(),
-                limit: params.limit,
-                offset: params.offset,
-                season: params.season,
-                episode: params.ep,
-                imdb_id: params.imdbid.clone(),
-                tvdb_id: params.tvdbid,
-                tmdb_id: params.tmdbid,
-                year: params.year,
-                genre: params.genre.clone(),
-                album: params.album.clone(),
-                artist: params.artist.clone(),
-                title: params.title.clone(),
-                author: params.author.clone(),
-                ..Default::default()
-            };
-
-            // Build search params for proxied indexers
-            let search_params = SearchParams {
-                query: params.q.clone().unwrap_or_default(),
-                search_type: action.to_string(),
-                cat: params.cat.clone(),
-                season: params.season,
-                ep: params.ep,
-                imdbid: params.imdbid.clone(),
-                tmdbid: params.tmdbid,
-                tvdbid: params.tvdbid,
-                year: params.year,
-                limit: params.limit,
-                ..Default::default()
+            let sports = match params.sports_filter(action) {
+                Ok(sports) => sports,
+                Err(e) => {
+                    return (
+                        StatusCode::BAD_REQUEST,
+                        [("Content-Type", "application/xml")],
+                        crate::torznab::generate_error_xml(201, &e),
+                    )
+                        .into_response();
+                }
             };
-
+            // A custom category can't be searched across indexers
+            let (cat, custom) = categories::without_custom(params.cat.as_deref());
+            if custom && cat.is_none() {
+                return (
+                    StatusCode::OK,
+                    [("Content-Type", "application/xml")],
+                    crate::torznab::generate_results_xml(
+                        &[],
+                        &title,
+                        None,
+                        None,
+                        None,
+                        Default::default(),
+                    ),
+                )
+                    .into_response();
+            }
+            params.cat = cat;
             let proxy_base = proxy_base_url.to_string();
-
-            // Collect all search futures
-            let mut futures: Vec<
-                std::pin::Pin<Box<dyn std::future::Future<Output = Vec<TorrentResult>> + Send>>,
-            > = Vec::new();
-
-            // Native indexers
-            let definitions = manager.list_all_definitions().await;
-            for def in definitions {
-                // Check if native indexer is enabled
-                if !config.is_enabled(&def.id) {
-                    continue;
-                }
-
-                let settings = config.native_settings.get(&def.id).cloned();
-                let executor = match SearchExecutor::new(config.proxy_url.as_deref()) {
-                    Ok(e) => e,
-                    Err(_) => continue,
-                };
-                let q = query.clone();
-                let indexer_id = def.id.clone();
-
-                futures.push(Box::pin(async move {
-                    match executor.search(&def, &q, settings.as_ref()).await {
-                        Ok(mut results) => {
-                            for r in &mut results {
-                                r.indexer = Some(indexer_id.clone());
-                            }
-                            results
-                        }
-                        Err(e) => {
-                            tracing::warn!("Native indexer {} search failed: {}", indexer_id, e);
-                            vec![]
-                        }
-                    }
-                }));
+            // Fail as a whole rather than with every indexer silently empty
+            let proxy_url = state.config.read().await.proxy_url.clone();
+            if let Err(e) = crate::http::ensure_proxy(proxy_url.as_deref()).await {
+                return (
+                    StatusCode::OK,
+                    [("Content-Type", "application/xml")],
+                    crate::torznab::generate_error_xml(900, &e.to_string()),
+                )
+                    .into_response();
             }
+            let prefetched = if super::rss_prefetch::is_rss(&params) {
+                super::rss_prefetch::record_poll(id, action, &params);
+                super::rss_prefetch::take(&*state.store, id, action, &params).await
+            } else {
+                None
+            };
+            let mut all_results = match prefetched {
+                Some(results) => results,
+                None => search_all_indexers(&state, action, &params, &selection).await,
+            };
 
-            // Proxied indexers
-            for idx in &config.indexers {
-                if !config.is_enabled(&idx.name) {
-                    continue;
-                }
-
-                let client = match TorznabClient::new(
-                    &idx.url,
-                    idx.apikey.as_deref(),
-                    config.proxy_url.as_deref(),
-                ) {
-                    Ok(c) => c,
-                    Err(_) => continue,
-                };
-
-                let p = search_params.clone();
-                let indexer_name = idx.name.clone();
-
-                futures.push(Box::pin(async move {
-                    match client.search(&p).await {
-                        Ok(mut results) => {
-                            for r in &mut results {
-                                r.indexer = Some(indexer_name.clone());
-                            }
-                            results
-                        }
-                        Err(e) => {
-                            tracing::warn!("Proxied indexer {} search failed: {}", indexer_name, e);
-                            vec![]
-                        }
-                    }
-                }));
+            if let Some(sports) = &sports {
+                crate::sports::apply(&mut all_results, sports);
             }
-
-            // Drop locks before awaiting
-            drop(config);
-            drop(manager);
-
-            // Execute all searches in parallel
-            let results_lists: Vec<Vec<TorrentResult>> = futures::future::join_all(futures).await;
-
-            // Aggregate results
-            let mut all_results: Vec<TorrentResult> = results_lists.into_iter().flatten().collect();
-
-            // Sort by seeders (descending)
-            all_results.sort_by(|a, b| b.seeders.unwrap_or(0).cmp(&a.seeders.unwrap_or(0)));
+            crate::search::apply_max_age(&mut all_results, params.maxage);
+            categories::strip_custom(&mut all_results);
 
             // Limit results
             let limit = params.limit.unwrap_or(100) as usize;
             all_results.truncate(limit);
+            super::music::match_music(&state, action, &params, &mut all_results).await;
+            let (magnets, api_key) = {
+                let config = state.config.read().await;
+                (config.magnet_preference, config.server.auth.api_key.clone())
+            };
 
             (
                 StatusCode::OK,
                 [("Content-Type", "application/xml")],
+                // Downloads go through "all", which finds the result's own indexer
                 crate::torznab::generate_results_xml(
                     &all_results,
-                    "All Indexers",
+                    &title,
                     Some(&proxy_base),
                     Some("all"),
+                    api_key.as_deref(),
+                    magnets,
                 ),
             )
                 .into_response()
@@ -641,7 +1167,156 @@ async fn torznab_all_indexers(
 
 #[cfg(test)]
 mod tests {
-    use crate::torznab::TorrentResult;
+    use super::*;
+    use crate::config::{Config, IndexerConfig, MagnetPreference};
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// Torznab server with one result per indexer at `/{indexer}/api`,
+    /// counting the searches it answers
+    async fn torznab_server() -> (String, Arc<AtomicUsize>) {
+        let searches = Arc::new(AtomicUsize::new(0));
+        let counter = searches.clone();
+        let app = axum::Router::new().fallback(move |uri: axum::http::Uri| {
+            let counter = counter.clone();
+            async move {
+                counter.fetch_add(1, Ordering::SeqCst);
+                let indexer = uri.path().split('/').nth(1).unwrap_or_default().to_string();
+                format!(
+                    "<rss><channel><item><title>{0} result</title><guid>{0}</guid>\
+                     <link>http://t.example/{0}.torrent</link></item></channel></rss>",
+                    indexer
+                )
+            }
+        });
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await });
+        (format!("http://{}", addr), searches)
+    }
+
+    /// Config with proxied indexers of the given names on `base`
+    fn proxied(base: &str, names: &[&str]) -> Config {
+        Config {
+            indexers: names
+                .iter()
+                .map(|name| IndexerConfig {
+                    name: name.to_string(),
+                    url: format!("{}/{}/api", base, name),
+                    apikey: None,
+                })
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    fn indexers_of(results: &[TorrentResult]) -> Vec<&str> {
+        let mut indexers: Vec<_> = results
+            .iter()
+            .filter_map(|r| r.indexer.as_deref())
+            .collect();
+        indexers.sort();
+        indexers
+    }
+
+    #[tokio::test]
+    async fn test_tag_indexer_searches_group() {
+        let (base, _) = torznab_server().await;
+        let mut config = proxied(&base, &["tag-test-a", "tag-test-b", "tag-test-c"]);
+        config.indexer_groups.insert(
+            "public".to_string(),
+            vec!["tag-test-a".to_string(), "tag-test-c".to_string()],
+        );
+        let state = AppState::for_tests(config.clone(), "tag-indexer");
+        let params = TorznabParams {
+            q: Some("ubuntu".to_string()),
+            ..Default::default()
+        };
+
+        let selection = config.aggregate_indexers("tag-public").unwrap();
+        let results = search_all_indexers(&state, "search", &params, &selection).await;
+        assert_eq!(indexers_of(&results), vec!["tag-test-a", "tag-test-c"]);
+
+        let selection = config.aggregate_indexers("all").unwrap();
+        let results = search_all_indexers(&state, "search", &params, &selection).await;
+        assert_eq!(
+            indexers_of(&results),
+            vec!["tag-test-a", "tag-test-b", "tag-test-c"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_search_refresh() {
+        let (base, searches) = torznab_server().await;
+        let state = AppState::for_tests(proxied(&base, &["refresh-test"]), "search-refresh");
+        let search = |query: &'static str| {
+            let state = state.clone();
+            async move {
+                let uri: axum::http::Uri = format!("/api/search?{}", query).parse().unwrap();
+                let response = search_api(State(state), Query::try_from_uri(&uri).unwrap())
+                    .await
+                    .into_response();
+                let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+                    .await
+                    .unwrap();
+                serde_json::from_slice::<Vec<TorrentResult>>(&body).unwrap()
+            }
+        };
+
+        let first = search("q=ubuntu").await;
+        assert_eq!(first.len(), 1);
+        assert!(!first[0].cached);
+        assert!(first[0].fetched_at.is_some());
+
+        // Served from the cache, keeping the original fetch time
+        let cached = search("q=ubuntu").await;
+        assert!(cached[0].cached);
+        assert_eq!(cached[0].fetched_at, first[0].fetched_at);
+        assert_eq!(searches.load(Ordering::SeqCst), 1);
+
+        // A refresh searches again and replaces the cached results
+        let refreshed = search("q=ubuntu&refresh=true").await;
+        assert!(!refreshed[0].cached);
+        assert!(refreshed[0].fetched_at > first[0].fetched_at);
+        assert_eq!(searches.load(Ordering::SeqCst), 2);
+        let cached = search("q=ubuntu").await;
+        assert!(cached[0].cached);
+        assert_eq!(cached[0].fetched_at, refreshed[0].fetched_at);
+    }
+
+    #[test]
+    fn test_partial_cache_key() {
+        let params = TorznabParams {
+            q: Some("ubuntu".to_string()),
+            season: Some(1),
+            ..Default::default()
+        };
+        let key = partial_cache_key("yts", "tvsearch", &params);
+        assert!(key.starts_with("partial:yts:ubuntu:"));
+        assert_ne!(key, partial_cache_key("eztv", "tvsearch", &params));
+        assert_ne!(key, partial_cache_key("yts", "search", &params));
+
+        let other_season = TorznabParams {
+            season: Some(2),
+            ..params
+        };
+        assert_ne!(key, partial_cache_key("yts", "tvsearch", &other_season));
+    }
+
+    #[test]
+    fn test_capped_limit() {
+        let greedy = TorznabParams {
+            limit: Some(5000),
+            ..Default::default()
+        };
+        assert_eq!(greedy.with_capped_limit().limit, Some(1000));
+        let modest = TorznabParams {
+            limit: Some(50),
+            ..Default::default()
+        };
+        assert_eq!(modest.with_capped_limit().limit, Some(50));
+        assert_eq!(TorznabParams::default().with_capped_limit().limit, None);
+    }
 
     #[test]
     fn test_all_indexers_caps_xml() {
@@ -649,7 +1324,8 @@ mod tests {
         let caps = crate::indexer::SearchCapabilities::basic();
         let categories = vec![2000, 5000]; // Movies and TV
 
-        let xml = crate::torznab::generate_caps_xml("All Indexers", &categories, &caps);
+        let custom = [(100_042, "Anime & Cartoons".to_string())];
+        let xml = crate::torznab::generate_caps_xml("All Indexers", &categories, &custom, &caps);
 
         assert!(xml.contains("Lodestarr - All Indexers"));
         assert!(xml.contains("<search available=\"yes\""));
@@ -657,6 +1333,46 @@ mod tests {
         assert!(xml.contains("<movie-search available=\"yes\""));
         assert!(xml.contains("category id=\"2000\""));
         assert!(xml.contains("category id=\"5000\""));
+        assert!(xml.contains("<category id=\"100042\" name=\"Anime &amp; Cartoons\" />"));
+    }
+
+    #[test]
+    fn test_definition_caps_xml() {
+        let definition = crate::indexer::definition::IndexerDefinition::from_yaml(
+            r#"
+id: shows
+name: Shows
+links: ["https://tracker.example/"]
+caps:
+  categories:
+    7: TV/Anime
+  categorymappings:
+    - {id: 2, cat: TV/HD}
+    - {id: 3, cat: Audio/MP3}
+  modes:
+    search: [q]
+    tv-search: [q, season, ep, tvdbid]
+search:
+  paths: [{path: search}]
+  rows: {selector: tr}
+  fields: {title: {selector: a}}
+"#,
+        )
+        .unwrap();
+        let caps = crate::indexer::SearchCapabilities::from_modes(&definition.caps.modes);
+        let categories = definition.extract_categories();
+        assert_eq!(categories, [3010, 5040, 5070]);
+        let xml = crate::torznab::generate_caps_xml("Shows", &categories, &[], &caps);
+
+        assert!(
+            xml.contains("<tv-search available=\"yes\" supportedParams=\"q,season,ep,tvdbid\" />")
+        );
+        assert!(!xml.contains("<movie-search"));
+        // Parents are listed for mapped subcategories only
+        assert!(xml.contains("<category id=\"5000\" name=\"TV\">"));
+        assert!(xml.contains("<subcat id=\"5070\" name=\"TV/Anime\" />"));
+        assert!(xml.contains("<category id=\"3000\" name=\"Audio\">"));
+        assert!(!xml.contains("id=\"2000\""));
     }
 
     #[test]
@@ -684,6 +1400,8 @@ mod tests {
             "All Indexers",
             Some("http://localhost:3420"),
             Some("all"),
+            None,
+            MagnetPreference::default(),
         );
 
         assert!(xml.contains("<title>All Indexers</title>"));
@@ -696,7 +1414,7 @@ mod tests {
     #[test]
     fn test_all_indexers_results_sorted_by_seeders() {
         // Test that results are sorted by seeders (highest first)
-        let mut results = vec![
+        let mut results = [
             TorrentResult {
                 title: "Low seeders".to_string(),
                 seeders: Some(10),
@@ -720,7 +1438,7 @@ mod tests {
         ];
 
         // Sort using the same logic as torznab_all_indexers
-        results.sort_by(|a, b| b.seeders.unwrap_or(0).cmp(&a.seeders.unwrap_or(0)));
+        results.sort_by_key(|r| std::cmp::Reverse(r.seeders.unwrap_or(0)));
 
         assert_eq!(results[0].title, "High seeders");
         assert_eq!(results[1].title, "Medium seeders");
@@ -743,10 +1461,24 @@ mod tests {
             "All Indexers",
             Some("http://localhost:3420"),
             Some("all"),
+            None,
+            MagnetPreference::default(),
         );
 
         // Download URL should be proxied through the /all indexer
         assert!(xml.contains("/api/v2.0/indexers/all/dl?link="));
+        assert!(!xml.contains("apikey="));
+
+        // With auth on, grabs carry the key
+        let xml = crate::torznab::generate_results_xml(
+            &results,
+            "All Indexers",
+            Some("http://localhost:3420"),
+            Some("all"),
+            Some("s3cret&"),
+            MagnetPreference::default(),
+        );
+        assert!(xml.contains("&amp;guid=guid&amp;apikey=s3cret%26</link>"));
     }
 
     #[test]
@@ -764,10 +1496,52 @@ mod tests {
             "All Indexers",
             Some("http://localhost:3420"),
             Some("all"),
+            None,
+            MagnetPreference::default(),
         );
 
         // Magnet URL should NOT be proxied
         assert!(xml.contains("magnet:?xt=urn:btih:abc123"));
         assert!(!xml.contains("/api/v2.0/indexers/all/dl?link=bWFnbmV0"));
     }
+
+    #[test]
+    fn test_magnet_preference() {
+        let results = vec![TorrentResult {
+            title: "Test".to_string(),
+            guid: "guid".to_string(),
+            link: Some("https://example.com/download/123".to_string()),
+            magnet: Some("magnet:?xt=urn:btih:ABC123&dn=x".to_string()),
+            ..Default::default()
+        }];
+        let xml = |magnets| {
+            crate::torznab::generate_results_xml(
+                &results,
+                "Test",
+                Some("http://localhost:3420"),
+                Some("test"),
+                None,
+                magnets,
+            )
+        };
+        let magneturl = "name=\"magneturl\"";
+
+        let magnet_first = xml(MagnetPreference::MagnetFirst);
+        assert!(magnet_first.contains("<link>magnet:?xt=urn:btih:ABC123&amp;dn=x</link>"));
+        assert!(magnet_first.contains("<enclosure url=\"magnet:"));
+        assert!(magnet_first.contains(magneturl));
+        assert!(!magnet_first.contains("/dl?link="));
+        // Taken from the magnet when the result has none
+        assert!(magnet_first.contains("name=\"infohash\" value=\"abc123\""));
+
+        let link_first = xml(MagnetPreference::LinkFirst);
+        assert!(link_first.contains("<link>http://localhost:3420/api/v2.0/indexers/test/dl?link="));
+        assert!(link_first.contains("<enclosure url=\"http://localhost:3420/"));
+        assert!(!link_first.contains("magnet:"));
+        assert!(link_first.contains("name=\"infohash\""));
+
+        let both = xml(MagnetPreference::Both);
+        assert!(both.contains("<enclosure url=\"http://localhost:3420/"));
+        assert!(both.contains(magneturl));
+    }
 }
@@ -1,7 +1,8 @@
 //! Info and statistics API endpoints
 
 use super::AppState;
-use axum::{Json, extract::State, response::IntoResponse};
+use super::auth::AuthUser;
+use axum::{Extension, Json, extract::State, response::IntoResponse};
 use chrono::{DateTime, Utc};
 use serde::Serialize;
 use std::time::SystemTime;
@@ -25,16 +26,59 @@ pub(super) struct StatsResponse {
     total_searches: usize,
     avg_search_time_ms: f64,
     recent_searches: Vec<SearchLog>,
+    /// Per-tracker-domain request budget counters
+    domain_requests: Vec<crate::http::DomainMetrics>,
+    /// Failed indexer searches in the last 24h by kind
+    indexer_errors: Vec<crate::db::IndexerErrorStat>,
+    /// Indexers skipped until a tracker rate limit expires
+    indexer_cooldowns: Vec<crate::indexer::backoff::Cooldown>,
+    /// Indexers that sent the most bytes in the last 30 days
+    bandwidth: Vec<crate::db::IndexerTraffic>,
 }
 
-/// Get application info (name, version)
-pub(super) async fn api_info() -> Json<serde_json::Value> {
+/// Get application info (name, version, signed-in user)
+pub(super) async fn api_info(
+    State(state): State<AppState>,
+    user: Option<Extension<AuthUser>>,
+) -> Json<serde_json::Value> {
+    let config = state.config.read().await;
     Json(serde_json::json!({
         "name": "Lodestarr",
         "version": env!("CARGO_PKG_VERSION"),
+        "build": crate::build_info::current(),
+        "update": crate::update::available(),
+        "display": config.display,
+        "user": user.map(|Extension(AuthUser(name))| name),
+        "login": config.server.auth.login_enabled(),
     }))
 }
 
+/// Liveness check for container healthchecks (`lodestarr ping`).
+/// Fails with 503 when the database is unreachable. Rate-limited indexers
+/// are listed but don't make the server unhealthy.
+pub(super) async fn healthz(State(state): State<AppState>) -> impl IntoResponse {
+    health(&state.db_pool)
+}
+
+fn health(pool: &crate::db::DbPool) -> (axum::http::StatusCode, Json<serde_json::Value>) {
+    let db_ok = pool
+        .get()
+        .is_ok_and(|conn| conn.query_row("SELECT 1", [], |_| Ok(())).is_ok());
+    let status = if db_ok {
+        axum::http::StatusCode::OK
+    } else {
+        axum::http::StatusCode::SERVICE_UNAVAILABLE
+    };
+    (
+        status,
+        Json(serde_json::json!({
+            "status": if db_ok { "ok" } else { "database unavailable" },
+            "version": env!("CARGO_PKG_VERSION"),
+            "cooldowns": crate::indexer::backoff::active(),
+        })),
+    )
+}
+
 /// Get application statistics
 pub(super) async fn get_stats(State(state): State<AppState>) -> Json<StatsResponse> {
     let config = state.config.read().await;
@@ -63,10 +107,10 @@ pub(super) async fn get_stats(State(state): State<AppState>) -> Json<StatsRespon
         .unwrap_or_default()
         .as_secs();
 
-    let total_searches = crate::db::get_total_searches(&state.db_pool).unwrap_or(0);
-    let avg_search_time_ms = crate::db::get_avg_duration(&state.db_pool).unwrap_or(0.0);
+    let (total_searches, avg_search_time_ms) =
+        state.store.search_totals().await.unwrap_or((0, 0.0));
 
-    let recent_db = crate::db::get_recent_logs(&state.db_pool, 20).unwrap_or_default();
+    let recent_db = state.store.recent_searches(20).await.unwrap_or_default();
     let recent = recent_db
         .into_iter()
         .map(|l| SearchLog {
@@ -77,9 +121,12 @@ pub(super) async fn get_stats(State(state): State<AppState>) -> Json<StatsRespon
         })
         .collect();
 
+    let indexer_cooldowns = crate::indexer::backoff::active();
+
     Json(StatsResponse {
         indexers_loaded: indexers_proxied + indexers_native,
-        indexers_healthy: indexers_proxied + indexers_native,
+        indexers_healthy: (indexers_proxied + indexers_native)
+            .saturating_sub(indexer_cooldowns.len()),
         indexers_native,
         indexers_proxied,
         indexers_enabled,
@@ -87,6 +134,19 @@ pub(super) async fn get_stats(State(state): State<AppState>) -> Json<StatsRespon
         total_searches,
         avg_search_time_ms,
         recent_searches: recent,
+        domain_requests: crate::http::domain_metrics(),
+        indexer_errors: crate::db::get_indexer_errors(
+            &state.db_pool,
+            Utc::now() - chrono::Duration::hours(24),
+        )
+        .unwrap_or_default(),
+        indexer_cooldowns,
+        bandwidth: crate::db::get_top_traffic(
+            &state.db_pool,
+            Utc::now() - chrono::Duration::days(30),
+            10,
+        )
+        .unwrap_or_default(),
     })
 }
 
@@ -98,21 +158,41 @@ pub(super) struct CachedSearchResponse {
     pub indexer: String,
     pub expires_at: DateTime<Utc>,
     pub result_count: usize,
+    /// Pages that gave no results were kept (`/api/history/{key}/raw`)
+    pub has_raw: bool,
 }
 
-/// Get list of cached searches
+/// Get list of cached searches, followed by uncached searches that have
+/// stored raw responses (their `expires_at` is when those were stored)
 pub(super) async fn get_history(State(state): State<AppState>) -> Json<Vec<CachedSearchResponse>> {
-    let cached = crate::db::get_cached_search_list(&state.db_pool).unwrap_or_default();
-    let response = cached
+    let cached = state.store.cached_searches().await.unwrap_or_default();
+    let mut raw_keys = crate::db::get_raw_response_keys(&state.db_pool).unwrap_or_default();
+    let mut response: Vec<CachedSearchResponse> = cached
         .into_iter()
-        .map(|c| CachedSearchResponse {
-            cache_key: c.cache_key,
-            query: c.query,
-            indexer: c.indexer,
-            expires_at: c.expires_at,
-            result_count: c.result_count,
+        .map(|c| {
+            let has_raw = raw_keys.iter().any(|(key, _)| *key == c.cache_key);
+            CachedSearchResponse {
+                cache_key: c.cache_key,
+                query: c.query,
+                indexer: c.indexer,
+                expires_at: c.expires_at,
+                result_count: c.result_count,
+                has_raw,
+            }
         })
         .collect();
+    raw_keys.retain(|(key, _)| !response.iter().any(|r| r.cache_key == *key));
+    response.extend(raw_keys.into_iter().map(|(key, stored_at)| {
+        let (indexer, query) = crate::db::describe_cache_key(&key);
+        CachedSearchResponse {
+            cache_key: key,
+            query,
+            indexer,
+            expires_at: stored_at,
+            result_count: 0,
+            has_raw: true,
+        }
+    }));
     Json(response)
 }
 
@@ -124,7 +204,7 @@ pub(super) async fn get_history_results(
     // URL decode the key
     let decoded_key = urlencoding::decode(&key).unwrap_or(std::borrow::Cow::Borrowed(&key));
 
-    match crate::db::get_cached_results_by_key(&state.db_pool, &decoded_key) {
+    match state.store.get_cached(&decoded_key).await {
         Ok(Some(results_json)) => (
             axum::http::StatusCode::OK,
             [(axum::http::header::CONTENT_TYPE, "application/json")],
@@ -144,23 +224,106 @@ pub(super) async fn get_history_results(
     }
 }
 
+/// Pages kept for a search that gave no results (see `raw_responses`)
+pub(super) async fn get_history_raw(
+    State(state): State<AppState>,
+    axum::extract::Path(key): axum::extract::Path<String>,
+) -> impl axum::response::IntoResponse {
+    let decoded_key = urlencoding::decode(&key).unwrap_or(std::borrow::Cow::Borrowed(&key));
+
+    match crate::db::get_raw_responses(&state.db_pool, &decoded_key) {
+        Ok(responses) if responses.is_empty() => (
+            axum::http::StatusCode::NOT_FOUND,
+            "No raw responses stored for this search",
+        )
+            .into_response(),
+        Ok(responses) => Json(responses).into_response(),
+        Err(e) => (
+            axum::http::StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Error: {}", e),
+        )
+            .into_response(),
+    }
+}
+
+/// Space on a filesystem Lodestarr writes to
+#[derive(Serialize)]
+pub(super) struct DiskUsage {
+    /// `download` (the download path) or `data` (database and caches)
+    name: &'static str,
+    path: String,
+    free: Option<u64>,
+    total: Option<u64>,
+    writable: bool,
+}
+
+/// Free space of the download path and data directory
+pub(super) async fn get_disk_usage(State(state): State<AppState>) -> Json<Vec<DiskUsage>> {
+    let config = state.config.read().await;
+    let mut paths = Vec::new();
+    if let Some(path) = &config.download_path {
+        paths.push(("download", std::path::PathBuf::from(path)));
+    }
+    if let Some(dir) = config
+        .get_db_path()
+        .ok()
+        .and_then(|p| p.parent().map(|p| p.to_path_buf()))
+    {
+        paths.push(("data", dir));
+    }
+    drop(config);
+
+    let usage = paths
+        .into_iter()
+        .map(|(name, path)| {
+            let space = crate::utils::disk_space(&path);
+            DiskUsage {
+                name,
+                writable: crate::utils::check_writable(&path).is_ok(),
+                path: path.to_string_lossy().into_owned(),
+                free: space.map(|(free, _)| free),
+                total: space.map(|(_, total)| total),
+            }
+        })
+        .collect();
+    Json(usage)
+}
+
+/// Every indexer's search record, and whether aggregate searches skip it for
+/// failing (see `indexer::failing`)
+pub(super) async fn get_indexer_stats(State(state): State<AppState>) -> impl IntoResponse {
+    match crate::db::get_indexer_health(&state.db_pool) {
+        Ok(health) => Json(health).into_response(),
+        Err(e) => (
+            axum::http::StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Failed to read indexer stats: {}", e),
+        )
+            .into_response(),
+    }
+}
+
+/// Phase durations of each indexer's latest search since startup
+pub(super) async fn get_search_timings() -> Json<Vec<crate::indexer::timings::SearchTimings>> {
+    Json(crate::indexer::timings::latest())
+}
+
 /// Get download history
 pub(super) async fn get_downloads(
     State(state): State<AppState>,
 ) -> Json<Vec<crate::db::DownloadLog>> {
-    let downloads = crate::db::get_download_logs(&state.db_pool, 500).unwrap_or_default();
+    let downloads = state.store.download_logs(500).await.unwrap_or_default();
     Json(downloads)
 }
 
 /// Get list of downloaded magnet/links for marking items in UI
 pub(super) async fn get_downloaded_links(State(state): State<AppState>) -> Json<Vec<String>> {
-    let links = crate::db::get_downloaded_links(&state.db_pool).unwrap_or_default();
+    let links = state.store.downloaded_links().await.unwrap_or_default();
     Json(links)
 }
 
 /// Clear download history
 pub(super) async fn clear_downloads(State(state): State<AppState>) -> impl IntoResponse {
-    match crate::db::clear_download_logs(&state.db_pool) {
+    match state.store.clear_download_logs().await {
         Ok(count) => (
             axum::http::StatusCode::OK,
             format!("Cleared {} download logs", count),
@@ -176,7 +339,7 @@ pub(super) async fn clear_downloads(State(state): State<AppState>) -> impl IntoR
 
 /// Clear search stats/logs
 pub(super) async fn clear_stats(State(state): State<AppState>) -> impl IntoResponse {
-    match crate::db::clear_search_logs(&state.db_pool) {
+    match state.store.clear_search_logs().await {
         Ok(count) => (
             axum::http::StatusCode::OK,
             format!("Cleared {} search logs", count),
@@ -194,13 +357,13 @@ pub(super) async fn clear_stats(State(state): State<AppState>) -> impl IntoRespo
 pub(super) async fn clear_all(State(state): State<AppState>) -> impl IntoResponse {
     let mut cleared = Vec::new();
 
-    if let Ok(n) = crate::db::clear_search_logs(&state.db_pool) {
+    if let Ok(n) = state.store.clear_search_logs().await {
         cleared.push(format!("{} search logs", n));
     }
-    if let Ok(n) = crate::db::clear_download_logs(&state.db_pool) {
+    if let Ok(n) = state.store.clear_download_logs().await {
         cleared.push(format!("{} downloads", n));
     }
-    if let Ok(n) = crate::db::clear_cache(&state.db_pool) {
+    if let Ok(n) = state.store.clear_cache().await {
         cleared.push(format!("{} cached searches", n));
     }
 
@@ -210,3 +373,51 @@ pub(super) async fn clear_all(State(state): State<AppState>) -> impl IntoRespons
     )
         .into_response()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Serve `/healthz` for `pool`, returning the base URL
+    async fn serve(pool: crate::db::DbPool) -> String {
+        let app = axum::Router::new().route(
+            "/healthz",
+            axum::routing::get(move || async move { health(&pool) }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await });
+        format!("http://{}/", addr)
+    }
+
+    #[tokio::test]
+    async fn test_healthz_and_ping() {
+        let path =
+            std::env::temp_dir().join(format!("lodestarr-test-healthz-{}.db", std::process::id()));
+        let healthy = serve(crate::db::init_db(&path)).await;
+        crate::handle_ping_command(&healthy, Some("secret"), 5)
+            .await
+            .unwrap();
+
+        // A database that can't be opened fails the check
+        let broken = r2d2::Pool::builder()
+            .connection_timeout(std::time::Duration::from_millis(100))
+            .build_unchecked(r2d2_sqlite::SqliteConnectionManager::file(
+                path.join("missing/lodestarr.db"),
+            ));
+        let unhealthy = serve(broken).await;
+        let e = crate::handle_ping_command(&unhealthy, None, 5)
+            .await
+            .unwrap_err();
+        assert!(e.to_string().contains("database unavailable"), "{e}");
+
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let closed = format!("http://{}", listener.local_addr().unwrap());
+        drop(listener);
+        let e = crate::handle_ping_command(&closed, None, 5)
+            .await
+            .unwrap_err();
+        assert!(e.to_string().contains("unreachable"), "{e}");
+        let _ = std::fs::remove_file(&path);
+    }
+}
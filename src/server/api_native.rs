@@ -1,8 +1,11 @@
 //! Native indexer API endpoints
 
 use super::AppState;
+use crate::config::IndexerSelection;
+use crate::indexer::report::TestReport;
 use crate::indexer::{IndexerDownloader, SearchExecutor};
-use crate::models::SearchQuery;
+use crate::models::{DateSource, SearchQuery, SearchType};
+use crate::sort::{SortField, SortOrder, Sortable};
 use axum::{
     Json,
     extract::{Path, Query, State},
@@ -12,6 +15,7 @@ use axum::{
 use chrono::{DateTime, Utc};
 use futures::stream::StreamExt;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 
 #[derive(Serialize)]
 pub(super) struct GithubIndexerInfo {
@@ -111,6 +115,12 @@ pub(super) struct LocalIndexerInfo {
     legacylinks: Vec<String>,
     categories: Vec<i32>,
     enabled: bool,
+    /// Recent searches suddenly return nothing (see `indexer::breakage`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    probably_broken: Option<crate::indexer::breakage::Breakage>,
+    /// Definition keys this version ignores (see `indexer validate`)
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    unknown_fields: Vec<String>,
 }
 
 #[derive(Serialize)]
@@ -125,6 +135,7 @@ pub(super) async fn list_local_indexers(
     let manager = state.native_indexers.read().await;
     let definitions = manager.list_all_definitions().await;
     let config = state.config.read().await;
+    let mut broken = crate::indexer::breakage::probably_broken(&state.db_pool);
 
     let indexers: Vec<LocalIndexerInfo> = definitions
         .iter()
@@ -138,6 +149,8 @@ pub(super) async fn list_local_indexers(
             legacylinks: def.legacylinks.clone(),
             categories: def.extract_categories(),
             enabled: config.is_enabled(&def.id),
+            probably_broken: broken.remove(&def.id),
+            unknown_fields: def.unknown_fields.clone(),
         })
         .collect();
 
@@ -166,8 +179,9 @@ pub(super) async fn download_indexers(
         .get_active_native_path()
         .map(|p| p.to_string_lossy().to_string())
         .unwrap_or_else(|_| "indexers".to_string());
+    drop(config);
     // Create the indexers directory if it doesn't exist
-    if let Err(e) = std::fs::create_dir_all(&active_native_path) {
+    if let Err(e) = tokio::fs::create_dir_all(&active_native_path).await {
         return (
             StatusCode::INTERNAL_SERVER_ERROR,
             format!("Failed to create indexers directory: {}", e),
@@ -189,9 +203,10 @@ pub(super) async fn download_indexers(
                 }
             }
 
-            // Reload indexers after download
+            // Reload indexers after download. The manager swaps in the new
+            // definitions itself, so searches running meanwhile aren't held up.
             if !success.is_empty() {
-                let manager = state.native_indexers.write().await;
+                let manager = state.native_indexers.read().await;
                 let path = std::path::Path::new(&active_native_path);
                 let _ = manager.load_definitions(path).await;
             }
@@ -231,7 +246,7 @@ pub(super) async fn delete_native_indexer(
 
     let indexer_file = active_native_path.join(format!("{}.yml", payload.name));
 
-    if !indexer_file.exists() {
+    if !tokio::fs::try_exists(&indexer_file).await.unwrap_or(false) {
         return (
             StatusCode::NOT_FOUND,
             format!("Indexer '{}' not found", payload.name),
@@ -239,7 +254,7 @@ pub(super) async fn delete_native_indexer(
             .into_response();
     }
 
-    if let Err(e) = std::fs::remove_file(&indexer_file) {
+    if let Err(e) = tokio::fs::remove_file(&indexer_file).await {
         return (
             StatusCode::INTERNAL_SERVER_ERROR,
             format!("Failed to delete: {}", e),
@@ -248,7 +263,7 @@ pub(super) async fn delete_native_indexer(
     }
 
     // Reload the indexer manager to reflect the deletion
-    let manager = state.native_indexers.write().await;
+    let manager = state.native_indexers.read().await;
     let _ = manager.load_definitions(&active_native_path).await;
 
     tracing::info!("Deleted native indexer: {}", payload.name);
@@ -264,9 +279,17 @@ pub(super) struct NativeSearchParams {
     q: String,
     indexer: Option<String>,
     cat: Option<String>,
+    /// Overrides the configured `[sort]`
+    sort: Option<SortField>,
+    order: Option<SortOrder>,
+    /// Only releases published in the last this many days
+    maxage: Option<u32>,
+    /// Search again even if the results are cached, replacing them
+    #[serde(default)]
+    refresh: bool,
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Clone, Serialize, Deserialize)]
 pub(super) struct NativeSearchResult {
     title: String,
     link: Option<String>,
@@ -276,10 +299,29 @@ pub(super) struct NativeSearchResult {
     leechers: Option<u32>,
     indexer: String,
     indexer_id: String,
+    /// Names of the other indexers that had the same torrent
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    other_indexers: Vec<String>,
     publish_date: Option<DateTime<Utc>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    date_source: Option<DateSource>,
     categories: Vec<i32>,
     comments: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    poster: Option<String>,
     guid: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    health: Option<u8>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    in_library: bool,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    category_fallback: bool,
+    /// When the search that found this result ran
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    fetched_at: Option<DateTime<Utc>>,
+    /// Served from the search cache instead of a new search
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    cached: bool,
 }
 
 pub(super) async fn search_native(
@@ -294,45 +336,98 @@ pub(super) async fn search_native(
         params.q,
         params.cat.as_deref().unwrap_or("")
     );
+    let (sort, selection) = {
+        let config = state.config.read().await;
+        (
+            config.sort.with(params.sort, params.order),
+            config.select_indexers(params.indexer.as_deref()),
+        )
+    };
+    let selection = match selection {
+        Ok(selection) => selection,
+        Err(e) => return (StatusCode::BAD_REQUEST, e.to_string()).into_response(),
+    };
 
-    // Check cache
-    if let Ok(Some(cached)) = crate::db::get_cached_results(&state.db_pool, &cache_key)
-        && let Ok(results) = serde_json::from_str::<Vec<NativeSearchResult>>(&cached)
+    // Check cache, or drop it when asked for fresh results
+    if params.refresh {
+        let _ = state.store.take_cached(&cache_key).await;
+    } else if let Ok(Some(cached)) = state.store.get_cached(&cache_key).await
+        && let Ok(mut results) = serde_json::from_str::<Vec<NativeSearchResult>>(&cached)
     {
+        for r in &mut results {
+            r.cached = true;
+        }
         // Log cached search
-        let _ = crate::db::log_search(
-            &state.db_pool,
-            &params.q,
-            target,
-            results.len(),
-            start.elapsed().as_millis(),
-        );
+        let _ = state
+            .store
+            .log_search(
+                &params.q,
+                target,
+                results.len(),
+                start.elapsed().as_millis(),
+            )
+            .await;
+        results.retain(|r| crate::search::within_max_age(r.publish_date, params.maxage));
+        sort.apply(&mut results, &params.q);
+        prefetch_torrents(&state, &*state.config.read().await, &results);
         return Json(results).into_response();
     }
 
+    // One clear error rather than every indexer failing the same way
+    let proxy_url = state.config.read().await.proxy_url.clone();
+    if let Err(e) = crate::http::ensure_proxy(proxy_url.as_deref()).await {
+        return (StatusCode::SERVICE_UNAVAILABLE, e.to_string()).into_response();
+    }
+
+    // Identical searches running at the same time share one run
+    let mut all_results = super::single_flight::run(
+        &state.store,
+        &cache_key,
+        search_indexers(&state, &params, &selection, &cache_key),
+    )
+    .await;
+    sort.apply(&mut all_results, &params.q);
+
+    // Record stat
+    let duration = start.elapsed();
+    let _ = state
+        .store
+        .log_search(&params.q, target, all_results.len(), duration.as_millis())
+        .await;
+
+    all_results.retain(|r| crate::search::within_max_age(r.publish_date, params.maxage));
+    prefetch_torrents(&state, &*state.config.read().await, &all_results);
+    Json(all_results).into_response()
+}
+
+/// Search the native indexers for the web UI and cache the merged results
+async fn search_indexers(
+    state: &AppState,
+    params: &NativeSearchParams,
+    selection: &IndexerSelection,
+    cache_key: &str,
+) -> Vec<NativeSearchResult> {
     let manager = state.native_indexers.read().await;
     let definitions = manager.list_all_definitions().await;
 
     if definitions.is_empty() {
-        return Json(Vec::<NativeSearchResult>::new()).into_response();
+        return Vec::new();
     }
 
     let mut all_results = Vec::new();
 
-    // Determine which indexers to search
-    let indexers_to_search: Vec<_> = if let Some(ref target) = params.indexer {
-        definitions
-            .into_iter()
-            .filter(|d| &d.id == target)
-            .collect()
-    } else {
-        definitions
-    };
-
     let config = state.config.read().await;
-    let mut indexers_to_search: Vec<_> = indexers_to_search
+    // Rate-limited and failing indexers sit out searches of several indexers
+    // until their cooldown ends, as do indexers with general search turned off
+    let single = selection.is_single();
+    let failing = crate::indexer::failing::failing(&state.db_pool);
+    let mut indexers_to_search: Vec<_> = definitions
         .into_iter()
+        .filter(|d| selection.includes(&d.id))
         .filter(|d| config.is_enabled(&d.id))
+        .filter(|d| single || crate::indexer::backoff::remaining(&d.id).is_none())
+        .filter(|d| single || !failing.contains_key(&d.id))
+        .filter(|d| single || config.search_type_enabled(&d.id, SearchType::Search))
         .collect();
 
     // Sort by priority (lower = first)
@@ -366,16 +461,33 @@ pub(super) async fn search_native(
 
     // Get proxy URL for creating executors
     let proxy_url = config.proxy_url.clone();
+    let keep_raw = config.raw_responses;
+    let concurrency = config.search.concurrency();
 
     let futures = indexers_to_search.into_iter().map(|def| {
         let q = search_query.clone();
         let proxy = proxy_url.clone();
         let settings = config.native_settings.get(&def.id).cloned();
+        let pool = state.db_pool.clone();
+        let search_config = config.search.clone();
+        let cache_key = cache_key.to_string();
+        let cap = config.max_results_for(&def.id);
         async move {
-            let executor = SearchExecutor::new_with_settings(proxy.as_deref(), settings.as_ref())
-                .unwrap_or_else(|_| SearchExecutor::new(None).expect("Failed to create executor"));
-            match executor.search(&def, &q, settings.as_ref()).await {
-                Ok(results) => Some((def.id.clone(), def.name.clone(), results)),
+            let executor =
+                SearchExecutor::for_indexer(&def.id, proxy.as_deref(), settings.as_ref())
+                    .unwrap_or_else(|_| {
+                        SearchExecutor::new(None).expect("Failed to create executor")
+                    });
+            let search = executor.search(&def, &q, settings.as_ref());
+            let result = super::latency::timed(&pool, &search_config, &def.id, search).await;
+            if keep_raw && !result.as_ref().is_ok_and(|r| !r.is_empty()) {
+                store_raw_responses(&pool, &cache_key, &def.id, &executor);
+            }
+            match result {
+                Ok(mut results) => {
+                    results.truncate(cap);
+                    Some((def.id.clone(), def.name.clone(), results))
+                }
                 Err(e) => {
                     tracing::warn!("Search failed for {}: {}", def.id, e);
                     None
@@ -386,57 +498,137 @@ pub(super) async fn search_native(
 
     let results: Vec<Option<(String, String, Vec<crate::models::TorrentResult>)>> =
         futures::stream::iter(futures)
-            .buffer_unordered(4)
+            .buffer_unordered(concurrency)
             .collect()
             .await;
 
-    for result in results.into_iter().flatten() {
-        let (indexer_id, indexer_name, items) = result;
-        for r in items {
-            all_results.push(NativeSearchResult {
-                title: r.title,
-                link: r.link,
-                magnet: r.magnet,
-                size: r.size,
-                seeders: r.seeders,
-                leechers: r.leechers,
-                indexer: indexer_name.clone(),
-                indexer_id: indexer_id.clone(),
-                publish_date: r.publish_date,
-                categories: r.categories,
-                comments: r.details,
-                guid: r.guid,
-            });
+    // One list, with copies of a torrent on several indexers merged
+    let mut names = HashMap::new();
+    let mut items = Vec::new();
+    for (indexer_id, indexer_name, mut found) in results.into_iter().flatten() {
+        for r in &mut found {
+            r.indexer = Some(indexer_id.clone());
         }
+        items.extend(found);
+        names.insert(indexer_id, indexer_name);
+    }
+    super::api_indexers::register_guids(&state.db_pool, None, &items);
+    let mut items = crate::dedup::merge_duplicates(items);
+    super::api_indexers::fill_first_seen(&state.db_pool, &mut items);
+    super::library::flag(&state.db_pool, &mut items);
+
+    let reliability = crate::health::reliability(&state.db_pool);
+    let now = chrono::Utc::now();
+    for r in items {
+        let indexer_id = r.indexer.clone().unwrap_or_default();
+        let health = crate::health::score(&r, reliability.get(&indexer_id).copied(), now);
+        all_results.push(NativeSearchResult {
+            title: r.title,
+            link: r.link,
+            magnet: r.magnet,
+            size: r.size,
+            seeders: r.seeders,
+            leechers: r.leechers,
+            indexer: names
+                .get(&indexer_id)
+                .cloned()
+                .unwrap_or_else(|| indexer_id.clone()),
+            indexer_id,
+            other_indexers: r
+                .other_indexers
+                .iter()
+                .map(|id| names.get(id).cloned().unwrap_or_else(|| id.clone()))
+                .collect(),
+            publish_date: r.publish_date,
+            date_source: r.date_source,
+            categories: r.categories,
+            comments: r.details,
+            poster: r.poster,
+            guid: r.guid,
+            health: Some(health),
+            in_library: r.in_library,
+            category_fallback: r.category_fallback,
+            fetched_at: Some(now),
+            cached: false,
+        });
     }
-
-    // Sort by seeders
-    all_results.sort_by(|a, b| b.seeders.unwrap_or(0).cmp(&a.seeders.unwrap_or(0)));
-
-    // Record stat
-    let duration = start.elapsed();
-    let _ = crate::db::log_search(
-        &state.db_pool,
-        &params.q,
-        target,
-        all_results.len(),
-        duration.as_millis(),
-    );
 
     // Cache results
     if !all_results.is_empty()
         && let Ok(serialized) = serde_json::to_string(&all_results)
     {
-        let _ = crate::db::set_cached_results(&state.db_pool, &cache_key, &serialized, 1);
+        let _ = state
+            .store
+            .set_cached(cache_key, &serialized, chrono::Duration::hours(1))
+            .await;
     }
+    all_results
+}
 
-    Json(all_results).into_response()
+/// Start fetching the top results' .torrent files (`[torrent_prefetch]`)
+fn prefetch_torrents(
+    state: &AppState,
+    config: &crate::config::Config,
+    results: &[NativeSearchResult],
+) {
+    let top = config.torrent_prefetch.top;
+    if top == 0 {
+        return;
+    }
+    let candidates = super::torrent_cache::candidates(
+        results
+            .iter()
+            .map(|r| (r.indexer_id.as_str(), r.link.as_deref())),
+        top,
+    );
+    super::torrent_cache::spawn(state.clone(), config, candidates);
+}
+
+/// Keep the pages an indexer answered a search with but that gave no results
+fn store_raw_responses(
+    pool: &crate::db::DbPool,
+    cache_key: &str,
+    indexer: &str,
+    executor: &SearchExecutor,
+) {
+    for page in executor.take_empty_responses() {
+        if let Err(e) = crate::db::add_raw_response(
+            pool,
+            cache_key,
+            indexer,
+            &page.url,
+            page.status,
+            &page.body,
+        ) {
+            tracing::debug!("Failed to store raw response from {}: {}", indexer, e);
+        }
+    }
+}
+
+impl Sortable for NativeSearchResult {
+    fn title(&self) -> &str {
+        &self.title
+    }
+    fn seeders(&self) -> Option<u32> {
+        self.seeders
+    }
+    fn size(&self) -> Option<u64> {
+        self.size
+    }
+    fn publish_date(&self) -> Option<DateTime<Utc>> {
+        self.publish_date
+    }
+    fn health(&self) -> Option<u8> {
+        self.health
+    }
 }
 
 #[derive(Serialize)]
 pub(super) struct NativeSettingsResponse {
     pub settings: Vec<crate::indexer::definition::Setting>,
     pub values: std::collections::HashMap<String, String>,
+    /// Login needs a captcha solved via `/api/native/{id}/captcha`
+    pub captcha: bool,
 }
 
 pub(super) async fn get_native_settings(
@@ -453,8 +645,9 @@ pub(super) async fn get_native_settings(
     let current_values = config.native_settings.get(&id).cloned().unwrap_or_default();
 
     Json(NativeSettingsResponse {
-        settings: def.settings.clone(),
+        settings: crate::indexer::settings::schema(&def),
         values: current_values,
+        captcha: def.login.as_ref().is_some_and(|l| l.captcha.is_some()),
     })
     .into_response()
 }
@@ -490,14 +683,6 @@ pub(super) struct TestNativeParams {
     pub settings: Option<std::collections::HashMap<String, String>>,
 }
 
-#[derive(Serialize)]
-pub(super) struct TestResult {
-    pub success: bool,
-    pub count: usize,
-    pub time_ms: u128,
-    pub message: String,
-}
-
 pub(super) async fn test_native_indexer(
     State(state): State<AppState>,
     Path(id): Path<String>,
@@ -529,43 +714,267 @@ pub(super) async fn test_native_indexer(
 
     let proxy_url = config.proxy_url.clone();
     let executor =
-        SearchExecutor::new_with_settings(proxy_url.as_deref(), settings_to_use.as_ref())
+        SearchExecutor::for_indexer(&def.id, proxy_url.as_deref(), settings_to_use.as_ref())
             .unwrap_or_else(|_| SearchExecutor::new(None).expect("Failed to create executor"));
 
-    match executor
+    let outcome = executor
         .search(&def, &query, settings_to_use.as_ref())
+        .await;
+    Json(TestReport::new(
+        &def.id,
+        query.query.as_deref().unwrap_or_default(),
+        &outcome,
+        start.elapsed(),
+        executor.take_requests(),
+    ))
+    .into_response()
+}
+
+#[derive(Deserialize)]
+pub(super) struct TryNativeParams {
+    /// Definition to run, as YAML
+    pub yaml: String,
+    #[serde(default)]
+    pub query: SearchQuery,
+    pub settings: Option<HashMap<String, String>>,
+}
+
+/// A search page that yielded no results
+#[derive(Serialize)]
+pub(super) struct TryPage {
+    url: String,
+    status: u16,
+    /// Start of the body, for checking selectors against
+    body: String,
+}
+
+#[derive(Serialize)]
+pub(super) struct TryResponse {
+    success: bool,
+    count: usize,
+    time_ms: u128,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+    /// How many results have each optional field filled in
+    fields: Vec<(&'static str, usize)>,
+    empty_pages: Vec<TryPage>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    timings: Option<crate::indexer::timings::SearchTimings>,
+    /// First results, as the search API returns them
+    results: Vec<crate::models::TorrentResult>,
+    /// Definition keys this version ignores
+    unknown_fields: Vec<String>,
+}
+
+/// Results shown by a trial run
+const TRY_MAX_RESULTS: usize = 50;
+/// Body bytes kept per empty page in a trial run
+const TRY_PAGE_BYTES: usize = 16 * 1024;
+
+/// How many results extracted each optional field
+fn field_coverage(results: &[crate::models::TorrentResult]) -> Vec<(&'static str, usize)> {
+    let count =
+        |has: fn(&crate::models::TorrentResult) -> bool| results.iter().filter(|r| has(r)).count();
+    vec![
+        ("link", count(|r| r.link.is_some())),
+        ("magnet", count(|r| r.magnet.is_some())),
+        ("details", count(|r| r.details.is_some())),
+        ("date", count(|r| r.publish_date.is_some())),
+        ("category", count(|r| !r.categories.is_empty())),
+        ("size", count(|r| r.size.is_some())),
+        ("seeders", count(|r| r.seeders.is_some())),
+        ("leechers", count(|r| r.leechers.is_some())),
+        ("grabs", count(|r| r.grabs.is_some())),
+        ("infohash", count(|r| r.info_hash.is_some())),
+        ("imdb", count(|r| r.imdb_id.is_some())),
+        (
+            "downloadvolumefactor",
+            count(|r| r.download_volume_factor.is_some()),
+        ),
+        (
+            "uploadvolumefactor",
+            count(|r| r.upload_volume_factor.is_some()),
+        ),
+    ]
+}
+
+/// Run a search with a definition that isn't installed, for authoring
+/// definitions. Nothing is written to disk, the database or the shared
+/// login sessions.
+pub(super) async fn try_native_definition(
+    State(state): State<AppState>,
+    Json(payload): Json<TryNativeParams>,
+) -> impl IntoResponse {
+    let def = match crate::indexer::definition::IndexerDefinition::from_yaml(&payload.yaml) {
+        Ok(def) => def,
+        Err(e) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                format!("Invalid definition: {}", e),
+            )
+                .into_response();
+        }
+    };
+
+    let proxy_url = state.config.read().await.proxy_url.clone();
+    let executor = match SearchExecutor::trial(proxy_url.as_deref(), payload.settings.as_ref()) {
+        Ok(e) => e,
+        Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    };
+
+    let start = std::time::Instant::now();
+    let result = executor
+        .search(&def, &payload.query, payload.settings.as_ref())
+        .await;
+    let time_ms = start.elapsed().as_millis();
+
+    let empty_pages = executor
+        .take_empty_responses()
+        .into_iter()
+        .map(|page| {
+            let end = crate::indexer::challenge::floor_char_boundary(&page.body, TRY_PAGE_BYTES);
+            TryPage {
+                url: page.url,
+                status: page.status,
+                body: page.body[..end].to_string(),
+            }
+        })
+        .collect();
+    let timings = executor.take_timings();
+
+    let (mut results, error) = match result {
+        Ok(results) => (results, None),
+        Err(e) => (Vec::new(), Some(e.to_string())),
+    };
+    let count = results.len();
+    let fields = field_coverage(&results);
+    results.truncate(TRY_MAX_RESULTS);
+
+    Json(TryResponse {
+        success: error.is_none() && count > 0,
+        count,
+        time_ms,
+        error,
+        fields,
+        empty_pages,
+        timings,
+        results,
+        unknown_fields: def.unknown_fields,
+    })
+    .into_response()
+}
+
+/// Fetch the captcha image from the indexer's login page
+pub(super) async fn get_native_captcha(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> impl IntoResponse {
+    let manager = state.native_indexers.read().await;
+    let Some(def) = manager.get_definition(&id).await else {
+        return (StatusCode::NOT_FOUND, "Indexer not found").into_response();
+    };
+
+    let config = state.config.read().await;
+    let settings = config.native_settings.get(&id);
+    let executor = match SearchExecutor::for_indexer(&id, config.proxy_url.as_deref(), settings) {
+        Ok(e) => e,
+        Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    };
+
+    match executor.fetch_captcha(&def, settings).await {
+        Ok((bytes, content_type)) => (
+            [
+                (axum::http::header::CONTENT_TYPE, content_type),
+                (axum::http::header::CACHE_CONTROL, "no-store".to_string()),
+            ],
+            bytes,
+        )
+            .into_response(),
+        Err(e) => (
+            crate::indexer::error::kind_of(&e).http_status(),
+            format!("Failed to fetch captcha: {}", e),
+        )
+            .into_response(),
+    }
+}
+
+#[derive(Deserialize)]
+pub(super) struct SolveCaptchaParams {
+    pub answer: String,
+}
+
+/// Complete the indexer login with the user's captcha answer
+pub(super) async fn solve_native_captcha(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Json(payload): Json<SolveCaptchaParams>,
+) -> impl IntoResponse {
+    let manager = state.native_indexers.read().await;
+    let Some(def) = manager.get_definition(&id).await else {
+        return (StatusCode::NOT_FOUND, "Indexer not found").into_response();
+    };
+
+    let config = state.config.read().await;
+    let settings = config.native_settings.get(&id);
+    let executor = match SearchExecutor::for_indexer(&id, config.proxy_url.as_deref(), settings) {
+        Ok(e) => e,
+        Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    };
+
+    match executor
+        .login_with_captcha(&def, settings, payload.answer.trim())
         .await
     {
-        Ok(results) => {
-            let time_ms = start.elapsed().as_millis();
-            let count = results.len();
-            if count == 0 {
-                Json(TestResult {
-                    success: false,
-                    count: 0,
-                    time_ms,
-                    message: "No results found - indexer may be down or misconfigured".to_string(),
-                })
-                .into_response()
-            } else {
-                Json(TestResult {
-                    success: true,
-                    count,
-                    time_ms,
-                    message: format!("Found {} releases in {}ms", count, time_ms),
-                })
-                .into_response()
-            }
-        }
-        Err(e) => {
-            let time_ms = start.elapsed().as_millis();
-            Json(TestResult {
-                success: false,
-                count: 0,
-                time_ms,
-                message: format!("Test failed: {}", e),
+        Ok(()) => (StatusCode::OK, "Logged in").into_response(),
+        Err(e) => (StatusCode::BAD_REQUEST, format!("Login failed: {}", e)).into_response(),
+    }
+}
+
+/// Cookie saved for an indexer, without its value
+#[derive(Serialize)]
+pub(super) struct SavedCookie {
+    domain: String,
+    path: String,
+    name: String,
+    secure: bool,
+    expires: Option<DateTime<Utc>>,
+}
+
+/// Cookies imported for the indexer
+pub(super) async fn get_native_cookies(Path(id): Path<String>) -> Json<Vec<SavedCookie>> {
+    Json(
+        crate::indexer::cookies::load(&id)
+            .into_iter()
+            .map(|c| SavedCookie {
+                domain: c.domain,
+                path: c.path,
+                name: c.name,
+                secure: c.secure,
+                expires: c.expires,
             })
-            .into_response()
-        }
+            .collect(),
+    )
+}
+
+/// Import a Netscape cookies.txt or browser JSON export (the request body)
+pub(super) async fn import_native_cookies(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    body: String,
+) -> impl IntoResponse {
+    let manager = state.native_indexers.read().await;
+    let Some(def) = manager.get_definition(&id).await else {
+        return (StatusCode::NOT_FOUND, "Indexer not found").into_response();
+    };
+    match crate::indexer::cookies::import(&def, &body) {
+        Ok(imported) => Json(imported).into_response(),
+        Err(e) => (StatusCode::BAD_REQUEST, e.to_string()).into_response(),
+    }
+}
+
+pub(super) async fn clear_native_cookies(Path(id): Path<String>) -> impl IntoResponse {
+    match crate::indexer::cookies::clear(&id) {
+        Ok(count) => Json(serde_json::json!({ "cleared": count })).into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
     }
 }
@@ -80,14 +80,26 @@ pub(super) async fn save_download_config(
     State(state): State<AppState>,
     Json(payload): Json<DownloadConfigParams>,
 ) -> impl IntoResponse {
-    if !payload.path.is_empty()
-        && let Err(e) = std::fs::create_dir_all(&payload.path)
-    {
-        return (
-            StatusCode::BAD_REQUEST,
-            format!("Invalid download path: {}", e),
-        )
-            .into_response();
+    let mut free = None;
+    if !payload.path.is_empty() {
+        let path = std::path::PathBuf::from(&payload.path);
+        let checked = tokio::task::spawn_blocking(move || {
+            std::fs::create_dir_all(&path)?;
+            crate::utils::check_writable(&path)?;
+            Ok::<_, std::io::Error>(crate::utils::disk_space(&path).map(|(free, _)| free))
+        })
+        .await;
+        match checked {
+            Ok(Ok(space)) => free = space,
+            Ok(Err(e)) => {
+                return (
+                    StatusCode::BAD_REQUEST,
+                    format!("Invalid download path: {}", e),
+                )
+                    .into_response();
+            }
+            Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+        }
     }
 
     let mut config = state.config.write().await;
@@ -99,7 +111,17 @@ pub(super) async fn save_download_config(
     if let Err((status, msg)) = save_config_or_error(&config) {
         return (status, msg).into_response();
     }
-    (StatusCode::OK, "Download path saved").into_response()
+    match free {
+        Some(free) => (
+            StatusCode::OK,
+            format!(
+                "Download path saved ({} free)",
+                crate::utils::format_size(free)
+            ),
+        )
+            .into_response(),
+        _ => (StatusCode::OK, "Download path saved").into_response(),
+    }
 }
 
 pub(super) async fn get_proxy_config(State(state): State<AppState>) -> Json<serde_json::Value> {
@@ -124,16 +146,15 @@ pub(super) async fn save_proxy_config(
         return (status, msg).into_response();
     }
 
-    let proxy_url = config.proxy_url.as_deref();
-    let mut manager = state.native_indexers.write().await;
-
-    let new_manager = IndexerManager::new(proxy_url);
-    if let Ok(active_native_path) = config.get_active_native_path()
-        && active_native_path.exists()
-    {
+    // Load the definitions again with the new proxy before swapping them in,
+    // so searches aren't locked out while the files are read
+    let new_manager = IndexerManager::new(config.proxy_url.as_deref());
+    let active_native_path = config.get_active_native_path();
+    drop(config);
+    if let Ok(active_native_path) = active_native_path {
         let _ = new_manager.load_definitions(&active_native_path).await;
     }
-    *manager = new_manager;
+    *state.native_indexers.write().await = new_manager;
 
     (StatusCode::OK, "Proxy settings saved").into_response()
 }
@@ -153,6 +174,12 @@ pub(super) async fn set_indexer_status(
     if let Err((status, msg)) = save_config_or_error(&config) {
         return (status, msg).into_response();
     }
+    // Enabling also brings back an indexer skipped for failing
+    if payload.enabled
+        && let Err(e) = crate::indexer::failing::reenable(&state.db_pool, &name)
+    {
+        tracing::warn!("Failed to clear failing mark of {}: {}", name, e);
+    }
     (StatusCode::OK, "Status updated").into_response()
 }
 
@@ -162,7 +189,7 @@ pub(super) struct ClearCacheResponse {
 }
 
 pub(super) async fn clear_cache_api(State(state): State<AppState>) -> impl IntoResponse {
-    match crate::db::clear_all_cache(&state.db_pool) {
+    match state.store.clear_cache().await {
         Ok(deleted) => Json(ClearCacheResponse { deleted }).into_response(),
         Err(e) => (
             StatusCode::INTERNAL_SERVER_ERROR,
@@ -173,7 +200,7 @@ pub(super) async fn clear_cache_api(State(state): State<AppState>) -> impl IntoR
 }
 
 pub(super) async fn clear_activity_api(State(state): State<AppState>) -> impl IntoResponse {
-    match crate::db::clear_search_logs(&state.db_pool) {
+    match state.store.clear_search_logs().await {
         Ok(deleted) => Json(ClearCacheResponse { deleted }).into_response(),
         Err(e) => (
             StatusCode::INTERNAL_SERVER_ERROR,
@@ -187,6 +214,15 @@ pub(super) async fn clear_activity_api(State(state): State<AppState>) -> impl In
 pub(super) struct TriggerDownloadParams {
     url: String,
     title: Option<String>,
+    /// Indexer and category for the path template (the indexer defaults to
+    /// the one in a proxy link)
+    indexer: Option<String>,
+    category: Option<i32>,
+    /// Path template for this download instead of `download_template`
+    template: Option<String>,
+    /// Report what would be downloaded instead of downloading it
+    #[serde(default, rename = "dryRun")]
+    dry_run: bool,
 }
 
 pub(super) async fn trigger_download(
@@ -200,6 +236,55 @@ pub(super) async fn trigger_download(
     };
 
     let proxy_url = config.proxy_url.as_deref();
+
+    let magnet = payload.url.starts_with("magnet:");
+    let proxy_indexer = crate::download::parse_proxy_link(&payload.url).map(|(id, _)| id);
+    let template = payload
+        .template
+        .as_deref()
+        .or(config.download_template.as_deref());
+    let target = match template {
+        Some(template) => {
+            let values = crate::download::TemplateValues {
+                title: payload.title.as_deref(),
+                indexer: payload.indexer.as_deref().or(proxy_indexer.as_deref()),
+                category: payload.category,
+            };
+            std::path::Path::new(&path)
+                .join(crate::download::render_template(template, &values, magnet))
+        }
+        // Joined here rather than by target_path, which would take a
+        // download path that doesn't exist yet for a file name
+        None => std::path::Path::new(&path).join(crate::download::target_path(
+            &payload.url,
+            None,
+            magnet,
+            payload.title.as_deref(),
+        )),
+    };
+    let target = crate::download::unique_path(target).await;
+    let filename = target.to_string_lossy().to_string();
+
+    if payload.dry_run {
+        let manager = state.native_indexers.read().await;
+        return match crate::download::plan_download(
+            &payload.url,
+            Some(&filename),
+            false,
+            payload.title.as_deref(),
+            &manager,
+            &config,
+        )
+        .await
+        {
+            Ok(plan) => Json(plan).into_response(),
+            Err(e) => (
+                StatusCode::BAD_GATEWAY,
+                format!("Failed to resolve download: {}", e),
+            )
+                .into_response(),
+        };
+    }
     let client = match TorznabClient::new("http://localhost", None, proxy_url) {
         Ok(c) => c,
         Err(e) => {
@@ -210,29 +295,51 @@ pub(super) async fn trigger_download(
                 .into_response();
         }
     };
+    if let Some(parent) = target.parent()
+        && let Err(e) = tokio::fs::create_dir_all(parent).await
+    {
+        return (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Failed to create {}: {}", parent.display(), e),
+        )
+            .into_response();
+    }
 
     let url = payload.url.clone();
     let title = payload.title.clone();
 
-    match crate::download::perform_download(
-        &client,
-        &payload.url,
-        Some(path),
-        false,
-        payload.title.as_deref(),
-    )
-    .await
-    {
+    // A prefetched .torrent is written as is, without asking the tracker
+    let link = crate::download::parse_proxy_link(&url).map_or_else(|| url.clone(), |(_, l)| l);
+    let cached = if url.starts_with("magnet:") {
+        None
+    } else {
+        super::torrent_cache::get(&config, &link).await
+    };
+    let result = match cached {
+        Some(bytes) => tokio::fs::write(&filename, bytes)
+            .await
+            .map_err(anyhow::Error::from),
+        None => {
+            crate::download::perform_download(
+                &client,
+                &payload.url,
+                Some(filename),
+                false,
+                payload.title.as_deref(),
+                None,
+            )
+            .await
+        }
+    };
+
+    match result {
         Ok(_) => {
             // Log the download to the database
-            if let Err(e) = crate::db::log_download(
-                &state.db_pool,
-                title.as_deref(),
-                None,
-                Some(&url),
-                None,
-                "server",
-            ) {
+            if let Err(e) = state
+                .store
+                .log_download(title.as_deref(), None, Some(&url), None, "server")
+                .await
+            {
                 tracing::warn!("Failed to log download: {}", e);
             }
             (StatusCode::OK, "Download started").into_response()
@@ -296,8 +403,10 @@ pub(super) struct TorrentMetadataResponse {
     info_hash: String,
     total_size: u64,
     piece_length: u64,
+    piece_count: usize,
     files: Vec<TorrentFileInfo>,
     trackers: Vec<String>,
+    private: bool,
     #[serde(skip_serializing_if = "Option::is_none")]
     created_by: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -306,58 +415,10 @@ pub(super) struct TorrentMetadataResponse {
     comment: Option<String>,
 }
 
-#[derive(Deserialize, Serialize)]
-struct TorrentInfo {
-    name: Option<String>,
-    #[serde(rename = "piece length")]
-    piece_length: Option<u64>,
-    length: Option<u64>,
-    files: Option<Vec<TorrentFile>>,
-    #[serde(skip)]
-    #[allow(dead_code)]
-    pieces: Option<serde_bytes::ByteBuf>,
-}
-
-#[derive(Deserialize, Serialize)]
-struct TorrentFile {
-    length: u64,
-    path: Vec<String>,
-}
-
-#[derive(Deserialize)]
-struct TorrentData {
-    info: TorrentInfo,
-    announce: Option<String>,
-    #[serde(rename = "announce-list")]
-    announce_list: Option<Vec<Vec<String>>>,
-    #[serde(rename = "created by")]
-    created_by: Option<String>,
-    #[serde(rename = "creation date")]
-    creation_date: Option<i64>,
-    comment: Option<String>,
-}
-
 pub(super) async fn get_torrent_metadata(
     State(state): State<AppState>,
     Json(payload): Json<TorrentMetaParams>,
 ) -> impl IntoResponse {
-    // Fetch the torrent file
-    let proxy_url = {
-        let config = state.config.read().await;
-        config.proxy_url.clone()
-    };
-
-    let client = match TorznabClient::new("http://localhost", None, proxy_url.as_deref()) {
-        Ok(c) => c,
-        Err(e) => {
-            return (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                format!("Failed to create client: {}", e),
-            )
-                .into_response();
-        }
-    };
-
     // Build full URL
     let full_url = if payload.url.starts_with("http") {
         payload.url.clone()
@@ -365,84 +426,54 @@ pub(super) async fn get_torrent_metadata(
         format!("http://localhost:3420{}", payload.url)
     };
 
-    // Download the torrent file
-    let bytes = match client.download(&full_url).await {
-        Ok(b) => b,
-        Err(e) => {
-            return (
-                StatusCode::BAD_GATEWAY,
-                format!("Failed to fetch torrent: {}", e),
-            )
-                .into_response();
+    // A prefetched .torrent is read without asking the tracker
+    let (cached, proxy_url) = {
+        let config = state.config.read().await;
+        let link = crate::download::parse_proxy_link(&full_url)
+            .map_or_else(|| full_url.clone(), |(_, l)| l);
+        (
+            super::torrent_cache::get(&config, &link).await,
+            config.proxy_url.clone(),
+        )
+    };
+
+    let bytes = match cached {
+        Some(bytes) => bytes,
+        None => {
+            let client = match TorznabClient::new("http://localhost", None, proxy_url.as_deref()) {
+                Ok(c) => c,
+                Err(e) => {
+                    return (
+                        StatusCode::INTERNAL_SERVER_ERROR,
+                        format!("Failed to create client: {}", e),
+                    )
+                        .into_response();
+                }
+            };
+            match client.download(&full_url).await {
+                Ok(b) => b,
+                Err(e) => {
+                    return (
+                        StatusCode::BAD_GATEWAY,
+                        format!("Failed to fetch torrent: {}", e),
+                    )
+                        .into_response();
+                }
+            }
         }
     };
 
-    // Parse bencode
-    let torrent: TorrentData = match serde_bencode::from_bytes(&bytes) {
+    let torrent = match crate::torrent::parse(&bytes) {
         Ok(t) => t,
         Err(e) => {
             return (
-                StatusCode::BAD_REQUEST,
+                StatusCode::BAD_GATEWAY,
                 format!("Failed to parse torrent: {}", e),
             )
                 .into_response();
         }
     };
 
-    // Calculate info hash
-    let info_bytes = match serde_bencode::to_bytes(&torrent.info) {
-        Ok(b) => b,
-        Err(_) => {
-            return (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                "Failed to re-encode info dict".to_string(),
-            )
-                .into_response();
-        }
-    };
-
-    use sha1::{Digest, Sha1};
-    let mut hasher = Sha1::new();
-    hasher.update(&info_bytes);
-    let result = hasher.finalize();
-    let info_hash = hex::encode(result);
-
-    // Extract files
-    let (files, total_size) = if let Some(file_list) = torrent.info.files {
-        let mut total = 0u64;
-        let files: Vec<TorrentFileInfo> = file_list
-            .iter()
-            .map(|f| {
-                total += f.length;
-                TorrentFileInfo {
-                    path: f.path.join("/"),
-                    size: f.length,
-                }
-            })
-            .collect();
-        (files, total)
-    } else {
-        // Single file torrent
-        let size = torrent.info.length.unwrap_or(0);
-        let name = torrent.info.name.clone().unwrap_or_default();
-        (vec![TorrentFileInfo { path: name, size }], size)
-    };
-
-    // Extract trackers
-    let mut trackers: Vec<String> = Vec::new();
-    if let Some(announce) = torrent.announce {
-        trackers.push(announce);
-    }
-    if let Some(announce_list) = torrent.announce_list {
-        for tier in announce_list {
-            for tracker in tier {
-                if !trackers.contains(&tracker) {
-                    trackers.push(tracker);
-                }
-            }
-        }
-    }
-
     // Format creation date
     let creation_date = torrent.creation_date.map(|ts| {
         chrono::DateTime::from_timestamp(ts, 0)
@@ -451,12 +482,21 @@ pub(super) async fn get_torrent_metadata(
     });
 
     let response = TorrentMetadataResponse {
-        name: torrent.info.name.unwrap_or_default(),
-        info_hash,
-        total_size,
-        piece_length: torrent.info.piece_length.unwrap_or(0),
-        files,
-        trackers,
+        name: torrent.name,
+        info_hash: torrent.info_hash,
+        total_size: torrent.total_size,
+        piece_length: torrent.piece_length,
+        piece_count: torrent.piece_count,
+        files: torrent
+            .files
+            .into_iter()
+            .map(|f| TorrentFileInfo {
+                path: f.path,
+                size: f.size,
+            })
+            .collect(),
+        trackers: torrent.trackers,
+        private: torrent.private,
         created_by: torrent.created_by,
         creation_date,
         comment: torrent.comment,
@@ -464,3 +504,12 @@ pub(super) async fn get_torrent_metadata(
 
     Json(response).into_response()
 }
+
+/// Check a config (TOML in the request body) without saving it
+pub(super) async fn validate_config(body: String) -> Json<serde_json::Value> {
+    let issues = crate::config::validate::validate(&body);
+    Json(serde_json::json!({
+        "valid": issues.is_empty(),
+        "issues": issues,
+    }))
+}
@@ -0,0 +1,78 @@
+//! Sports calendar API (`/api/sports`): TV/Sport results from all indexers,
+//! grouped by event date
+
+use super::AppState;
+use super::api_indexers::{TorznabParams, search_all_indexers};
+use crate::config::IndexerSelection;
+use crate::models::TorrentResult;
+use crate::sports::{SPORTS_CATEGORY, SportsFilter};
+use axum::{
+    Json,
+    extract::{Query, State},
+    http::StatusCode,
+    response::IntoResponse,
+};
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+
+#[derive(Deserialize)]
+pub(super) struct SportsParams {
+    q: Option<String>,
+    from: Option<String>,
+    to: Option<String>,
+    league: Option<String>,
+}
+
+/// Events on one day (`date` is null for releases without a date)
+#[derive(Serialize)]
+pub(super) struct SportsDay {
+    date: Option<NaiveDate>,
+    results: Vec<TorrentResult>,
+}
+
+pub(super) async fn sports_events(
+    State(state): State<AppState>,
+    Query(params): Query<SportsParams>,
+) -> impl IntoResponse {
+    let cat = SPORTS_CATEGORY.to_string();
+    let filter = match SportsFilter::for_search(
+        Some(&cat),
+        params.from.as_deref(),
+        params.to.as_deref(),
+        params.league.as_deref(),
+    ) {
+        Ok(filter) => filter.unwrap_or_default(),
+        Err(e) => return (StatusCode::BAD_REQUEST, e).into_response(),
+    };
+
+    let torznab = TorznabParams {
+        t: Some("search".to_string()),
+        q: params.q.filter(|q| !q.trim().is_empty()),
+        cat: Some(cat),
+        ..Default::default()
+    };
+    let mut results = search_all_indexers(&state, "search", &torznab, &IndexerSelection::All).await;
+    crate::sports::apply(&mut results, &filter);
+
+    Json(group_by_day(results)).into_response()
+}
+
+/// Newest day first, undated releases last
+fn group_by_day(results: Vec<TorrentResult>) -> Vec<SportsDay> {
+    let mut days: Vec<SportsDay> = Vec::new();
+    for result in results {
+        let date = result.sports.as_ref().and_then(|s| s.date);
+        match days.iter_mut().find(|d| d.date == date) {
+            Some(day) => day.results.push(result),
+            None => days.push(SportsDay {
+                date,
+                results: vec![result],
+            }),
+        }
+    }
+    days.sort_by(|a, b| match (a.date, b.date) {
+        (Some(a), Some(b)) => b.cmp(&a),
+        (a, b) => b.is_some().cmp(&a.is_some()),
+    });
+    days
+}
@@ -0,0 +1,219 @@
+//! Search analytics for dashboard charts (`/api/stats/timeseries`)
+//!
+//! Searches (count, results and latency percentiles) come from the search
+//! history in the store. Per-indexer results come from this instance's
+//! indexer statistics.
+
+use super::AppState;
+use super::latency::percentile;
+use crate::db::{IndexerSample, SearchLog};
+use axum::{
+    Json,
+    extract::{Query, State},
+};
+use chrono::{DateTime, Duration, DurationRound, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Longest period that can be requested
+const MAX_HOURS: i64 = 24 * 90;
+/// Most buckets in one response; the bucket size grows to stay under it
+const MAX_BUCKETS: i64 = 500;
+
+#[derive(Deserialize)]
+pub(super) struct TimeseriesParams {
+    /// Period covered, ending now (default 24)
+    hours: Option<i64>,
+    /// Bucket size (default 60)
+    bucket_minutes: Option<i64>,
+}
+
+#[derive(Serialize)]
+pub(super) struct Timeseries {
+    bucket_minutes: i64,
+    /// Indexers by total results, most first
+    indexers: Vec<String>,
+    buckets: Vec<Bucket>,
+}
+
+#[derive(Serialize, Default)]
+pub(super) struct Bucket {
+    start: DateTime<Utc>,
+    searches: usize,
+    results: usize,
+    p50_ms: Option<u64>,
+    p90_ms: Option<u64>,
+    p99_ms: Option<u64>,
+    /// Results each indexer returned (indexers without any are left out)
+    indexer_results: BTreeMap<String, usize>,
+    /// Failed searches of each indexer
+    indexer_failures: BTreeMap<String, usize>,
+}
+
+pub(super) async fn stats_timeseries(
+    State(state): State<AppState>,
+    Query(params): Query<TimeseriesParams>,
+) -> Json<Timeseries> {
+    let hours = params.hours.unwrap_or(24).clamp(1, MAX_HOURS);
+    let bucket_minutes = params
+        .bucket_minutes
+        .unwrap_or(60)
+        .max(1)
+        .max(hours * 60 / MAX_BUCKETS);
+    let bucket = Duration::minutes(bucket_minutes);
+    let now = Utc::now();
+    let since = now - Duration::hours(hours);
+
+    let searches = state.store.searches_since(since).await.unwrap_or_else(|e| {
+        tracing::warn!("Failed to read search history: {}", e);
+        Vec::new()
+    });
+    let samples = crate::db::get_indexer_samples(&state.db_pool, since).unwrap_or_else(|e| {
+        tracing::warn!("Failed to read indexer statistics: {}", e);
+        Vec::new()
+    });
+    Json(build(since, now, bucket, &searches, &samples))
+}
+
+/// Bucket searches and indexer samples between `from` and `to`. Empty
+/// buckets are kept, so charts get an evenly spaced axis.
+fn build(
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+    bucket: Duration,
+    searches: &[SearchLog],
+    samples: &[IndexerSample],
+) -> Timeseries {
+    let first = from.duration_trunc(bucket).unwrap_or(from);
+    let count = ((to - first).num_seconds() / bucket.num_seconds().max(1) + 1).max(1);
+    let mut buckets: Vec<Bucket> = (0..count)
+        .map(|i| Bucket {
+            start: first + bucket * i as i32,
+            ..Default::default()
+        })
+        .collect();
+    let len = buckets.len();
+    let index = |at: DateTime<Utc>| {
+        let i = (at - first)
+            .num_seconds()
+            .div_euclid(bucket.num_seconds().max(1));
+        usize::try_from(i).ok().filter(|&i| i < len)
+    };
+
+    let mut latencies: Vec<Vec<u64>> = vec![Vec::new(); buckets.len()];
+    for search in searches {
+        let Some(i) = index(search.timestamp) else {
+            continue;
+        };
+        buckets[i].searches += 1;
+        buckets[i].results += search.result_count;
+        // Zero durations are history written before they were kept
+        if search.duration_ms > 0 {
+            latencies[i].push(search.duration_ms);
+        }
+    }
+
+    let mut totals: BTreeMap<&str, usize> = BTreeMap::new();
+    for sample in samples {
+        let Some(i) = index(sample.timestamp) else {
+            continue;
+        };
+        *totals.entry(&sample.indexer).or_default() += sample.result_count;
+        let (counts, add) = if sample.success {
+            (&mut buckets[i].indexer_results, sample.result_count)
+        } else {
+            (&mut buckets[i].indexer_failures, 1)
+        };
+        *counts.entry(sample.indexer.clone()).or_default() += add;
+    }
+
+    for (bucket, samples) in buckets.iter_mut().zip(&mut latencies) {
+        bucket.p50_ms = percentile(samples, 50.0);
+        bucket.p90_ms = percentile(samples, 90.0);
+        bucket.p99_ms = percentile(samples, 99.0);
+    }
+
+    let mut indexers: Vec<(&str, usize)> = totals.into_iter().collect();
+    indexers.sort_by_key(|&(_, total)| std::cmp::Reverse(total));
+    Timeseries {
+        bucket_minutes: bucket.num_minutes(),
+        indexers: indexers.into_iter().map(|(id, _)| id.to_string()).collect(),
+        buckets,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn search(at: DateTime<Utc>, result_count: usize, duration_ms: u64) -> SearchLog {
+        SearchLog {
+            query: "ubuntu".to_string(),
+            indexer: "all".to_string(),
+            timestamp: at,
+            result_count,
+            duration_ms,
+        }
+    }
+
+    fn sample(
+        indexer: &str,
+        at: DateTime<Utc>,
+        success: bool,
+        result_count: usize,
+    ) -> IndexerSample {
+        IndexerSample {
+            indexer: indexer.to_string(),
+            timestamp: at,
+            success,
+            result_count,
+        }
+    }
+
+    #[test]
+    fn test_build() {
+        let at = |h, m| Utc.with_ymd_and_hms(2026, 5, 1, h, m, 0).unwrap();
+        let searches = [
+            search(at(9, 5), 10, 100),
+            search(at(9, 40), 0, 300),
+            search(at(9, 50), 5, 0),
+            search(at(11, 59), 20, 1000),
+            // Before the period
+            search(at(7, 45), 1, 1),
+        ];
+        let samples = [
+            sample("yts", at(9, 5), true, 4),
+            sample("eztv", at(9, 5), true, 6),
+            sample("eztv", at(11, 59), true, 20),
+            sample("yts", at(11, 59), false, 0),
+        ];
+        let series = build(
+            at(8, 30),
+            at(11, 59),
+            Duration::hours(1),
+            &searches,
+            &samples,
+        );
+
+        assert_eq!(series.bucket_minutes, 60);
+        let starts: Vec<_> = series.buckets.iter().map(|b| b.start).collect();
+        assert_eq!(starts, [at(8, 0), at(9, 0), at(10, 0), at(11, 0)]);
+        assert_eq!(series.indexers, ["eztv", "yts"]);
+
+        let nine = &series.buckets[1];
+        assert_eq!((nine.searches, nine.results), (3, 15));
+        assert_eq!((nine.p50_ms, nine.p99_ms), (Some(100), Some(300)));
+        assert_eq!(nine.indexer_results["yts"], 4);
+        assert!(nine.indexer_failures.is_empty());
+
+        let ten = &series.buckets[2];
+        assert_eq!(ten.searches, 0);
+        assert_eq!(ten.p50_ms, None);
+
+        let eleven = &series.buckets[3];
+        assert_eq!(eleven.indexer_results["eztv"], 20);
+        assert_eq!(eleven.indexer_failures["yts"], 1);
+        assert!(!eleven.indexer_results.contains_key("yts"));
+    }
+}
@@ -0,0 +1,269 @@
+//! Request authentication (`[server.auth]`)
+//!
+//! Each configured [`Authenticator`] looks at a request in turn, and the
+//! first one that recognizes it lets it in or turns it away. A request none
+//! of them recognizes is refused. With nothing configured, every request is
+//! allowed, as before. The web UI's static files are always served, so the
+//! login page can load.
+
+use crate::config::AuthConfig;
+use axum::extract::{ConnectInfo, Request, State};
+use axum::http::{HeaderName, StatusCode};
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+use ipnet::IpNet;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::Arc;
+
+/// What an authenticator made of a request
+#[derive(Debug, PartialEq)]
+pub(super) enum Outcome {
+    /// Comes from this user
+    Allow(String),
+    /// Recognized, but not let in
+    Deny(StatusCode, &'static str),
+    /// Carries nothing this authenticator understands
+    Pass,
+}
+
+/// One way for a request to prove who it comes from
+pub(super) trait Authenticator: Send + Sync {
+    /// `peer` is the address of the connecting client, if known
+    fn authenticate(&self, req: &Request, peer: Option<IpAddr>) -> Outcome;
+}
+
+/// The authenticated user, added to the request's extensions
+#[derive(Debug, Clone)]
+pub(super) struct AuthUser(pub String);
+
+/// Paths open to everyone, so health checks and the login itself work
+const PUBLIC_PATHS: [&str; 3] = ["/healthz", "/api/auth/login", "/api/auth/logout"];
+
+/// The configured authenticators, tried in order
+#[derive(Clone, Default)]
+pub(super) struct Auth {
+    methods: Arc<Vec<Box<dyn Authenticator>>>,
+}
+
+impl Auth {
+    pub(super) fn new(config: &AuthConfig, pool: crate::db::DbPool) -> anyhow::Result<Self> {
+        let mut methods: Vec<Box<dyn Authenticator>> = Vec::new();
+        if let Some(header) = config
+            .forward_user_header
+            .as_deref()
+            .filter(|h| !h.is_empty())
+        {
+            methods.push(Box::new(ForwardAuth::new(config, header)?));
+        }
+        if config.login_enabled() {
+            methods.push(Box::new(super::session::SessionAuth { pool }));
+        }
+        if let Some(key) = config.api_key.as_deref().filter(|k| !k.is_empty()) {
+            methods.push(Box::new(ApiKey(key.to_string())));
+        }
+        Ok(Self {
+            methods: Arc::new(methods),
+        })
+    }
+
+    fn authenticate(&self, req: &Request, peer: Option<IpAddr>) -> Outcome {
+        self.methods
+            .iter()
+            .map(|method| method.authenticate(req, peer))
+            .find(|outcome| *outcome != Outcome::Pass)
+            .unwrap_or(Outcome::Pass)
+    }
+}
+
+/// Middleware refusing requests no authenticator lets in
+pub(super) async fn require_auth(
+    State(auth): State<Auth>,
+    mut req: Request,
+    next: Next,
+) -> Response {
+    if auth.methods.is_empty() || PUBLIC_PATHS.contains(&req.uri().path()) {
+        return next.run(req).await;
+    }
+    let peer = req
+        .extensions()
+        .get::<ConnectInfo<SocketAddr>>()
+        .map(|info| info.0.ip().to_canonical());
+    match auth.authenticate(&req, peer) {
+        Outcome::Allow(user) => {
+            req.extensions_mut().insert(AuthUser(user));
+            next.run(req).await
+        }
+        Outcome::Deny(status, reason) => (status, reason).into_response(),
+        Outcome::Pass => (StatusCode::UNAUTHORIZED, "Authentication required").into_response(),
+    }
+}
+
+/// The `apikey` query parameter or `X-Api-Key` header
+struct ApiKey(String);
+
+impl Authenticator for ApiKey {
+    fn authenticate(&self, req: &Request, _peer: Option<IpAddr>) -> Outcome {
+        let from_query = req.uri().query().and_then(|query| {
+            url::form_urlencoded::parse(query.as_bytes())
+                .find(|(name, _)| name == "apikey")
+                .map(|(_, value)| value.into_owned())
+        });
+        let from_header = || {
+            req.headers()
+                .get("x-api-key")
+                .and_then(|v| v.to_str().ok())
+                .map(str::to_string)
+        };
+        match from_query.or_else(from_header) {
+            Some(key) if key == self.0 => Outcome::Allow("apikey".to_string()),
+            Some(_) => Outcome::Deny(StatusCode::UNAUTHORIZED, "Invalid API key"),
+            None => Outcome::Pass,
+        }
+    }
+}
+
+/// A user header set by a trusted reverse proxy that already signed the
+/// user in, so Lodestarr never sees their credentials
+struct ForwardAuth {
+    user_header: HeaderName,
+    groups_header: Option<HeaderName>,
+    required_group: Option<String>,
+    trusted: Vec<IpNet>,
+}
+
+impl ForwardAuth {
+    fn new(config: &AuthConfig, user_header: &str) -> anyhow::Result<Self> {
+        let groups_header = config
+            .forward_groups_header
+            .as_deref()
+            .map(HeaderName::try_from)
+            .transpose()?;
+        if config.required_group.is_some() && groups_header.is_none() {
+            anyhow::bail!("server.auth.required_group needs forward_groups_header");
+        }
+        Ok(Self {
+            user_header: HeaderName::try_from(user_header)?,
+            groups_header,
+            required_group: config.required_group.clone(),
+            trusted: config.trusted_networks().map_err(anyhow::Error::msg)?,
+        })
+    }
+
+    /// Only loopback unless `trusted_proxies` lists the proxy: any other
+    /// host on the network could send the header itself
+    fn trusts(&self, peer: IpAddr) -> bool {
+        if self.trusted.is_empty() {
+            return peer.is_loopback();
+        }
+        self.trusted.iter().any(|net| net.contains(&peer))
+    }
+}
+
+impl Authenticator for ForwardAuth {
+    fn authenticate(&self, req: &Request, peer: Option<IpAddr>) -> Outcome {
+        let header = |name: &HeaderName| {
+            req.headers()
+                .get(name)
+                .and_then(|v| v.to_str().ok())
+                .map(str::trim)
+                .filter(|v| !v.is_empty())
+        };
+        let Some(user) = header(&self.user_header) else {
+            return Outcome::Pass;
+        };
+        if !peer.is_some_and(|ip| self.trusts(ip)) {
+            tracing::warn!(
+                "Ignoring {} header from untrusted address {:?}",
+                self.user_header,
+                peer
+            );
+            return Outcome::Pass;
+        }
+        if let Some(group) = &self.required_group {
+            let groups = self.groups_header.as_ref().and_then(header).unwrap_or("");
+            if !groups.split(',').any(|g| g.trim() == group) {
+                return Outcome::Deny(StatusCode::FORBIDDEN, "Not in the required group");
+            }
+        }
+        Outcome::Allow(user.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::Body;
+
+    #[test]
+    fn test_authenticate() {
+        let pool = crate::db::init_db(":memory:");
+        let auth = Auth::new(
+            &AuthConfig {
+                api_key: Some("secret".into()),
+                forward_user_header: Some("Remote-User".into()),
+                forward_groups_header: Some("Remote-Groups".into()),
+                required_group: Some("media".into()),
+                trusted_proxies: vec!["172.16.0.0/12".into()],
+                ..Default::default()
+            },
+            pool,
+        )
+        .unwrap();
+        let proxy = Some("172.18.0.5".parse().unwrap());
+        let request = |uri: &str, headers: &[(&str, &str)]| {
+            let mut req = Request::builder().uri(uri);
+            for (name, value) in headers {
+                req = req.header(*name, *value);
+            }
+            req.body(Body::empty()).unwrap()
+        };
+
+        let sso = request(
+            "/",
+            &[("Remote-User", "ann"), ("Remote-Groups", "dev, media")],
+        );
+        assert_eq!(auth.authenticate(&sso, proxy), Outcome::Allow("ann".into()));
+        // The same headers straight from a client aren't trusted
+        let direct = Some("203.0.113.9".parse().unwrap());
+        assert_eq!(auth.authenticate(&sso, direct), Outcome::Pass);
+
+        let outsider = request("/", &[("Remote-User", "bob"), ("Remote-Groups", "dev")]);
+        assert!(matches!(
+            auth.authenticate(&outsider, proxy),
+            Outcome::Deny(StatusCode::FORBIDDEN, _)
+        ));
+
+        let arr = request(
+            "/api/v2.0/indexers/all/results/torznab?t=caps&apikey=secret",
+            &[],
+        );
+        assert_eq!(
+            auth.authenticate(&arr, direct),
+            Outcome::Allow("apikey".into())
+        );
+        let wrong = request("/api/info", &[("X-Api-Key", "guess")]);
+        assert!(matches!(
+            auth.authenticate(&wrong, direct),
+            Outcome::Deny(StatusCode::UNAUTHORIZED, _)
+        ));
+        assert_eq!(auth.authenticate(&request("/", &[]), proxy), Outcome::Pass);
+
+        // Without trusted_proxies, only a proxy on the same host is believed
+        let local = Auth::new(
+            &AuthConfig {
+                forward_user_header: Some("Remote-User".into()),
+                ..Default::default()
+            },
+            crate::db::init_db(":memory:"),
+        )
+        .unwrap();
+        let admin = request("/", &[("Remote-User", "admin")]);
+        let lan = Some("192.168.1.20".parse().unwrap());
+        assert_eq!(local.authenticate(&admin, lan), Outcome::Pass);
+        let loopback = Some("127.0.0.1".parse().unwrap());
+        assert_eq!(
+            local.authenticate(&admin, loopback),
+            Outcome::Allow("admin".into())
+        );
+    }
+}
@@ -0,0 +1,282 @@
+//! RSS feeds for RSS readers and simple automations
+//!
+//! - `/feeds/saved/{id}.rss` runs a saved search and lists its results
+//! - `/feeds/downloads.rss` lists recent grabs from the download history
+//!
+//! Saved searches are managed through `/api/saved-searches`.
+
+use super::AppState;
+use super::api_indexers::{TorznabParams, search_all_indexers, search_native_indexer};
+use crate::config::IndexerSelection;
+use crate::models::TorrentResult;
+use crate::torznab::{SearchParams, TorznabClient};
+use anyhow::Context;
+use axum::{
+    Json,
+    extract::{Path, State},
+    http::{HeaderMap, StatusCode, header},
+    response::IntoResponse,
+};
+use serde::Deserialize;
+
+/// Items per feed
+const FEED_LIMIT: usize = 100;
+
+#[derive(Deserialize)]
+pub(super) struct SavedSearchRequest {
+    /// Feed title; defaults to the query
+    name: Option<String>,
+    query: String,
+    indexer: Option<String>,
+    category: Option<String>,
+}
+
+pub(super) async fn list_saved_searches(State(state): State<AppState>) -> impl IntoResponse {
+    match crate::db::get_saved_searches(&state.db_pool) {
+        Ok(searches) => Json(searches).into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, format!("Error: {}", e)).into_response(),
+    }
+}
+
+pub(super) async fn add_saved_search(
+    State(state): State<AppState>,
+    Json(payload): Json<SavedSearchRequest>,
+) -> impl IntoResponse {
+    let query = payload.query.trim();
+    if query.is_empty() {
+        return (StatusCode::BAD_REQUEST, "Query is required").into_response();
+    }
+    let name = payload
+        .name
+        .as_deref()
+        .map(str::trim)
+        .filter(|n| !n.is_empty())
+        .unwrap_or(query);
+    let non_empty = |v: &Option<String>| v.clone().filter(|s| !s.is_empty());
+    let indexer = non_empty(&payload.indexer);
+    let category = non_empty(&payload.category);
+
+    match crate::db::add_saved_search(
+        &state.db_pool,
+        name,
+        query,
+        indexer.as_deref(),
+        category.as_deref(),
+    ) {
+        Ok(id) => match crate::db::get_saved_search(&state.db_pool, id) {
+            Ok(Some(saved)) => (StatusCode::CREATED, Json(saved)).into_response(),
+            Ok(None) => StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+            Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, format!("Error: {}", e)).into_response(),
+        },
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Failed to save search: {}", e),
+        )
+            .into_response(),
+    }
+}
+
+pub(super) async fn delete_saved_search(
+    State(state): State<AppState>,
+    Path(id): Path<i64>,
+) -> impl IntoResponse {
+    match crate::db::delete_saved_search(&state.db_pool, id) {
+        Ok(true) => (StatusCode::OK, "Saved search deleted").into_response(),
+        Ok(false) => (StatusCode::NOT_FOUND, "Saved search not found").into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, format!("Error: {}", e)).into_response(),
+    }
+}
+
+/// `/feeds/saved/{id}.rss`
+pub(super) async fn saved_search_feed(
+    State(state): State<AppState>,
+    Path(file): Path<String>,
+    headers: HeaderMap,
+) -> impl IntoResponse {
+    let Some(id) = file
+        .strip_suffix(".rss")
+        .and_then(|id| id.parse::<i64>().ok())
+    else {
+        return (StatusCode::NOT_FOUND, "Feed not found").into_response();
+    };
+    let saved = match crate::db::get_saved_search(&state.db_pool, id) {
+        Ok(Some(saved)) => saved,
+        Ok(None) => return (StatusCode::NOT_FOUND, "Feed not found").into_response(),
+        Err(e) => {
+            return (StatusCode::INTERNAL_SERVER_ERROR, format!("Error: {}", e)).into_response();
+        }
+    };
+
+    let mut results = match search_saved(
+        &state,
+        &saved.query,
+        saved.indexer.as_deref(),
+        saved.category.clone(),
+    )
+    .await
+    {
+        Ok(Some(results)) => results,
+        Ok(None) => {
+            let id = saved.indexer.as_deref().unwrap_or_default();
+            return (StatusCode::NOT_FOUND, format!("Indexer not found: {}", id)).into_response();
+        }
+        Err(e) => return (StatusCode::BAD_GATEWAY, format!("{:#}", e)).into_response(),
+    };
+    // Newest first, as feed readers expect
+    results.sort_by_key(|r| std::cmp::Reverse(r.publish_date));
+    results.truncate(FEED_LIMIT);
+
+    let base_url = base_url(&headers);
+    let indexer = saved.indexer.as_deref().unwrap_or("all");
+    let (magnets, api_key) = {
+        let config = state.config.read().await;
+        (config.magnet_preference, config.server.auth.api_key.clone())
+    };
+    rss_response(crate::torznab::generate_results_xml(
+        &results,
+        &saved.name,
+        Some(&base_url),
+        Some(indexer),
+        api_key.as_deref(),
+        magnets,
+    ))
+}
+
+/// Run a saved query on one native or proxied indexer, or all of them.
+/// `None` if the indexer doesn't exist, an error if its search failed.
+/// Searching all indexers leaves out the ones that fail.
+pub(super) async fn search_saved(
+    state: &AppState,
+    query: &str,
+    indexer: Option<&str>,
+    category: Option<String>,
+) -> anyhow::Result<Option<Vec<TorrentResult>>> {
+    let params = TorznabParams {
+        t: Some("search".to_string()),
+        q: Some(query.to_string()),
+        cat: category,
+        ..Default::default()
+    };
+    let Some(id) = indexer else {
+        let results = search_all_indexers(state, "search", &params, &IndexerSelection::All).await;
+        return Ok(Some(results));
+    };
+
+    let definition = state.native_indexers.read().await.get_definition(id).await;
+    if let Some(definition) = definition {
+        let results = search_native_indexer(state, &definition, "search", &params)
+            .await
+            .with_context(|| format!("Search for '{}' failed on {}", query, id))?;
+        return Ok(Some(results));
+    }
+
+    let client = {
+        let config = state.config.read().await;
+        let Some(idx) = config.get_indexer(id) else {
+            return Ok(None);
+        };
+        TorznabClient::new(&idx.url, idx.apikey.as_deref(), config.proxy_url.as_deref())?
+    };
+    let search = SearchParams {
+        query: query.to_string(),
+        search_type: "search".to_string(),
+        cat: params.cat,
+        ..Default::default()
+    };
+    let mut results = client
+        .search(&search)
+        .await
+        .with_context(|| format!("Search for '{}' failed on {}", query, id))?;
+    for r in &mut results {
+        r.indexer = Some(id.to_string());
+    }
+    Ok(Some(results))
+}
+
+/// `/feeds/downloads.rss`
+pub(super) async fn downloads_feed(State(state): State<AppState>) -> impl IntoResponse {
+    match state.store.download_logs(FEED_LIMIT).await {
+        Ok(logs) => rss_response(downloads_xml(&logs)),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, format!("Error: {}", e)).into_response(),
+    }
+}
+
+pub(super) fn base_url(headers: &HeaderMap) -> String {
+    let host = headers
+        .get("host")
+        .and_then(|h| h.to_str().ok())
+        .unwrap_or("localhost:3420");
+    format!("http://{}", host)
+}
+
+fn rss_response(xml: String) -> axum::response::Response {
+    (
+        StatusCode::OK,
+        [(header::CONTENT_TYPE, "application/rss+xml; charset=utf-8")],
+        xml,
+    )
+        .into_response()
+}
+
+fn downloads_xml(logs: &[crate::db::DownloadLog]) -> String {
+    use crate::torznab::escape_xml;
+
+    let mut xml = String::from(r#"<?xml version="1.0" encoding="UTF-8"?>"#);
+    xml.push_str("\n<rss version=\"2.0\">\n<channel>\n");
+    xml.push_str("  <title>Lodestarr Downloads</title>\n");
+    xml.push_str("  <description>Recently grabbed releases</description>\n");
+    for log in logs {
+        let title = log.title.as_deref().unwrap_or("Untitled");
+        let destination = match &log.client_name {
+            Some(client) => format!("Sent to {}", client),
+            None => format!("Downloaded ({})", log.download_type),
+        };
+        xml.push_str("  <item>\n");
+        xml.push_str(&format!("    <title>{}</title>\n", escape_xml(title)));
+        xml.push_str(&format!(
+            "    <guid isPermaLink=\"false\">lodestarr-download-{}</guid>\n",
+            log.id
+        ));
+        if let Some(link) = log.download_link.as_ref().or(log.magnet.as_ref()) {
+            xml.push_str(&format!("    <link>{}</link>\n", escape_xml(link)));
+        }
+        xml.push_str(&format!(
+            "    <description>{}</description>\n",
+            escape_xml(&destination)
+        ));
+        xml.push_str(&format!(
+            "    <pubDate>{}</pubDate>\n",
+            log.timestamp.format("%a, %d %b %Y %H:%M:%S +0000")
+        ));
+        xml.push_str("  </item>\n");
+    }
+    xml.push_str("</channel>\n</rss>\n");
+    xml
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::DownloadLog;
+
+    #[test]
+    fn test_downloads_xml() {
+        let logs = vec![DownloadLog {
+            id: 7,
+            title: Some("Ubuntu & Friends".to_string()),
+            magnet: Some("magnet:?xt=urn:btih:abc&dn=x".to_string()),
+            download_link: None,
+            client_name: Some("qBittorrent".to_string()),
+            download_type: "client".to_string(),
+            timestamp: chrono::DateTime::parse_from_rfc3339("2024-05-01T10:00:00Z")
+                .unwrap()
+                .into(),
+        }];
+        let xml = downloads_xml(&logs);
+        assert!(xml.contains("<title>Ubuntu &amp; Friends</title>"));
+        assert!(xml.contains("<link>magnet:?xt=urn:btih:abc&amp;dn=x</link>"));
+        assert!(xml.contains("<description>Sent to qBittorrent</description>"));
+        assert!(xml.contains("<pubDate>Wed, 01 May 2024 10:00:00 +0000</pubDate>"));
+        assert!(xml.contains("lodestarr-download-7"));
+    }
+}
@@ -0,0 +1,311 @@
+//! Poster/image proxy (`/api/image-proxy?u=<url>&indexer=<id>`)
+//!
+//! Poster URLs point at tracker domains the browser often can't reach
+//! (cookies, VPN, blocked in the user's country). Images are fetched through
+//! the indexer's executor, so they get its session and proxy, and kept in an
+//! on-disk cache next to the database. An indexer's cookies and headers are
+//! only sent to hosts its links cover, and local addresses are refused so the
+//! proxy can't be pointed at internal services.
+
+use super::AppState;
+use crate::indexer::SearchExecutor;
+use crate::indexer::definition::IndexerDefinition;
+use axum::{
+    extract::{Query, State},
+    http::{StatusCode, header},
+    response::IntoResponse,
+};
+use serde::Deserialize;
+use sha1::{Digest, Sha1};
+use std::net::IpAddr;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+use url::{Host, Url};
+
+/// Largest image served
+const MAX_IMAGE_BYTES: usize = 5 * 1024 * 1024;
+/// Cache size before the least recently used images are removed
+const MAX_CACHE_BYTES: u64 = 200 * 1024 * 1024;
+/// What the cache is trimmed down to, so it isn't pruned on every store
+const PRUNE_TO_BYTES: u64 = MAX_CACHE_BYTES * 9 / 10;
+
+#[derive(Deserialize)]
+pub(super) struct ImageProxyParams {
+    u: String,
+    /// Indexer whose session to use, if its links cover the URL's host;
+    /// guessed from the host if omitted
+    indexer: Option<String>,
+}
+
+pub(super) async fn image_proxy(
+    State(state): State<AppState>,
+    Query(params): Query<ImageProxyParams>,
+) -> impl IntoResponse {
+    let url = match Url::parse(&params.u) {
+        Ok(url) if matches!(url.scheme(), "http" | "https") => url,
+        _ => return (StatusCode::BAD_REQUEST, "Invalid image URL").into_response(),
+    };
+    if !is_public(&url).await {
+        return (
+            StatusCode::BAD_REQUEST,
+            "Image URL points at a local address",
+        )
+            .into_response();
+    }
+
+    let config = state.config.read().await;
+    let cache_dir = match config.get_image_cache_path() {
+        Ok(dir) => dir,
+        Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    };
+    let key = hex::encode(Sha1::digest(url.as_str().as_bytes()));
+    if let Some((content_type, body)) = read_cached(&cache_dir, &key).await {
+        return image_response(content_type, body);
+    }
+
+    let definitions = state
+        .native_indexers
+        .read()
+        .await
+        .list_all_definitions()
+        .await;
+    let indexer = site_indexer(&definitions, &url, params.indexer.as_deref());
+    if indexer.is_none()
+        && let Some(id) = &params.indexer
+    {
+        tracing::debug!("Not sending {}'s session to {}", id, url);
+    }
+    let settings = indexer.and_then(|d| config.native_settings.get(&d.id).cloned());
+    let executor = match indexer {
+        Some(d) => {
+            SearchExecutor::for_indexer(&d.id, config.proxy_url.as_deref(), settings.as_ref())
+        }
+        None => SearchExecutor::new(config.proxy_url.as_deref()),
+    };
+    drop(config);
+    let executor = match executor {
+        Ok(e) => e,
+        Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    };
+
+    match executor
+        .fetch_image(url.as_str(), MAX_IMAGE_BYTES, settings.as_ref())
+        .await
+    {
+        Ok((content_type, body)) => {
+            if let Err(e) = store(&cache_dir, &key, &content_type, &body).await {
+                tracing::warn!("Failed to cache image {}: {}", url, e);
+            }
+            image_response(content_type, body)
+        }
+        Err(e) => {
+            tracing::debug!("Image proxy failed for {}: {}", url, e);
+            let kind = crate::indexer::error::kind_of(&e);
+            (kind.http_status(), format!("Image fetch failed: {}", e)).into_response()
+        }
+    }
+}
+
+fn image_response(content_type: String, body: Vec<u8>) -> axum::response::Response {
+    (
+        [
+            (header::CONTENT_TYPE, content_type),
+            (header::CACHE_CONTROL, "public, max-age=604800".to_string()),
+        ],
+        body,
+    )
+        .into_response()
+}
+
+/// Indexer whose settings and session may go with a request for `url`: the
+/// requested one (or any, without a request) whose links cover the URL's host
+fn site_indexer<'a>(
+    definitions: &'a [IndexerDefinition],
+    url: &Url,
+    requested: Option<&str>,
+) -> Option<&'a IndexerDefinition> {
+    let host = url.host_str()?;
+    definitions
+        .iter()
+        .filter(|d| requested.is_none_or(|id| d.id == id))
+        .find(|d| {
+            d.links.iter().chain(&d.legacylinks).any(|link| {
+                Url::parse(link)
+                    .ok()
+                    .and_then(|l| l.host_str().map(|h| same_site(host, h)))
+                    .unwrap_or(false)
+            })
+        })
+}
+
+/// `url` doesn't point at this machine or the local network. Hostnames that
+/// don't resolve here are let through: they may only resolve over DoH or the
+/// proxy, which can't reach local addresses either.
+async fn is_public(url: &Url) -> bool {
+    match url.host() {
+        Some(Host::Ipv4(ip)) => is_public_ip(IpAddr::V4(ip)),
+        Some(Host::Ipv6(ip)) => is_public_ip(IpAddr::V6(ip)),
+        Some(Host::Domain(domain)) => {
+            let domain = domain.to_ascii_lowercase();
+            if domain == "localhost" || domain.ends_with(".localhost") {
+                return false;
+            }
+            let port = url.port_or_known_default().unwrap_or(80);
+            match tokio::net::lookup_host((domain.as_str(), port)).await {
+                Ok(mut addrs) => addrs.all(|addr| is_public_ip(addr.ip())),
+                Err(_) => true,
+            }
+        }
+        None => false,
+    }
+}
+
+fn is_public_ip(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => {
+            !(ip.is_loopback()
+                || ip.is_private()
+                || ip.is_link_local()
+                || ip.is_unspecified()
+                || ip.is_broadcast())
+        }
+        IpAddr::V6(ip) => match ip.to_ipv4_mapped() {
+            Some(ip) => is_public_ip(IpAddr::V4(ip)),
+            None => {
+                !(ip.is_loopback()
+                    || ip.is_unspecified()
+                    || ip.is_unique_local()
+                    || ip.is_unicast_link_local())
+            }
+        },
+    }
+}
+
+/// Host `host` belongs to the site at `site` (same host or a subdomain)
+fn same_site(host: &str, site: &str) -> bool {
+    let site = site.trim_start_matches("www.");
+    host == site || host.ends_with(&format!(".{}", site))
+}
+
+async fn read_cached(dir: &Path, key: &str) -> Option<(String, Vec<u8>)> {
+    let path = dir.join(key);
+    let body = tokio::fs::read(&path).await.ok()?;
+    let content_type = tokio::fs::read_to_string(dir.join(format!("{}.type", key)))
+        .await
+        .ok()?;
+    touch(path).await;
+    Some((content_type, body))
+}
+
+/// Mark a cached file as recently used for pruning
+pub(super) async fn touch(path: PathBuf) {
+    let _ = tokio::task::spawn_blocking(move || {
+        if let Ok(file) = std::fs::File::options().write(true).open(&path) {
+            let _ = file.set_modified(SystemTime::now());
+        }
+    })
+    .await;
+}
+
+async fn store(dir: &Path, key: &str, content_type: &str, body: &[u8]) -> anyhow::Result<()> {
+    tokio::fs::create_dir_all(dir).await?;
+    tokio::fs::write(dir.join(format!("{}.type", key)), content_type).await?;
+    tokio::fs::write(dir.join(key), body).await?;
+    let dir = dir.to_path_buf();
+    tokio::task::spawn_blocking(move || prune(&dir, MAX_CACHE_BYTES, PRUNE_TO_BYTES)).await??;
+    Ok(())
+}
+
+/// Remove least recently used files once a cache outgrows `max_bytes`,
+/// down to `prune_to` bytes
+pub(super) fn prune(dir: &Path, max_bytes: u64, prune_to: u64) -> anyhow::Result<()> {
+    let mut images: Vec<(PathBuf, u64, SystemTime)> = Vec::new();
+    for entry in std::fs::read_dir(dir)? {
+        let entry = entry?;
+        let path = entry.path();
+        if path.extension().is_some() {
+            continue;
+        }
+        let meta = entry.metadata()?;
+        images.push((path, meta.len(), meta.modified()?));
+    }
+    let mut total: u64 = images.iter().map(|(_, size, _)| size).sum();
+    if total <= max_bytes {
+        return Ok(());
+    }
+    images.sort_by_key(|(_, _, modified)| *modified);
+    for (path, size, _) in images {
+        if total <= prune_to {
+            break;
+        }
+        std::fs::remove_file(&path)?;
+        let _ = std::fs::remove_file(path.with_extension("type"));
+        total -= size;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_same_site() {
+        assert!(same_site("rutracker.org", "rutracker.org"));
+        assert!(same_site("static.rutracker.org", "www.rutracker.org"));
+        assert!(!same_site("evilrutracker.org", "rutracker.org"));
+    }
+
+    #[test]
+    fn test_site_indexer_requires_matching_host() {
+        let definitions = vec![
+            IndexerDefinition::from_yaml(
+                r#"
+id: tracker
+name: Tracker
+links: ["https://tracker.example/"]
+search:
+  paths: [{path: search}]
+  rows: {selector: tr}
+  fields: {title: {selector: a}}
+"#,
+            )
+            .unwrap(),
+        ];
+        let poster = Url::parse("https://img.tracker.example/poster.jpg").unwrap();
+        let elsewhere = Url::parse("https://attacker.example/steal.jpg").unwrap();
+
+        let matched = site_indexer(&definitions, &poster, Some("tracker"));
+        assert_eq!(matched.map(|d| d.id.as_str()), Some("tracker"));
+        assert!(site_indexer(&definitions, &poster, None).is_some());
+        // An explicit indexer= for another host gets no cookies or session
+        assert!(site_indexer(&definitions, &elsewhere, Some("tracker")).is_none());
+        assert!(site_indexer(&definitions, &elsewhere, None).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_local_targets_are_refused() {
+        for target in [
+            "http://127.0.0.1:8080/admin",
+            "http://localhost/",
+            "http://192.168.1.1/",
+            "http://169.254.169.254/latest/meta-data/",
+            "http://[::1]/",
+            "http://[fd00::1]/",
+            "http://[::ffff:10.0.0.1]/",
+        ] {
+            assert!(!is_public(&Url::parse(target).unwrap()).await, "{}", target);
+        }
+        assert!(is_public(&Url::parse("http://93.184.216.34/x.jpg").unwrap()).await);
+
+        let state = AppState::for_tests(crate::config::Config::default(), "image-proxy-local");
+        let params = ImageProxyParams {
+            u: "http://127.0.0.1:1/poster.jpg".to_string(),
+            indexer: None,
+        };
+        let response = image_proxy(State(state), Query(params))
+            .await
+            .into_response();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+}
@@ -0,0 +1,125 @@
+//! Adaptive per-indexer deadlines for aggregate searches
+//!
+//! Every indexer search run through [`timed`] records its latency. With
+//! `[search] adaptive_timeout` enabled, an indexer's deadline is its recent p95
+//! latency times a factor, so one chronically slow tracker can't hold up an
+//! "all" search while normal variance is still tolerated.
+
+use crate::config::SearchConfig;
+use crate::db::DbPool;
+use crate::indexer::IndexerError;
+use crate::models::TorrentResult;
+use std::future::Future;
+use std::time::{Duration, Instant};
+
+/// Recent samples considered per indexer
+const WINDOW: usize = 50;
+/// Fewer samples than this and the indexer keeps its static timeout
+const MIN_SAMPLES: usize = 10;
+
+/// Value at `pct` (0-100) using nearest rank
+pub fn percentile(samples: &mut [u64], pct: f64) -> Option<u64> {
+    if samples.is_empty() {
+        return None;
+    }
+    samples.sort_unstable();
+    let rank = ((pct / 100.0) * samples.len() as f64).ceil() as usize;
+    Some(samples[rank.clamp(1, samples.len()) - 1])
+}
+
+/// Deadline for an indexer given its recent latencies (ms), if adaptive
+/// timeouts are enabled and there is enough history
+pub fn adaptive_timeout(config: &SearchConfig, mut samples: Vec<u64>) -> Option<Duration> {
+    if !config.adaptive_timeout || samples.len() < MIN_SAMPLES {
+        return None;
+    }
+    let p95 = percentile(&mut samples, 95.0)? as f64;
+    let min = config.adaptive_timeout_min_secs as f64;
+    let max = config
+        .adaptive_timeout_max_secs
+        .max(config.adaptive_timeout_min_secs) as f64;
+    let secs = (p95 / 1000.0 * config.adaptive_timeout_factor).clamp(min, max);
+    Some(Duration::from_secs_f64(secs))
+}
+
+/// Run one indexer's search under its adaptive deadline and record the
+/// latency, result count and bytes received, and whether it keeps failing
+pub async fn timed(
+    pool: &DbPool,
+    config: &SearchConfig,
+    indexer: &str,
+    search: impl Future<Output = anyhow::Result<Vec<TorrentResult>>>,
+) -> anyhow::Result<Vec<TorrentResult>> {
+    let deadline = adaptive_timeout(
+        config,
+        crate::db::get_indexer_latencies(pool, indexer, WINDOW).unwrap_or_default(),
+    );
+
+    let start = Instant::now();
+    let (result, bytes) = crate::http::traffic::metered(async {
+        match deadline {
+            Some(limit) => match tokio::time::timeout(limit, search).await {
+                Ok(result) => result,
+                Err(_) => Err(IndexerError::Network(format!(
+                    "{} timed out after {:.1}s (adaptive timeout)",
+                    indexer,
+                    limit.as_secs_f64()
+                ))
+                .into()),
+            },
+            None => search.await,
+        }
+    })
+    .await;
+
+    if let Err(e) = &result {
+        crate::indexer::backoff::note(indexer, e);
+    }
+    let error = result.as_ref().err().map(crate::indexer::error::kind_of);
+    let result_count = result.as_ref().ok().map(Vec::len);
+    let elapsed = start.elapsed().as_millis();
+    if let Err(e) = crate::db::log_indexer_latency(pool, indexer, elapsed, error, result_count) {
+        tracing::debug!("Failed to record latency for {}: {}", indexer, e);
+    }
+    if let Err(e) = crate::indexer::failing::record(pool, config, indexer, elapsed, error) {
+        tracing::debug!("Failed to record health of {}: {}", indexer, e);
+    }
+    if let Err(e) = crate::db::log_indexer_traffic(pool, indexer, "search", bytes) {
+        tracing::debug!("Failed to record traffic for {}: {}", indexer, e);
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_adaptive_timeout() {
+        let config = SearchConfig {
+            adaptive_timeout: true,
+            ..Default::default()
+        };
+        // 20 samples around 1s with two 4s outliers: p95 lands on an outlier
+        let mut samples = vec![1000; 18];
+        samples.extend([4000, 4000]);
+        assert_eq!(
+            adaptive_timeout(&config, samples.clone()),
+            Some(Duration::from_secs(12))
+        );
+
+        // Fast indexers still get the minimum, slow ones are capped
+        assert_eq!(
+            adaptive_timeout(&config, vec![100; 20]),
+            Some(Duration::from_secs(5))
+        );
+        assert_eq!(
+            adaptive_timeout(&config, vec![45_000; 20]),
+            Some(Duration::from_secs(60))
+        );
+
+        // Not enough history, or disabled
+        assert_eq!(adaptive_timeout(&config, vec![1000; 3]), None);
+        assert_eq!(adaptive_timeout(&SearchConfig::default(), samples), None);
+    }
+}
@@ -0,0 +1,202 @@
+//! Library of .torrent files already in the download path
+//!
+//! The download path is scanned every few minutes, and the name and infohash
+//! of each .torrent file are kept in the database. Only files that changed
+//! since the last scan are parsed again. Search results matching a library
+//! file, by infohash or exact name, are flagged `InLibrary`.
+//! `/api/library/search` finds library files by name.
+
+use super::AppState;
+use crate::db::{DbPool, LibraryEntry};
+use crate::models::TorrentResult;
+use axum::{
+    Json,
+    extract::{Query, State},
+    http::StatusCode,
+    response::IntoResponse,
+};
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+const SCAN_INTERVAL: Duration = Duration::from_secs(10 * 60);
+/// Subdirectories of the download path that are searched for .torrent files
+const MAX_DEPTH: usize = 3;
+
+/// .torrent files under `dir`, with their modification time
+fn find_torrents(dir: &Path, depth: usize, found: &mut Vec<(PathBuf, i64)>) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let Ok(meta) = entry.metadata() else {
+            continue;
+        };
+        if meta.is_dir() {
+            if depth < MAX_DEPTH {
+                find_torrents(&path, depth + 1, found);
+            }
+        } else if path
+            .extension()
+            .is_some_and(|e| e.eq_ignore_ascii_case("torrent"))
+        {
+            let modified = meta
+                .modified()
+                .ok()
+                .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+                .map_or(0, |d| d.as_secs() as i64);
+            found.push((path, modified));
+        }
+    }
+}
+
+/// Bring the library up to date with `dir`. Returns the number of files indexed.
+/// Reads the disk directly, so async code runs it on the blocking pool.
+pub fn scan(pool: &DbPool, dir: &Path) -> anyhow::Result<usize> {
+    let mut found = Vec::new();
+    find_torrents(dir, 0, &mut found);
+
+    let mut known = crate::db::get_library_mtimes(pool)?;
+    let mut indexed = 0;
+    for (path, modified) in found {
+        let path_str = path.to_string_lossy().into_owned();
+        if known.remove(&path_str) == Some(modified) {
+            continue;
+        }
+        let parsed = std::fs::read(&path)
+            .map_err(anyhow::Error::from)
+            .and_then(|bytes| crate::torrent::parse(&bytes));
+        match parsed {
+            Ok(torrent) => {
+                let entry = LibraryEntry {
+                    path: path_str,
+                    name: torrent.name,
+                    info_hash: torrent.info_hash,
+                    size: torrent.total_size,
+                    indexed_at: chrono::Utc::now(),
+                };
+                crate::db::upsert_library_entry(pool, &entry, modified)?;
+                indexed += 1;
+            }
+            Err(e) => tracing::debug!("Skipping {}: {}", path.display(), e),
+        }
+    }
+    // Whatever wasn't found again is gone (or the download path changed)
+    for gone in known.keys() {
+        crate::db::remove_library_entry(pool, gone)?;
+    }
+    Ok(indexed)
+}
+
+/// Start rescanning the configured download path in the background
+pub fn spawn(state: AppState) {
+    tokio::spawn(async move {
+        let mut tick = tokio::time::interval(SCAN_INTERVAL);
+        loop {
+            tick.tick().await;
+            let Some(dir) = state.config.read().await.download_path.clone() else {
+                continue;
+            };
+            let pool = state.db_pool.clone();
+            match tokio::task::spawn_blocking(move || scan(&pool, Path::new(&dir))).await {
+                Ok(Ok(n)) if n > 0 => tracing::info!("Indexed {} torrent files in library", n),
+                Ok(Ok(_)) => {}
+                Ok(Err(e)) => tracing::warn!("Library scan failed: {}", e),
+                Err(e) => tracing::warn!("Library scan panicked: {}", e),
+            }
+        }
+    });
+}
+
+/// Flag results whose torrent is already in the library
+pub fn flag(pool: &DbPool, results: &mut [TorrentResult]) {
+    let (hashes, names) = match crate::db::get_library_keys(pool) {
+        Ok(keys) => keys,
+        Err(e) => {
+            tracing::debug!("Failed to read library: {}", e);
+            return;
+        }
+    };
+    if hashes.is_empty() {
+        return;
+    }
+    for result in results {
+        result.in_library = crate::dedup::info_hash_of(result).is_some_and(|h| hashes.contains(&h))
+            || names.contains(&result.title.to_lowercase());
+    }
+}
+
+#[derive(Deserialize)]
+pub(super) struct LibrarySearchParams {
+    #[serde(default)]
+    q: String,
+    limit: Option<usize>,
+}
+
+/// Search the library by name (`/api/library/search?q=`)
+pub(super) async fn search_library(
+    State(state): State<AppState>,
+    Query(params): Query<LibrarySearchParams>,
+) -> impl IntoResponse {
+    match crate::db::search_library(&state.db_pool, &params.q, params.limit.unwrap_or(100)) {
+        Ok(entries) => Json(entries).into_response(),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Failed to search library: {}", e),
+        )
+            .into_response(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sha1::{Digest, Sha1};
+
+    #[test]
+    fn test_scan() {
+        let dir = std::env::temp_dir().join(format!("lodestarr-library-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(dir.join("movies")).unwrap();
+        let db_path = dir.join("test.db");
+        let pool = crate::db::init_db(&db_path);
+
+        let info = b"d6:lengthi1024e4:name8:Dune.mkv12:piece lengthi16384e6:pieces0:e";
+        let torrent = [b"d4:info".as_slice(), info, b"e"].concat();
+        std::fs::write(dir.join("movies/dune.torrent"), &torrent).unwrap();
+        std::fs::write(dir.join("broken.torrent"), b"not bencode").unwrap();
+
+        let expected_hash = hex::encode(Sha1::digest(info));
+        let parsed = crate::torrent::parse(&torrent).unwrap();
+        assert_eq!(
+            (parsed.name, parsed.info_hash, parsed.total_size),
+            ("Dune.mkv".to_string(), expected_hash.clone(), 1024)
+        );
+
+        assert_eq!(scan(&pool, &dir).unwrap(), 1);
+        // Unchanged files aren't parsed again
+        assert_eq!(scan(&pool, &dir).unwrap(), 0);
+        let found = crate::db::search_library(&pool, "dune", 10).unwrap();
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].info_hash, expected_hash);
+
+        let mut results = vec![
+            TorrentResult {
+                info_hash: Some(expected_hash.to_uppercase()),
+                ..TorrentResult::new("Dune 2021".into(), "a".into())
+            },
+            TorrentResult::new("dune.mkv".into(), "b".into()),
+            TorrentResult::new("Arrival".into(), "c".into()),
+        ];
+        flag(&pool, &mut results);
+        let flagged: Vec<bool> = results.iter().map(|r| r.in_library).collect();
+        assert_eq!(flagged, vec![true, true, false]);
+
+        std::fs::remove_file(dir.join("movies/dune.torrent")).unwrap();
+        scan(&pool, &dir).unwrap();
+        assert!(crate::db::search_library(&pool, "", 10).unwrap().is_empty());
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -11,7 +11,21 @@ mod api_indexers;
 mod api_info;
 mod api_native;
 mod api_settings;
+mod api_sports;
+mod api_timeseries;
+mod auth;
+mod feeds;
+mod image_proxy;
+mod latency;
+mod library;
+mod music;
+mod opds;
+mod rss_prefetch;
+mod rss_rules;
+mod session;
+mod single_flight;
 mod static_files;
+mod torrent_cache;
 
 use crate::config::Config;
 use crate::indexer::{IndexerDownloader, IndexerManager};
@@ -28,11 +42,21 @@ use tower_http::trace::TraceLayer;
 use api_clients::*;
 use api_indexers::*;
 use api_info::{
-    api_info, clear_all, clear_downloads, clear_stats, get_downloaded_links, get_downloads,
-    get_history, get_history_results, get_stats,
+    api_info, clear_all, clear_downloads, clear_stats, get_disk_usage, get_downloaded_links,
+    get_downloads, get_history, get_history_raw, get_history_results, get_indexer_stats,
+    get_search_timings, get_stats, healthz,
 };
 use api_native::*;
 use api_settings::*;
+use api_sports::sports_events;
+use api_timeseries::stats_timeseries;
+use feeds::*;
+use image_proxy::image_proxy;
+use music::music_artists;
+use opds::{opds_opensearch, opds_root, opds_search};
+use rss_rules::{
+    add_rss_rule, delete_rss_rule, list_rss_matches, list_rss_rules, run_rss_rule, update_rss_rule,
+};
 use static_files::static_handler;
 
 /// Shared application state
@@ -42,10 +66,32 @@ pub struct AppState {
     pub start_time: SystemTime,
     pub native_indexers: Arc<RwLock<IndexerManager>>,
     pub db_pool: crate::db::DbPool,
+    /// Search cache and history, possibly shared with other instances
+    pub store: crate::store::SharedStore,
     /// Cached list of available indexers from GitHub (loaded at startup, refreshed on demand)
     pub cached_github_indexers: Arc<RwLock<Vec<crate::indexer::AvailableIndexer>>>,
 }
 
+#[cfg(test)]
+impl AppState {
+    /// State for handler tests: `config`, no native indexers, and a fresh
+    /// database in a temp file named after `name`
+    pub(crate) fn for_tests(config: Config, name: &str) -> Self {
+        let path =
+            std::env::temp_dir().join(format!("lodestarr-test-{}-{}.db", name, std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let db_pool = crate::db::init_db(&path);
+        Self {
+            config: Arc::new(RwLock::new(config)),
+            start_time: SystemTime::now(),
+            native_indexers: Arc::new(RwLock::new(IndexerManager::new(None))),
+            store: Arc::new(crate::store::SqliteStore::new(db_pool.clone())),
+            db_pool,
+            cached_github_indexers: Arc::new(RwLock::new(Vec::new())),
+        }
+    }
+}
+
 /// Start the web server
 pub async fn start_server(config: Config, host: &str, port: u16) -> anyhow::Result<()> {
     // Initialize native indexer manager
@@ -109,31 +155,59 @@ pub async fn start_server(config: Config, host: &str, port: u16) -> anyhow::Resu
     };
 
     let db_pool = crate::db::init_db(db_path);
+    let store = crate::store::open(&config.store, &db_pool)?;
+    tracing::info!("Keeping the search cache and history in {}", store.name());
 
     // Clean up expired cache entries at startup
-    if let Err(e) = crate::db::cleanup_cache(&db_pool) {
+    if let Err(e) = store.cleanup_cache().await {
         tracing::warn!("Failed to cleanup expired cache: {}", e);
     }
 
+    if config.check_updates {
+        crate::update::spawn_daily(config.proxy_url.clone());
+    }
+
     let state = AppState {
         config: Arc::new(RwLock::new(config)),
         start_time: SystemTime::now(),
         native_indexers: Arc::new(RwLock::new(native_manager)),
         db_pool,
+        store,
         cached_github_indexers: Arc::new(RwLock::new(github_indexers)),
     };
 
+    let auth = auth::Auth::new(
+        &state.config.read().await.server.auth,
+        state.db_pool.clone(),
+    )?;
+
+    library::spawn(state.clone());
+    rss_rules::spawn(state.clone());
+    if state.config.read().await.rss_prefetch {
+        rss_prefetch::spawn(state.clone());
+    }
+
     let app = Router::new()
         // API Endpoints
+        .route("/healthz", get(healthz))
         .route("/api/info", get(api_info))
+        .route("/api/auth/login", axum::routing::post(session::login))
+        .route("/api/auth/logout", axum::routing::post(session::logout))
         .route("/api/clear-all", delete(clear_all))
         .route("/api/stats", get(get_stats).delete(clear_stats))
+        .route("/api/stats/timeseries", get(stats_timeseries))
+        .route("/api/stats/indexers", get(get_indexer_stats))
+        .route("/api/debug/timings", get(get_search_timings))
+        .route("/api/system/disk", get(get_disk_usage))
         .route("/api/history", get(get_history))
         .route("/api/history/{key}", get(get_history_results))
+        .route("/api/history/{key}/raw", get(get_history_raw))
         .route("/api/downloads", get(get_downloads).delete(clear_downloads))
         .route("/api/downloads/links", get(get_downloaded_links))
+        .route("/api/library/search", get(library::search_library))
         .route("/api/v2.0/indexers", get(list_indexers))
         .route("/api/v2.0/search", get(search_api))
+        .route("/api/best", get(best_match_api))
         .route(
             "/api/v2.0/indexers/{indexer}/results/torznab",
             get(torznab_api),
@@ -160,6 +234,32 @@ pub async fn start_server(config: Config, host: &str, port: u16) -> anyhow::Resu
             axum::routing::post(delete_native_indexer),
         )
         .route("/api/native/search", get(search_native))
+        .route(
+            "/api/native/try",
+            axum::routing::post(try_native_definition),
+        )
+        .route("/api/image-proxy", get(image_proxy))
+        .route("/api/music/artists", get(music_artists))
+        .route("/api/sports", get(sports_events))
+        // Saved searches and RSS feeds
+        .route(
+            "/api/saved-searches",
+            get(list_saved_searches).post(add_saved_search),
+        )
+        .route("/api/saved-searches/{id}", delete(delete_saved_search))
+        .route("/api/rss/rules", get(list_rss_rules).post(add_rss_rule))
+        .route(
+            "/api/rss/rules/{id}",
+            axum::routing::put(update_rss_rule).delete(delete_rss_rule),
+        )
+        .route("/api/rss/rules/{id}/run", axum::routing::post(run_rss_rule))
+        .route("/api/rss/matches", get(list_rss_matches))
+        .route("/feeds/saved/{file}", get(saved_search_feed))
+        .route("/feeds/downloads.rss", get(downloads_feed))
+        // OPDS catalog for e-readers
+        .route("/opds", get(opds_root))
+        .route("/opds/search", get(opds_search))
+        .route("/opds/opensearch.xml", get(opds_opensearch))
         .route(
             "/api/native/{id}/settings",
             get(get_native_settings).put(update_native_settings),
@@ -168,6 +268,16 @@ pub async fn start_server(config: Config, host: &str, port: u16) -> anyhow::Resu
             "/api/native/{id}/test",
             axum::routing::post(test_native_indexer),
         )
+        .route(
+            "/api/native/{id}/captcha",
+            get(get_native_captcha).post(solve_native_captcha),
+        )
+        .route(
+            "/api/native/{id}/cookies",
+            get(get_native_cookies)
+                .post(import_native_cookies)
+                .delete(clear_native_cookies),
+        )
         .route(
             "/api/settings/indexer",
             axum::routing::post(add_indexer_api),
@@ -200,6 +310,10 @@ pub async fn start_server(config: Config, host: &str, port: u16) -> anyhow::Resu
             "/api/settings/activity/clear",
             axum::routing::post(clear_activity_api),
         )
+        .route(
+            "/api/settings/validate",
+            axum::routing::post(validate_config),
+        )
         .route("/api/download", axum::routing::post(trigger_download))
         .route(
             "/api/torrent/meta",
@@ -214,10 +328,19 @@ pub async fn start_server(config: Config, host: &str, port: u16) -> anyhow::Resu
             "/api/settings/clients/{id}",
             axum::routing::delete(remove_client),
         )
+        .route("/api/clients/discover", get(discover_clients))
         .route(
             "/api/clients/{id}/send",
             axum::routing::post(send_to_client),
         )
+        // Release push to Sonarr/Radarr
+        .route("/api/push", get(list_arr_apps))
+        .route("/api/push/{app}", axum::routing::post(push_release))
+        // Only API routes; the UI's static files stay public for the login page
+        .route_layer(axum::middleware::from_fn_with_state(
+            auth,
+            auth::require_auth,
+        ))
         .with_state(state)
         .fallback(static_handler)
         .layer(TraceLayer::new_for_http());
@@ -226,9 +349,12 @@ pub async fn start_server(config: Config, host: &str, port: u16) -> anyhow::Resu
     println!("Web UI running at http://{}", addr);
 
     let listener = tokio::net::TcpListener::bind(&addr).await?;
-    axum::serve(listener, app)
-        .with_graceful_shutdown(shutdown_signal())
-        .await?;
+    axum::serve(
+        listener,
+        app.into_make_service_with_connect_info::<std::net::SocketAddr>(),
+    )
+    .with_graceful_shutdown(shutdown_signal())
+    .await?;
 
     Ok(())
 }
@@ -0,0 +1,67 @@
+//! MusicBrainz matching for music searches (see `crate::musicbrainz`)
+
+use super::AppState;
+use super::api_indexers::TorznabParams;
+use crate::models::TorrentResult;
+use axum::{
+    Json,
+    extract::{Query, State},
+    http::StatusCode,
+    response::IntoResponse,
+};
+use serde::Deserialize;
+
+#[derive(Deserialize)]
+pub(super) struct ArtistParams {
+    q: String,
+}
+
+/// Tag `t=music` results with the MusicBrainz release they belong to
+pub(super) async fn match_music(
+    state: &AppState,
+    action: &str,
+    params: &TorznabParams,
+    results: &mut [TorrentResult],
+) {
+    let Some(artist) = params.artist.as_deref().filter(|a| !a.trim().is_empty()) else {
+        return;
+    };
+    if action != "music" || results.is_empty() {
+        return;
+    }
+    let config = state.config.read().await;
+    if !config.musicbrainz {
+        return;
+    }
+    let proxy_url = config.proxy_url.clone();
+    drop(config);
+
+    let album = params.album.as_deref().filter(|a| !a.trim().is_empty());
+    if let Some(found) = crate::musicbrainz::lookup(artist, album, proxy_url.as_deref()).await {
+        crate::musicbrainz::tag_results(results, &found);
+    }
+}
+
+/// Artist autocomplete (`/api/music/artists?q=`); empty unless MusicBrainz
+/// matching is enabled
+pub(super) async fn music_artists(
+    State(state): State<AppState>,
+    Query(params): Query<ArtistParams>,
+) -> impl IntoResponse {
+    let config = state.config.read().await;
+    let query = params.q.trim();
+    if !config.musicbrainz || query.chars().count() < 2 {
+        return Json(Vec::<crate::musicbrainz::ArtistSuggestion>::new()).into_response();
+    }
+    let proxy_url = config.proxy_url.clone();
+    drop(config);
+
+    match crate::musicbrainz::suggest_artists(query, proxy_url.as_deref()).await {
+        Ok(artists) => Json(artists).into_response(),
+        Err(e) => (
+            StatusCode::BAD_GATEWAY,
+            format!("MusicBrainz lookup failed: {}", e),
+        )
+            .into_response(),
+    }
+}
@@ -0,0 +1,234 @@
+//! OPDS catalog for book results, so e-reader apps can browse and search
+//! Lodestarr directly
+//!
+//! - `/opds` root navigation feed
+//! - `/opds/search?q=` book search across all enabled indexers (the latest
+//!   releases without `q`)
+//! - `/opds/opensearch.xml` search description the catalog points readers to
+
+use super::AppState;
+use super::api_indexers::{TorznabParams, search_all_indexers};
+use super::feeds::base_url;
+use crate::config::IndexerSelection;
+use crate::models::TorrentResult;
+use crate::torznab::escape_xml;
+use axum::{
+    extract::{Query, State},
+    http::{HeaderMap, StatusCode, header},
+    response::IntoResponse,
+};
+use chrono::Utc;
+use serde::Deserialize;
+
+const NAVIGATION: &str = "application/atom+xml;profile=opds-catalog;kind=navigation";
+const ACQUISITION: &str = "application/atom+xml;profile=opds-catalog;kind=acquisition";
+/// Torznab Books range
+const BOOK_CATEGORIES: std::ops::Range<i32> = 7000..8000;
+const FEED_LIMIT: usize = 100;
+
+#[derive(Deserialize)]
+pub(super) struct OpdsSearchParams {
+    q: Option<String>,
+}
+
+pub(super) async fn opds_root(headers: HeaderMap) -> impl IntoResponse {
+    let base = base_url(&headers);
+    let mut xml = feed_header(
+        "urn:lodestarr:opds",
+        "Lodestarr",
+        &base,
+        "/opds",
+        NAVIGATION,
+    );
+    xml.push_str(&format!(
+        "  <entry>\n    <title>Latest Books</title>\n    <id>urn:lodestarr:opds:latest</id>\n    <updated>{}</updated>\n    <content type=\"text\">Newest book releases from all indexers</content>\n    <link rel=\"subsection\" href=\"{}/opds/search\" type=\"{}\"/>\n  </entry>\n",
+        Utc::now().to_rfc3339(),
+        base,
+        ACQUISITION
+    ));
+    xml.push_str("</feed>\n");
+    opds_response(xml, NAVIGATION)
+}
+
+pub(super) async fn opds_opensearch(headers: HeaderMap) -> impl IntoResponse {
+    let base = base_url(&headers);
+    let xml = format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<OpenSearchDescription xmlns="http://a9.com/-/spec/opensearch/1.1/">
+  <ShortName>Lodestarr</ShortName>
+  <Description>Search book releases</Description>
+  <Url type="{}" template="{}/opds/search?q={{searchTerms}}"/>
+</OpenSearchDescription>
+"#,
+        escape_xml(ACQUISITION),
+        base
+    );
+    opds_response(xml, "application/opensearchdescription+xml")
+}
+
+pub(super) async fn opds_search(
+    State(state): State<AppState>,
+    Query(params): Query<OpdsSearchParams>,
+    headers: HeaderMap,
+) -> impl IntoResponse {
+    let query = params.q.filter(|q| !q.trim().is_empty());
+    let torznab = TorznabParams {
+        t: Some("book".to_string()),
+        q: query.clone(),
+        cat: Some(BOOK_CATEGORIES.start.to_string()),
+        ..Default::default()
+    };
+    let mut results: Vec<TorrentResult> =
+        search_all_indexers(&state, "book", &torznab, &IndexerSelection::All)
+            .await
+            .into_iter()
+            .filter(|r| {
+                r.categories.is_empty() || r.categories.iter().any(|c| BOOK_CATEGORIES.contains(c))
+            })
+            .collect();
+    if query.is_none() {
+        results.sort_by_key(|r| std::cmp::Reverse(r.publish_date));
+    }
+    results.truncate(FEED_LIMIT);
+
+    let (title, path) = match &query {
+        Some(q) => (
+            format!("Search: {}", q),
+            format!("/opds/search?q={}", urlencoding::encode(q)),
+        ),
+        None => ("Latest Books".to_string(), "/opds/search".to_string()),
+    };
+    opds_response(
+        acquisition_feed(&results, &title, &base_url(&headers), &path),
+        ACQUISITION,
+    )
+}
+
+fn opds_response(xml: String, content_type: &'static str) -> axum::response::Response {
+    (StatusCode::OK, [(header::CONTENT_TYPE, content_type)], xml).into_response()
+}
+
+fn feed_header(id: &str, title: &str, base: &str, path: &str, kind: &str) -> String {
+    format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<feed xmlns="http://www.w3.org/2005/Atom" xmlns:opds="http://opds-spec.org/2010/catalog">
+  <id>{}</id>
+  <title>{}</title>
+  <updated>{}</updated>
+  <author><name>Lodestarr</name></author>
+  <link rel="self" href="{base}{}" type="{}"/>
+  <link rel="start" href="{base}/opds" type="{NAVIGATION}"/>
+  <link rel="search" href="{base}/opds/opensearch.xml" type="application/opensearchdescription+xml"/>
+"#,
+        escape_xml(id),
+        escape_xml(title),
+        Utc::now().to_rfc3339(),
+        escape_xml(path),
+        kind,
+    )
+}
+
+fn acquisition_feed(results: &[TorrentResult], title: &str, base: &str, path: &str) -> String {
+    let mut xml = feed_header("urn:lodestarr:opds:search", title, base, path, ACQUISITION);
+    for result in results {
+        let indexer = result.indexer.as_deref().unwrap_or("all");
+        xml.push_str("  <entry>\n");
+        xml.push_str(&format!(
+            "    <title>{}</title>\n",
+            escape_xml(&result.title)
+        ));
+        xml.push_str(&format!(
+            "    <id>urn:lodestarr:{}</id>\n",
+            escape_xml(&result.guid)
+        ));
+        xml.push_str(&format!(
+            "    <updated>{}</updated>\n",
+            result.publish_date.unwrap_or_else(Utc::now).to_rfc3339()
+        ));
+
+        let mut summary = vec![format!("Indexer: {}", indexer)];
+        if let Some(size) = result.size {
+            summary.push(format!("Size: {}", crate::utils::format_size(size)));
+        }
+        if let Some(seeders) = result.seeders {
+            summary.push(format!("Seeders: {}", seeders));
+        }
+        xml.push_str(&format!(
+            "    <content type=\"text\">{}</content>\n",
+            escape_xml(&summary.join(" | "))
+        ));
+
+        if let Some(link) = result.link.as_ref().filter(|l| !l.starts_with("magnet:")) {
+            // Through the download proxy, which has the indexer's session
+            let href = crate::download::proxy_download_url(base, indexer, link, &result.guid, None);
+            xml.push_str(&format!(
+                "    <link rel=\"http://opds-spec.org/acquisition\" href=\"{}\" type=\"application/x-bittorrent\"/>\n",
+                escape_xml(&href)
+            ));
+        }
+        if let Some(magnet) = &result.magnet {
+            xml.push_str(&format!(
+                "    <link rel=\"http://opds-spec.org/acquisition\" href=\"{}\" type=\"x-scheme-handler/magnet\"/>\n",
+                escape_xml(magnet)
+            ));
+        }
+        if let Some(poster) = &result.poster {
+            let href = format!(
+                "{}/api/image-proxy?u={}&indexer={}",
+                base,
+                urlencoding::encode(poster),
+                urlencoding::encode(indexer)
+            );
+            for rel in [
+                "http://opds-spec.org/image",
+                "http://opds-spec.org/image/thumbnail",
+            ] {
+                xml.push_str(&format!(
+                    "    <link rel=\"{}\" href=\"{}\"/>\n",
+                    rel,
+                    escape_xml(&href)
+                ));
+            }
+        }
+        if let Some(details) = &result.details {
+            xml.push_str(&format!(
+                "    <link rel=\"alternate\" href=\"{}\" type=\"text/html\"/>\n",
+                escape_xml(details)
+            ));
+        }
+        xml.push_str("  </entry>\n");
+    }
+    xml.push_str("</feed>\n");
+    xml
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_acquisition_feed() {
+        let mut result = TorrentResult::new("Dune & Sequels".into(), "guid-1".into());
+        result.indexer = Some("rutracker".into());
+        result.link = Some("https://rutracker.org/dl.php?t=1".into());
+        result.magnet = Some("magnet:?xt=urn:btih:abc&dn=dune".into());
+        result.poster = Some("https://rutracker.org/p/1.jpg".into());
+        result.categories = vec![7020];
+
+        let xml = acquisition_feed(
+            &[result],
+            "Search: dune",
+            "http://localhost:3420",
+            "/opds/search?q=dune",
+        );
+        assert!(xml.contains("<title>Dune &amp; Sequels</title>"));
+        assert!(xml.contains(
+            "href=\"http://localhost:3420/api/v2.0/indexers/rutracker/dl?link=aHR0cHM6Ly9ydXRyYWNrZXIub3JnL2RsLnBocD90PTE&amp;guid=guid-1\""
+        ));
+        assert!(xml.contains("href=\"magnet:?xt=urn:btih:abc&amp;dn=dune\""));
+        assert!(xml.contains(
+            "/api/image-proxy?u=https%3A%2F%2Frutracker.org%2Fp%2F1.jpg&amp;indexer=rutracker"
+        ));
+        assert!(xml.contains("rel=\"search\""));
+    }
+}
@@ -0,0 +1,230 @@
+//! RSS warm-up for recurring *arr polls (enabled with `rss_prefetch = true`)
+//!
+//! Sonarr/Radarr poll each indexer's RSS feed (a search without a query) on a
+//! fixed interval. Once a feed has been polled at a steady rhythm, the search
+//! is run shortly before the next poll is due and the results are parked in
+//! the cache, so the poll itself is answered immediately and tracker requests
+//! happen on our schedule instead of all at once.
+
+use super::AppState;
+use super::api_indexers::{TorznabParams, search_all_indexers, search_native_indexer};
+use crate::models::TorrentResult;
+use crate::store::Store;
+use once_cell::sync::Lazy;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Poll timestamps kept per feed
+const HISTORY: usize = 4;
+/// Polls needed before a feed counts as recurring
+const MIN_POLLS: usize = 3;
+/// Longest/shortest interval ratio still considered regular
+const MAX_JITTER: f64 = 1.5;
+/// How far ahead of the expected poll the refresh runs
+const LEAD: Duration = Duration::from_secs(30);
+const TICK: Duration = Duration::from_secs(15);
+
+/// A feed being polled by a client
+struct Pattern {
+    indexer: String,
+    action: String,
+    params: TorznabParams,
+    polls: Vec<Instant>,
+    /// Expected poll the current prefetch was made for
+    prefetched_for: Option<Instant>,
+}
+
+static PATTERNS: Lazy<Mutex<HashMap<String, Pattern>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+fn pattern_key(indexer: &str, action: &str, params: &TorznabParams) -> String {
+    format!("{}:{}", indexer, params.fingerprint(action))
+}
+
+fn cache_key(indexer: &str, action: &str, params: &TorznabParams) -> String {
+    format!("rss:{}", pattern_key(indexer, action, params))
+}
+
+/// True for an RSS poll: a search with nothing to search for
+pub fn is_rss(params: &TorznabParams) -> bool {
+    params.q.as_deref().is_none_or(|q| q.trim().is_empty())
+        && params.season.is_none()
+        && params.ep.is_none()
+        && params.imdbid.is_none()
+        && params.tvdbid.is_none()
+        && params.tmdbid.is_none()
+        && params.year.is_none()
+        && params.genre.is_none()
+        && params.album.is_none()
+        && params.artist.is_none()
+        && params.title.is_none()
+        && params.author.is_none()
+}
+
+/// Remember that a client polled this feed
+pub fn record_poll(indexer: &str, action: &str, params: &TorznabParams) {
+    let Ok(mut patterns) = PATTERNS.lock() else {
+        return;
+    };
+    let pattern = patterns
+        .entry(pattern_key(indexer, action, params))
+        .or_insert_with(|| Pattern {
+            indexer: indexer.to_string(),
+            action: action.to_string(),
+            params: params.clone(),
+            polls: Vec::new(),
+            prefetched_for: None,
+        });
+    pattern.polls.push(Instant::now());
+    if pattern.polls.len() > HISTORY {
+        pattern.polls.remove(0);
+    }
+}
+
+/// Prefetched results for this poll, if any (each prefetch is served once)
+pub async fn take(
+    store: &dyn Store,
+    indexer: &str,
+    action: &str,
+    params: &TorznabParams,
+) -> Option<Vec<TorrentResult>> {
+    let json = store
+        .take_cached(&cache_key(indexer, action, params))
+        .await
+        .ok()
+        .flatten()?;
+    serde_json::from_str(&json).ok()
+}
+
+/// Expected time of the next poll and the polling interval, if the polls so
+/// far are regular enough to predict it
+fn next_poll(polls: &[Instant]) -> Option<(Instant, Duration)> {
+    if polls.len() < MIN_POLLS {
+        return None;
+    }
+    let intervals: Vec<Duration> = polls.windows(2).map(|w| w[1] - w[0]).collect();
+    let min = *intervals.iter().min()?;
+    let max = *intervals.iter().max()?;
+    if min.is_zero() || max.as_secs_f64() / min.as_secs_f64() > MAX_JITTER {
+        return None;
+    }
+    let interval = intervals.iter().sum::<Duration>() / intervals.len() as u32;
+    Some((*polls.last()? + interval, interval))
+}
+
+/// Feeds whose next poll falls within the lead window and haven't been
+/// prefetched for it yet. Feeds that stopped polling are forgotten.
+fn due(now: Instant) -> Vec<(String, String, TorznabParams, Duration)> {
+    let Ok(mut patterns) = PATTERNS.lock() else {
+        return Vec::new();
+    };
+    let mut due = Vec::new();
+    patterns.retain(|_, pattern| {
+        let Some((expected, interval)) = next_poll(&pattern.polls) else {
+            // Keep collecting unless the feed went quiet
+            return pattern
+                .polls
+                .last()
+                .is_some_and(|last| now.duration_since(*last) < Duration::from_secs(6 * 3600));
+        };
+        // Missed two polls in a row: the client is gone or changed schedule
+        if now > expected + interval * 2 {
+            return false;
+        }
+        if now + LEAD >= expected && pattern.prefetched_for != Some(expected) {
+            pattern.prefetched_for = Some(expected);
+            due.push((
+                pattern.indexer.clone(),
+                pattern.action.clone(),
+                pattern.params.clone(),
+                interval,
+            ));
+        }
+        true
+    });
+    due
+}
+
+async fn prefetch(
+    state: &AppState,
+    indexer: &str,
+    action: &str,
+    params: &TorznabParams,
+    ttl: Duration,
+) -> anyhow::Result<()> {
+    let aggregate = state.config.read().await.aggregate_indexers(indexer);
+    let results = if let Some(selection) = aggregate {
+        search_all_indexers(state, action, params, &selection).await
+    } else {
+        let definition = state
+            .native_indexers
+            .read()
+            .await
+            .get_definition(indexer)
+            .await
+            .ok_or_else(|| anyhow::anyhow!("Indexer {} no longer exists", indexer))?;
+        search_native_indexer(state, &definition, action, params).await?
+    };
+    let json = serde_json::to_string(&results)?;
+    state
+        .store
+        .set_cached(
+            &cache_key(indexer, action, params),
+            &json,
+            chrono::Duration::from_std(ttl)?,
+        )
+        .await?;
+    tracing::debug!("Prefetched RSS for {} ({} results)", indexer, results.len());
+    Ok(())
+}
+
+/// Start the background prefetcher
+pub fn spawn(state: AppState) {
+    tokio::spawn(async move {
+        let mut tick = tokio::time::interval(TICK);
+        loop {
+            tick.tick().await;
+            for (indexer, action, params, interval) in due(Instant::now()) {
+                let state = state.clone();
+                tokio::spawn(async move {
+                    if let Err(e) = prefetch(&state, &indexer, &action, &params, interval).await {
+                        tracing::debug!("RSS prefetch for {} failed: {}", indexer, e);
+                    }
+                });
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_rss() {
+        let mut params = TorznabParams {
+            cat: Some("5000".to_string()),
+            ..Default::default()
+        };
+        assert!(is_rss(&params));
+        params.q = Some("show".to_string());
+        assert!(!is_rss(&params));
+        params.q = None;
+        params.tvdbid = Some(123);
+        assert!(!is_rss(&params));
+    }
+
+    #[test]
+    fn test_next_poll() {
+        let start = Instant::now();
+        let at = |secs: u64| start + Duration::from_secs(secs);
+
+        assert_eq!(next_poll(&[at(0), at(900)]), None);
+        assert_eq!(
+            next_poll(&[at(0), at(900), at(1800)]),
+            Some((at(2700), Duration::from_secs(900)))
+        );
+        // Irregular polling isn't predicted
+        assert_eq!(next_poll(&[at(0), at(100), at(1000)]), None);
+    }
+}
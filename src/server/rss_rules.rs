@@ -0,0 +1,533 @@
+//! Scheduled RSS rules: saved searches the server runs on its own
+//!
+//! Every rule is searched again once its interval has passed. Results that
+//! pass the rule's filters (title regex, minimum seeders, size range,
+//! category) and weren't seen before are kept in `rss_matches`. When the
+//! rule names a download client, new matches are sent to it through the
+//! download proxy, and failed sends are retried on later runs; otherwise
+//! they wait as pending for `lodestarr rss pending` or `/api/rss/matches`.
+//!
+//! A rule's first run only records what is already listed (as seen), so
+//! adding a rule doesn't grab the whole backlog. A run whose search fails
+//! isn't recorded, so the next one is still the first. Matches no longer
+//! listed are dropped after [`MATCH_RETENTION_DAYS`].
+//!
+//! Rules are managed through `/api/rss/rules`.
+
+use super::AppState;
+use crate::clients::create_client;
+use crate::config::DownloadClient;
+use crate::db::{MatchStatus, RssRule};
+use crate::models::TorrentResult;
+use axum::{
+    Json,
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::IntoResponse,
+};
+use chrono::{DateTime, Utc};
+use regex::{Regex, RegexBuilder};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+const TICK: Duration = Duration::from_secs(60);
+const DEFAULT_INTERVAL_MINUTES: u32 = 60;
+/// Shortest interval a rule can have, to spare the trackers
+const MIN_INTERVAL_MINUTES: u32 = 5;
+/// Matches listed by `/api/rss/matches`
+const MATCHES_LIMIT: usize = 500;
+/// Sends of a match before it is left failed
+const MAX_SEND_ATTEMPTS: u32 = 5;
+/// Days a match is kept after its rule last listed it
+pub const MATCH_RETENTION_DAYS: i64 = 30;
+
+/// Run rules as they come due
+pub fn spawn(state: AppState) {
+    tokio::spawn(async move {
+        let mut tick = tokio::time::interval(TICK);
+        loop {
+            tick.tick().await;
+            let rules = match crate::db::get_rss_rules(&state.db_pool) {
+                Ok(rules) => rules,
+                Err(e) => {
+                    tracing::warn!("Failed to load RSS rules: {}", e);
+                    continue;
+                }
+            };
+            let now = Utc::now();
+            for rule in rules.iter().filter(|r| is_due(r, now)) {
+                match run_rule(&state, rule).await {
+                    Ok(run) if run.new > 0 || run.retried > 0 => tracing::info!(
+                        "RSS rule '{}': {} new matches, {} retried, {} sent",
+                        rule.name,
+                        run.new,
+                        run.retried,
+                        run.sent
+                    ),
+                    Ok(_) => {}
+                    Err(e) => tracing::warn!("RSS rule '{}' failed: {:#}", rule.name, e),
+                }
+            }
+            let cutoff = now - chrono::Duration::days(MATCH_RETENTION_DAYS);
+            if let Err(e) = crate::db::prune_rss_matches(&state.db_pool, cutoff) {
+                tracing::warn!("Failed to prune RSS matches: {}", e);
+            }
+        }
+    });
+}
+
+fn is_due(rule: &RssRule, now: DateTime<Utc>) -> bool {
+    let interval = chrono::Duration::minutes(i64::from(rule.interval_minutes));
+    rule.enabled && rule.last_run.is_none_or(|last| now - last >= interval)
+}
+
+/// Outcome of one run of a rule
+#[derive(Serialize, Default)]
+pub(super) struct Run {
+    /// Results that passed the filters
+    matched: usize,
+    /// Matches not seen before
+    new: usize,
+    sent: usize,
+    /// Earlier failed sends tried again
+    retried: usize,
+}
+
+/// Search for a rule, keep its new matches and send them to its client
+async fn run_rule(state: &AppState, rule: &RssRule) -> anyhow::Result<Run> {
+    let pattern = compile(rule.title_pattern.as_deref())?;
+    let client = match &rule.client_id {
+        Some(id) => {
+            let config = state.config.read().await;
+            let client = config.download_clients.iter().find(|c| &c.id == id);
+            Some(
+                client
+                    .cloned()
+                    .ok_or_else(|| anyhow::anyhow!("Download client not found: {}", id))?,
+            )
+        }
+        None => None,
+    };
+    let results = super::feeds::search_saved(
+        state,
+        &rule.query,
+        rule.indexer.as_deref(),
+        rule.category.clone(),
+    )
+    .await?
+    .ok_or_else(|| {
+        anyhow::anyhow!(
+            "Indexer not found: {}",
+            rule.indexer.as_deref().unwrap_or_default()
+        )
+    })?;
+
+    // The first run only takes note of what is already listed
+    let first_run = rule.last_run.is_none();
+    let status = if first_run {
+        MatchStatus::Seen
+    } else {
+        MatchStatus::Pending
+    };
+
+    let mut run = Run::default();
+    let retries = match &client {
+        Some(_) => crate::db::get_rss_retries(&state.db_pool, rule.id, MAX_SEND_ATTEMPTS)?,
+        None => Vec::new(),
+    };
+    let mut to_send = Vec::new();
+    for result in results
+        .iter()
+        .filter(|r| matches(rule, pattern.as_ref(), r))
+    {
+        run.matched += 1;
+        if crate::db::add_rss_match(&state.db_pool, rule.id, result, status)? {
+            run.new += 1;
+            if !first_run {
+                to_send.push(result.clone());
+            }
+        }
+    }
+
+    if let Some(client) = &client {
+        let base_url = state.config.read().await.server.base_url();
+        run.retried = retries.len();
+        for result in retries
+            .iter()
+            .map(crate::db::RssMatch::to_result)
+            .chain(to_send)
+        {
+            if send(state, rule, client, &base_url, &result).await? {
+                run.sent += 1;
+            }
+        }
+    }
+    crate::db::set_rss_rule_run(&state.db_pool, rule.id, Utc::now())?;
+    Ok(run)
+}
+
+/// Send a match to the rule's client through Lodestarr's download proxy
+/// (as a push to an *arr app would) and record how it went
+async fn send(
+    state: &AppState,
+    rule: &RssRule,
+    client: &DownloadClient,
+    base_url: &str,
+    result: &TorrentResult,
+) -> anyhow::Result<bool> {
+    let api_key = state.config.read().await.server.auth.api_key.clone();
+    let link =
+        super::api_clients::release_for(result, base_url, api_key.as_deref(), None).download_url;
+    let sent = if link.is_empty() {
+        Err(anyhow::anyhow!("Result has no link"))
+    } else {
+        create_client(client).add_torrent(&link).await
+    };
+    let (status, error) = match &sent {
+        Ok(()) => (MatchStatus::Sent, None),
+        Err(e) => (MatchStatus::Failed, Some(e.to_string())),
+    };
+    crate::db::set_rss_match_status(
+        &state.db_pool,
+        rule.id,
+        &result.guid,
+        status,
+        error.as_deref(),
+    )?;
+    if sent.is_ok()
+        && let Err(e) = state
+            .store
+            .log_download(
+                Some(&result.title),
+                result.magnet.as_deref(),
+                result.link.as_deref(),
+                Some(&client.name),
+                "rss",
+            )
+            .await
+    {
+        tracing::warn!("Failed to log download: {}", e);
+    }
+    Ok(sent.is_ok())
+}
+
+fn compile(pattern: Option<&str>) -> Result<Option<Regex>, regex::Error> {
+    pattern
+        .map(|p| RegexBuilder::new(p).case_insensitive(true).build())
+        .transpose()
+}
+
+/// Whether a result passes the rule's filters. A category matches itself,
+/// and a parent category (5000) matches its subcategories (5040).
+fn matches(rule: &RssRule, pattern: Option<&Regex>, result: &TorrentResult) -> bool {
+    if pattern.is_some_and(|p| !p.is_match(&result.title)) {
+        return false;
+    }
+    if rule
+        .min_seeders
+        .is_some_and(|min| result.seeders.unwrap_or(0) < min)
+    {
+        return false;
+    }
+    let size = result.size.unwrap_or(0);
+    if rule.min_size.is_some_and(|min| size < min) || rule.max_size.is_some_and(|max| size > max) {
+        return false;
+    }
+    let categories: Vec<i32> = rule
+        .category
+        .as_deref()
+        .unwrap_or_default()
+        .split(',')
+        .filter_map(|c| c.trim().parse().ok())
+        .collect();
+    categories.is_empty()
+        || result.categories.iter().any(|&cat| {
+            categories
+                .iter()
+                .any(|&want| cat == want || (want % 1000 == 0 && cat / 1000 == want / 1000))
+        })
+}
+
+#[derive(Deserialize)]
+pub(super) struct RssRuleRequest {
+    /// Defaults to the query
+    name: Option<String>,
+    query: String,
+    indexer: Option<String>,
+    category: Option<String>,
+    title_pattern: Option<String>,
+    min_seeders: Option<u32>,
+    min_size: Option<u64>,
+    max_size: Option<u64>,
+    client_id: Option<String>,
+    interval_minutes: Option<u32>,
+    enabled: Option<bool>,
+}
+
+impl RssRuleRequest {
+    /// The rule this request describes, or what's wrong with it
+    async fn into_rule(self, state: &AppState, id: i64) -> Result<RssRule, String> {
+        let query = self.query.trim().to_string();
+        if query.is_empty() {
+            return Err("Query is required".to_string());
+        }
+        let non_empty =
+            |v: Option<String>| v.map(|s| s.trim().to_string()).filter(|s| !s.is_empty());
+        let title_pattern = non_empty(self.title_pattern);
+        compile(title_pattern.as_deref()).map_err(|e| format!("Invalid title pattern: {}", e))?;
+        if let (Some(min), Some(max)) = (self.min_size, self.max_size)
+            && min > max
+        {
+            return Err("min_size is larger than max_size".to_string());
+        }
+        let interval_minutes = self.interval_minutes.unwrap_or(DEFAULT_INTERVAL_MINUTES);
+        if interval_minutes < MIN_INTERVAL_MINUTES {
+            return Err(format!(
+                "interval_minutes must be at least {}",
+                MIN_INTERVAL_MINUTES
+            ));
+        }
+        let client_id = non_empty(self.client_id);
+        if let Some(client_id) = &client_id {
+            let config = state.config.read().await;
+            if !config.download_clients.iter().any(|c| &c.id == client_id) {
+                return Err(format!("Download client not found: {}", client_id));
+            }
+        }
+
+        Ok(RssRule {
+            id,
+            name: non_empty(self.name).unwrap_or_else(|| query.clone()),
+            query,
+            indexer: non_empty(self.indexer),
+            category: non_empty(self.category),
+            title_pattern,
+            min_seeders: self.min_seeders,
+            min_size: self.min_size,
+            max_size: self.max_size,
+            client_id,
+            interval_minutes,
+            enabled: self.enabled.unwrap_or(true),
+            ..Default::default()
+        })
+    }
+}
+
+pub(super) async fn list_rss_rules(State(state): State<AppState>) -> impl IntoResponse {
+    match crate::db::get_rss_rules(&state.db_pool) {
+        Ok(rules) => Json(rules).into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, format!("Error: {}", e)).into_response(),
+    }
+}
+
+pub(super) async fn add_rss_rule(
+    State(state): State<AppState>,
+    Json(payload): Json<RssRuleRequest>,
+) -> impl IntoResponse {
+    let rule = match payload.into_rule(&state, 0).await {
+        Ok(rule) => rule,
+        Err(e) => return (StatusCode::BAD_REQUEST, e).into_response(),
+    };
+    match crate::db::add_rss_rule(&state.db_pool, &rule) {
+        Ok(id) => match crate::db::get_rss_rule(&state.db_pool, id) {
+            Ok(Some(rule)) => (StatusCode::CREATED, Json(rule)).into_response(),
+            Ok(None) => StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+            Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, format!("Error: {}", e)).into_response(),
+        },
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Failed to save rule: {}", e),
+        )
+            .into_response(),
+    }
+}
+
+pub(super) async fn update_rss_rule(
+    State(state): State<AppState>,
+    Path(id): Path<i64>,
+    Json(payload): Json<RssRuleRequest>,
+) -> impl IntoResponse {
+    let rule = match payload.into_rule(&state, id).await {
+        Ok(rule) => rule,
+        Err(e) => return (StatusCode::BAD_REQUEST, e).into_response(),
+    };
+    match crate::db::update_rss_rule(&state.db_pool, &rule) {
+        Ok(true) => match crate::db::get_rss_rule(&state.db_pool, id) {
+            Ok(Some(rule)) => Json(rule).into_response(),
+            Ok(None) => (StatusCode::NOT_FOUND, "Rule not found").into_response(),
+            Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, format!("Error: {}", e)).into_response(),
+        },
+        Ok(false) => (StatusCode::NOT_FOUND, "Rule not found").into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, format!("Error: {}", e)).into_response(),
+    }
+}
+
+pub(super) async fn delete_rss_rule(
+    State(state): State<AppState>,
+    Path(id): Path<i64>,
+) -> impl IntoResponse {
+    match crate::db::delete_rss_rule(&state.db_pool, id) {
+        Ok(true) => (StatusCode::OK, "Rule deleted").into_response(),
+        Ok(false) => (StatusCode::NOT_FOUND, "Rule not found").into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, format!("Error: {}", e)).into_response(),
+    }
+}
+
+/// `POST /api/rss/rules/{id}/run`: run a rule now
+pub(super) async fn run_rss_rule(
+    State(state): State<AppState>,
+    Path(id): Path<i64>,
+) -> impl IntoResponse {
+    let rule = match crate::db::get_rss_rule(&state.db_pool, id) {
+        Ok(Some(rule)) => rule,
+        Ok(None) => return (StatusCode::NOT_FOUND, "Rule not found").into_response(),
+        Err(e) => {
+            return (StatusCode::INTERNAL_SERVER_ERROR, format!("Error: {}", e)).into_response();
+        }
+    };
+    match run_rule(&state, &rule).await {
+        Ok(run) => Json(run).into_response(),
+        Err(e) => (StatusCode::BAD_GATEWAY, format!("{:#}", e)).into_response(),
+    }
+}
+
+#[derive(Deserialize)]
+pub(super) struct MatchesParams {
+    /// seen, pending, sent or failed (all when unset)
+    status: Option<String>,
+}
+
+pub(super) async fn list_rss_matches(
+    State(state): State<AppState>,
+    Query(params): Query<MatchesParams>,
+) -> impl IntoResponse {
+    let status = match params.status.as_deref().filter(|s| !s.is_empty()) {
+        Some(status) => match MatchStatus::parse(status) {
+            Some(status) => Some(status),
+            None => {
+                return (
+                    StatusCode::BAD_REQUEST,
+                    "status must be seen, pending, sent or failed",
+                )
+                    .into_response();
+            }
+        },
+        None => None,
+    };
+    match crate::db::get_rss_matches(&state.db_pool, status, MATCHES_LIMIT) {
+        Ok(matches) => Json(matches).into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, format!("Error: {}", e)).into_response(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_matches() {
+        let rule = RssRule {
+            title_pattern: Some(r"ubuntu 24\.04".to_string()),
+            min_seeders: Some(5),
+            min_size: Some(1_000),
+            max_size: Some(5_000),
+            category: Some("4000".to_string()),
+            interval_minutes: 30,
+            enabled: true,
+            ..Default::default()
+        };
+        let pattern = compile(rule.title_pattern.as_deref()).unwrap();
+        let result = |title: &str, seeders, size, category| TorrentResult {
+            title: title.to_string(),
+            seeders: Some(seeders),
+            size: Some(size),
+            categories: vec![category],
+            ..Default::default()
+        };
+        let check = |r: TorrentResult| matches(&rule, pattern.as_ref(), &r);
+
+        assert!(check(result("Ubuntu 24.04 Desktop", 10, 2_000, 4050)));
+        assert!(!check(result("Ubuntu 22.04 Desktop", 10, 2_000, 4050)));
+        assert!(!check(result("Ubuntu 24.04 Desktop", 1, 2_000, 4050)));
+        assert!(!check(result("Ubuntu 24.04 Desktop", 10, 9_000, 4050)));
+        assert!(!check(result("Ubuntu 24.04 Desktop", 10, 2_000, 5000)));
+
+        let now = Utc::now();
+        assert!(is_due(&rule, now));
+        let ran = RssRule {
+            last_run: Some(now - chrono::Duration::minutes(10)),
+            ..rule.clone()
+        };
+        assert!(!is_due(&ran, now));
+        assert!(is_due(&ran, now + chrono::Duration::minutes(20)));
+        assert!(!is_due(
+            &RssRule {
+                enabled: false,
+                ..rule
+            },
+            now
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_failed_first_run_is_not_recorded() {
+        use std::sync::Arc;
+        use std::sync::atomic::{AtomicBool, Ordering};
+
+        let down = Arc::new(AtomicBool::new(true));
+        let flag = down.clone();
+        let app = axum::Router::new().fallback(move || {
+            let down = flag.load(Ordering::SeqCst);
+            async move {
+                if down {
+                    return r#"<error code="900" description="Tracker is down"/>"#;
+                }
+                "<rss><channel><item><title>Old release</title><guid>old</guid>\
+                 <link>http://t.example/old.torrent</link></item></channel></rss>"
+            }
+        });
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await });
+
+        let config = crate::config::Config {
+            indexers: vec![crate::config::IndexerConfig {
+                name: "flaky".to_string(),
+                url: format!("http://{}/api", addr),
+                apikey: None,
+            }],
+            ..Default::default()
+        };
+        let state = AppState::for_tests(config, "rss-failed-first-run");
+        let rule = RssRule {
+            name: "old".to_string(),
+            query: "old".to_string(),
+            indexer: Some("flaky".to_string()),
+            interval_minutes: 30,
+            enabled: true,
+            ..Default::default()
+        };
+        let id = crate::db::add_rss_rule(&state.db_pool, &rule).unwrap();
+        let rule = crate::db::get_rss_rule(&state.db_pool, id)
+            .unwrap()
+            .unwrap();
+
+        assert!(run_rule(&state, &rule).await.is_err());
+        let rule = crate::db::get_rss_rule(&state.db_pool, id)
+            .unwrap()
+            .unwrap();
+        assert!(rule.last_run.is_none());
+
+        // The first successful run still only takes note of the backlog
+        down.store(false, Ordering::SeqCst);
+        let run = run_rule(&state, &rule).await.unwrap();
+        assert_eq!(run.new, 1);
+        let matches = crate::db::get_rss_matches(&state.db_pool, None, 10).unwrap();
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].status, MatchStatus::Seen);
+        let rule = crate::db::get_rss_rule(&state.db_pool, id)
+            .unwrap()
+            .unwrap();
+        assert!(rule.last_run.is_some());
+    }
+}
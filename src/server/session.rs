@@ -0,0 +1,197 @@
+//! Username/password login for the web UI
+//!
+//! A successful login sets an HTTP-only session cookie. The database keeps
+//! only a SHA-256 of each token. *arr apps keep using the API key; sessions
+//! are for browsers. Failed logins are limited per client address.
+
+use super::AppState;
+use super::auth::{Authenticator, Outcome};
+use crate::db::DbPool;
+use axum::{
+    Extension, Json,
+    extract::{ConnectInfo, Request, State},
+    http::{HeaderMap, StatusCode, header},
+    response::{IntoResponse, Response},
+};
+use chrono::Utc;
+use once_cell::sync::Lazy;
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+const COOKIE: &str = "lodestarr_session";
+const SESSION_DAYS: i64 = 30;
+/// Failed logins allowed from one address within [`FAILURE_WINDOW`]
+const MAX_FAILURES: usize = 5;
+const FAILURE_WINDOW: Duration = Duration::from_secs(15 * 60);
+
+/// Recent failed login times by client address
+static FAILURES: Lazy<Mutex<HashMap<IpAddr, Vec<Instant>>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Accepts requests carrying a live session cookie
+pub(super) struct SessionAuth {
+    pub(super) pool: DbPool,
+}
+
+impl Authenticator for SessionAuth {
+    fn authenticate(&self, req: &Request, _peer: Option<IpAddr>) -> Outcome {
+        let Some(token) = session_token(req.headers()) else {
+            return Outcome::Pass;
+        };
+        match crate::db::get_session_user(&self.pool, &token_hash(&token)) {
+            Ok(Some(user)) => Outcome::Allow(user),
+            Ok(None) => Outcome::Pass,
+            Err(e) => {
+                tracing::warn!("Failed to look up session: {}", e);
+                Outcome::Pass
+            }
+        }
+    }
+}
+
+fn session_token(headers: &HeaderMap) -> Option<String> {
+    headers
+        .get_all(header::COOKIE)
+        .iter()
+        .filter_map(|v| v.to_str().ok())
+        .flat_map(|v| v.split(';'))
+        .find_map(|pair| pair.trim().strip_prefix(COOKIE)?.strip_prefix('='))
+        .filter(|token| !token.is_empty())
+        .map(str::to_string)
+}
+
+fn token_hash(token: &str) -> String {
+    hex::encode(Sha256::digest(token.as_bytes()))
+}
+
+/// Whether `ip` has used up its failed logins
+fn locked_out(ip: IpAddr) -> bool {
+    let mut failures = FAILURES.lock().unwrap_or_else(|e| e.into_inner());
+    let Some(times) = failures.get_mut(&ip) else {
+        return false;
+    };
+    times.retain(|t| t.elapsed() < FAILURE_WINDOW);
+    times.len() >= MAX_FAILURES
+}
+
+fn record_failure(ip: IpAddr) {
+    let mut failures = FAILURES.lock().unwrap_or_else(|e| e.into_inner());
+    failures.retain(|_, times| times.iter().any(|t| t.elapsed() < FAILURE_WINDOW));
+    failures.entry(ip).or_default().push(Instant::now());
+}
+
+#[derive(Deserialize)]
+pub(super) struct LoginRequest {
+    username: String,
+    password: String,
+}
+
+/// Check the credentials and start a session
+pub(super) async fn login(
+    State(state): State<AppState>,
+    connect: Option<Extension<ConnectInfo<SocketAddr>>>,
+    headers: HeaderMap,
+    Json(payload): Json<LoginRequest>,
+) -> Response {
+    let peer = connect.map(|Extension(ConnectInfo(addr))| addr.ip().to_canonical());
+    if peer.is_some_and(locked_out) {
+        return (
+            StatusCode::TOO_MANY_REQUESTS,
+            "Too many failed logins, try again later",
+        )
+            .into_response();
+    }
+
+    let auth = state.config.read().await.server.auth.clone();
+    let (Some(username), Some(hash)) = (auth.username, auth.password_hash) else {
+        return (StatusCode::NOT_FOUND, "Login is not set up").into_response();
+    };
+    let password = payload.password;
+    let password_ok =
+        tokio::task::spawn_blocking(move || crate::password::verify_password(&password, &hash))
+            .await
+            .unwrap_or(false);
+    if !password_ok || payload.username != username {
+        tracing::warn!("Failed web UI login for '{}'", payload.username);
+        if let Some(ip) = peer {
+            record_failure(ip);
+        }
+        return (StatusCode::UNAUTHORIZED, "Invalid username or password").into_response();
+    }
+    if let Some(ip) = peer {
+        FAILURES
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(&ip);
+    }
+
+    let token = format!(
+        "{}{}",
+        uuid::Uuid::new_v4().simple(),
+        uuid::Uuid::new_v4().simple()
+    );
+    let expires = Utc::now() + chrono::Duration::days(SESSION_DAYS);
+    if let Err(e) =
+        crate::db::create_session(&state.db_pool, &token_hash(&token), &username, expires)
+    {
+        return (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response();
+    }
+
+    // Behind a TLS-terminating proxy the browser only sends it back over HTTPS
+    let secure = headers
+        .get("x-forwarded-proto")
+        .is_some_and(|v| v.as_bytes().eq_ignore_ascii_case(b"https"));
+    let cookie = format!(
+        "{}={}; Path=/; HttpOnly; SameSite=Strict; Max-Age={}{}",
+        COOKIE,
+        token,
+        SESSION_DAYS * 24 * 60 * 60,
+        if secure { "; Secure" } else { "" }
+    );
+    (
+        [(header::SET_COOKIE, cookie)],
+        Json(serde_json::json!({ "user": username })),
+    )
+        .into_response()
+}
+
+/// End the current session
+pub(super) async fn logout(State(state): State<AppState>, headers: HeaderMap) -> Response {
+    if let Some(token) = session_token(&headers)
+        && let Err(e) = crate::db::delete_session(&state.db_pool, &token_hash(&token))
+    {
+        tracing::warn!("Failed to delete session: {}", e);
+    }
+    let cookie = format!("{}=; Path=/; HttpOnly; SameSite=Strict; Max-Age=0", COOKIE);
+    ([(header::SET_COOKIE, cookie)], StatusCode::NO_CONTENT).into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_session_token() {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            header::COOKIE,
+            "theme=dark; lodestarr_session=abc123; other=1"
+                .parse()
+                .unwrap(),
+        );
+        assert_eq!(session_token(&headers).as_deref(), Some("abc123"));
+        headers.insert(header::COOKIE, "lodestarr_session=".parse().unwrap());
+        assert_eq!(session_token(&headers), None);
+
+        let ip: IpAddr = "192.0.2.7".parse().unwrap();
+        for _ in 0..MAX_FAILURES {
+            assert!(!locked_out(ip));
+            record_failure(ip);
+        }
+        assert!(locked_out(ip));
+    }
+}
@@ -0,0 +1,161 @@
+//! One run for identical concurrent searches
+//!
+//! When several requests run the same uncached search at once, only the
+//! first one queries the indexers and the rest share its result. With a
+//! shared `[store]`, this also holds across instances: the first instance
+//! claims the search, and the others wait for its results to reach the
+//! shared cache. They search on their own if the results never arrive (empty
+//! results aren't cached) or the claim runs out.
+
+use crate::store::SharedStore;
+use once_cell::sync::Lazy;
+use serde::de::DeserializeOwned;
+use std::any::Any;
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::OnceCell;
+
+/// Longest another instance's claim on a search is waited for
+const CLAIM_TTL: Duration = Duration::from_secs(120);
+/// How often a waiting instance checks the shared cache
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Result slot of a running search, shared by everyone waiting on it
+type Flight = Arc<dyn Any + Send + Sync>;
+
+/// Cache key -> running search
+static FLIGHTS: Lazy<Mutex<HashMap<String, Flight>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Run `search` for `key`, unless an identical search is already running, in
+/// which case wait for that one's result. `search` must store its results
+/// under `key` in the cache for other instances to pick them up.
+pub async fn run<T, F>(store: &SharedStore, key: &str, search: F) -> T
+where
+    T: Clone + DeserializeOwned + Send + Sync + 'static,
+    F: Future<Output = T>,
+{
+    let cell = FLIGHTS.lock().ok().and_then(|mut flights| {
+        flights
+            .entry(key.to_string())
+            .or_insert_with(|| Arc::new(OnceCell::<T>::new()))
+            .clone()
+            .downcast::<OnceCell<T>>()
+            .ok()
+    });
+    let Some(cell) = cell else {
+        return search.await;
+    };
+
+    // If the search that's running is cancelled (its client went away), the
+    // next waiter runs its own
+    let results = cell
+        .get_or_init(|| across_instances(store, key, search))
+        .await
+        .clone();
+
+    if let Ok(mut flights) = FLIGHTS.lock()
+        && flights
+            .get(key)
+            .is_some_and(|f| std::ptr::addr_eq(Arc::as_ptr(f), Arc::as_ptr(&cell)))
+    {
+        flights.remove(key);
+    }
+    results
+}
+
+/// Claim the search in the shared store, or wait on the instance that claimed it
+async fn across_instances<T, F>(store: &SharedStore, key: &str, search: F) -> T
+where
+    T: DeserializeOwned,
+    F: Future<Output = T>,
+{
+    let started = Instant::now();
+    loop {
+        match store.claim(key, CLAIM_TTL).await {
+            Ok(true) => break,
+            Ok(false) => {}
+            Err(e) => {
+                tracing::debug!("Failed to claim search {}: {}", key, e);
+                break;
+            }
+        }
+        tokio::time::sleep(POLL_INTERVAL).await;
+        if let Some(results) = store
+            .get_cached(key)
+            .await
+            .ok()
+            .flatten()
+            .and_then(|json| serde_json::from_str(&json).ok())
+        {
+            return results;
+        }
+        if started.elapsed() > CLAIM_TTL {
+            break;
+        }
+    }
+
+    let _claim = Claim {
+        store: store.clone(),
+        key: key.to_string(),
+    };
+    search.await
+}
+
+/// Releases a claim when the search finishes or is cancelled
+struct Claim {
+    store: SharedStore,
+    key: String,
+}
+
+impl Drop for Claim {
+    fn drop(&mut self) {
+        let store = self.store.clone();
+        let key = std::mem::take(&mut self.key);
+        tokio::spawn(async move {
+            if let Err(e) = store.release(&key).await {
+                tracing::debug!("Failed to release search {}: {}", key, e);
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[tokio::test]
+    async fn test_run() {
+        let path = std::env::temp_dir().join(format!(
+            "lodestarr-test-single-flight-{}.db",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_file(&path);
+        let pool = crate::db::init_db(&path);
+        let store: SharedStore = Arc::new(crate::store::SqliteStore::new(pool));
+        let runs = AtomicUsize::new(0);
+
+        let search = || async {
+            runs.fetch_add(1, Ordering::SeqCst);
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            vec!["ubuntu".to_string()]
+        };
+        let (a, b, c) = tokio::join!(
+            run(&store, "native:all:ubuntu:", search()),
+            run(&store, "native:all:ubuntu:", search()),
+            run(&store, "native:all:ubuntu:", search()),
+        );
+        assert_eq!(runs.load(Ordering::SeqCst), 1);
+        assert_eq!(a, b);
+        assert_eq!(b, c);
+
+        // Finished searches aren't shared with later ones
+        run(&store, "native:all:ubuntu:", search()).await;
+        assert_eq!(runs.load(Ordering::SeqCst), 2);
+        assert!(!FLIGHTS.lock().unwrap().contains_key("native:all:ubuntu:"));
+
+        let _ = std::fs::remove_file(&path);
+    }
+}
@@ -0,0 +1,166 @@
+//! Prefetched .torrent files (enabled with `[torrent_prefetch] top = N`)
+//!
+//! After a web UI search, the .torrent files of the top results are
+//! downloaded in the background and kept in an on-disk cache next to the
+//! database, so grabbing one of them (download to server, or an *arr
+//! fetching it through the download proxy) doesn't wait on the tracker.
+//! Prefetches go through the indexer's executor and therefore its per-domain
+//! budget, run a couple at a time, skip rate-limited indexers, and leave
+//! private trackers alone unless `private = true`.
+
+use super::AppState;
+use crate::config::Config;
+use crate::indexer::SearchExecutor;
+use once_cell::sync::Lazy;
+use sha1::{Digest, Sha1};
+use std::collections::HashSet;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use tokio::sync::Semaphore;
+
+/// Largest .torrent file kept
+const MAX_TORRENT_BYTES: usize = 10 * 1024 * 1024;
+/// Cache size before the least recently used files are removed
+const MAX_CACHE_BYTES: u64 = 100 * 1024 * 1024;
+const PRUNE_TO_BYTES: u64 = MAX_CACHE_BYTES * 9 / 10;
+/// Prefetches running at once, across all searches
+const MAX_CONCURRENT: usize = 2;
+
+static SLOTS: Lazy<Arc<Semaphore>> = Lazy::new(|| Arc::new(Semaphore::new(MAX_CONCURRENT)));
+/// Links being prefetched, so repeated searches don't fetch them twice
+static IN_FLIGHT: Lazy<Mutex<HashSet<String>>> = Lazy::new(|| Mutex::new(HashSet::new()));
+
+fn key(link: &str) -> String {
+    hex::encode(Sha1::digest(link.as_bytes()))
+}
+
+/// The cached .torrent for an upstream link
+pub(super) async fn get(config: &Config, link: &str) -> Option<Vec<u8>> {
+    let path = config.get_torrent_cache_path().ok()?.join(key(link));
+    let body = tokio::fs::read(&path).await.ok()?;
+    super::image_proxy::touch(path).await;
+    Some(body)
+}
+
+async fn store(dir: &Path, link: &str, body: &[u8]) -> anyhow::Result<()> {
+    tokio::fs::create_dir_all(dir).await?;
+    tokio::fs::write(dir.join(key(link)), body).await?;
+    let dir = dir.to_path_buf();
+    tokio::task::spawn_blocking(move || {
+        super::image_proxy::prune(&dir, MAX_CACHE_BYTES, PRUNE_TO_BYTES)
+    })
+    .await??;
+    Ok(())
+}
+
+/// (indexer id, link) of the first `top` results worth prefetching:
+/// magnets need no download
+pub(super) fn candidates<'a>(
+    results: impl Iterator<Item = (&'a str, Option<&'a str>)>,
+    top: usize,
+) -> Vec<(String, String)> {
+    results
+        .take(top)
+        .filter_map(|(indexer, link)| {
+            let link = link.filter(|l| !l.is_empty() && !l.starts_with("magnet:"))?;
+            Some((indexer.to_string(), link.to_string()))
+        })
+        .collect()
+}
+
+/// Prefetch the given results' .torrent files in the background
+pub(super) fn spawn(state: AppState, config: &Config, candidates: Vec<(String, String)>) {
+    if candidates.is_empty() {
+        return;
+    }
+    let Ok(dir) = config.get_torrent_cache_path() else {
+        return;
+    };
+    let include_private = config.torrent_prefetch.private;
+    let proxy_url = config.proxy_url.clone();
+    let settings = config.native_settings.clone();
+
+    tokio::spawn(async move {
+        let definitions = state
+            .native_indexers
+            .read()
+            .await
+            .list_all_definitions()
+            .await;
+        for (indexer, link) in candidates {
+            let Some(def) = definitions.iter().find(|d| d.id == indexer) else {
+                continue;
+            };
+            if (!include_private && def.indexer_type != "public")
+                || crate::indexer::backoff::remaining(&def.id).is_some()
+                || cached(&dir, &link)
+                || !IN_FLIGHT.lock().is_ok_and(|mut f| f.insert(link.clone()))
+            {
+                continue;
+            }
+
+            let Ok(_slot) = SLOTS.clone().acquire_owned().await else {
+                break;
+            };
+            let settings = settings.get(&def.id);
+            let fetched = match SearchExecutor::for_indexer(&def.id, proxy_url.as_deref(), settings)
+            {
+                Ok(executor) => {
+                    let (download, received) =
+                        crate::http::traffic::metered(executor.download(def, &link, settings))
+                            .await;
+                    super::api_indexers::log_download_traffic(&state.db_pool, &def.id, received);
+                    download
+                }
+                Err(e) => Err(e),
+            };
+            match fetched {
+                // Real torrents only: not login pages or error HTML
+                Ok(body)
+                    if body.len() <= MAX_TORRENT_BYTES && crate::torrent::parse(&body).is_ok() =>
+                {
+                    if let Err(e) = store(&dir, &link, &body).await {
+                        tracing::warn!("Failed to cache torrent {}: {}", link, e);
+                    }
+                }
+                Ok(_) => tracing::debug!("Prefetch of {} didn't return a torrent", link),
+                Err(e) => tracing::debug!("Prefetch of {} failed: {}", link, e),
+            }
+            if let Ok(mut in_flight) = IN_FLIGHT.lock() {
+                in_flight.remove(&link);
+            }
+        }
+    });
+}
+
+fn cached(dir: &Path, link: &str) -> bool {
+    dir.join(key(link)).exists()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_candidates() {
+        let results = [
+            ("yts", Some("https://yts.example/t/1.torrent")),
+            ("eztv", Some("magnet:?xt=urn:btih:abc")),
+            ("nyaa", None),
+            ("1337x", Some("https://1337x.example/t/2")),
+        ];
+        let picked = candidates(results.iter().map(|(i, l)| (*i, *l)), 3);
+        assert_eq!(
+            picked,
+            vec![(
+                "yts".to_string(),
+                "https://yts.example/t/1.torrent".to_string()
+            )]
+        );
+        assert_eq!(
+            candidates(results.iter().map(|(i, l)| (*i, *l)), 4).len(),
+            2
+        );
+        assert!(candidates(results.iter().map(|(i, l)| (*i, *l)), 0).is_empty());
+    }
+}
@@ -0,0 +1,189 @@
+//! Result ordering (`sort` and `order`)
+//!
+//! Searches list their results in the order `[sort]` in the config asks for
+//! (most seeders first when it isn't set). The web APIs and Torznab take
+//! `sort=seeders|size|date|relevance|health` and `order=asc|desc` to
+//! override it for one search, and the CLI takes `--sort` and `--order`.
+
+use crate::torznab::TorrentResult;
+use chrono::{DateTime, Utc};
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use std::cmp::Reverse;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum SortField {
+    #[default]
+    Seeders,
+    Size,
+    /// Publish date; undated results always come last
+    Date,
+    /// Share of the query's words found in the title, then seeders
+    Relevance,
+    /// Health score (see `crate::health`)
+    Health,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum SortOrder {
+    Asc,
+    #[default]
+    Desc,
+}
+
+/// `[sort]`: how results are ordered when a search doesn't say
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Sort {
+    pub by: SortField,
+    pub order: SortOrder,
+}
+
+/// What results are sorted on
+pub trait Sortable {
+    fn title(&self) -> &str;
+    fn seeders(&self) -> Option<u32>;
+    fn size(&self) -> Option<u64>;
+    fn publish_date(&self) -> Option<DateTime<Utc>>;
+    fn health(&self) -> Option<u8>;
+}
+
+impl Sortable for TorrentResult {
+    fn title(&self) -> &str {
+        &self.title
+    }
+    fn seeders(&self) -> Option<u32> {
+        self.seeders
+    }
+    fn size(&self) -> Option<u64> {
+        self.size
+    }
+    fn publish_date(&self) -> Option<DateTime<Utc>> {
+        self.publish_date
+    }
+    fn health(&self) -> Option<u8> {
+        self.health
+    }
+}
+
+impl Sort {
+    /// This sort with a search's own `sort` and `order`, where given
+    pub fn with(self, by: Option<SortField>, order: Option<SortOrder>) -> Self {
+        Self {
+            by: by.unwrap_or(self.by),
+            order: order.unwrap_or(self.order),
+        }
+    }
+
+    /// Sort results found for `query` (only used for relevance). Ties keep
+    /// their order.
+    pub fn apply<T: Sortable>(&self, results: &mut [T], query: &str) {
+        let words = words(query);
+        let asc = self.order == SortOrder::Asc;
+        let key = |r: &T| -> (i64, u32) {
+            let seeders = r.seeders().unwrap_or(0);
+            let primary = match self.by {
+                SortField::Seeders => i64::from(seeders),
+                SortField::Size => r.size().map_or(0, |s| s.min(i64::MAX as u64) as i64),
+                SortField::Date => match r.publish_date() {
+                    Some(date) => date.timestamp(),
+                    None if asc => i64::MAX,
+                    None => i64::MIN,
+                },
+                SortField::Relevance => relevance(&words, r.title()),
+                SortField::Health => i64::from(r.health().unwrap_or(0)),
+            };
+            (primary, seeders)
+        };
+        if asc {
+            results.sort_by_cached_key(key);
+        } else {
+            results.sort_by_cached_key(|r| Reverse(key(r)));
+        }
+    }
+}
+
+/// Lowercase alphanumeric words
+fn words(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .map(str::to_lowercase)
+        .collect()
+}
+
+/// Per mille of the query's words that are words of the title
+fn relevance(query: &[String], title: &str) -> i64 {
+    if query.is_empty() {
+        return 0;
+    }
+    let title = words(title);
+    let found = query.iter().filter(|w| title.contains(w)).count();
+    (found * 1000 / query.len()) as i64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn result(title: &str, seeders: u32, size: u64, day: Option<u32>) -> TorrentResult {
+        TorrentResult {
+            title: title.to_string(),
+            seeders: Some(seeders),
+            size: Some(size),
+            publish_date: day.map(|d| Utc.with_ymd_and_hms(2026, 5, d, 0, 0, 0).unwrap()),
+            ..Default::default()
+        }
+    }
+
+    fn titles(results: &[TorrentResult]) -> Vec<&str> {
+        results.iter().map(|r| r.title.as_str()).collect()
+    }
+
+    #[test]
+    fn test_apply() {
+        let mut results = vec![
+            result("Ubuntu 24.04 Desktop", 10, 300, Some(2)),
+            result("Kubuntu 24.04", 50, 100, None),
+            result("Ubuntu Server", 5, 200, Some(9)),
+        ];
+
+        Sort::default().apply(&mut results, "");
+        assert_eq!(
+            titles(&results),
+            ["Kubuntu 24.04", "Ubuntu 24.04 Desktop", "Ubuntu Server"]
+        );
+
+        let by_size = Sort::default().with(Some(SortField::Size), Some(SortOrder::Asc));
+        by_size.apply(&mut results, "");
+        assert_eq!(
+            titles(&results),
+            ["Kubuntu 24.04", "Ubuntu Server", "Ubuntu 24.04 Desktop"]
+        );
+
+        // Undated results come last whichever way dates are sorted
+        let newest = Sort::default().with(Some(SortField::Date), None);
+        newest.apply(&mut results, "");
+        assert_eq!(
+            titles(&results),
+            ["Ubuntu Server", "Ubuntu 24.04 Desktop", "Kubuntu 24.04"]
+        );
+        newest
+            .with(None, Some(SortOrder::Asc))
+            .apply(&mut results, "");
+        assert_eq!(
+            titles(&results),
+            ["Ubuntu 24.04 Desktop", "Ubuntu Server", "Kubuntu 24.04"]
+        );
+
+        // Whole words only: "Kubuntu" doesn't match "ubuntu"
+        let relevant = Sort::default().with(Some(SortField::Relevance), None);
+        relevant.apply(&mut results, "ubuntu 24.04");
+        assert_eq!(
+            titles(&results),
+            ["Ubuntu 24.04 Desktop", "Kubuntu 24.04", "Ubuntu Server"]
+        );
+    }
+}
@@ -0,0 +1,219 @@
+//! Sports releases: event dates and leagues parsed from titles
+//!
+//! Sports releases are named by league and event date
+//! ("NBA.2024.05.01.Celtics.vs.Heat.720p", "Formula1.2024.Round05.Miami").
+//! Searches in TV/Sport (`cat=5060`) may pass `from`/`to` (YYYY-MM-DD) and
+//! `league` to keep only the matching events.
+
+use crate::models::{SportsEvent, TorrentResult};
+use chrono::NaiveDate;
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+/// Torznab TV/Sport
+pub const SPORTS_CATEGORY: i32 = 5060;
+
+/// League tokens as they appear in titles, and the name they're reported as
+const LEAGUES: &[(&[&str], &str)] = &[
+    (&["nba"], "NBA"),
+    (&["wnba"], "WNBA"),
+    (&["nfl"], "NFL"),
+    (&["nhl"], "NHL"),
+    (&["mlb"], "MLB"),
+    (&["mls"], "MLS"),
+    (&["ncaa", "ncaaf", "ncaab"], "NCAA"),
+    (&["ufc"], "UFC"),
+    (&["bellator"], "Bellator"),
+    (&["pfl"], "PFL"),
+    (&["wwe", "smackdown"], "WWE"),
+    (&["aew"], "AEW"),
+    (&["f1", "formula1", "formula 1"], "F1"),
+    (&["motogp"], "MotoGP"),
+    (&["nascar"], "NASCAR"),
+    (&["indycar"], "IndyCar"),
+    (&["epl", "premier league"], "EPL"),
+    (&["ucl", "champions league"], "UCL"),
+    (&["uefa"], "UEFA"),
+    (&["laliga", "la liga"], "LaLiga"),
+    (&["serie a", "seriea"], "Serie A"),
+    (&["bundesliga"], "Bundesliga"),
+    (&["ligue 1", "ligue1"], "Ligue 1"),
+    (&["pga"], "PGA"),
+    (&["atp"], "ATP"),
+    (&["wta"], "WTA"),
+];
+
+/// Date range and league a sports search is narrowed to
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SportsFilter {
+    pub from: Option<NaiveDate>,
+    pub to: Option<NaiveDate>,
+    pub league: Option<String>,
+}
+
+impl SportsFilter {
+    /// Filter for a search, if it is one: TV/Sport categories with a date
+    /// range or league given. Unparseable dates are an error.
+    pub fn for_search(
+        cat: Option<&str>,
+        from: Option<&str>,
+        to: Option<&str>,
+        league: Option<&str>,
+    ) -> Result<Option<Self>, String> {
+        let is_sports = cat.is_some_and(|c| {
+            c.split(',')
+                .filter_map(|c| c.trim().parse::<i32>().ok())
+                .any(|c| c == SPORTS_CATEGORY)
+        });
+        let parse = |value: Option<&str>, name: &str| {
+            value
+                .filter(|v| !v.trim().is_empty())
+                .map(|v| {
+                    NaiveDate::parse_from_str(v.trim(), "%Y-%m-%d")
+                        .map_err(|_| format!("Invalid {} date '{}', expected YYYY-MM-DD", name, v))
+                })
+                .transpose()
+        };
+        let filter = Self {
+            from: parse(from, "from")?,
+            to: parse(to, "to")?,
+            league: league
+                .map(str::trim)
+                .filter(|l| !l.is_empty())
+                .map(|l| league_name(l).unwrap_or(l).to_string()),
+        };
+        Ok((is_sports && filter != Self::default()).then_some(filter))
+    }
+
+    pub fn matches(&self, event: Option<&SportsEvent>) -> bool {
+        let date = event.and_then(|e| e.date);
+        let in_range = match (self.from, self.to) {
+            (None, None) => true,
+            (from, to) => {
+                date.is_some_and(|d| from.is_none_or(|f| d >= f) && to.is_none_or(|t| d <= t))
+            }
+        };
+        let league = match &self.league {
+            None => true,
+            Some(wanted) => event
+                .and_then(|e| e.league.as_deref())
+                .is_some_and(|l| l.eq_ignore_ascii_case(wanted)),
+        };
+        in_range && league
+    }
+}
+
+/// Parse every result's event and keep those the filter allows
+pub fn apply(results: &mut Vec<TorrentResult>, filter: &SportsFilter) {
+    for result in results.iter_mut() {
+        if result.sports.is_none() {
+            result.sports = parse(&result.title);
+        }
+    }
+    results.retain(|r| filter.matches(r.sports.as_ref()));
+}
+
+/// Event date and league named in a release title
+pub fn parse(title: &str) -> Option<SportsEvent> {
+    let event = SportsEvent {
+        date: event_date(title),
+        league: league_of(title).map(str::to_string),
+    };
+    (event.date.is_some() || event.league.is_some()).then_some(event)
+}
+
+fn event_date(title: &str) -> Option<NaiveDate> {
+    static YMD: Lazy<Regex> = Lazy::new(|| {
+        Regex::new(r"\b((?:19|20)\d{2})[.\-_ ](\d{2})[.\-_ ](\d{2})\b").expect("invalid date regex")
+    });
+    static DMY: Lazy<Regex> = Lazy::new(|| {
+        Regex::new(r"\b(\d{2})[.\-_ ](\d{2})[.\-_ ]((?:19|20)\d{2})\b").expect("invalid date regex")
+    });
+    let ymd = YMD.captures_iter(title).find_map(|c| {
+        NaiveDate::from_ymd_opt(c[1].parse().ok()?, c[2].parse().ok()?, c[3].parse().ok()?)
+    });
+    ymd.or_else(|| {
+        DMY.captures_iter(title).find_map(|c| {
+            NaiveDate::from_ymd_opt(c[3].parse().ok()?, c[2].parse().ok()?, c[1].parse().ok()?)
+        })
+    })
+}
+
+/// Words of the title, lower-cased, with `.`/`_` separators as spaces
+fn normalized(title: &str) -> String {
+    let words: Vec<String> = title
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .map(str::to_lowercase)
+        .collect();
+    format!(" {} ", words.join(" "))
+}
+
+fn league_of(title: &str) -> Option<&'static str> {
+    let title = normalized(title);
+    LEAGUES.iter().find_map(|(tokens, name)| {
+        tokens
+            .iter()
+            .any(|t| title.contains(&format!(" {} ", t)))
+            .then_some(*name)
+    })
+}
+
+/// Canonical name for a league given by the user ("formula1" -> "F1")
+fn league_name(league: &str) -> Option<&'static str> {
+    let wanted = league.to_lowercase();
+    LEAGUES.iter().find_map(|(tokens, name)| {
+        (name.eq_ignore_ascii_case(&wanted) || tokens.contains(&wanted.as_str())).then_some(*name)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let event = parse("NBA.2024.05.01.Boston.Celtics.vs.Miami.Heat.720p.WEB.h264").unwrap();
+        assert_eq!(event.date, NaiveDate::from_ymd_opt(2024, 5, 1));
+        assert_eq!(event.league.as_deref(), Some("NBA"));
+
+        let event = parse("Formula 1 Miami Grand Prix 05-05-2024 Race 1080p").unwrap();
+        assert_eq!(event.date, NaiveDate::from_ymd_opt(2024, 5, 5));
+        assert_eq!(event.league.as_deref(), Some("F1"));
+
+        let event = parse("UFC 300 Pereira vs Hill PPV 1080p").unwrap();
+        assert_eq!(event.date, None);
+        assert_eq!(event.league.as_deref(), Some("UFC"));
+
+        assert!(parse("Some.Movie.2024.1080p.BluRay").is_none());
+    }
+
+    #[test]
+    fn test_filter() {
+        assert_eq!(
+            SportsFilter::for_search(Some("5000"), Some("2024-05-01"), None, None),
+            Ok(None)
+        );
+        assert!(SportsFilter::for_search(Some("5060"), Some("May 1"), None, None).is_err());
+
+        let filter = SportsFilter::for_search(
+            Some("5060"),
+            Some("2024-05-01"),
+            Some("2024-05-03"),
+            Some("formula1"),
+        )
+        .unwrap()
+        .unwrap();
+        assert_eq!(filter.league.as_deref(), Some("F1"));
+
+        let mut results = vec![
+            TorrentResult::new("F1.2024.05.02.Miami.Practice".into(), "a".into()),
+            TorrentResult::new("F1.2024.04.21.China.Race".into(), "b".into()),
+            TorrentResult::new("NBA.2024.05.02.Game".into(), "c".into()),
+            TorrentResult::new("F1 Season Review".into(), "d".into()),
+        ];
+        apply(&mut results, &filter);
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].guid, "a");
+    }
+}
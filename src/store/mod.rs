@@ -0,0 +1,181 @@
+//! Search cache and history storage (`[store]`)
+//!
+//! The server keeps cached results, search history and download history
+//! behind the [`Store`] trait. The default backend is the local SQLite
+//! database. With the `redis-store` feature, a Redis server can hold them
+//! instead, so several instances behind a load balancer share one cache and
+//! one history. Its calls run on the blocking thread pool so a slow Redis
+//! server doesn't stall the async workers.
+//!
+//! Data tied to an instance stays in SQLite whatever the backend: sessions,
+//! the library index, indexer statistics, raw responses and the TUI.
+
+mod sqlite;
+pub use sqlite::SqliteStore;
+
+#[cfg(feature = "redis-store")]
+mod redis_store;
+#[cfg(feature = "redis-store")]
+pub use redis_store::RedisStore;
+
+use crate::config::{StoreBackend, StoreConfig};
+use crate::db::{CachedSearch, DbPool, DownloadLog, SearchLog};
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use std::sync::Arc;
+
+pub type SharedStore = Arc<dyn Store>;
+
+/// Shared search cache and history
+#[async_trait::async_trait]
+pub trait Store: Send + Sync {
+    /// Backend name for logs
+    fn name(&self) -> &'static str;
+
+    /// Cached results that haven't expired
+    async fn get_cached(&self, key: &str) -> Result<Option<String>>;
+    /// Cache results for `ttl`, replacing any previous entry
+    async fn set_cached(&self, key: &str, results: &str, ttl: chrono::Duration) -> Result<()>;
+    /// Get a cache entry and remove it, so it's served at most once
+    async fn take_cached(&self, key: &str) -> Result<Option<String>>;
+    /// Unexpired web searches in the cache, latest expiry first
+    async fn cached_searches(&self) -> Result<Vec<CachedSearch>>;
+    /// Remove every cache entry, returning how many there were
+    async fn clear_cache(&self) -> Result<usize>;
+    /// Drop expired entries (for backends that don't expire them on their own)
+    async fn cleanup_cache(&self) -> Result<()> {
+        Ok(())
+    }
+    /// Claim a search for this instance until it is released or `ttl` passes.
+    /// False while another instance holds the claim. Backends that aren't
+    /// shared always grant it.
+    async fn claim(&self, _key: &str, _ttl: std::time::Duration) -> Result<bool> {
+        Ok(true)
+    }
+    async fn release(&self, _key: &str) -> Result<()> {
+        Ok(())
+    }
+
+    async fn log_search(
+        &self,
+        query: &str,
+        indexer: &str,
+        result_count: usize,
+        duration_ms: u128,
+    ) -> Result<()>;
+    /// Newest searches first
+    async fn recent_searches(&self, limit: usize) -> Result<Vec<SearchLog>>;
+    /// Searches logged since `since`, oldest first
+    async fn searches_since(&self, since: DateTime<Utc>) -> Result<Vec<SearchLog>>;
+    /// Number of logged searches and their average duration in milliseconds
+    async fn search_totals(&self) -> Result<(usize, f64)>;
+    async fn clear_search_logs(&self) -> Result<usize>;
+
+    async fn log_download(
+        &self,
+        title: Option<&str>,
+        magnet: Option<&str>,
+        download_link: Option<&str>,
+        client_name: Option<&str>,
+        download_type: &str,
+    ) -> Result<()>;
+    /// Newest downloads first
+    async fn download_logs(&self, limit: usize) -> Result<Vec<DownloadLog>>;
+    /// Every magnet and link that was downloaded
+    async fn downloaded_links(&self) -> Result<Vec<String>>;
+    async fn clear_download_logs(&self) -> Result<usize>;
+}
+
+/// Open the configured backend. `pool` is the local database, used by the
+/// SQLite backend.
+pub fn open(config: &StoreConfig, pool: &DbPool) -> Result<SharedStore> {
+    match config.backend {
+        StoreBackend::Sqlite => Ok(Arc::new(SqliteStore::new(pool.clone()))),
+        #[cfg(feature = "redis-store")]
+        StoreBackend::Redis => {
+            use anyhow::Context;
+            let url = config
+                .url
+                .as_deref()
+                .map(str::trim)
+                .filter(|u| !u.is_empty())
+                .context("[store] backend = \"redis\" needs a url")?;
+            let prefix = config.prefix.as_deref().unwrap_or("lodestarr:");
+            Ok(Arc::new(RedisStore::connect(url, prefix)?))
+        }
+        #[cfg(not(feature = "redis-store"))]
+        StoreBackend::Redis => anyhow::bail!(
+            "[store] backend = \"redis\" needs a build with the redis-store feature \
+             (cargo build --release --features redis-store)"
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_sqlite_store() {
+        let path =
+            std::env::temp_dir().join(format!("lodestarr-test-store-{}.db", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let pool = crate::db::init_db(&path);
+        let store = open(&StoreConfig::default(), &pool).unwrap();
+        assert_eq!(store.name(), "sqlite");
+
+        let hour = chrono::Duration::hours(1);
+        store
+            .set_cached("native:yts:ubuntu:", "[{}, {}]", hour)
+            .await
+            .unwrap();
+        store
+            .set_cached("partial:yts:abc", "[]", hour)
+            .await
+            .unwrap();
+        store.set_cached("rss:all:def", "[]", hour).await.unwrap();
+        assert_eq!(
+            store
+                .get_cached("native:yts:ubuntu:")
+                .await
+                .unwrap()
+                .as_deref(),
+            Some("[{}, {}]")
+        );
+        let searches = store.cached_searches().await.unwrap();
+        assert_eq!(searches.len(), 1);
+        assert_eq!(searches[0].result_count, 2);
+        assert!(store.take_cached("rss:all:def").await.unwrap().is_some());
+        assert!(store.take_cached("rss:all:def").await.unwrap().is_none());
+        assert_eq!(store.clear_cache().await.unwrap(), 2);
+
+        store.log_search("ubuntu", "yts", 2, 100).await.unwrap();
+        store.log_search("debian", "all", 0, 300).await.unwrap();
+        assert_eq!(store.search_totals().await.unwrap(), (2, 200.0));
+        assert_eq!(store.recent_searches(1).await.unwrap().len(), 1);
+        let since = chrono::Utc::now() - hour;
+        let logged = store.searches_since(since).await.unwrap();
+        assert_eq!(logged[0].query, "ubuntu");
+        assert_eq!(logged[1].duration_ms, 300);
+
+        store
+            .log_download(Some("Ubuntu"), Some("magnet:?xt=1"), None, None, "magnet")
+            .await
+            .unwrap();
+        assert_eq!(
+            store.downloaded_links().await.unwrap(),
+            vec!["magnet:?xt=1"]
+        );
+        assert_eq!(store.clear_download_logs().await.unwrap(), 1);
+        assert!(store.download_logs(10).await.unwrap().is_empty());
+
+        let redis = StoreConfig {
+            backend: StoreBackend::Redis,
+            ..Default::default()
+        };
+        assert!(open(&redis, &pool).is_err());
+
+        drop(pool);
+        let _ = std::fs::remove_file(&path);
+    }
+}
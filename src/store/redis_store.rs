@@ -0,0 +1,462 @@
+//! [`Store`] on a Redis server shared by several instances
+//!
+//! Cache entries are plain keys with a Redis expiry. Histories are capped
+//! lists of JSON records, newest first. The client is synchronous, so every
+//! call runs on the blocking thread pool.
+
+use super::Store;
+use crate::db::{CachedSearch, DownloadLog, SearchLog};
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use redis::Commands;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Entries kept in each history list
+const HISTORY_LIMIT: isize = 1000;
+
+pub struct RedisStore {
+    inner: Arc<Inner>,
+}
+
+impl RedisStore {
+    pub fn connect(url: &str, prefix: &str) -> Result<Self> {
+        let client = redis::Client::open(url).context("Invalid Redis URL")?;
+        let pool = r2d2::Pool::builder()
+            .connection_timeout(Duration::from_secs(5))
+            .build(client)
+            .context("Failed to connect to Redis")?;
+        Ok(Self {
+            inner: Arc::new(Inner {
+                pool,
+                prefix: prefix.to_string(),
+            }),
+        })
+    }
+
+    /// Run `f` on the blocking thread pool
+    async fn blocking<T, F>(&self, f: F) -> Result<T>
+    where
+        T: Send + 'static,
+        F: FnOnce(&Inner) -> Result<T> + Send + 'static,
+    {
+        let inner = self.inner.clone();
+        tokio::task::spawn_blocking(move || f(&inner)).await?
+    }
+}
+
+/// The connection pool and the synchronous commands
+struct Inner {
+    pool: r2d2::Pool<redis::Client>,
+    prefix: String,
+}
+
+impl Inner {
+    fn key(&self, name: &str) -> String {
+        format!("{}{}", self.prefix, name)
+    }
+
+    fn conn(&self) -> Result<r2d2::PooledConnection<redis::Client>> {
+        Ok(self.pool.get()?)
+    }
+
+    /// Push a record onto a history list and trim it
+    fn push(&self, list: &str, record: &impl serde::Serialize) -> Result<()> {
+        let json = serde_json::to_string(record)?;
+        redis::pipe()
+            .lpush(self.key(list), json)
+            .ignore()
+            .ltrim(self.key(list), 0, HISTORY_LIMIT - 1)
+            .ignore()
+            .exec(&mut *self.conn()?)?;
+        Ok(())
+    }
+
+    fn range<T: serde::de::DeserializeOwned>(&self, list: &str, limit: usize) -> Result<Vec<T>> {
+        if limit == 0 {
+            return Ok(Vec::new());
+        }
+        let items: Vec<String> =
+            self.conn()?
+                .lrange(self.key(list), 0, (limit as isize).saturating_sub(1))?;
+        Ok(items
+            .iter()
+            .filter_map(|item| serde_json::from_str(item).ok())
+            .collect())
+    }
+}
+
+/// Every key matching `pattern`
+fn scan(conn: &mut redis::Connection, pattern: &str) -> Result<Vec<String>> {
+    Ok(conn
+        .scan_match::<_, String>(pattern)?
+        .collect::<Result<_, _>>()?)
+}
+
+/// The [`Store`] commands, run through [`RedisStore::blocking`]
+impl Inner {
+    fn get_cached(&self, key: &str) -> Result<Option<String>> {
+        Ok(self.conn()?.get(self.key(&format!("cache:{}", key)))?)
+    }
+
+    fn set_cached(&self, key: &str, results: &str, ttl: chrono::Duration) -> Result<()> {
+        let seconds = ttl.num_seconds().max(1) as u64;
+        let _: () = self
+            .conn()?
+            .set_ex(self.key(&format!("cache:{}", key)), results, seconds)?;
+        Ok(())
+    }
+
+    fn take_cached(&self, key: &str) -> Result<Option<String>> {
+        Ok(self.conn()?.get_del(self.key(&format!("cache:{}", key)))?)
+    }
+
+    fn cached_searches(&self) -> Result<Vec<CachedSearch>> {
+        let prefix = self.key("cache:");
+        let mut conn = self.conn()?;
+        let keys: Vec<String> = scan(&mut conn, &format!("{}*", prefix))?
+            .into_iter()
+            .filter(|key| {
+                key.strip_prefix(&prefix)
+                    // Not aggregate partials or RSS prefetches
+                    .is_some_and(|k| !k.starts_with("partial:") && !k.starts_with("rss:"))
+            })
+            .collect();
+        let now = Utc::now();
+        let mut searches = Vec::new();
+        for key in keys {
+            let (results, ttl): (Option<String>, i64) =
+                redis::pipe().get(&key).ttl(&key).query(&mut *conn)?;
+            if let Some(results) = results {
+                let cache_key = key[prefix.len()..].to_string();
+                let expires_at = now + chrono::Duration::seconds(ttl.max(0));
+                searches.push(CachedSearch::new(cache_key, &results, expires_at));
+            }
+        }
+        searches.sort_by_key(|s| std::cmp::Reverse(s.expires_at));
+        Ok(searches)
+    }
+
+    fn clear_cache(&self) -> Result<usize> {
+        let mut conn = self.conn()?;
+        let keys = scan(&mut conn, &self.key("cache:*"))?;
+        let mut deleted = 0;
+        for chunk in keys.chunks(500) {
+            deleted += conn.del::<_, usize>(chunk)?;
+        }
+        Ok(deleted)
+    }
+
+    fn claim(&self, key: &str, ttl: Duration) -> Result<bool> {
+        let claimed: Option<String> = redis::cmd("SET")
+            .arg(self.key(&format!("claim:{}", key)))
+            .arg(1)
+            .arg("NX")
+            .arg("PX")
+            .arg(ttl.as_millis() as u64)
+            .query(&mut *self.conn()?)?;
+        Ok(claimed.is_some())
+    }
+
+    fn release(&self, key: &str) -> Result<()> {
+        let _: () = self.conn()?.del(self.key(&format!("claim:{}", key)))?;
+        Ok(())
+    }
+
+    fn log_search(
+        &self,
+        query: &str,
+        indexer: &str,
+        result_count: usize,
+        duration_ms: u128,
+    ) -> Result<()> {
+        self.push(
+            "searches",
+            &SearchLog {
+                query: query.to_string(),
+                indexer: indexer.to_string(),
+                timestamp: Utc::now(),
+                result_count,
+                duration_ms: duration_ms as u64,
+            },
+        )?;
+        redis::pipe()
+            .incr(self.key("search_count"), 1)
+            .ignore()
+            .incr(self.key("search_ms"), duration_ms as u64)
+            .ignore()
+            .exec(&mut *self.conn()?)?;
+        Ok(())
+    }
+
+    fn recent_searches(&self, limit: usize) -> Result<Vec<SearchLog>> {
+        self.range("searches", limit)
+    }
+
+    fn searches_since(&self, since: DateTime<Utc>) -> Result<Vec<SearchLog>> {
+        let mut logs: Vec<SearchLog> = self.range("searches", HISTORY_LIMIT as usize)?;
+        logs.retain(|log| log.timestamp > since);
+        logs.reverse();
+        Ok(logs)
+    }
+
+    fn search_totals(&self) -> Result<(usize, f64)> {
+        let (count, ms): (Option<u64>, Option<u64>) = self
+            .conn()?
+            .mget(&[self.key("search_count"), self.key("search_ms")])?;
+        let count = count.unwrap_or(0);
+        let avg = if count == 0 {
+            0.0
+        } else {
+            ms.unwrap_or(0) as f64 / count as f64
+        };
+        Ok((count as usize, avg))
+    }
+
+    fn clear_search_logs(&self) -> Result<usize> {
+        let mut conn = self.conn()?;
+        let count: Option<usize> = conn.get(self.key("search_count"))?;
+        let _: () = conn.del(&[
+            self.key("searches"),
+            self.key("search_count"),
+            self.key("search_ms"),
+        ])?;
+        Ok(count.unwrap_or(0))
+    }
+
+    fn log_download(
+        &self,
+        title: Option<&str>,
+        magnet: Option<&str>,
+        download_link: Option<&str>,
+        client_name: Option<&str>,
+        download_type: &str,
+    ) -> Result<()> {
+        let id: i64 = self.conn()?.incr(self.key("download_id"), 1)?;
+        self.push(
+            "downloads",
+            &DownloadLog {
+                id,
+                title: title.map(str::to_string),
+                magnet: magnet.map(str::to_string),
+                download_link: download_link.map(str::to_string),
+                client_name: client_name.map(str::to_string),
+                download_type: download_type.to_string(),
+                timestamp: Utc::now(),
+            },
+        )?;
+        let links: Vec<&str> = magnet.into_iter().chain(download_link).collect();
+        if !links.is_empty() {
+            let _: () = self.conn()?.sadd(self.key("downloaded"), links)?;
+        }
+        Ok(())
+    }
+
+    fn download_logs(&self, limit: usize) -> Result<Vec<DownloadLog>> {
+        self.range("downloads", limit)
+    }
+
+    fn downloaded_links(&self) -> Result<Vec<String>> {
+        Ok(self.conn()?.smembers(self.key("downloaded"))?)
+    }
+
+    fn clear_download_logs(&self) -> Result<usize> {
+        let mut conn = self.conn()?;
+        let count: usize = conn.llen(self.key("downloads"))?;
+        let _: () = conn.del(&[self.key("downloads"), self.key("downloaded")])?;
+        Ok(count)
+    }
+}
+
+#[async_trait::async_trait]
+impl Store for RedisStore {
+    fn name(&self) -> &'static str {
+        "redis"
+    }
+
+    async fn get_cached(&self, key: &str) -> Result<Option<String>> {
+        let key = key.to_string();
+        self.blocking(move |r| r.get_cached(&key)).await
+    }
+
+    async fn set_cached(&self, key: &str, results: &str, ttl: chrono::Duration) -> Result<()> {
+        let (key, results) = (key.to_string(), results.to_string());
+        self.blocking(move |r| r.set_cached(&key, &results, ttl))
+            .await
+    }
+
+    async fn take_cached(&self, key: &str) -> Result<Option<String>> {
+        let key = key.to_string();
+        self.blocking(move |r| r.take_cached(&key)).await
+    }
+
+    async fn cached_searches(&self) -> Result<Vec<CachedSearch>> {
+        self.blocking(Inner::cached_searches).await
+    }
+
+    async fn clear_cache(&self) -> Result<usize> {
+        self.blocking(Inner::clear_cache).await
+    }
+
+    async fn claim(&self, key: &str, ttl: Duration) -> Result<bool> {
+        let key = key.to_string();
+        self.blocking(move |r| r.claim(&key, ttl)).await
+    }
+
+    async fn release(&self, key: &str) -> Result<()> {
+        let key = key.to_string();
+        self.blocking(move |r| r.release(&key)).await
+    }
+
+    async fn log_search(
+        &self,
+        query: &str,
+        indexer: &str,
+        result_count: usize,
+        duration_ms: u128,
+    ) -> Result<()> {
+        let (query, indexer) = (query.to_string(), indexer.to_string());
+        self.blocking(move |r| r.log_search(&query, &indexer, result_count, duration_ms))
+            .await
+    }
+
+    async fn recent_searches(&self, limit: usize) -> Result<Vec<SearchLog>> {
+        self.blocking(move |r| r.recent_searches(limit)).await
+    }
+
+    async fn searches_since(&self, since: DateTime<Utc>) -> Result<Vec<SearchLog>> {
+        self.blocking(move |r| r.searches_since(since)).await
+    }
+
+    async fn search_totals(&self) -> Result<(usize, f64)> {
+        self.blocking(Inner::search_totals).await
+    }
+
+    async fn clear_search_logs(&self) -> Result<usize> {
+        self.blocking(Inner::clear_search_logs).await
+    }
+
+    async fn log_download(
+        &self,
+        title: Option<&str>,
+        magnet: Option<&str>,
+        download_link: Option<&str>,
+        client_name: Option<&str>,
+        download_type: &str,
+    ) -> Result<()> {
+        let owned = |s: Option<&str>| s.map(str::to_string);
+        let (title, magnet, download_link, client_name) = (
+            owned(title),
+            owned(magnet),
+            owned(download_link),
+            owned(client_name),
+        );
+        let download_type = download_type.to_string();
+        self.blocking(move |r| {
+            r.log_download(
+                title.as_deref(),
+                magnet.as_deref(),
+                download_link.as_deref(),
+                client_name.as_deref(),
+                &download_type,
+            )
+        })
+        .await
+    }
+
+    async fn download_logs(&self, limit: usize) -> Result<Vec<DownloadLog>> {
+        self.blocking(move |r| r.download_logs(limit)).await
+    }
+
+    async fn downloaded_links(&self) -> Result<Vec<String>> {
+        self.blocking(Inner::downloaded_links).await
+    }
+
+    async fn clear_download_logs(&self) -> Result<usize> {
+        self.blocking(Inner::clear_download_logs).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Runs against the server in `LODESTARR_TEST_REDIS_URL`, under a prefix
+    /// of its own
+    #[tokio::test]
+    #[ignore] // Requires a Redis server
+    async fn test_redis_store() {
+        let url = std::env::var("LODESTARR_TEST_REDIS_URL")
+            .unwrap_or_else(|_| "redis://127.0.0.1:6379".to_string());
+        let prefix = format!("lodestarr-test-{}:", std::process::id());
+        let store = RedisStore::connect(&url, &prefix).unwrap();
+        let other = RedisStore::connect(&url, &prefix).unwrap();
+
+        let hour = chrono::Duration::hours(1);
+        store
+            .set_cached("native:yts:ubuntu:", "[{}, {}]", hour)
+            .await
+            .unwrap();
+        store
+            .set_cached("partial:yts:abc", "[]", hour)
+            .await
+            .unwrap();
+        // Another instance sees the same cache
+        assert_eq!(
+            other
+                .get_cached("native:yts:ubuntu:")
+                .await
+                .unwrap()
+                .as_deref(),
+            Some("[{}, {}]")
+        );
+        let searches = store.cached_searches().await.unwrap();
+        assert_eq!(searches.len(), 1);
+        assert_eq!(searches[0].result_count, 2);
+        assert!(
+            store
+                .take_cached("partial:yts:abc")
+                .await
+                .unwrap()
+                .is_some()
+        );
+        assert!(
+            other
+                .take_cached("partial:yts:abc")
+                .await
+                .unwrap()
+                .is_none()
+        );
+
+        let claim = std::time::Duration::from_secs(10);
+        assert!(store.claim("native:all:x:", claim).await.unwrap());
+        assert!(!other.claim("native:all:x:", claim).await.unwrap());
+        store.release("native:all:x:").await.unwrap();
+        assert!(other.claim("native:all:x:", claim).await.unwrap());
+        other.release("native:all:x:").await.unwrap();
+
+        store.log_search("ubuntu", "yts", 2, 100).await.unwrap();
+        other.log_search("debian", "all", 0, 300).await.unwrap();
+        assert_eq!(store.search_totals().await.unwrap(), (2, 200.0));
+        let logged = store
+            .searches_since(chrono::Utc::now() - hour)
+            .await
+            .unwrap();
+        assert_eq!(logged[0].query, "ubuntu");
+        assert_eq!(logged[1].duration_ms, 300);
+
+        store
+            .log_download(Some("Ubuntu"), Some("magnet:?xt=1"), None, None, "magnet")
+            .await
+            .unwrap();
+        assert_eq!(
+            other.downloaded_links().await.unwrap(),
+            vec!["magnet:?xt=1"]
+        );
+        assert_eq!(store.download_logs(10).await.unwrap().len(), 1);
+
+        assert_eq!(store.clear_cache().await.unwrap(), 1);
+        assert_eq!(store.clear_search_logs().await.unwrap(), 2);
+        assert_eq!(store.clear_download_logs().await.unwrap(), 1);
+    }
+}
@@ -0,0 +1,106 @@
+//! [`Store`] on the local database (the default)
+
+use super::Store;
+use crate::db::{self, CachedSearch, DbPool, DownloadLog, SearchLog};
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+
+pub struct SqliteStore {
+    pool: DbPool,
+}
+
+impl SqliteStore {
+    pub fn new(pool: DbPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait::async_trait]
+impl Store for SqliteStore {
+    fn name(&self) -> &'static str {
+        "sqlite"
+    }
+
+    async fn get_cached(&self, key: &str) -> Result<Option<String>> {
+        db::get_cached_results(&self.pool, key)
+    }
+
+    async fn set_cached(&self, key: &str, results: &str, ttl: chrono::Duration) -> Result<()> {
+        db::set_cached_results_for(&self.pool, key, results, ttl)
+    }
+
+    async fn take_cached(&self, key: &str) -> Result<Option<String>> {
+        db::take_cached_results(&self.pool, key)
+    }
+
+    async fn cached_searches(&self) -> Result<Vec<CachedSearch>> {
+        db::get_cached_search_list(&self.pool)
+    }
+
+    async fn clear_cache(&self) -> Result<usize> {
+        db::clear_all_cache(&self.pool)
+    }
+
+    async fn cleanup_cache(&self) -> Result<()> {
+        db::cleanup_cache(&self.pool)
+    }
+
+    async fn log_search(
+        &self,
+        query: &str,
+        indexer: &str,
+        result_count: usize,
+        duration_ms: u128,
+    ) -> Result<()> {
+        db::log_search(&self.pool, query, indexer, result_count, duration_ms)
+    }
+
+    async fn recent_searches(&self, limit: usize) -> Result<Vec<SearchLog>> {
+        db::get_recent_logs(&self.pool, limit)
+    }
+
+    async fn searches_since(&self, since: DateTime<Utc>) -> Result<Vec<SearchLog>> {
+        db::get_search_logs_since(&self.pool, since)
+    }
+
+    async fn search_totals(&self) -> Result<(usize, f64)> {
+        Ok((
+            db::get_total_searches(&self.pool)?,
+            db::get_avg_duration(&self.pool)?,
+        ))
+    }
+
+    async fn clear_search_logs(&self) -> Result<usize> {
+        db::clear_search_logs(&self.pool)
+    }
+
+    async fn log_download(
+        &self,
+        title: Option<&str>,
+        magnet: Option<&str>,
+        download_link: Option<&str>,
+        client_name: Option<&str>,
+        download_type: &str,
+    ) -> Result<()> {
+        db::log_download(
+            &self.pool,
+            title,
+            magnet,
+            download_link,
+            client_name,
+            download_type,
+        )
+    }
+
+    async fn download_logs(&self, limit: usize) -> Result<Vec<DownloadLog>> {
+        db::get_download_logs(&self.pool, limit)
+    }
+
+    async fn downloaded_links(&self) -> Result<Vec<String>> {
+        db::get_downloaded_links(&self.pool)
+    }
+
+    async fn clear_download_logs(&self) -> Result<usize> {
+        db::clear_download_logs(&self.pool)
+    }
+}
@@ -0,0 +1,326 @@
+//! Reading .torrent files
+//!
+//! A small bencode parser that keeps byte strings borrowed from the input.
+//! The infohash is the SHA-1 of the `info` dictionary exactly as it appears
+//! in the file, so torrents with unsorted keys or extra fields still hash to
+//! what clients and trackers see.
+
+use anyhow::{Result, anyhow, bail};
+use sha1::{Digest, Sha1};
+use std::collections::BTreeMap;
+
+/// Deepest nesting accepted, so crafted input can't exhaust the stack
+const MAX_DEPTH: usize = 64;
+
+/// A bencoded value
+#[derive(Debug, Clone, PartialEq)]
+enum Value<'a> {
+    Int(i64),
+    Bytes(&'a [u8]),
+    List(Vec<Value<'a>>),
+    Dict(BTreeMap<&'a [u8], Value<'a>>),
+}
+
+impl<'a> Value<'a> {
+    fn get(&self, key: &str) -> Option<&Value<'a>> {
+        match self {
+            Value::Dict(dict) => dict.get(key.as_bytes()),
+            _ => None,
+        }
+    }
+
+    fn int(&self) -> Option<i64> {
+        match self {
+            Value::Int(n) => Some(*n),
+            _ => None,
+        }
+    }
+
+    fn text(&self) -> Option<String> {
+        match self {
+            Value::Bytes(bytes) => Some(String::from_utf8_lossy(bytes).into_owned()),
+            _ => None,
+        }
+    }
+
+    fn list(&self) -> &[Value<'a>] {
+        match self {
+            Value::List(items) => items,
+            _ => &[],
+        }
+    }
+}
+
+struct Parser<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn peek(&self) -> Result<u8> {
+        self.data
+            .get(self.pos)
+            .copied()
+            .ok_or_else(|| anyhow!("Unexpected end of data"))
+    }
+
+    /// Digits up to `end`, consuming the terminator
+    fn number(&mut self, end: u8) -> Result<i64> {
+        let len = self.data[self.pos..]
+            .iter()
+            .position(|&b| b == end)
+            .ok_or_else(|| anyhow!("Unterminated number at byte {}", self.pos))?;
+        let digits = std::str::from_utf8(&self.data[self.pos..self.pos + len])?;
+        let valid = !digits.is_empty()
+            && digits
+                .strip_prefix('-')
+                .unwrap_or(digits)
+                .bytes()
+                .all(|b| b.is_ascii_digit());
+        if !valid {
+            bail!("Invalid number {:?} at byte {}", digits, self.pos);
+        }
+        self.pos += len + 1;
+        Ok(digits.parse()?)
+    }
+
+    fn bytes(&mut self) -> Result<&'a [u8]> {
+        let len = usize::try_from(self.number(b':')?)?;
+        let end = self
+            .pos
+            .checked_add(len)
+            .filter(|&end| end <= self.data.len())
+            .ok_or_else(|| anyhow!("String runs past the end of the data"))?;
+        let bytes = &self.data[self.pos..end];
+        self.pos = end;
+        Ok(bytes)
+    }
+
+    fn value(&mut self, depth: usize) -> Result<Value<'a>> {
+        if depth > MAX_DEPTH {
+            bail!("Nested too deeply");
+        }
+        match self.peek()? {
+            b'i' => {
+                self.pos += 1;
+                Ok(Value::Int(self.number(b'e')?))
+            }
+            b'0'..=b'9' => Ok(Value::Bytes(self.bytes()?)),
+            b'l' => {
+                self.pos += 1;
+                let mut items = Vec::new();
+                while self.peek()? != b'e' {
+                    items.push(self.value(depth + 1)?);
+                }
+                self.pos += 1;
+                Ok(Value::List(items))
+            }
+            b'd' => {
+                self.pos += 1;
+                let mut dict = BTreeMap::new();
+                while self.peek()? != b'e' {
+                    let key = self.bytes()?;
+                    dict.insert(key, self.value(depth + 1)?);
+                }
+                self.pos += 1;
+                Ok(Value::Dict(dict))
+            }
+            other => bail!("Unexpected byte {:?} at byte {}", other as char, self.pos),
+        }
+    }
+}
+
+/// A file in a torrent
+#[derive(Debug, Clone, PartialEq)]
+pub struct TorrentFile {
+    /// Path inside the torrent, `/`-separated
+    pub path: String,
+    pub size: u64,
+}
+
+/// What Lodestarr reads from a .torrent file
+#[derive(Debug, Clone)]
+pub struct Torrent {
+    pub name: String,
+    /// Hex SHA-1 of the info dictionary
+    pub info_hash: String,
+    pub piece_length: u64,
+    pub piece_count: usize,
+    pub total_size: u64,
+    pub files: Vec<TorrentFile>,
+    /// `announce` followed by the `announce-list` tiers, without duplicates
+    pub trackers: Vec<String>,
+    pub private: bool,
+    pub created_by: Option<String>,
+    /// Unix timestamp
+    pub creation_date: Option<i64>,
+    pub comment: Option<String>,
+}
+
+/// Parse a .torrent file
+pub fn parse(data: &[u8]) -> Result<Torrent> {
+    if looks_like_html(data) {
+        bail!("Got an HTML page instead of a torrent (login or error page?)");
+    }
+    if data.first() != Some(&b'd') {
+        bail!("Not a torrent file");
+    }
+
+    // Walk the top-level dictionary by hand to keep the info dictionary's
+    // original bytes for the infohash
+    let mut parser = Parser { data, pos: 1 };
+    let mut top = BTreeMap::new();
+    let mut info_bytes = None;
+    while parser.peek()? != b'e' {
+        let key = parser.bytes()?;
+        let start = parser.pos;
+        let value = parser.value(1)?;
+        if key == b"info" {
+            info_bytes = Some(&data[start..parser.pos]);
+        }
+        top.insert(key, value);
+    }
+    // Some trackers end the file with a newline
+    if !data[parser.pos + 1..].iter().all(u8::is_ascii_whitespace) {
+        bail!("Trailing data after byte {}", parser.pos + 1);
+    }
+    let top = Value::Dict(top);
+
+    let (Some(info_bytes), Some(info @ Value::Dict(_))) = (info_bytes, top.get("info")) else {
+        bail!("Torrent has no info dictionary");
+    };
+    let name = info
+        .get("name.utf-8")
+        .or_else(|| info.get("name"))
+        .and_then(Value::text)
+        .ok_or_else(|| anyhow!("Torrent has no name"))?;
+    let size = |value: &Value| value.get("length").and_then(Value::int).unwrap_or(0).max(0) as u64;
+
+    let files: Vec<TorrentFile> = match info.get("files") {
+        Some(files) => files
+            .list()
+            .iter()
+            .map(|file| {
+                let path = file
+                    .get("path.utf-8")
+                    .or_else(|| file.get("path"))
+                    .map(|path| {
+                        path.list()
+                            .iter()
+                            .filter_map(Value::text)
+                            .collect::<Vec<_>>()
+                            .join("/")
+                    })
+                    .unwrap_or_default();
+                TorrentFile {
+                    path,
+                    size: size(file),
+                }
+            })
+            .collect(),
+        None => vec![TorrentFile {
+            path: name.clone(),
+            size: size(info),
+        }],
+    };
+
+    let mut trackers: Vec<String> = Vec::new();
+    let tiers = top
+        .get("announce-list")
+        .map(Value::list)
+        .unwrap_or_default();
+    for tracker in top
+        .get("announce")
+        .into_iter()
+        .chain(tiers.iter().flat_map(Value::list))
+        .filter_map(Value::text)
+    {
+        if !tracker.is_empty() && !trackers.contains(&tracker) {
+            trackers.push(tracker);
+        }
+    }
+
+    let pieces = match info.get("pieces") {
+        Some(Value::Bytes(pieces)) => pieces.len(),
+        _ => 0,
+    };
+    Ok(Torrent {
+        info_hash: hex::encode(Sha1::digest(info_bytes)),
+        piece_length: info
+            .get("piece length")
+            .and_then(Value::int)
+            .unwrap_or(0)
+            .max(0) as u64,
+        piece_count: pieces / 20,
+        total_size: files.iter().map(|f| f.size).sum(),
+        files,
+        trackers,
+        private: info.get("private").and_then(Value::int) == Some(1),
+        created_by: top.get("created by").and_then(Value::text),
+        creation_date: top.get("creation date").and_then(Value::int),
+        comment: top.get("comment").and_then(Value::text),
+        name,
+    })
+}
+
+/// Whether a download is a web page rather than a torrent
+fn looks_like_html(data: &[u8]) -> bool {
+    let start = String::from_utf8_lossy(&data[..data.len().min(256)]).to_ascii_lowercase();
+    let start = start.trim_start_matches('\u{feff}').trim_start();
+    start.starts_with("<!doctype html") || start.starts_with("<html") || start.starts_with("<?xml")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        // Keys out of order, as some trackers write them: the infohash must
+        // come from the bytes as given
+        let info = b"d4:name4:Dune12:piece lengthi16384e6:pieces40:aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa5:filesld6:lengthi100e4:pathl4:Dune8:dune.mkveed6:lengthi5e4:pathl8:dune.nfoeee7:privatei1ee";
+        let torrent = [
+            b"d8:announce14:http://t.ex/an13:announce-listll14:http://t.ex/anel13:udp://t.ex:80ee4:info"
+                .as_slice(),
+            info,
+            b"e",
+        ]
+        .concat();
+
+        let parsed = parse(&torrent).unwrap();
+        assert_eq!(parsed.name, "Dune");
+        assert_eq!(parsed.info_hash, hex::encode(Sha1::digest(info)));
+        assert_eq!(parsed.piece_count, 2);
+        assert_eq!(parsed.piece_length, 16384);
+        assert_eq!(parsed.total_size, 105);
+        assert_eq!(
+            parsed.files,
+            vec![
+                TorrentFile {
+                    path: "Dune/dune.mkv".to_string(),
+                    size: 100
+                },
+                TorrentFile {
+                    path: "dune.nfo".to_string(),
+                    size: 5
+                },
+            ]
+        );
+        assert_eq!(parsed.trackers, vec!["http://t.ex/an", "udp://t.ex:80"]);
+        assert!(parsed.private);
+
+        assert!(
+            parse(b"<!DOCTYPE html><html>Login</html>")
+                .unwrap_err()
+                .to_string()
+                .contains("HTML")
+        );
+        assert!(parse(b"d4:info").is_err());
+        assert!(parse(b"d4:infod4:name1:xee trailing").is_err());
+        assert_eq!(parse(b"d4:infod4:name1:xee\r\n").unwrap().name, "x");
+        assert!(parse(b"d3:foo3:bare").is_err());
+        let value = |data| Parser { data, pos: 0 }.value(0);
+        assert!(value(&[b'l'; 1000]).is_err());
+        assert_eq!(value(b"i-3e").unwrap(), Value::Int(-3));
+    }
+}
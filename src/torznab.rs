@@ -1,8 +1,9 @@
 //! Torznab API client library
 
+use crate::config::MagnetPreference;
 use crate::indexer::SearchCapabilities;
-use crate::models::CATEGORIES;
 pub use crate::models::TorrentResult;
+use crate::models::{CATEGORIES, DateSource};
 use anyhow::{Result, anyhow};
 use chrono::{DateTime, Utc};
 use reqwest::Client;
@@ -14,6 +15,9 @@ pub struct TorznabClient {
     client: Client,
     base_url: Url,
     apikey: Option<String>,
+    /// Proxy checked before each request under `require_proxy`; `None` in
+    /// a direct client
+    proxy_url: Option<Option<String>>,
 }
 
 /// Server capabilities
@@ -43,6 +47,8 @@ pub struct SearchParams {
     pub tvdbid: Option<i32>,
     pub year: Option<u32>,
     pub limit: Option<u32>,
+    /// Only releases published in the last this many days
+    pub maxage: Option<u32>,
     // Extended external IDs (Jackett/Prowlarr parity)
     pub rid: Option<i32>,      // TVRage ID
     pub tvmazeid: Option<i32>, // TVMaze ID
@@ -65,27 +71,45 @@ pub struct SearchParams {
 impl TorznabClient {
     /// Create a new Torznab client
     pub fn new(base_url: &str, apikey: Option<&str>, proxy_url: Option<&str>) -> Result<Self> {
-        let base_url = Url::parse(base_url)?;
+        let builder = crate::http::client_builder(proxy_url, None)?;
+        Self::build(builder, base_url, apikey, Some(proxy_url.map(String::from)))
+    }
+
+    /// Client that never uses the proxy, for another Lodestarr instance
+    /// (which makes the tracker requests itself)
+    pub fn direct(base_url: &str, apikey: Option<&str>) -> Result<Self> {
+        Self::build(crate::http::tune(Client::builder()), base_url, apikey, None)
+    }
 
-        let mut builder = Client::builder()
+    fn build(
+        builder: reqwest::ClientBuilder,
+        base_url: &str,
+        apikey: Option<&str>,
+        proxy_url: Option<Option<String>>,
+    ) -> Result<Self> {
+        let client = builder
             .user_agent("torznab-cli/0.1.0")
             .cookie_store(true)
-            .timeout(std::time::Duration::from_secs(30));
-
-        if let Some(url) = proxy_url {
-            let proxy = reqwest::Proxy::all(url)?;
-            builder = builder.proxy(proxy);
-        }
-
-        let client = builder.build()?;
+            .timeout(std::time::Duration::from_secs(30))
+            .build()?;
 
         Ok(Self {
             client,
-            base_url,
+            base_url: Url::parse(base_url)?,
             apikey: apikey.map(String::from),
+            proxy_url,
         })
     }
 
+    /// Check the required proxy and wait for the domain's budget
+    async fn throttle(&self, url: &str) -> Result<()> {
+        if let Some(proxy_url) = &self.proxy_url {
+            crate::http::ensure_proxy(proxy_url.as_deref()).await?;
+        }
+        crate::http::throttle(url).await;
+        Ok(())
+    }
+
     /// Set the API key
     #[allow(dead_code)]
     pub fn set_apikey(&mut self, apikey: Option<String>) {
@@ -117,6 +141,7 @@ impl TorznabClient {
     pub async fn get_caps(&self) -> Result<Capabilities> {
         let url = self.build_url(&[("t", "caps")])?;
 
+        self.throttle(url.as_str()).await?;
         let response = self.client.get(url).send().await?;
 
         if !response.status().is_success() {
@@ -124,6 +149,7 @@ impl TorznabClient {
         }
 
         let text = response.text().await?;
+        crate::http::traffic::add(text.len());
 
         // Check for error in XML
         if text.contains("<error") {
@@ -209,8 +235,12 @@ impl TorznabClient {
         if let Some(ep) = params.ep {
             query_params.push(("ep", ep.to_string()));
         }
-        if let Some(ref imdbid) = params.imdbid {
-            query_params.push(("imdbid", imdbid.clone()));
+        if let Some(imdbid) = params
+            .imdbid
+            .as_deref()
+            .and_then(crate::models::normalize_imdb_id)
+        {
+            query_params.push(("imdbid", imdbid));
         }
         if let Some(tmdbid) = params.tmdbid {
             query_params.push(("tmdbid", tmdbid.to_string()));
@@ -224,6 +254,9 @@ impl TorznabClient {
         if let Some(limit) = params.limit {
             query_params.push(("limit", limit.to_string()));
         }
+        if let Some(maxage) = params.maxage {
+            query_params.push(("maxage", maxage.to_string()));
+        }
         // Extended external IDs
         if let Some(rid) = params.rid {
             query_params.push(("rid", rid.to_string()));
@@ -269,8 +302,18 @@ impl TorznabClient {
 
         let url = self.build_url(&params_ref)?;
 
+        self.throttle(url.as_str()).await?;
         let response = self.client.get(url).send().await?;
+        let status = response.status();
+        if let Ok(e) = crate::indexer::IndexerError::rate_limit(
+            status,
+            response.headers(),
+            format!("API Error: HTTP {}", status),
+        ) {
+            return Err(e.into());
+        }
         let text = response.text().await?;
+        crate::http::traffic::add(text.len());
 
         // Check for error
         if text.contains("<error") {
@@ -325,6 +368,8 @@ impl TorznabClient {
             let grabs = extract_attr(item_text, "grabs").and_then(|s| s.parse().ok());
             let infohash = extract_attr(item_text, "infohash");
             let magneturl = extract_attr(item_text, "magneturl");
+            let date_source = extract_attr(item_text, "pubdatesource");
+            let category_fallback = extract_attr(item_text, "categoryfallback");
 
             // Extract categories
             let mut categories = Vec::new();
@@ -347,6 +392,12 @@ impl TorznabClient {
                 } else {
                     None
                 };
+                // Another Lodestarr says where its date came from
+                let date_source = publish_date.and(
+                    date_source
+                        .as_deref()
+                        .map_or(Some(DateSource::Indexer), DateSource::parse),
+                );
 
                 results.push(TorrentResult {
                     title,
@@ -355,6 +406,7 @@ impl TorznabClient {
                     details: comments,
                     magnet: magneturl,
                     publish_date,
+                    date_source,
                     categories,
                     size,
                     files: None,
@@ -371,6 +423,7 @@ impl TorznabClient {
                     download_volume_factor: None,
                     upload_volume_factor: None,
                     indexer: None,
+                    category_fallback: category_fallback.as_deref() == Some("1"),
                     ..Default::default()
                 });
             }
@@ -381,6 +434,7 @@ impl TorznabClient {
 
     /// Download a torrent file
     pub async fn download(&self, url: &str) -> Result<Vec<u8>> {
+        self.throttle(url).await?;
         let response = self.client.get(url).send().await?;
 
         if !response.status().is_success() {
@@ -388,6 +442,7 @@ impl TorznabClient {
         }
 
         let bytes = response.bytes().await?;
+        crate::http::traffic::add(bytes.len());
         Ok(bytes.to_vec())
     }
 }
@@ -436,6 +491,7 @@ fn html_decode(s: &str) -> String {
 pub fn generate_caps_xml(
     indexer_name: &str,
     categories: &[i32],
+    custom_categories: &[(i32, String)],
     capabilities: &SearchCapabilities,
 ) -> String {
     let mut xml = String::from(r#"<?xml version="1.0" encoding="UTF-8"?>"#);
@@ -450,11 +506,19 @@ pub fn generate_caps_xml(
     // Limits
     xml.push_str("  <limits default=\"100\" max=\"100\" />\n");
 
-    // Searching capabilities
+    // Searching capabilities. Parameters a definition lists for a mode are
+    // advertised as they are, the others follow from the flags.
+    let listed = |mode: &str, implied: Vec<&str>| match capabilities.mode_params.get(mode) {
+        Some(params) if !params.is_empty() => params.join(","),
+        _ => implied.join(","),
+    };
     xml.push_str("  <searching>\n");
 
     if capabilities.search {
-        xml.push_str("    <search available=\"yes\" supportedParams=\"q\" />\n");
+        xml.push_str(&format!(
+            "    <search available=\"yes\" supportedParams=\"{}\" />\n",
+            listed("search", vec!["q"])
+        ));
     }
 
     if capabilities.tv_search {
@@ -492,7 +556,7 @@ pub fn generate_caps_xml(
         }
         xml.push_str(&format!(
             "    <tv-search available=\"yes\" supportedParams=\"{}\" />\n",
-            params.join(",")
+            listed("tv-search", params)
         ));
     }
 
@@ -518,7 +582,7 @@ pub fn generate_caps_xml(
         }
         xml.push_str(&format!(
             "    <movie-search available=\"yes\" supportedParams=\"{}\" />\n",
-            params.join(",")
+            listed("movie-search", params)
         ));
     }
 
@@ -538,7 +602,7 @@ pub fn generate_caps_xml(
         }
         xml.push_str(&format!(
             "    <music-search available=\"yes\" supportedParams=\"{}\" />\n",
-            params.join(",")
+            listed("music-search", params)
         ));
     }
 
@@ -555,7 +619,7 @@ pub fn generate_caps_xml(
         }
         xml.push_str(&format!(
             "    <book-search available=\"yes\" supportedParams=\"{}\" />\n",
-            params.join(",")
+            listed("book-search", params)
         ));
     }
 
@@ -564,13 +628,11 @@ pub fn generate_caps_xml(
     // Categories - hierarchical structure (Jackett/Prowlarr parity)
     xml.push_str("  <categories>\n");
 
-    // Group categories by their parent (id / 1000 * 1000)
-    let mut parent_ids: Vec<i32> = categories
-        .iter()
-        .filter(|&&id| id % 1000 == 0)
-        .copied()
-        .collect();
+    // Group categories by their parent (id / 1000 * 1000), which is listed
+    // even when only its subcategories are mapped
+    let mut parent_ids: Vec<i32> = categories.iter().map(|id| id / 1000 * 1000).collect();
     parent_ids.sort();
+    parent_ids.dedup();
 
     for parent_id in &parent_ids {
         if let Some(parent_cat) = CATEGORIES.iter().find(|c| c.id == *parent_id) {
@@ -596,6 +658,14 @@ pub fn generate_caps_xml(
             xml.push_str("    </category>\n");
         }
     }
+    // Tracker-specific categories (`_trackerCategories`)
+    for (id, name) in custom_categories {
+        xml.push_str(&format!(
+            "    <category id=\"{}\" name=\"{}\" />\n",
+            id,
+            escape_xml(name)
+        ));
+    }
 
     xml.push_str("  </categories>\n");
 
@@ -604,12 +674,16 @@ pub fn generate_caps_xml(
 }
 
 /// Generate a Torznab search results XML response
-/// If proxy_base_url and indexer_id are provided, download URLs will be wrapped through the proxy
+/// If proxy_base_url and indexer_id are provided, download URLs will be wrapped through the proxy,
+/// with `api_key` added so grabs pass the server's auth.
+/// `magnets` picks between a result's .torrent link and magnet, the same way for every indexer.
 pub fn generate_results_xml(
     results: &[TorrentResult],
     indexer_name: &str,
     proxy_base_url: Option<&str>,
     indexer_id: Option<&str>,
+    api_key: Option<&str>,
+    magnets: MagnetPreference,
 ) -> String {
     let mut xml = String::from(r#"<?xml version="1.0" encoding="UTF-8"?>"#);
     xml.push('\n');
@@ -629,15 +703,38 @@ pub fn generate_results_xml(
         ));
         xml.push_str(&format!("    <guid>{}</guid>\n", escape_xml(&result.guid)));
 
-        // link element is REQUIRED by Sonarr - always include it with fallback chain: link -> magnet -> details -> guid
-        let link_url = result
-            .link
-            .as_ref()
-            .or(result.magnet.as_ref())
-            .or(result.details.as_ref())
-            .map(|s| s.as_str())
+        let link = result.link.as_deref().filter(|l| !l.is_empty());
+        let magnet = result.magnet.as_deref().filter(|m| !m.is_empty());
+        let download = match magnets {
+            MagnetPreference::MagnetFirst => magnet.or(link),
+            MagnetPreference::LinkFirst | MagnetPreference::Both => link.or(magnet),
+        };
+
+        // link and enclosure are REQUIRED by Sonarr - always include them with fallback chain: download -> details -> guid
+        let raw_enclosure_url = download
+            .or(result.details.as_deref())
             .unwrap_or(&result.guid);
-        xml.push_str(&format!("    <link>{}</link>\n", escape_xml(link_url)));
+
+        // Wrap non-magnet URLs through proxy if base_url and indexer_id provided
+        let enclosure_url = if !raw_enclosure_url.starts_with("magnet:") {
+            if let (Some(base), Some(indexer)) = (proxy_base_url, indexer_id) {
+                crate::download::proxy_download_url(
+                    base,
+                    indexer,
+                    raw_enclosure_url,
+                    &result.guid,
+                    api_key,
+                )
+            } else {
+                raw_enclosure_url.to_string()
+            }
+        } else {
+            raw_enclosure_url.to_string()
+        };
+        xml.push_str(&format!(
+            "    <link>{}</link>\n",
+            escape_xml(&enclosure_url)
+        ));
 
         if let Some(ref details) = result.details {
             xml.push_str(&format!(
@@ -653,6 +750,16 @@ pub fn generate_results_xml(
             pub_date.format("%a, %d %b %Y %H:%M:%S +0000")
         ));
 
+        if let Some(source) = result.date_source {
+            xml.push_str(&format!(
+                "    <torznab:attr name=\"pubdatesource\" value=\"{}\" />\n",
+                source.as_str()
+            ));
+        }
+        if result.category_fallback {
+            xml.push_str("    <torznab:attr name=\"categoryfallback\" value=\"1\" />\n");
+        }
+
         // Torznab attributes
         if let Some(size) = result.size {
             xml.push_str(&format!("    <size>{}</size>\n", size));
@@ -700,43 +807,24 @@ pub fn generate_results_xml(
             ));
         }
 
-        if let Some(ref infohash) = result.info_hash {
+        // From the result or its magnet, so clients can match items across indexers
+        if let Some(infohash) = crate::dedup::info_hash_of(result) {
             xml.push_str(&format!(
                 "    <torznab:attr name=\"infohash\" value=\"{}\" />\n",
-                escape_xml(infohash)
+                escape_xml(&infohash)
             ));
         }
 
-        if let Some(ref magnet) = result.magnet {
+        // A second download only when asked for, or when the magnet is the download
+        if let Some(magnet) = magnet
+            && (magnets == MagnetPreference::Both || download == Some(magnet))
+        {
             xml.push_str(&format!(
                 "    <torznab:attr name=\"magneturl\" value=\"{}\" />\n",
                 escape_xml(magnet)
             ));
         }
 
-        // Enclosure is REQUIRED by Sonarr - always include it with fallback chain: magnet -> link -> details
-        let raw_enclosure_url = result
-            .magnet
-            .as_ref()
-            .or(result.link.as_ref())
-            .or(result.details.as_ref())
-            .map(|s| s.as_str())
-            .unwrap_or(&result.guid);
-
-        // Wrap non-magnet URLs through proxy if base_url and indexer_id provided
-        let enclosure_url = if !raw_enclosure_url.starts_with("magnet:") {
-            if let (Some(base), Some(indexer)) = (proxy_base_url, indexer_id) {
-                // Encode URL as BASE64 for proxy
-                use base64::{Engine as _, engine::general_purpose::URL_SAFE_NO_PAD};
-                let encoded = URL_SAFE_NO_PAD.encode(raw_enclosure_url);
-                format!("{}/api/v2.0/indexers/{}/dl?link={}", base, indexer, encoded)
-            } else {
-                raw_enclosure_url.to_string()
-            }
-        } else {
-            raw_enclosure_url.to_string()
-        };
-
         let size = result.size.unwrap_or(0);
         xml.push_str(&format!(
             "    <enclosure url=\"{}\" length=\"{}\" type=\"application/x-bittorrent\" />\n",
@@ -794,6 +882,13 @@ pub fn generate_results_xml(
             ));
         }
 
+        if let Some(ref group) = result.release_group {
+            xml.push_str(&format!(
+                "    <torznab:attr name=\"group\" value=\"{}\" />\n",
+                escape_xml(group)
+            ));
+        }
+
         // Indexer flags (Prowlarr parity)
         for flag in &result.flags {
             xml.push_str(&format!(
@@ -824,6 +919,25 @@ pub fn generate_results_xml(
             ));
         }
 
+        // MusicBrainz match (see `crate::musicbrainz`)
+        if let Some(ref music) = result.music {
+            let attrs = [
+                ("artist", Some(&music.artist)),
+                ("artistmbid", Some(&music.artist_mbid)),
+                ("album", music.album.as_ref()),
+                ("releasembid", music.release_mbid.as_ref()),
+            ];
+            for (name, value) in attrs {
+                if let Some(value) = value {
+                    xml.push_str(&format!(
+                        "    <torznab:attr name=\"{}\" value=\"{}\" />\n",
+                        name,
+                        escape_xml(value)
+                    ));
+                }
+            }
+        }
+
         xml.push_str("  </item>\n");
     }
 
@@ -844,7 +958,7 @@ pub fn generate_error_xml(code: u32, description: &str) -> String {
 }
 
 /// Escape special XML characters
-fn escape_xml(s: &str) -> String {
+pub fn escape_xml(s: &str) -> String {
     s.replace('&', "&amp;")
         .replace('<', "&lt;")
         .replace('>', "&gt;")
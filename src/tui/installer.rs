@@ -0,0 +1,180 @@
+//! Native indexer installer screen (Indexers tab)
+//!
+//! Lists definitions from the local GitHub cache (or GitHub itself when the
+//! cache is empty) with fuzzy filtering, and tracks which ones are installed.
+
+use crate::indexer::AvailableIndexer;
+use ratatui::{prelude::*, widgets::*};
+use std::collections::HashSet;
+use tui_input::Input;
+
+#[derive(Default)]
+pub struct Installer {
+    /// Definitions that can be installed
+    pub available: Vec<AvailableIndexer>,
+    /// Names installed in active/native/
+    pub installed: HashSet<String>,
+    /// True once `available` has been loaded
+    pub loaded: bool,
+    pub filter: Input,
+    pub editing: bool,
+    pub state: TableState,
+    /// Table area from the last draw (for mouse hit-testing)
+    pub table_area: Rect,
+}
+
+impl Installer {
+    /// Available definitions matching the filter, best matches first
+    pub fn filtered(&self) -> Vec<&AvailableIndexer> {
+        let pattern = self.filter.value();
+        let mut matches: Vec<(i32, &AvailableIndexer)> = self
+            .available
+            .iter()
+            .filter_map(|idx| fuzzy_score(pattern, &idx.name).map(|s| (s, idx)))
+            .collect();
+        matches.sort_by(|(sa, a), (sb, b)| sb.cmp(sa).then_with(|| a.name.cmp(&b.name)));
+        matches.into_iter().map(|(_, idx)| idx).collect()
+    }
+
+    /// Name of the selected definition
+    pub fn selected_name(&self) -> Option<String> {
+        self.state
+            .selected()
+            .and_then(|i| self.filtered().get(i).map(|idx| idx.name.clone()))
+    }
+
+    /// Move the selection by `delta` rows, wrapping around
+    pub fn move_selection(&mut self, delta: isize) {
+        let len = self.filtered().len();
+        if len == 0 {
+            self.state.select(None);
+            return;
+        }
+        let current = self.state.selected().unwrap_or(0) as isize;
+        self.state
+            .select(Some((current + delta).rem_euclid(len as isize) as usize));
+    }
+
+    pub fn render(&mut self, f: &mut Frame, area: Rect, hint: &str) {
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(3), Constraint::Min(1)])
+            .split(area);
+
+        let filter = Paragraph::new(self.filter.value())
+            .style(if self.editing {
+                Style::default().fg(Color::Yellow)
+            } else {
+                Style::default()
+            })
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title("Filter ('/' to edit)"),
+            );
+        f.render_widget(filter, chunks[0]);
+        if self.editing {
+            f.set_cursor_position((
+                chunks[0].x + self.filter.visual_cursor() as u16 + 1,
+                chunks[0].y + 1,
+            ));
+        }
+
+        let filtered = self.filtered();
+        let total = filtered.len();
+        let header = ["Name", "Status"]
+            .into_iter()
+            .map(Cell::from)
+            .collect::<Row>()
+            .style(Style::default().fg(Color::Yellow))
+            .height(1);
+        let rows = filtered.into_iter().map(|idx| {
+            let status = if self.installed.contains(&idx.name) {
+                Cell::from("Installed").style(Style::default().fg(Color::Green))
+            } else {
+                Cell::from("-").style(Style::default().fg(Color::DarkGray))
+            };
+            Row::new(vec![Cell::from(idx.name.clone()), status])
+        });
+
+        let title = if self.loaded {
+            format!("Available Indexers ({}) - {}", total, hint)
+        } else {
+            "Loading available indexers...".to_string()
+        };
+        let t = Table::new(rows, [Constraint::Min(30), Constraint::Length(12)])
+            .header(header)
+            .block(Block::default().borders(Borders::ALL).title(title))
+            .row_highlight_style(Style::default().add_modifier(Modifier::REVERSED))
+            .highlight_symbol(">> ");
+
+        self.table_area = chunks[1];
+        f.render_stateful_widget(t, chunks[1], &mut self.state);
+    }
+}
+
+/// Case-insensitive subsequence match. Higher scores mean better matches;
+/// consecutive and prefix matches are rewarded. `None` if not a match.
+pub fn fuzzy_score(pattern: &str, text: &str) -> Option<i32> {
+    if pattern.is_empty() {
+        return Some(0);
+    }
+
+    let text: Vec<char> = text.to_lowercase().chars().collect();
+    let mut score = 0;
+    let mut pos = 0;
+    let mut last_match: Option<usize> = None;
+
+    for pc in pattern.to_lowercase().chars() {
+        let found = text[pos..].iter().position(|c| *c == pc)? + pos;
+        score += match last_match {
+            Some(last) if found == last + 1 => 5,
+            _ if found == 0 => 10,
+            _ => 1,
+        };
+        last_match = Some(found);
+        pos = found + 1;
+    }
+
+    // Prefer shorter names for equal matches
+    Some(score * 10 - text.len() as i32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fuzzy_score() {
+        assert!(fuzzy_score("yts", "yts").is_some());
+        assert!(fuzzy_score("ezt", "eztv").is_some());
+        assert!(fuzzy_score("tpb", "thepiratebay").is_some());
+        assert!(fuzzy_score("xyz", "eztv").is_none());
+        // Prefix/consecutive matches beat scattered ones
+        assert!(fuzzy_score("eztv", "eztv") > fuzzy_score("eztv", "e-zone-tv"));
+    }
+
+    #[test]
+    fn test_filtered_orders_by_score() {
+        let available = ["thepiratebay", "rutracker", "rarbg"]
+            .into_iter()
+            .map(|name| AvailableIndexer {
+                name: name.to_string(),
+                filename: format!("{}.yml", name),
+                download_url: String::new(),
+            })
+            .collect();
+        let installer = Installer {
+            available,
+            filter: Input::new("r".to_string()),
+            ..Default::default()
+        };
+
+        let names: Vec<&str> = installer
+            .filtered()
+            .iter()
+            .map(|i| i.name.as_str())
+            .collect();
+        assert_eq!(names, vec!["rarbg", "rutracker", "thepiratebay"]);
+    }
+}
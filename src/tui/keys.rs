@@ -0,0 +1,227 @@
+//! Configurable TUI keybindings
+//!
+//! Defaults can be overridden from the `[tui.keys]` config section, e.g.:
+//!
+//! ```toml
+//! [tui.keys]
+//! delete_indexer = "x"
+//! copy_link = "c"
+//! ```
+
+use crossterm::event::KeyCode;
+use std::collections::HashMap;
+
+/// A rebindable TUI action
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Action {
+    Help,
+    EditQuery,
+    CycleSort,
+    SaveMagnet,
+    CopyLink,
+    CopyTitle,
+    ToggleHistory,
+    DeleteIndexer,
+    ReloadIndexers,
+    ToggleInstaller,
+    ClearCache,
+    ResetSettings,
+}
+
+impl Action {
+    pub const ALL: [Action; 12] = [
+        Action::Help,
+        Action::EditQuery,
+        Action::CycleSort,
+        Action::SaveMagnet,
+        Action::CopyLink,
+        Action::CopyTitle,
+        Action::ToggleHistory,
+        Action::DeleteIndexer,
+        Action::ReloadIndexers,
+        Action::ToggleInstaller,
+        Action::ClearCache,
+        Action::ResetSettings,
+    ];
+
+    /// Name used in the `[tui.keys]` config section
+    pub fn config_name(&self) -> &'static str {
+        match self {
+            Action::Help => "help",
+            Action::EditQuery => "edit_query",
+            Action::CycleSort => "cycle_sort",
+            Action::SaveMagnet => "save_magnet",
+            Action::CopyLink => "copy_link",
+            Action::CopyTitle => "copy_title",
+            Action::ToggleHistory => "toggle_history",
+            Action::DeleteIndexer => "delete_indexer",
+            Action::ReloadIndexers => "reload_indexers",
+            Action::ToggleInstaller => "toggle_installer",
+            Action::ClearCache => "clear_cache",
+            Action::ResetSettings => "reset_settings",
+        }
+    }
+
+    fn default_key(&self) -> KeyCode {
+        match self {
+            Action::Help => KeyCode::Char('?'),
+            Action::EditQuery => KeyCode::Char('i'),
+            Action::CycleSort => KeyCode::Char('s'),
+            Action::SaveMagnet => KeyCode::Char('m'),
+            Action::CopyLink => KeyCode::Char('y'),
+            Action::CopyTitle => KeyCode::Char('Y'),
+            Action::ToggleHistory => KeyCode::Char('h'),
+            Action::DeleteIndexer => KeyCode::Char('d'),
+            Action::ReloadIndexers => KeyCode::Char('r'),
+            Action::ToggleInstaller => KeyCode::Char('n'),
+            Action::ClearCache => KeyCode::Char('c'),
+            Action::ResetSettings => KeyCode::Char('R'),
+        }
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            Action::Help => "Toggle this help",
+            Action::EditQuery => "Edit search query",
+            Action::CycleSort => "Cycle sort order",
+            Action::SaveMagnet => "Save magnet",
+            Action::CopyLink => "Copy magnet/link to clipboard",
+            Action::CopyTitle => "Copy title to clipboard",
+            Action::ToggleHistory => "Toggle query history",
+            Action::DeleteIndexer => "Remove selected indexer",
+            Action::ReloadIndexers => "Reload indexer clients / available list",
+            Action::ToggleInstaller => "Toggle native indexer installer",
+            Action::ClearCache => "Clear search cache",
+            Action::ResetSettings => "Reset settings to defaults",
+        }
+    }
+
+    /// Help overlay section the action is listed under
+    pub fn section(&self) -> &'static str {
+        match self {
+            Action::Help => "Global",
+            Action::DeleteIndexer | Action::ReloadIndexers | Action::ToggleInstaller => "Indexers",
+            Action::ClearCache | Action::ResetSettings => "Settings",
+            _ => "Search",
+        }
+    }
+}
+
+/// Resolved keybindings (defaults plus config overrides)
+#[derive(Debug, Clone)]
+pub struct Keymap {
+    bindings: HashMap<Action, KeyCode>,
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        Self {
+            bindings: Action::ALL.iter().map(|a| (*a, a.default_key())).collect(),
+        }
+    }
+}
+
+impl Keymap {
+    /// Build a keymap from `[tui.keys]` overrides.
+    ///
+    /// Returns the keymap and a warning for every entry that was ignored.
+    pub fn from_config(overrides: &HashMap<String, String>) -> (Self, Vec<String>) {
+        let mut keymap = Self::default();
+        let mut warnings = Vec::new();
+
+        for (name, key) in overrides {
+            let Some(action) = Action::ALL.iter().find(|a| a.config_name() == name) else {
+                warnings.push(format!("Unknown TUI action '{}'", name));
+                continue;
+            };
+            match parse_key(key) {
+                Some(code) => {
+                    keymap.bindings.insert(*action, code);
+                }
+                None => warnings.push(format!("Invalid key '{}' for '{}'", key, name)),
+            }
+        }
+
+        (keymap, warnings)
+    }
+
+    /// Returns true if `code` is bound to `action`
+    pub fn matches(&self, action: Action, code: KeyCode) -> bool {
+        self.bindings.get(&action) == Some(&code)
+    }
+
+    /// Human readable key for an action (for hints and the help overlay)
+    pub fn label(&self, action: Action) -> String {
+        self.bindings
+            .get(&action)
+            .map(key_label)
+            .unwrap_or_default()
+    }
+}
+
+/// Parse a key name from config: a single character, or a named key like "enter", "f2"
+pub fn parse_key(s: &str) -> Option<KeyCode> {
+    let mut chars = s.chars();
+    if let (Some(c), None) = (chars.next(), chars.next()) {
+        return Some(KeyCode::Char(c));
+    }
+
+    let lower = s.to_lowercase();
+    let code = match lower.as_str() {
+        "enter" => KeyCode::Enter,
+        "space" => KeyCode::Char(' '),
+        "backspace" => KeyCode::Backspace,
+        "delete" | "del" => KeyCode::Delete,
+        "insert" | "ins" => KeyCode::Insert,
+        "home" => KeyCode::Home,
+        "end" => KeyCode::End,
+        "pageup" => KeyCode::PageUp,
+        "pagedown" => KeyCode::PageDown,
+        f if f.starts_with('f') => {
+            KeyCode::F(f[1..].parse().ok().filter(|n| (1..=12).contains(n))?)
+        }
+        _ => return None,
+    };
+    Some(code)
+}
+
+fn key_label(code: &KeyCode) -> String {
+    match code {
+        KeyCode::Char(' ') => "Space".to_string(),
+        KeyCode::Char(c) => c.to_string(),
+        KeyCode::F(n) => format!("F{}", n),
+        other => format!("{:?}", other),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_key() {
+        assert_eq!(parse_key("x"), Some(KeyCode::Char('x')));
+        assert_eq!(parse_key("D"), Some(KeyCode::Char('D')));
+        assert_eq!(parse_key("Enter"), Some(KeyCode::Enter));
+        assert_eq!(parse_key("space"), Some(KeyCode::Char(' ')));
+        assert_eq!(parse_key("f5"), Some(KeyCode::F(5)));
+        assert_eq!(parse_key("f13"), None);
+        assert_eq!(parse_key("nope"), None);
+    }
+
+    #[test]
+    fn test_keymap_overrides() {
+        let overrides = HashMap::from([
+            ("delete_indexer".to_string(), "x".to_string()),
+            ("bogus".to_string(), "z".to_string()),
+            ("copy_link".to_string(), "nope".to_string()),
+        ]);
+        let (keymap, warnings) = Keymap::from_config(&overrides);
+
+        assert!(keymap.matches(Action::DeleteIndexer, KeyCode::Char('x')));
+        assert!(!keymap.matches(Action::DeleteIndexer, KeyCode::Char('d')));
+        // Invalid overrides keep the default
+        assert!(keymap.matches(Action::CopyLink, KeyCode::Char('y')));
+        assert_eq!(warnings.len(), 2);
+    }
+}
@@ -0,0 +1,1895 @@
+mod installer;
+mod keys;
+
+use crate::config::Config;
+use crate::indexer::{AvailableIndexer, IndexerDownloader};
+use crate::sort::{Sort, SortField};
+use crate::torznab::{SearchParams, TorrentResult, TorznabClient};
+use anyhow::Result;
+use crossterm::{
+    event::{
+        self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyEventKind, MouseButton,
+        MouseEvent, MouseEventKind,
+    },
+    execute,
+    terminal::{EnterAlternateScreen, LeaveAlternateScreen, disable_raw_mode, enable_raw_mode},
+};
+use futures::future::join_all;
+use installer::Installer;
+use keys::{Action, Keymap};
+use ratatui::{prelude::*, widgets::*};
+use std::{
+    io,
+    time::{Duration, Instant},
+};
+use tui_input::{Input, backend::crossterm::EventHandler};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ActiveTab {
+    Dashboard,
+    Search,
+    Indexers,
+    Settings,
+}
+
+impl ActiveTab {
+    fn next(&self) -> Self {
+        match self {
+            Self::Dashboard => Self::Search,
+            Self::Search => Self::Indexers,
+            Self::Indexers => Self::Settings,
+            Self::Settings => Self::Dashboard,
+        }
+    }
+
+    fn prev(&self) -> Self {
+        match self {
+            Self::Dashboard => Self::Settings,
+            Self::Search => Self::Dashboard,
+            Self::Indexers => Self::Search,
+            Self::Settings => Self::Indexers,
+        }
+    }
+
+    fn title(&self) -> &str {
+        match self {
+            Self::Dashboard => "Dashboard",
+            Self::Search => "Search",
+            Self::Indexers => "Indexers",
+            Self::Settings => "Settings",
+        }
+    }
+}
+
+enum InputMode {
+    Normal,
+    Editing,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SortMode {
+    Seeders,
+    Size,
+    Date,
+    Relevance,
+    Indexer,
+    Health,
+}
+
+impl SortMode {
+    fn next(&self) -> Self {
+        match self {
+            Self::Seeders => Self::Size,
+            Self::Size => Self::Date,
+            Self::Date => Self::Relevance,
+            Self::Relevance => Self::Indexer,
+            Self::Indexer => Self::Health,
+            Self::Health => Self::Seeders,
+        }
+    }
+
+    fn as_str(&self) -> &str {
+        match self {
+            Self::Seeders => "Seeders",
+            Self::Size => "Size",
+            Self::Date => "Date",
+            Self::Relevance => "Relevance",
+            Self::Indexer => "Indexer",
+            Self::Health => "Health",
+        }
+    }
+
+    /// Field the mode sorts on, `None` for by indexer
+    fn field(&self) -> Option<SortField> {
+        match self {
+            Self::Seeders => Some(SortField::Seeders),
+            Self::Size => Some(SortField::Size),
+            Self::Date => Some(SortField::Date),
+            Self::Relevance => Some(SortField::Relevance),
+            Self::Indexer => None,
+            Self::Health => Some(SortField::Health),
+        }
+    }
+}
+
+impl From<SortField> for SortMode {
+    fn from(field: SortField) -> Self {
+        match field {
+            SortField::Seeders => Self::Seeders,
+            SortField::Size => Self::Size,
+            SortField::Date => Self::Date,
+            SortField::Relevance => Self::Relevance,
+            SortField::Health => Self::Health,
+        }
+    }
+}
+
+/// Number of previous queries shown in the history sidebar
+const HISTORY_LIMIT: usize = 50;
+
+/// Max delay between two clicks on the same row to count as a double-click
+const DOUBLE_CLICK: Duration = Duration::from_millis(400);
+
+/// How long a toast stays on screen
+const TOAST_DURATION: Duration = Duration::from_secs(4);
+
+/// Transient notification shown in the bottom-right corner
+struct Toast {
+    message: String,
+    success: bool,
+    expires_at: Instant,
+}
+
+/// Modal popups drawn over the active tab
+enum Popup {
+    /// Choose a download client (or disk) for the selected result
+    ClientPicker {
+        result: Box<TorrentResult>,
+        state: ListState,
+    },
+    /// Read-only details of a result
+    Details(Box<TorrentResult>),
+    /// Ask y/n before running a destructive action
+    Confirm {
+        message: String,
+        action: ConfirmAction,
+    },
+}
+
+/// Destructive actions that go through the confirmation dialog
+enum ConfirmAction {
+    RemoveIndexer(String),
+    RemoveNative(String),
+    ClearCache,
+    ResetSettings,
+}
+
+pub struct App {
+    #[allow(dead_code)]
+    config: Config,
+    client_cache: Vec<(String, TorznabClient)>,
+    // Navigation
+    active_tab: ActiveTab,
+    // Search State
+    search_input: Input,
+    search_mode: InputMode,
+    results: Vec<TorrentResult>,
+    results_state: TableState,
+    sort_mode: SortMode,
+    // History sidebar
+    db_pool: Option<crate::db::DbPool>,
+    history: Vec<String>,
+    history_state: ListState,
+    show_history: bool,
+    // Dashboard State
+    // TODO: Add dashboard stats storage
+    // Indexer State
+    indexer_state: TableState,
+    installer: Installer,
+    installer_open: bool,
+
+    keys: Keymap,
+    show_help: bool,
+    // Mouse hit-testing: table areas from the last draw
+    results_area: Rect,
+    indexers_area: Rect,
+    last_click: Option<(Instant, usize)>,
+    popup: Option<Popup>,
+    toast: Option<Toast>,
+    /// Kept alive for the session: on X11 the clipboard owner must outlive the copy
+    clipboard: Option<arboard::Clipboard>,
+    status_msg: String,
+}
+
+impl App {
+    pub fn new(config: Config) -> Result<Self> {
+        let mut client_cache = Vec::new();
+        for idx in &config.indexers {
+            if let Ok(c) =
+                TorznabClient::new(&idx.url, idx.apikey.as_deref(), config.proxy_url.as_deref())
+            {
+                client_cache.push((idx.name.clone(), c));
+            }
+        }
+
+        let db_pool = match config.get_db_path() {
+            Ok(path) => {
+                if let Some(parent) = path.parent() {
+                    std::fs::create_dir_all(parent)?;
+                }
+                Some(crate::db::init_db(path))
+            }
+            Err(e) => {
+                tracing::warn!("Search history disabled: {}", e);
+                None
+            }
+        };
+        let history = db_pool
+            .as_ref()
+            .and_then(|pool| crate::db::get_tui_history(pool, HISTORY_LIMIT).ok())
+            .unwrap_or_default();
+
+        // Restore the last session's results from the cache
+        let mut search_input = Input::default();
+        let mut results = Vec::new();
+        let mut status_msg = "Welcome to Lodestarr TUI. Press 'Tab' to switch views.".to_string();
+        if let (Some(pool), Some(query)) = (&db_pool, history.first())
+            && let Ok(Some(cached)) = crate::db::get_cached_results(pool, &tui_cache_key(query))
+            && let Ok(cached) = serde_json::from_str::<Vec<TorrentResult>>(&cached)
+        {
+            search_input = Input::new(query.clone());
+            status_msg = format!("Restored {} results for '{}'", cached.len(), query);
+            results = cached;
+            crate::health::apply(&mut results, &crate::health::reliability(pool));
+        }
+        let (keys, key_warnings) = Keymap::from_config(&config.tui.keys);
+        if !key_warnings.is_empty() {
+            status_msg = format!("[tui.keys] {}", key_warnings.join("; "));
+        }
+
+        let mut results_state = TableState::default();
+        if !results.is_empty() {
+            results_state.select(Some(0));
+        }
+
+        let sort_mode = config.sort.by.into();
+        Ok(Self {
+            config,
+            client_cache,
+            active_tab: ActiveTab::Dashboard,
+            search_input,
+            search_mode: InputMode::Normal,
+            results,
+            results_state,
+            sort_mode,
+            db_pool,
+            history,
+            history_state: ListState::default(),
+            show_history: false,
+            indexer_state: TableState::default(),
+            installer: Installer::default(),
+            installer_open: false,
+            keys,
+            show_help: false,
+            results_area: Rect::default(),
+            indexers_area: Rect::default(),
+            last_click: None,
+            popup: None,
+            toast: None,
+            clipboard: None,
+            status_msg,
+        })
+    }
+
+    pub async fn run(&mut self) -> Result<()> {
+        // ... existing code ...
+
+        // Setup terminal
+        enable_raw_mode()?;
+        let mut stdout = io::stdout();
+        execute!(stdout, EnterAlternateScreen, EnableMouseCapture)?;
+        let backend = CrosstermBackend::new(stdout);
+        let mut terminal = Terminal::new(backend)?;
+
+        let res = self.run_app(&mut terminal).await;
+
+        // Restore terminal
+        disable_raw_mode()?;
+        execute!(
+            terminal.backend_mut(),
+            LeaveAlternateScreen,
+            DisableMouseCapture
+        )?;
+        terminal.show_cursor()?;
+
+        if let Err(err) = res {
+            println!("{:?}", err);
+        }
+
+        Ok(())
+    }
+
+    async fn run_app<B: Backend>(&mut self, terminal: &mut Terminal<B>) -> Result<()> {
+        loop {
+            if self
+                .toast
+                .as_ref()
+                .is_some_and(|t| t.expires_at <= Instant::now())
+            {
+                self.toast = None;
+            }
+
+            terminal.draw(|f| self.ui(f))?;
+
+            if !event::poll(Duration::from_millis(100))? {
+                continue;
+            }
+            let event = event::read()?;
+            if let Event::Mouse(mouse) = event {
+                self.handle_mouse(mouse);
+                continue;
+            }
+
+            if let Event::Key(key) = event
+                && key.kind == KeyEventKind::Press
+            {
+                // Popups capture all input while open
+                if self.popup.is_some() {
+                    self.handle_popup_input(key, terminal).await?;
+                    continue;
+                }
+                // Any key closes the help overlay
+                if self.show_help {
+                    self.show_help = false;
+                    continue;
+                }
+
+                // Global Navigation
+                let editing = self.is_editing();
+                match key.code {
+                    code if !editing && self.keys.matches(Action::Help, code) => {
+                        self.show_help = true;
+                        continue;
+                    }
+                    KeyCode::Tab => {
+                        self.active_tab = self.active_tab.next();
+                        self.status_msg = format!("Switched to {}", self.active_tab.title());
+                        continue;
+                    }
+                    KeyCode::BackTab => {
+                        self.active_tab = self.active_tab.prev();
+                        self.status_msg = format!("Switched to {}", self.active_tab.title());
+                        continue;
+                    }
+                    KeyCode::Esc => {
+                        if editing {
+                            self.search_mode = InputMode::Normal;
+                            self.installer.editing = false;
+                            self.status_msg = "Exited edit mode.".to_string();
+                        } else {
+                            return Ok(());
+                        }
+                        continue;
+                    }
+                    _ => {}
+                }
+
+                // Tab specific handling
+                match self.active_tab {
+                    ActiveTab::Dashboard => self.handle_dashboard_input(key).await?,
+                    ActiveTab::Search => self.handle_search_input(key, terminal).await?,
+                    ActiveTab::Indexers if self.installer_open => {
+                        self.handle_installer_input(key, terminal).await?
+                    }
+                    ActiveTab::Indexers => self.handle_indexers_input(key, terminal).await?,
+                    ActiveTab::Settings => self.handle_settings_input(key),
+                }
+            }
+        }
+    }
+
+    fn handle_mouse(&mut self, mouse: MouseEvent) {
+        if self.popup.is_some() || self.show_help {
+            return;
+        }
+
+        let installer_len = self.installer.filtered().len();
+        let (area, len, state) = match self.active_tab {
+            ActiveTab::Indexers if self.installer_open => (
+                self.installer.table_area,
+                installer_len,
+                &mut self.installer.state,
+            ),
+            ActiveTab::Search => (
+                self.results_area,
+                self.results.len(),
+                &mut self.results_state,
+            ),
+            ActiveTab::Indexers => (
+                self.indexers_area,
+                self.config.indexers.len(),
+                &mut self.indexer_state,
+            ),
+            _ => return,
+        };
+        if len == 0 || !area.contains(Position::new(mouse.column, mouse.row)) {
+            return;
+        }
+
+        match mouse.kind {
+            MouseEventKind::ScrollDown => {
+                let i = state.selected().map_or(0, |i| (i + 1).min(len - 1));
+                state.select(Some(i));
+            }
+            MouseEventKind::ScrollUp => {
+                let i = state.selected().map_or(0, |i| i.saturating_sub(1));
+                state.select(Some(i));
+            }
+            MouseEventKind::Down(MouseButton::Left) => {
+                let Some(row) = table_row_at(area, mouse.row, state.offset()).filter(|r| *r < len)
+                else {
+                    return;
+                };
+                state.select(Some(row));
+
+                let now = Instant::now();
+                let double = self
+                    .last_click
+                    .is_some_and(|(at, r)| r == row && now.duration_since(at) <= DOUBLE_CLICK);
+                self.last_click = Some((now, row));
+
+                if double && self.active_tab == ActiveTab::Search {
+                    self.last_click = None;
+                    self.popup = Some(Popup::Details(Box::new(self.results[row].clone())));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn reload_clients(&mut self) {
+        self.client_cache.clear();
+        for idx in &self.config.indexers {
+            if let Ok(c) = TorznabClient::new(
+                &idx.url,
+                idx.apikey.as_deref(),
+                self.config.proxy_url.as_deref(),
+            ) {
+                self.client_cache.push((idx.name.clone(), c));
+            }
+        }
+    }
+
+    fn remove_indexer(&mut self, name: &str) {
+        if !self.config.remove_indexer(name) {
+            return;
+        }
+        if let Err(e) = self.config.save() {
+            self.status_msg = format!("Failed to save config: {}", e);
+            return;
+        }
+
+        self.reload_clients();
+        self.status_msg = format!("Removed indexer '{}'", name);
+        // Adjust selection
+        let len = self.config.indexers.len();
+        if len == 0 {
+            self.indexer_state.select(None);
+        } else if self.indexer_state.selected().is_some_and(|i| i >= len) {
+            self.indexer_state.select(Some(len - 1));
+        }
+    }
+
+    fn handle_settings_input(&mut self, key: event::KeyEvent) {
+        match key.code {
+            code if self.keys.matches(Action::ClearCache, code) => self.confirm(
+                "Clear all cached search results?".to_string(),
+                ConfirmAction::ClearCache,
+            ),
+            code if self.keys.matches(Action::ResetSettings, code) => self.confirm(
+                "Reset download path, proxy, scoring and keybindings to defaults?".to_string(),
+                ConfirmAction::ResetSettings,
+            ),
+            _ => {}
+        }
+    }
+
+    async fn handle_dashboard_input(&mut self, _key: event::KeyEvent) -> Result<()> {
+        // TODO: Interactive dashboard elements
+        Ok(())
+    }
+
+    fn is_editing(&self) -> bool {
+        matches!(self.search_mode, InputMode::Editing) || self.installer.editing
+    }
+
+    fn native_downloader(&self) -> Result<IndexerDownloader> {
+        let active = self.config.get_active_native_path()?;
+        let available = self.config.get_available_indexers_path()?;
+        Ok(IndexerDownloader::with_available_dir(
+            active.to_string_lossy().to_string(),
+            self.config.proxy_url.clone(),
+            Some(available.to_string_lossy().to_string()),
+        ))
+    }
+
+    /// Load available definitions (local cache first, GitHub otherwise) and install status
+    async fn load_installer<B: Backend>(&mut self, terminal: &mut Terminal<B>) -> Result<()> {
+        self.installer.loaded = false;
+        self.status_msg = "Loading available indexers...".to_string();
+        terminal.draw(|f| self.ui(f))?;
+
+        let downloader = self.native_downloader()?;
+        let local = downloader.list_available_local().await.unwrap_or_default();
+        let available = if local.is_empty() {
+            match downloader.list_available().await {
+                Ok(list) => list,
+                Err(e) => {
+                    self.status_msg = format!("Failed to fetch indexer list: {}", e);
+                    Vec::new()
+                }
+            }
+        } else {
+            local
+                .into_iter()
+                .map(|name| AvailableIndexer {
+                    filename: format!("{}.yml", name),
+                    name,
+                    download_url: String::new(), // Installed from the local cache
+                })
+                .collect()
+        };
+
+        self.installer.installed = downloader
+            .list_local_indexers()
+            .await
+            .unwrap_or_default()
+            .into_iter()
+            .collect();
+        self.installer.available = available;
+        self.installer.loaded = true;
+        self.installer.move_selection(0);
+        self.status_msg = format!(
+            "{} indexers available, {} installed",
+            self.installer.available.len(),
+            self.installer.installed.len()
+        );
+        Ok(())
+    }
+
+    async fn install_selected<B: Backend>(&mut self, terminal: &mut Terminal<B>) -> Result<()> {
+        let Some(name) = self.installer.selected_name() else {
+            return Ok(());
+        };
+        if self.installer.installed.contains(&name) {
+            self.status_msg = format!("'{}' is already installed", name);
+            return Ok(());
+        }
+
+        self.show_toast(format!("Installing '{}'...", name), true);
+        terminal.draw(|f| self.ui(f))?;
+
+        let downloader = self.native_downloader()?;
+        let github_entry = self
+            .installer
+            .available
+            .iter()
+            .find(|i| i.name == name && !i.download_url.is_empty())
+            .cloned();
+        let result = match github_entry {
+            Some(entry) => {
+                std::fs::create_dir_all(self.config.get_active_native_path()?)?;
+                downloader.download_indexer(&entry).await
+            }
+            None => downloader.install_from_available(&name).await,
+        };
+
+        match result {
+            Ok(_) => {
+                self.installer.installed.insert(name.clone());
+                self.show_toast(format!("Installed '{}'", name), true);
+            }
+            Err(e) => self.show_toast(format!("Install failed: {}", e), false),
+        }
+        Ok(())
+    }
+
+    fn remove_native(&mut self, name: &str) {
+        let result = self
+            .config
+            .get_active_native_path()
+            .and_then(|dir| Ok(std::fs::remove_file(dir.join(format!("{}.yml", name)))?));
+        match result {
+            Ok(()) => {
+                self.installer.installed.remove(name);
+                self.show_toast(format!("Removed '{}'", name), true);
+            }
+            Err(e) => self.show_toast(format!("Remove failed: {}", e), false),
+        }
+    }
+
+    async fn handle_installer_input<B: Backend>(
+        &mut self,
+        key: event::KeyEvent,
+        terminal: &mut Terminal<B>,
+    ) -> Result<()> {
+        if self.installer.editing {
+            match key.code {
+                KeyCode::Enter => self.installer.editing = false,
+                _ => {
+                    self.installer.filter.handle_event(&Event::Key(key));
+                    self.installer.state.select(Some(0));
+                    self.installer.move_selection(0);
+                }
+            }
+            return Ok(());
+        }
+
+        match key.code {
+            code if self.keys.matches(Action::ToggleInstaller, code) => {
+                self.installer_open = false;
+                self.status_msg = "Proxied indexers".to_string();
+            }
+            KeyCode::Char('/') => self.installer.editing = true,
+            KeyCode::Down | KeyCode::Char('j') => self.installer.move_selection(1),
+            KeyCode::Up | KeyCode::Char('k') => self.installer.move_selection(-1),
+            KeyCode::Enter => self.install_selected(terminal).await?,
+            code if self.keys.matches(Action::DeleteIndexer, code) => {
+                if let Some(name) = self.installer.selected_name()
+                    && self.installer.installed.contains(&name)
+                {
+                    self.confirm(
+                        format!("Remove native indexer '{}'?", name),
+                        ConfirmAction::RemoveNative(name),
+                    );
+                }
+            }
+            code if self.keys.matches(Action::ReloadIndexers, code) => {
+                self.load_installer(terminal).await?;
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    async fn handle_indexers_input<B: Backend>(
+        &mut self,
+        key: event::KeyEvent,
+        terminal: &mut Terminal<B>,
+    ) -> Result<()> {
+        match key.code {
+            code if self.keys.matches(Action::ToggleInstaller, code) => {
+                self.installer_open = true;
+                if !self.installer.loaded {
+                    self.load_installer(terminal).await?;
+                }
+            }
+            KeyCode::Down | KeyCode::Char('j') => {
+                let i = match self.indexer_state.selected() {
+                    Some(i) => {
+                        if i >= self.config.indexers.len().saturating_sub(1) {
+                            0
+                        } else {
+                            i + 1
+                        }
+                    }
+                    None => 0,
+                };
+                self.indexer_state.select(Some(i));
+            }
+            KeyCode::Up | KeyCode::Char('k') => {
+                let i = match self.indexer_state.selected() {
+                    Some(i) => {
+                        if i == 0 {
+                            self.config.indexers.len().saturating_sub(1)
+                        } else {
+                            i - 1
+                        }
+                    }
+                    None => 0,
+                };
+                self.indexer_state.select(Some(i));
+            }
+            code if self.keys.matches(Action::DeleteIndexer, code) => {
+                if let Some(idx) = self
+                    .indexer_state
+                    .selected()
+                    .and_then(|i| self.config.indexers.get(i))
+                {
+                    let name = idx.name.clone();
+                    self.confirm(
+                        format!("Remove indexer '{}'?", name),
+                        ConfirmAction::RemoveIndexer(name),
+                    );
+                }
+            }
+            code if self.keys.matches(Action::ReloadIndexers, code) => {
+                self.reload_clients();
+                self.status_msg = "Reloaded indexer clients".to_string();
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Persist the query and its results so they survive a restart
+    fn record_history(&mut self, query: &str) {
+        let Some(pool) = &self.db_pool else {
+            return;
+        };
+
+        if let Err(e) = crate::db::add_tui_history(pool, query) {
+            tracing::warn!("Failed to save search history: {}", e);
+        }
+        if let Ok(serialized) = serde_json::to_string(&self.results) {
+            let _ = crate::db::set_cached_results(pool, &tui_cache_key(query), &serialized, 24);
+        }
+        if let Ok(history) = crate::db::get_tui_history(pool, HISTORY_LIMIT) {
+            self.history = history;
+        }
+    }
+
+    async fn handle_history_input<B: Backend>(
+        &mut self,
+        key: event::KeyEvent,
+        terminal: &mut Terminal<B>,
+    ) -> Result<()> {
+        match key.code {
+            code if self.keys.matches(Action::ToggleHistory, code) => {
+                self.show_history = false;
+            }
+            KeyCode::Down | KeyCode::Char('j') if !self.history.is_empty() => {
+                let i = self
+                    .history_state
+                    .selected()
+                    .map_or(0, |i| (i + 1) % self.history.len());
+                self.history_state.select(Some(i));
+            }
+            KeyCode::Up | KeyCode::Char('k') if !self.history.is_empty() => {
+                let i = self
+                    .history_state
+                    .selected()
+                    .map_or(0, |i| (i + self.history.len() - 1) % self.history.len());
+                self.history_state.select(Some(i));
+            }
+            KeyCode::Enter => {
+                if let Some(query) = self
+                    .history_state
+                    .selected()
+                    .and_then(|i| self.history.get(i))
+                {
+                    self.search_input = Input::new(query.clone());
+                    self.show_history = false;
+                    self.perform_search(terminal).await?;
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    async fn handle_search_input<B: Backend>(
+        &mut self,
+        key: event::KeyEvent,
+        terminal: &mut Terminal<B>,
+    ) -> Result<()> {
+        match self.search_mode {
+            InputMode::Editing => match key.code {
+                KeyCode::Enter => {
+                    if !self.search_input.value().is_empty() {
+                        self.perform_search(terminal).await?;
+                        self.search_mode = InputMode::Normal;
+                    }
+                }
+                _ => {
+                    self.search_input.handle_event(&Event::Key(key));
+                }
+            },
+            InputMode::Normal if self.show_history => {
+                self.handle_history_input(key, terminal).await?;
+            }
+            InputMode::Normal => {
+                match key.code {
+                    code if self.keys.matches(Action::ToggleHistory, code) => {
+                        self.show_history = true;
+                        if self.history_state.selected().is_none() && !self.history.is_empty() {
+                            self.history_state.select(Some(0));
+                        }
+                    }
+                    code if code == KeyCode::Char('/')
+                        || self.keys.matches(Action::EditQuery, code) =>
+                    {
+                        self.search_mode = InputMode::Editing;
+                        self.status_msg = "Editing search query...".to_string();
+                    }
+                    KeyCode::Enter => {
+                        // If we have results, Enter might download? Or switch to "Results Focused" mode?
+                        // For now, if no results, enter edit mode.
+                        if self.results.is_empty() {
+                            self.search_mode = InputMode::Editing;
+                        } else {
+                            self.handle_download();
+                        }
+                    }
+                    // Navigation
+                    KeyCode::Down | KeyCode::Char('j') if !self.results.is_empty() => {
+                        let i = match self.results_state.selected() {
+                            Some(i) => {
+                                if i >= self.results.len() - 1 {
+                                    0
+                                } else {
+                                    i + 1
+                                }
+                            }
+                            None => 0,
+                        };
+                        self.results_state.select(Some(i));
+                    }
+                    KeyCode::Up | KeyCode::Char('k') if !self.results.is_empty() => {
+                        let i = match self.results_state.selected() {
+                            Some(i) => {
+                                if i == 0 {
+                                    self.results.len() - 1
+                                } else {
+                                    i - 1
+                                }
+                            }
+                            None => 0,
+                        };
+                        self.results_state.select(Some(i));
+                    }
+                    KeyCode::Char('g') if !self.results.is_empty() => {
+                        self.results_state.select(Some(0));
+                    }
+                    KeyCode::Char('G') if !self.results.is_empty() => {
+                        self.results_state.select(Some(self.results.len() - 1));
+                    }
+                    code if self.keys.matches(Action::SaveMagnet, code) => {
+                        self.handle_save_magnet(terminal).await?;
+                    }
+                    code if self.keys.matches(Action::CopyLink, code) => self.copy_selected(false),
+                    code if self.keys.matches(Action::CopyTitle, code) => self.copy_selected(true),
+                    code if self.keys.matches(Action::CycleSort, code) => {
+                        self.sort_mode = self.sort_mode.next();
+                        self.sort_results();
+                        self.status_msg = format!("Sorted by {}", self.sort_mode.as_str());
+                    }
+                    _ => {}
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn sort_results(&mut self) {
+        match self.sort_mode.field() {
+            Some(by) => {
+                let sort = Sort {
+                    by,
+                    order: self.config.sort.order,
+                };
+                sort.apply(&mut self.results, self.search_input.value());
+            }
+            None => self.results.sort_by(|a, b| a.indexer.cmp(&b.indexer)),
+        }
+    }
+
+    async fn perform_search<B: Backend>(&mut self, terminal: &mut Terminal<B>) -> Result<()> {
+        self.status_msg = format!("Searching for '{}'...", self.search_input.value());
+        terminal.draw(|f| self.ui(f))?;
+
+        if self.client_cache.is_empty() {
+            self.status_msg = "No indexers configured!".to_string();
+            return Ok(());
+        }
+
+        let params = SearchParams {
+            query: self.search_input.value().to_string(),
+            search_type: "search".to_string(),
+            ..Default::default()
+        };
+
+        let futures = self.client_cache.iter().map(|(name, client)| {
+            let p = params.clone();
+            let n = name.clone();
+            async move {
+                match client.search(&p).await {
+                    Ok(mut res) => {
+                        for r in &mut res {
+                            r.indexer = Some(n.clone());
+                        }
+                        Ok::<Vec<TorrentResult>, (String, anyhow::Error)>(res)
+                    }
+                    Err(e) => Err((n, e)),
+                }
+            }
+        });
+
+        let results_lists: Vec<Result<Vec<TorrentResult>, _>> = join_all(futures).await;
+        let mut all_results = Vec::new();
+        for list in results_lists.into_iter().flatten() {
+            all_results.extend(list);
+        }
+
+        if let Some(pool) = &self.db_pool {
+            crate::health::apply(&mut all_results, &crate::health::reliability(pool));
+        } else {
+            crate::health::apply(&mut all_results, &Default::default());
+        }
+        self.results = all_results;
+        self.sort_results();
+        self.results_state.select(Some(0));
+        self.status_msg = format!("Found {} results.", self.results.len());
+        self.search_mode = InputMode::Normal;
+        self.record_history(&params.query);
+
+        Ok(())
+    }
+
+    /// Copy the selected result's magnet/link (or its title) to the system clipboard
+    fn copy_selected(&mut self, title: bool) {
+        let Some(result) = self
+            .results_state
+            .selected()
+            .and_then(|i| self.results.get(i))
+        else {
+            return;
+        };
+
+        let Some((text, what)) = copy_text(result, title) else {
+            self.status_msg = "No magnet or link for this result".to_string();
+            return;
+        };
+
+        let copied = match self.clipboard.as_mut() {
+            Some(clipboard) => clipboard.set_text(text),
+            None => arboard::Clipboard::new().and_then(|mut clipboard| {
+                let res = clipboard.set_text(text);
+                self.clipboard = Some(clipboard);
+                res
+            }),
+        };
+        self.status_msg = match copied {
+            Ok(()) => format!("Copied {} to clipboard", what),
+            Err(e) => format!("Clipboard error: {}", e),
+        };
+    }
+
+    fn show_toast(&mut self, message: String, success: bool) {
+        self.toast = Some(Toast {
+            message,
+            success,
+            expires_at: Instant::now() + TOAST_DURATION,
+        });
+    }
+
+    /// Open the download target picker for the selected result
+    fn handle_download(&mut self) {
+        if let Some(result) = self
+            .results_state
+            .selected()
+            .and_then(|i| self.results.get(i))
+        {
+            let mut state = ListState::default();
+            state.select(Some(0));
+            self.popup = Some(Popup::ClientPicker {
+                result: Box::new(result.clone()),
+                state,
+            });
+        }
+    }
+
+    /// Entries of the client picker: configured clients followed by "Save to disk"
+    fn picker_options(&self) -> Vec<String> {
+        self.config
+            .download_clients
+            .iter()
+            .map(|c| format!("{} ({:?})", c.name, c.client_type))
+            .chain(std::iter::once("Save to disk".to_string()))
+            .collect()
+    }
+
+    fn confirm(&mut self, message: String, action: ConfirmAction) {
+        self.popup = Some(Popup::Confirm { message, action });
+    }
+
+    async fn handle_popup_input<B: Backend>(
+        &mut self,
+        key: event::KeyEvent,
+        terminal: &mut Terminal<B>,
+    ) -> Result<()> {
+        match self.popup {
+            Some(Popup::ClientPicker { .. }) => self.handle_picker_input(key, terminal).await,
+            Some(Popup::Details(_)) => {
+                // Any key closes the details view
+                self.popup = None;
+                Ok(())
+            }
+            Some(Popup::Confirm { .. }) => {
+                match key.code {
+                    KeyCode::Char('y') | KeyCode::Char('Y') => {
+                        if let Some(Popup::Confirm { action, .. }) = self.popup.take() {
+                            self.run_confirmed(action);
+                        }
+                    }
+                    KeyCode::Char('n') | KeyCode::Char('N') | KeyCode::Esc => {
+                        self.popup = None;
+                        self.status_msg = "Cancelled".to_string();
+                    }
+                    _ => {}
+                }
+                Ok(())
+            }
+            None => Ok(()),
+        }
+    }
+
+    fn run_confirmed(&mut self, action: ConfirmAction) {
+        match action {
+            ConfirmAction::RemoveIndexer(name) => self.remove_indexer(&name),
+            ConfirmAction::RemoveNative(name) => self.remove_native(&name),
+            ConfirmAction::ClearCache => {
+                let Some(pool) = &self.db_pool else {
+                    self.show_toast("No database available".to_string(), false);
+                    return;
+                };
+                match crate::db::clear_all_cache(pool) {
+                    Ok(n) => self.show_toast(format!("Cleared {} cached searches", n), true),
+                    Err(e) => self.show_toast(format!("Failed to clear cache: {}", e), false),
+                }
+            }
+            ConfirmAction::ResetSettings => {
+                // Indexers and download clients are kept; only preferences are reset
+                let defaults = Config::default();
+                self.config.download_path = defaults.download_path;
+                self.config.proxy_url = defaults.proxy_url;
+                self.config.scoring = defaults.scoring;
+                self.config.tui = defaults.tui;
+                self.keys = Keymap::default();
+                match self.config.save() {
+                    Ok(()) => {
+                        self.reload_clients();
+                        self.show_toast("Settings reset to defaults".to_string(), true);
+                    }
+                    Err(e) => self.show_toast(format!("Failed to save config: {}", e), false),
+                }
+            }
+        }
+    }
+
+    async fn handle_picker_input<B: Backend>(
+        &mut self,
+        key: event::KeyEvent,
+        terminal: &mut Terminal<B>,
+    ) -> Result<()> {
+        let option_count = self.picker_options().len();
+        let Some(Popup::ClientPicker { state, .. }) = self.popup.as_mut() else {
+            return Ok(());
+        };
+
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('q') => {
+                self.popup = None;
+            }
+            KeyCode::Down | KeyCode::Char('j') => {
+                let i = state.selected().map_or(0, |i| (i + 1) % option_count);
+                state.select(Some(i));
+            }
+            KeyCode::Up | KeyCode::Char('k') => {
+                let i = state
+                    .selected()
+                    .map_or(0, |i| (i + option_count - 1) % option_count);
+                state.select(Some(i));
+            }
+            KeyCode::Enter => {
+                let choice = state.selected().unwrap_or(0);
+                if let Some(Popup::ClientPicker { result, .. }) = self.popup.take() {
+                    self.show_toast(format!("Sending '{}'...", result.title), true);
+                    terminal.draw(|f| self.ui(f))?;
+
+                    let outcome = if choice < self.config.download_clients.len() {
+                        self.send_to_client(&result, choice).await
+                    } else {
+                        self.save_to_disk(&result).await
+                    };
+                    match outcome {
+                        Ok(msg) => self.show_toast(msg, true),
+                        Err(e) => self.show_toast(format!("Failed: {}", e), false),
+                    }
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    async fn send_to_client(&self, result: &TorrentResult, index: usize) -> Result<String> {
+        let client_config = &self.config.download_clients[index];
+        let link = result
+            .magnet
+            .clone()
+            .or_else(|| result.link.clone())
+            .ok_or_else(|| anyhow::anyhow!("No download link available"))?;
+
+        crate::clients::create_client(client_config)
+            .add_torrent(&link)
+            .await?;
+        Ok(format!("Sent '{}' to {}", result.title, client_config.name))
+    }
+
+    /// Save the result to the configured download path (or the current directory)
+    async fn save_to_disk(&self, result: &TorrentResult) -> Result<String> {
+        let dir = std::path::PathBuf::from(self.config.download_path.as_deref().unwrap_or("."));
+        let name = crate::utils::sanitize_filename(&result.title);
+
+        let link = result.link.as_deref().filter(|l| !l.starts_with("magnet:"));
+        let Some(url) = link else {
+            let magnet = result
+                .magnet
+                .as_deref()
+                .or(result.link.as_deref())
+                .ok_or_else(|| anyhow::anyhow!("No download link available"))?;
+            let path = dir.join(format!("{}.magnet", name));
+            std::fs::write(&path, magnet)?;
+            return Ok(format!("Saved magnet to {}", path.display()));
+        };
+
+        let indexer_name = result.indexer.as_deref().unwrap_or_default();
+        let client = self
+            .client_cache
+            .iter()
+            .find(|(n, _)| n == indexer_name)
+            .map(|(_, c)| c)
+            .ok_or_else(|| anyhow::anyhow!("No client for indexer '{}'", indexer_name))?;
+
+        let bytes = client.download(url).await?;
+        let path = dir.join(format!("{}.torrent", name));
+        std::fs::write(&path, bytes)?;
+        Ok(format!("Saved to {}", path.display()))
+    }
+
+    async fn handle_save_magnet<B: Backend>(&mut self, _terminal: &mut Terminal<B>) -> Result<()> {
+        if let Some(_i) = self.results_state.selected() {
+            // ... existing logic ...
+            self.status_msg = "Magnet save logic here".to_string();
+        }
+        Ok(())
+    }
+
+    fn ui(&mut self, f: &mut Frame) {
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Length(3), // Tabs
+                Constraint::Min(0),    // Content
+                Constraint::Length(1), // StatusBar
+            ])
+            .split(f.area());
+
+        // Tabs
+        let tabs = Tabs::new(vec!["Dashboard", "Search", "Indexers", "Settings"])
+            .select(self.active_tab as usize)
+            .block(Block::default().borders(Borders::BOTTOM))
+            .style(Style::default().fg(Color::White))
+            .highlight_style(
+                Style::default()
+                    .fg(Color::Yellow)
+                    .add_modifier(Modifier::BOLD),
+            );
+        f.render_widget(tabs, chunks[0]);
+
+        // Content
+        match self.active_tab {
+            ActiveTab::Dashboard => self.render_dashboard(f, chunks[1]),
+            ActiveTab::Search => self.render_search(f, chunks[1]),
+            ActiveTab::Indexers => self.render_indexers(f, chunks[1]),
+            ActiveTab::Settings => self.render_settings(f, chunks[1]),
+        }
+
+        // Status Bar
+        let status = Paragraph::new(self.status_msg.clone()).style(
+            Style::default()
+                .fg(Color::Cyan)
+                .add_modifier(Modifier::BOLD),
+        );
+        f.render_widget(status, chunks[2]);
+        if let Some(info) = crate::update::available() {
+            let update = Paragraph::new(format!("Update available: v{} ", info.latest))
+                .style(Style::default().fg(Color::Yellow))
+                .alignment(Alignment::Right);
+            f.render_widget(update, chunks[2]);
+        }
+
+        self.render_popup(f);
+        self.render_toast(f, chunks[1]);
+        if self.show_help {
+            self.render_help(f);
+        }
+    }
+
+    fn render_help(&self, f: &mut Frame) {
+        let heading = Style::default()
+            .fg(Color::Yellow)
+            .add_modifier(Modifier::BOLD);
+        let binding = |key: String, desc: &str| {
+            Line::from(vec![
+                Span::styled(format!("  {:<12}", key), Style::default().fg(Color::Cyan)),
+                Span::raw(desc.to_string()),
+            ])
+        };
+
+        let mut lines = vec![
+            Line::styled("Global", heading),
+            binding("Tab/S-Tab".to_string(), "Next/previous tab"),
+            binding("Esc".to_string(), "Leave edit mode / quit"),
+        ];
+        let mut section = "Global";
+        for action in Action::ALL {
+            if action.section() != section {
+                section = action.section();
+                lines.push(Line::default());
+                lines.push(Line::styled(section, heading));
+                if section == "Search" {
+                    lines.push(binding("/".to_string(), "Edit search query"));
+                    lines.push(binding("Enter".to_string(), "Download selected result"));
+                    lines.push(binding("j/k g/G".to_string(), "Move selection"));
+                } else if section == "Indexers" {
+                    lines.push(binding("j/k".to_string(), "Move selection"));
+                    lines.push(binding("/".to_string(), "Installer: filter definitions"));
+                    lines.push(binding("Enter".to_string(), "Installer: install selected"));
+                }
+            }
+            lines.push(binding(self.keys.label(action), action.description()));
+        }
+
+        let area = centered_rect(60, 70, f.area());
+        f.render_widget(Clear, area);
+        f.render_widget(
+            Paragraph::new(lines).block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title("Keybindings")
+                    .title_bottom("Press any key to close"),
+            ),
+            area,
+        );
+    }
+
+    fn render_popup(&mut self, f: &mut Frame) {
+        let options = self.picker_options();
+        match self.popup.as_mut() {
+            Some(Popup::ClientPicker { result, state }) => {
+                let area = centered_rect(60, 40, f.area());
+                let items: Vec<ListItem> = options.into_iter().map(ListItem::new).collect();
+                let list = List::new(items)
+                    .block(
+                        Block::default()
+                            .borders(Borders::ALL)
+                            .title(format!("Send '{}' to", result.title))
+                            .title_bottom("Enter: select  Esc: cancel"),
+                    )
+                    .highlight_style(Style::default().add_modifier(Modifier::REVERSED))
+                    .highlight_symbol(">> ");
+
+                f.render_widget(Clear, area);
+                f.render_stateful_widget(list, area, state);
+            }
+            Some(Popup::Details(result)) => {
+                let field = |name: &str, value: String| {
+                    Line::from(vec![
+                        Span::styled(format!("{:<12}", name), Style::default().fg(Color::Yellow)),
+                        Span::raw(value),
+                    ])
+                };
+                let opt = |v: Option<String>| v.unwrap_or_else(|| "-".to_string());
+                let lines = vec![
+                    Line::styled(
+                        result.title.clone(),
+                        Style::default().add_modifier(Modifier::BOLD),
+                    ),
+                    Line::default(),
+                    field("Indexer", opt(result.indexer.clone())),
+                    field("Size", opt(result.size.map(crate::utils::format_size))),
+                    field("Seeders", opt(result.seeders.map(|v| v.to_string()))),
+                    field("Leechers", opt(result.leechers.map(|v| v.to_string()))),
+                    field("Health", opt(result.health.map(|v| format!("{}/100", v)))),
+                    field(
+                        "Published",
+                        opt(result.publish_date.map(|d| d.to_rfc2822())),
+                    ),
+                    field(
+                        "Categories",
+                        result
+                            .categories
+                            .iter()
+                            .map(|c| c.to_string())
+                            .collect::<Vec<_>>()
+                            .join(", "),
+                    ),
+                    field("Details", opt(result.details.clone())),
+                    field("Link", opt(result.link.clone())),
+                    field("Magnet", opt(result.magnet.clone())),
+                ];
+
+                let area = centered_rect(80, 60, f.area());
+                f.render_widget(Clear, area);
+                f.render_widget(
+                    Paragraph::new(lines).wrap(Wrap { trim: false }).block(
+                        Block::default()
+                            .borders(Borders::ALL)
+                            .title("Details")
+                            .title_bottom("Press any key to close"),
+                    ),
+                    area,
+                );
+            }
+            Some(Popup::Confirm { message, .. }) => {
+                let area = centered_rect(50, 20, f.area());
+                f.render_widget(Clear, area);
+                f.render_widget(
+                    Paragraph::new(message.as_str())
+                        .wrap(Wrap { trim: true })
+                        .alignment(Alignment::Center)
+                        .block(
+                            Block::default()
+                                .borders(Borders::ALL)
+                                .border_style(Style::default().fg(Color::Red))
+                                .title("Confirm")
+                                .title_bottom("y: yes  n: no"),
+                        ),
+                    area,
+                );
+            }
+            None => {}
+        }
+    }
+
+    fn render_toast(&self, f: &mut Frame, area: Rect) {
+        let Some(toast) = &self.toast else {
+            return;
+        };
+
+        let width = (toast.message.chars().count() as u16 + 4).min(area.width);
+        let height = 3.min(area.height);
+        let rect = Rect {
+            x: area.right().saturating_sub(width),
+            y: area.bottom().saturating_sub(height),
+            width,
+            height,
+        };
+        let color = if toast.success {
+            Color::Green
+        } else {
+            Color::Red
+        };
+
+        f.render_widget(Clear, rect);
+        f.render_widget(
+            Paragraph::new(toast.message.as_str())
+                .style(Style::default().fg(color))
+                .block(
+                    Block::default()
+                        .borders(Borders::ALL)
+                        .border_style(Style::default().fg(color)),
+                ),
+            rect,
+        );
+    }
+
+    fn render_dashboard(&self, f: &mut Frame, area: Rect) {
+        let dashboard_chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Length(7), // Stats
+                Constraint::Min(4),    // Activity
+            ])
+            .split(area);
+
+        // Stats Row
+        let stats_layout = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([
+                Constraint::Percentage(25),
+                Constraint::Percentage(25),
+                Constraint::Percentage(25),
+                Constraint::Percentage(25),
+            ])
+            .split(dashboard_chunks[0]);
+
+        // Helper for stats
+        let total_indexers = self.config.indexers.len().to_string();
+
+        f.render_widget(
+            Paragraph::new(total_indexers.as_str())
+                .style(
+                    Style::default()
+                        .fg(Color::Magenta)
+                        .add_modifier(Modifier::BOLD),
+                )
+                .block(
+                    Block::default()
+                        .borders(Borders::ALL)
+                        .title("Total Indexers"),
+                )
+                .alignment(Alignment::Center),
+            stats_layout[0],
+        );
+
+        f.render_widget(
+            Paragraph::new("45ms")
+                .style(
+                    Style::default()
+                        .fg(Color::Blue)
+                        .add_modifier(Modifier::BOLD),
+                )
+                .block(Block::default().borders(Borders::ALL).title("Avg Response"))
+                .alignment(Alignment::Center),
+            stats_layout[1],
+        );
+
+        f.render_widget(
+            Paragraph::new("12")
+                .style(
+                    Style::default()
+                        .fg(Color::Cyan)
+                        .add_modifier(Modifier::BOLD),
+                )
+                .block(
+                    Block::default()
+                        .borders(Borders::ALL)
+                        .title("Total Searches"),
+                )
+                .alignment(Alignment::Center),
+            stats_layout[2],
+        );
+
+        f.render_widget(
+            Paragraph::new("12m")
+                .style(
+                    Style::default()
+                        .fg(Color::Green)
+                        .add_modifier(Modifier::BOLD),
+                )
+                .block(Block::default().borders(Borders::ALL).title("Uptime"))
+                .alignment(Alignment::Center),
+            stats_layout[3],
+        );
+
+        // Activity Log
+        let activity = Paragraph::new("12:00: Search 'ubuntu'\n12:01: Download 'Ubuntu 24.04 ISO'")
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title("Recent Activity"),
+            );
+        f.render_widget(activity, dashboard_chunks[1]);
+    }
+
+    fn render_search(&mut self, f: &mut Frame, area: Rect) {
+        let area = if self.show_history {
+            let panes = Layout::default()
+                .direction(Direction::Horizontal)
+                .constraints([Constraint::Length(30), Constraint::Min(20)])
+                .split(area);
+            self.render_history(f, panes[0]);
+            panes[1]
+        } else {
+            area
+        };
+
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Length(3), // Input
+                Constraint::Min(1),    // Results
+            ])
+            .split(area);
+
+        // Input
+        let scroll = self
+            .search_input
+            .visual_scroll(chunks[0].width.max(3) as usize - 3);
+        let title = format!(
+            "Query ('{}' edit, '{}' sort [{}], '{}' history, '{}' help)",
+            self.keys.label(Action::EditQuery),
+            self.keys.label(Action::CycleSort),
+            self.sort_mode.as_str(),
+            self.keys.label(Action::ToggleHistory),
+            self.keys.label(Action::Help),
+        );
+        let input = Paragraph::new(self.search_input.value())
+            .style(match self.search_mode {
+                InputMode::Editing => Style::default().fg(Color::Yellow),
+                _ => Style::default(),
+            })
+            .scroll((0, scroll as u16))
+            .block(Block::default().borders(Borders::ALL).title(title));
+        f.render_widget(input, chunks[0]);
+
+        if matches!(self.search_mode, InputMode::Editing) {
+            f.set_cursor_position((
+                chunks[0].x + ((self.search_input.visual_cursor().max(scroll) - scroll) as u16) + 1,
+                chunks[0].y + 1,
+            ));
+        }
+
+        // Table
+        let header_style = Style::default().fg(Color::Yellow);
+        let selected_style = Style::default().add_modifier(Modifier::REVERSED);
+
+        let header = ["Indexer", "Seed", "Leech", "Size", "Title"]
+            .into_iter()
+            .map(Cell::from)
+            .collect::<Row>()
+            .style(header_style)
+            .height(1);
+
+        let rows = self.results.iter().map(|item| {
+            let indexer = item.indexer.clone().unwrap_or_default();
+            let seed = item
+                .seeders
+                .map(|s| s.to_string())
+                .unwrap_or("-".to_string());
+            let leech = item
+                .leechers
+                .map(|s| s.to_string())
+                .unwrap_or("-".to_string());
+            let size = item
+                .size
+                .map(crate::utils::format_size)
+                .unwrap_or("-".to_string());
+
+            let color = if item.seeders.unwrap_or(0) > 0 {
+                Color::Green
+            } else {
+                Color::Red
+            };
+
+            Row::new(vec![
+                Cell::from(indexer).style(Style::default().fg(Color::Cyan)),
+                Cell::from(seed).style(Style::default().fg(color)),
+                Cell::from(leech),
+                Cell::from(size),
+                Cell::from(item.title.clone()),
+            ])
+        });
+
+        let t = Table::new(
+            rows,
+            [
+                Constraint::Length(12),
+                Constraint::Length(6),
+                Constraint::Length(6),
+                Constraint::Length(10),
+                Constraint::Min(10),
+            ],
+        )
+        .header(header)
+        .block(Block::default().borders(Borders::ALL).title("Results"))
+        .row_highlight_style(selected_style)
+        .highlight_symbol(">> ");
+
+        self.results_area = chunks[1];
+        f.render_stateful_widget(t, chunks[1], &mut self.results_state);
+    }
+
+    fn render_settings(&self, f: &mut Frame, area: Rect) {
+        let value = |v: Option<&str>| v.unwrap_or("(default)").to_string();
+        let db_path = self
+            .config
+            .get_db_path()
+            .map(|p| p.display().to_string())
+            .unwrap_or_default();
+        let rows = [
+            ("Download path", value(self.config.download_path.as_deref())),
+            ("Proxy", value(self.config.proxy_url.as_deref())),
+            ("Database", db_path),
+            (
+                "Download clients",
+                self.config.download_clients.len().to_string(),
+            ),
+        ]
+        .into_iter()
+        .map(|(k, v)| {
+            Row::new(vec![
+                Cell::from(k).style(Style::default().fg(Color::Yellow)),
+                Cell::from(v),
+            ])
+        });
+
+        let t = Table::new(rows, [Constraint::Length(20), Constraint::Min(20)]).block(
+            Block::default().borders(Borders::ALL).title(format!(
+                "Settings ('{}' clear cache, '{}' reset)",
+                self.keys.label(Action::ClearCache),
+                self.keys.label(Action::ResetSettings)
+            )),
+        );
+        f.render_widget(t, area);
+    }
+
+    fn render_history(&mut self, f: &mut Frame, area: Rect) {
+        let items: Vec<ListItem> = self
+            .history
+            .iter()
+            .map(|q| ListItem::new(q.as_str()))
+            .collect();
+        let list = List::new(items)
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .border_style(Style::default().fg(Color::Yellow))
+                    .title("History ('h' to hide)"),
+            )
+            .highlight_style(Style::default().add_modifier(Modifier::REVERSED))
+            .highlight_symbol(">> ");
+
+        f.render_stateful_widget(list, area, &mut self.history_state);
+    }
+
+    fn render_indexers(&mut self, f: &mut Frame, area: Rect) {
+        if self.installer_open {
+            let hint = format!(
+                "Enter install, '{}' remove, '{}' back",
+                self.keys.label(Action::DeleteIndexer),
+                self.keys.label(Action::ToggleInstaller)
+            );
+            self.installer.render(f, area, &hint);
+            return;
+        }
+
+        let header = ["Name", "URL", "Status"]
+            .into_iter()
+            .map(Cell::from)
+            .collect::<Row>()
+            .style(Style::default().fg(Color::Yellow))
+            .height(1);
+
+        let rows = self.config.indexers.iter().map(|idx| {
+            Row::new(vec![
+                Cell::from(idx.name.clone()),
+                Cell::from(idx.url.clone()),
+                Cell::from("Active").style(Style::default().fg(Color::Green)),
+            ])
+        });
+
+        let t = Table::new(
+            rows,
+            [
+                Constraint::Length(20),
+                Constraint::Min(30),
+                Constraint::Length(10),
+            ],
+        )
+        .header(header)
+        .block(Block::default().borders(Borders::ALL).title(format!(
+            "Proxied Indexers ('{}' native indexer installer)",
+            self.keys.label(Action::ToggleInstaller)
+        )))
+        .row_highlight_style(Style::default().add_modifier(Modifier::REVERSED))
+        .highlight_symbol(">> ");
+
+        self.indexers_area = area;
+        f.render_stateful_widget(t, area, &mut self.indexer_state);
+    }
+}
+
+/// What copying a result puts on the clipboard: its title, or else its
+/// magnet or link. Also returns what was copied, for the status bar.
+fn copy_text(result: &TorrentResult, title: bool) -> Option<(String, &'static str)> {
+    if title {
+        Some((result.title.clone(), "title"))
+    } else if let Some(magnet) = &result.magnet {
+        Some((magnet.clone(), "magnet"))
+    } else {
+        result.link.clone().map(|link| (link, "link"))
+    }
+}
+
+/// Cache key for TUI search results (same layout as the web UI's cache keys)
+fn tui_cache_key(query: &str) -> String {
+    format!("tui:all:{}:", query)
+}
+
+/// Map a terminal row to a table row index, for a bordered table with a one-line header
+fn table_row_at(area: Rect, y: u16, offset: usize) -> Option<usize> {
+    let first = area.y + 2;
+    let last = area.bottom().saturating_sub(1);
+    (y >= first && y < last).then(|| offset + (y - first) as usize)
+}
+
+/// Rect of the given percentage size centered within `r`
+fn centered_rect(percent_x: u16, percent_y: u16, r: Rect) -> Rect {
+    let vertical = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Percentage((100 - percent_y) / 2),
+            Constraint::Percentage(percent_y),
+            Constraint::Percentage((100 - percent_y) / 2),
+        ])
+        .split(r);
+
+    Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([
+            Constraint::Percentage((100 - percent_x) / 2),
+            Constraint::Percentage(percent_x),
+            Constraint::Percentage((100 - percent_x) / 2),
+        ])
+        .split(vertical[1])[1]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::backend::TestBackend;
+    use std::path::PathBuf;
+
+    /// App with its database and download path in a fresh temp directory
+    fn test_app(name: &str) -> (App, PathBuf) {
+        let dir = std::env::temp_dir().join(format!(
+            "lodestarr-test-tui-{}-{}",
+            name,
+            std::process::id()
+        ));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let config = Config {
+            db_path: Some(dir.join("lodestarr.db").to_string_lossy().into_owned()),
+            download_path: Some(dir.to_string_lossy().into_owned()),
+            ..Default::default()
+        };
+        (App::new(config).unwrap(), dir)
+    }
+
+    fn magnet_result(title: &str) -> TorrentResult {
+        TorrentResult {
+            title: title.to_string(),
+            magnet: Some("magnet:?xt=urn:btih:abc".to_string()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_copy_text() {
+        let mut result = magnet_result("Some Show");
+        result.link = Some("https://t.example/1.torrent".to_string());
+        assert_eq!(
+            copy_text(&result, true),
+            Some(("Some Show".to_string(), "title"))
+        );
+        // Magnets are preferred over .torrent links
+        assert_eq!(
+            copy_text(&result, false),
+            Some(("magnet:?xt=urn:btih:abc".to_string(), "magnet"))
+        );
+        result.magnet = None;
+        assert_eq!(
+            copy_text(&result, false),
+            Some(("https://t.example/1.torrent".to_string(), "link"))
+        );
+        result.link = None;
+        assert_eq!(copy_text(&result, false), None);
+    }
+
+    #[test]
+    fn test_history_restores_last_results() {
+        let (mut app, dir) = test_app("history");
+        app.results = vec![magnet_result("Dune")];
+        app.record_history("dune");
+        app.results = vec![magnet_result("Alien")];
+        app.record_history("alien");
+        // Searching again moves a query back to the top
+        app.results = vec![magnet_result("Dune"), magnet_result("Dune Messiah")];
+        app.record_history("dune");
+        assert_eq!(app.history, vec!["dune", "alien"]);
+
+        // The next session starts with the last query and its results
+        let app = App::new(app.config.clone()).unwrap();
+        assert_eq!(app.search_input.value(), "dune");
+        assert_eq!(app.results.len(), 2);
+        assert_eq!(app.results[1].title, "Dune Messiah");
+        assert_eq!(app.results_state.selected(), Some(0));
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[tokio::test]
+    async fn test_confirm_dialog() {
+        let (mut app, dir) = test_app("confirm");
+        let mut terminal = Terminal::new(TestBackend::new(80, 24)).unwrap();
+        let pool = app.db_pool.clone().unwrap();
+        crate::db::set_cached_results(&pool, "tui:all:dune:", "[]", 24).unwrap();
+        let cached = || crate::db::get_cached_results(&pool, "tui:all:dune:").unwrap();
+
+        // 'n' cancels, and other keys leave the dialog open
+        app.handle_settings_input(KeyCode::Char('c').into());
+        assert!(matches!(app.popup, Some(Popup::Confirm { .. })));
+        app.handle_popup_input(KeyCode::Char('x').into(), &mut terminal)
+            .await
+            .unwrap();
+        assert!(app.popup.is_some());
+        app.handle_popup_input(KeyCode::Char('n').into(), &mut terminal)
+            .await
+            .unwrap();
+        assert!(app.popup.is_none());
+        assert!(cached().is_some());
+
+        app.handle_settings_input(KeyCode::Char('c').into());
+        app.handle_popup_input(KeyCode::Char('y').into(), &mut terminal)
+            .await
+            .unwrap();
+        assert!(app.popup.is_none());
+        assert!(cached().is_none());
+        assert_eq!(
+            app.toast.as_ref().unwrap().message,
+            "Cleared 1 cached searches"
+        );
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[tokio::test]
+    async fn test_download_picker() {
+        let (mut app, dir) = test_app("picker");
+        let mut terminal = Terminal::new(TestBackend::new(80, 24)).unwrap();
+        app.config
+            .download_clients
+            .push(crate::config::DownloadClient {
+                id: "qb".to_string(),
+                name: "Home".to_string(),
+                client_type: crate::config::ClientType::QBittorrent,
+                url: "http://127.0.0.1:1".to_string(),
+                username: None,
+                password: None,
+            });
+        app.results = vec![magnet_result("Some Show")];
+        app.results_state.select(Some(0));
+
+        app.handle_download();
+        assert!(matches!(app.popup, Some(Popup::ClientPicker { .. })));
+        assert_eq!(
+            app.picker_options(),
+            vec!["Home (QBittorrent)".to_string(), "Save to disk".to_string()]
+        );
+
+        // Up wraps around to the last entry
+        app.handle_popup_input(KeyCode::Up.into(), &mut terminal)
+            .await
+            .unwrap();
+        app.handle_popup_input(KeyCode::Enter.into(), &mut terminal)
+            .await
+            .unwrap();
+        assert!(app.popup.is_none());
+        let toast = app.toast.as_ref().unwrap();
+        assert!(toast.success, "{}", toast.message);
+        assert_eq!(
+            std::fs::read_to_string(dir.join("Some_Show.magnet")).unwrap(),
+            "magnet:?xt=urn:btih:abc"
+        );
+
+        // A failed send shows a failure toast
+        app.handle_download();
+        app.handle_popup_input(KeyCode::Enter.into(), &mut terminal)
+            .await
+            .unwrap();
+        assert!(!app.toast.as_ref().unwrap().success);
+
+        // Esc closes the picker without sending
+        app.handle_download();
+        app.handle_popup_input(KeyCode::Esc.into(), &mut terminal)
+            .await
+            .unwrap();
+        assert!(app.popup.is_none());
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn test_table_row_at() {
+        let area = Rect::new(0, 5, 40, 10);
+        // Border and header rows are not table rows
+        assert_eq!(table_row_at(area, 5, 0), None);
+        assert_eq!(table_row_at(area, 6, 0), None);
+        assert_eq!(table_row_at(area, 7, 0), Some(0));
+        assert_eq!(table_row_at(area, 9, 3), Some(5));
+        // Bottom border
+        assert_eq!(table_row_at(area, 14, 0), None);
+    }
+}
@@ -0,0 +1,196 @@
+//! Opt-in update checker (enabled with `check_updates = true`)
+//!
+//! Queries the GitHub releases API at most once a day. The last answer is kept
+//! in `update-check.json` next to the config so short-lived CLI runs don't hit
+//! GitHub every time.
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use directories::ProjectDirs;
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::RwLock;
+use std::time::Duration;
+
+const RELEASES_URL: &str = "https://api.github.com/repos/ddonindia/Lodestarr/releases/latest";
+const CHECK_INTERVAL_HOURS: i64 = 24;
+
+/// Latest release seen by the last check
+static LATEST: Lazy<RwLock<Option<UpdateInfo>>> = Lazy::new(|| RwLock::new(None));
+
+/// GitHub release (only the fields we use)
+#[derive(Debug, Deserialize)]
+pub struct GitHubRelease {
+    pub tag_name: String,
+    pub html_url: String,
+    #[cfg(feature = "self-update")]
+    #[serde(default)]
+    pub assets: Vec<ReleaseAsset>,
+}
+
+#[cfg(feature = "self-update")]
+#[derive(Debug, Deserialize)]
+pub struct ReleaseAsset {
+    pub name: String,
+    pub browser_download_url: String,
+}
+
+/// Latest published release
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UpdateInfo {
+    /// Release version without the leading "v"
+    pub latest: String,
+    /// Release page
+    pub url: String,
+    pub checked_at: DateTime<Utc>,
+}
+
+impl UpdateInfo {
+    /// True if this release is newer than the running binary
+    pub fn is_newer(&self) -> bool {
+        is_newer(&self.latest, env!("CARGO_PKG_VERSION"))
+    }
+}
+
+/// Newer release than the running binary, if the last check found one
+pub fn available() -> Option<UpdateInfo> {
+    LATEST.read().ok()?.clone().filter(|info| info.is_newer())
+}
+
+/// Refresh the latest release (from the daily state file, or GitHub when stale)
+pub async fn refresh(proxy_url: Option<&str>) -> Option<UpdateInfo> {
+    let cached = load_state().filter(|info| {
+        Utc::now() - info.checked_at < chrono::Duration::hours(CHECK_INTERVAL_HOURS)
+    });
+    let info = match cached {
+        Some(info) => info,
+        None => match fetch_latest(proxy_url).await {
+            Ok(info) => {
+                save_state(&info);
+                info
+            }
+            Err(e) => {
+                tracing::debug!("Update check failed: {}", e);
+                return None;
+            }
+        },
+    };
+
+    if let Ok(mut latest) = LATEST.write() {
+        *latest = Some(info.clone());
+    }
+    Some(info).filter(|info| info.is_newer())
+}
+
+/// Check now and then once a day (for the server)
+pub fn spawn_daily(proxy_url: Option<String>) {
+    tokio::spawn(async move {
+        loop {
+            if let Some(info) = refresh(proxy_url.as_deref()).await {
+                tracing::info!(
+                    "Lodestarr {} is available (running {}): {}",
+                    info.latest,
+                    env!("CARGO_PKG_VERSION"),
+                    info.url
+                );
+            }
+            tokio::time::sleep(Duration::from_secs(CHECK_INTERVAL_HOURS as u64 * 3600)).await;
+        }
+    });
+}
+
+/// HTTP client for GitHub requests
+pub fn client(proxy_url: Option<&str>, timeout: Duration) -> Result<reqwest::Client> {
+    let mut builder = reqwest::Client::builder()
+        .user_agent(concat!("Lodestarr/", env!("CARGO_PKG_VERSION")))
+        .timeout(timeout);
+    if let Some(url) = proxy_url {
+        builder = builder.proxy(reqwest::Proxy::all(url)?);
+    }
+    Ok(builder.build()?)
+}
+
+/// Fetch the latest release including its assets
+pub async fn fetch_release(proxy_url: Option<&str>) -> Result<GitHubRelease> {
+    Ok(client(proxy_url, Duration::from_secs(10))?
+        .get(RELEASES_URL)
+        .header("Accept", "application/vnd.github+json")
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?)
+}
+
+/// Query GitHub for the latest release
+pub async fn fetch_latest(proxy_url: Option<&str>) -> Result<UpdateInfo> {
+    let release = fetch_release(proxy_url).await?;
+    Ok(UpdateInfo {
+        latest: release.tag_name.trim_start_matches('v').to_string(),
+        url: release.html_url,
+        checked_at: Utc::now(),
+    })
+}
+
+fn state_path() -> Option<PathBuf> {
+    let proj_dirs = ProjectDirs::from("com", "lodestarr", "lodestarr")?;
+    Some(proj_dirs.config_dir().join("update-check.json"))
+}
+
+fn load_state() -> Option<UpdateInfo> {
+    let content = std::fs::read_to_string(state_path()?).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+fn save_state(info: &UpdateInfo) {
+    let Some(path) = state_path() else {
+        return;
+    };
+    if let Some(parent) = path.parent() {
+        let _ = std::fs::create_dir_all(parent);
+    }
+    if let Ok(json) = serde_json::to_string(info)
+        && let Err(e) = std::fs::write(&path, json)
+    {
+        tracing::debug!("Failed to save update check state: {}", e);
+    }
+}
+
+/// Compare dotted versions ("0.4.10" > "0.4.9"). A pre-release of the same
+/// version ("0.4.2-0") is older than the release.
+pub fn is_newer(latest: &str, current: &str) -> bool {
+    fn parse(v: &str) -> (Vec<u64>, bool) {
+        let v = v.trim().trim_start_matches('v');
+        let (core, pre) = match v.split_once('-') {
+            Some((core, _)) => (core, true),
+            None => (v, false),
+        };
+        let parts = core.split('.').map(|p| p.parse().unwrap_or(0)).collect();
+        (parts, pre)
+    }
+
+    let (latest, latest_pre) = parse(latest);
+    let (current, current_pre) = parse(current);
+    match latest.cmp(&current) {
+        std::cmp::Ordering::Greater => true,
+        std::cmp::Ordering::Less => false,
+        std::cmp::Ordering::Equal => current_pre && !latest_pre,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_newer() {
+        assert!(is_newer("v0.4.3", "0.4.2"));
+        assert!(is_newer("0.4.10", "0.4.9"));
+        assert!(is_newer("1.0.0", "0.9.9"));
+        assert!(is_newer("0.4.2", "0.4.2-0"));
+        assert!(!is_newer("0.4.2", "0.4.2"));
+        assert!(!is_newer("0.4.1", "0.4.2-0"));
+        assert!(!is_newer("0.4.2-1", "0.4.2"));
+    }
+}
@@ -1,17 +1,117 @@
+use crate::config::{DisplayConfig, SizeUnits};
+use once_cell::sync::Lazy;
+use std::sync::RwLock;
+
+static FORMAT: Lazy<RwLock<NumberFormat>> =
+    Lazy::new(|| RwLock::new(NumberFormat::new(&DisplayConfig::default())));
+
+/// Register the `[display]` settings used by [`format_size`] and [`format_count`]
+pub fn configure_display(config: &DisplayConfig) {
+    if let Ok(mut format) = FORMAT.write() {
+        *format = NumberFormat::new(config);
+    }
+}
+
+fn current_format() -> NumberFormat {
+    FORMAT
+        .read()
+        .map(|f| *f)
+        .unwrap_or_else(|_| NumberFormat::new(&DisplayConfig::default()))
+}
+
 /// Format bytes into human readable string
 pub fn format_size(bytes: u64) -> String {
-    const KB: u64 = 1024;
-    const MB: u64 = KB * 1024;
-    const GB: u64 = MB * 1024;
+    current_format().size(bytes)
+}
+
+/// Format a count with the locale's thousands separator
+pub fn format_count(n: u64) -> String {
+    current_format().count(n)
+}
+
+/// Size units and separators for displayed numbers
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NumberFormat {
+    pub units: SizeUnits,
+    pub decimal_sep: char,
+    pub group_sep: char,
+}
+
+impl NumberFormat {
+    /// Settings from the config, with the locale taken from the environment
+    /// when the config leaves it unset
+    pub fn new(config: &DisplayConfig) -> Self {
+        let locale = config.locale.clone().or_else(|| {
+            ["LC_ALL", "LC_NUMERIC", "LANG"]
+                .iter()
+                .find_map(|var| std::env::var(var).ok().filter(|v| !v.is_empty()))
+        });
+        let (decimal_sep, group_sep) = separators(locale.as_deref().unwrap_or("C"));
+        Self {
+            units: config.size_units,
+            decimal_sep,
+            group_sep,
+        }
+    }
+
+    pub fn size(&self, bytes: u64) -> String {
+        let (base, units) = match self.units {
+            SizeUnits::Binary => (1024.0, ["B", "KiB", "MiB", "GiB", "TiB"]),
+            SizeUnits::Decimal => (1000.0, ["B", "kB", "MB", "GB", "TB"]),
+        };
+        if (bytes as f64) < base {
+            return format!("{} B", bytes);
+        }
+        let mut value = bytes as f64;
+        let mut unit = 0;
+        while value >= base && unit < units.len() - 1 {
+            value /= base;
+            unit += 1;
+        }
+        let fixed = format!("{:.1}", value);
+        let (int, frac) = fixed.split_once('.').unwrap_or((&fixed, "0"));
+        format!(
+            "{}{}{} {}",
+            self.group(int),
+            self.decimal_sep,
+            frac,
+            units[unit]
+        )
+    }
 
-    if bytes >= GB {
-        format!("{:.1} GB", bytes as f64 / GB as f64)
-    } else if bytes >= MB {
-        format!("{:.1} MB", bytes as f64 / MB as f64)
-    } else if bytes >= KB {
-        format!("{:.1} KB", bytes as f64 / KB as f64)
-    } else {
-        format!("{} B", bytes)
+    pub fn count(&self, n: u64) -> String {
+        self.group(&n.to_string())
+    }
+
+    /// Insert the thousands separator into a run of ASCII digits
+    fn group(&self, digits: &str) -> String {
+        let mut out = String::with_capacity(digits.len() + digits.len() / 3 * 2);
+        for (i, c) in digits.chars().enumerate() {
+            if i > 0 && (digits.len() - i).is_multiple_of(3) {
+                out.push(self.group_sep);
+            }
+            out.push(c);
+        }
+        out
+    }
+}
+
+/// Decimal and thousands separators for a locale such as `de_DE.UTF-8` or
+/// `fr-FR`, by language. Space-grouping locales get a no-break space so a
+/// number never wraps across lines.
+fn separators(locale: &str) -> (char, char) {
+    let lang = locale
+        .split(['_', '-', '.', '@'])
+        .next()
+        .unwrap_or_default()
+        .to_ascii_lowercase();
+    match lang.as_str() {
+        "de" | "es" | "it" | "nl" | "pt" | "id" | "tr" | "da" | "el" | "ro" | "hr" | "sl" => {
+            (',', '.')
+        }
+        "fr" | "sv" | "fi" | "nb" | "nn" | "no" | "cs" | "sk" | "pl" | "ru" | "uk" | "hu"
+        | "bg" => (',', '\u{a0}'),
+        _ => ('.', ','),
     }
 }
 
@@ -22,3 +122,59 @@ pub fn sanitize_filename(name: &str) -> String {
         "_",
     )
 }
+
+/// Free and total bytes on the filesystem holding `path`. A path that
+/// doesn't exist yet is measured at its nearest existing parent.
+pub fn disk_space(path: &std::path::Path) -> Option<(u64, u64)> {
+    let existing = path.ancestors().find(|p| p.exists())?;
+    let free = fs2::available_space(existing).ok()?;
+    let total = fs2::total_space(existing).ok()?;
+    Some((free, total))
+}
+
+/// Check that files can be created in `dir` by writing and removing a probe file
+pub fn check_writable(dir: &std::path::Path) -> std::io::Result<()> {
+    let probe = dir.join(".lodestarr-write-test");
+    std::fs::write(&probe, b"")?;
+    std::fs::remove_file(&probe)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_number_format() {
+        let binary = NumberFormat {
+            units: SizeUnits::Binary,
+            decimal_sep: '.',
+            group_sep: ',',
+        };
+        assert_eq!(binary.size(512), "512 B");
+        assert_eq!(binary.size(1536), "1.5 KiB");
+        assert_eq!(binary.size(3 * 1024u64.pow(4)), "3.0 TiB");
+        assert_eq!(binary.size(2000 * 1024u64.pow(4)), "2,000.0 TiB");
+        assert_eq!(binary.count(1234567), "1,234,567");
+
+        let (decimal_sep, group_sep) = separators("de_DE.UTF-8");
+        let german = NumberFormat {
+            units: SizeUnits::Decimal,
+            decimal_sep,
+            group_sep,
+        };
+        assert_eq!(german.size(1_500_000_000), "1,5 GB");
+        assert_eq!(german.size(999), "999 B");
+        assert_eq!(german.count(12345), "12.345");
+        assert_eq!(separators("fr-FR").1, '\u{a0}');
+        assert_eq!(separators("C"), ('.', ','));
+    }
+
+    #[test]
+    fn test_disk_checks() {
+        let dir = std::env::temp_dir();
+        let (free, total) = disk_space(&dir.join("not/created/yet")).unwrap();
+        assert!(free <= total && total > 0);
+        assert!(check_writable(&dir).is_ok());
+        assert!(check_writable(&dir.join("lodestarr-missing-dir")).is_err());
+    }
+}
@@ -1,18 +1,53 @@
-import { useState } from 'react';
+import { useCallback, useEffect, useState } from 'react';
 import { Toaster } from 'react-hot-toast';
-import { Sun, Moon, Monitor } from 'lucide-react';
+import { Sun, Moon, Monitor, LogOut } from 'lucide-react';
 import { useTheme } from './contexts/ThemeContext';
 
 import Search from './components/Search';
 import Dashboard from './components/Dashboard';
+import Sports from './components/Sports';
 import Indexers from './components/NativeIndexers'; // Using NativeIndexers as main Indexers view
 import Settings from './components/Settings';
 import RecentActivity from './components/RecentActivity';
 import Sidebar from './components/Sidebar';
+import UpdateBanner from './components/UpdateBanner';
+import Login from './components/Login';
+import { configureDisplay } from './utils/formatters';
 
 function App() {
-  const [view, setView] = useState<'dashboard' | 'search' | 'indexers' | 'settings' | 'activity'>('dashboard');
+  const [view, setView] = useState<'dashboard' | 'search' | 'sports' | 'indexers' | 'settings' | 'activity'>('dashboard');
   const { settings, setColorMode, resolvedMode } = useTheme();
+  const [auth, setAuth] = useState<'checking' | 'ok' | 'login'>('checking');
+  const [session, setSession] = useState<string | null>(null);
+
+  const loadInfo = useCallback(() => {
+    fetch('/api/info')
+      .then(res => {
+        if (res.status === 401) {
+          setAuth('login');
+          return null;
+        }
+        return res.json();
+      })
+      .then(info => {
+        if (!info) return;
+        if (info.display) configureDisplay(info.display);
+        // Only password logins can be signed out of here
+        setSession(info.login && info.user && info.user !== 'apikey' ? info.user : null);
+        setAuth('ok');
+      })
+      .catch(() => setAuth('ok'));
+  }, []);
+
+  useEffect(() => {
+    loadInfo();
+  }, [loadInfo]);
+
+  const logout = async () => {
+    await fetch('/api/auth/logout', { method: 'POST' }).catch(() => {});
+    setSession(null);
+    setAuth('login');
+  };
 
   const toggleTheme = () => {
     const nextMode = settings.colorMode === 'dark' ? 'light' : settings.colorMode === 'light' ? 'auto' : 'dark';
@@ -24,6 +59,9 @@ function App() {
     return resolvedMode === 'dark' ? <Moon size={20} /> : <Sun size={20} />;
   };
 
+  if (auth === 'checking') return null;
+  if (auth === 'login') return <Login onLogin={loadInfo} />;
+
   return (
     <div
       className="min-h-screen font-sans transition-colors"
@@ -60,13 +98,24 @@ function App() {
             >
               {getThemeIcon()}
             </button>
+            {session && (
+              <button
+                onClick={logout}
+                className="p-2 rounded-full hover:bg-white/10 transition-colors text-neutral-400 hover:text-white"
+                title={`Sign out ${session}`}
+              >
+                <LogOut size={20} />
+              </button>
+            )}
           </div>
         </header>
+        <UpdateBanner />
 
         <div className="p-4 lg:p-8 pb-24 lg:pb-8 max-w-[1600px] mx-auto">
           <div className="animate-in fade-in slide-in-from-bottom-4 duration-300">
             {view === 'dashboard' && <Dashboard />}
             {view === 'search' && <Search />}
+            {view === 'sports' && <Sports />}
             {view === 'indexers' && <Indexers />}
             {view === 'settings' && <Settings />}
             {view === 'activity' && <RecentActivity />}
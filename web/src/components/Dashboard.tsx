@@ -3,6 +3,7 @@ import { Activity, Database, Zap, TrendingUp, RefreshCw, TestTube, HardDrive, Cp
 import { Card, CardHeader, CardBody, CardTitle, Button, Badge, Spinner } from './ui';
 import { AreaChart, Area, XAxis, YAxis, CartesianGrid, Tooltip, ResponsiveContainer } from 'recharts';
 import toast from 'react-hot-toast';
+import { formatCount, formatSize } from '../utils/formatters';
 
 interface SearchLog {
     query: string;
@@ -21,6 +22,14 @@ interface StatsResponse {
     total_searches: number;
     avg_search_time_ms: number;
     recent_searches: SearchLog[];
+    bandwidth?: IndexerTraffic[];
+}
+
+interface IndexerTraffic {
+    indexer: string;
+    search_bytes: number;
+    download_bytes: number;
+    total_bytes: number;
 }
 
 export default function Dashboard() {
@@ -109,7 +118,7 @@ export default function Dashboard() {
                 />
                 <StatCard
                     title="Total Searches"
-                    value={stats?.total_searches ?? 0}
+                    value={formatCount(stats?.total_searches ?? 0)}
                     icon={<Activity className="w-6 h-6" />}
                     color="blue"
                     trend="Cumulative session searches"
@@ -205,6 +214,26 @@ export default function Dashboard() {
                         </CardBody>
                     </Card>
 
+                    {stats?.bandwidth && stats.bandwidth.length > 0 && (
+                        <Card>
+                            <CardHeader>
+                                <CardTitle>Bandwidth (30 days)</CardTitle>
+                            </CardHeader>
+                            <CardBody className="space-y-2">
+                                {stats.bandwidth.map(t => (
+                                    <div
+                                        key={t.indexer}
+                                        className="flex items-center justify-between text-sm"
+                                        title={`Searches: ${formatSize(t.search_bytes)}, downloads: ${formatSize(t.download_bytes)}`}
+                                    >
+                                        <span className="truncate">{t.indexer}</span>
+                                        <span className="font-mono opacity-80">{formatSize(t.total_bytes)}</span>
+                                    </div>
+                                ))}
+                            </CardBody>
+                        </Card>
+                    )}
+
                     <Card>
                         <CardHeader>
                             <CardTitle>Quick Actions</CardTitle>
@@ -6,6 +6,7 @@ import { type IndexerSetting } from '../types/api';
 import IndexerSettingsForm from './indexers/IndexerSettingsForm';
 import ProxiedIndexerForm from './indexers/ProxiedIndexerForm';
 import IndexerTestSection from './indexers/IndexerTestSection';
+import CaptchaLogin from './indexers/CaptchaLogin';
 
 interface Props {
     isOpen: boolean;
@@ -18,6 +19,7 @@ export default function EditIndexerModal({ isOpen, onClose, indexer, onSave }: P
     const [loading, setLoading] = useState(false);
     const [settings, setSettings] = useState<Record<string, string>>({});
     const [definitions, setDefinitions] = useState<IndexerSetting[]>([]);
+    const [needsCaptcha, setNeedsCaptcha] = useState(false);
 
     // For Proxied Indexers
     const [proxiedForm, setProxiedForm] = useState({ url: '', apikey: '' });
@@ -49,6 +51,7 @@ export default function EditIndexerModal({ isOpen, onClose, indexer, onSave }: P
             if (res.ok) {
                 const data = await res.json();
                 setDefinitions(data.settings || []);
+                setNeedsCaptcha(!!data.captcha);
                 // Merge saved values with defaults for advanced settings
                 const savedValues = data.values || {};
                 setSettings({
@@ -219,6 +222,10 @@ export default function EditIndexerModal({ isOpen, onClose, indexer, onSave }: P
                                 />
                             )}
 
+                            {indexer.isNative && needsCaptcha && (
+                                <CaptchaLogin indexerId={indexer.id} />
+                            )}
+
                             <IndexerTestSection
                                 onTest={handleTest}
                                 testing={testing}
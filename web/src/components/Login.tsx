@@ -0,0 +1,79 @@
+import { useState, type FormEvent } from 'react';
+import { Button, Card, CardBody } from './ui';
+
+interface LoginProps {
+    onLogin: () => void;
+}
+
+const inputClass = 'w-full rounded-lg px-4 py-2.5 text-sm transition-all outline-none focus:ring-2 focus:ring-indigo-500/50';
+const inputStyle = { backgroundColor: 'var(--theme-bg)', border: '1px solid var(--theme-border)', color: 'inherit' };
+
+/** Sign-in form shown when the server asks for a session ([server.auth] username) */
+export default function Login({ onLogin }: LoginProps) {
+    const [username, setUsername] = useState('');
+    const [password, setPassword] = useState('');
+    const [error, setError] = useState<string | null>(null);
+    const [loading, setLoading] = useState(false);
+
+    const submit = async (e: FormEvent) => {
+        e.preventDefault();
+        setLoading(true);
+        setError(null);
+        try {
+            const res = await fetch('/api/auth/login', {
+                method: 'POST',
+                headers: { 'Content-Type': 'application/json' },
+                body: JSON.stringify({ username, password }),
+            });
+            if (res.ok) {
+                onLogin();
+            } else {
+                setError(await res.text());
+            }
+        } catch {
+            setError('Could not reach the server');
+        } finally {
+            setLoading(false);
+        }
+    };
+
+    return (
+        <div
+            className="min-h-screen flex items-center justify-center p-4 font-sans"
+            style={{ backgroundColor: 'var(--theme-bg)', color: 'var(--theme-text)' }}
+        >
+            <Card className="w-full max-w-sm">
+                <CardBody>
+                    <form onSubmit={submit} className="space-y-4">
+                        <div className="flex items-center gap-3">
+                            <img src="/icon.png" alt="Logo" className="w-8 h-8 rounded-lg" />
+                            <h1 className="text-lg font-semibold">Lodestarr</h1>
+                        </div>
+                        <input
+                            className={inputClass}
+                            style={inputStyle}
+                            value={username}
+                            onChange={e => setUsername(e.target.value)}
+                            placeholder="Username"
+                            autoComplete="username"
+                            autoFocus
+                        />
+                        <input
+                            className={inputClass}
+                            style={inputStyle}
+                            type="password"
+                            value={password}
+                            onChange={e => setPassword(e.target.value)}
+                            placeholder="Password"
+                            autoComplete="current-password"
+                        />
+                        {error && <p className="text-sm text-red-400">{error}</p>}
+                        <Button type="submit" className="w-full" loading={loading}>
+                            Sign in
+                        </Button>
+                    </form>
+                </CardBody>
+            </Card>
+        </div>
+    );
+}
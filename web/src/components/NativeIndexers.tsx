@@ -1,10 +1,10 @@
 import { useEffect, useState } from 'react';
-import { Search, Download, RefreshCw, Globe, X, Plus, HardDrive } from 'lucide-react';
+import { Search, Download, RefreshCw, Globe, X, Plus, HardDrive, FlaskConical } from 'lucide-react';
 import { Button, Spinner } from './ui';
 import EditIndexerModal from './EditIndexerModal';
 import toast from 'react-hot-toast';
 import type { GithubIndexer, LocalIndexer, ProxiedIndexer, UnifiedIndexer } from '../types/indexer';
-import { AddIndexerForm, BrowseIndexersGrid, InstalledIndexersTable } from './indexers';
+import { AddIndexerForm, BrowseIndexersGrid, DefinitionPlayground, InstalledIndexersTable } from './indexers';
 
 export default function NativeIndexers() {
     const [githubIndexers, setGithubIndexers] = useState<GithubIndexer[]>([]);
@@ -15,7 +15,7 @@ export default function NativeIndexers() {
     const [refreshing, setRefreshing] = useState(false);
     const [downloading, setDownloading] = useState<Set<string>>(new Set());
     const [searchQuery, setSearchQuery] = useState('');
-    const [activeTab, setActiveTab] = useState<'browse' | 'installed' | 'add'>('installed');
+    const [activeTab, setActiveTab] = useState<'browse' | 'installed' | 'add' | 'playground'>('installed');
 
     const [editingIndexer, setEditingIndexer] = useState<UnifiedIndexer | null>(null);
     const [testingId, setTestingId] = useState<string | null>(null);
@@ -223,12 +223,15 @@ export default function NativeIndexers() {
                     <button onClick={() => setActiveTab('browse')} className={`px-4 py-2 font-medium transition-colors ${activeTab === 'browse' ? 'text-primary-600 border-b-2 border-primary-500' : 'text-neutral-400 hover:text-white'}`}>
                         <Globe className="w-4 h-4 inline mr-2" />Browse Available ({githubIndexers.length})
                     </button>
+                    <button onClick={() => setActiveTab('playground')} className={`px-4 py-2 font-medium transition-colors ${activeTab === 'playground' ? 'text-primary-600 border-b-2 border-primary-500' : 'text-neutral-400 hover:text-white'}`}>
+                        <FlaskConical className="w-4 h-4 inline mr-2" />Playground
+                    </button>
                 </div>
                 <Button onClick={loadData} variant="secondary" size="sm"><RefreshCw className="w-4 h-4 mr-2" />Refresh</Button>
             </header>
 
             {/* Toolbar */}
-            {activeTab !== 'add' && (
+            {activeTab !== 'add' && activeTab !== 'playground' && (
                 <div className="flex flex-col md:flex-row gap-4 mb-6">
                     <div className="relative flex-1">
                         <Search className="absolute left-3 top-1/2 -translate-y-1/2 w-5 h-5 text-neutral-400" />
@@ -263,6 +266,7 @@ export default function NativeIndexers() {
             {/* Content */}
             {activeTab === 'browse' && <BrowseIndexersGrid indexers={filteredGithubIndexers} downloading={downloading} onDownload={downloadIndexer} />}
             {activeTab === 'installed' && <InstalledIndexersTable indexers={filteredInstalledIndexers} testingId={testingId} onToggle={toggleIndexer} onEdit={setEditingIndexer} onDelete={deleteIndexer} onQuickTest={quickTestIndexer} />}
+            {activeTab === 'playground' && <DefinitionPlayground />}
             {activeTab === 'add' && <AddIndexerForm onSuccess={() => { loadData(); setActiveTab('installed'); }} />}
 
             {/* Empty state */}
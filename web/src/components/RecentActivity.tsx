@@ -1,6 +1,6 @@
 
 import { useEffect, useState } from 'react';
-import { Search, Trash2, RefreshCw, Eye, X } from 'lucide-react';
+import { Search, Trash2, RefreshCw, Eye, X, FileCode } from 'lucide-react';
 import { Card, CardHeader, CardBody, CardTitle, Button, Badge, Spinner } from './ui';
 import toast from 'react-hot-toast';
 import type { TorrentResult } from '../types';
@@ -16,6 +16,7 @@ interface CachedSearch {
     indexer: string;
     expires_at: string;
     result_count: number;
+    has_raw?: boolean;
 }
 
 export default function RecentActivity() {
@@ -28,7 +29,7 @@ export default function RecentActivity() {
     const [inspectedResult, setInspectedResult] = useState<TorrentResult | null>(null);
 
     // Use the shared hooks
-    const { clients, handleSendToClient, downloadConfigured, downloading, handleServerDownload, downloadedLinks } = useDownloadClients();
+    const { clients, handleSendToClient, downloadConfigured, downloading, handleServerDownload, downloadedLinks, arrApps, handlePush } = useDownloadClients();
     const { copiedField, copyToClipboard } = useCopyToClipboard();
     const { torrentMeta, loadingMeta, fetchTorrentMeta } = useTorrentMeta();
 
@@ -158,9 +159,20 @@ export default function RecentActivity() {
                                                 </Badge>
                                             </td>
                                             <td className="px-6 py-3 text-neutral-400 font-mono text-xs">
-                                                {new Date(search.expires_at).toLocaleString()}
+                                                {search.result_count === 0 && search.has_raw ? '-' : new Date(search.expires_at).toLocaleString()}
                                             </td>
-                                            <td className="px-6 py-3 text-right">
+                                            <td className="px-6 py-3 text-right space-x-2">
+                                                {search.has_raw && (
+                                                    <Button
+                                                        variant="secondary"
+                                                        size="sm"
+                                                        title="Pages that gave no results"
+                                                        onClick={() => window.open(`/api/history/${encodeURIComponent(search.cache_key)}/raw`, '_blank')}
+                                                    >
+                                                        <FileCode className="w-4 h-4 mr-1" />
+                                                        Raw
+                                                    </Button>
+                                                )}
                                                 <Button
                                                     variant="secondary"
                                                     size="sm"
@@ -237,6 +249,8 @@ export default function RecentActivity() {
                 onSendToClient={handleSendToClient}
                 downloadConfigured={downloadConfigured}
                 onDownload={handleServerDownload}
+                arrApps={arrApps}
+                onPush={handlePush}
                 downloadingId={downloading}
                 onFetchMeta={fetchTorrentMeta}
                 loadingMeta={loadingMeta}
@@ -1,5 +1,5 @@
 
-import { X, Info, Check, Copy, Magnet, Download, ExternalLink, Send, Server } from 'lucide-react';
+import { X, Info, Check, Copy, Magnet, Download, ExternalLink, Send, Server, Tv } from 'lucide-react';
 import { Button } from './ui';
 import type { TorrentResult, TorrentMetadata } from '../types';
 import {
@@ -12,7 +12,8 @@ import {
     getResultMagnet,
     getResultLink,
     getResultDetails,
-    getResultIndexerId
+    getResultIndexerId,
+    getResultPosterUrl
 } from '../types';
 import { formatSize } from '../utils/formatters';
 
@@ -29,10 +30,14 @@ interface ResultDetailsModalProps {
 
     // Optional props for Send to Client and Download to Server
     clients?: { id: string; name: string }[];
-    onSendToClient?: (clientId: string, magnet: string, title: string) => void;
+    onSendToClient?: (clientId: string, magnet: string, title: string, size?: number) => void;
     downloadConfigured?: boolean;
-    onDownload?: (link: string, title: string) => void;
+    onDownload?: (link: string, title: string, size?: number, result?: TorrentResult) => void;
     downloadingId?: string | null;
+
+    // Optional props for release push to Sonarr/Radarr
+    arrApps?: { name: string }[];
+    onPush?: (app: string, result: TorrentResult) => void;
 }
 
 export default function ResultDetailsModal({
@@ -47,7 +52,9 @@ export default function ResultDetailsModal({
     onSendToClient,
     downloadConfigured = false,
     onDownload,
-    downloadingId = null
+    downloadingId = null,
+    arrApps = [],
+    onPush
 }: ResultDetailsModalProps) {
 
     if (!result) return null;
@@ -62,6 +69,7 @@ export default function ResultDetailsModal({
     const link = getResultLink(result);
     const details = getResultDetails(result);
     const indexerId = getResultIndexerId(result);
+    const posterUrl = getResultPosterUrl(result);
 
     return (
         <div className="fixed inset-0 bg-black/80 flex items-center justify-center z-[60] p-4">
@@ -81,9 +89,19 @@ export default function ResultDetailsModal({
                 </div>
                 <div className="overflow-auto flex-1 p-4 space-y-4">
                     {/* Title */}
-                    <div>
-                        <label className="block text-xs text-neutral-400 mb-1">Title</label>
-                        <div className="text-white font-medium">{title}</div>
+                    <div className="flex gap-4">
+                        {posterUrl && (
+                            <img
+                                src={posterUrl}
+                                alt=""
+                                className="w-24 max-h-36 object-cover rounded border border-neutral-700"
+                                onError={(e) => { e.currentTarget.style.display = 'none'; }}
+                            />
+                        )}
+                        <div>
+                            <label className="block text-xs text-neutral-400 mb-1">Title</label>
+                            <div className="text-white font-medium">{title}</div>
+                        </div>
                     </div>
 
                     {/* Basic Info */}
@@ -212,8 +230,8 @@ export default function ResultDetailsModal({
                                     <div className="text-white">{formatSize(torrentMeta.total_size)}</div>
                                 </div>
                                 <div>
-                                    <label className="block text-xs text-neutral-400 mb-1">Piece Size</label>
-                                    <div className="text-white">{formatSize(torrentMeta.piece_length)}</div>
+                                    <label className="block text-xs text-neutral-400 mb-1">Pieces</label>
+                                    <div className="text-white">{formatSize(torrentMeta.piece_length)} × {torrentMeta.piece_count}{torrentMeta.private ? ' (private)' : ''}</div>
                                 </div>
                                 <div>
                                     <label className="block text-xs text-neutral-400 mb-1">Info Hash</label>
@@ -275,14 +293,14 @@ export default function ResultDetailsModal({
                     )}
 
                     {/* Action Buttons */}
-                    {(link || magnet) && (clients.length > 0 || downloadConfigured) && (
+                    {(link || magnet) && (clients.length > 0 || downloadConfigured || arrApps.length > 0) && (
                         <div className="pt-4 border-t border-neutral-700">
                             <label className="block text-xs text-neutral-400 mb-2">Quick Actions</label>
                             <div className="flex flex-wrap gap-2">
                                 {/* Download to Server */}
                                 {downloadConfigured && link && onDownload && (
                                     <button
-                                        onClick={() => onDownload(link, title)}
+                                        onClick={() => onDownload(link, title, size, result)}
                                         disabled={downloadingId === link}
                                         className="flex items-center gap-2 px-4 py-2 bg-blue-600 hover:bg-blue-500 disabled:opacity-50 rounded-lg text-sm font-medium transition-colors"
                                     >
@@ -303,7 +321,7 @@ export default function ResultDetailsModal({
                                 {/* Send to Client - Single client */}
                                 {clients.length === 1 && onSendToClient && (magnet || link) && (
                                     <button
-                                        onClick={() => onSendToClient(clients[0].id, magnet || link || '', title)}
+                                        onClick={() => onSendToClient(clients[0].id, magnet || link || '', title, size)}
                                         className="flex items-center gap-2 px-4 py-2 bg-emerald-600 hover:bg-emerald-500 rounded-lg text-sm font-medium transition-colors"
                                     >
                                         <Send className="w-4 h-4" />
@@ -319,7 +337,7 @@ export default function ResultDetailsModal({
                                             style={{ backgroundColor: '#059669' }}
                                             onChange={(e) => {
                                                 if (e.target.value) {
-                                                    onSendToClient(e.target.value, magnet || link || '', title);
+                                                    onSendToClient(e.target.value, magnet || link || '', title, size);
                                                     e.target.value = '';
                                                 }
                                             }}
@@ -333,6 +351,18 @@ export default function ResultDetailsModal({
                                         <Send className="absolute right-3 top-1/2 -translate-y-1/2 w-4 h-4 pointer-events-none" />
                                     </div>
                                 )}
+
+                                {/* Release push to Sonarr/Radarr */}
+                                {onPush && arrApps.map(app => (
+                                    <button
+                                        key={app.name}
+                                        onClick={() => onPush(app.name, result)}
+                                        className="flex items-center gap-2 px-4 py-2 bg-violet-600 hover:bg-violet-500 rounded-lg text-sm font-medium transition-colors"
+                                    >
+                                        <Tv className="w-4 h-4" />
+                                        Push to {app.name}
+                                    </button>
+                                ))}
                             </div>
                         </div>
                     )}
@@ -10,6 +10,7 @@ import { useDownloadClients } from '../hooks/useDownloadClients';
 import { useCopyToClipboard } from '../hooks/useCopyToClipboard';
 import { useTorrentMeta } from '../hooks/useTorrentMeta';
 import { TORZNAB_CATEGORIES } from '../constants/categories';
+import { formatAge } from '../utils/formatters';
 import { inputStyle, buttonPrimaryStyle } from '../styles/shared';
 
 interface Category {
@@ -46,7 +47,7 @@ export default function Search() {
     const [currentPage, setCurrentPage] = useState(1);
 
     // Use the shared hooks
-    const { clients, handleSendToClient, downloadConfigured, downloading, handleServerDownload, downloadedLinks } = useDownloadClients();
+    const { clients, handleSendToClient, downloadConfigured, downloading, handleServerDownload, downloadedLinks, arrApps, handlePush } = useDownloadClients();
     const { copiedField, copyToClipboard } = useCopyToClipboard();
     const { torrentMeta, loadingMeta, fetchTorrentMeta, clearMeta } = useTorrentMeta();
 
@@ -59,6 +60,46 @@ export default function Search() {
 
     const itemsPerPage = 25;
 
+    // Artist suggestions from MusicBrainz while an Audio category is selected
+    const [artistSuggestions, setArtistSuggestions] = useState<{ id: string; name: string; disambiguation?: string }[]>([]);
+    const isAudioCategory = selectedCategory.startsWith('3') && selectedCategory.length === 4;
+
+    useEffect(() => {
+        if (!isAudioCategory || query.trim().length < 2) {
+            setArtistSuggestions([]);
+            return;
+        }
+        const timer = setTimeout(() => {
+            fetch(`/api/music/artists?q=${encodeURIComponent(query.trim())}`)
+                .then(res => res.ok ? res.json() : [])
+                .then(setArtistSuggestions)
+                .catch(() => setArtistSuggestions([]));
+        }, 400);
+        return () => clearTimeout(timer);
+    }, [query, isAudioCategory]);
+
+    // Saved searches can only target native indexers (or all of them)
+    const canSaveFeed = !!query && (selectedIndexers === 'all-native' || nativeIndexers.some(n => n.id === selectedIndexers));
+
+    const handleSaveFeed = async () => {
+        try {
+            const res = await fetch('/api/saved-searches', {
+                method: 'POST',
+                headers: { 'Content-Type': 'application/json' },
+                body: JSON.stringify({
+                    query,
+                    indexer: selectedIndexers === 'all-native' ? null : selectedIndexers,
+                    category: selectedCategory || null
+                })
+            });
+            if (!res.ok) throw new Error(await res.text());
+            const saved = await res.json();
+            copyToClipboard(`${window.location.origin}/feeds/saved/${saved.id}.rss`, 'feed');
+        } catch (err) {
+            setError(`Failed to save feed: ${(err as Error).message}`);
+        }
+    };
+
     // Fetch available indexers on mount
     useEffect(() => {
         const loadIndexers = async () => {
@@ -138,8 +179,13 @@ export default function Search() {
         setCurrentPage(1);
     }, [results, filterText, filterCategory, filterIndexer, sortField, sortDirection]);
 
-    const handleSearch = async (e: React.FormEvent) => {
+    const handleSearch = (e: React.FormEvent) => {
         e.preventDefault();
+        runSearch(false);
+    };
+
+    // `refresh` skips the server's search cache and replaces it
+    const runSearch = async (refresh: boolean) => {
         if (!selectedIndexers && !query) return;
 
         setLoading(true);
@@ -164,9 +210,12 @@ export default function Search() {
                 if (selectedCategory) {
                     params.append('cat', selectedCategory);
                 }
+                if (refresh) {
+                    params.append('refresh', 'true');
+                }
 
                 const res = await fetch(`${baseUrl}?${params.toString()}`);
-                if (!res.ok) throw new Error('Search failed');
+                if (!res.ok) throw new Error(await res.text() || 'Search failed');
                 const data = await res.json();
 
                 if (Array.isArray(data)) {
@@ -180,10 +229,15 @@ export default function Search() {
                         Indexer: r.indexer ?? null,
                         IndexerId: r.indexer_id ?? null,
                         PublishDate: r.publish_date ?? null,
+                        PublishDateSource: r.date_source,
                         Category: r.categories || [],
+                        Poster: r.poster,
+                        Health: r.health ?? null,
                         Comments: r.comments || r.guid || '',
                         Guid: r.guid || r.title,
-                        Grabs: r.grabs ?? 0
+                        Grabs: r.grabs ?? 0,
+                        FetchedAt: r.fetched_at,
+                        Cached: r.cached
                     }));
                 }
             } else {
@@ -248,6 +302,7 @@ export default function Search() {
             case 'Title': valA = a.Title || ''; valB = b.Title || ''; break;
             case 'Size': valA = a.Size || 0; valB = b.Size || 0; break;
             case 'Seeders': valA = a.Seeders || 0; valB = b.Seeders || 0; break;
+            case 'Health': valA = a.Health ?? 0; valB = b.Health ?? 0; break;
             case 'Date': valA = a.PublishDate ? new Date(a.PublishDate).getTime() : 0; valB = b.PublishDate ? new Date(b.PublishDate).getTime() : 0; break;
         }
 
@@ -330,7 +385,13 @@ export default function Search() {
                                     placeholder="Search queries..."
                                     className="w-full rounded-lg pl-10 pr-4 py-3 outline-none"
                                     style={inputStyle}
+                                    list={artistSuggestions.length > 0 ? 'artist-suggestions' : undefined}
                                 />
+                                <datalist id="artist-suggestions">
+                                    {artistSuggestions.map(a => (
+                                        <option key={a.id} value={a.name}>{a.disambiguation}</option>
+                                    ))}
+                                </datalist>
                                 <svg className="absolute left-3 top-3.5 h-5 w-5 opacity-50" fill="none" viewBox="0 0 24 24" stroke="currentColor">
                                     <path strokeLinecap="round" strokeLinejoin="round" strokeWidth={2} d="M21 21l-6-6m2-5a7 7 0 11-14 0 7 7 0 0114 0z" />
                                 </svg>
@@ -345,6 +406,16 @@ export default function Search() {
                             >
                                 {loading ? 'Searching...' : 'Search'}
                             </button>
+                            <button
+                                type="button"
+                                onClick={handleSaveFeed}
+                                disabled={!canSaveFeed}
+                                title="Save this search and copy its RSS feed URL"
+                                className="disabled:opacity-50 disabled:cursor-not-allowed px-4 py-3 rounded-lg font-medium transition-colors"
+                                style={inputStyle}
+                            >
+                                {copiedField === 'feed' ? 'Copied!' : 'Save as Feed'}
+                            </button>
                         </form>
                     </div>
                 </div>
@@ -377,6 +448,19 @@ export default function Search() {
                 </div>
             )}
 
+            {!loading && results[0]?.Cached && results[0].FetchedAt && (
+                <div className="mb-4 text-sm opacity-70 flex items-center gap-2">
+                    <span>Results from {formatAge(results[0].FetchedAt)}</span>
+                    <button
+                        onClick={() => runSearch(true)}
+                        className="underline hover:opacity-80"
+                        style={{ color: 'var(--theme-accent)' }}
+                    >
+                        Refresh
+                    </button>
+                </div>
+            )}
+
             {/* Mobile Card View */}
             <div className="lg:hidden">
                 {paginatedResults.length === 0 && !loading && !error && (
@@ -431,6 +515,8 @@ export default function Search() {
                 onSendToClient={handleSendToClient}
                 downloadConfigured={downloadConfigured}
                 onDownload={handleServerDownload}
+                arrApps={arrApps}
+                onPush={handlePush}
                 downloadingId={downloading}
             />
         </div>
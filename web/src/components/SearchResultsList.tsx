@@ -7,6 +7,7 @@ import {
     getResultSeeders,
     getResultPeers,
     getResultDate,
+    getResultDateNote,
     getResultCategories,
     getResultIndexerId,
     getResultLink,
@@ -21,10 +22,10 @@ interface SearchResultsListProps {
     error?: string | null;
     onInspect: (result: TorrentResult) => void;
     downloadConfigured?: boolean;
-    onDownload: (link: string, title: string) => void;
+    onDownload: (link: string, title: string, size?: number, result?: TorrentResult) => void;
     downloadingId?: string | null;
     clients: { id: string; name: string }[];
-    onSendToClient: (clientId: string, magnet: string, title: string) => void;
+    onSendToClient: (clientId: string, magnet: string, title: string, size?: number) => void;
 }
 
 export default function SearchResultsList({
@@ -86,7 +87,7 @@ export default function SearchResultsList({
                                     <span className="text-red-400">↓{peers}</span>
                                 </div>
                             </div>
-                            <span className="opacity-50">{formatDate(date)}</span>
+                            <span className="opacity-50" title={getResultDateNote(result)}>{formatDate(date)}{getResultDateNote(result) && '*'}</span>
                         </div>
 
                         {/* Categories */}
@@ -125,7 +126,7 @@ export default function SearchResultsList({
                             </a>
                             {downloadConfigured && (
                                 <button
-                                    onClick={() => link && onDownload(link, title)}
+                                    onClick={() => link && onDownload(link, title, size, result)}
                                     disabled={downloadingId === link || !link}
                                     className="px-4 py-2 rounded-lg text-sm font-medium transition-colors disabled:opacity-50"
                                     style={{ backgroundColor: 'var(--theme-bg)', border: '1px solid var(--theme-border)' }}
@@ -138,7 +139,7 @@ export default function SearchResultsList({
                                 <>
                                     {clients.length === 1 ? (
                                         <button
-                                            onClick={() => onSendToClient(clients[0].id, magnet || link || '', title)}
+                                            onClick={() => onSendToClient(clients[0].id, magnet || link || '', title, size)}
                                             className="px-4 py-2 rounded-lg text-sm font-medium transition-colors w-full sm:w-auto"
                                             style={{ backgroundColor: 'var(--theme-bg)', border: '1px solid var(--theme-border)', color: 'var(--theme-accent)' }}
                                         >
@@ -148,7 +149,7 @@ export default function SearchResultsList({
                                         <select
                                             onChange={(e) => {
                                                 if (e.target.value) {
-                                                    onSendToClient(e.target.value, magnet || link || '', title);
+                                                    onSendToClient(e.target.value, magnet || link || '', title, size);
                                                     e.target.value = ''; // Reset
                                                 }
                                             }}
@@ -4,12 +4,17 @@ import {
     getResultTitle,
     getResultSize,
     getResultSeeders,
+    getResultHealth,
     getResultPeers,
     getResultLink,
     getResultIndexer,
     getResultDate,
+    getResultDateNote,
     getResultMagnet,
-    getResultIndexerId
+    getResultIndexerId,
+    getResultOtherIndexers,
+    isResultInLibrary,
+    isResultCategoryFallback
 } from '../types';
 import { formatSize, formatDate } from '../utils/formatters';
 import { buttonSecondaryStyle } from '../styles/shared';
@@ -22,12 +27,12 @@ interface SearchResultsTableProps {
     sortDirection?: 'asc' | 'desc';
     onSort?: (field: SortField) => void;
     onInspect: (result: TorrentResult) => void;
-    onDownload: (link: string, title: string) => void;
+    onDownload: (link: string, title: string, size?: number, result?: TorrentResult) => void;
     downloadConfigured?: boolean;
     downloadingId?: string | null;
     variant?: 'full' | 'simple'; // 'full' allows sorting columns, 'simple' is for basic display
     clients?: { id: string; name: string }[];
-    onSendToClient?: (clientId: string, magnet: string, title: string) => void;
+    onSendToClient?: (clientId: string, magnet: string, title: string, size?: number) => void;
     downloadedLinks?: Set<string>;
 }
 
@@ -108,6 +113,19 @@ export default function SearchResultsTable({
                                     <SortIcon field="Seeders" />
                                 </div>
                             </th>
+                            {variant === 'full' && (
+                                <th
+                                    data-testid="sort-health"
+                                    className="px-6 py-4 text-right cursor-pointer hover:text-white select-none transition-colors"
+                                    onClick={() => handleSort('Health')}
+                                    title="0-100: seeders, peer ratio, age, indexer reliability and freeleech"
+                                >
+                                    <div className="flex items-center justify-end gap-1">
+                                        Health
+                                        <SortIcon field="Health" />
+                                    </div>
+                                </th>
+                            )}
                             {variant === 'full' && (
                                 <th
                                     data-testid="sort-date"
@@ -126,14 +144,14 @@ export default function SearchResultsTable({
                     <tbody className="divide-y divide-neutral-800">
                         {results.length === 0 && !loading && !error && (
                             <tr>
-                                <td colSpan={variant === 'full' ? 6 : 5} className="px-6 py-12 text-center text-neutral-500">
+                                <td colSpan={variant === 'full' ? 7 : 5} className="px-6 py-12 text-center text-neutral-500">
                                     No results found
                                 </td>
                             </tr>
                         )}
                         {loading && (
                             <tr>
-                                <td colSpan={variant === 'full' ? 6 : 5} className="px-6 py-12 text-center text-neutral-500">
+                                <td colSpan={variant === 'full' ? 7 : 5} className="px-6 py-12 text-center text-neutral-500">
                                     Loading...
                                 </td>
                             </tr>
@@ -145,20 +163,44 @@ export default function SearchResultsTable({
                             const seeders = getResultSeeders(result).toString();
                             const peers = getResultPeers(result);
                             const date = getResultDate(result);
+                            const health = getResultHealth(result);
                             const link = getResultLink(result);
                             const magnet = getResultMagnet(result);
                             const indexerId = getResultIndexerId(result);
+                            const otherIndexers = getResultOtherIndexers(result);
 
                             return (
                                 <tr key={`${idx}-${title}`} className="hover:bg-white/5 transition-colors group">
-                                    <td className="px-6 py-4 font-mono text-xs opacity-70 whitespace-nowrap">
+                                    <td
+                                        className="px-6 py-4 font-mono text-xs opacity-70 whitespace-nowrap"
+                                        title={otherIndexers.length > 0 ? `Also on ${otherIndexers.join(', ')}` : undefined}
+                                    >
                                         {indexer}
+                                        {otherIndexers.length > 0 && <span className="opacity-60"> +{otherIndexers.length}</span>}
                                     </td>
                                     <td
                                         className="px-6 py-4 font-medium text-white max-w-xl truncate cursor-pointer hover:text-[var(--theme-accent)] transition-colors"
                                         title={title}
                                         onClick={() => onInspect(result)}
                                     >
+                                        {isResultInLibrary(result) && (
+                                            <span
+                                                data-testid="in-library"
+                                                className="mr-2 px-1.5 py-0.5 rounded text-[10px] font-semibold bg-sky-500/20 text-sky-300"
+                                                title="A .torrent for this release is already in the download path"
+                                            >
+                                                In library
+                                            </span>
+                                        )}
+                                        {isResultCategoryFallback(result) && (
+                                            <span
+                                                data-testid="category-fallback"
+                                                className="mr-2 px-1.5 py-0.5 rounded text-[10px] font-semibold bg-amber-500/20 text-amber-300"
+                                                title="Found by searching a nearby category, not the one selected"
+                                            >
+                                                Nearby category
+                                            </span>
+                                        )}
                                         {title}
                                     </td>
                                     <td className="px-6 py-4 text-right font-mono text-xs opacity-80 whitespace-nowrap">
@@ -170,8 +212,19 @@ export default function SearchResultsTable({
                                         <span className="text-red-400">{peers}</span>
                                     </td>
                                     {variant === 'full' && (
-                                        <td className="px-6 py-4 text-right font-mono text-xs opacity-60 whitespace-nowrap">
-                                            {formatDate(date)}
+                                        <td className="px-6 py-4 text-right font-mono text-xs whitespace-nowrap">
+                                            {health === null ? (
+                                                <span className="opacity-30">-</span>
+                                            ) : (
+                                                <span className={health >= 70 ? 'text-emerald-400' : health >= 40 ? 'text-amber-400' : 'text-red-400'}>
+                                                    {health}
+                                                </span>
+                                            )}
+                                        </td>
+                                    )}
+                                    {variant === 'full' && (
+                                        <td className="px-6 py-4 text-right font-mono text-xs opacity-60 whitespace-nowrap" title={getResultDateNote(result)}>
+                                            {formatDate(date)}{getResultDateNote(result) && '*'}
                                         </td>
                                     )}
                                     <td className="px-6 py-4 text-center">
@@ -203,7 +256,7 @@ export default function SearchResultsTable({
                                                 {/* Download to Server */}
                                                 {downloadConfigured && link && (
                                                     <button
-                                                        onClick={() => onDownload(link, title)}
+                                                        onClick={() => onDownload(link, title, size, result)}
                                                         disabled={downloadingId === link}
                                                         className="p-2 rounded-md hover:bg-neutral-700 transition-colors disabled:opacity-50"
                                                         title="Save to Server"
@@ -224,7 +277,7 @@ export default function SearchResultsTable({
                                                     <div className="relative group/client">
                                                         {clients.length === 1 ? (
                                                             <button
-                                                                onClick={() => onSendToClient(clients[0].id, magnet || link || '', title)}
+                                                                onClick={() => onSendToClient(clients[0].id, magnet || link || '', title, size)}
                                                                 className="p-2 rounded-md hover:bg-neutral-700 transition-colors"
                                                                 title={`Send to ${clients[0].name}`}
                                                                 style={buttonSecondaryStyle}
@@ -248,7 +301,7 @@ export default function SearchResultsTable({
                                                                     className="absolute inset-0 opacity-0 cursor-pointer"
                                                                     onChange={(e) => {
                                                                         if (e.target.value) {
-                                                                            onSendToClient(e.target.value, magnet || link || '', title);
+                                                                            onSendToClient(e.target.value, magnet || link || '', title, size);
                                                                             e.target.value = '';
                                                                         }
                                                                     }}
@@ -1,6 +1,6 @@
-import { LayoutDashboard, Search, Database, Settings, Activity, Heart } from 'lucide-react';
+import { LayoutDashboard, Search, Database, Settings, Activity, Heart, Trophy } from 'lucide-react';
 
-export type View = 'dashboard' | 'search' | 'indexers' | 'activity' | 'settings';
+export type View = 'dashboard' | 'search' | 'sports' | 'indexers' | 'activity' | 'settings';
 
 interface SidebarProps {
     currentView: View;
@@ -11,6 +11,7 @@ export default function Sidebar({ currentView, setView }: SidebarProps) {
     const navItems = [
         { id: 'dashboard', label: 'Dashboard', icon: <LayoutDashboard size={20} /> },
         { id: 'search', label: 'Search', icon: <Search size={20} /> },
+        { id: 'sports', label: 'Sports', icon: <Trophy size={20} /> },
         { id: 'indexers', label: 'Indexers', icon: <Database size={20} /> },
         { id: 'activity', label: 'Activity', icon: <Activity size={20} /> },
         { id: 'settings', label: 'Settings', icon: <Settings size={20} /> },
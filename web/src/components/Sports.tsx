@@ -0,0 +1,183 @@
+import { useEffect, useState } from 'react';
+import { Trophy, RefreshCw, Calendar } from 'lucide-react';
+import { Card, CardHeader, CardBody, CardTitle, Button, Badge, Spinner } from './ui';
+import toast from 'react-hot-toast';
+import type { TorrentResult } from '../types';
+import SearchResultsTable from './SearchResultsTable';
+import ResultDetailsModal from './ResultDetailsModal';
+import { useDownloadClients } from '../hooks/useDownloadClients';
+import { useCopyToClipboard } from '../hooks/useCopyToClipboard';
+import { useTorrentMeta } from '../hooks/useTorrentMeta';
+
+interface SportsDay {
+    date: string | null;
+    results: TorrentResult[];
+}
+
+function isoDate(d: Date): string {
+    return d.toISOString().slice(0, 10);
+}
+
+function daysAgo(n: number): string {
+    const d = new Date();
+    d.setDate(d.getDate() - n);
+    return isoDate(d);
+}
+
+export default function Sports() {
+    const [query, setQuery] = useState('');
+    const [league, setLeague] = useState('');
+    const [from, setFrom] = useState(daysAgo(7));
+    const [to, setTo] = useState(daysAgo(0));
+    const [days, setDays] = useState<SportsDay[]>([]);
+    const [loading, setLoading] = useState(false);
+    const [inspectedResult, setInspectedResult] = useState<TorrentResult | null>(null);
+
+    const { clients, handleSendToClient, downloadConfigured, downloading, handleServerDownload, downloadedLinks } = useDownloadClients();
+    const { copiedField, copyToClipboard } = useCopyToClipboard();
+    const { torrentMeta, loadingMeta, fetchTorrentMeta } = useTorrentMeta();
+
+    useEffect(() => {
+        loadEvents();
+    }, []);
+
+    const loadEvents = async () => {
+        setLoading(true);
+        try {
+            const params = new URLSearchParams();
+            if (query.trim()) params.set('q', query.trim());
+            if (league.trim()) params.set('league', league.trim());
+            if (from) params.set('from', from);
+            if (to) params.set('to', to);
+            const res = await fetch(`/api/sports?${params}`);
+            if (!res.ok) {
+                throw new Error(await res.text());
+            }
+            const data: SportsDay[] = await res.json();
+            setDays(data);
+        } catch (err) {
+            console.error('Failed to load sports events:', err);
+            toast.error(`Failed to load sports events: ${err instanceof Error ? err.message : err}`);
+        } finally {
+            setLoading(false);
+        }
+    };
+
+    const total = days.reduce((n, d) => n + d.results.length, 0);
+
+    return (
+        <div className="p-6 max-w-6xl mx-auto space-y-6">
+            <Card>
+                <CardHeader className="flex flex-row items-center justify-between">
+                    <CardTitle>Sports Events</CardTitle>
+                    <Badge variant="neutral">{total} releases</Badge>
+                </CardHeader>
+                <CardBody>
+                    <form
+                        className="flex flex-wrap items-end gap-3"
+                        onSubmit={e => {
+                            e.preventDefault();
+                            loadEvents();
+                        }}
+                    >
+                        <label className="flex flex-col text-xs text-neutral-400 gap-1">
+                            Search
+                            <input
+                                className="px-3 py-2 rounded bg-[#262626] border border-neutral-700 text-sm text-white"
+                                value={query}
+                                onChange={e => setQuery(e.target.value)}
+                                placeholder="Team, event..."
+                            />
+                        </label>
+                        <label className="flex flex-col text-xs text-neutral-400 gap-1">
+                            League
+                            <input
+                                className="px-3 py-2 rounded bg-[#262626] border border-neutral-700 text-sm text-white w-32"
+                                value={league}
+                                onChange={e => setLeague(e.target.value)}
+                                placeholder="NBA, F1..."
+                            />
+                        </label>
+                        <label className="flex flex-col text-xs text-neutral-400 gap-1">
+                            From
+                            <input
+                                type="date"
+                                className="px-3 py-2 rounded bg-[#262626] border border-neutral-700 text-sm text-white"
+                                value={from}
+                                onChange={e => setFrom(e.target.value)}
+                            />
+                        </label>
+                        <label className="flex flex-col text-xs text-neutral-400 gap-1">
+                            To
+                            <input
+                                type="date"
+                                className="px-3 py-2 rounded bg-[#262626] border border-neutral-700 text-sm text-white"
+                                value={to}
+                                onChange={e => setTo(e.target.value)}
+                            />
+                        </label>
+                        <Button type="submit" variant="primary" disabled={loading}>
+                            <RefreshCw className={`w-4 h-4 mr-1 ${loading ? 'animate-spin' : ''}`} />
+                            Load
+                        </Button>
+                    </form>
+                </CardBody>
+            </Card>
+
+            {loading ? (
+                <div className="flex items-center justify-center min-h-[200px]">
+                    <Spinner size="lg" />
+                </div>
+            ) : days.length === 0 ? (
+                <div className="text-center py-12 opacity-50">
+                    <Trophy className="w-12 h-12 mx-auto mb-2 opacity-20" />
+                    <p>No events in this range</p>
+                </div>
+            ) : (
+                days.map(day => (
+                    <Card key={day.date ?? 'undated'}>
+                        <CardHeader className="flex flex-row items-center justify-between">
+                            <CardTitle className="flex items-center gap-2">
+                                <Calendar className="w-4 h-4" />
+                                {day.date
+                                    ? new Date(`${day.date}T00:00:00`).toLocaleDateString(undefined, {
+                                        weekday: 'long', year: 'numeric', month: 'long', day: 'numeric',
+                                    })
+                                    : 'Undated'}
+                            </CardTitle>
+                            <Badge variant="neutral" size="sm">{day.results.length}</Badge>
+                        </CardHeader>
+                        <CardBody className="p-0">
+                            <SearchResultsTable
+                                results={day.results}
+                                onInspect={setInspectedResult}
+                                onDownload={handleServerDownload}
+                                downloadConfigured={downloadConfigured}
+                                downloadingId={downloading}
+                                variant="simple"
+                                clients={clients}
+                                onSendToClient={handleSendToClient}
+                                downloadedLinks={downloadedLinks}
+                            />
+                        </CardBody>
+                    </Card>
+                ))
+            )}
+
+            <ResultDetailsModal
+                result={inspectedResult}
+                onClose={() => setInspectedResult(null)}
+                onCopyToClipboard={copyToClipboard}
+                copiedField={copiedField}
+                clients={clients}
+                onSendToClient={handleSendToClient}
+                downloadConfigured={downloadConfigured}
+                onDownload={handleServerDownload}
+                downloadingId={downloading}
+                onFetchMeta={fetchTorrentMeta}
+                loadingMeta={loadingMeta}
+                torrentMeta={torrentMeta}
+            />
+        </div>
+    );
+}
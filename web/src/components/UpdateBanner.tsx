@@ -0,0 +1,43 @@
+import { useEffect, useState } from 'react';
+import { ArrowUpCircle, X } from 'lucide-react';
+
+interface UpdateInfo {
+    latest: string;
+    url: string;
+}
+
+/** Shown when the server's update check (check_updates = true) found a newer release */
+export default function UpdateBanner() {
+    const [update, setUpdate] = useState<UpdateInfo | null>(null);
+    const [dismissed, setDismissed] = useState(false);
+
+    useEffect(() => {
+        fetch('/api/info')
+            .then((res) => (res.ok ? res.json() : null))
+            .then((info) => setUpdate(info?.update ?? null))
+            .catch(() => setUpdate(null));
+    }, []);
+
+    if (!update || dismissed) return null;
+
+    return (
+        <div className="flex items-center justify-between gap-3 px-4 lg:px-8 py-2 text-sm bg-amber-500/10 border-b border-amber-500/30 text-amber-400">
+            <a
+                href={update.url}
+                target="_blank"
+                rel="noreferrer"
+                className="flex items-center gap-2 hover:underline"
+            >
+                <ArrowUpCircle size={16} />
+                Lodestarr v{update.latest} is available
+            </a>
+            <button
+                onClick={() => setDismissed(true)}
+                className="p-1 rounded hover:bg-white/10"
+                title="Dismiss"
+            >
+                <X size={14} />
+            </button>
+        </div>
+    );
+}
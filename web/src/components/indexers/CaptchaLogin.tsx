@@ -0,0 +1,84 @@
+
+import { useState } from 'react';
+import { KeyRound, RefreshCw } from 'lucide-react';
+import toast from 'react-hot-toast';
+import { Button, Spinner } from '../ui';
+
+interface CaptchaLoginProps {
+    indexerId: string;
+}
+
+export default function CaptchaLogin({ indexerId }: CaptchaLoginProps) {
+    const [imageUrl, setImageUrl] = useState<string | null>(null);
+    const [answer, setAnswer] = useState('');
+    const [submitting, setSubmitting] = useState(false);
+
+    // Cache-bust so every click fetches a fresh captcha
+    const loadCaptcha = () => {
+        setAnswer('');
+        setImageUrl(`/api/native/${indexerId}/captcha?t=${Date.now()}`);
+    };
+
+    const submit = async () => {
+        setSubmitting(true);
+        try {
+            const res = await fetch(`/api/native/${indexerId}/captcha`, {
+                method: 'POST',
+                headers: { 'Content-Type': 'application/json' },
+                body: JSON.stringify({ answer }),
+            });
+            if (res.ok) {
+                toast.success('Logged in');
+                setImageUrl(null);
+            } else {
+                toast.error(await res.text());
+                loadCaptcha();
+            }
+        } catch (e) {
+            console.error(e);
+            toast.error('Login failed');
+        } finally {
+            setSubmitting(false);
+        }
+    };
+
+    return (
+        <div className="pt-6 border-t border-neutral-800">
+            <h3 className="text-sm font-semibold text-white mb-3 flex items-center gap-2">
+                <KeyRound className="w-4 h-4 text-primary-500" />
+                Captcha Login
+            </h3>
+            <p className="text-xs text-neutral-500 mb-3">
+                This tracker requires a captcha to log in. Save your credentials first, then solve the captcha.
+            </p>
+
+            {imageUrl ? (
+                <div className="space-y-3">
+                    <div className="flex items-center gap-3">
+                        <img src={imageUrl} alt="Captcha" className="rounded border border-neutral-700 bg-white" />
+                        <button onClick={loadCaptcha} className="text-neutral-400 hover:text-white" title="New captcha">
+                            <RefreshCw size={16} />
+                        </button>
+                    </div>
+                    <div className="flex gap-2">
+                        <input
+                            type="text"
+                            data-testid="captcha-input"
+                            value={answer}
+                            onChange={(e) => setAnswer(e.target.value)}
+                            onKeyDown={(e) => e.key === 'Enter' && answer && submit()}
+                            className="flex-1 px-3 py-2 bg-neutral-900 border border-neutral-700 rounded-lg focus:ring-2 focus:ring-primary-500 outline-none text-white font-mono text-sm"
+                        />
+                        <Button variant="primary" size="sm" onClick={submit} disabled={!answer || submitting}>
+                            {submitting ? <Spinner size="sm" /> : 'Log in'}
+                        </Button>
+                    </div>
+                </div>
+            ) : (
+                <Button variant="secondary" size="sm" onClick={loadCaptcha} className="w-full">
+                    Load Captcha
+                </Button>
+            )}
+        </div>
+    );
+}
@@ -0,0 +1,127 @@
+import { FlaskConical, Play } from 'lucide-react';
+import { Card, CardBody, Button, Spinner } from '../ui';
+import toast from 'react-hot-toast';
+import { useState } from 'react';
+import { formatSize } from '../../utils/formatters';
+
+interface TryResponse {
+    success: boolean;
+    count: number;
+    time_ms: number;
+    error?: string;
+    fields: [string, number][];
+    empty_pages: { url: string; status: number; body: string }[];
+    timings?: { phases: { name: string; ms: number; detail?: string }[] };
+    results: { Title: string; Link?: string; Size?: number; Seeders?: number }[];
+}
+
+export default function DefinitionPlayground() {
+    const [yaml, setYaml] = useState('');
+    const [query, setQuery] = useState('');
+    const [running, setRunning] = useState(false);
+    const [result, setResult] = useState<TryResponse | null>(null);
+
+    const run = async () => {
+        if (!yaml.trim()) {
+            toast.error('Paste a definition first');
+            return;
+        }
+        setRunning(true);
+        try {
+            const res = await fetch('/api/native/try', {
+                method: 'POST',
+                headers: { 'Content-Type': 'application/json' },
+                body: JSON.stringify({ yaml, query: { query } })
+            });
+            if (!res.ok) {
+                throw new Error(await res.text() || 'Trial run failed');
+            }
+            setResult(await res.json());
+        } catch (err) {
+            setResult(null);
+            toast.error(err instanceof Error ? err.message : 'Trial run failed');
+        } finally {
+            setRunning(false);
+        }
+    };
+
+    return (
+        <Card>
+            <CardBody className="space-y-4">
+                <div>
+                    <h3 className="text-lg font-semibold text-white flex items-center gap-2">
+                        <FlaskConical className="w-5 h-5 text-primary-500" />Definition Playground
+                    </h3>
+                    <p className="text-sm text-neutral-400 mt-1">Run a Cardigann YAML definition without installing it</p>
+                </div>
+                <textarea
+                    className="w-full h-72 px-3 py-2 font-mono text-xs bg-neutral-900 border border-neutral-700 rounded-lg text-white focus:ring-2 focus:ring-primary-500 outline-none"
+                    placeholder="id: mytracker&#10;name: My Tracker&#10;..."
+                    spellCheck={false}
+                    value={yaml}
+                    onChange={(e) => setYaml(e.target.value)}
+                />
+                <div className="flex gap-2">
+                    <input
+                        type="text"
+                        placeholder="Query (empty for latest releases)"
+                        className="flex-1 px-3 py-2 bg-neutral-700 border border-neutral-600 rounded-lg text-white focus:ring-2 focus:ring-primary-500 outline-none"
+                        value={query}
+                        onChange={(e) => setQuery(e.target.value)}
+                        onKeyDown={(e) => e.key === 'Enter' && run()}
+                    />
+                    <Button variant="primary" onClick={run} disabled={running}>
+                        {running ? <Spinner size="sm" /> : <Play className="w-4 h-4 mr-2" />}Run
+                    </Button>
+                </div>
+
+                {result && (
+                    <div className="space-y-4 text-sm">
+                        <div className={`p-3 rounded-lg ${result.success ? 'bg-emerald-500/10 text-emerald-400' : 'bg-red-500/10 text-red-400'}`}>
+                            {result.error ?? `${result.count} results in ${result.time_ms}ms`}
+                        </div>
+
+                        {result.count > 0 && (
+                            <div className="flex flex-wrap gap-2">
+                                {result.fields.map(([field, n]) => (
+                                    <span key={field} className={`px-2 py-1 rounded text-xs ${n === result.count ? 'bg-emerald-500/10 text-emerald-400' : n === 0 ? 'bg-neutral-800 text-neutral-500' : 'bg-amber-500/10 text-amber-400'}`}>
+                                        {field} {n}/{result.count}
+                                    </span>
+                                ))}
+                            </div>
+                        )}
+
+                        {result.timings && (
+                            <div className="text-xs text-neutral-400">
+                                {result.timings.phases.map((p, i) => (
+                                    <div key={i}>{p.name}: {p.ms}ms {p.detail && <span className="text-neutral-500">{p.detail}</span>}</div>
+                                ))}
+                            </div>
+                        )}
+
+                        {result.empty_pages.map((page, i) => (
+                            <details key={i} className="bg-neutral-900 rounded-lg p-3">
+                                <summary className="cursor-pointer text-neutral-300">No rows matched: {page.url} (HTTP {page.status})</summary>
+                                <pre className="mt-2 text-xs text-neutral-400 whitespace-pre-wrap break-all max-h-80 overflow-auto">{page.body}</pre>
+                            </details>
+                        ))}
+
+                        {result.results.length > 0 && (
+                            <table className="w-full text-xs">
+                                <tbody>
+                                    {result.results.map((r, i) => (
+                                        <tr key={i} className="border-t border-neutral-800">
+                                            <td className="py-1 pr-2 text-white">{r.Title}</td>
+                                            <td className="py-1 pr-2 text-neutral-400 whitespace-nowrap">{r.Size != null ? formatSize(r.Size) : '-'}</td>
+                                            <td className="py-1 text-neutral-400">{r.Seeders ?? '-'}</td>
+                                        </tr>
+                                    ))}
+                                </tbody>
+                            </table>
+                        )}
+                    </div>
+                )}
+            </CardBody>
+        </Card>
+    );
+}
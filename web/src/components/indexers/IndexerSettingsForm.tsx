@@ -2,6 +2,14 @@
 import { AlertCircle } from 'lucide-react';
 import { type IndexerSetting } from '../../types/api';
 
+const SEARCH_TYPES = [
+    { value: 'search', label: 'General' },
+    { value: 'tvsearch', label: 'TV' },
+    { value: 'movie', label: 'Movies' },
+    { value: 'music', label: 'Music' },
+    { value: 'book', label: 'Books' },
+];
+
 interface IndexerSettingsFormProps {
     definitions: IndexerSetting[];
     settings: Record<string, string>;
@@ -132,6 +140,23 @@ export default function IndexerSettingsForm({ definitions, settings, links, onSe
                         />
                         <p className="text-xs text-neutral-500 mt-1">Max results per search</p>
                     </div>
+
+                    {/* Category Fallback */}
+                    <div>
+                        <label className="block text-sm font-medium text-neutral-300 mb-1">
+                            Category Fallback
+                        </label>
+                        <select
+                            value={settings['_categoryFallback'] || 'parent'}
+                            onChange={(e) => handleChange('_categoryFallback', e.target.value)}
+                            className="w-full px-3 py-2 bg-neutral-900 border border-neutral-700 rounded-lg focus:ring-2 focus:ring-primary-500 outline-none text-white"
+                        >
+                            <option value="parent">Parent category</option>
+                            <option value="all">All subcategories</option>
+                            <option value="strict">None (strict)</option>
+                        </select>
+                        <p className="text-xs text-neutral-500 mt-1">For categories the tracker doesn't map</p>
+                    </div>
                 </div>
 
                 {/* Enable Toggle */}
@@ -151,6 +176,109 @@ export default function IndexerSettingsForm({ definitions, settings, links, onSe
                     </button>
                 </div>
 
+                {/* Search Types */}
+                <div className="mt-4 p-3 bg-neutral-900 rounded-lg">
+                    <span className="text-sm font-medium text-neutral-300">Search Types</span>
+                    <p className="text-xs text-neutral-500 mb-2">Untick a type to stop sending those searches here (e.g. TV searches to a movie-only tracker)</p>
+                    <div className="flex flex-wrap gap-4">
+                        {SEARCH_TYPES.map(({ value, label }) => {
+                            const disabled = (settings['_disabledModes'] || '')
+                                .split(',')
+                                .map((m) => m.trim())
+                                .filter(Boolean);
+                            return (
+                                <label key={value} className="flex items-center gap-2 text-sm text-neutral-300">
+                                    <input
+                                        type="checkbox"
+                                        data-testid={`mode-${value}`}
+                                        checked={!disabled.includes(value)}
+                                        onChange={(e) => {
+                                            const next = e.target.checked
+                                                ? disabled.filter((m) => m !== value)
+                                                : [...disabled, value];
+                                            handleChange('_disabledModes', next.join(','));
+                                        }}
+                                        className="accent-primary-500"
+                                    />
+                                    {label}
+                                </label>
+                            );
+                        })}
+                    </div>
+                </div>
+
+                {/* Dates from details pages */}
+                <div className="mt-4 flex items-center justify-between p-3 bg-neutral-900 rounded-lg">
+                    <div>
+                        <span className="text-sm font-medium text-neutral-300">Dates from Details Page</span>
+                        <p className="text-xs text-neutral-500">Open undated results to find their upload date (extra requests)</p>
+                    </div>
+                    <button
+                        type="button"
+                        data-testid="date-from-details-toggle"
+                        onClick={() => handleChange('_dateFromDetails', settings['_dateFromDetails'] === 'true' ? 'false' : 'true')}
+                        className={`relative w-12 h-6 rounded-full transition-colors ${settings['_dateFromDetails'] === 'true' ? 'bg-emerald-500' : 'bg-neutral-700'
+                            }`}
+                    >
+                        <span className={`absolute top-1 w-4 h-4 rounded-full bg-white transition-transform ${settings['_dateFromDetails'] === 'true' ? 'left-7' : 'left-1'
+                            }`} />
+                    </button>
+                </div>
+
+                {/* BBCode in titles */}
+                <div className="mt-4 flex items-center justify-between p-3 bg-neutral-900 rounded-lg">
+                    <div>
+                        <span className="text-sm font-medium text-neutral-300">Strip BBCode</span>
+                        <p className="text-xs text-neutral-500">Remove tags like [b] or [color=red] from titles and descriptions</p>
+                    </div>
+                    <button
+                        type="button"
+                        data-testid="strip-bbcode-toggle"
+                        onClick={() => handleChange('_stripBbcode', settings['_stripBbcode'] === 'true' ? 'false' : 'true')}
+                        className={`relative w-12 h-6 rounded-full transition-colors ${settings['_stripBbcode'] === 'true' ? 'bg-emerald-500' : 'bg-neutral-700'
+                            }`}
+                    >
+                        <span className={`absolute top-1 w-4 h-4 rounded-full bg-white transition-transform ${settings['_stripBbcode'] === 'true' ? 'left-7' : 'left-1'
+                            }`} />
+                    </button>
+                </div>
+
+                {/* Jackett-style 100000+id categories */}
+                <div className="mt-4 flex items-center justify-between p-3 bg-neutral-900 rounded-lg">
+                    <div>
+                        <span className="text-sm font-medium text-neutral-300">Tracker Categories</span>
+                        <p className="text-xs text-neutral-500">Also report the tracker's own categories as 100000+id, like Jackett</p>
+                    </div>
+                    <button
+                        type="button"
+                        data-testid="tracker-categories-toggle"
+                        onClick={() => handleChange('_trackerCategories', settings['_trackerCategories'] === 'true' ? 'false' : 'true')}
+                        className={`relative w-12 h-6 rounded-full transition-colors ${settings['_trackerCategories'] === 'true' ? 'bg-emerald-500' : 'bg-neutral-700'
+                            }`}
+                    >
+                        <span className={`absolute top-1 w-4 h-4 rounded-full bg-white transition-transform ${settings['_trackerCategories'] === 'true' ? 'left-7' : 'left-1'
+                            }`} />
+                    </button>
+                </div>
+
+                {/* Keyword retry for TV searches */}
+                <div className="mt-4 flex items-center justify-between p-3 bg-neutral-900 rounded-lg">
+                    <div>
+                        <span className="text-sm font-medium text-neutral-300">Retry TV Searches as Keywords</span>
+                        <p className="text-xs text-neutral-500">When a season/episode search finds nothing, search again for "Title S01E02"</p>
+                    </div>
+                    <button
+                        type="button"
+                        data-testid="keyword-retry-toggle"
+                        onClick={() => handleChange('_keywordRetry', settings['_keywordRetry'] === 'true' ? 'false' : 'true')}
+                        className={`relative w-12 h-6 rounded-full transition-colors ${settings['_keywordRetry'] === 'true' ? 'bg-emerald-500' : 'bg-neutral-700'
+                            }`}
+                    >
+                        <span className={`absolute top-1 w-4 h-4 rounded-full bg-white transition-transform ${settings['_keywordRetry'] === 'true' ? 'left-7' : 'left-1'
+                            }`} />
+                    </button>
+                </div>
+
                 {/* Cookie/User-Agent (for private trackers) */}
                 <div className="mt-4 pt-4 border-t border-neutral-800">
                     <h4 className="text-xs font-semibold text-neutral-400 mb-3 uppercase tracking-wider">Authentication</h4>
@@ -189,6 +317,82 @@ export default function IndexerSettingsForm({ definitions, settings, links, onSe
                                 Optional. Use if site requires a specific browser signature.
                             </p>
                         </div>
+
+                        <div>
+                            <label className="block text-sm font-medium text-neutral-300 mb-1">
+                                TOTP Secret
+                            </label>
+                            <input
+                                type="password"
+                                data-testid="totp-secret-input"
+                                placeholder="JBSWY3DPEHPK3PXP"
+                                value={settings['_totpSecret'] || ''}
+                                onChange={(e) => handleChange('_totpSecret', e.target.value)}
+                                className="w-full px-3 py-2 bg-neutral-900 border border-neutral-700 rounded-lg focus:ring-2 focus:ring-primary-500 outline-none text-white font-mono text-sm"
+                            />
+                            <p className="text-xs text-neutral-500 mt-1">
+                                For trackers with two-factor login. The base32 key shown when setting up your authenticator app.
+                            </p>
+                        </div>
+
+                        <div>
+                            <label className="block text-sm font-medium text-neutral-300 mb-1">
+                                Remote Instance
+                            </label>
+                            <input
+                                type="text"
+                                data-testid="remote-input"
+                                placeholder="http://10.8.0.2:3420"
+                                value={settings['_remote'] || ''}
+                                onChange={(e) => handleChange('_remote', e.target.value)}
+                                className="w-full px-3 py-2 bg-neutral-900 border border-neutral-700 rounded-lg focus:ring-2 focus:ring-primary-500 outline-none text-white font-mono text-sm"
+                            />
+                            <input
+                                type="password"
+                                data-testid="remote-apikey-input"
+                                placeholder="Remote API key (optional)"
+                                value={settings['_remoteApiKey'] || ''}
+                                onChange={(e) => handleChange('_remoteApiKey', e.target.value)}
+                                className="w-full mt-2 px-3 py-2 bg-neutral-900 border border-neutral-700 rounded-lg focus:ring-2 focus:ring-primary-500 outline-none text-white font-mono text-sm"
+                            />
+                            <p className="text-xs text-neutral-500 mt-1">
+                                Forward this indexer's searches and downloads to another Lodestarr (e.g. one behind a VPN). It must have this indexer set up.
+                            </p>
+                        </div>
+
+                        <div>
+                            <label className="block text-sm font-medium text-neutral-300 mb-1">
+                                Timezone
+                            </label>
+                            <input
+                                type="text"
+                                data-testid="timezone-input"
+                                placeholder="Europe/Moscow or +03:00"
+                                value={settings['_timezone'] || ''}
+                                onChange={(e) => handleChange('_timezone', e.target.value)}
+                                className="w-full px-3 py-2 bg-neutral-900 border border-neutral-700 rounded-lg focus:ring-2 focus:ring-primary-500 outline-none text-white font-mono text-sm"
+                            />
+                            <p className="text-xs text-neutral-500 mt-1">
+                                Timezone the site shows dates in. Leave empty to use the definition's default (UTC).
+                            </p>
+                        </div>
+
+                        <div>
+                            <label className="block text-sm font-medium text-neutral-300 mb-1">
+                                Extra Headers
+                            </label>
+                            <textarea
+                                data-testid="headers-input"
+                                rows={3}
+                                placeholder={'Referer: https://tracker.example/'}
+                                value={settings['_headers'] || ''}
+                                onChange={(e) => handleChange('_headers', e.target.value)}
+                                className="w-full px-3 py-2 bg-neutral-900 border border-neutral-700 rounded-lg focus:ring-2 focus:ring-primary-500 outline-none text-white font-mono text-sm"
+                            />
+                            <p className="text-xs text-neutral-500 mt-1">
+                                One "Name: value" per line. Sent with searches and downloads.
+                            </p>
+                        </div>
                     </div>
                 </div>
             </div>
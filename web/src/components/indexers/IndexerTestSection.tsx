@@ -4,11 +4,23 @@ import { Button, Spinner } from '../ui';
 
 interface TestResult {
     success: boolean;
+    status?: 'ok' | 'empty' | 'protected' | 'error';
     count: number;
     time_ms: number;
     message: string;
+    error?: 'network' | 'parse' | 'login' | 'rate_limited' | 'blocked' | 'not_found' | 'proxy_down' | 'other';
 }
 
+const ERROR_LABELS: Record<string, string> = {
+    network: 'Site unreachable',
+    parse: 'Unexpected response',
+    login: 'Needs credentials',
+    rate_limited: 'Rate limited',
+    blocked: 'Blocked',
+    not_found: 'Not found',
+    proxy_down: 'Proxy down',
+};
+
 interface IndexerTestSectionProps {
     onTest: () => Promise<void>;
     testing: boolean;
@@ -36,8 +48,15 @@ export default function IndexerTestSection({ onTest, testing, results }: Indexer
             {results && (
                 <div className={`mt-4 p-3 rounded-lg text-sm ${results.success
                     ? 'bg-emerald-500/10 text-emerald-400'
-                    : 'bg-red-500/10 text-red-400'
+                    : results.status === 'protected'
+                        ? 'bg-amber-500/10 text-amber-400'
+                        : 'bg-red-500/10 text-red-400'
                     }`}>
+                    {results.status === 'protected' ? (
+                        <div className="text-xs font-semibold uppercase tracking-wider mb-1">Protected</div>
+                    ) : results.error && ERROR_LABELS[results.error] && (
+                        <div className="text-xs font-semibold uppercase tracking-wider mb-1">{ERROR_LABELS[results.error]}</div>
+                    )}
                     <div className="font-medium">{results.message}</div>
                     {results.time_ms > 0 && (
                         <div className="text-xs mt-1 opacity-75">
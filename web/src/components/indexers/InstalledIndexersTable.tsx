@@ -119,6 +119,11 @@ export default function InstalledIndexersTable({
                                     {!indexer.isNative && (
                                         <Badge variant="warning" size="sm">Proxy</Badge>
                                     )}
+                                    {indexer.probably_broken && (
+                                        <span title={`The last ${indexer.probably_broken.empty_streak} searches found nothing. Download the definition again to update it.`}>
+                                            <Badge variant="danger" size="sm">Probably broken</Badge>
+                                        </span>
+                                    )}
                                 </div>
                                 <div className="text-xs text-neutral-500 mt-1 line-clamp-2">
                                     {indexer.description}
@@ -170,6 +175,11 @@ export default function InstalledIndexersTable({
                                                 {!indexer.isNative && (
                                                     <Badge variant="warning" size="sm">Proxy</Badge>
                                                 )}
+                                                {indexer.probably_broken && (
+                                                    <span title={`The last ${indexer.probably_broken.empty_streak} searches found nothing. Download the definition again to update it.`}>
+                                                        <Badge variant="danger" size="sm">Probably broken</Badge>
+                                                    </span>
+                                                )}
                                             </div>
                                             <div className="text-xs text-neutral-500 line-clamp-1 max-w-md">
                                                 {indexer.description}
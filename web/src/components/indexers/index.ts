@@ -1,4 +1,5 @@
 // Re-export indexer sub-components
 export { default as AddIndexerForm } from './AddIndexerForm';
 export { default as BrowseIndexersGrid } from './BrowseIndexersGrid';
+export { default as DefinitionPlayground } from './DefinitionPlayground';
 export { default as InstalledIndexersTable } from './InstalledIndexersTable';
@@ -1,14 +1,23 @@
 import { useState, useEffect } from 'react';
-import { Trash2, Plus, Server, AlertCircle } from 'lucide-react';
+import { Trash2, Plus, Server, AlertCircle, Search } from 'lucide-react';
 import toast from 'react-hot-toast';
 
 interface DownloadClient {
     id: string;
     name: string;
-    client_type: 'TorrServer';
+    client_type: 'TorrServer' | 'QBittorrent' | 'Transmission';
     url: string;
 }
 
+interface DiscoveredClient {
+    client_type: DownloadClient['client_type'];
+    name: string;
+    url: string;
+    version: string | null;
+    needs_login: boolean;
+    configured: boolean;
+}
+
 export default function ClientsSettings() {
     const [clients, setClients] = useState<DownloadClient[]>([]);
     const [loading, setLoading] = useState(true);
@@ -21,6 +30,10 @@ export default function ClientsSettings() {
     const [newUsername, setNewUsername] = useState('');
     const [newPassword, setNewPassword] = useState('');
 
+    // Clients found on the network
+    const [discovering, setDiscovering] = useState(false);
+    const [discovered, setDiscovered] = useState<DiscoveredClient[] | null>(null);
+
     useEffect(() => {
         fetchClients();
     }, []);
@@ -77,6 +90,47 @@ export default function ClientsSettings() {
         }
     };
 
+    const handleDiscover = async () => {
+        setDiscovering(true);
+        try {
+            const res = await fetch('/api/clients/discover');
+            if (!res.ok) throw new Error(await res.text());
+            setDiscovered(await res.json());
+        } catch (error) {
+            toast.error('Failed to look for clients');
+        } finally {
+            setDiscovering(false);
+        }
+    };
+
+    // Ready clients are added as they are; those behind a login fill the form
+    const handleUseDiscovered = async (found: DiscoveredClient) => {
+        if (found.needs_login) {
+            setNewType(found.client_type);
+            // After the type effect has set its defaults
+            setTimeout(() => {
+                setNewName(found.name);
+                setNewUrl(found.url);
+            });
+            toast('Enter the username and password, then add the client');
+            return;
+        }
+        try {
+            const res = await fetch('/api/settings/clients', {
+                method: 'POST',
+                headers: { 'Content-Type': 'application/json' },
+                body: JSON.stringify({ name: found.name, client_type: found.client_type, url: found.url })
+            });
+            if (!res.ok) throw new Error(await res.text());
+            const client = await res.json();
+            setClients(current => [...current, client]);
+            setDiscovered(current => current?.map(c => c.url === found.url ? { ...c, configured: true } : c) ?? null);
+            toast.success(`Added ${found.name}`);
+        } catch (error) {
+            toast.error(`Failed to add client: ${error instanceof Error ? error.message : error}`);
+        }
+    };
+
     // Update defaults when type changes
     useEffect(() => {
         if (newType === 'TorrServer') {
@@ -85,6 +139,9 @@ export default function ClientsSettings() {
         } else if (newType === 'QBittorrent') {
             setNewName('qBittorrent');
             setNewUrl('http://localhost:8080');
+        } else if (newType === 'Transmission') {
+            setNewName('Transmission');
+            setNewUrl('http://localhost:9091');
         }
     }, [newType]);
 
@@ -111,7 +168,7 @@ export default function ClientsSettings() {
         <div className="space-y-6">
             <div>
                 <h2 className="text-xl font-semibold text-white">Download Clients</h2>
-                <p className="text-neutral-400 text-sm mt-1">Configure external download clients like TorrServer and qBittorrent.</p>
+                <p className="text-neutral-400 text-sm mt-1">Configure external download clients like TorrServer, qBittorrent and Transmission.</p>
             </div>
 
             {/* List Clients */}
@@ -152,6 +209,52 @@ export default function ClientsSettings() {
                 )}
             </div>
 
+            {/* Discovery */}
+            <div className="p-5 rounded-xl border border-neutral-800 bg-neutral-900/30">
+                <div className="flex items-center justify-between">
+                    <div>
+                        <h3 className="font-medium text-white">Find Clients</h3>
+                        <p className="text-xs text-neutral-500 mt-1">Looks for qBittorrent, Transmission and TorrServer on their default ports on this machine and known hosts.</p>
+                    </div>
+                    <button
+                        type="button"
+                        onClick={handleDiscover}
+                        disabled={discovering}
+                        className="px-4 py-2 bg-neutral-800 text-white rounded-lg text-sm font-medium hover:bg-neutral-700 transition-colors disabled:opacity-50 flex items-center gap-2"
+                    >
+                        <Search size={16} />
+                        {discovering ? 'Searching...' : 'Discover'}
+                    </button>
+                </div>
+                {discovered && discovered.length === 0 && (
+                    <p className="text-sm text-neutral-500 mt-4">No download clients found.</p>
+                )}
+                {discovered && discovered.length > 0 && (
+                    <div className="mt-4 space-y-2">
+                        {discovered.map(found => (
+                            <div key={`${found.client_type}-${found.url}`} className="flex items-center justify-between p-3 rounded-lg bg-neutral-950 border border-neutral-800">
+                                <div className="text-sm">
+                                    <span className="text-white font-medium">{found.name}</span>
+                                    <span className="text-neutral-500 ml-2">{found.url}</span>
+                                    {found.version && <span className="text-neutral-500 ml-2">{found.version}</span>}
+                                </div>
+                                {found.configured ? (
+                                    <span className="text-xs text-emerald-400">Configured</span>
+                                ) : (
+                                    <button
+                                        type="button"
+                                        onClick={() => handleUseDiscovered(found)}
+                                        className="px-3 py-1 bg-accent text-white rounded-lg text-xs font-medium hover:brightness-110 transition-all"
+                                    >
+                                        {found.needs_login ? 'Set up login' : 'Add'}
+                                    </button>
+                                )}
+                            </div>
+                        ))}
+                    </div>
+                )}
+            </div>
+
             {/* Add Client Form */}
             <div className="p-5 rounded-xl border border-neutral-800 bg-neutral-900/30">
                 <h3 className="font-medium text-white mb-4">Add New Client</h3>
@@ -177,6 +280,7 @@ export default function ClientsSettings() {
                             >
                                 <option value="TorrServer">TorrServer</option>
                                 <option value="QBittorrent">qBittorrent</option>
+                                <option value="Transmission">Transmission</option>
                             </select>
                         </div>
                         <div className="md:col-span-2">
@@ -191,8 +295,8 @@ export default function ClientsSettings() {
                             />
                         </div>
 
-                        {/* Auth Fields for qBittorrent */}
-                        {newType === 'QBittorrent' && (
+                        {/* Auth Fields for qBittorrent and Transmission */}
+                        {(newType === 'QBittorrent' || newType === 'Transmission') && (
                             <>
                                 <div>
                                     <label className="block text-xs font-medium text-neutral-400 mb-1">Username</label>
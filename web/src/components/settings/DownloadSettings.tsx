@@ -1,16 +1,34 @@
 import { useState, useEffect } from 'react';
 import { HardDrive, Save } from 'lucide-react';
+import { formatSize } from '../../utils/formatters';
+
+interface DiskUsage {
+    name: string;
+    path: string;
+    free: number | null;
+    total: number | null;
+    writable: boolean;
+}
 
 export default function DownloadSettings() {
     const [downloadPath, setDownloadPath] = useState('');
     const [saving, setSaving] = useState(false);
     const [error, setError] = useState('');
     const [success, setSuccess] = useState('');
+    const [disk, setDisk] = useState<DiskUsage | null>(null);
 
     useEffect(() => {
         fetchDownloadConfig();
+        fetchDiskUsage();
     }, []);
 
+    const fetchDiskUsage = () => {
+        fetch('/api/system/disk')
+            .then(res => res.json())
+            .then((disks: DiskUsage[]) => setDisk(disks.find(d => d.name === 'download') ?? null))
+            .catch(() => { });
+    };
+
     const fetchDownloadConfig = () => {
         fetch('/api/settings/download')
             .then(res => res.json())
@@ -31,13 +49,14 @@ export default function DownloadSettings() {
             });
 
             if (res.ok) {
-                setSuccess('Download settings saved');
+                setSuccess(await res.text());
                 setTimeout(() => setSuccess(''), 3000);
+                fetchDiskUsage();
             } else {
-                throw new Error('Failed to save');
+                throw new Error(await res.text());
             }
         } catch (err) {
-            setError('Failed to save settings');
+            setError(err instanceof Error && err.message ? err.message : 'Failed to save settings');
         } finally {
             setSaving(false);
         }
@@ -87,6 +106,14 @@ export default function DownloadSettings() {
                                 onChange={e => setDownloadPath(e.target.value)}
                                 placeholder="/path/to/downloads"
                             />
+                            {disk && (
+                                <p data-testid="download-disk" className={`text-xs ${disk.writable ? 'text-neutral-400' : 'text-red-400'}`}>
+                                    {disk.writable ? '' : 'Not writable. '}
+                                    {disk.free !== null && disk.total !== null
+                                        ? `${formatSize(disk.free)} free of ${formatSize(disk.total)}`
+                                        : 'Free space unknown'}
+                                </p>
+                            )}
                             <div className="flex justify-end">
                                 <button
                                     onClick={saveSettings}
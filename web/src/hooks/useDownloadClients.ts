@@ -1,19 +1,33 @@
 import { useState, useEffect, useCallback } from 'react';
 import toast from 'react-hot-toast';
+import { formatSize } from '../utils/formatters';
+import type { TorrentResult } from '../types';
+import {
+    getResultTitle, getResultGuid, getResultLink, getResultMagnet, getResultDetails, getResultDate,
+    getResultSize, getResultSeeders, getResultPeers, getResultInfoHash, getResultIndexer, getResultIndexerId,
+    getResultCategories
+} from '../types';
 
 interface DownloadClient {
     id: string;
     name: string;
 }
 
+interface ArrApp {
+    name: string;
+    kind: string;
+}
+
 interface UseDownloadClientsReturn {
     clients: DownloadClient[];
-    handleSendToClient: (clientId: string, magnet: string, title: string) => Promise<void>;
+    handleSendToClient: (clientId: string, magnet: string, title: string, size?: number) => Promise<void>;
     downloadConfigured: boolean;
     downloading: string | null;
-    handleServerDownload: (link: string, title: string) => Promise<void>;
+    handleServerDownload: (link: string, title: string, size?: number, result?: TorrentResult) => Promise<void>;
     downloadedLinks: Set<string>;
     refreshDownloadedLinks: () => Promise<void>;
+    arrApps: ArrApp[];
+    handlePush: (app: string, result: TorrentResult) => Promise<void>;
 }
 
 /**
@@ -22,9 +36,11 @@ interface UseDownloadClientsReturn {
  */
 export function useDownloadClients(): UseDownloadClientsReturn {
     const [clients, setClients] = useState<DownloadClient[]>([]);
+    const [arrApps, setArrApps] = useState<ArrApp[]>([]);
     const [downloadConfigured, setDownloadConfigured] = useState(false);
     const [downloading, setDownloading] = useState<string | null>(null);
     const [downloadedLinks, setDownloadedLinks] = useState<Set<string>>(new Set());
+    const [freeSpace, setFreeSpace] = useState<number | null>(null);
 
     // Fetch downloaded links
     const refreshDownloadedLinks = useCallback(async () => {
@@ -47,19 +63,40 @@ export function useDownloadClients(): UseDownloadClientsReturn {
             .then(data => setDownloadConfigured(!!data.path))
             .catch(() => { });
 
+        // Free space of the download path, to warn before oversized grabs
+        fetch('/api/system/disk')
+            .then(res => res.json())
+            .then((disks: { name: string; free: number | null }[]) =>
+                setFreeSpace(disks.find(d => d.name === 'download')?.free ?? null))
+            .catch(() => { });
+
         // Load download clients
         fetch('/api/settings/clients')
             .then(res => res.json())
             .then(data => setClients(data))
             .catch(err => console.error('Failed to load clients', err));
 
+        // Sonarr/Radarr instances for release push
+        fetch('/api/push')
+            .then(res => res.json())
+            .then(data => setArrApps(data))
+            .catch(() => { });
+
         // Load downloaded links
         refreshDownloadedLinks();
     }, [refreshDownloadedLinks]);
 
+    // Ask before grabbing a release larger than the free space
+    const hasRoomFor = useCallback((size?: number) => {
+        if (!size || freeSpace === null || size <= freeSpace) return true;
+        return window.confirm(
+            `This release is ${formatSize(size)} but only ${formatSize(freeSpace)} is free on the server's download disk. Continue?`
+        );
+    }, [freeSpace]);
+
     // Send torrent to a download client
-    const handleSendToClient = useCallback(async (clientId: string, magnet: string, title: string) => {
-        if (!magnet) return;
+    const handleSendToClient = useCallback(async (clientId: string, magnet: string, title: string, size?: number) => {
+        if (!magnet || !hasRoomFor(size)) return;
         const toastId = toast.loading(`Sending "${title}"...`);
         try {
             const res = await fetch(`/api/clients/${clientId}/send`, {
@@ -78,11 +115,43 @@ export function useDownloadClients(): UseDownloadClientsReturn {
         } catch {
             toast.error('Failed to send torrent', { id: toastId });
         }
+    }, [refreshDownloadedLinks, hasRoomFor]);
+
+    // Push a result to Sonarr/Radarr, which grabs it through its own client
+    const handlePush = useCallback(async (app: string, result: TorrentResult) => {
+        const toastId = toast.loading(`Pushing to ${app}...`);
+        try {
+            const res = await fetch(`/api/push/${encodeURIComponent(app)}`, {
+                method: 'POST',
+                headers: { 'Content-Type': 'application/json' },
+                // The server wants the indexer id, which routes the download through its proxy
+                body: JSON.stringify({
+                    Title: getResultTitle(result),
+                    Guid: getResultGuid(result) || getResultLink(result) || getResultMagnet(result) || '',
+                    Link: getResultLink(result),
+                    Magnet: getResultMagnet(result),
+                    Comments: getResultDetails(result),
+                    PublishDate: getResultDate(result) || null,
+                    Size: getResultSize(result) || null,
+                    Seeders: getResultSeeders(result),
+                    Peers: getResultPeers(result),
+                    InfoHash: getResultInfoHash(result),
+                    Indexer: getResultIndexerId(result) || getResultIndexer(result),
+                })
+            });
+            if (!res.ok) {
+                throw new Error(await res.text() || 'Push failed');
+            }
+            toast.success(`Pushed to ${app}`, { id: toastId });
+            refreshDownloadedLinks();
+        } catch (err) {
+            toast.error(err instanceof Error ? err.message : 'Push failed', { id: toastId });
+        }
     }, [refreshDownloadedLinks]);
 
     // Download torrent to server
-    const handleServerDownload = useCallback(async (link: string, title: string) => {
-        if (!link) return;
+    const handleServerDownload = useCallback(async (link: string, title: string, size?: number, result?: TorrentResult) => {
+        if (!link || !hasRoomFor(size)) return;
         setDownloading(link);
         try {
             const res = await fetch('/api/download', {
@@ -90,7 +159,10 @@ export function useDownloadClients(): UseDownloadClientsReturn {
                 headers: { 'Content-Type': 'application/json' },
                 body: JSON.stringify({
                     url: link,
-                    title: title
+                    title: title,
+                    // Filled into the server's download_template
+                    indexer: result ? getResultIndexerId(result) || undefined : undefined,
+                    category: result ? getResultCategories(result)[0] : undefined
                 })
             });
 
@@ -108,7 +180,7 @@ export function useDownloadClients(): UseDownloadClientsReturn {
         } finally {
             setDownloading(null);
         }
-    }, [refreshDownloadedLinks]);
+    }, [refreshDownloadedLinks, hasRoomFor]);
 
     return {
         clients,
@@ -117,6 +189,8 @@ export function useDownloadClients(): UseDownloadClientsReturn {
         downloading,
         handleServerDownload,
         downloadedLinks,
-        refreshDownloadedLinks
+        refreshDownloadedLinks,
+        arrApps,
+        handlePush
     };
 }
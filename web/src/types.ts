@@ -18,6 +18,9 @@ export interface TorrentResult {
     PublishDate: string | null;
     publish_date?: string | null;
 
+    PublishDateSource?: string;
+    date_source?: string;
+
     Category: number[];
     categories?: number[];
 
@@ -39,8 +42,29 @@ export interface TorrentResult {
     IndexerId?: string | null;
     indexer_id?: string | null;
 
+    /** Other indexers that had the same torrent */
+    OtherIndexers?: string[];
+    other_indexers?: string[];
+
     InfoHash?: string;
     info_hash?: string;
+
+    Poster?: string | null;
+    poster?: string | null;
+
+    Sports?: { Date?: string | null; League?: string | null } | null;
+
+    Health?: number | null;
+    health?: number | null;
+
+    InLibrary?: boolean;
+    in_library?: boolean;
+
+    CategoryFallback?: boolean;
+    category_fallback?: boolean;
+
+    FetchedAt?: string;
+    Cached?: boolean;
 }
 
 // Helper to normalize result access
@@ -51,12 +75,32 @@ export function getResultSize(r: TorrentResult): number { return r.Size || r.siz
 export function getResultSeeders(r: TorrentResult): number { return r.Seeders ?? r.seeders ?? 0; }
 export function getResultPeers(r: TorrentResult): number { return r.Peers ?? r.leechers ?? 0; }
 export function getResultIndexer(r: TorrentResult): string { return r.Indexer || r.indexer || 'Unknown'; }
+export function getResultOtherIndexers(r: TorrentResult): string[] { return r.OtherIndexers ?? r.other_indexers ?? []; }
+export function getResultHealth(r: TorrentResult): number | null { return r.Health ?? r.health ?? null; }
+export function isResultInLibrary(r: TorrentResult): boolean { return r.InLibrary ?? r.in_library ?? false; }
+export function isResultCategoryFallback(r: TorrentResult): boolean { return r.CategoryFallback ?? r.category_fallback ?? false; }
 export function getResultDate(r: TorrentResult): string { return r.PublishDate || r.publish_date || ''; }
+/** Tooltip for dates the indexer didn't list itself */
+export function getResultDateNote(r: TorrentResult): string | undefined {
+    switch (r.PublishDateSource || r.date_source) {
+        case 'details': return 'Date read from the details page';
+        case 'first_seen': return 'No date listed; first seen by Lodestarr';
+        default: return undefined;
+    }
+}
 export function getResultCategories(r: TorrentResult): number[] { return r.Category || r.categories || []; }
 export function getResultGuid(r: TorrentResult): string { return r.Guid || r.guid || ''; }
 export function getResultDetails(r: TorrentResult): string | null { return r.Comments || r.comments || null; }
 export function getResultInfoHash(r: TorrentResult): string | undefined { return r.InfoHash || r.info_hash; }
 export function getResultIndexerId(r: TorrentResult): string | null { return r.IndexerId || r.indexer_id || null; }
+/** Poster URL routed through the server, which has the indexer's cookies and proxy */
+export function getResultPosterUrl(r: TorrentResult): string | null {
+    const poster = r.Poster || r.poster;
+    if (!poster) return null;
+    const indexerId = getResultIndexerId(r);
+    return `/api/image-proxy?u=${encodeURIComponent(poster)}` +
+        (indexerId ? `&indexer=${encodeURIComponent(indexerId)}` : '');
+}
 
 
 export interface IndexerDefinition {
@@ -71,15 +115,17 @@ export interface IndexerDefinition {
     enabled?: boolean; // UI-only property for toggle state
 }
 
-export type SortField = 'Indexer' | 'Title' | 'Size' | 'Seeders' | 'Date';
+export type SortField = 'Indexer' | 'Title' | 'Size' | 'Seeders' | 'Health' | 'Date';
 
 export interface TorrentMetadata {
     name: string;
     info_hash: string;
     total_size: number;
     piece_length: number;
+    piece_count: number;
     files: { path: string; size: number }[];
     trackers: string[];
+    private: boolean;
     created_by?: string;
     creation_date?: string;
     comment?: string;
@@ -12,12 +12,17 @@ export interface NativeSearchResult {
     leechers?: number;
     grabs?: number;
     publish_date?: string;
+    date_source?: string;
     categories?: number[];
     indexer?: string;
     indexer_id?: string;
     comments?: string;
     info_hash?: string;
     details?: string;
+    poster?: string;
+    health?: number;
+    fetched_at?: string;
+    cached?: boolean;
 }
 
 /** Error object for catch blocks */
@@ -1,6 +1,13 @@
 // Type definitions for indexer components
 // Shared across NativeIndexers and its sub-components
 
+/** Why a native indexer looks broken (its recent searches suddenly find nothing) */
+export interface Breakage {
+    empty_streak: number;
+    baseline_searches: number;
+    baseline_empty_rate: number;
+}
+
 export interface GithubIndexer {
     name: string;
     installed: boolean;
@@ -16,6 +23,7 @@ export interface LocalIndexer {
     legacylinks: string[];
     isNative: true;
     enabled: boolean;
+    probably_broken?: Breakage;
 }
 
 export interface ProxiedIndexer {
@@ -38,4 +46,5 @@ export interface UnifiedIndexer {
     legacylinks?: string[];
     url?: string;
     enabled: boolean;
+    probably_broken?: Breakage;
 }
@@ -1,16 +1,51 @@
+/**
+ * Server `[display]` settings, loaded from /api/info
+ */
+export interface DisplaySettings {
+    size_units: 'binary' | 'decimal';
+    locale?: string | null;
+}
+
+let display: DisplaySettings = { size_units: 'binary' };
+
+export function configureDisplay(settings: DisplaySettings) {
+    display = settings;
+}
+
+/** The configured locale as a BCP 47 tag (de_DE.UTF-8 -> de-DE), else the browser's */
+function displayLocale(): string | undefined {
+    const tag = display.locale?.split(/[.@]/)[0].replace('_', '-');
+    if (!tag || tag === 'C' || tag === 'POSIX') return undefined;
+    return Intl.NumberFormat.supportedLocalesOf(tag).length ? tag : undefined;
+}
+
 /**
  * Format bytes into human-readable size string
  */
 export function formatSize(bytes: number): string {
     if (!bytes) return '-';
-    const units = ['B', 'KB', 'MB', 'GB', 'TB'];
+    const [base, units] = display.size_units === 'decimal'
+        ? [1000, ['B', 'kB', 'MB', 'GB', 'TB']]
+        : [1024, ['B', 'KiB', 'MiB', 'GiB', 'TiB']];
+    if (bytes < base) return `${bytes} B`;
     let i = 0;
     let size = bytes;
-    while (size >= 1024 && i < units.length - 1) {
-        size /= 1024;
+    while (size >= base && i < units.length - 1) {
+        size /= base;
         i++;
     }
-    return `${size.toFixed(1)} ${units[i]}`;
+    const number = size.toLocaleString(displayLocale(), {
+        minimumFractionDigits: 1,
+        maximumFractionDigits: 1,
+    });
+    return `${number} ${units[i]}`;
+}
+
+/**
+ * Format a count with the locale's thousands separator
+ */
+export function formatCount(n: number): string {
+    return n.toLocaleString(displayLocale());
 }
 
 /**
@@ -24,3 +59,15 @@ export function formatDate(dateStr: string): string {
         return dateStr;
     }
 }
+
+/**
+ * Format how long ago a date was, e.g. "40 minutes ago"
+ */
+export function formatAge(dateStr: string): string {
+    const minutes = Math.round((Date.now() - new Date(dateStr).getTime()) / 60000);
+    if (isNaN(minutes)) return '';
+    if (minutes < 1) return 'just now';
+    if (minutes < 60) return `${minutes} minute${minutes === 1 ? '' : 's'} ago`;
+    const hours = Math.round(minutes / 60);
+    return `${hours} hour${hours === 1 ? '' : 's'} ago`;
+}